@@ -50,14 +50,13 @@ fn init(name: &str) -> Group {
 
 /// █▓▒░ Handle polling of all devices in `Group`
 fn poll(poller: &mut Group) -> Result<(), ErrorType> {
-    match poller.poll() {
-        Ok(_) => match poller.save() {
+    if poller.poll().executed() {
+        match poller.save() {
             Ok(_) => println!("\n"),
             Err(t) => {
                 return Err(t);
             }
-        },
-        _ => (),
+        }
     };
     Ok(())
 }
@@ -0,0 +1,54 @@
+/*
+ * C API of sensd. See `sensd-ffi/src/lib.rs` for documentation of each function.
+ *
+ * Link against `libsensd_ffi` (`cargo build -p sensd-ffi --release`).
+ */
+
+#ifndef SENSD_H
+#define SENSD_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define SENSD_OK 0
+#define SENSD_NOT_DUE 1
+#define SENSD_ERR_ARGUMENT -1
+#define SENSD_ERR_NOT_FOUND -2
+#define SENSD_ERR_EXISTS -3
+#define SENSD_ERR_NO_VALUE -4
+#define SENSD_ERR_IO -5
+
+typedef struct SensdGroup sensd_group_t;
+
+/* Store read value in `value` and return 0, or return non-zero if read failed */
+typedef int (*sensd_read_cb)(void *ctx, double *value);
+
+/* Return 0 on success, or non-zero if write failed */
+typedef int (*sensd_write_cb)(void *ctx, double value);
+
+/* `root` may be NULL to use the default data root. Returns NULL if `name` is invalid. */
+sensd_group_t *sensd_group_new(const char *name, const char *root);
+void sensd_group_free(sensd_group_t *group);
+
+int sensd_group_set_interval(sensd_group_t *group, uint64_t millis);
+
+/* `ctx` is passed to callbacks and must remain valid for as long as the group exists */
+int sensd_group_add_input(sensd_group_t *group, const char *name, uint32_t id, sensd_read_cb read, void *ctx);
+int sensd_group_add_output(sensd_group_t *group, const char *name, uint32_t id, sensd_write_cb write, void *ctx);
+
+/* `failures` may be NULL */
+int sensd_group_poll(sensd_group_t *group, int *failures);
+int sensd_group_pause(sensd_group_t *group, int safe);
+int sensd_group_resume(sensd_group_t *group);
+
+int sensd_group_read(const sensd_group_t *group, uint32_t id, double *value);
+int sensd_group_save(const sensd_group_t *group);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SENSD_H */
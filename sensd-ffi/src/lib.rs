@@ -0,0 +1,399 @@
+//! C-compatible API for embedding sensd in other runtimes
+//!
+//! Exposes group creation, device addition with callback-based commands, polling, and access to
+//! latest values, so that existing C/C++ controller firmware (or any language with a C FFI) can
+//! embed the control core. The matching header is `include/sensd.h`.
+//!
+//! All functions return a status code: [`SENSD_OK`] on success, or a negative `SENSD_ERR_*` code.
+//! Values are exchanged as `double`. Binary values are `1.0` or `0.0`.
+//!
+//! # Example
+//!
+//! ```c
+//! #include "sensd.h"
+//!
+//! static int read_temperature(void *ctx, double *value) {
+//!     *value = adc_read((adc_t *) ctx);
+//!     return 0;
+//! }
+//!
+//! sensd_group_t *group = sensd_group_new("greenhouse", "/var/sensd");
+//! sensd_group_set_interval(group, 1000);
+//! sensd_group_add_input(group, "temperature", 0, read_temperature, &adc);
+//!
+//! for (;;) {
+//!     sensd_group_poll(group, NULL);
+//!     sleep(1);
+//! }
+//! sensd_group_free(group);
+//! ```
+
+use chrono::Duration;
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::sync::Arc;
+
+use sensd::action::IOCommand;
+use sensd::console;
+use sensd::errors::ReadError;
+use sensd::io::{Device, DeviceGetters, IdType, Input, Output, RawValue};
+use sensd::storage::{Group, Persistent};
+
+pub const SENSD_OK: c_int = 0;
+/// Poll was not executed because it was not yet due, or group is paused
+pub const SENSD_NOT_DUE: c_int = 1;
+/// Required pointer was null, or string was not valid UTF-8
+pub const SENSD_ERR_ARGUMENT: c_int = -1;
+/// No device with given id
+pub const SENSD_ERR_NOT_FOUND: c_int = -2;
+/// Device with given id already exists
+pub const SENSD_ERR_EXISTS: c_int = -3;
+/// Device has no value yet
+pub const SENSD_ERR_NO_VALUE: c_int = -4;
+/// Group could not be saved
+pub const SENSD_ERR_IO: c_int = -5;
+
+/// Callback which reads an input
+///
+/// Stores the read value in `value` and returns `0`, or returns non-zero if read failed.
+pub type ReadCallback = extern "C" fn(ctx: *mut c_void, value: *mut f64) -> c_int;
+
+/// Callback which writes an output. Returns `0` on success, or non-zero if write failed.
+pub type WriteCallback = extern "C" fn(ctx: *mut c_void, value: f64) -> c_int;
+
+/// Opaque handle of a [`Group`]
+pub struct SensdGroup {
+    group: Group,
+}
+
+/// User pointer passed back to callbacks
+///
+/// The embedding application is responsible for the thread-safety of whatever it points to.
+#[derive(Clone, Copy)]
+struct Context(*mut c_void);
+
+unsafe impl Send for Context {}
+unsafe impl Sync for Context {}
+
+impl Context {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+/// Create a group
+///
+/// # Parameters
+///
+/// - `name`: Name of group
+/// - `root`: Data root. May be null to use the default root.
+///
+/// # Returns
+///
+/// Handle that must be released by [`sensd_group_free()`], or null if `name` is invalid
+///
+/// # Safety
+///
+/// `name` and `root` must be null or point to nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_new(name: *const c_char, root: *const c_char) -> *mut SensdGroup {
+    let name = match to_str(name) {
+        Some(name) => name,
+        None => return std::ptr::null_mut(),
+    };
+    let group = match to_str(root) {
+        Some(root) => Group::with_root(name, root),
+        None => Group::new(name),
+    };
+    Box::into_raw(Box::new(SensdGroup { group }))
+}
+
+/// Release a group created by [`sensd_group_new()`]
+///
+/// # Safety
+///
+/// `group` must be null or a handle returned by [`sensd_group_new()`] which has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_free(group: *mut SensdGroup) {
+    if !group.is_null() {
+        drop(Box::from_raw(group));
+    }
+}
+
+/// Set polling interval in milliseconds
+///
+/// # Safety
+///
+/// `group` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_set_interval(group: *mut SensdGroup, millis: u64) -> c_int {
+    match group.as_mut() {
+        Some(handle) if millis > 0 => {
+            handle.group.set_interval(Duration::milliseconds(millis as i64));
+            SENSD_OK
+        }
+        _ => SENSD_ERR_ARGUMENT,
+    }
+}
+
+/// Add an input which is read by calling `read` with `ctx`
+///
+/// Values are stored as floats.
+///
+/// # Safety
+///
+/// `group` must be null or a valid handle, and `name` must be null or point to a nul-terminated
+/// string. `ctx` must remain valid for as long as the group exists.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_add_input(
+    group: *mut SensdGroup,
+    name: *const c_char,
+    id: IdType,
+    read: ReadCallback,
+    ctx: *mut c_void,
+) -> c_int {
+    let (handle, name) = match (group.as_mut(), to_str(name)) {
+        (Some(handle), Some(name)) => (handle, name),
+        _ => return SENSD_ERR_ARGUMENT,
+    };
+    if handle.group.inputs.get(&id).is_some() {
+        return SENSD_ERR_EXISTS;
+    }
+
+    let ctx = Context(ctx);
+    let command = IOCommand::TryInputFn(Arc::new(move || {
+        let mut value = 0.0;
+        match read(ctx.get(), &mut value) {
+            0 => Ok(RawValue::Float(value as f32)),
+            code => Err(ReadError::Bus { source: std::io::Error::other(format!("callback returned {}", code)) }),
+        }
+    }));
+    let input = Input::new(name, id, None)
+        .set_command(command)
+        .init_log();
+    handle.group.push_input(input);
+    SENSD_OK
+}
+
+/// Add an output which is written by calling `write` with `ctx`
+///
+/// # Safety
+///
+/// `group` must be null or a valid handle, and `name` must be null or point to a nul-terminated
+/// string. `ctx` must remain valid for as long as the group exists.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_add_output(
+    group: *mut SensdGroup,
+    name: *const c_char,
+    id: IdType,
+    write: WriteCallback,
+    ctx: *mut c_void,
+) -> c_int {
+    let (handle, name) = match (group.as_mut(), to_str(name)) {
+        (Some(handle), Some(name)) => (handle, name),
+        _ => return SENSD_ERR_ARGUMENT,
+    };
+    if handle.group.outputs.get(&id).is_some() {
+        return SENSD_ERR_EXISTS;
+    }
+
+    let ctx = Context(ctx);
+    let command = IOCommand::OutputFn(Arc::new(move |value| {
+        match write(ctx.get(), to_double(value)) {
+            0 => Ok(()),
+            _ => Err(()),
+        }
+    }));
+    let output = Output::new(name, id, None)
+        .set_command(command)
+        .init_log();
+    handle.group.push_output(output);
+    SENSD_OK
+}
+
+/// Poll all inputs
+///
+/// # Parameters
+///
+/// - `group`: Group to poll
+/// - `failures`: Set to the number of failed reads when poll is executed. May be null.
+///
+/// # Returns
+///
+/// - [`SENSD_OK`] if poll was executed
+/// - [`SENSD_NOT_DUE`] if poll was not yet due, or group is paused
+///
+/// # Safety
+///
+/// `group` must be null or a valid handle, and `failures` must be null or point to an `int`.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_poll(group: *mut SensdGroup, failures: *mut c_int) -> c_int {
+    let handle = match group.as_mut() {
+        Some(handle) => handle,
+        None => return SENSD_ERR_ARGUMENT,
+    };
+    let report = handle.group.poll();
+    if !report.executed() {
+        return SENSD_NOT_DUE;
+    }
+    for error in report.errors() {
+        console::error(error);
+    }
+    if let Some(failures) = failures.as_mut() {
+        *failures = report.errors().count() as c_int;
+    }
+    SENSD_OK
+}
+
+/// Suspend polling. If `safe` is non-zero, outputs are driven to their safe state.
+///
+/// # Safety
+///
+/// `group` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_pause(group: *mut SensdGroup, safe: c_int) -> c_int {
+    match group.as_mut() {
+        Some(handle) => {
+            handle.group.pause(safe != 0);
+            SENSD_OK
+        }
+        None => SENSD_ERR_ARGUMENT,
+    }
+}
+
+/// Resume polling
+///
+/// # Safety
+///
+/// `group` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_resume(group: *mut SensdGroup) -> c_int {
+    match group.as_mut() {
+        Some(handle) => {
+            handle.group.resume();
+            SENSD_OK
+        }
+        None => SENSD_ERR_ARGUMENT,
+    }
+}
+
+/// Latest value of input or output with `id`
+///
+/// Inputs are searched first.
+///
+/// # Safety
+///
+/// `group` must be null or a valid handle, and `value` must be null or point to a `double`.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_read(group: *const SensdGroup, id: IdType, value: *mut f64) -> c_int {
+    let (handle, value) = match (group.as_ref(), value.as_mut()) {
+        (Some(handle), Some(value)) => (handle, value),
+        _ => return SENSD_ERR_ARGUMENT,
+    };
+    let state = match (handle.group.inputs.get(&id), handle.group.outputs.get(&id)) {
+        (Some(input), _) => *input.lock().unwrap().state(),
+        (None, Some(output)) => *output.lock().unwrap().state(),
+        (None, None) => return SENSD_ERR_NOT_FOUND,
+    };
+    match state {
+        Some(state) => {
+            *value = to_double(state);
+            SENSD_OK
+        }
+        None => SENSD_ERR_NO_VALUE,
+    }
+}
+
+/// Save group and device logs to data root
+///
+/// # Safety
+///
+/// `group` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn sensd_group_save(group: *const SensdGroup) -> c_int {
+    match group.as_ref() {
+        Some(handle) => match handle.group.save() {
+            Ok(_) => SENSD_OK,
+            Err(_) => SENSD_ERR_IO,
+        },
+        None => SENSD_ERR_ARGUMENT,
+    }
+}
+
+/// Convert a nul-terminated string. `None` if null or not valid UTF-8.
+unsafe fn to_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    match ptr.is_null() {
+        true => None,
+        false => CStr::from_ptr(ptr).to_str().ok(),
+    }
+}
+
+fn to_double(value: RawValue) -> f64 {
+    match value {
+        RawValue::Binary(state) => if state { 1.0 } else { 0.0 },
+        RawValue::Float64(value) => value,
+        RawValue::Int64(value) => value as f64,
+        RawValue::Fixed(value) => value.to_f64(),
+        _ => value.as_float().map(f64::from).unwrap_or(f64::NAN),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_int, c_void, CString};
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use crate::*;
+
+    extern "C" fn read(ctx: *mut c_void, value: *mut f64) -> c_int {
+        let calls = unsafe { &*(ctx as *const AtomicI32) };
+        match calls.fetch_add(1, Ordering::SeqCst) {
+            0 => {
+                unsafe { *value = 21.5 };
+                0
+            }
+            _ => 1,
+        }
+    }
+
+    extern "C" fn write(_: *mut c_void, _: f64) -> c_int {
+        0
+    }
+
+    #[test]
+    fn lifecycle() {
+        let calls = AtomicI32::new(0);
+        let ctx = &calls as *const AtomicI32 as *mut c_void;
+        let name = CString::new("ffi").unwrap();
+        let input = CString::new("input").unwrap();
+
+        unsafe {
+            let group = sensd_group_new(name.as_ptr(), std::ptr::null());
+            assert!(!group.is_null());
+            assert_eq!(SENSD_OK, sensd_group_set_interval(group, 1));
+            assert_eq!(SENSD_OK, sensd_group_add_input(group, input.as_ptr(), 0, read, ctx));
+            assert_eq!(SENSD_ERR_EXISTS, sensd_group_add_input(group, input.as_ptr(), 0, read, ctx));
+            assert_eq!(SENSD_OK, sensd_group_add_output(group, input.as_ptr(), 1, write, ctx));
+
+            let mut value = 0.0;
+            assert_eq!(SENSD_ERR_NO_VALUE, sensd_group_read(group, 0, &mut value));
+
+            let mut failures = -1;
+            assert_eq!(SENSD_OK, sensd_group_poll(group, &mut failures));
+            assert_eq!(0, failures);
+            assert_eq!(SENSD_OK, sensd_group_read(group, 0, &mut value));
+            assert_eq!(21.5, value);
+
+            // failed callback is reported as a failed read
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            assert_eq!(SENSD_OK, sensd_group_poll(group, &mut failures));
+            assert_eq!(1, failures);
+
+            assert_eq!(SENSD_OK, sensd_group_pause(group, 0));
+            assert_eq!(SENSD_NOT_DUE, sensd_group_poll(group, std::ptr::null_mut()));
+
+            assert_eq!(SENSD_ERR_NOT_FOUND, sensd_group_read(group, 2, &mut value));
+            assert_eq!(SENSD_ERR_ARGUMENT, sensd_group_poll(std::ptr::null_mut(), std::ptr::null_mut()));
+            sensd_group_free(group);
+        }
+    }
+}
@@ -1,14 +1,28 @@
-use crate::io::{IOEvent, Output, RawValue};
-use std::ops::DerefMut;
+use crate::action::{ParameterDescriptor, ParameterType, WriteTarget};
+use crate::console;
+use crate::io::{CorrelationId, IOEvent, Output, RawValue};
+use serde_json::Value;
+use crate::errors::{ActionError, ErrorType};
 use crate::helpers::Def;
+use crate::storage::Log;
 
 pub type BoxedAction = Box<dyn Action>;
 
+/// Behavior of an [`Action`] while its source input is isolated from control (ie: during calibration)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HoldPolicy {
+    /// Leave output in its current state
+    #[default]
+    Hold,
+    /// Drive output to its safe state. See [`Output::set_safe_state()`].
+    Safe,
+}
+
 /// Trait that enables actions to be performed based on incoming data.
 ///
 /// Actions are designed to activate [`Output`] devices based on data
 /// from [`crate::io::Input`] devices. The primary method for processing incoming
-/// data is [`Action::evaluate()`]
+/// data is [`Action::evaluate()`]. Destinations other than outputs are possible via [`WriteTarget`].
 pub trait Action {
     fn name(&self) -> &String;
 
@@ -19,21 +33,27 @@ pub trait Action {
     /// - `data`: Raw incoming data from input device.
     fn evaluate(&mut self, data: &IOEvent);
 
-    /// Builder function for setting `output` field.
+    /// Builder function for setting write target
     ///
     /// # Parameters
     ///
-    /// - `device`: `DeferredDevice` to set as output
+    /// - `target`: [`WriteTarget`] or [`Def<Output>`] to write to
     ///
     /// # Returns
     ///
     /// Ownership of `self` to allow builder pattern method chaining
-    fn set_output(self, device: Def<Output>) -> Self
+    fn set_output<T>(self, target: T) -> Self
     where
+        T: Into<WriteTarget>,
         Self: Sized;
 
-    /// Getter function for `output` field.
-    fn output(&self) -> Option<Def<Output>>;
+    /// Getter function for write target
+    fn target(&self) -> Option<WriteTarget>;
+
+    /// Physical output device, if write target is an [`Output`]
+    fn output(&self) -> Option<Def<Output>> {
+        self.target().and_then(|target| target.output())
+    }
 
     /// Setter function for output device field
     ///
@@ -41,26 +61,168 @@ pub trait Action {
     ///
     /// - `value`: Binary value to send to device
     ///
+    /// Errors are printed to stderr. See [`Action::write_correlated()`].
+    ///
     /// # Panics
     ///
-    /// - If error occurs when writing to device
-    /// - If output has no associated output
+    /// - If action has no associated target
     fn write(&self, value: RawValue) {
-        let output = self.output()
-            .expect("Action has no associated output device");
+        self.write_correlated(value, None)
+    }
 
-        let mut binding = output.try_lock().unwrap();
-        let device = binding.deref_mut();
+    /// Write to target on behalf of an input event
+    ///
+    /// Generated [`IOEvent`] shares the correlation id of the originating event.
+    ///
+    /// # Parameters
+    ///
+    /// - `value`: Value to send to target
+    /// - `correlation`: Correlation id of originating event
+    ///
+    /// Errors are printed to stderr rather than interrupting the control loop. A failed output
+    /// command (see [`crate::errors::DeviceError::WriteFailed`]) has already been logged and dispatched to hooks
+    /// by [`crate::io::Output::write()`], while other errors (ie: an unavailable target, or a
+    /// failed notification) are not recorded elsewhere. Actions which react to failures should
+    /// write to [`Action::target()`] directly.
+    ///
+    /// # Panics
+    ///
+    /// - If action has no associated target
+    fn write_correlated(&self, value: RawValue, correlation: Option<CorrelationId>) {
+        let result = self.target()
+            .expect("Action has no associated write target")
+            .write(self.name(), value, correlation);
+        if let Err(e) = result {
+            console::error(format!("{} could not write to target: {}", self.name(), e));
+        }
+    }
 
-        device.write(value)
-            .expect("Unexpected error when writing to output device.");
+    /// Get value of a named parameter
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, or if action has no parameter named `name`
+    fn parameter(&self, _name: &str) -> Option<RawValue> {
+        None
+    }
+
+    /// Names of all parameters returned by [`Action::parameter()`]
+    ///
+    /// Used to capture configuration in [`crate::storage::Snapshot`]. Empty by default.
+    fn parameter_names(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Schema and current value of every parameter
+    ///
+    /// By default, parameters of [`Action::parameter_names()`] are described without bounds, and
+    /// their type is taken from their current value. Actions should override this to declare bounds,
+    /// or the type of parameters which may not have a value.
+    fn describe(&self) -> Vec<ParameterDescriptor> {
+        self.parameter_names().iter()
+            .map(|name| {
+                let value = self.parameter(name);
+                let kind = value.map(ParameterType::from).unwrap_or(ParameterType::Float);
+                ParameterDescriptor::new(*name, kind).set_value(value)
+            })
+            .collect()
+    }
+
+    /// Set value of a named parameter (ie: setpoint) at runtime
+    ///
+    /// This allows parameters to be driven by other actions via [`WriteTarget::Parameter`].
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of parameter
+    /// - `value`: New value
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` if parameter was set
+    /// - `Err` if parameter is unknown or value is invalid. Parameters are unknown by default.
+    fn set_parameter(&mut self, name: &str, _value: RawValue) -> Result<(), ErrorType> {
+        Err(Box::new(ActionError::UnknownParameter {
+            action: self.name().clone(),
+            parameter: name.to_string(),
+        }))
+    }
+
+    /// Called when source input stops propagating data to this action
+    ///
+    /// By default, every output of [`Action::target()`] is driven to its safe state when `policy` is
+    /// [`HoldPolicy::Safe`]. Tagged outputs are resolved at the time of calling.
+    ///
+    /// # Parameters
+    ///
+    /// - `policy`: How output should be treated while held
+    fn hold(&mut self, policy: HoldPolicy) {
+        if policy == HoldPolicy::Safe {
+            let outputs = self.target().map(|target| target.outputs()).unwrap_or_default();
+            for output in outputs {
+                let mut output = output.try_lock().unwrap();
+                if let Some(Err(e)) = output.write_safe_state() {
+                    console::error(format!("Could not write safe state for {}: {}", self.name(), e));
+                }
+            }
+        }
+    }
+
+    /// Called when source input resumes propagating data to this action
+    ///
+    /// Actions with internal state that accumulates over time should reset it here.
+    fn release(&mut self) {}
+
+    /// Called with log of source input when subscribed, so that internal state may be seeded from
+    /// recent history rather than starting cold after every restart
+    ///
+    /// Also called once archived events have been loaded by [`crate::storage::Persistent::load()`]
+    /// of [`crate::storage::Group`]. Therefore, implementations should replace seeded state rather
+    /// than accumulate it. Does nothing by default.
+    ///
+    /// # Parameters
+    ///
+    /// - `log`: Log of source input
+    fn on_attach(&mut self, _log: &Log) {}
+
+    /// Called when group is paused by [`crate::storage::Group::pause()`]
+    ///
+    /// Scheduled routines are not executed while paused, so actions which energize outputs for a
+    /// period of time should release them here. Does nothing by default.
+    fn on_pause(&mut self) {}
+
+    /// Called once by [`crate::storage::Group::shutdown()`] before the group stops
+    ///
+    /// Scheduled routines will never be executed, so outputs energized by this action should be
+    /// released here. Runtime state returned by [`Action::state()`] is persisted afterwards. Does
+    /// nothing by default.
+    fn on_shutdown(&mut self) {}
+
+    /// Runtime state which should survive restarts (ie: accumulated controller state)
+    ///
+    /// Configuration should not be included since it is restored from code or settings.
+    ///
+    /// # Returns
+    ///
+    /// `None` by default, for actions which are stateless
+    fn state(&self) -> Option<Value> {
+        None
+    }
+
+    /// Restore runtime state previously returned by [`Action::state()`]
+    ///
+    /// # Parameters
+    ///
+    /// - `state`: Previously saved state
+    fn restore_state(&mut self, _state: &Value) -> Result<(), ErrorType> {
+        Ok(())
     }
 
-    /// Print notification to stdout.
+    /// Print notification to stdout in the format selected by [`crate::settings::Settings`]
     ///
     /// This should be controlled by an internal option flag.
     fn notify(&self, msg: &str) {
-        println!("{}", msg);
+        console::info(msg);
     }
 
     /// Consume [`Self`] and wrap in a [`Box`] so it can be coerced into an [`Action`] trait object.
@@ -0,0 +1,325 @@
+use std::collections::VecDeque;
+
+use crate::action::{Action, BoxedAction, WriteTarget};
+use crate::alarm::{Alarm, AlarmHandler, AlarmSeverity};
+use crate::helpers::Def;
+use crate::io::{EventQuality, IOEvent, RawValue};
+use crate::storage::Log;
+
+/// Statistical test used by [`Anomaly`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnomalyMethod {
+    /// Flag values more than `k` standard deviations from the mean of the baseline
+    ZScore(f32),
+    /// Flag values more than `k` interquartile ranges outside of the first or third quartile
+    Iqr(f32),
+}
+
+/// Detect readings that deviate from a rolling baseline
+///
+/// Unlike [`crate::action::actions::Threshold`], which compares against a fixed value, [`Anomaly`]
+/// learns what "normal" looks like from the most recent readings. This catches probe faults and
+/// process upsets that fixed thresholds miss.
+///
+/// Anomalous readings are not added to the baseline. Detection begins once the baseline window has
+/// been filled.
+///
+/// When an anomaly is detected:
+///
+/// - the logged [`IOEvent`] is tagged with [`EventQuality::Suspect`] if a source log is set by
+///   [`Anomaly::set_log()`]
+/// - an alarm is raised if an [`AlarmHandler`] is set by [`Anomaly::set_alarms()`]. The alarm is
+///   cleared once a normal reading is received.
+/// - output is actuated if one is set. Output is de-actuated by the next normal reading.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::{Action, IOCommand};
+/// use sensd::action::actions::{Anomaly, AnomalyMethod};
+/// use sensd::io::{Device, Input, RawValue};
+/// use sensd::storage::Chronicle;
+///
+/// let mut input = Input::default()
+///     .set_command(IOCommand::Input(|| RawValue::Float(7.0)))
+///     .init_log()
+///     .init_publisher();
+///
+/// let anomaly = Anomaly::new("ph anomaly", AnomalyMethod::ZScore(3.0), 20)
+///     .set_log(input.log().unwrap());
+/// input.publisher_mut().as_mut().unwrap().subscribe(anomaly.into_boxed());
+/// ```
+pub struct Anomaly {
+    name: String,
+    method: AnomalyMethod,
+
+    /// Number of readings used as baseline
+    window: usize,
+    baseline: VecDeque<f32>,
+
+    /// Whether the last reading was anomalous
+    anomalous: bool,
+
+    log: Option<Def<Log>>,
+    alarms: Option<Def<AlarmHandler>>,
+    target: Option<WriteTarget>,
+}
+
+impl Anomaly {
+    /// Constructor for [`Anomaly`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of action. Also used as the name of raised alarms.
+    /// - `method`: Statistical test to apply
+    /// - `window`: Number of readings in rolling baseline
+    ///
+    /// # Panics
+    ///
+    /// If `window` is less than 2
+    pub fn new<N>(name: N, method: AnomalyMethod, window: usize) -> Self
+    where
+        N: Into<String>
+    {
+        assert!(window >= 2, "Baseline window must contain at least 2 readings");
+        Self {
+            name: name.into(),
+            method,
+            window,
+            baseline: VecDeque::with_capacity(window),
+            anomalous: false,
+            log: None,
+            alarms: None,
+            target: None,
+        }
+    }
+
+    /// Builder method to set log of source device so that events can be tagged
+    pub fn set_log(mut self, log: Def<Log>) -> Self {
+        self.log = Some(log);
+        self
+    }
+
+    /// Builder method to set alarm handler used to raise alarms
+    pub fn set_alarms(mut self, alarms: Def<AlarmHandler>) -> Self {
+        self.alarms = Some(alarms);
+        self
+    }
+
+    /// Whether the last reading was anomalous
+    pub fn is_anomalous(&self) -> bool {
+        self.anomalous
+    }
+
+    /// Check if value is anomalous relative to current baseline
+    ///
+    /// # Returns
+    ///
+    /// `false` until the baseline window has been filled
+    pub fn check(&self, value: f32) -> bool {
+        if self.baseline.len() < self.window {
+            return false;
+        }
+
+        match self.method {
+            AnomalyMethod::ZScore(k) => {
+                let n = self.baseline.len() as f32;
+                let mean = self.baseline.iter().sum::<f32>() / n;
+                let variance = self.baseline.iter()
+                    .map(|x| (x - mean).powi(2))
+                    .sum::<f32>() / (n - 1.0);
+                (value - mean).abs() > k * variance.sqrt()
+            }
+            AnomalyMethod::Iqr(k) => {
+                let mut sorted: Vec<f32> = self.baseline.iter().copied().collect();
+                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+                let q1 = quantile(&sorted, 0.25);
+                let q3 = quantile(&sorted, 0.75);
+                let iqr = q3 - q1;
+                value < q1 - k * iqr || value > q3 + k * iqr
+            }
+        }
+    }
+
+    /// Tag logged event as suspect
+    fn tag(&self, data: &IOEvent) {
+        if let Some(log) = &self.log {
+            if let Some(event) = log.try_lock().unwrap().get_mut(&data.timestamp) {
+                event.quality = EventQuality::Suspect;
+            }
+        }
+    }
+}
+
+/// Linearly interpolated quantile of sorted data
+fn quantile(sorted: &[f32], q: f32) -> f32 {
+    let position = q * (sorted.len() - 1) as f32;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f32)
+}
+
+impl Action for Anomaly {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Evaluate incoming data against baseline
+    ///
+    /// Non-numeric and non-finite data is ignored.
+    fn evaluate(&mut self, data: &IOEvent) {
+        let value = match data.value.finite_float() {
+            Some(value) => value,
+            None => return,
+        };
+
+        let anomalous = self.check(value);
+
+        if anomalous {
+            self.tag(data);
+            self.notify(&format!("Anomalous reading for {}: {}", self.name, data.value));
+
+            if let Some(alarms) = &self.alarms {
+                let alarm = Alarm::new(self.name.clone(), AlarmSeverity::Warning,
+                                       "Reading deviates from baseline", data.timestamp)
+                    .set_value(data.value);
+                alarms.try_lock().unwrap().raise_alarm(alarm);
+            }
+        } else {
+            if self.baseline.len() == self.window {
+                self.baseline.pop_front();
+            }
+            self.baseline.push_back(value);
+
+            if self.anomalous {
+                if let Some(alarms) = &self.alarms {
+                    alarms.try_lock().unwrap().clear(&self.name);
+                }
+            }
+        }
+
+        if self.target.is_some() && anomalous != self.anomalous {
+            self.write_correlated(RawValue::Binary(anomalous), data.correlation);
+        }
+        self.anomalous = anomalous;
+    }
+
+    /// Seed baseline with most recent readings that were not flagged as suspect or bad
+    fn on_attach(&mut self, log: &Log) {
+        let recent: Vec<f32> = log.iter().rev()
+            .filter(|(_, event)| event.quality == EventQuality::Good)
+            .filter_map(|(_, event)| event.value.finite_float())
+            .take(self.window)
+            .collect();
+        self.baseline = recent.into_iter().rev().collect();
+    }
+
+    fn set_output<T>(mut self, target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized,
+    {
+        self.target = Some(target.into());
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        self.target.clone()
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::action::Action;
+    use crate::action::actions::{Anomaly, AnomalyMethod};
+    use crate::alarm::AlarmHandler;
+    use crate::helpers::Def;
+    use crate::io::{Device, EventQuality, Input, IOEvent, RawValue};
+    use crate::storage::{Chronicle, Log};
+
+    const BASELINE: [f32; 6] = [7.0, 7.1, 6.9, 7.0, 7.2, 6.8];
+
+    fn fill(action: &mut Anomaly) {
+        for value in BASELINE {
+            action.evaluate(&IOEvent::new(RawValue::Float(value)));
+        }
+    }
+
+    #[test]
+    fn z_score() {
+        let mut action = Anomaly::new("", AnomalyMethod::ZScore(3.0), BASELINE.len());
+        assert!(!action.check(100.0));
+
+        fill(&mut action);
+        assert!(!action.check(7.3));
+        assert!(action.check(9.0));
+        assert!(action.check(5.0));
+    }
+
+    #[test]
+    fn iqr() {
+        let mut action = Anomaly::new("", AnomalyMethod::Iqr(1.5), BASELINE.len());
+        fill(&mut action);
+
+        assert!(!action.check(7.2));
+        assert!(action.check(8.0));
+    }
+
+    #[test]
+    fn tag_and_alarm() {
+        let log = Def::new(Log::default());
+        let alarms = Def::new(AlarmHandler::default());
+        let mut action = Anomaly::new("probe", AnomalyMethod::ZScore(3.0), BASELINE.len())
+            .set_log(log.clone())
+            .set_alarms(alarms.clone());
+        fill(&mut action);
+
+        let event = IOEvent::new(RawValue::Float(20.0));
+        log.try_lock().unwrap().push(event.clone()).unwrap();
+        action.evaluate(&event);
+
+        assert!(action.is_anomalous());
+        assert_eq!(EventQuality::Suspect, log.try_lock().unwrap().get_mut(&event.timestamp).unwrap().quality);
+        assert!(alarms.try_lock().unwrap().get("probe").is_some());
+
+        // anomaly is not added to baseline and alarm clears on normal reading
+        action.evaluate(&IOEvent::new(RawValue::Float(7.0)));
+        assert!(!action.is_anomalous());
+        assert!(alarms.try_lock().unwrap().get("probe").is_none());
+    }
+
+    #[test]
+    /// Assert that baseline is seeded from log of source input when subscribed
+    fn attach() {
+        let input = Input::new("", 0, None).init_log();
+        {
+            let log = input.log().unwrap();
+            let mut log = log.try_lock().unwrap();
+            let start = Utc::now();
+            for (i, value) in BASELINE.iter().enumerate() {
+                log.push(IOEvent::with_timestamp(start + Duration::seconds(i as i64), RawValue::Float(*value))).unwrap();
+            }
+            let mut suspect = IOEvent::with_timestamp(start + Duration::seconds(10), RawValue::Float(20.0));
+            suspect.quality = EventQuality::Suspect;
+            log.push(suspect).unwrap();
+        }
+
+        let alarms = Def::new(AlarmHandler::default());
+        let mut input = input.init_publisher();
+        let publisher = input.publisher_mut().as_mut().unwrap();
+        publisher.subscribe(Anomaly::new("probe", AnomalyMethod::ZScore(3.0), BASELINE.len())
+            .set_alarms(alarms.clone())
+            .into_boxed());
+
+        // suspect reading is excluded from baseline, so that detection begins immediately
+        publisher.propagate(&IOEvent::new(RawValue::Float(9.0)));
+        assert!(alarms.try_lock().unwrap().get("probe").is_some());
+    }
+}
@@ -0,0 +1,395 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::action::metrics::last_write;
+use crate::action::{Action, BoxedAction, HoldPolicy, ParameterDescriptor, WriteTarget};
+use crate::console;
+use crate::errors::ErrorType;
+use crate::helpers::Def;
+use crate::io::{IOEvent, RawValue};
+use crate::storage::Log;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Labelled set of parameter values compared by [`Comparison`]
+pub struct ParameterSet {
+    pub label: String,
+
+    /// Values passed to [`Action::set_parameter()`] when set becomes active
+    pub parameters: Vec<(String, RawValue)>,
+}
+
+impl ParameterSet {
+    pub fn new<L: Into<String>>(label: L) -> Self {
+        Self { label: label.into(), parameters: Vec::new() }
+    }
+
+    /// Builder method to add a parameter value
+    pub fn set<N: Into<String>>(mut self, name: N, value: RawValue) -> Self {
+        self.parameters.push((name.into(), value));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Performance of the loop while a [`ParameterSet`] was active
+pub struct SetMetrics {
+    pub label: String,
+
+    /// Number of time blocks during which set was active
+    pub blocks: usize,
+
+    /// Number of readings which were scored
+    pub samples: usize,
+
+    /// Sum of absolute error between readings and reference
+    pub absolute_error: f64,
+    /// Sum of squared error between readings and reference
+    pub squared_error: f64,
+    /// Largest absolute error
+    pub max_error: f64,
+
+    /// Number of times the written value changed (ie: a relay cycling)
+    pub activations: usize,
+    /// Sum of numeric values written, to compare control effort
+    pub effort: f64,
+    /// Number of values written
+    pub writes: usize,
+}
+
+impl SetMetrics {
+    fn new(label: &str) -> Self {
+        Self { label: label.to_string(), ..Default::default() }
+    }
+
+    /// Mean absolute error. `None` if no readings were scored.
+    pub fn mean_error(&self) -> Option<f64> {
+        (self.samples > 0).then(|| self.absolute_error / self.samples as f64)
+    }
+
+    /// Root mean square error. `None` if no readings were scored.
+    pub fn rms_error(&self) -> Option<f64> {
+        (self.samples > 0).then(|| (self.squared_error / self.samples as f64).sqrt())
+    }
+
+    /// Mean of values written. `None` if nothing was written.
+    pub fn mean_effort(&self) -> Option<f64> {
+        (self.writes > 0).then(|| self.effort / self.writes as f64)
+    }
+
+    fn record(&mut self, error: Option<f64>, written: Option<RawValue>, changed: bool) {
+        if let Some(error) = error {
+            self.samples += 1;
+            self.absolute_error += error.abs();
+            self.squared_error += error * error;
+            self.max_error = self.max_error.max(error.abs());
+        }
+        if let Some(value) = written.and_then(|value| value.as_float()) {
+            self.writes += 1;
+            self.effort += f64::from(value);
+        }
+        if changed {
+            self.activations += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Metrics of each [`ParameterSet`] compared by [`Comparison`]
+pub struct ComparisonReport {
+    pub sets: Vec<SetMetrics>,
+}
+
+impl ComparisonReport {
+    /// Set with the lowest mean absolute error
+    ///
+    /// # Returns
+    ///
+    /// `None` until every set has been scored
+    pub fn preferred(&self) -> Option<&SetMetrics> {
+        if self.sets.is_empty() || self.sets.iter().any(|set| set.samples == 0) {
+            return None;
+        }
+        self.sets.iter()
+            .min_by(|a, b| a.mean_error().unwrap().total_cmp(&b.mean_error().unwrap()))
+    }
+
+    /// Human readable table of metrics
+    pub fn to_text(&self) -> String {
+        let format = |value: Option<f64>| value.map(|value| format!("{:.3}", value)).unwrap_or("-".to_string());
+        let mut text = String::from("set\tblocks\tsamples\tmean error\trms error\tmax error\tactivations\tmean effort");
+        for set in self.sets.iter() {
+            text.push_str(&format!("\n{}\t{}\t{}\t{}\t{}\t{:.3}\t{}\t{}",
+                                   set.label, set.blocks, set.samples, format(set.mean_error()),
+                                   format(set.rms_error()), set.max_error, set.activations,
+                                   format(set.mean_effort())));
+        }
+        if let Some(preferred) = self.preferred() {
+            text.push_str(&format!("\npreferred: {}", preferred.label));
+        }
+        text
+    }
+}
+
+/// Tuning aid which alternates parameter sets of an action over successive time blocks
+///
+/// [`Comparison`] wraps a controller (ie: [`crate::action::actions::PID`] gains, or the threshold of
+/// [`crate::action::actions::Threshold`]) and applies each [`ParameterSet`] in turn for a block of
+/// time. Every reading is scored against the reference parameter of the wrapped action (`setpoint`
+/// by default), so that sets are compared on the same loop under similar conditions. Readings
+/// immediately after a switch may be excluded from error metrics using [`Comparison::set_settle()`],
+/// so that one set is not penalized for the transient caused by another.
+///
+/// Block boundaries are determined from the timestamps of readings. Metrics are shared through the
+/// handle returned by [`Comparison::report()`], which remains valid once the action is boxed.
+///
+/// All other methods of [`Action`] are delegated to the wrapped action.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{Duration, Utc};
+/// use sensd::action::Action;
+/// use sensd::action::actions::{Comparison, ParameterSet, PID};
+/// use sensd::io::{IOEvent, RawValue};
+///
+/// let mut comparison = Comparison::new(
+///     PID::new("heater", 22.0, 10.0),
+///     vec![ParameterSet::new("gentle").set("kp", RawValue::Float(0.5)),
+///          ParameterSet::new("aggressive").set("kp", RawValue::Float(2.0))],
+///     Duration::hours(1));
+/// let report = comparison.report();
+///
+/// let start = Utc::now();
+/// for minute in 0..240 {
+///     let reading = IOEvent::with_timestamp(start + Duration::minutes(minute), RawValue::Float(21.0));
+///     comparison.evaluate(&reading);
+/// }
+///
+/// let report = report.try_lock().unwrap();
+/// assert_eq!((2, 2), (report.sets[0].blocks, report.sets[1].blocks));
+/// assert!(report.preferred().is_some());
+/// ```
+pub struct Comparison<A: Action> {
+    inner: A,
+    sets: Vec<ParameterSet>,
+
+    /// Duration that each set is active
+    block: Duration,
+
+    /// Duration after a switch during which readings are not scored
+    settle: Duration,
+
+    /// Name of parameter of wrapped action that readings are compared to
+    reference: String,
+
+    /// Index of active set and start of its block. `None` until first reading.
+    active: Option<(usize, DateTime<Utc>)>,
+
+    /// Most recent value written by wrapped action
+    last_value: Option<RawValue>,
+
+    report: Def<ComparisonReport>,
+}
+
+impl<A: Action> Comparison<A> {
+    /// Constructor for [`Comparison`]
+    ///
+    /// # Parameters
+    ///
+    /// - `inner`: Action to tune. Its name and write target are used.
+    /// - `sets`: Parameter sets to alternate between, in order
+    /// - `block`: Duration that each set is active before switching to the next
+    pub fn new(inner: A, sets: Vec<ParameterSet>, block: Duration) -> Self {
+        let report = ComparisonReport { sets: sets.iter().map(|set| SetMetrics::new(&set.label)).collect() };
+        Self {
+            inner,
+            sets,
+            block,
+            settle: Duration::zero(),
+            reference: "setpoint".to_string(),
+            active: None,
+            last_value: None,
+            report: Def::new(report),
+        }
+    }
+
+    /// Builder method to exclude readings taken within `settle` of a switch from error metrics
+    ///
+    /// Values written during this time are still counted.
+    pub fn set_settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+
+    /// Builder method to set name of the parameter that readings are compared to
+    ///
+    /// ie: `threshold` for [`crate::action::actions::Threshold`]
+    pub fn set_reference<N: Into<String>>(mut self, name: N) -> Self {
+        self.reference = name.into();
+        self
+    }
+
+    /// Shared handle to metrics of each set
+    pub fn report(&self) -> Def<ComparisonReport> {
+        self.report.clone()
+    }
+
+    /// Label of the set which is currently active
+    pub fn active(&self) -> Option<&String> {
+        self.active.map(|(index, _)| &self.sets[index].label)
+    }
+
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Apply set and begin a new block
+    fn switch(&mut self, index: usize, at: DateTime<Utc>) {
+        for (name, value) in self.sets[index].parameters.iter() {
+            if let Err(e) = self.inner.set_parameter(name, *value) {
+                console::error(format!("Could not apply \"{}\" to {}: {}", self.sets[index].label, self.inner.name(), e));
+            }
+        }
+        self.active = Some((index, at));
+        self.report.try_lock().unwrap().sets[index].blocks += 1;
+    }
+}
+
+impl<A: Action + 'static> Action for Comparison<A> {
+    fn name(&self) -> &String {
+        self.inner.name()
+    }
+
+    /// Switch set once block has elapsed, then evaluate wrapped action and score reading
+    fn evaluate(&mut self, data: &IOEvent) {
+        if self.sets.is_empty() {
+            self.inner.evaluate(data);
+            return;
+        }
+        match self.active {
+            None => self.switch(0, data.timestamp),
+            Some((index, start)) if data.timestamp - start >= self.block => {
+                self.switch((index + 1) % self.sets.len(), data.timestamp);
+            }
+            _ => (),
+        }
+        let (index, start) = self.active.unwrap();
+
+        let reference = self.inner.parameter(&self.reference).and_then(|value| value.as_float());
+        let written = last_write(|| self.inner.evaluate(data));
+        let changed = written.is_some_and(|value| self.last_value.is_some_and(|last| last != value));
+        if written.is_some() {
+            self.last_value = written;
+        }
+
+        let error = data.value.finite_float().zip(reference)
+            .filter(|_| data.timestamp - start >= self.settle)
+            .map(|(value, reference)| f64::from(value) - f64::from(reference));
+        self.report.try_lock().unwrap().sets[index].record(error, written, changed);
+    }
+
+    fn set_output<T>(mut self, target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized,
+    {
+        self.inner = self.inner.set_output(target);
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        self.inner.target()
+    }
+
+    fn parameter(&self, name: &str) -> Option<RawValue> {
+        self.inner.parameter(name)
+    }
+
+    fn parameter_names(&self) -> &[&'static str] {
+        self.inner.parameter_names()
+    }
+
+    fn describe(&self) -> Vec<ParameterDescriptor> {
+        self.inner.describe()
+    }
+
+    /// Parameters which belong to a set are overwritten when the next block begins
+    fn set_parameter(&mut self, name: &str, value: RawValue) -> Result<(), ErrorType> {
+        self.inner.set_parameter(name, value)
+    }
+
+    fn hold(&mut self, policy: HoldPolicy) {
+        self.inner.hold(policy)
+    }
+
+    fn release(&mut self) {
+        self.inner.release()
+    }
+
+    fn on_attach(&mut self, log: &Log) {
+        self.inner.on_attach(log)
+    }
+
+    fn on_pause(&mut self) {
+        self.inner.on_pause()
+    }
+
+    fn on_shutdown(&mut self) {
+        self.inner.on_shutdown()
+    }
+
+    fn state(&self) -> Option<Value> {
+        self.inner.state()
+    }
+
+    fn restore_state(&mut self, state: &Value) -> Result<(), ErrorType> {
+        self.inner.restore_state(state)
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::action::{Action, Trigger, WriteTarget};
+    use crate::action::actions::{Comparison, ParameterSet, Threshold};
+    use crate::io::{IOEvent, RawValue};
+    use crate::storage::Variables;
+
+    #[test]
+    fn alternate_sets() {
+        let variables = Variables::default();
+        let mut comparison = Comparison::new(
+            Threshold::new("heat", RawValue::Float(20.0), Trigger::LT),
+            vec![ParameterSet::new("low").set("threshold", RawValue::Float(19.0)),
+                 ParameterSet::new("high").set("threshold", RawValue::Float(21.0))],
+            Duration::minutes(10))
+            .set_reference("threshold")
+            .set_settle(Duration::minutes(2))
+            .set_output(WriteTarget::variable(variables.clone(), "heater"));
+        let report = comparison.report();
+
+        let start = Utc::now();
+        for minute in 0..20 {
+            let reading = IOEvent::with_timestamp(start + Duration::minutes(minute), RawValue::Float(20.0));
+            comparison.evaluate(&reading);
+            let expected = if minute < 10 { "low" } else { "high" };
+            assert_eq!(expected, comparison.active().unwrap());
+        }
+        assert_eq!(Some(RawValue::Binary(true)), variables.get("heater"));
+
+        let report = report.try_lock().unwrap();
+        let (low, high) = (&report.sets[0], &report.sets[1]);
+        // readings within settle time are not scored
+        assert_eq!((8, 8), (low.samples, high.samples));
+        assert_eq!((Some(1.0), Some(1.0)), (low.mean_error(), high.mean_error()));
+        // output turns on once threshold is raised above reading
+        assert_eq!((0, 1), (low.activations, high.activations));
+        assert!(report.to_text().contains("preferred: low"));
+    }
+}
@@ -0,0 +1,268 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+use crate::action::{Action, BoxedAction, Trigger, WriteTarget};
+use crate::io::{IOEvent, RawValue};
+
+/// Model fitted to recent readings by [`Forecast`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ForecastModel {
+    /// Least-squares straight line
+    #[default]
+    Linear,
+    /// Least-squares fit of `ln(value)`. Requires strictly positive readings.
+    Exponential,
+}
+
+/// Predictive controller that actuates before a threshold is crossed
+///
+/// A short trend is fitted to the most recent readings and extrapolated by `horizon`. Output is
+/// actuated when either the current reading or the predicted value exceeds the threshold, and is
+/// de-actuated otherwise. This allows slow processes to be acted upon early (ie: start a chiller
+/// before temperature exceeds the limit).
+///
+/// No prediction is made until at least two readings have been received, or if the
+/// [`ForecastModel::Exponential`] model receives non-positive readings.
+///
+/// # Example
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::action::{Action, IOCommand, Trigger};
+/// use sensd::action::actions::{Forecast, ForecastModel};
+/// use sensd::io::{Device, Output, RawValue};
+///
+/// let chiller = Output::default()
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .into_deferred();
+///
+/// let action = Forecast::new("pre-chill", RawValue::Float(30.0), Trigger::GT, Duration::minutes(10))
+///     .set_model(ForecastModel::Linear)
+///     .set_window(12)
+///     .set_output(chiller);
+/// ```
+pub struct Forecast {
+    name: String,
+    threshold: RawValue,
+    trigger: Trigger,
+
+    /// How far ahead to predict
+    horizon: Duration,
+    model: ForecastModel,
+
+    /// Maximum number of readings used for fit
+    window: usize,
+    samples: VecDeque<(DateTime<Utc>, f32)>,
+
+    target: Option<WriteTarget>,
+}
+
+impl Forecast {
+    /// Constructor for [`Forecast`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of action
+    /// - `threshold`: Value not to exceed
+    /// - `trigger`: Relationship between predicted value and threshold
+    /// - `horizon`: How far ahead of the latest reading to predict
+    ///
+    /// # Returns
+    ///
+    /// [`Forecast`] using a linear model fitted to 10 readings
+    pub fn new<N>(name: N, threshold: RawValue, trigger: Trigger, horizon: Duration) -> Self
+    where
+        N: Into<String>
+    {
+        Self {
+            name: name.into(),
+            threshold,
+            trigger,
+            horizon,
+            model: ForecastModel::default(),
+            window: 10,
+            samples: VecDeque::new(),
+            target: None,
+        }
+    }
+
+    /// Builder method to set fitted model
+    pub fn set_model(mut self, model: ForecastModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Builder method to set number of readings used for fit
+    ///
+    /// # Panics
+    ///
+    /// If `window` is less than 2
+    pub fn set_window(mut self, window: usize) -> Self {
+        assert!(window >= 2, "At least 2 readings are required to fit a trend");
+        self.window = window;
+        self
+    }
+
+    pub fn horizon(&self) -> Duration {
+        self.horizon
+    }
+
+    /// Predict value at `horizon` after the latest reading
+    ///
+    /// # Returns
+    ///
+    /// `None` if a fit is not possible
+    pub fn predict(&self) -> Option<f32> {
+        let (latest, _) = *self.samples.back()?;
+        if self.samples.len() < 2 {
+            return None;
+        }
+
+        // seconds relative to latest reading to preserve precision
+        let points = self.samples.iter()
+            .map(|(timestamp, value)| {
+                let x = (*timestamp - latest).num_milliseconds() as f32 / 1000.0;
+                let y = match self.model {
+                    ForecastModel::Linear => Some(*value),
+                    ForecastModel::Exponential if *value > 0.0 => Some(value.ln()),
+                    ForecastModel::Exponential => None,
+                };
+                y.map(|y| (x, y))
+            })
+            .collect::<Option<Vec<(f32, f32)>>>()?;
+
+        let (slope, intercept) = fit(&points)?;
+        let predicted = intercept + slope * (self.horizon.num_milliseconds() as f32 / 1000.0);
+
+        match self.model {
+            ForecastModel::Linear => Some(predicted),
+            ForecastModel::Exponential => Some(predicted.exp()),
+        }
+    }
+}
+
+/// Ordinary least-squares fit
+///
+/// # Returns
+///
+/// Slope and intercept, or `None` if all `x` are equal
+fn fit(points: &[(f32, f32)]) -> Option<(f32, f32)> {
+    let n = points.len() as f32;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f32>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f32>() / n;
+
+    let covariance: f32 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f32 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+    if variance == 0.0 {
+        return None;
+    }
+    let slope = covariance / variance;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+impl Action for Forecast {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Add reading to window and actuate output if current or predicted value exceeds threshold
+    ///
+    /// Non-numeric and non-finite data is ignored.
+    fn evaluate(&mut self, data: &IOEvent) {
+        let value = match data.value.finite_float() {
+            Some(value) => value,
+            None => return,
+        };
+        let threshold = match self.threshold.as_float() {
+            Some(threshold) => RawValue::Float(threshold),
+            None => return,
+        };
+
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((data.timestamp, value));
+
+        let exceeded = self.trigger.exceeded(RawValue::Float(value), threshold);
+        let predicted = self.predict()
+            .map(|predicted| self.trigger.exceeded(RawValue::Float(predicted), threshold))
+            .unwrap_or(false);
+
+        if predicted && !exceeded {
+            self.notify(&format!("{} predicted to exceed {} within {}", self.name, self.threshold, self.horizon));
+        }
+
+        if self.target.is_some() {
+            self.write_correlated(RawValue::Binary(exceeded || predicted), data.correlation);
+        }
+    }
+
+    fn set_output<T>(mut self, target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized,
+    {
+        self.target = Some(target.into());
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        self.target.clone()
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::action::{Action, IOCommand, Trigger};
+    use crate::action::actions::{Forecast, ForecastModel};
+    use crate::io::{Device, DeviceGetters, IOEvent, Output, RawValue};
+
+    #[test]
+    fn linear() {
+        let mut action = Forecast::new("", RawValue::Float(30.0), Trigger::GT, Duration::seconds(10));
+        let now = Utc::now();
+
+        action.evaluate(&IOEvent::with_timestamp(now, RawValue::Float(20.0)));
+        assert!(action.predict().is_none());
+
+        action.evaluate(&IOEvent::with_timestamp(now + Duration::seconds(1), RawValue::Float(21.0)));
+        assert!((action.predict().unwrap() - 31.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn exponential() {
+        let mut action = Forecast::new("", RawValue::Float(30.0), Trigger::GT, Duration::seconds(1))
+            .set_model(ForecastModel::Exponential);
+        let now = Utc::now();
+
+        action.evaluate(&IOEvent::with_timestamp(now, RawValue::Float(1.0)));
+        action.evaluate(&IOEvent::with_timestamp(now + Duration::seconds(1), RawValue::Float(2.0)));
+        assert!((action.predict().unwrap() - 4.0).abs() < 0.01);
+
+        action.evaluate(&IOEvent::with_timestamp(now + Duration::seconds(2), RawValue::Float(0.0)));
+        assert!(action.predict().is_none());
+    }
+
+    #[test]
+    fn actuates_early() {
+        let output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .into_deferred();
+        let mut action = Forecast::new("", RawValue::Float(30.0), Trigger::GT, Duration::seconds(10))
+            .set_output(output.clone());
+        let now = Utc::now();
+
+        action.evaluate(&IOEvent::with_timestamp(now, RawValue::Float(20.0)));
+        assert_eq!(Some(RawValue::Binary(false)), *output.try_lock().unwrap().state());
+
+        action.evaluate(&IOEvent::with_timestamp(now + Duration::seconds(1), RawValue::Float(21.0)));
+        assert_eq!(Some(RawValue::Binary(true)), *output.try_lock().unwrap().state());
+    }
+}
@@ -0,0 +1,353 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::action::{Action, BoxedAction, HoldPolicy, ParameterDescriptor, ParameterType, WriteTarget};
+use crate::alarm::{Alarm, AlarmHandler, AlarmSeverity};
+use crate::errors::{ActionError, ErrorType, FilesystemError};
+use crate::helpers::Def;
+use crate::io::{is_on, DeviceGetters, EventQuality, IOEvent, Output, RawValue};
+
+#[derive(Serialize, Deserialize)]
+/// Runtime state of [`LeakDetector`] persisted by snapshots
+struct LeakState {
+    tripped: bool,
+}
+
+/// Safety interlock which closes a master valve when water flows unexpectedly
+///
+/// [`LeakDetector`] subscribes to the publisher of a flow [`crate::io::Input`] and watches the
+/// commanded state of any number of valve [`Output`]s. A leak is detected when:
+///
+/// - flow is detected while every valve is commanded closed, for longer than the delay set by
+///   [`LeakDetector::set_delay()`] (ie: to ignore water draining after valves close)
+/// - or flow has been continuous for longer than the limit set by
+///   [`LeakDetector::set_max_duration()`], regardless of valves (ie: a burst pipe downstream of an
+///   open valve)
+///
+/// Once a leak is detected, the detector trips: the master valve given to
+/// [`Action::set_output()`] is closed by writing `false`, and a [`AlarmSeverity::Critical`] alarm
+/// named after the action is raised if an [`AlarmHandler`] is set. A tripped detector stays tripped,
+/// including across restarts, until the `tripped` parameter is set to `false`. Resetting clears the
+/// alarm, but never reopens the master valve.
+///
+/// Valves which have never been written, or which cannot be locked, are considered closed and open
+/// respectively.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::{Action, IOCommand};
+/// use sensd::action::actions::LeakDetector;
+/// use sensd::io::{Device, DeviceGetters, Input, IOKind, Output, RawValue};
+///
+/// let valve = Output::new("zone 1", 1, IOKind::Unassigned)
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .into_deferred();
+/// let master = Output::new("master", 2, IOKind::Unassigned)
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .into_deferred();
+///
+/// let mut flow = Input::new("flow", 0, IOKind::Unassigned)
+///     .set_command(IOCommand::Input(|| RawValue::Float(4.0)))
+///     .init_publisher();
+/// flow.publisher_mut().as_mut().unwrap().subscribe(
+///     LeakDetector::new("leak", 0.5, vec![valve.clone()])
+///         .set_output(master.clone())
+///         .into_boxed());
+///
+/// // flowing with every valve closed
+/// valve.try_lock().unwrap().write(RawValue::Binary(false)).unwrap();
+/// flow.read().unwrap();
+/// assert_eq!(Some(RawValue::Binary(false)), *master.try_lock().unwrap().state());
+/// ```
+pub struct LeakDetector {
+    name: String,
+
+    /// Flow above which water is considered to be flowing, to ignore sensor noise
+    threshold: f32,
+
+    /// Valves whose commanded state is checked
+    valves: Vec<Def<Output>>,
+
+    /// Time that unexpected flow must persist before a leak is detected
+    delay: Duration,
+
+    /// Longest continuous flow allowed with valves open
+    max_duration: Option<Duration>,
+
+    /// Time at which continuous flow began
+    flowing_since: Option<DateTime<Utc>>,
+
+    /// Time at which flow with every valve closed began
+    unexpected_since: Option<DateTime<Utc>>,
+
+    tripped: bool,
+
+    alarms: Option<Def<AlarmHandler>>,
+
+    /// Master valve
+    target: Option<WriteTarget>,
+}
+
+impl LeakDetector {
+    /// Constructor for [`LeakDetector`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of action. Also used as the name of raised alarms.
+    /// - `threshold`: Flow above which water is considered to be flowing
+    /// - `valves`: Valves which are expected to be open whenever water flows. Does not include the
+    ///   master valve.
+    pub fn new<N>(name: N, threshold: f32, valves: Vec<Def<Output>>) -> Self
+    where
+        N: Into<String>
+    {
+        Self {
+            name: name.into(),
+            threshold,
+            valves,
+            delay: Duration::zero(),
+            max_duration: None,
+            flowing_since: None,
+            unexpected_since: None,
+            tripped: false,
+            alarms: None,
+            target: None,
+        }
+    }
+
+    /// Builder method to set time that flow with every valve closed must persist
+    pub fn set_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Builder method to limit duration of continuous flow, even when valves are open
+    pub fn set_max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration = Some(duration);
+        self
+    }
+
+    /// Builder method to set alarm handler used to raise alarms
+    pub fn set_alarms(mut self, alarms: Def<AlarmHandler>) -> Self {
+        self.alarms = Some(alarms);
+        self
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped
+    }
+
+    /// Check if every valve is commanded closed
+    pub fn valves_closed(&self) -> bool {
+        self.valves.iter().all(|valve| match valve.try_lock() {
+            Ok(valve) => !valve.state().is_some_and(is_on),
+            Err(_) => false,
+        })
+    }
+
+    /// Close master valve and raise alarm
+    fn trip(&mut self, message: String, data: &IOEvent) {
+        self.tripped = true;
+        self.notify(&format!("{}: {}", self.name, message));
+
+        if self.target.is_some() {
+            self.write_correlated(RawValue::Binary(false), data.correlation);
+        }
+        if let Some(alarms) = &self.alarms {
+            let alarm = Alarm::new(self.name.clone(), AlarmSeverity::Critical, message, data.timestamp)
+                .set_value(data.value);
+            alarms.try_lock().unwrap().raise_alarm(alarm);
+        }
+    }
+}
+
+impl Action for LeakDetector {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Evaluate flow against valve state
+    ///
+    /// Non-numeric, non-finite, and [`EventQuality::Bad`] readings are ignored.
+    fn evaluate(&mut self, data: &IOEvent) {
+        let flow = match data.value.finite_float() {
+            Some(flow) if data.quality != EventQuality::Bad => flow,
+            _ => return,
+        };
+
+        if flow <= self.threshold {
+            self.flowing_since = None;
+            self.unexpected_since = None;
+            return;
+        }
+        let flowing_since = *self.flowing_since.get_or_insert(data.timestamp);
+        if self.valves_closed() {
+            self.unexpected_since.get_or_insert(data.timestamp);
+        } else {
+            self.unexpected_since = None;
+        }
+
+        if self.tripped {
+            return;
+        }
+        if self.unexpected_since.is_some_and(|since| data.timestamp - since >= self.delay) {
+            self.trip(format!("Flow of {} while all valves are closed", data.value), data);
+        } else if let Some(limit) = self.max_duration.filter(|limit| data.timestamp - flowing_since > *limit) {
+            self.trip(format!("Continuous flow for longer than {} minutes", limit.num_minutes()), data);
+        }
+    }
+
+    fn set_output<T>(mut self, target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized,
+    {
+        self.target = Some(target.into());
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        self.target.clone()
+    }
+
+    fn parameter(&self, name: &str) -> Option<RawValue> {
+        match name {
+            "threshold" => Some(RawValue::Float(self.threshold)),
+            "tripped" => Some(RawValue::Binary(self.tripped)),
+            _ => None,
+        }
+    }
+
+    fn parameter_names(&self) -> &[&'static str] {
+        &["threshold", "tripped"]
+    }
+
+    fn describe(&self) -> Vec<ParameterDescriptor> {
+        vec![
+            ParameterDescriptor::new("threshold", ParameterType::Float)
+                .set_range(0.0, None)
+                .set_value(RawValue::Float(self.threshold)),
+            ParameterDescriptor::new("tripped", ParameterType::Binary)
+                .set_value(RawValue::Binary(self.tripped)),
+        ]
+    }
+
+    /// Setting `tripped` to `false` resets detector. Detector cannot be tripped manually.
+    fn set_parameter(&mut self, name: &str, value: RawValue) -> Result<(), ErrorType> {
+        let invalid = || ActionError::InvalidParameter { action: self.name.clone(), parameter: name.to_string() };
+        match (name, value) {
+            ("threshold", value) => {
+                self.threshold = value.finite_float().filter(|threshold| *threshold >= 0.0).ok_or_else(invalid)?;
+            }
+            ("tripped", RawValue::Binary(false)) => {
+                self.tripped = false;
+                if let Some(alarms) = &self.alarms {
+                    alarms.try_lock().unwrap().clear(&self.name);
+                }
+            }
+            ("tripped", _) => return Err(Box::new(invalid())),
+            _ => return Err(Box::new(ActionError::UnknownParameter {
+                action: self.name.clone(),
+                parameter: name.to_string(),
+            })),
+        }
+        Ok(())
+    }
+
+    /// Master valve is kept closed
+    fn hold(&mut self, _policy: HoldPolicy) {}
+
+    fn state(&self) -> Option<Value> {
+        serde_json::to_value(LeakState { tripped: self.tripped }).ok()
+    }
+
+    fn restore_state(&mut self, state: &Value) -> Result<(), ErrorType> {
+        let state: LeakState = serde_json::from_value(state.clone())
+            .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+        self.tripped = state.tripped;
+        Ok(())
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::action::{Action, IOCommand};
+    use crate::action::actions::LeakDetector;
+    use crate::alarm::{AlarmHandler, AlarmSeverity};
+    use crate::helpers::Def;
+    use crate::io::{Device, DeviceGetters, IOEvent, Output, RawValue};
+
+    fn valve() -> Def<Output> {
+        Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .into_deferred()
+    }
+
+    #[test]
+    fn unexpected_flow() {
+        let (zone, master) = (valve(), valve());
+        let alarms = Def::new(AlarmHandler::default());
+        let mut detector = LeakDetector::new("leak", 0.5, vec![zone.clone()])
+            .set_delay(Duration::seconds(30))
+            .set_alarms(alarms.clone())
+            .set_output(master.clone());
+        let start = Utc::now();
+        let flow = |seconds, value| IOEvent::with_timestamp(start + Duration::seconds(seconds), RawValue::Float(value));
+
+        // flow with valve open, and noise with valve closed
+        zone.try_lock().unwrap().write(RawValue::Binary(true)).unwrap();
+        detector.evaluate(&flow(0, 5.0));
+        zone.try_lock().unwrap().write(RawValue::Binary(false)).unwrap();
+        detector.evaluate(&flow(10, 0.2));
+        assert!(!detector.is_tripped());
+
+        // draining is ignored until delay has elapsed
+        detector.evaluate(&flow(20, 1.0));
+        detector.evaluate(&flow(40, 1.0));
+        assert!(!detector.is_tripped());
+        detector.evaluate(&flow(50, 1.0));
+        assert!(detector.is_tripped());
+        assert_eq!(Some(RawValue::Binary(false)), *master.try_lock().unwrap().state());
+        assert_eq!(AlarmSeverity::Critical, alarms.try_lock().unwrap().get("leak").unwrap().severity());
+
+        // trip persists across restarts, and reset clears alarm
+        let mut restarted = LeakDetector::new("leak", 0.5, vec![zone.clone()]).set_alarms(alarms.clone());
+        restarted.restore_state(&detector.state().unwrap()).unwrap();
+        assert!(restarted.is_tripped());
+        assert!(restarted.set_parameter("tripped", RawValue::Binary(true)).is_err());
+        restarted.set_parameter("tripped", RawValue::Binary(false)).unwrap();
+        assert!(alarms.try_lock().unwrap().get("leak").is_none());
+    }
+
+    #[test]
+    fn max_duration() {
+        let (zone, master) = (valve(), valve());
+        zone.try_lock().unwrap().write(RawValue::Binary(true)).unwrap();
+        master.try_lock().unwrap().write(RawValue::Binary(true)).unwrap();
+        let mut detector = LeakDetector::new("leak", 0.5, vec![zone])
+            .set_max_duration(Duration::hours(1))
+            .set_output(master.clone());
+        let start = Utc::now();
+        let flow = |minutes, value| IOEvent::with_timestamp(start + Duration::minutes(minutes), RawValue::Float(value));
+
+        detector.evaluate(&flow(0, 5.0));
+        detector.evaluate(&flow(50, 5.0));
+        // flow stopped, so duration restarts
+        detector.evaluate(&flow(55, 0.0));
+        detector.evaluate(&flow(60, 5.0));
+        detector.evaluate(&flow(110, 5.0));
+        assert!(!detector.is_tripped());
+
+        detector.evaluate(&flow(121, 5.0));
+        assert!(detector.is_tripped());
+        assert_eq!(Some(RawValue::Binary(false)), *master.try_lock().unwrap().state());
+    }
+}
@@ -0,0 +1,255 @@
+use chrono::NaiveTime;
+
+use crate::action::{Action, BoxedAction, Trigger, WriteTarget};
+use crate::errors::{ActionError, ErrorType};
+use crate::io::{IOEvent, RawValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Daily time window in UTC during which energy is cheap
+///
+/// Windows where `end` is before `start` wrap around midnight.
+pub struct TariffWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TariffWindow {
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    /// Check if time of day falls within window
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Defers non-urgent actuation to cheap-tariff windows
+///
+/// Like [`crate::action::actions::Threshold`], output is actuated while the threshold is exceeded.
+/// However, outside of the configured [`TariffWindow`]s, demand is deferred until the next window
+/// begins. A hard limit takes priority over tariffs: once exceeded, output is actuated immediately.
+///
+/// Time of day is taken from the timestamp of incoming readings. Output is only written when its
+/// commanded state changes.
+///
+/// # Example
+///
+/// Top up a reservoir below 80% overnight, but never let it drop below 20%:
+///
+/// ```
+/// use chrono::NaiveTime;
+/// use sensd::action::{Action, IOCommand, Trigger};
+/// use sensd::action::actions::{LoadShift, TariffWindow};
+/// use sensd::io::{Device, Output, RawValue};
+///
+/// let pump = Output::default()
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .into_deferred();
+///
+/// let night = TariffWindow::new(
+///     NaiveTime::from_hms_opt(23, 0, 0).unwrap(),
+///     NaiveTime::from_hms_opt(6, 0, 0).unwrap());
+///
+/// let action = LoadShift::new("top-up", RawValue::Float(80.0), Trigger::LT)
+///     .add_window(night)
+///     .set_hard_limit(RawValue::Float(20.0))
+///     .set_output(pump);
+/// ```
+pub struct LoadShift {
+    name: String,
+    threshold: RawValue,
+    trigger: Trigger,
+
+    /// Value at which actuation is no longer deferred
+    hard_limit: Option<RawValue>,
+    windows: Vec<TariffWindow>,
+
+    /// Most recently commanded state
+    actuated: Option<bool>,
+    /// Demand exists but is waiting for a tariff window
+    deferred: bool,
+
+    target: Option<WriteTarget>,
+}
+
+impl LoadShift {
+    /// Constructor for [`LoadShift`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of action
+    /// - `threshold`: Value at which there is non-urgent demand for actuation
+    /// - `trigger`: Relationship between readings and both `threshold` and hard limit
+    ///
+    /// # Returns
+    ///
+    /// [`LoadShift`] without windows. Demand is deferred indefinitely until a window is added.
+    pub fn new<N>(name: N, threshold: RawValue, trigger: Trigger) -> Self
+    where
+        N: Into<String>
+    {
+        Self {
+            name: name.into(),
+            threshold,
+            trigger,
+            hard_limit: None,
+            windows: Vec::new(),
+            actuated: None,
+            deferred: false,
+            target: None,
+        }
+    }
+
+    /// Builder method to add a cheap-tariff window
+    pub fn add_window(mut self, window: TariffWindow) -> Self {
+        self.windows.push(window);
+        self
+    }
+
+    /// Builder method to set hard constraint which overrides tariff windows
+    ///
+    /// # Parameters
+    ///
+    /// - `limit`: Value at which output is actuated regardless of time (ie: minimum level)
+    pub fn set_hard_limit(mut self, limit: RawValue) -> Self {
+        self.hard_limit = Some(limit);
+        self
+    }
+
+    /// Check if demand currently exists but is waiting for a tariff window
+    pub fn is_deferred(&self) -> bool {
+        self.deferred
+    }
+
+    fn in_window(&self, time: NaiveTime) -> bool {
+        self.windows.iter().any(|window| window.contains(time))
+    }
+}
+
+impl Action for LoadShift {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn evaluate(&mut self, data: &IOEvent) {
+        let demand = self.trigger.exceeded(data.value, self.threshold);
+        let urgent = self.hard_limit
+            .map(|limit| self.trigger.exceeded(data.value, limit))
+            .unwrap_or(false);
+        let cheap = self.in_window(data.timestamp.time());
+
+        let actuate = urgent || (demand && cheap);
+        let deferred = demand && !actuate;
+        if deferred && !self.deferred {
+            self.notify(&format!("{} deferred until next tariff window", self.name));
+        }
+        self.deferred = deferred;
+
+        if self.target.is_some() && self.actuated != Some(actuate) {
+            self.write_correlated(RawValue::Binary(actuate), data.correlation);
+        }
+        self.actuated = Some(actuate);
+    }
+
+    fn set_output<T>(mut self, target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized,
+    {
+        self.target = Some(target.into());
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        self.target.clone()
+    }
+
+    /// Parameters are `threshold` and `hard_limit`
+    fn parameter(&self, name: &str) -> Option<RawValue> {
+        match name {
+            "threshold" => Some(self.threshold),
+            "hard_limit" => self.hard_limit,
+            _ => None,
+        }
+    }
+
+    fn parameter_names(&self) -> &[&'static str] {
+        &["threshold", "hard_limit"]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: RawValue) -> Result<(), ErrorType> {
+        match name {
+            "threshold" => self.threshold = value,
+            "hard_limit" => self.hard_limit = Some(value),
+            _ => return Err(Box::new(ActionError::UnknownParameter {
+                action: self.name.clone(),
+                parameter: name.to_string(),
+            })),
+        }
+        Ok(())
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveTime, TimeZone, Utc};
+
+    use crate::action::{Action, IOCommand, Trigger};
+    use crate::action::actions::{LoadShift, TariffWindow};
+    use crate::io::{Device, DeviceGetters, IOEvent, Output, RawValue};
+
+    #[test]
+    fn window() {
+        let at = |hour| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+        let night = TariffWindow::new(at(23), at(6));
+        assert!(night.contains(at(0)));
+        assert!(night.contains(at(23)));
+        assert!(!night.contains(at(6)));
+        assert!(!night.contains(at(12)));
+    }
+
+    #[test]
+    fn defers() {
+        let output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .into_deferred();
+        let mut action = LoadShift::new("", RawValue::Float(80.0), Trigger::LT)
+            .add_window(TariffWindow::new(
+                NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(6, 0, 0).unwrap()))
+            .set_hard_limit(RawValue::Float(20.0))
+            .set_output(output.clone());
+        let state = || *output.try_lock().unwrap().state();
+        let read = |hour, value| IOEvent::with_timestamp(
+            Utc.with_ymd_and_hms(2023, 1, 1, hour, 0, 0).unwrap(), RawValue::Float(value));
+
+        // demand outside of window is deferred
+        action.evaluate(&read(12, 50.0));
+        assert!(action.is_deferred());
+        assert_eq!(Some(RawValue::Binary(false)), state());
+
+        // hard limit overrides tariff
+        action.evaluate(&read(13, 10.0));
+        assert!(!action.is_deferred());
+        assert_eq!(Some(RawValue::Binary(true)), state());
+
+        action.evaluate(&read(14, 50.0));
+        assert_eq!(Some(RawValue::Binary(false)), state());
+
+        // demand is met within window
+        action.evaluate(&read(1, 50.0));
+        assert_eq!(Some(RawValue::Binary(true)), state());
+
+        action.evaluate(&read(2, 90.0));
+        assert_eq!(Some(RawValue::Binary(false)), state());
+    }
+}
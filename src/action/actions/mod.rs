@@ -1,5 +1,19 @@
+mod anomaly;
+mod compare;
+mod forecast;
+mod leak;
+mod load_shift;
+mod normalized;
+mod photoperiod;
 mod pid;
 mod threshold;
 
+pub use anomaly::{Anomaly, AnomalyMethod};
+pub use compare::{Comparison, ComparisonReport, ParameterSet, SetMetrics};
+pub use forecast::{Forecast, ForecastModel};
+pub use leak::LeakDetector;
+pub use load_shift::{LoadShift, TariffWindow};
+pub use normalized::Normalized;
+pub use photoperiod::Photoperiod;
 pub use self::pid::PID;
 pub use threshold::Threshold;
@@ -0,0 +1,170 @@
+use serde_json::Value;
+
+use crate::action::{Action, BoxedAction, HoldPolicy, ParameterDescriptor, WriteTarget};
+use crate::errors::ErrorType;
+use crate::io::{IOEvent, RawValue, Span};
+use crate::storage::Log;
+
+/// Adapter which passes readings to an action as a percent of range
+///
+/// Wrapping an action in [`Normalized`] allows it to be configured in percent terms (ie: a
+/// [`crate::action::actions::Threshold`] at 80%, or a [`crate::action::actions::PID`] setpoint of
+/// 50%), so that the same configuration may be reused for sensors with different raw scales. Only
+/// the span differs between sensors, and is usually taken from the source input (see
+/// [`crate::io::Input::set_span()`]).
+///
+/// Numeric readings are converted by [`Span::normalize()`]. Other readings are passed unchanged.
+/// Seeded history given to [`Action::on_attach()`] is not converted. All other methods of [`Action`]
+/// are delegated to the wrapped action.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::{Action, IOCommand, Trigger, WriteTarget};
+/// use sensd::action::actions::{Normalized, Threshold};
+/// use sensd::io::{Device, Input, IOKind, RawValue, Span};
+/// use sensd::storage::Variables;
+///
+/// let variables = Variables::default();
+/// let high = Threshold::new("high", RawValue::Float(80.0), Trigger::GT)
+///     .set_output(WriteTarget::variable(variables.clone(), "high"));
+///
+/// // level sensor reporting millimetres
+/// let mut input = Input::new("level", 0, IOKind::Unassigned)
+///     .set_command(IOCommand::Input(|| RawValue::Float(1700.0)))
+///     .set_span(Span::new(0.0, 2000.0))
+///     .init_publisher();
+/// let action = Normalized::new(high, input.span().unwrap());
+/// input.publisher_mut().as_mut().unwrap().subscribe(action.into_boxed());
+///
+/// input.read().unwrap();
+/// assert_eq!(Some(RawValue::Binary(true)), variables.get("high"));
+/// ```
+pub struct Normalized<A: Action> {
+    inner: A,
+    span: Span,
+}
+
+impl<A: Action> Normalized<A> {
+    /// Constructor for [`Normalized`]
+    ///
+    /// # Parameters
+    ///
+    /// - `inner`: Action configured in percent terms. Its name and write target are used.
+    /// - `span`: Range of source readings
+    pub fn new(inner: A, span: Span) -> Self {
+        Self { inner, span }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+}
+
+impl<A: Action + 'static> Action for Normalized<A> {
+    fn name(&self) -> &String {
+        self.inner.name()
+    }
+
+    /// Evaluate wrapped action with reading converted to percent of span
+    fn evaluate(&mut self, data: &IOEvent) {
+        match self.span.normalize(data.value) {
+            Some(percent) => {
+                let mut event = data.clone();
+                event.value = percent;
+                self.inner.evaluate(&event);
+            }
+            None => self.inner.evaluate(data),
+        }
+    }
+
+    fn set_output<T>(mut self, target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized,
+    {
+        self.inner = self.inner.set_output(target);
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        self.inner.target()
+    }
+
+    fn parameter(&self, name: &str) -> Option<RawValue> {
+        self.inner.parameter(name)
+    }
+
+    fn parameter_names(&self) -> &[&'static str] {
+        self.inner.parameter_names()
+    }
+
+    fn describe(&self) -> Vec<ParameterDescriptor> {
+        self.inner.describe()
+    }
+
+    fn set_parameter(&mut self, name: &str, value: RawValue) -> Result<(), ErrorType> {
+        self.inner.set_parameter(name, value)
+    }
+
+    fn hold(&mut self, policy: HoldPolicy) {
+        self.inner.hold(policy)
+    }
+
+    fn release(&mut self) {
+        self.inner.release()
+    }
+
+    fn on_attach(&mut self, log: &Log) {
+        self.inner.on_attach(log)
+    }
+
+    fn on_pause(&mut self) {
+        self.inner.on_pause()
+    }
+
+    fn on_shutdown(&mut self) {
+        self.inner.on_shutdown()
+    }
+
+    fn state(&self) -> Option<Value> {
+        self.inner.state()
+    }
+
+    fn restore_state(&mut self, state: &Value) -> Result<(), ErrorType> {
+        self.inner.restore_state(state)
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::{Action, Trigger, WriteTarget};
+    use crate::action::actions::{Normalized, Threshold};
+    use crate::io::{IOEvent, RawValue, Span};
+    use crate::storage::Variables;
+
+    #[test]
+    /// Assert that one configuration is reused across sensors of different scales
+    fn shared_configuration() {
+        let variables = Variables::default();
+        let threshold = |name: &str| Threshold::new(name, RawValue::Float(50.0), Trigger::GT)
+            .set_output(WriteTarget::variable(variables.clone(), name));
+
+        let mut litres = Normalized::new(threshold("litres"), Span::new(0.0, 400.0));
+        let mut inches = Normalized::new(threshold("inches"), Span::new(0.0, 12.0));
+        litres.evaluate(&IOEvent::new(RawValue::Float(250.0)));
+        inches.evaluate(&IOEvent::new(RawValue::Float(5.0)));
+
+        assert_eq!(Some(RawValue::Binary(true)), variables.get("litres"));
+        assert_eq!(Some(RawValue::Binary(false)), variables.get("inches"));
+        assert_eq!(Some(RawValue::Float(50.0)), litres.parameter("threshold"));
+    }
+}
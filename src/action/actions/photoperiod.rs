@@ -0,0 +1,142 @@
+use chrono::NaiveTime;
+
+use crate::action::actions::TariffWindow;
+use crate::action::{Action, BoxedAction, Trigger, WriteTarget};
+use crate::errors::{ActionError, ErrorType};
+use crate::io::{IOEvent, RawValue};
+
+/// Switches lights on for a fixed daily period
+///
+/// Lights are on from `on` until `off` in UTC. Periods where `off` is before `on` wrap around
+/// midnight. Time of day is taken from the timestamp of incoming readings, therefore the action
+/// should subscribe to an input which is polled regularly (ie: a light sensor).
+///
+/// With [`Photoperiod::set_supplement()`], lights are only switched on during the period while
+/// readings are below a light level, so that artificial light only supplements daylight.
+///
+/// Output is only written when its commanded state changes.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{NaiveTime, TimeZone, Utc};
+/// use sensd::action::{Action, IOCommand};
+/// use sensd::action::actions::Photoperiod;
+/// use sensd::io::{Device, DeviceGetters, IOEvent, Output, RawValue};
+///
+/// let lights = Output::default()
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .into_deferred();
+/// let mut action = Photoperiod::new(
+///         "18/6",
+///         NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+///         NaiveTime::from_hms_opt(0, 0, 0).unwrap())
+///     .set_output(lights.clone());
+///
+/// let noon = Utc.with_ymd_and_hms(2023, 6, 1, 12, 0, 0).unwrap();
+/// action.evaluate(&IOEvent::with_timestamp(noon, RawValue::Float(0.0)));
+/// assert_eq!(Some(RawValue::Binary(true)), *lights.try_lock().unwrap().state());
+/// ```
+pub struct Photoperiod {
+    name: String,
+    period: TariffWindow,
+
+    /// Light level above which lights are not needed
+    supplement: Option<RawValue>,
+
+    /// Most recently commanded state
+    lit: Option<bool>,
+
+    target: Option<WriteTarget>,
+}
+
+impl Photoperiod {
+    /// Constructor for [`Photoperiod`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of action
+    /// - `on`: Time of day in UTC that period begins
+    /// - `off`: Time of day in UTC that period ends
+    pub fn new<N>(name: N, on: NaiveTime, off: NaiveTime) -> Self
+    where
+        N: Into<String>
+    {
+        Self {
+            name: name.into(),
+            period: TariffWindow::new(on, off),
+            supplement: None,
+            lit: None,
+            target: None,
+        }
+    }
+
+    /// Builder method to only switch lights on while readings are below `level`
+    pub fn set_supplement(mut self, level: RawValue) -> Self {
+        self.supplement = Some(level);
+        self
+    }
+
+    /// Check if time of day falls within period
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        self.period.contains(time)
+    }
+}
+
+impl Action for Photoperiod {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn evaluate(&mut self, data: &IOEvent) {
+        let dark = self.supplement
+            .map(|level| !Trigger::GTE.exceeded(data.value, level))
+            .unwrap_or(true);
+        let lit = dark && self.contains(data.timestamp.time());
+
+        if self.target.is_some() && self.lit != Some(lit) {
+            self.write_correlated(RawValue::Binary(lit), data.correlation);
+        }
+        self.lit = Some(lit);
+    }
+
+    fn set_output<T>(mut self, target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized,
+    {
+        self.target = Some(target.into());
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        self.target.clone()
+    }
+
+    /// The only parameter is `supplement`
+    fn parameter(&self, name: &str) -> Option<RawValue> {
+        match name {
+            "supplement" => self.supplement,
+            _ => None,
+        }
+    }
+
+    fn parameter_names(&self) -> &[&'static str] {
+        &["supplement"]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: RawValue) -> Result<(), ErrorType> {
+        match name {
+            "supplement" => self.supplement = Some(value),
+            _ => return Err(Box::new(ActionError::UnknownParameter {
+                action: self.name.clone(),
+                parameter: name.to_string(),
+            })),
+        }
+        Ok(())
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
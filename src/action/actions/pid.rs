@@ -1,17 +1,26 @@
 use chrono::Duration;
 use ext_pid::Pid;
-use crate::action::{Action, BoxedAction, SchedRoutineHandler};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::action::{Action, BoxedAction, ParameterDescriptor, ParameterType, SchedRoutineHandler, SetpointSource, WriteTarget};
+use crate::console;
+use crate::errors::{ActionError, ErrorType, FilesystemError};
 use crate::helpers::Def;
-use crate::io::{Output, IOEvent, RawValue};
+use crate::io::{is_on, DeviceGetters, IOEvent, RawValue};
+use crate::storage::Log;
 
 /// Action implementing a PID controller to control a single output
 ///
-/// This is a wrapper for [`Pid`] that conforms to Rust API guidelines and attaches an [`Output`].
+/// This is a wrapper for [`Pid`] that conforms to Rust API guidelines and attaches an [`crate::io::Output`].
 /// Output should be a device which can be controlled in a binary fashion (eg: pump, valve, etc).
 ///
+/// When the write target is not an output (see [`WriteTarget`]), the controller output is written
+/// as a float on every evaluation instead. This allows cascade control by writing to the setpoint of
+/// another controller.
+///
 /// # Example
 ///
-/// Using the [`PID::new()`] constructor, [`Output`] and [`SchedRoutineHandler`]
+/// Using the [`PID::new()`] constructor, [`crate::io::Output`] and [`SchedRoutineHandler`]
 /// have to be manually associated:
 /// ```
 /// use sensd::action::{Action, SchedRoutineHandler};
@@ -96,7 +105,12 @@ pub struct PID {
     name: String,
     pid: Pid<f32>,
 
-    output: Option<Def<Output>>,
+    /// Most recent controller output
+    last_output: Option<f32>,
+
+    setpoint_source: SetpointSource,
+
+    target: Option<WriteTarget>,
     handler: Option<Def<SchedRoutineHandler>>,
 }
 
@@ -137,7 +151,9 @@ impl PID {
             name: name.into(),
             pid: Pid::new(setpoint.into(),
                           output_limit.into()),
-            output: None,
+            last_output: None,
+            setpoint_source: SetpointSource::Fixed,
+            target: None,
             handler: None,
         }
     }
@@ -364,6 +380,28 @@ impl PID {
         self
     }
 
+    /// Builder method to set origin of setpoint
+    ///
+    /// Setpoint is updated from `source` before every evaluation. Setpoint passed to
+    /// [`PID::new()`] is used until `source` yields a value.
+    ///
+    /// # Parameters
+    ///
+    /// - `source`: [`SetpointSource`] to use
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to enable method chaining
+    pub fn set_setpoint_source(mut self, source: SetpointSource) -> Self {
+        self.setpoint_source = source;
+        self
+    }
+
+    /// Getter for origin of setpoint
+    pub fn setpoint_source(&self) -> &SetpointSource {
+        &self.setpoint_source
+    }
+
     /// Getter for output limit
     ///
     /// # Returns
@@ -439,6 +477,7 @@ impl PID {
         let measurement = measurement.into();
         let output = self.pid.next_control_output(
             measurement.into()).output;
+        self.last_output = Some(output);
 
 
         Duration::seconds(output.trunc() as i64) +
@@ -480,6 +519,33 @@ impl PID {
     pub fn has_handler(&self) -> bool {
         self.handler.is_some()
     }
+
+    /// Getter for most recent controller output
+    pub fn last_output(&self) -> Option<f32> {
+        self.last_output
+    }
+
+    /// Write `false` to output if it was left on by [`Action::evaluate()`]
+    ///
+    /// Has no effect when write target is not an output.
+    fn switch_off(&self) {
+        let on = match self.output() {
+            Some(output) => output.try_lock().unwrap().state().map(is_on).unwrap_or(false),
+            None => false,
+        };
+        if on {
+            if let Err(e) = self.target().unwrap().write(&self.name, RawValue::Binary(false), None) {
+                console::error(format!("Could not turn off output of {}: {}", self.name, e));
+            }
+        }
+    }
+}
+
+/// Serialized runtime state of [`PID`]
+#[derive(Serialize, Deserialize)]
+struct PIDState {
+    pid: Pid<f32>,
+    last_output: Option<f32>,
 }
 
 impl Action for PID {
@@ -488,25 +554,37 @@ impl Action for PID {
     }
 
     fn evaluate(&mut self, data: &IOEvent) {
-        let measurement = data.value;
-        if let RawValue::Float(value) = measurement {
+        if let Some(setpoint) = self.setpoint_source.resolve() {
+            self.set_setpoint(setpoint);
+        }
+
+        if let Some(RawValue::Float(value)) = data.value.to_finite() {
 
             let duration =
                 self.calculate(value);
 
+            // targets other than outputs receive the controller output directly
+            if self.target.is_some() && self.output().is_none() {
+                let output = self.last_output.unwrap();
+                self.write_correlated(RawValue::Float(output), data.correlation);
+                return;
+            }
+
             if duration > Duration::milliseconds(0) {
                 if self.handler.is_none() {
                     panic!("Handler has not been set!");
                 }
 
-                self.write(RawValue::Binary(true));
+                self.write_correlated(RawValue::Binary(true), data.correlation);
 
-                let output = self.output.as_ref()
-                    .expect("Output has not been set!")
-                    .try_lock().unwrap();
-                let routine = output.create_routine(
+                let output = self.output()
+                    .expect("Output has not been set!");
+                let routine = output.try_lock().unwrap().create_routine(
                     RawValue::Binary(false),
-                    duration);
+                    duration)
+                    .set_correlation(data.correlation)
+                    .set_output(&output)
+                    .set_owner(self.name.clone());
                 self.handler.as_ref().unwrap().try_lock().unwrap().push(routine);
             }
         }
@@ -537,16 +615,124 @@ impl Action for PID {
     ///
     /// assert!(action.output().is_some());
     /// ```
-    fn set_output(mut self, device: Def<Output>) -> Self
+    fn set_output<T>(mut self, target: T) -> Self
     where
+        T: Into<WriteTarget>,
         Self: Sized,
     {
-        self.output = Some(device);
+        self.target = Some(target.into());
         self
     }
 
-    fn output(&self) -> Option<Def<Output>> {
-        self.output.clone()
+    fn target(&self) -> Option<WriteTarget> {
+        self.target.clone()
+    }
+
+    /// Parameters are `setpoint`, `output_limit`, and the gains `kp`, `ki`, and `kd`
+    ///
+    /// Limits of gains are not exposed as parameters, and are retained when gains are set.
+    fn parameter(&self, name: &str) -> Option<RawValue> {
+        match name {
+            "setpoint" => Some(RawValue::Float(self.setpoint())),
+            "output_limit" => Some(RawValue::Float(self.output_limit())),
+            "kp" => Some(RawValue::Float(self.p())),
+            "ki" => Some(RawValue::Float(self.i())),
+            "kd" => Some(RawValue::Float(self.d())),
+            _ => None,
+        }
+    }
+
+    fn parameter_names(&self) -> &[&'static str] {
+        &["setpoint", "output_limit", "kp", "ki", "kd"]
+    }
+
+    fn describe(&self) -> Vec<ParameterDescriptor> {
+        vec![
+            ParameterDescriptor::new("setpoint", ParameterType::Float)
+                .set_value(RawValue::Float(self.setpoint())),
+            ParameterDescriptor::new("output_limit", ParameterType::Float)
+                .set_range(0.0, None)
+                .set_value(RawValue::Float(self.output_limit())),
+            ParameterDescriptor::new("kp", ParameterType::Float)
+                .set_value(RawValue::Float(self.p())),
+            ParameterDescriptor::new("ki", ParameterType::Float)
+                .set_value(RawValue::Float(self.i())),
+            ParameterDescriptor::new("kd", ParameterType::Float)
+                .set_value(RawValue::Float(self.d())),
+        ]
+    }
+
+    /// Values outside of the bounds given by [`PID::describe()`] are invalid
+    fn set_parameter(&mut self, name: &str, value: RawValue) -> Result<(), ErrorType> {
+        let invalid = || ActionError::InvalidParameter { action: self.name.clone(), parameter: name.to_string() };
+        if self.describe().iter().any(|parameter| parameter.name == name && !parameter.accepts(value)) {
+            return Err(Box::new(invalid()));
+        }
+        match name {
+            "setpoint" => { self.set_setpoint(value.as_float().ok_or_else(invalid)?); },
+            "output_limit" => { self.set_output_limit(value.as_float().ok_or_else(invalid)?); },
+            "kp" => { self.set_p_ref(value.as_float().ok_or_else(invalid)?, self.p_limit()); },
+            "ki" => { self.set_i_ref(value.as_float().ok_or_else(invalid)?, self.i_limit()); },
+            "kd" => { self.set_d_ref(value.as_float().ok_or_else(invalid)?, self.d_limit()); },
+            _ => return Err(Box::new(ActionError::UnknownParameter {
+                action: self.name.clone(),
+                parameter: name.to_string(),
+            })),
+        }
+        Ok(())
+    }
+
+    /// Reset integral term so that error accumulated before hold does not cause a surge
+    fn release(&mut self) {
+        self.pid.reset_integral_term();
+    }
+
+    /// Seed last measurement with most recent reading so that derivative term does not kick on the
+    /// first evaluation
+    ///
+    /// Has no effect once the controller has been evaluated or its state has been restored.
+    fn on_attach(&mut self, log: &Log) {
+        if self.last_output.is_some() {
+            return;
+        }
+        if let Some(value) = log.iter().rev().find_map(|(_, event)| event.value.finite_float()) {
+            self.calculate(value);
+            self.pid.reset_integral_term();
+        }
+    }
+
+    /// Turn off output, since the routine that would have done so is not executed while paused
+    fn on_pause(&mut self) {
+        self.switch_off();
+    }
+
+    /// Turn off output, since the routine that would have done so is never executed
+    fn on_shutdown(&mut self) {
+        self.switch_off();
+    }
+
+    /// Integral accumulator, last measurement, and last output
+    fn state(&self) -> Option<Value> {
+        serde_json::to_value(PIDState { pid: self.pid, last_output: self.last_output }).ok()
+    }
+
+    /// Restore accumulated state
+    ///
+    /// Setpoint, gains, and limits are retained from the current configuration.
+    fn restore_state(&mut self, state: &Value) -> Result<(), ErrorType> {
+        let state: PIDState = serde_json::from_value(state.clone())
+            .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+
+        let mut pid = state.pid;
+        pid.setpoint = self.pid.setpoint;
+        pid.output_limit = self.pid.output_limit;
+        pid.p(self.pid.kp, self.pid.p_limit);
+        pid.i(self.pid.ki, self.pid.i_limit);
+        pid.d(self.pid.kd, self.pid.d_limit);
+
+        self.pid = pid;
+        self.last_output = state.last_output;
+        Ok(())
     }
 
     fn into_boxed(self) -> BoxedAction {
@@ -1,13 +1,13 @@
-use crate::action::{Action, BoxedAction};
-use crate::io::{IOEvent, Output, RawValue};
+use crate::action::{Action, BoxedAction, SetpointSource, WriteTarget};
+use crate::errors::{ActionError, ErrorType};
+use crate::io::{CorrelationId, IOEvent, Output, RawValue};
 use crate::action::trigger::Trigger;
 use crate::helpers::Def;
 
 /// Bang-bang (on-off) controller
 ///
 /// If threshold is exceeded, a notification is printed and output is actuated until next polling cycle
-/// where input value is below threshold. In the future, upper and lower thresholds will be added for
-/// finer control.
+/// where input value is below threshold.
 ///
 /// Unlike the [`crate::action::actions::PID`] subscriber, [`Threshold`] is unable create a
 /// [`crate::action::Routine`].
@@ -21,13 +21,21 @@ use crate::helpers::Def;
 /// valve that decreases fill level. Two separate [`Threshold`] could be used for controlling
 /// this system based off of input from the level sensor. Depending on polling frequency there might be
 /// some variance between threshold value and the input value when actuation stops.
-// TODO: add upper/lower threshold
+///
+/// To prevent rapid cycling of an output while input hovers around threshold, a band may be set
+/// with [`Threshold::set_hysteresis()`].
 pub struct Threshold {
     name: String,
     threshold: RawValue,
+    setpoint_source: SetpointSource,
+
+    /// Distance past threshold that input must return before output is de-actuated
+    hysteresis: f32,
+    /// Output was actuated by last evaluation
+    active: bool,
 
     trigger: Trigger,
-    output: Option<Def<Output>>,
+    target: Option<WriteTarget>,
 }
 
 impl Threshold {
@@ -66,8 +74,11 @@ impl Threshold {
         Self {
             name: name.into(),
             threshold,
+            setpoint_source: SetpointSource::Fixed,
+            hysteresis: 0.0,
+            active: false,
             trigger,
-            output: None,
+            target: None,
         }
     }
 
@@ -132,20 +143,71 @@ impl Threshold {
         self.threshold
     }
 
+    /// Builder method to set origin of threshold
+    ///
+    /// Threshold is updated from `source` as a float before every evaluation. Threshold passed to
+    /// [`Threshold::new()`] is used until `source` yields a value.
+    ///
+    /// # Parameters
+    ///
+    /// - `source`: [`SetpointSource`] to use
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to enable method chaining
+    pub fn set_setpoint_source(mut self, source: SetpointSource) -> Self {
+        self.setpoint_source = source;
+        self
+    }
+
+    /// Getter for origin of threshold
+    pub fn setpoint_source(&self) -> &SetpointSource {
+        &self.setpoint_source
+    }
+
+    /// Builder method to hold output actuated until input returns past threshold by `band`
+    ///
+    /// For example, with [`Trigger::LT`], a threshold of 20 and a band of 1, output is actuated
+    /// below 20 and de-actuated at or above 21.
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to enable method chaining
+    pub fn set_hysteresis(mut self, band: f32) -> Self {
+        self.hysteresis = band.abs();
+        self
+    }
+
+    pub fn hysteresis(&self) -> f32 {
+        self.hysteresis
+    }
+
+    /// Threshold that input must pass for output to stay actuated
+    fn release_threshold(&self) -> RawValue {
+        let band = match self.trigger {
+            Trigger::GT | Trigger::GTE => -self.hysteresis,
+            Trigger::LT | Trigger::LTE => self.hysteresis,
+        };
+        match (self.active, self.threshold.finite_float()) {
+            (true, Some(threshold)) if band != 0.0 => RawValue::Float(threshold + band),
+            _ => self.threshold,
+        }
+    }
+
     #[inline]
     /// Actuate output device without runtime validation
     ///
     /// Sends a `true` value to output device. Does not check value [`Result`] from [`Action::write()`].
-    fn on_unchecked(&self) {
-        let _ = self.write(RawValue::Binary(true));
+    fn on_unchecked(&self, correlation: Option<CorrelationId>) {
+        self.write_correlated(RawValue::Binary(true), correlation);
     }
 
     #[inline]
     /// De-actuate output device without runtime validation
     ///
     /// Sends a `false` value to output device. Does not check value [`Result`] from [`Action::write()`].
-    fn off_unchecked(&self) {
-        let _ = self.write(RawValue::Binary(false));
+    fn off_unchecked(&self, correlation: Option<CorrelationId>) {
+        self.write_correlated(RawValue::Binary(false), correlation);
     }
 }
 
@@ -159,17 +221,23 @@ impl Action for Threshold {
     #[inline]
     /// Evaluate external data
     ///
-    /// Incoming data is compared against internal threshold using [`Trigger::exceeded()`]. If
-    /// incoming data exceeds threshold, output device is actuated. Otherwise, output device is
-    /// deactivated.
+    /// Threshold is first updated from [`SetpointSource`]. Incoming data is then compared against
+    /// internal threshold using [`Trigger::exceeded()`]. If incoming data exceeds threshold, output
+    /// device is actuated. Otherwise, output device is deactivated. While actuated, threshold is
+    /// offset by hysteresis. See [`Threshold::set_hysteresis()`].
     ///
     /// # Notes
     ///
     /// - This function is inline because it is used in iterator loops
     /// - Any error returned by [`Self::write()`] is silenced.
     fn evaluate(&mut self, data: &IOEvent) {
+        if let Some(threshold) = self.setpoint_source.resolve() {
+            self.threshold = RawValue::Float(threshold);
+        }
+
         let input = data.value;
-        let exceeded = self.trigger.exceeded(input, self.threshold);
+        let exceeded = self.trigger.exceeded(input, self.release_threshold());
+        self.active = exceeded;
 
         match exceeded {
             true => {
@@ -177,9 +245,9 @@ impl Action for Threshold {
                 let msg = format!("{} {} {}", input, &self.trigger, self.threshold);
                 self.notify(msg.as_str());
 
-                self.on_unchecked();
+                self.on_unchecked(data.correlation);
             },
-            false => { self.off_unchecked() },
+            false => { self.off_unchecked(data.correlation) },
         };
     }
 
@@ -207,18 +275,44 @@ impl Action for Threshold {
     ///                 .set_output(output);
     /// assert!(action.output().is_some())
     /// ```
-    fn set_output(mut self, device: Def<Output>) -> Self
+    fn set_output<T>(mut self, target: T) -> Self
     where
+        T: Into<WriteTarget>,
         Self: Sized,
     {
-        self.output = Some(device);
+        self.target = Some(target.into());
 
         self
     }
 
     #[inline]
-    fn output(&self) -> Option<Def<Output>> {
-        self.output.clone()
+    fn target(&self) -> Option<WriteTarget> {
+        self.target.clone()
+    }
+
+    /// The only parameter is `threshold`
+    fn parameter(&self, name: &str) -> Option<RawValue> {
+        match name {
+            "threshold" => Some(self.threshold),
+            _ => None,
+        }
+    }
+
+    fn parameter_names(&self) -> &[&'static str] {
+        &["threshold"]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: RawValue) -> Result<(), ErrorType> {
+        match name {
+            "threshold" => {
+                self.threshold = value;
+                Ok(())
+            },
+            _ => Err(Box::new(ActionError::UnknownParameter {
+                action: self.name.clone(),
+                parameter: name.to_string(),
+            })),
+        }
     }
 
     #[inline]
@@ -229,9 +323,13 @@ impl Action for Threshold {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use crate::action::actions::Threshold;
-    use crate::action::Trigger;
-    use crate::io::{Device, Output, RawValue};
+    use crate::action::{Action, IOCommand, SetpointSource, Trigger};
+    use crate::helpers::Def;
+    use crate::io::{CorrelationId, Device, DeviceGetters, DeviceSetters, Input, IOEvent, Output, RawValue};
+    use crate::storage::{Chronicle, Log};
 
     #[test]
     /// Ensure that `name` can be given to `new()` constructor as `String` or `&str`
@@ -253,4 +351,65 @@ mod tests {
         let name = String::from(name);
         Threshold::with_output(name, RawValue::default(), Trigger::GT, output);
     }
-}
\ No newline at end of file
+
+    #[test]
+    /// Assert that output events share correlation id of input event
+    fn correlation() {
+        let mut output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())));
+        output.set_log(Def::new(Log::default()));
+        let output = output.into_deferred();
+
+        let mut action = Threshold::with_output("", RawValue::Float(1.0), Trigger::GT, output.clone());
+
+        let id = CorrelationId::generate();
+        action.evaluate(&IOEvent::new(RawValue::Float(2.0)).set_correlation(id));
+
+        let log = output.try_lock().unwrap().log().unwrap();
+        let log = log.try_lock().unwrap();
+        let (_, event) = log.iter().next().unwrap();
+        assert_eq!(Some(id), event.correlation);
+    }
+
+    #[test]
+    /// Assert that threshold tracks another input
+    fn setpoint_source() {
+        let room = Def::new(20.0);
+        let reading = room.clone();
+        let input = Input::default()
+            .set_command(IOCommand::InputFn(Arc::new(move || RawValue::Float(*reading.lock().unwrap()))))
+            .into_deferred();
+        let output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .into_deferred();
+        let mut action = Threshold::with_output("", RawValue::Float(0.0), Trigger::LT, output.clone())
+            .set_setpoint_source(SetpointSource::track(input.clone(), 1.0, -2.0));
+        let state = || *output.try_lock().unwrap().state();
+
+        input.try_lock().unwrap().read().unwrap();
+        action.evaluate(&IOEvent::new(RawValue::Float(17.0)));
+        assert_eq!(RawValue::Float(18.0), action.threshold());
+        assert_eq!(Some(RawValue::Binary(true)), state());
+
+        *room.lock().unwrap() = 16.0;
+        input.try_lock().unwrap().read().unwrap();
+        action.evaluate(&IOEvent::new(RawValue::Float(17.0)));
+        assert_eq!(Some(RawValue::Binary(false)), state());
+    }
+
+    #[test]
+    /// Assert that output stays actuated until input returns past hysteresis band
+    fn hysteresis() {
+        let output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .into_deferred();
+        let mut action = Threshold::with_output("", RawValue::Float(20.0), Trigger::LT, output.clone())
+            .set_hysteresis(-1.0);
+        let state = || *output.try_lock().unwrap().state();
+
+        for (value, expected) in [(20.5, false), (19.5, true), (20.5, true), (21.0, false), (20.5, false)] {
+            action.evaluate(&IOEvent::new(RawValue::Float(value)));
+            assert_eq!(Some(RawValue::Binary(expected)), state(), "value {}", value);
+        }
+    }
+}
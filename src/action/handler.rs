@@ -1,14 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
 use crate::action::Routine;
+use crate::helpers::now;
+use crate::io::{CorrelationId, RawValue};
 
 #[allow(unused_imports)]
 use crate::storage::Group;
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Execution of a single [`Routine`] as decided by [`SchedRoutineHandler`]
+pub struct ScheduleRecord {
+    /// Time that routine was scheduled for
+    pub scheduled: DateTime<Utc>,
+    /// Time that scheduler executed routine
+    pub executed: DateTime<Utc>,
+    /// Value passed to output
+    pub value: RawValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation: Option<CorrelationId>,
+}
+
 #[derive(Default)]
 /// Wrapper for a collection of scheduled [`Routine`] instances that handles real-time execution
 /// Self-contained collection of scheduled [`Routine`]s for a single [`crate::action::Publisher`].
 ///
 /// This struct acts as a facade for an arbitrary collection (in this case, [`Vec`]).
-pub struct SchedRoutineHandler(Vec<Routine>);
+///
+/// # Record and Replay
+///
+/// Execution decisions can be captured with [`SchedRoutineHandler::record()`]. A recorded schedule
+/// may then be fed to [`SchedRoutineHandler::replay()`], which drives the scheduler through the same
+/// instants in virtual time. This allows actuation schedules to be asserted upon without relying on
+/// system time.
+pub struct SchedRoutineHandler {
+    routines: Vec<Routine>,
+
+    /// Execution decisions captured while recording
+    recording: Option<Vec<ScheduleRecord>>,
+}
 
 impl SchedRoutineHandler {
     /// Push a new [`Routine`] to internal collection
@@ -17,7 +47,7 @@ impl SchedRoutineHandler {
     ///
     /// - `routine`: `Routine` to add to internal collection
     pub fn push(&mut self, routine: Routine) {
-        self.0.push(routine)
+        self.routines.push(routine)
     }
 
     /// Attempt to execute scheduled routines.
@@ -30,16 +60,108 @@ impl SchedRoutineHandler {
     ///
     /// Any routines executed by [`Routine::attempt()`] are cleared from the internal container.
     pub fn attempt_routines(&mut self) {
-        let mut executed = Vec::default();
-        for (index, routine) in self.0.iter().enumerate() {
-            if routine.attempt() {
-                executed.push(index);
+        self.attempt_with(now(), Routine::attempt)
+    }
+
+    /// Attempt to execute scheduled routines relative to a given time
+    ///
+    /// Routines are attempted in the order they were pushed.
+    ///
+    /// # Parameters
+    ///
+    /// - `now`: Current time
+    ///
+    /// # See Also
+    ///
+    /// - [`Routine::attempt_at()`]
+    pub fn attempt_routines_at(&mut self, now: DateTime<Utc>) {
+        self.attempt_with(now, |routine| routine.attempt_at(now))
+    }
+
+    /// Attempt routines using `attempt` to execute each, recording executions at `now`
+    fn attempt_with<F>(&mut self, now: DateTime<Utc>, attempt: F)
+    where
+        F: Fn(&Routine) -> bool,
+    {
+        let recording = &mut self.recording;
+        // completed routines are removed
+        self.routines.retain(|routine| {
+            let executed = attempt(routine);
+            if executed {
+                if let Some(recording) = recording {
+                    recording.push(ScheduleRecord {
+                        scheduled: routine.timestamp(),
+                        executed: now,
+                        value: routine.value(),
+                        correlation: routine.correlation(),
+                    });
+                }
             }
+            !executed
+        });
+    }
+
+    /// Begin recording execution decisions
+    ///
+    /// Any previous recording is discarded.
+    pub fn record(&mut self) {
+        self.recording = Some(Vec::default());
+    }
+
+    /// Execution decisions recorded so far, or `None` if not recording
+    pub fn recording(&self) -> Option<&[ScheduleRecord]> {
+        self.recording.as_deref()
+    }
+
+    /// Stop recording and return recorded execution decisions
+    pub fn stop_recording(&mut self) -> Vec<ScheduleRecord> {
+        self.recording.take().unwrap_or_default()
+    }
+
+    /// Drive scheduler through the execution instants of a previously recorded schedule
+    ///
+    /// Scheduled routines are attempted at each distinct [`ScheduleRecord::executed`] time in
+    /// `schedule`. Comparing the returned schedule with `schedule` reveals any change in behavior.
+    ///
+    /// # Parameters
+    ///
+    /// - `schedule`: Previously recorded schedule
+    ///
+    /// # Returns
+    ///
+    /// Schedule recorded during replay
+    pub fn replay(&mut self, schedule: &[ScheduleRecord]) -> Vec<ScheduleRecord> {
+        let mut instants: Vec<DateTime<Utc>> = schedule.iter().map(|record| record.executed).collect();
+        instants.sort();
+        instants.dedup();
+
+        self.record();
+        for instant in instants {
+            self.attempt_routines_at(instant);
         }
-        // remove completed routines
-        for index in executed {
-            self.0.remove(index);
-        }
+        self.stop_recording()
+    }
+
+    /// Number of scheduled routines whose device log has been dropped
+    ///
+    /// # See Also
+    ///
+    /// - [`Routine::is_dangling()`]
+    pub fn dangling(&self) -> usize {
+        self.routines.iter().filter(|routine| routine.is_dangling()).count()
+    }
+
+    /// Discard scheduled routines whose device log has been dropped
+    ///
+    /// Discarded routines are never executed.
+    ///
+    /// # Returns
+    ///
+    /// Number of routines discarded
+    pub fn purge_dangling(&mut self) -> usize {
+        let count = self.routines.len();
+        self.routines.retain(|routine| !routine.is_dangling());
+        count - self.routines.len()
     }
 
     /// Getter function for internal collection
@@ -48,7 +170,7 @@ impl SchedRoutineHandler {
     ///
     /// Slice of [`Routine`]
     pub fn scheduled(&self) -> &[Routine] {
-        &self.0
+        &self.routines
     }
 }
 
@@ -97,8 +219,6 @@ mod tests {
     }
 
     #[test]
-    /// Sometimes this fails due to race condition mentioned below (issue #95). In that case,
-    /// running the tests again should pass.
     fn test_attempt() {
         let metadata = DeviceMetadata::default();
         let log = Def::new(Log::with_metadata(&metadata));
@@ -120,24 +240,51 @@ mod tests {
 
         let command = IOCommand::Output(|_| Ok(()));
 
-        // BUG: why does this operation fail with any value less than 31 microseconds? There seems
-        // to be a race condition.
-        let ts2 = Utc::now() + Duration::microseconds(120);
+        let ts2 = timestamp + Duration::microseconds(1);
         let value = RawValue::Binary(true);
 
         let routine = Routine::new(ts2, value, log.clone(), command);
         scheduled.push(routine);
 
-        while Utc::now() < timestamp {
-            assert_eq!(2, scheduled.scheduled().into_iter().count());
-            scheduled.attempt_routines();
-        }
-        scheduled.attempt_routines();
-        while Utc::now() < ts2 {
-            assert_eq!(1, scheduled.scheduled().into_iter().count());
-            scheduled.attempt_routines();
+        scheduled.attempt_routines_at(timestamp - Duration::microseconds(1));
+        assert_eq!(2, scheduled.scheduled().iter().count());
+
+        scheduled.attempt_routines_at(timestamp);
+        assert_eq!(1, scheduled.scheduled().iter().count());
+
+        scheduled.attempt_routines_at(ts2);
+        assert_eq!(0, scheduled.scheduled().iter().count());
+    }
+
+    #[test]
+    fn record_replay() {
+        let start = Utc::now();
+        let build = || {
+            let mut scheduled = SchedRoutineHandler::default();
+            for (offset, value) in [(3, true), (1, false), (2, true)] {
+                let routine = Routine::new(
+                    start + Duration::seconds(offset),
+                    RawValue::Binary(value),
+                    None,
+                    IOCommand::Output(|_| Ok(())));
+                scheduled.push(routine);
+            }
+            scheduled
+        };
+
+        let mut scheduled = build();
+        scheduled.record();
+        for offset in 0..5 {
+            scheduled.attempt_routines_at(start + Duration::seconds(offset));
         }
-        scheduled.attempt_routines();
-        assert_eq!(0, scheduled.scheduled().into_iter().count());
+        let recorded = scheduled.stop_recording();
+
+        assert_eq!(3, recorded.len());
+        assert_eq!(RawValue::Binary(false), recorded[0].value);
+        assert_eq!(start + Duration::seconds(1), recorded[0].executed);
+        assert_eq!(start + Duration::seconds(3), recorded[2].executed);
+        assert!(scheduled.recording().is_none());
+
+        assert_eq!(recorded, build().replay(&recorded));
     }
 }
@@ -0,0 +1,202 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::action::{Command, IOCommand};
+use crate::console;
+use crate::helpers::now;
+use crate::io::{IODirection, RawValue};
+
+/// Pattern used to drive a status LED
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeartbeatPattern {
+    /// Toggle output with full on/off cycle lasting `period`
+    Blink(Duration),
+    /// Hold output high
+    On,
+    /// Hold output low
+    Off,
+}
+
+/// Blinks a status LED while the main loop is healthy
+///
+/// A heartbeat is a common visual indicator for headless controllers: a blinking LED shows that the
+/// event loop is running and that no fault has occurred. By default, the LED toggles at 1 Hz while
+/// healthy, and is held low when a fault is reported.
+///
+/// Low-level [`IOCommand`] is called directly, so toggling does not generate [`crate::io::IOEvent`]s
+/// or flood logs.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::{Heartbeat, HeartbeatPattern, IOCommand};
+/// use sensd::storage::Group;
+///
+/// let led = IOCommand::Output(|_| Ok(()));
+/// let heartbeat = Heartbeat::new(led)
+///     .set_fault_pattern(HeartbeatPattern::On);
+///
+/// let mut group = Group::new("");
+/// group.set_heartbeat(heartbeat);
+///
+/// // called from within main event loop
+/// group.attempt_routines();
+/// ```
+pub struct Heartbeat {
+    command: IOCommand,
+
+    healthy: HeartbeatPattern,
+    faulted: HeartbeatPattern,
+
+    fault: bool,
+    /// Last value written to LED
+    state: Option<bool>,
+    last_toggle: Option<DateTime<Utc>>,
+}
+
+impl Heartbeat {
+    /// Constructor for [`Heartbeat`]
+    ///
+    /// # Parameters
+    ///
+    /// - `command`: Low-level output command for status LED
+    ///
+    /// # Panics
+    ///
+    /// If `command` is not [`IOCommand::Output`]
+    pub fn new(command: IOCommand) -> Self {
+        command.agrees(IODirection::Out)
+            .expect("Command is not output");
+
+        Self {
+            command,
+            healthy: HeartbeatPattern::Blink(Duration::seconds(1)),
+            faulted: HeartbeatPattern::Off,
+            fault: false,
+            state: None,
+            last_toggle: None,
+        }
+    }
+
+    /// Builder method to set pattern used while healthy
+    pub fn set_healthy_pattern(mut self, pattern: HeartbeatPattern) -> Self {
+        self.healthy = pattern;
+        self
+    }
+
+    /// Builder method to set pattern used during fault state
+    pub fn set_fault_pattern(mut self, pattern: HeartbeatPattern) -> Self {
+        self.faulted = pattern;
+        self
+    }
+
+    /// Enter fault state
+    pub fn fault(&mut self) {
+        self.fault = true;
+    }
+
+    /// Return to healthy state
+    pub fn clear_fault(&mut self) {
+        self.fault = false;
+    }
+
+    pub fn is_faulted(&self) -> bool {
+        self.fault
+    }
+
+    /// Current pattern as dictated by fault state
+    pub fn pattern(&self) -> HeartbeatPattern {
+        match self.fault {
+            true => self.faulted,
+            false => self.healthy,
+        }
+    }
+
+    /// Last value written to LED
+    pub fn state(&self) -> Option<bool> {
+        self.state
+    }
+
+    /// Update LED according to current pattern
+    ///
+    /// Should be called as often as possible.
+    pub fn attempt(&mut self) {
+        self.attempt_at(now())
+    }
+
+    /// Update LED relative to a given time
+    pub fn attempt_at(&mut self, now: DateTime<Utc>) {
+        match self.pattern() {
+            HeartbeatPattern::Blink(period) => {
+                // a backwards clock jump is treated as elapsed so blinking does not freeze
+                let elapsed = self.last_toggle
+                    .map(|last| now < last || now - last >= period / 2)
+                    .unwrap_or(true);
+                if elapsed {
+                    let state = !self.state.unwrap_or(false);
+                    self.write(state);
+                    self.last_toggle = Some(now);
+                }
+            }
+            HeartbeatPattern::On => self.hold(true),
+            HeartbeatPattern::Off => self.hold(false),
+        }
+    }
+
+    /// Write value only when it differs from current state
+    fn hold(&mut self, state: bool) {
+        if self.state != Some(state) {
+            self.write(state);
+        }
+        self.last_toggle = None;
+    }
+
+    fn write(&mut self, state: bool) {
+        match self.command.execute(RawValue::Binary(state)) {
+            Ok(_) => self.state = Some(state),
+            Err(e) => console::error(format!("Could not write heartbeat: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::action::{Heartbeat, HeartbeatPattern, IOCommand};
+
+    #[test]
+    fn blink() {
+        let mut heartbeat = Heartbeat::new(IOCommand::Output(|_| Ok(())));
+        let now = Utc::now();
+
+        heartbeat.attempt_at(now);
+        assert_eq!(Some(true), heartbeat.state());
+
+        heartbeat.attempt_at(now + Duration::milliseconds(100));
+        assert_eq!(Some(true), heartbeat.state());
+
+        heartbeat.attempt_at(now + Duration::milliseconds(500));
+        assert_eq!(Some(false), heartbeat.state());
+
+        heartbeat.attempt_at(now + Duration::milliseconds(1000));
+        assert_eq!(Some(true), heartbeat.state());
+    }
+
+    #[test]
+    fn fault() {
+        let mut heartbeat = Heartbeat::new(IOCommand::Output(|_| Ok(())));
+        let now = Utc::now();
+
+        heartbeat.attempt_at(now);
+        heartbeat.fault();
+        assert_eq!(HeartbeatPattern::Off, heartbeat.pattern());
+
+        heartbeat.attempt_at(now + Duration::milliseconds(500));
+        heartbeat.attempt_at(now + Duration::milliseconds(1000));
+        assert_eq!(Some(false), heartbeat.state());
+
+        heartbeat.clear_fault();
+        heartbeat.attempt_at(now + Duration::milliseconds(1100));
+        assert_eq!(Some(true), heartbeat.state());
+    }
+}
@@ -1,11 +1,35 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::ptr::fn_addr_eq;
+use std::sync::Arc;
+
 use crate::action::Command;
-use crate::errors::DeviceError;
-use crate::io::{IODirection, RawValue};
+use crate::console;
+use crate::errors::{DeviceError, ReadError};
+use crate::io::{DeviceMetadata, IODirection, RawValue};
+
+/// Closure alternative to [`IOCommand::Input`]
+pub type InputFn = Arc<dyn Fn() -> RawValue + Send + Sync>;
+
+/// Fallible closure alternative to [`IOCommand::Input`]
+pub type TryInputFn = Arc<dyn Fn() -> Result<RawValue, ReadError> + Send + Sync>;
+
+/// Pending read returned by [`AsyncInputFn`]
+pub type ReadFuture = Pin<Box<dyn Future<Output = Result<RawValue, ReadError>> + Send>>;
+
+/// Asynchronous closure alternative to [`IOCommand::TryInputFn`]
+pub type AsyncInputFn = Arc<dyn Fn() -> ReadFuture + Send + Sync>;
+
+/// Closure alternative to [`IOCommand::Output`]
+pub type OutputFn = Arc<dyn Fn(RawValue) -> Result<(), ()> + Send + Sync>;
 
 /// Command design pattern for storing low-level I/O code
 ///
 /// Should be used as an interface for HAL code and otherwise perform no other logic.
-#[derive(Clone, PartialEq)]
+///
+/// Closure variants allow commands to capture state, such as a driver handle or the fake devices
+/// provided by [`crate::testkit`].
+#[derive(Clone)]
 pub enum IOCommand {
     /// Low-level code to read HW input
     Input(fn() -> RawValue),
@@ -15,20 +39,37 @@ pub enum IOCommand {
     /// `Err` is returned if `RawValue` variant is incorrect. Otherwise, `Ok` is returned by
     /// default.
     Output(fn(RawValue) -> Result<(), ()>),
+    /// Closure which reads HW input
+    InputFn(InputFn),
+    /// Closure which writes to HW output
+    OutputFn(OutputFn),
+    /// Closure which reads HW input and reports low-level failures
+    ///
+    /// Failures are converted into [`DeviceError`] variants that carry device metadata and the
+    /// number of attempts made by [`crate::io::Input::read()`].
+    TryInputFn(TryInputFn),
+    /// Asynchronous closure which reads HW input and reports low-level failures
+    ///
+    /// Commands of all inputs are awaited concurrently by
+    /// [`crate::storage::Group::poll_async()`], so that slow buses do not delay other inputs.
+    /// Since futures may depend on a runtime (ie: tokio timers or I/O), they are never driven
+    /// synchronously. [`crate::io::Input::read()`] and [`crate::storage::Group::poll()`] fail with
+    /// [`crate::errors::DeviceError::Asynchronous`] instead.
+    AsyncInputFn(AsyncInputFn),
 }
 
 impl IOCommand {
     pub fn is_output(&self) -> bool {
         match self {
-            Self::Input(_) => false,
-            Self::Output(_) => true,
+            Self::Input(_) | Self::InputFn(_) | Self::TryInputFn(_) | Self::AsyncInputFn(_) => false,
+            Self::Output(_) | Self::OutputFn(_) => true,
         }
     }
 
     pub fn is_input(&self) -> bool {
         match self {
-            Self::Input(_) => true,
-            Self::Output(_) => false,
+            Self::Input(_) | Self::InputFn(_) | Self::TryInputFn(_) | Self::AsyncInputFn(_) => true,
+            Self::Output(_) | Self::OutputFn(_) => false,
         }
     }
 
@@ -37,8 +78,8 @@ impl IOCommand {
     /// Used to verify device type aligns with function intention: input with input, vice versa.
     pub fn direction(&self) -> IODirection {
         match self {
-            IOCommand::Input(_) => IODirection::In,
-            IOCommand::Output(_) => IODirection::Out,
+            IOCommand::Input(_) | IOCommand::InputFn(_) | IOCommand::TryInputFn(_) | IOCommand::AsyncInputFn(_) => IODirection::In,
+            IOCommand::Output(_) | IOCommand::OutputFn(_) => IODirection::Out,
         }
     }
 
@@ -59,6 +100,38 @@ impl IOCommand {
             false => Err(())
         }
     }
+
+    /// Read HW input, retaining low-level failures of [`IOCommand::TryInputFn`]
+    ///
+    /// # Returns
+    ///
+    /// - `None` if command is an output
+    /// - `Some(Err)` with [`ReadError::Asynchronous`] if command is [`IOCommand::AsyncInputFn`],
+    ///   which must be awaited instead
+    pub fn try_read(&self) -> Option<Result<RawValue, ReadError>> {
+        match self {
+            Self::Input(inner) => Some(Ok(inner())),
+            Self::InputFn(inner) => Some(Ok(inner())),
+            Self::TryInputFn(inner) => Some(inner()),
+            Self::AsyncInputFn(_) => Some(Err(ReadError::Asynchronous)),
+            Self::Output(_) | Self::OutputFn(_) => None,
+        }
+    }
+}
+
+impl PartialEq for IOCommand {
+    /// Commands are equal when they refer to the same function or the same shared closure
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Input(a), Self::Input(b)) => fn_addr_eq(*a, *b),
+            (Self::Output(a), Self::Output(b)) => fn_addr_eq(*a, *b),
+            (Self::InputFn(a), Self::InputFn(b)) => Arc::ptr_eq(a, b),
+            (Self::OutputFn(a), Self::OutputFn(b)) => Arc::ptr_eq(a, b),
+            (Self::TryInputFn(a), Self::TryInputFn(b)) => Arc::ptr_eq(a, b),
+            (Self::AsyncInputFn(a), Self::AsyncInputFn(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
 }
 
 impl Default for IOCommand {
@@ -83,8 +156,9 @@ impl Command<RawValue, DeviceError> for IOCommand {
     /// - `Ok` containing [`RawValue`] if internal function is [`IOCommand::Input`]. Otherwise, `None`
     ///   since internal function is [`IOCommand::Output`].
     ///
-    /// Currently, there is no scenario that returns `Err`. It is set as the return type to match
-    /// [`Input::read()`] and [`Output::write()`].
+    /// - `Err` if [`IOCommand::TryInputFn`] fails, or with [`DeviceError::WriteFailed`] if an output
+    ///   command returns `Err`. Since command is not aware of device, error contains default
+    ///   metadata.
     ///
     /// # Panics
     ///
@@ -94,21 +168,27 @@ impl Command<RawValue, DeviceError> for IOCommand {
         V: Into<Option<RawValue>>
     {
         let value = value.into();
-        match self {
-            Self::Input(inner) => {
-                // throw warning for unused value
-                value.is_some().then(unused_value);
+        if self.is_input() {
+            // throw warning for unused value
+            value.is_some().then(unused_value);
 
-                let read_value = inner();
+            // device metadata is unknown to command, and is attached by `Input::read()` instead
+            let read_value = self.try_read()
+                .unwrap()
+                .map_err(|e| e.with_metadata(DeviceMetadata::default()))?;
 
-                Ok(Some(read_value))
-            }
-            Self::Output(inner) => {
-                let unwrapped_value = value.expect("No value was passed to write...");
-                let _ = inner(unwrapped_value); // TODO: handle bad result
+            Ok(Some(read_value))
+        } else {
+            let unwrapped_value = value.expect("No value was passed to write...");
+            let result = match self {
+                Self::Output(inner) => inner(unwrapped_value),
+                Self::OutputFn(inner) => inner(unwrapped_value),
+                _ => unreachable!(),
+            };
 
-                Ok(None)
-            }
+            // device metadata is attached by `Output::write()`
+            result.map_err(|_| DeviceError::WriteFailed { metadata: DeviceMetadata::default(), value: unwrapped_value })?;
+            Ok(None)
         }
     }
 }
@@ -116,11 +196,13 @@ impl Command<RawValue, DeviceError> for IOCommand {
 /// Print a warning on console stderr
 fn unused_value() {
     const MSG: &str = "Unused value passed when reading input...";
-    eprintln!("{}", MSG);
+    console::warning(MSG);
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use crate::action::{Command, IOCommand};
     use crate::io::{IODirection, RawValue};
 
@@ -158,4 +240,27 @@ mod tests {
                        .err()
                        .unwrap());
     }
+
+    #[test]
+    fn closures() {
+        let offset = 2;
+        let input = IOCommand::InputFn(Arc::new(move || RawValue::Int8(offset)));
+        assert_eq!(IODirection::In, input.direction());
+        assert_eq!(Some(RawValue::Int8(2)), input.execute(None).unwrap());
+
+        let written = Arc::new(Mutex::new(None));
+        let output = {
+            let written = written.clone();
+            IOCommand::OutputFn(Arc::new(move |val| {
+                *written.lock().unwrap() = Some(val);
+                Ok(())
+            }))
+        };
+        assert_eq!(IODirection::Out, output.direction());
+        output.execute(RawValue::Binary(true)).unwrap();
+        assert_eq!(Some(RawValue::Binary(true)), *written.lock().unwrap());
+
+        assert!(output == output.clone());
+        assert!(input != output);
+    }
 }
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+
+use crate::helpers::now;
+use crate::io::{is_on, RawValue};
+
+thread_local! {
+    /// Most recent value written by [`crate::action::WriteTarget`] on the current thread
+    static LAST_WRITE: Cell<Option<RawValue>> = const { Cell::new(None) };
+}
+
+/// Record a successful write to a [`crate::action::WriteTarget`]
+pub(crate) fn record_write(value: RawValue) {
+    LAST_WRITE.with(|last| last.set(Some(value)));
+}
+
+/// Most recent value written while running `f`
+///
+/// Writes of nested calls are also seen by the outer call.
+pub(crate) fn last_write<F: FnOnce()>(f: F) -> Option<RawValue> {
+    let outer = LAST_WRITE.with(|last| last.take());
+    f();
+    let written = LAST_WRITE.with(|last| last.get());
+    LAST_WRITE.with(|last| last.set(written.or(outer)));
+    written
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Counters describing how often an [`crate::action::Action`] has been evaluated and has fired
+///
+/// Maintained by [`crate::action::Publisher`] for every subscriber. An action that activates far
+/// more often than expected (ie: a threshold firing hundreds of times a day) is likely chattering.
+pub struct ActionMetrics {
+    /// Number of events passed to [`crate::action::Action::evaluate()`]
+    pub evaluations: usize,
+
+    /// Number of evaluations which wrote a different value than was previously written
+    ///
+    /// A first write only counts if it turns the target on, so that an action which is initially
+    /// off is not considered to have fired.
+    pub activations: usize,
+
+    /// Number of events withheld from action, either during bring-up or while held
+    pub suppressions: usize,
+
+    /// Time of the most recent activation
+    pub last_activation: Option<DateTime<Utc>>,
+
+    /// Time at which counting started
+    pub since: DateTime<Utc>,
+
+    /// Most recent value written by action
+    pub last_value: Option<RawValue>,
+}
+
+impl ActionMetrics {
+    /// Constructor for [`ActionMetrics`] with all counters at zero
+    pub fn new(since: DateTime<Utc>) -> Self {
+        Self {
+            evaluations: 0,
+            activations: 0,
+            suppressions: 0,
+            last_activation: None,
+            since,
+            last_value: None,
+        }
+    }
+
+    /// Record an evaluation and the value it wrote, if any
+    pub(crate) fn evaluated(&mut self, written: Option<RawValue>, at: DateTime<Utc>) {
+        self.evaluations += 1;
+        if let Some(value) = written {
+            let changed = match self.last_value {
+                Some(last) => last != value,
+                None => is_on(value),
+            };
+            if changed {
+                self.activations += 1;
+                self.last_activation = Some(at);
+            }
+            self.last_value = Some(value);
+        }
+    }
+}
+
+impl Default for ActionMetrics {
+    fn default() -> Self {
+        Self::new(now())
+    }
+}
@@ -3,16 +3,28 @@ mod action;
 mod command;
 mod trigger;
 mod handler;
+mod heartbeat;
 mod io;
+mod metrics;
+mod parameter;
 mod publisher;
+mod ramp;
 mod routine;
+mod setpoint;
+mod target;
 
 pub mod actions;
 
-pub use action::{Action, BoxedAction};
+pub use action::{Action, BoxedAction, HoldPolicy};
 pub use command::*;
 pub use trigger::Trigger;
-pub use handler::SchedRoutineHandler;
-pub use io::IOCommand;
+pub use handler::{SchedRoutineHandler, ScheduleRecord};
+pub use heartbeat::{Heartbeat, HeartbeatPattern};
+pub use io::{AsyncInputFn, InputFn, IOCommand, OutputFn, ReadFuture, TryInputFn};
+pub use metrics::ActionMetrics;
+pub use parameter::{ParameterDescriptor, ParameterType};
 pub use publisher::Publisher;
+pub use ramp::Ramp;
 pub use routine::Routine;
+pub use setpoint::SetpointSource;
+pub use target::WriteTarget;
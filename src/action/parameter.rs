@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::io::RawValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Type of value accepted by a parameter
+pub enum ParameterType {
+    Binary,
+    Integer,
+    Float,
+    Timestamp,
+}
+
+impl From<RawValue> for ParameterType {
+    fn from(value: RawValue) -> Self {
+        match value {
+            RawValue::Binary(_) => Self::Binary,
+            RawValue::PosInt8(_) | RawValue::Int8(_) | RawValue::PosInt(_) | RawValue::Int(_)
+            | RawValue::Int64(_) => Self::Integer,
+            RawValue::Float(_) | RawValue::Float64(_) | RawValue::Fixed(_) => Self::Float,
+            RawValue::Timestamp(_) => Self::Timestamp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Schema and current value of a tunable parameter of an [`crate::action::Action`]
+///
+/// Returned by [`crate::action::Action::describe()`] so that editors may be rendered for any
+/// action without knowledge of its type.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::{Action, ParameterType};
+/// use sensd::action::actions::PID;
+///
+/// let pid = PID::new("heater", 22.0, 10.0);
+/// let limit = pid.describe().into_iter().find(|parameter| parameter.name == "output_limit").unwrap();
+///
+/// assert_eq!(ParameterType::Float, limit.kind);
+/// assert_eq!(Some(0.0), limit.min);
+/// ```
+pub struct ParameterDescriptor {
+    pub name: String,
+    pub kind: ParameterType,
+
+    /// Inclusive lower bound of numeric parameters, if any
+    pub min: Option<f64>,
+    /// Inclusive upper bound of numeric parameters, if any
+    pub max: Option<f64>,
+
+    /// Current value, as returned by [`crate::action::Action::parameter()`]
+    pub value: Option<RawValue>,
+}
+
+impl ParameterDescriptor {
+    /// Constructor for an unbounded parameter without a value
+    pub fn new<N: Into<String>>(name: N, kind: ParameterType) -> Self {
+        Self { name: name.into(), kind, min: None, max: None, value: None }
+    }
+
+    /// Builder method to set inclusive bounds. Either bound may be `None`.
+    pub fn set_range<A, B>(mut self, min: A, max: B) -> Self
+    where
+        A: Into<Option<f64>>,
+        B: Into<Option<f64>>,
+    {
+        self.min = min.into();
+        self.max = max.into();
+        self
+    }
+
+    /// Builder method to set current value
+    pub fn set_value<V: Into<Option<RawValue>>>(mut self, value: V) -> Self {
+        self.value = value.into();
+        self
+    }
+
+    /// Check that `value` is of the described type and within bounds
+    ///
+    /// Integers are accepted by float parameters.
+    pub fn accepts(&self, value: RawValue) -> bool {
+        let kind = ParameterType::from(value);
+        let compatible = kind == self.kind || (kind == ParameterType::Integer && self.kind == ParameterType::Float);
+        if !compatible {
+            return false;
+        }
+        match value.as_float().map(f64::from) {
+            Some(value) => self.min.is_none_or(|min| min <= value) && self.max.is_none_or(|max| value <= max),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::actions::{Threshold, PID};
+    use crate::action::{Action, ParameterDescriptor, ParameterType, Trigger};
+    use crate::io::RawValue;
+
+    #[test]
+    fn accepts() {
+        let parameter = ParameterDescriptor::new("limit", ParameterType::Float).set_range(0.0, None);
+        assert!(parameter.accepts(RawValue::Float(1.5)));
+        assert!(parameter.accepts(RawValue::Int(2)));
+        assert!(!parameter.accepts(RawValue::Float(-1.0)));
+        assert!(!parameter.accepts(RawValue::Binary(true)));
+
+        let parameter = ParameterDescriptor::new("enabled", ParameterType::Binary);
+        assert!(parameter.accepts(RawValue::Binary(false)));
+        assert!(!parameter.accepts(RawValue::Int(1)));
+    }
+
+    #[test]
+    fn describe() {
+        // type is taken from value by default
+        let threshold = Threshold::new("", RawValue::Int(4), Trigger::GT);
+        let described = threshold.describe();
+        assert_eq!(1, described.len());
+        assert_eq!(("threshold", ParameterType::Integer), (described[0].name.as_str(), described[0].kind));
+        assert_eq!(Some(RawValue::Int(4)), described[0].value);
+
+        // bounds are enforced
+        let mut pid = PID::new("", 22.0, 10.0);
+        assert!(pid.set_parameter("output_limit", RawValue::Float(-1.0)).is_err());
+        pid.set_parameter("output_limit", RawValue::Float(5.0)).unwrap();
+        assert_eq!(Some(RawValue::Float(5.0)), pid.describe()[1].value);
+    }
+}
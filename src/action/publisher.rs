@@ -1,8 +1,14 @@
 //! Implements a control system based off of evaluating incoming data.
 
-use crate::action::{BoxedAction, SchedRoutineHandler};
-use crate::helpers::Def;
-use crate::io::IOEvent;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::action::metrics::last_write;
+use crate::action::{ActionMetrics, BoxedAction, HoldPolicy, ParameterDescriptor, SchedRoutineHandler};
+use crate::errors::{ActionError, ErrorType};
+use crate::helpers::{check_results, now, Def};
+use crate::io::{IOEvent, RawValue};
+use crate::storage::Log;
 
 #[derive(Default)]
 /// Handles storage and association between an [`Input`] and [`crate::action::Action`] instances
@@ -11,13 +17,39 @@ use crate::io::IOEvent;
 /// to disseminate [`IOEvent`]'s to [`crate::action::Action`] subscribers. Only one [`Publisher`] can
 /// be assigned to an [`Input`], but any number of subscribers are allowed. When data is read from an
 /// [`Input`], the generated [`IOEvent`] is passed to to all subscribers by [`Publisher::propagate()`].
+/// An [`crate::io::Output`] may also be assigned a [`Publisher`], in which case successful writes are
+/// propagated instead.
 ///
 /// Additionally, [`Publisher`] maintains the internal collection of scheduled [`crate::action::Routine`]s
 /// for any number of output devices and provides [`Publisher::attempt_routines()`] for executing those
 /// scheduled commands at their scheduled time.
+///
+/// # Bring-up
+///
+/// Subscribers can be held back until the source [`crate::io::Input`] has produced a number of valid readings,
+/// as set by [`Publisher::set_bring_up()`]. Until then, incoming data is not passed to subscribers.
+///
+/// # Metrics
+///
+/// Evaluations, activations, and suppressed events are counted for every subscriber, and are
+/// returned by [`Publisher::metrics()`].
 pub struct Publisher {
     actions: Vec<BoxedAction>,
     scheduled: Def<SchedRoutineHandler>,
+
+    /// Number of readings required before subscribers are enabled
+    bring_up: usize,
+    /// Number of readings received so far
+    readings: usize,
+
+    /// Incoming data is withheld between [`Publisher::hold()`] and [`Publisher::release()`]
+    held: bool,
+
+    /// Counters of each subscriber, in order of subscription
+    metrics: Vec<ActionMetrics>,
+
+    /// Log of source input, passed to [`crate::action::Action::on_attach()`]
+    log: Option<Def<Log>>,
 }
 
 impl Publisher {
@@ -50,11 +82,44 @@ impl Publisher {
 
     /// Add [`crate::action::Action`] to internal collection.
     ///
+    /// [`crate::action::Action::on_attach()`] is called with log of source input, if any.
+    ///
     /// # Parameters
     ///
     /// - `subscriber`: [`BoxedAction`] to add to internal store.
-    pub fn subscribe(&mut self, subscriber: BoxedAction) {
-        self.actions.push(subscriber)
+    pub fn subscribe(&mut self, mut subscriber: BoxedAction) {
+        if let Some(log) = &self.log {
+            subscriber.on_attach(&log.try_lock().unwrap());
+        }
+        self.actions.push(subscriber);
+        self.metrics.push(ActionMetrics::default());
+    }
+
+    /// Get mutable reference to subscriber by name
+    pub fn subscriber_mut(&mut self, name: &str) -> Option<&mut BoxedAction> {
+        self.actions.iter_mut().find(|subscriber| subscriber.name() == name)
+    }
+
+    /// Set parameter of a subscriber
+    ///
+    /// # Parameters
+    ///
+    /// - `action`: Name of subscriber
+    /// - `parameter`: Name of parameter
+    /// - `value`: New value
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` if parameter was set
+    /// - `Err` if no subscriber is named `action`, or subscriber rejected parameter
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::action::Action::set_parameter()`]
+    pub fn set_parameter(&mut self, action: &str, parameter: &str, value: RawValue) -> Result<(), ErrorType> {
+        self.subscriber_mut(action)
+            .ok_or_else(|| ActionError::UnknownAction { name: action.to_string() })?
+            .set_parameter(parameter, value)
     }
 
     /// Handle incoming data
@@ -66,11 +131,192 @@ impl Publisher {
     ///
     /// - `data`: Incoming [`IOEvent`] generated from [`crate::io::Input::read()`]
     pub fn propagate(&mut self, data: &IOEvent) {
+        if self.held || !self.is_enabled() {
+            if !self.held {
+                self.readings += 1;
+            }
+            for metrics in self.metrics.iter_mut() {
+                metrics.suppressions += 1;
+            }
+            return;
+        }
+
+        let at = now();
+        for (subscriber, metrics) in self.actions.iter_mut().zip(self.metrics.iter_mut()) {
+            let written = last_write(|| subscriber.evaluate(data));
+            metrics.evaluated(written, at);
+        }
+    }
+
+    /// Counters of all subscribers
+    ///
+    /// # Returns
+    ///
+    /// Map of [`ActionMetrics`] keyed by subscriber name
+    pub fn metrics(&self) -> BTreeMap<String, ActionMetrics> {
+        self.actions.iter()
+            .zip(self.metrics.iter())
+            .map(|(subscriber, metrics)| (subscriber.name().clone(), metrics.clone()))
+            .collect()
+    }
+
+    /// Total evaluations of all subscribers since counting started
+    pub fn evaluations(&self) -> usize {
+        self.metrics.iter().map(|metrics| metrics.evaluations).sum()
+    }
+
+    /// Restart counting for all subscribers, such as at the start of a day
+    pub fn reset_metrics(&mut self) {
+        let since = now();
+        for metrics in self.metrics.iter_mut() {
+            *metrics = ActionMetrics::new(since);
+        }
+    }
+
+    /// Collect runtime state of all stateful subscribers
+    ///
+    /// # Returns
+    ///
+    /// Map of [`crate::action::Action::state()`] keyed by subscriber name
+    pub fn state(&self) -> BTreeMap<String, Value> {
+        self.actions.iter()
+            .filter_map(|subscriber| subscriber.state().map(|state| (subscriber.name().clone(), state)))
+            .collect()
+    }
+
+    /// Collect current parameter values of all subscribers
+    ///
+    /// # Returns
+    ///
+    /// Map of parameter values keyed by subscriber name. Subscribers without parameters are omitted.
+    pub fn parameters(&self) -> BTreeMap<String, BTreeMap<String, RawValue>> {
+        self.actions.iter()
+            .map(|subscriber| {
+                let parameters: BTreeMap<String, RawValue> = subscriber.parameter_names().iter()
+                    .filter_map(|name| subscriber.parameter(name).map(|value| (name.to_string(), value)))
+                    .collect();
+                (subscriber.name().clone(), parameters)
+            })
+            .filter(|(_, parameters)| !parameters.is_empty())
+            .collect()
+    }
+
+    /// Describe parameters of all subscribers
+    ///
+    /// # Returns
+    ///
+    /// Map of [`crate::action::Action::describe()`] keyed by subscriber name. Subscribers without
+    /// parameters are omitted.
+    pub fn describe(&self) -> BTreeMap<String, Vec<ParameterDescriptor>> {
+        self.actions.iter()
+            .map(|subscriber| (subscriber.name().clone(), subscriber.describe()))
+            .filter(|(_, parameters)| !parameters.is_empty())
+            .collect()
+    }
+
+    /// Restore runtime state of subscribers by name
+    ///
+    /// Subscribers without a matching entry are left untouched. An error restoring one subscriber
+    /// does not prevent restoring others.
+    ///
+    /// # Parameters
+    ///
+    /// - `states`: Map previously returned by [`Publisher::state()`]
+    pub fn restore_state(&mut self, states: &BTreeMap<String, Value>) -> Result<(), ErrorType> {
+        let mut results = Vec::new();
+        for subscriber in self.actions.iter_mut() {
+            if let Some(state) = states.get(subscriber.name()) {
+                results.push(subscriber.restore_state(state));
+            }
+        }
+        check_results(&results)
+    }
+
+    /// Notify all subscribers that data will no longer be propagated
+    ///
+    /// # Parameters
+    ///
+    /// - `policy`: How subscribers should treat their outputs
+    pub fn hold(&mut self, policy: HoldPolicy) {
+        self.held = true;
+        for subscriber in self.actions.iter_mut() {
+            subscriber.hold(policy);
+        }
+    }
+
+    /// Notify all subscribers that data will be propagated again
+    pub fn release(&mut self) {
+        self.held = false;
+        for subscriber in self.actions.iter_mut() {
+            subscriber.release();
+        }
+    }
+
+    /// Set log of source input
+    ///
+    /// Called by [`crate::io::Input`] when a log or publisher is assigned.
+    pub fn set_log(&mut self, log: Def<Log>) {
+        self.log = Some(log);
+    }
+
+    /// Call [`crate::action::Action::on_attach()`] of all subscribers again
+    ///
+    /// Used to re-seed subscribers once archived events have been loaded into log of source input.
+    /// Has no effect if no log is set.
+    pub fn attach(&mut self) {
+        if let Some(log) = &self.log {
+            let log = log.try_lock().unwrap();
+            for subscriber in self.actions.iter_mut() {
+                subscriber.on_attach(&log);
+            }
+        }
+    }
+
+    /// Notify all subscribers that group has been paused
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::action::Action::on_pause()`]
+    pub fn on_pause(&mut self) {
         for subscriber in self.actions.iter_mut() {
-            subscriber.evaluate(data);
+            subscriber.on_pause();
         }
     }
 
+    /// Notify all subscribers that group is shutting down
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::action::Action::on_shutdown()`]
+    pub fn on_shutdown(&mut self) {
+        for subscriber in self.actions.iter_mut() {
+            subscriber.on_shutdown();
+        }
+    }
+
+    /// Check if incoming data is withheld by [`Publisher::hold()`]
+    pub fn is_held(&self) -> bool {
+        self.held
+    }
+
+    /// Set number of valid readings required before subscribers are enabled
+    ///
+    /// # Parameters
+    ///
+    /// - `count`: Number of readings to discard. `0` enables subscribers immediately.
+    pub fn set_bring_up(&mut self, count: usize) {
+        self.bring_up = count;
+    }
+
+    /// Check if subscribers receive incoming data
+    ///
+    /// # Returns
+    ///
+    /// `true` once the bring-up count has been reached
+    pub fn is_enabled(&self) -> bool {
+        self.readings >= self.bring_up
+    }
+
     /// Method to get passable reference to internal handler
     ///
     /// This is used when an [`crate::action::Action`] needs to schedule
@@ -83,3 +329,36 @@ impl Publisher {
         self.scheduled.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::action::actions::Threshold;
+    use crate::action::{Action, HoldPolicy, Publisher, Trigger, WriteTarget};
+    use crate::io::{IOEvent, RawValue};
+    use crate::storage::Variables;
+
+    #[test]
+    fn metrics() {
+        let action = Threshold::new("fan", RawValue::Float(1.0), Trigger::GT)
+            .set_output(WriteTarget::variable(Variables::default(), "fan"));
+        let mut publisher = Publisher::default();
+        publisher.subscribe(action.into_boxed());
+        publisher.set_bring_up(1);
+
+        for value in [2.0, 0.0, 0.0, 2.0, 0.0, 2.0] {
+            publisher.propagate(&IOEvent::new(RawValue::Float(value)));
+        }
+        publisher.hold(HoldPolicy::Hold);
+        publisher.propagate(&IOEvent::new(RawValue::Float(2.0)));
+
+        let metrics = &publisher.metrics()["fan"];
+        assert_eq!(5, metrics.evaluations);
+        // initial write of `false` is not an activation
+        assert_eq!(3, metrics.activations);
+        assert_eq!(2, metrics.suppressions);
+        assert!(metrics.last_activation.is_some());
+
+        publisher.reset_metrics();
+        assert_eq!(0, publisher.metrics()["fan"].evaluations);
+    }
+}
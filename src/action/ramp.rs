@@ -0,0 +1,305 @@
+use chrono::Duration;
+
+use crate::action::SchedRoutineHandler;
+use crate::errors::ErrorType;
+use crate::helpers::Def;
+use crate::io::{CorrelationId, DeviceGetters, IOEvent, Output, RawValue};
+
+/// Slew-rate limiter for analog [`Output`] devices
+///
+/// Step changes can damage pumps or cause visible flicker in dimmable lights. Instead of writing a
+/// commanded value directly, [`Ramp`] moves from the current output state to the commanded value in
+/// evenly spaced steps across a configured duration. The first step is written immediately, and the
+/// remaining steps are scheduled as [`crate::action::Routine`]s, so that the commanded value is
+/// reached once the duration has elapsed. Cached state of the output follows each executed step.
+///
+/// Binary values, or outputs without a cached state, are written directly.
+///
+/// Outputs which require claims (see [`Output::require_claims()`]) are written on behalf of the
+/// owner set by [`Ramp::set_owner()`]. Remaining steps are dropped once the owner loses its claim.
+///
+/// # Example
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::action::{IOCommand, Ramp, SchedRoutineHandler};
+/// use sensd::helpers::Def;
+/// use sensd::io::{Device, Output, RawValue};
+///
+/// let output = Output::default()
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .init_log()
+///     .into_deferred();
+/// let handler = Def::new(SchedRoutineHandler::default());
+///
+/// let ramp = Ramp::new(output.clone(), Duration::seconds(5), handler.clone())
+///     .set_steps(5);
+///
+/// // no previous state, so value is written directly
+/// ramp.write(RawValue::Float(0.0)).unwrap();
+///
+/// // ramp to full power over 5 seconds
+/// ramp.write(RawValue::Float(100.0)).unwrap();
+/// assert_eq!(4, handler.try_lock().unwrap().scheduled().len());
+/// ```
+///
+/// # Notes
+///
+/// Routines scheduled by a previous ramp are not cancelled when a new value is commanded. Therefore,
+/// commands should not be issued more often than the ramp duration.
+pub struct Ramp {
+    output: Def<Output>,
+    duration: Duration,
+    steps: usize,
+    handler: Def<SchedRoutineHandler>,
+
+    /// Name of claimant on whose behalf output is written
+    owner: Option<String>,
+}
+
+impl Ramp {
+    /// Constructor for [`Ramp`]
+    ///
+    /// # Parameters
+    ///
+    /// - `output`: Analog output device to control
+    /// - `duration`: Time taken to move from current value to commanded value
+    /// - `handler`: Handler used to schedule intermediate writes
+    ///
+    /// # Returns
+    ///
+    /// [`Ramp`] with 10 steps
+    pub fn new(output: Def<Output>, duration: Duration, handler: Def<SchedRoutineHandler>) -> Self {
+        Self {
+            output,
+            duration,
+            steps: 10,
+            handler,
+            owner: None,
+        }
+    }
+
+    /// Builder method to set number of intermediate writes
+    ///
+    /// # Panics
+    ///
+    /// If `steps` is zero
+    pub fn set_steps(mut self, steps: usize) -> Self {
+        assert!(steps > 0, "Ramp must have at least one step");
+        self.steps = steps;
+        self
+    }
+
+    /// Builder method to write on behalf of `owner`
+    ///
+    /// See [`Output::write_as()`]
+    pub fn set_owner<O>(mut self, owner: O) -> Self
+    where
+        O: Into<String>
+    {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    pub fn owner(&self) -> Option<&String> {
+        self.owner.as_ref()
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Calculate intermediate values
+    ///
+    /// # Parameters
+    ///
+    /// - `from`: Current value
+    /// - `to`: Commanded value
+    ///
+    /// # Returns
+    ///
+    /// Vector of offsets from now and values to write. The first offset is zero, and the last value
+    /// is always `to`, written once `duration` has elapsed. `None` is returned if either value is
+    /// not numeric.
+    pub fn plan(&self, from: RawValue, to: RawValue) -> Option<Vec<(Duration, RawValue)>> {
+        let start = from.as_float()?;
+        let end = to.as_float()?;
+
+        let intervals = (self.steps - 1).max(1) as i32;
+        let plan = (1..=self.steps)
+            .map(|step| {
+                let fraction = step as f32 / self.steps as f32;
+                let value = match step == self.steps {
+                    true => to,
+                    false => to.with_float(start + (end - start) * fraction).unwrap(),
+                };
+                (self.duration * (step - 1) as i32 / intervals, value)
+            })
+            .collect();
+        Some(plan)
+    }
+
+    /// Ramp output to commanded value
+    ///
+    /// # Parameters
+    ///
+    /// - `value`: Commanded value
+    ///
+    /// # Returns
+    ///
+    /// [`IOEvent`] of first write, or the error returned by [`Output::write_correlated()`]. Errors of
+    /// intermediate writes are printed when their routines are executed.
+    ///
+    /// # Panics
+    ///
+    /// - If output has no command
+    /// - If output has no log, and intermediate routines need to be scheduled
+    pub fn write(&self, value: RawValue) -> Result<IOEvent, ErrorType> {
+        self.write_correlated(value, None)
+    }
+
+    /// Ramp output to commanded value on behalf of an input event
+    ///
+    /// Behaves identically to [`Ramp::write()`], but all writes share `correlation`.
+    pub fn write_correlated(&self, value: RawValue, correlation: Option<CorrelationId>) -> Result<IOEvent, ErrorType> {
+        let mut output = self.output.try_lock().unwrap();
+
+        let plan = output.state()
+            .and_then(|current| self.plan(current, value));
+
+        match plan {
+            Some(plan) => {
+                let mut steps = plan.into_iter();
+                let (_, first) = steps.next().unwrap();
+                let event = self.write_first(&mut output, first, correlation)?;
+
+                let mut handler = self.handler.try_lock().unwrap();
+                for (offset, value) in steps {
+                    handler.push(output.create_routine(value, offset)
+                        .set_correlation(correlation)
+                        .set_output(&self.output)
+                        .set_owner(self.owner.clone()));
+                }
+                Ok(event)
+            }
+            None => self.write_first(&mut output, value, correlation),
+        }
+    }
+
+    /// Write immediately, on behalf of owner if set
+    fn write_first(&self, output: &mut Output, value: RawValue, correlation: Option<CorrelationId>) -> Result<IOEvent, ErrorType> {
+        match &self.owner {
+            Some(owner) => output.write_as(owner, value, correlation),
+            None => output.write_correlated(value, correlation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::action::{IOCommand, Ramp, SchedRoutineHandler};
+    use crate::helpers::Def;
+    use crate::io::{ClaimPriority, Device, DeviceGetters, Output, Quantization, RawValue};
+
+    #[test]
+    fn plan() {
+        let ramp = Ramp::new(Output::default().into_deferred(), Duration::seconds(3), Def::default())
+            .set_steps(4);
+
+        let plan = ramp.plan(RawValue::Int(0), RawValue::Int(10)).unwrap();
+        let values: Vec<RawValue> = plan.iter().map(|(_, value)| *value).collect();
+        let offsets: Vec<Duration> = plan.iter().map(|(offset, _)| *offset).collect();
+
+        assert_eq!(vec![RawValue::Int(3), RawValue::Int(5), RawValue::Int(8), RawValue::Int(10)], values);
+        assert_eq!(vec![Duration::zero(), Duration::seconds(1), Duration::seconds(2), Duration::seconds(3)], offsets);
+
+        assert!(ramp.plan(RawValue::Binary(false), RawValue::Binary(true)).is_none());
+    }
+
+    #[test]
+    fn binary_written_directly() {
+        let output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .into_deferred();
+        let handler = Def::new(SchedRoutineHandler::default());
+        let ramp = Ramp::new(output, Duration::seconds(1), handler.clone());
+
+        ramp.write(RawValue::Binary(false)).unwrap();
+        ramp.write(RawValue::Binary(true)).unwrap();
+
+        assert_eq!(0, handler.try_lock().unwrap().scheduled().len());
+    }
+
+    #[test]
+    /// Assert that cached state follows intermediate writes, so that a repeated command is a no-op
+    fn state_follows_steps() {
+        let output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .init_log()
+            .into_deferred();
+        let handler = Def::new(SchedRoutineHandler::default());
+        let ramp = Ramp::new(output.clone(), Duration::seconds(3), handler.clone())
+            .set_steps(4);
+
+        ramp.write(RawValue::Float(0.0)).unwrap();
+        ramp.write(RawValue::Float(100.0)).unwrap();
+        assert_eq!(Some(RawValue::Float(25.0)), *output.try_lock().unwrap().state());
+
+        handler.try_lock().unwrap().attempt_routines_at(Utc::now() + Duration::seconds(3));
+        assert!(handler.try_lock().unwrap().scheduled().is_empty());
+        assert_eq!(Some(RawValue::Float(100.0)), *output.try_lock().unwrap().state());
+
+        let event = ramp.write(RawValue::Float(100.0)).unwrap();
+        assert_eq!(RawValue::Float(100.0), event.value);
+    }
+
+    #[test]
+    /// Assert that every step is quantized
+    fn quantized_steps() {
+        let output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .set_quantization(Quantization::new(10.0))
+            .init_log()
+            .into_deferred();
+        let handler = Def::new(SchedRoutineHandler::default());
+        let ramp = Ramp::new(output.clone(), Duration::seconds(3), handler.clone())
+            .set_steps(4);
+
+        ramp.write(RawValue::Float(0.0)).unwrap();
+        ramp.write(RawValue::Float(100.0)).unwrap();
+        let steps: Vec<RawValue> = handler.try_lock().unwrap().scheduled().iter()
+            .map(|routine| routine.value())
+            .collect();
+        assert_eq!(vec![RawValue::Float(50.0), RawValue::Float(80.0), RawValue::Float(100.0)], steps);
+        assert_eq!(Some(RawValue::Float(30.0)), *output.try_lock().unwrap().state());
+    }
+
+    #[test]
+    /// Assert that remaining steps are dropped once owner is preempted
+    fn preempted_owner() {
+        let output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .init_log()
+            .require_claims()
+            .into_deferred();
+        output.try_lock().unwrap().write(RawValue::Float(0.0)).unwrap();
+        output.try_lock().unwrap().claim("dimmer", ClaimPriority::Automatic).unwrap();
+        let handler = Def::new(SchedRoutineHandler::default());
+        let ramp = Ramp::new(output.clone(), Duration::seconds(3), handler.clone())
+            .set_steps(4)
+            .set_owner("dimmer");
+
+        ramp.write(RawValue::Float(100.0)).unwrap();
+        output.try_lock().unwrap().claim("interlock", ClaimPriority::Interlock).unwrap();
+
+        handler.try_lock().unwrap().attempt_routines_at(Utc::now() + Duration::seconds(3));
+        assert!(handler.try_lock().unwrap().scheduled().is_empty());
+        assert_eq!(Some(RawValue::Float(25.0)), *output.try_lock().unwrap().state());
+    }
+}
@@ -1,11 +1,13 @@
 use std::ops::Not;
 use crate::action::{Command, IOCommand};
+use crate::console;
 use crate::errors::ErrorType;
-use crate::helpers::Def;
-use crate::io::{IOEvent, RawValue};
+use crate::helpers::{monotonic, now, Def};
+use crate::io::{is_on, CorrelationId, DeviceGetters, EventKind, IOEvent, Output, RawValue};
 use crate::storage::{Chronicle, Log};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
 
 /// A [`Command`] that should be executed at a scheduled time *outside* of the normal event loop.
 ///
@@ -21,10 +23,19 @@ use std::sync::{Arc, Mutex, Weak};
 /// The primary use case is turning off a pump or other output after a predetermined period of time.
 /// The normal event loop will execute the first action, but to avoid blocking the thread, a
 /// [`Routine`] should be scheduled.
+///
+/// # Clock Corrections
+///
+/// The scheduled wall-clock time is converted to a monotonic deadline upon construction. Therefore,
+/// [`Routine::attempt()`] is unaffected by system clock jumps (ie: NTP corrections) that occur
+/// after the routine is scheduled.
 pub struct Routine {
     /// Scheduled time to execute function
     timestamp: DateTime<Utc>,
 
+    /// Monotonic equivalent of `timestamp`
+    deadline: Instant,
+
     /// Value to pass to `IOCommand`
     value: RawValue,
 
@@ -33,6 +44,15 @@ pub struct Routine {
 
     /// Low-level command to execute
     command: IOCommand,
+
+    /// Id of input event that caused this routine to be scheduled
+    correlation: Option<CorrelationId>,
+
+    /// Weak reference to originating output, whose cached state follows executed writes
+    output: Option<Weak<Mutex<Output>>>,
+
+    /// Name of action on whose behalf routine writes
+    owner: Option<String>,
 }
 
 impl Routine {
@@ -71,14 +91,112 @@ impl Routine {
             panic!("Command is not Output");
         }
 
+        let deadline = monotonic() + (timestamp - now()).to_std().unwrap_or_default();
+
         Self {
             timestamp,
+            deadline,
             value,
             log: weak_log,
             command,
+            correlation: None,
+            output: None,
+            owner: None,
+        }
+    }
+
+    /// Builder method to set correlation id
+    ///
+    /// The [`IOEvent`] generated on execution is stamped with this id so that it can be traced back
+    /// to the originating input event.
+    ///
+    /// # Parameters
+    ///
+    /// - `correlation`: Id of originating event
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `self` to allow method chaining
+    pub fn set_correlation<C>(mut self, correlation: C) -> Self
+    where
+        C: Into<Option<CorrelationId>>
+    {
+        self.correlation = correlation.into();
+        self
+    }
+
+    /// Getter for correlation id
+    pub fn correlation(&self) -> Option<CorrelationId> {
+        self.correlation
+    }
+
+    /// Builder method to write on behalf of an owner
+    ///
+    /// When the output set by [`Routine::set_output()`] requires claims, the routine is dropped
+    /// without writing unless `owner` holds the claim at the time of execution. Routines without an
+    /// owner are system writes, which are never rejected.
+    ///
+    /// # Parameters
+    ///
+    /// - `owner`: Name of action which scheduled routine
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `self` to allow method chaining
+    pub fn set_owner<O>(mut self, owner: O) -> Self
+    where
+        O: Into<Option<String>>
+    {
+        self.owner = owner.into();
+        self
+    }
+
+    /// Getter for name of owner
+    pub fn owner(&self) -> Option<&String> {
+        self.owner.as_ref()
+    }
+
+    /// Builder method to keep cached state of `output` in step with executed writes
+    ///
+    /// Without it, [`Output::state()`] continues to report the value written before the routine
+    /// was scheduled. While `output` is locked elsewhere, execution is postponed until the next
+    /// attempt. While `output` is inhibited (see [`Output::inhibit()`]), a value which would turn
+    /// it on is queued instead of written.
+    ///
+    /// [`Output::state()`]: crate::io::DeviceGetters::state
+    ///
+    /// # Parameters
+    ///
+    /// - `output`: Output that routine writes to. Reference is internally downgraded.
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `self` to allow method chaining
+    pub fn set_output(mut self, output: &Def<Output>) -> Self {
+        self.output = Some(Arc::downgrade(&output.clone().into()));
+        self
+    }
+
+    /// Check if routine was created with a log that has since been dropped
+    ///
+    /// This occurs when the originating device is removed while the routine is still scheduled.
+    pub fn is_dangling(&self) -> bool {
+        match &self.log {
+            Some(log) => log.strong_count() == 0,
+            None => false,
         }
     }
 
+    /// Getter for scheduled time of execution
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    /// Getter for value passed to command
+    pub fn value(&self) -> RawValue {
+        self.value
+    }
+
     /// Main polling function
     ///
     /// Acts as wrapper for [`Command::execute()`]. Checks scheduled time,
@@ -94,17 +212,64 @@ impl Routine {
     /// - `false`: if [`IOCommand`] has not been executed. Instance should
     ///   not be dropped yet.
     pub fn attempt(&self) -> bool {
-        let now = Utc::now();
-        if now >= self.timestamp {
+        self.attempt_if(monotonic() >= self.deadline)
+    }
+
+    /// Attempt execution relative to a given wall-clock time
+    ///
+    /// Unlike [`Routine::attempt()`], the scheduled wall-clock time is used. This allows routines
+    /// to be driven in virtual time.
+    ///
+    /// # Parameters
+    ///
+    /// - `now`: Current time
+    ///
+    /// # Returns
+    ///
+    /// See [`Routine::attempt()`]
+    pub fn attempt_at(&self, now: DateTime<Utc>) -> bool {
+        self.attempt_if(now >= self.timestamp)
+    }
+
+    fn attempt_if(&self, due: bool) -> bool {
+        if due {
+            let output = self.output.as_ref().and_then(Weak::upgrade);
+            let mut output = match output.as_ref().map(|output| output.try_lock()) {
+                Some(Ok(output)) => Some(output),
+                // retry once output is released
+                Some(Err(_)) => return false,
+                None => None,
+            };
+
+            // preempted owners no longer control output
+            if let (Some(owner), Some(output)) = (&self.owner, &output) {
+                if output.claims_required() && output.claimant().is_none_or(|claim| &claim.owner != owner) {
+                    console::warning(format!("Dropped routine of \"{}\", which does not hold claim on {}",
+                                             owner, output.metadata()));
+                    return true;
+                }
+            }
+
+            // inhibited outputs queue writes which turn them on, as with `Output::write()`
+            if let Some(output) = output.as_mut() {
+                if output.is_inhibited() && is_on(self.value) {
+                    output.queue(self.value, self.correlation);
+                    return true;
+                }
+            }
+
             let result = self.execute(self.value);
             match result {
                 Ok(event) => {
                     let event = event.unwrap();
-                    let _ = self.push_to_log(&event);
+                    if let Some(output) = output.as_mut() {
+                        output.sync_state(&event);
+                    }
+                    self.record(event);
                     return true;
                 }
                 Err(e) => {
-                    eprintln!("{}", e);
+                    console::error(e);
                 }
             };
         };
@@ -112,6 +277,20 @@ impl Routine {
         // return false by default
         false
     }
+
+    /// Add event of executed command to log
+    ///
+    /// When other writes have already been logged at the scheduled time (ie: by a poll or another
+    /// routine scheduled for the same time), the event is recorded at the next free nanosecond so
+    /// that no write is lost and order of execution is preserved.
+    fn record(&self, mut event: IOEvent) {
+        if let Some(log) = self.log() {
+            let mut log = log.try_lock().expect("Could not lock `Log`");
+            while log.push(event.clone()).is_err() {
+                event.timestamp += Duration::nanoseconds(1);
+            }
+        }
+    }
 }
 
 impl Command<IOEvent, ErrorType> for Routine {
@@ -122,7 +301,9 @@ impl Command<IOEvent, ErrorType> for Routine {
         let value = value.into();
         match self.command.execute(value) {
             Ok(_) => {
-                let event = IOEvent::with_timestamp(self.timestamp, value.unwrap());
+                let event = IOEvent::with_timestamp(self.timestamp, value.unwrap())
+                    .set_kind(EventKind::OutputWrite)
+                    .set_correlation(self.correlation);
                 Ok(Some(event))
             }
             Err(e) => Err(e.into()),
@@ -182,11 +363,9 @@ mod functionality_tests {
             assert_ne!(REGISTER, value);
         }
 
-        while Utc::now() < timestamp {
-            assert_eq!(false, routine.attempt());
-        }
+        assert!(!routine.attempt_at(timestamp - Duration::microseconds(1)));
 
-        assert!(routine.attempt());
+        assert!(routine.attempt_at(timestamp));
         unsafe {
             assert_eq!(REGISTER, value);
         }
@@ -196,13 +375,14 @@ mod functionality_tests {
 
 #[cfg(test)]
 mod meta_tests {
-    use chrono::Utc;
+    use chrono::{Duration, Utc};
 
     use crate::{
         action::{IOCommand, Routine},
         helpers::Def,
-        io::{DeviceMetadata, RawValue},
+        io::{DeviceMetadata, IOEvent, RawValue},
         storage::Log,
+        testkit::FakeClock,
     };
     #[test]
     fn test_constructor_w_none() {
@@ -229,6 +409,53 @@ mod meta_tests {
         assert!(routine.attempt());
     }
 
+    #[test]
+    /// Assert that wall-clock jumps do not affect scheduled execution
+    fn clock_jump() {
+        let start = Utc::now();
+        let clock = FakeClock::install(start);
+
+        let routine = Routine::new(start + Duration::seconds(10), RawValue::Binary(true), None, IOCommand::Output(|_| Ok(())));
+
+        clock.set(start - Duration::hours(1));
+        clock.advance(Duration::seconds(9));
+        assert!(!routine.attempt());
+
+        clock.advance(Duration::seconds(1));
+        assert!(routine.attempt());
+    }
+
+    #[test]
+    fn dangling() {
+        let log = Def::new(Log::with_metadata(&DeviceMetadata::default()));
+        let routine = Routine::new(Utc::now(), RawValue::Binary(true), log.clone(), IOCommand::Output(|_| Ok(())));
+        assert!(!routine.is_dangling());
+
+        drop(log);
+        assert!(routine.is_dangling());
+
+        let routine = Routine::new(Utc::now(), RawValue::Binary(true), None, IOCommand::Output(|_| Ok(())));
+        assert!(!routine.is_dangling());
+    }
+
+    #[test]
+    /// Assert that writes logged at the scheduled time do not cause the routine's event to be lost
+    fn collision() {
+        let timestamp = Utc::now();
+        let log = Def::new(Log::with_metadata(&DeviceMetadata::default()));
+        log.lock().unwrap().push(IOEvent::with_timestamp(timestamp, RawValue::Binary(true))).unwrap();
+
+        for _ in 0..2 {
+            let routine = Routine::new(timestamp, RawValue::Binary(false), log.clone(), IOCommand::Output(|_| Ok(())));
+            assert!(routine.attempt());
+        }
+
+        let log = log.lock().unwrap();
+        assert_eq!(3, log.len());
+        assert_eq!(timestamp + Duration::nanoseconds(2), log.last().unwrap().timestamp);
+        assert_eq!(RawValue::Binary(false), log.last().unwrap().value);
+    }
+
     #[test]
     #[should_panic]
     fn validate_command() {
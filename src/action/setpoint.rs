@@ -0,0 +1,91 @@
+use crate::helpers::Def;
+use crate::io::{DeviceGetters, Input};
+
+/// Origin of the setpoint of an [`crate::action::Action`]
+///
+/// By default, setpoints are fixed and only change when set explicitly. A setpoint may instead
+/// track the latest reading of another input, so that a process is controlled relative to its
+/// surroundings. The setpoint is re-evaluated every time the action evaluates incoming data.
+///
+/// # Example
+///
+/// Keeping nutrient temperature 2 °C below room temperature:
+///
+/// ```
+/// use sensd::action::{Action, SetpointSource};
+/// use sensd::action::actions::PID;
+/// use sensd::io::{Device, Input, IOKind};
+///
+/// let room = Input::new("room", 0, IOKind::Temperature).into_deferred();
+/// let chiller = PID::new("chiller", 18.0, 10.0)
+///     .set_setpoint_source(SetpointSource::track(room, 1.0, -2.0));
+/// ```
+#[derive(Clone, Default)]
+pub enum SetpointSource {
+    /// Setpoint is only changed explicitly (ie: by [`crate::action::Action::set_parameter()`])
+    #[default]
+    Fixed,
+
+    /// Setpoint is `value * scale + offset`, where `value` is the latest reading of `input`
+    ///
+    /// `input` must not be the input which evaluates the action, since it is locked while actions
+    /// are evaluated.
+    Track {
+        input: Def<Input>,
+        scale: f32,
+        offset: f32,
+    },
+}
+
+impl SetpointSource {
+    /// Constructor for [`SetpointSource::Track`]
+    ///
+    /// # Parameters
+    ///
+    /// - `input`: Input to track
+    /// - `scale`: Factor applied to reading of `input`
+    /// - `offset`: Value added after scaling
+    pub fn track(input: Def<Input>, scale: f32, offset: f32) -> Self {
+        SetpointSource::Track { input, scale, offset }
+    }
+
+    /// Current setpoint given by source
+    ///
+    /// # Returns
+    ///
+    /// `None` if setpoint is fixed, or if tracked input has no finite reading or is locked. The
+    /// current setpoint should be retained in either case.
+    pub fn resolve(&self) -> Option<f32> {
+        match self {
+            SetpointSource::Fixed => None,
+            SetpointSource::Track { input, scale, offset } => {
+                let value = (*input.try_lock().ok()?.state())?.finite_float()?;
+                Some(value * scale + offset)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::{IOCommand, SetpointSource};
+    use crate::io::{Device, Input, RawValue};
+
+    #[test]
+    fn resolve() {
+        assert_eq!(None, SetpointSource::Fixed.resolve());
+
+        let input = Input::default()
+            .set_command(IOCommand::Input(|| RawValue::Float(20.0)))
+            .into_deferred();
+        let source = SetpointSource::track(input.clone(), 0.5, -2.0);
+        assert_eq!(None, source.resolve());
+
+        input.try_lock().unwrap().read().unwrap();
+        assert_eq!(Some(8.0), source.resolve());
+
+        // locked input is skipped
+        let _binding = input.try_lock().unwrap();
+        assert_eq!(None, source.resolve());
+    }
+}
@@ -0,0 +1,253 @@
+use std::ops::DerefMut;
+
+use crate::action::metrics::record_write;
+use crate::alarm::{Alarm, AlarmSeverity, BoxedSink};
+use crate::errors::{ActionError, DeviceError, ErrorType};
+use crate::helpers::{now, Def};
+use crate::io::{CorrelationId, Input, Output, RawValue};
+use crate::storage::{Roster, Variables};
+
+/// Destination of values written by an [`crate::action::Action`]
+///
+/// Besides physical outputs, actions may drive the parameters of other actions (ie: cascade
+/// control where an outer loop adjusts the setpoint of an inner loop), virtual variables for
+/// supervisory logic, or notification sinks. The latter two allow actions to be exercised without
+/// hardware.
+///
+/// Outputs may also be selected by tag using [`WriteTarget::Tagged`], so that outputs added to a
+/// group later are driven by existing actions.
+///
+/// [`Def<Output>`] converts into [`WriteTarget::Output`], therefore outputs may be passed directly
+/// to [`crate::action::Action::set_output()`].
+///
+/// # Example
+///
+/// An outer controller writing to the setpoint of an inner controller:
+///
+/// ```
+/// use sensd::action::{Action, WriteTarget};
+/// use sensd::action::actions::PID;
+/// use sensd::io::{Device, Input};
+///
+/// let inner = Input::default().into_deferred();
+/// let outer = PID::new("outer", 30.0, 10.0)
+///     .set_output(WriteTarget::parameter(inner, "inner", "setpoint"));
+///
+/// assert!(outer.target().is_some());
+/// assert!(outer.output().is_none());
+/// ```
+#[derive(Clone)]
+pub enum WriteTarget {
+    /// Physical output device
+    Output(Def<Output>),
+
+    /// Named parameter of an action subscribed to `input`
+    ///
+    /// `input` must not be the input which evaluates the writing action, since it is locked while
+    /// actions are evaluated.
+    Parameter {
+        input: Def<Input>,
+        action: String,
+        parameter: String,
+    },
+
+    /// Named entry of a [`Variables`] store, which holds the most recently written value
+    Variable {
+        variables: Variables,
+        name: String,
+    },
+
+    /// Values are only passed to a notification sink as [`AlarmSeverity::Info`]
+    Sink(Def<BoxedSink>),
+
+    /// Every output of a group labelled with `tag`, resolved on each write
+    Tagged {
+        roster: Roster,
+        tag: String,
+    },
+}
+
+impl WriteTarget {
+    /// Constructor for [`WriteTarget::Parameter`]
+    ///
+    /// # Parameters
+    ///
+    /// - `input`: Input whose publisher owns the action
+    /// - `action`: Name of action
+    /// - `parameter`: Name of parameter. See [`crate::action::Action::set_parameter()`].
+    pub fn parameter<A, P>(input: Def<Input>, action: A, parameter: P) -> Self
+    where
+        A: Into<String>,
+        P: Into<String>,
+    {
+        WriteTarget::Parameter {
+            input,
+            action: action.into(),
+            parameter: parameter.into(),
+        }
+    }
+
+    /// Constructor for [`WriteTarget::Variable`]
+    ///
+    /// # Parameters
+    ///
+    /// - `variables`: Store which holds variable. See [`crate::storage::Group::variables()`].
+    /// - `name`: Name of variable
+    pub fn variable<N>(variables: Variables, name: N) -> Self
+    where
+        N: Into<String>
+    {
+        WriteTarget::Variable { variables, name: name.into() }
+    }
+
+    /// Constructor for [`WriteTarget::Tagged`]
+    ///
+    /// # Parameters
+    ///
+    /// - `roster`: Outputs to select from. See [`crate::storage::Group::roster()`].
+    /// - `tag`: Tag of outputs to write to
+    pub fn tagged<T>(roster: Roster, tag: T) -> Self
+    where
+        T: Into<String>
+    {
+        WriteTarget::Tagged { roster, tag: tag.into() }
+    }
+
+    /// Physical output device, if target is an output
+    pub fn output(&self) -> Option<Def<Output>> {
+        match self {
+            WriteTarget::Output(output) => Some(output.clone()),
+            _ => None,
+        }
+    }
+
+    /// All physical output devices currently written by target
+    ///
+    /// Tags are resolved at the time of calling. Empty if target is not an output.
+    pub fn outputs(&self) -> Vec<Def<Output>> {
+        match self {
+            WriteTarget::Output(output) => vec![output.clone()],
+            WriteTarget::Tagged { roster, tag } => roster.tagged(tag),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Write value to target
+    ///
+    /// # Parameters
+    ///
+    /// - `source`: Name of action performing write
+    /// - `value`: Value to write
+    /// - `correlation`: Correlation id of originating event. Only used by outputs.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` if value was written, if write to output was queued (see
+    ///   [`DeviceError::is_queued()`]), or if write was dropped because `source` does not hold claim
+    ///   on output (see [`Output::require_claims()`])
+    /// - `Err` if output write failed, parameter is unknown or invalid, or target could not be
+    ///   locked. When writing to tagged outputs, every output is written before the first error is
+    ///   returned.
+    pub fn write(
+        &self,
+        source: &str,
+        value: RawValue,
+        correlation: Option<CorrelationId>,
+    ) -> Result<(), ErrorType> {
+        let unavailable = || ActionError::TargetUnavailable { name: source.to_string() };
+        match self {
+            WriteTarget::Output(output) => {
+                let mut binding = output.try_lock().map_err(|_| unavailable())?;
+                write_output(binding.deref_mut(), source, value, correlation)?;
+            }
+            WriteTarget::Parameter { input, action, parameter } => {
+                let mut input = input.try_lock().map_err(|_| unavailable())?;
+                let publisher = input.publisher_mut().as_mut()
+                    .ok_or_else(|| ActionError::UnknownAction { name: action.clone() })?;
+                publisher.set_parameter(action, parameter, value)?;
+            }
+            WriteTarget::Variable { variables, name } => {
+                variables.set(name.as_str(), value);
+            }
+            WriteTarget::Sink(sink) => {
+                let message = format!("{} wrote {}", source, value);
+                let alarm = Alarm::new(source, AlarmSeverity::Info, message, now()).set_value(value);
+                sink.try_lock().map_err(|_| unavailable())?.notify(&alarm)?;
+            }
+            WriteTarget::Tagged { roster, tag } => {
+                let results: Vec<_> = roster.tagged(tag).iter()
+                    .map(|output| {
+                        let mut binding = output.try_lock().map_err(|_| unavailable())?;
+                        write_output(binding.deref_mut(), source, value, correlation)
+                    })
+                    .collect();
+                results.into_iter().collect::<Result<(), _>>()?;
+            }
+        }
+        record_write(value);
+        Ok(())
+    }
+}
+
+/// Write to output on behalf of `source`, treating queued writes as successful
+fn write_output(output: &mut Output, source: &str, value: RawValue, correlation: Option<CorrelationId>) -> Result<(), ErrorType> {
+    match output.write_as(source, value, correlation) {
+        // write will be performed once output is able to turn on
+        Err(e) if e.downcast_ref().map(DeviceError::is_queued).unwrap_or(false) => Ok(()),
+        // action has been preempted, and is not in control of output
+        Err(e) if matches!(e.downcast_ref(), Some(DeviceError::NotClaimed { .. })) => Ok(()),
+        result => result.map(|_| ()),
+    }
+}
+
+impl From<Def<Output>> for WriteTarget {
+    fn from(output: Def<Output>) -> Self {
+        WriteTarget::Output(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::actions::{Threshold, PID};
+    use crate::action::{Action, Trigger, WriteTarget};
+    use crate::io::{Device, IOEvent, Input, RawValue};
+    use crate::storage::Variables;
+
+    #[test]
+    fn variable() {
+        let variables = Variables::default();
+        let mut action = Threshold::new("", RawValue::Float(1.0), Trigger::GT)
+            .set_output(WriteTarget::variable(variables.clone(), "exceeded"));
+
+        action.evaluate(&IOEvent::new(RawValue::Float(2.0)));
+        assert_eq!(Some(RawValue::Binary(true)), variables.get("exceeded"));
+    }
+
+    #[test]
+    fn parameter() {
+        let mut inner = Input::default().init_publisher();
+        inner.publisher_mut().as_mut().unwrap()
+            .subscribe(PID::new("inner", 20.0, 10.0).into_boxed());
+        let inner = inner.into_deferred();
+
+        let mut outer = PID::new("outer", 30.0, 10.0)
+            .set_p(1.0, 100.0)
+            .set_output(WriteTarget::parameter(inner.clone(), "inner", "setpoint"));
+        outer.evaluate(&IOEvent::new(RawValue::Float(25.0)));
+
+        let binding = inner.try_lock().unwrap();
+        let action = &binding.publisher().as_ref().unwrap().subscribers()[0];
+        assert_eq!(Some(RawValue::Float(5.0)), action.parameter("setpoint"));
+
+        // unknown action
+        let target = WriteTarget::parameter(inner.clone(), "missing", "setpoint");
+        drop(binding);
+        assert!(target.write("", RawValue::Float(1.0), None).is_err());
+
+        // errors are reported rather than interrupting the control loop
+        let mut action = PID::new("outer", 30.0, 10.0)
+            .set_p(1.0, 100.0)
+            .set_output(target);
+        action.evaluate(&IOEvent::new(RawValue::Float(25.0)));
+    }
+}
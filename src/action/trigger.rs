@@ -26,6 +26,11 @@ impl Trigger {
     ///
     /// A `bool` if threshold is exceeded or not in relation to variant of `self`
     pub fn exceeded(&self, value: RawValue, threshold: RawValue) -> bool {
+        // non-finite values never exceed a threshold
+        let (value, threshold) = match (value.to_finite(), threshold.to_finite()) {
+            (Some(value), Some(threshold)) => (value, threshold),
+            _ => return false,
+        };
         match &self {
             &Trigger::GT => value > threshold,
             &Trigger::GTE => value >= threshold,
@@ -129,4 +134,14 @@ mod tests {
                    trigger.exceeded(smaller,  bigger, )
         );
     }
+
+    #[test]
+    fn non_finite() {
+        let nan = RawValue::Float(f32::NAN);
+        for trigger in [Trigger::GT, Trigger::GTE, Trigger::LT, Trigger::LTE] {
+            assert!(!trigger.exceeded(nan, RawValue::Float(1.0)));
+            assert!(!trigger.exceeded(RawValue::Float(1.0), nan));
+        }
+        assert!(!Trigger::GT.exceeded(RawValue::Float(f32::INFINITY), RawValue::Float(1.0)));
+    }
 }
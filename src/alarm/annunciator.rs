@@ -0,0 +1,281 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+
+use crate::action::{Command, IOCommand};
+use crate::alarm::{AlarmHandler, AlarmSeverity};
+use crate::console;
+use crate::helpers::now;
+use crate::io::{IODirection, RawValue};
+
+/// Pattern used to drive a buzzer or strobe
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnnunciatorPattern {
+    /// Hold output low
+    Off,
+    /// Hold output high
+    Steady,
+    /// Toggle output with full on/off cycle lasting `period`
+    Flash(Duration),
+    /// Repeat `count` on/off cycles lasting `period` each, followed by `pause` with output low
+    ///
+    /// Beep codes let an operator tell conditions apart by ear (ie: 2 beeps for a warning).
+    Code { count: u32, period: Duration, pause: Duration },
+}
+
+impl AnnunciatorPattern {
+    /// State of output `elapsed` after pattern was started
+    pub fn state_at(&self, elapsed: Duration) -> bool {
+        let elapsed = elapsed.num_milliseconds().max(0);
+        match *self {
+            AnnunciatorPattern::Off => false,
+            AnnunciatorPattern::Steady => true,
+            AnnunciatorPattern::Flash(period) => {
+                let period = period.num_milliseconds().max(1);
+                elapsed % period < period / 2
+            }
+            AnnunciatorPattern::Code { count, period, pause } => {
+                let period = period.num_milliseconds().max(1);
+                let beeps = period * count as i64;
+                let position = elapsed % (beeps + pause.num_milliseconds().max(0)).max(1);
+                position < beeps && position % period < period / 2
+            }
+        }
+    }
+}
+
+/// A single output of an [`AlarmAnnunciator`]
+///
+/// Severities are mapped to patterns. The pattern mapped to the highest active severity is used,
+/// or else the pattern of the nearest lower severity.
+pub struct Signal {
+    name: String,
+    command: IOCommand,
+    patterns: BTreeMap<AlarmSeverity, AnnunciatorPattern>,
+
+    /// Acknowledged alarms are ignored, so that acknowledging silences output
+    silenced_by_ack: bool,
+
+    /// Current pattern and time that it was started
+    current: Option<(AnnunciatorPattern, DateTime<Utc>)>,
+    /// Last value written to output
+    state: Option<bool>,
+}
+
+impl Signal {
+    /// Constructor for [`Signal`] that is silenced when alarms are acknowledged
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of output, used in error messages
+    /// - `command`: Low-level output command of buzzer or strobe
+    ///
+    /// # Panics
+    ///
+    /// If `command` is not [`IOCommand::Output`]
+    pub fn new<N: Into<String>>(name: N, command: IOCommand) -> Self {
+        command.agrees(IODirection::Out)
+            .expect("Command is not output");
+
+        Self {
+            name: name.into(),
+            command,
+            patterns: BTreeMap::new(),
+            silenced_by_ack: true,
+            current: None,
+            state: None,
+        }
+    }
+
+    /// Builder method to set pattern used while the highest active severity is `severity`
+    ///
+    /// Severities below that of every pattern hold output low.
+    pub fn set_pattern(mut self, severity: AlarmSeverity, pattern: AnnunciatorPattern) -> Self {
+        self.patterns.insert(severity, pattern);
+        self
+    }
+
+    /// Builder method to keep signalling acknowledged alarms until they clear (ie: for a strobe)
+    pub fn ignore_acks(mut self) -> Self {
+        self.silenced_by_ack = false;
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Last value written to output
+    pub fn state(&self) -> Option<bool> {
+        self.state
+    }
+
+    /// Current pattern. `None` until first update.
+    pub fn pattern(&self) -> Option<AnnunciatorPattern> {
+        self.current.map(|(pattern, _)| pattern)
+    }
+
+    /// Pattern dictated by active alarms
+    fn select(&self, alarms: &AlarmHandler) -> AnnunciatorPattern {
+        alarms.active()
+            .filter(|alarm| !alarms.is_suppressed(alarm))
+            .filter(|alarm| !(self.silenced_by_ack && alarm.is_acknowledged()))
+            .map(|alarm| alarm.severity())
+            .max()
+            .and_then(|severity| self.patterns.range(..=severity).next_back())
+            .map(|(_, pattern)| *pattern)
+            .unwrap_or(AnnunciatorPattern::Off)
+    }
+
+    fn attempt_at(&mut self, alarms: &AlarmHandler, now: DateTime<Utc>) {
+        let pattern = self.select(alarms);
+        let started = match self.current {
+            // a backwards clock jump restarts pattern so it does not freeze
+            Some((current, started)) if current == pattern && started <= now => started,
+            _ => now,
+        };
+        self.current = Some((pattern, started));
+
+        let state = pattern.state_at(now - started);
+        if self.state != Some(state) {
+            match self.command.execute(RawValue::Binary(state)) {
+                Ok(_) => self.state = Some(state),
+                Err(e) => console::error(format!("Could not write to {}: {}", self.name, e)),
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+/// Signals active alarms on buzzers, strobes, or stack lights
+///
+/// Field hardware signals problems without a screen. Each [`Signal`] maps alarm severities to an
+/// [`AnnunciatorPattern`], and follows alarms as they are raised, acknowledged, and cleared.
+/// Alarms suppressed for maintenance are not signalled.
+///
+/// As with [`crate::action::Heartbeat`], low-level [`IOCommand`]s are called directly, so
+/// toggling does not generate [`crate::io::IOEvent`]s or flood logs.
+///
+/// # Example
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::action::IOCommand;
+/// use sensd::alarm::{AlarmAnnunciator, AlarmHandler, AlarmSeverity, AnnunciatorPattern, Signal};
+/// use sensd::helpers::Def;
+/// use sensd::storage::Group;
+///
+/// let buzzer = Signal::new("buzzer", IOCommand::Output(|_| Ok(())))
+///     .set_pattern(AlarmSeverity::Warning, AnnunciatorPattern::Code {
+///         count: 2,
+///         period: Duration::milliseconds(400),
+///         pause: Duration::seconds(5),
+///     })
+///     .set_pattern(AlarmSeverity::Critical, AnnunciatorPattern::Steady);
+/// let strobe = Signal::new("strobe", IOCommand::Output(|_| Ok(())))
+///     .set_pattern(AlarmSeverity::Critical, AnnunciatorPattern::Flash(Duration::seconds(1)))
+///     .ignore_acks();
+///
+/// let mut group = Group::new("");
+/// group.set_alarm_handler(Def::new(AlarmHandler::default()));
+/// group.set_annunciator(AlarmAnnunciator::new().add_signal(buzzer).add_signal(strobe));
+///
+/// // called from within main event loop
+/// group.attempt_routines();
+/// ```
+pub struct AlarmAnnunciator {
+    signals: Vec<Signal>,
+}
+
+impl AlarmAnnunciator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to add an output
+    pub fn add_signal(mut self, signal: Signal) -> Self {
+        self.signals.push(signal);
+        self
+    }
+
+    pub fn signals(&self) -> &[Signal] {
+        &self.signals
+    }
+
+    /// Update outputs according to active alarms
+    ///
+    /// Should be called as often as possible.
+    pub fn attempt(&mut self, alarms: &AlarmHandler) {
+        self.attempt_at(alarms, now())
+    }
+
+    /// Update outputs according to active alarms, relative to a given time
+    pub fn attempt_at(&mut self, alarms: &AlarmHandler, now: DateTime<Utc>) {
+        for signal in self.signals.iter_mut() {
+            signal.attempt_at(alarms, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::action::IOCommand;
+    use crate::alarm::{AlarmAnnunciator, AlarmHandler, AlarmSeverity, AnnunciatorPattern, Signal};
+
+    #[test]
+    fn code() {
+        let pattern = AnnunciatorPattern::Code {
+            count: 2,
+            period: Duration::milliseconds(200),
+            pause: Duration::milliseconds(600),
+        };
+        let states: Vec<bool> = (0..10)
+            .map(|step| pattern.state_at(Duration::milliseconds(step * 100)))
+            .collect();
+        assert_eq!(vec![true, false, true, false, false, false, false, false, false, false], states);
+        assert!(pattern.state_at(Duration::milliseconds(1000)));
+    }
+
+    #[test]
+    fn follows_alarms() {
+        let buzzer = Signal::new("buzzer", IOCommand::Output(|_| Ok(())))
+            .set_pattern(AlarmSeverity::Warning, AnnunciatorPattern::Flash(Duration::seconds(1)))
+            .set_pattern(AlarmSeverity::Critical, AnnunciatorPattern::Steady);
+        let strobe = Signal::new("strobe", IOCommand::Output(|_| Ok(())))
+            .set_pattern(AlarmSeverity::Warning, AnnunciatorPattern::Steady)
+            .ignore_acks();
+        let mut annunciator = AlarmAnnunciator::new().add_signal(buzzer).add_signal(strobe);
+        let mut alarms = AlarmHandler::default();
+        let now = Utc::now();
+        let states = |annunciator: &AlarmAnnunciator| {
+            annunciator.signals().iter().map(|signal| signal.state()).collect::<Vec<_>>()
+        };
+
+        annunciator.attempt_at(&alarms, now);
+        assert_eq!(vec![Some(false), Some(false)], states(&annunciator));
+
+        alarms.raise("level", AlarmSeverity::Warning, "");
+        annunciator.attempt_at(&alarms, now);
+        assert_eq!(vec![Some(true), Some(true)], states(&annunciator));
+        annunciator.attempt_at(&alarms, now + Duration::milliseconds(600));
+        assert_eq!(vec![Some(false), Some(true)], states(&annunciator));
+
+        // highest severity wins
+        alarms.raise("pressure", AlarmSeverity::Critical, "");
+        annunciator.attempt_at(&alarms, now + Duration::milliseconds(700));
+        assert_eq!(Some(AnnunciatorPattern::Steady), annunciator.signals()[0].pattern());
+        assert_eq!(vec![Some(true), Some(true)], states(&annunciator));
+
+        // acknowledging silences buzzer, but not strobe
+        alarms.acknowledge("level", "operator").unwrap();
+        alarms.acknowledge("pressure", "operator").unwrap();
+        annunciator.attempt_at(&alarms, now + Duration::milliseconds(800));
+        assert_eq!(vec![Some(false), Some(true)], states(&annunciator));
+
+        alarms.clear("level");
+        alarms.clear("pressure");
+        annunciator.attempt_at(&alarms, now + Duration::milliseconds(900));
+        assert_eq!(vec![Some(false), Some(false)], states(&annunciator));
+    }
+}
@@ -0,0 +1,183 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+use crate::alarm::{Alarm, BoxedSink, NotificationSink};
+use crate::errors::ErrorType;
+use crate::helpers::now;
+
+/// Notifications of a single alarm since its window opened
+struct Window {
+    opened: DateTime<Utc>,
+
+    /// Most recent notification, which reflects whether condition is active or cleared
+    latest: Alarm,
+    /// Number of notifications held back
+    held: usize,
+}
+
+/// Sink that merges repeated notifications of the same alarm
+///
+/// Wraps another sink to prevent notification storms, such as from a sensor bouncing around a
+/// threshold. The first notification of an alarm is delivered immediately and opens a window.
+/// Notifications of the same alarm (including recoveries) within the window are held back. Once the
+/// window has elapsed, held notifications are merged into a single message whose
+/// [`Alarm::occurrences()`] is the number of notifications within the window, and which reflects
+/// whether the condition is currently active or cleared.
+///
+/// Escalations to a higher severity are never held back.
+///
+/// Held notifications are delivered by [`NotificationSink::flush()`], which is called by
+/// [`crate::alarm::AlarmHandler::attempt_escalations()`].
+///
+/// # Example
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::alarm::{AlarmHandler, CoalescingSink, ConsoleSink};
+///
+/// let mut alarms = AlarmHandler::default();
+/// alarms.add_sink("console", Box::new(CoalescingSink::new(Box::new(ConsoleSink), Duration::minutes(5))));
+/// ```
+pub struct CoalescingSink {
+    inner: BoxedSink,
+    window: Duration,
+
+    /// Open windows keyed by alarm name
+    windows: HashMap<String, Window>,
+}
+
+impl CoalescingSink {
+    /// Constructor for [`CoalescingSink`]
+    ///
+    /// # Parameters
+    ///
+    /// - `inner`: Sink that notifications are delivered to
+    /// - `window`: Duration that repeated notifications are held back for
+    pub fn new(inner: BoxedSink, window: Duration) -> Self {
+        Self {
+            inner,
+            window,
+            windows: HashMap::new(),
+        }
+    }
+
+    /// Number of notifications currently held back for alarm named `name`
+    pub fn held(&self, name: &str) -> usize {
+        self.windows.get(name).map(|window| window.held).unwrap_or(0)
+    }
+
+    /// Deliver or hold back a notification
+    fn coalesce(&mut self, alarm: &Alarm, now: DateTime<Utc>) -> Result<(), ErrorType> {
+        let result = self.flush(now);
+
+        match self.windows.get_mut(alarm.name()) {
+            Some(window) => {
+                let escalated = !alarm.is_cleared() && alarm.severity() > window.latest.severity();
+                window.latest = alarm.clone();
+                if !escalated {
+                    window.held += 1;
+                    return result;
+                }
+            }
+            None => {
+                self.windows.insert(alarm.name().clone(), Window { opened: now, latest: alarm.clone(), held: 0 });
+            }
+        }
+
+        match alarm.is_cleared() {
+            true => self.inner.recover(alarm)?,
+            false => self.inner.notify(alarm)?,
+        }
+        result
+    }
+}
+
+impl NotificationSink for CoalescingSink {
+    fn notify(&mut self, alarm: &Alarm) -> Result<(), ErrorType> {
+        self.coalesce(alarm, now())
+    }
+
+    fn recover(&mut self, alarm: &Alarm) -> Result<(), ErrorType> {
+        self.coalesce(alarm, now())
+    }
+
+    /// Close every elapsed window, delivering a merged notification if any were held back
+    ///
+    /// A bouncing condition therefore results in at most two messages per window, regardless of
+    /// how often it bounces.
+    fn flush(&mut self, now: DateTime<Utc>) -> Result<(), ErrorType> {
+        let mut result = Ok(());
+        let window = self.window;
+        let inner = &mut self.inner;
+        self.windows.retain(|_, state| {
+            if now - state.opened < window {
+                return true;
+            }
+            if state.held > 0 {
+                let merged = state.latest.clone().set_occurrences(state.held + 1);
+                let delivered = match merged.is_cleared() {
+                    true => inner.recover(&merged),
+                    false => inner.notify(&merged),
+                };
+                if let Err(e) = delivered {
+                    result = Err(e);
+                }
+            }
+            false
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::alarm::{Alarm, AlarmSeverity, CoalescingSink, NotificationSink};
+    use crate::errors::ErrorType;
+    use crate::testkit::FakeClock;
+
+    /// Sink that records rendered notifications
+    struct RecordingSink(Rc<RefCell<Vec<String>>>);
+
+    impl NotificationSink for RecordingSink {
+        fn notify(&mut self, alarm: &Alarm) -> Result<(), ErrorType> {
+            self.0.borrow_mut().push(alarm.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn coalesce() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let clock = FakeClock::install(start);
+        let delivered = Rc::new(RefCell::new(Vec::new()));
+        let mut sink = CoalescingSink::new(Box::new(RecordingSink(delivered.clone())), Duration::minutes(5));
+
+        let alarm = Alarm::new("ph", AlarmSeverity::Warning, "pH low", start);
+        let mut recovery = alarm.clone();
+        recovery.clear(start);
+
+        // sensor bounces around threshold
+        for _ in 0..3 {
+            sink.notify(&alarm).unwrap();
+            sink.recover(&recovery).unwrap();
+        }
+        assert_eq!(vec![String::from("[Warning] ph: pH low")], *delivered.borrow());
+        assert_eq!(5, sink.held("ph"));
+
+        // escalation is delivered immediately
+        sink.notify(&Alarm::new("ph", AlarmSeverity::Critical, "pH low", start)).unwrap();
+        assert_eq!(2, delivered.borrow().len());
+
+        clock.advance(Duration::minutes(5));
+        sink.flush(clock.now()).unwrap();
+        assert_eq!("[Critical] ph: pH low (6 occurrences)", delivered.borrow()[2]);
+
+        // window is closed, so recovery is delivered immediately
+        sink.recover(&recovery).unwrap();
+        assert_eq!("[Cleared] ph: pH low", delivered.borrow()[3]);
+    }
+}
@@ -0,0 +1,361 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::alarm::{Alarm, AlarmHandler, AlarmSeverity};
+use crate::helpers::now;
+use crate::io::{DeviceGetters, EventKind, ValueFormat};
+use crate::name::Name;
+use crate::report::{actuator_stats, ActuatorStats};
+use crate::storage::{Chronicle, Group, Log};
+
+/// Name of the notification passed to sinks when a digest is delivered
+pub const DIGEST_NAME: &str = "digest";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Length of time summarized by a [`Digest`]
+///
+/// Periods are aligned to UTC hours or days.
+pub enum DigestPeriod {
+    Hourly,
+    Daily,
+}
+
+impl DigestPeriod {
+    pub fn duration(&self) -> Duration {
+        match self {
+            DigestPeriod::Hourly => Duration::hours(1),
+            DigestPeriod::Daily => Duration::days(1),
+        }
+    }
+
+    /// Beginning of the period containing `time`
+    pub fn start_of(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        time.duration_trunc(self.duration()).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Statistics of numeric readings from a single input
+pub struct ValueSummary {
+    pub samples: usize,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+impl ValueSummary {
+    /// Summarize a sequence of values
+    ///
+    /// # Returns
+    ///
+    /// `None` if `values` is empty
+    pub fn from_values(values: &[f32]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        Some(Self {
+            samples: values.len(),
+            min: values.iter().cloned().fold(f32::INFINITY, f32::min),
+            max: values.iter().cloned().fold(f32::NEG_INFINITY, f32::max),
+            mean: values.iter().sum::<f32>() / values.len() as f32,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Alarm activity within a digest period
+pub struct AlarmCounts {
+    /// Alarms raised during period. Only counted when a log is set via
+    /// [`AlarmHandler::set_log()`].
+    pub raised: usize,
+
+    /// Alarms active when digest was generated
+    pub active: usize,
+
+    /// Active alarms which have not been acknowledged
+    pub unacknowledged: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Summary of a [`Group`] over a single digest period
+pub struct DigestSummary {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+
+    /// Reading statistics keyed by input name. Inputs without numeric readings are omitted.
+    pub values: BTreeMap<String, ValueSummary>,
+
+    /// Run-time statistics keyed by output name
+    pub actuators: BTreeMap<String, ActuatorStats>,
+
+    pub alarms: AlarmCounts,
+
+    /// Units of readings keyed by input name. See [`crate::io::IOKind::unit()`].
+    #[serde(default)]
+    pub units: BTreeMap<String, String>,
+}
+
+impl DigestSummary {
+    /// Summarize device logs and alarms between `start` and `end`
+    ///
+    /// # Parameters
+    ///
+    /// - `group`: Group whose input and output logs are summarized
+    /// - `alarms`: Source of alarm counts
+    /// - `start`: Beginning of period
+    /// - `end`: End of period
+    pub fn generate(group: &Group, alarms: &AlarmHandler, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        let mut values = BTreeMap::new();
+        let mut units = BTreeMap::new();
+        for device in group.inputs.values() {
+            let device = device.try_lock().unwrap();
+            if let Some(log) = device.log() {
+                if let Some(summary) = summarize(&log.try_lock().unwrap(), start, end) {
+                    values.insert(device.name().clone(), summary);
+                }
+            }
+            if let Some(unit) = device.kind().unit() {
+                units.insert(device.name().clone(), unit.to_string());
+            }
+        }
+
+        let mut actuators = BTreeMap::new();
+        for device in group.outputs.values() {
+            let device = device.try_lock().unwrap();
+            if let Some(log) = device.log() {
+                let stats = actuator_stats(&log.try_lock().unwrap(), start, end);
+                actuators.insert(device.name().clone(), stats);
+            }
+        }
+
+        let raised = alarms.log()
+            .map(|log| {
+                log.try_lock().unwrap().range(start, end)
+                    .filter(|(_, event)| event.kind == EventKind::AlarmRaised)
+                    .count()
+            })
+            .unwrap_or(0);
+        let alarms = AlarmCounts {
+            raised,
+            active: alarms.active().count(),
+            unacknowledged: alarms.active().filter(|alarm| !alarm.is_acknowledged()).count(),
+        };
+
+        Self { start, end, values, actuators, alarms, units }
+    }
+
+    /// Render summary as plain text suitable for email or chat sinks
+    pub fn to_text(&self) -> String {
+        self.to_text_with(&ValueFormat::default())
+    }
+
+    /// Render summary as plain text, formatting readings with `format`
+    ///
+    /// The unit of each input takes precedence over the unit of `format`.
+    pub fn to_text_with(&self, format: &ValueFormat) -> String {
+        let mut text = format!("Digest {} to {}\n", self.start, self.end);
+
+        text.push_str(&format!(
+            "Alarms: {} raised, {} active, {} unacknowledged\n",
+            self.alarms.raised, self.alarms.active, self.alarms.unacknowledged));
+
+        for (name, value) in self.values.iter() {
+            let format = match self.units.get(name) {
+                Some(unit) => format.clone().set_unit(unit.as_str()),
+                None => format.clone(),
+            };
+            text.push_str(&format!(
+                "{}: min {}, max {}, mean {} ({} samples)\n",
+                name, format.format_float(value.min), format.format_float(value.max),
+                format.format_float(value.mean), value.samples));
+        }
+        for (name, stats) in self.actuators.iter() {
+            text.push_str(&format!(
+                "{}: on {:.1}s ({:.1}%), {} activations\n",
+                name, stats.on_seconds, stats.duty_cycle * 100.0, stats.activations));
+        }
+        text
+    }
+}
+
+/// Periodic job which delivers a [`DigestSummary`] through notification sinks
+///
+/// Operators receive routine oversight without checking dashboards. Once each period has elapsed,
+/// the period is summarized and passed to the named sinks of an [`AlarmHandler`] as an
+/// [`AlarmSeverity::Info`] notification named [`DIGEST_NAME`]. The summary text is the message.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{Duration, TimeZone, Utc};
+/// use sensd::alarm::{AlarmHandler, ConsoleSink, Digest, DigestPeriod};
+/// use sensd::storage::Group;
+///
+/// let group = Group::new("");
+/// let mut alarms = AlarmHandler::default();
+/// alarms.add_sink("console", Box::new(ConsoleSink));
+///
+/// let mut digest = Digest::new(DigestPeriod::Hourly, &["console"]);
+///
+/// let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 30, 0).unwrap();
+/// assert!(digest.attempt_at(&group, &mut alarms, start).is_none());
+///
+/// // first full hour has elapsed
+/// let summary = digest.attempt_at(&group, &mut alarms, start + Duration::hours(1)).unwrap();
+/// assert_eq!(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(), summary.start);
+/// ```
+pub struct Digest {
+    period: DigestPeriod,
+    sinks: Vec<String>,
+
+    /// End of the next period to summarize. `None` until first attempt.
+    next: Option<DateTime<Utc>>,
+}
+
+impl Digest {
+    /// # Parameters
+    ///
+    /// - `period`: Length of time summarized by each digest
+    /// - `sinks`: Names of [`AlarmHandler`] sinks which receive digests
+    pub fn new<S>(period: DigestPeriod, sinks: &[S]) -> Self
+    where
+        S: AsRef<str>
+    {
+        Self {
+            period,
+            sinks: sinks.iter().map(|s| s.as_ref().to_string()).collect(),
+            next: None,
+        }
+    }
+
+    pub fn period(&self) -> DigestPeriod {
+        self.period
+    }
+
+    /// Deliver digest if the current period has elapsed
+    ///
+    /// This should be called regularly from the main event loop.
+    ///
+    /// # Returns
+    ///
+    /// The delivered [`DigestSummary`], or `None` if no period has elapsed
+    pub fn attempt(&mut self, group: &Group, alarms: &mut AlarmHandler) -> Option<DigestSummary> {
+        self.attempt_at(group, alarms, now())
+    }
+
+    /// Deliver digest relative to a given time
+    ///
+    /// The first call only schedules the end of the current period, so that partial periods are
+    /// not summarized. If several periods elapsed since the last attempt, only the most recent is
+    /// delivered.
+    ///
+    /// # Parameters
+    ///
+    /// - `group`: Group to summarize
+    /// - `alarms`: Source of alarm counts and notification sinks
+    /// - `now`: Current time
+    pub fn attempt_at(
+        &mut self,
+        group: &Group,
+        alarms: &mut AlarmHandler,
+        now: DateTime<Utc>,
+    ) -> Option<DigestSummary> {
+        let current = self.period.start_of(now);
+        let next = self.next.get_or_insert(current + self.period.duration());
+        if now < *next {
+            return None;
+        }
+        *next = current + self.period.duration();
+
+        let summary = DigestSummary::generate(group, alarms, current - self.period.duration(), current);
+        let notification = Alarm::new(DIGEST_NAME, AlarmSeverity::Info, summary.to_text(), now);
+        alarms.deliver(&self.sinks, &notification);
+        Some(summary)
+    }
+}
+
+/// Statistics of numeric readings within `[start, end)`
+fn summarize(log: &Log, start: DateTime<Utc>, end: DateTime<Utc>) -> Option<ValueSummary> {
+    let values: Vec<f32> = log.range(start, end)
+        .filter(|(_, event)| event.kind == EventKind::SensorRead)
+        .filter_map(|(_, event)| event.value.finite_float())
+        .collect();
+    ValueSummary::from_values(&values)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::alarm::{Alarm, AlarmHandler, AlarmSeverity, Digest, DigestPeriod, NotificationSink, DIGEST_NAME};
+    use crate::errors::ErrorType;
+    use crate::helpers::Def;
+    use crate::io::{Device, DeviceMetadata, IOEvent, Input, Output, RawValue};
+    use crate::storage::{Chronicle, Group, Log};
+
+    struct Recorder(Rc<RefCell<Vec<Alarm>>>);
+
+    impl NotificationSink for Recorder {
+        fn notify(&mut self, alarm: &Alarm) -> Result<(), ErrorType> {
+            self.0.borrow_mut().push(alarm.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn attempt_at() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let at = |minutes| start + Duration::minutes(minutes);
+
+        let mut group = Group::new("");
+        group
+            .push_input(Input::new("ph", 0, None).init_log())
+            .push_output(Output::new("pump", 0, None).init_log());
+
+        let input = group.inputs.values().next().unwrap().try_lock().unwrap().log().unwrap();
+        for (minutes, value) in [(10, 6.0), (20, 7.0), (30, 8.0), (70, 100.0)] {
+            input.try_lock().unwrap().push(IOEvent::with_timestamp(at(minutes), RawValue::Float(value))).unwrap();
+        }
+        let output = group.outputs.values().next().unwrap().try_lock().unwrap().log().unwrap();
+        for (minutes, value) in [(15, true), (21, false)] {
+            output.try_lock().unwrap().push(IOEvent::with_timestamp(at(minutes), RawValue::Binary(value))).unwrap();
+        }
+
+        let delivered = Rc::new(RefCell::new(Vec::new()));
+        let mut alarms = AlarmHandler::default();
+        alarms
+            .add_sink("recorder", Box::new(Recorder(delivered.clone())))
+            .set_log(Def::new(Log::with_metadata(&DeviceMetadata::default())));
+        alarms.raise("overtemp", AlarmSeverity::Warning, "");
+
+        let mut digest = Digest::new(DigestPeriod::Hourly, &["recorder"]);
+        assert!(digest.attempt_at(&group, &mut alarms, at(5)).is_none());
+        assert!(digest.attempt_at(&group, &mut alarms, at(59)).is_none());
+
+        let summary = digest.attempt_at(&group, &mut alarms, at(61)).unwrap();
+        assert_eq!(start, summary.start);
+        assert_eq!(at(60), summary.end);
+
+        let ph = &summary.values["ph"];
+        assert_eq!(3, ph.samples);
+        assert_eq!(6.0, ph.min);
+        assert_eq!(8.0, ph.max);
+        assert_eq!(7.0, ph.mean);
+
+        assert_eq!(1, summary.actuators["pump"].activations);
+        assert_eq!(360.0, summary.actuators["pump"].on_seconds);
+        assert_eq!(1, summary.alarms.active);
+        assert_eq!(1, summary.alarms.unacknowledged);
+
+        assert_eq!(1, delivered.borrow().len());
+        assert_eq!(DIGEST_NAME, delivered.borrow()[0].name());
+        assert_eq!(&summary.to_text(), delivered.borrow()[0].message());
+
+        // next period has not elapsed
+        assert!(digest.attempt_at(&group, &mut alarms, at(90)).is_none());
+        assert_eq!(1, delivered.borrow().len());
+    }
+}
@@ -0,0 +1,85 @@
+use chrono::Duration;
+use crate::alarm::AlarmSeverity;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A single step of an [`EscalationPolicy`]
+pub struct EscalationTier {
+    /// Time since alarm was raised before this tier is applied
+    pub after: Duration,
+
+    /// Severity to escalate to
+    pub severity: AlarmSeverity,
+
+    /// Names of additional sinks to notify
+    pub sinks: Vec<String>,
+}
+
+impl EscalationTier {
+    pub fn new<S>(after: Duration, severity: AlarmSeverity, sinks: &[S]) -> Self
+    where
+        S: AsRef<str>
+    {
+        Self {
+            after,
+            severity,
+            sinks: sinks.iter().map(|s| s.as_ref().to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Describes who is notified when an alarm is raised, and how an unacknowledged alarm escalates
+///
+/// # Example
+///
+/// First notify via a webhook, then send an SMS if the alarm is not acknowledged within 10 minutes:
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::alarm::{AlarmSeverity, EscalationPolicy, EscalationTier};
+///
+/// let policy =
+///     EscalationPolicy::new(&["webhook"])
+///         .add_tier(EscalationTier::new(Duration::minutes(10), AlarmSeverity::Critical, &["sms"]));
+///
+/// assert_eq!(1, policy.tiers().len());
+/// ```
+pub struct EscalationPolicy {
+    sinks: Vec<String>,
+    tiers: Vec<EscalationTier>,
+}
+
+impl EscalationPolicy {
+    /// Constructor for [`EscalationPolicy`]
+    ///
+    /// # Parameters
+    ///
+    /// - `sinks`: Names of sinks to notify when alarm is first raised
+    pub fn new<S>(sinks: &[S]) -> Self
+    where
+        S: AsRef<str>
+    {
+        Self {
+            sinks: sinks.iter().map(|s| s.as_ref().to_string()).collect(),
+            tiers: Vec::default(),
+        }
+    }
+
+    /// Builder method for adding an escalation tier
+    ///
+    /// Tiers are kept sorted by [`EscalationTier::after`].
+    pub fn add_tier(mut self, tier: EscalationTier) -> Self {
+        self.tiers.push(tier);
+        self.tiers.sort_by_key(|tier| tier.after);
+        self
+    }
+
+    /// Names of sinks to notify when alarm is first raised
+    pub fn sinks(&self) -> &[String] {
+        &self.sinks
+    }
+
+    pub fn tiers(&self) -> &[EscalationTier] {
+        &self.tiers
+    }
+}
@@ -0,0 +1,776 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Values;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::alarm::{AckRecord, Alarm, AlarmSeverity, AlarmTransition, BoxedSink, EscalationPolicy};
+use crate::console;
+use crate::errors::{AlarmError, ErrorType, FilesystemError};
+use crate::helpers::{check_results, now, writable_or_create, Def};
+use crate::io::{EventKind, IOEvent, RawValue};
+use crate::storage::{append_record, read_records, Chronicle, Document, Log, Persistent, FILETYPE};
+
+/// Filename used for persisting alarm records
+const ALARM_FN: &str = "alarms";
+
+/// Filename of alarm audit trail within directory set by [`Document::set_dir()`]
+pub const ALARM_HISTORY_FILENAME: &str = "alarm_history.jsonl";
+
+#[derive(Serialize, Deserialize, Default)]
+/// Manages active alarms, notification sinks, escalation, and acknowledgement
+///
+/// Alarms are raised with [`AlarmHandler::raise()`] and remain active until the alarm condition is
+/// cleared by [`AlarmHandler::clear()`]. While an alarm is active and unacknowledged,
+/// [`AlarmHandler::attempt_escalations()`] applies the tiers of the associated [`EscalationPolicy`]:
+/// severity is increased and additional sinks are notified.
+///
+/// A per-alarm policy is set by [`AlarmHandler::set_policy()`], otherwise the default policy is used.
+///
+/// # Example
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::alarm::{AlarmHandler, AlarmSeverity, ConsoleSink, EscalationPolicy, EscalationTier};
+///
+/// let mut alarms = AlarmHandler::default();
+/// alarms
+///     .add_sink("console", Box::new(ConsoleSink))
+///     .set_default_policy(
+///         EscalationPolicy::new(&["console"])
+///             .add_tier(EscalationTier::new(Duration::minutes(5), AlarmSeverity::Critical, &["console"])));
+///
+/// alarms.raise("overtemp", AlarmSeverity::Warning, "Temperature exceeded 30°C");
+/// assert_eq!(1, alarms.active().count());
+///
+/// alarms.acknowledge("overtemp", "operator").unwrap();
+/// assert_eq!(1, alarms.acknowledgements().len());
+/// ```
+///
+/// # Persistence
+///
+/// Active alarms and acknowledgement records are persisted via [`Persistent`] to a dedicated file
+/// within the directory set by [`Document::set_dir()`]. When the directory exists, the file is also
+/// saved after every transition, so that a restart does not silently clear an active alarm.
+/// [`Persistent::load()`] re-raises persisted alarms: unacknowledged alarms are delivered to sinks
+/// again and must be acknowledged through [`AlarmHandler::acknowledge()`].
+///
+/// Raised and cleared alarms are additionally recorded as [`EventKind::AlarmRaised`] and
+/// [`EventKind::AlarmCleared`] events when a [`Log`] is set via [`AlarmHandler::set_log()`].
+///
+/// When the directory exists, every [`AlarmTransition`] is appended to an audit trail, from which
+/// active alarms are reconstructed after a crash by [`AlarmHandler::replay()`].
+///
+/// # Suppression
+///
+/// Alarms associated with a device (see [`Alarm::set_device()`]) may be suppressed during
+/// maintenance by [`AlarmHandler::suppress()`]. Suppressed alarms are tracked, logged, and recorded
+/// as usual, but sinks are not notified and escalation is deferred until
+/// [`AlarmHandler::unsuppress()`] is called.
+pub struct AlarmHandler {
+    #[serde(default)]
+    active: HashMap<String, Alarm>,
+
+    acknowledged: Vec<AckRecord>,
+
+    #[serde(skip)]
+    default_policy: EscalationPolicy,
+    #[serde(skip)]
+    policies: HashMap<String, EscalationPolicy>,
+    #[serde(skip)]
+    sinks: HashMap<String, BoxedSink>,
+
+    /// Alarms raised while suppressed, keyed by device name
+    #[serde(skip)]
+    suppressed: HashMap<String, Vec<Alarm>>,
+
+    #[serde(skip)]
+    dir: Option<PathBuf>,
+
+    #[serde(skip)]
+    log: Option<Def<Log>>,
+}
+
+impl AlarmHandler {
+    /// Add a notification sink
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name used to reference sink from [`EscalationPolicy`]
+    /// - `sink`: Boxed sink
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn add_sink<N>(&mut self, name: N, sink: BoxedSink) -> &mut Self
+    where
+        N: Into<String>
+    {
+        self.sinks.insert(name.into(), sink);
+        self
+    }
+
+    /// Set log used to record alarm transitions
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_log(&mut self, log: Def<Log>) -> &mut Self {
+        self.log = Some(log);
+        self
+    }
+
+    /// Set policy used for alarms without a dedicated policy
+    pub fn set_default_policy(&mut self, policy: EscalationPolicy) -> &mut Self {
+        self.default_policy = policy;
+        self
+    }
+
+    /// Set a dedicated policy for alarms named `name`
+    pub fn set_policy<N>(&mut self, name: N, policy: EscalationPolicy) -> &mut Self
+    where
+        N: Into<String>
+    {
+        self.policies.insert(name.into(), policy);
+        self
+    }
+
+    /// Get policy associated with alarm name
+    ///
+    /// # Returns
+    ///
+    /// Dedicated policy if one is set, otherwise the default policy
+    pub fn policy(&self, name: &str) -> &EscalationPolicy {
+        self.policies.get(name).unwrap_or(&self.default_policy)
+    }
+
+    /// Raise an alarm
+    ///
+    /// Sinks listed by the alarm's policy are notified. If an alarm of the same name is already
+    /// active, then nothing happens.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Unique name of alarm condition
+    /// - `severity`: Initial severity
+    /// - `message`: Human readable description
+    ///
+    /// # Returns
+    ///
+    /// Reference to active [`Alarm`]
+    ///
+    /// # See Also
+    ///
+    /// - [`AlarmHandler::raise_alarm()`] for raising an alarm with device context
+    pub fn raise<N, M>(&mut self, name: N, severity: AlarmSeverity, message: M) -> &Alarm
+    where
+        N: Into<String>,
+        M: Into<String>,
+    {
+        self.raise_alarm(Alarm::new(name, severity, message, now()))
+    }
+
+    /// Raise a pre-built alarm
+    ///
+    /// Behaves like [`AlarmHandler::raise()`] but allows device name, value, and threshold to be
+    /// passed to sinks. Sinks are not notified if the device of `alarm` is suppressed.
+    ///
+    /// # Parameters
+    ///
+    /// - `alarm`: New alarm. Ignored if an alarm of the same name is already active.
+    ///
+    /// # Returns
+    ///
+    /// Reference to active [`Alarm`]
+    pub fn raise_alarm(&mut self, alarm: Alarm) -> &Alarm {
+        let name = alarm.name().clone();
+        if !self.active.contains_key(&name) {
+            match alarm.device().and_then(|device| self.suppressed.get_mut(device)) {
+                Some(suppressed) => suppressed.push(alarm.clone()),
+                None => {
+                    let policy = self.policies.get(&name).unwrap_or(&self.default_policy);
+                    notify(&mut self.sinks, policy.sinks(), &alarm);
+                }
+            }
+
+            let value = alarm.value().unwrap_or(RawValue::Binary(true));
+            self.push_to_log(&IOEvent::with_timestamp(alarm.raised(), value)
+                .set_kind(EventKind::AlarmRaised));
+
+            self.record(&AlarmTransition::Raised { alarm: alarm.clone() });
+            self.active.insert(name.clone(), alarm);
+        }
+        self.active.get(&name).unwrap()
+    }
+
+    /// Clear an active alarm once the alarm condition no longer exists
+    ///
+    /// A recovery notification is delivered to the initial sinks of the alarm's policy, unless the
+    /// alarm is suppressed.
+    ///
+    /// # Returns
+    ///
+    /// The cleared [`Alarm`], or `None` if no alarm named `name` is active
+    pub fn clear(&mut self, name: &str) -> Option<Alarm> {
+        let mut alarm = self.active.remove(name)?;
+        let now = now();
+        alarm.clear(now);
+
+        if !self.is_suppressed(&alarm) {
+            let policy = self.policies.get(name).unwrap_or(&self.default_policy);
+            recover(&mut self.sinks, policy.sinks(), &alarm);
+        }
+
+        self.push_to_log(&IOEvent::new(RawValue::Binary(false))
+            .set_kind(EventKind::AlarmCleared));
+        self.record(&AlarmTransition::Cleared { timestamp: now, name: name.to_string() });
+        Some(alarm)
+    }
+
+    /// Acknowledge an active alarm
+    ///
+    /// Acknowledged alarms are no longer escalated and a persisted [`AckRecord`] is stored.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of active alarm
+    /// - `by`: Name of user or process acknowledging alarm
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with reference to stored [`AckRecord`]
+    /// - `Err` if alarm is not active or has already been acknowledged
+    pub fn acknowledge<B>(&mut self, name: &str, by: B) -> Result<&AckRecord, AlarmError>
+    where
+        B: Into<String>
+    {
+        let alarm = self.active.get_mut(name)
+            .ok_or_else(|| AlarmError::UnknownAlarm { name: name.to_string() })?;
+
+        if alarm.is_acknowledged() {
+            return Err(AlarmError::AlreadyAcknowledged { name: name.to_string() });
+        }
+
+        let now = now();
+        alarm.acknowledge(now);
+
+        let record = AckRecord {
+            alarm: alarm.clone(),
+            acknowledged: now,
+            by: by.into(),
+        };
+        self.record(&AlarmTransition::Acknowledged {
+            timestamp: now,
+            name: name.to_string(),
+            by: record.by.clone(),
+        });
+        self.acknowledged.push(record);
+        Ok(self.acknowledged.last().unwrap())
+    }
+
+    /// Escalate any active, unacknowledged alarms that have exceeded the next tier in their policy
+    ///
+    /// Notifications held back by sinks (see [`crate::alarm::CoalescingSink`]) are also delivered.
+    ///
+    /// This should be called regularly from the main event loop.
+    pub fn attempt_escalations(&mut self) {
+        let now = now();
+        self.escalate_at(now);
+        self.flush_at(now);
+    }
+
+    /// Deliver notifications held back by sinks until `now`
+    ///
+    /// Errors are printed to stderr, and do not prevent flushing other sinks.
+    pub fn flush_at(&mut self, now: DateTime<Utc>) {
+        let results: Vec<Result<(), ErrorType>> = self.sinks.values_mut()
+            .map(|sink| sink.flush(now))
+            .collect();
+        let _ = check_results(&results);
+    }
+
+    /// Escalate alarms relative to a given time
+    ///
+    /// # Parameters
+    ///
+    /// - `now`: Time used to determine how long alarms have been active
+    pub fn escalate_at(&mut self, now: DateTime<Utc>) {
+        let mut transitions = Vec::new();
+        for alarm in self.active.values_mut() {
+            let suppressed = alarm.device().is_some_and(|device| self.suppressed.contains_key(device));
+            if alarm.is_acknowledged() || suppressed {
+                continue;
+            }
+
+            let policy = self.policies.get(alarm.name()).unwrap_or(&self.default_policy);
+
+            // apply all tiers that have elapsed
+            while let Some(tier) = policy.tiers().get(alarm.tier()) {
+                if now - alarm.raised() < tier.after {
+                    break;
+                }
+                alarm.escalate(tier.severity);
+                notify(&mut self.sinks, &tier.sinks, alarm);
+                transitions.push(AlarmTransition::Escalated {
+                    timestamp: now,
+                    name: alarm.name().clone(),
+                    severity: tier.severity,
+                });
+            }
+        }
+        for transition in transitions.iter() {
+            self.record(transition);
+        }
+    }
+
+    /// Suppress notification and escalation of alarms associated with `device`
+    ///
+    /// Has no effect if `device` is already suppressed.
+    ///
+    /// # Parameters
+    ///
+    /// - `device`: Name of device. See [`Alarm::set_device()`].
+    pub fn suppress<N>(&mut self, device: N) -> &mut Self
+    where
+        N: Into<String>
+    {
+        self.suppressed.entry(device.into()).or_default();
+        self
+    }
+
+    /// Resume notification and escalation of alarms associated with `device`
+    ///
+    /// Alarms that were raised while suppressed and are still active are not delivered to sinks,
+    /// but are escalated by the next call to [`AlarmHandler::attempt_escalations()`].
+    ///
+    /// # Returns
+    ///
+    /// Alarms raised while `device` was suppressed
+    pub fn unsuppress(&mut self, device: &str) -> Vec<Alarm> {
+        self.suppressed.remove(device).unwrap_or_default()
+    }
+
+    /// Check if notifications of `alarm` are suppressed
+    pub fn is_suppressed(&self, alarm: &Alarm) -> bool {
+        alarm.device().is_some_and(|device| self.suppressed.contains_key(device))
+    }
+
+    /// Get specific active alarm
+    pub fn get(&self, name: &str) -> Option<&Alarm> {
+        self.active.get(name)
+    }
+
+    /// Iterator over active alarms
+    pub fn active(&self) -> Values<'_, String, Alarm> {
+        self.active.values()
+    }
+
+    /// All acknowledgement records
+    pub fn acknowledgements(&self) -> &[AckRecord] {
+        &self.acknowledged
+    }
+
+    /// Deliver a notification to named sinks without raising an alarm
+    ///
+    /// This is used for informational notices, such as a [`crate::alarm::Digest`]. The
+    /// notification is neither tracked as active nor logged.
+    ///
+    /// # Parameters
+    ///
+    /// - `sinks`: Names of sinks to notify
+    /// - `alarm`: Notification to deliver
+    pub fn deliver(&mut self, sinks: &[String], alarm: &Alarm) {
+        notify(&mut self.sinks, sinks, alarm);
+    }
+
+    /// Reconstruct active alarms by replaying the audit trail
+    ///
+    /// Any active alarms are replaced. Sinks are not notified of replayed alarms.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with number of replayed transitions. Zero if no directory is set or no trail exists.
+    /// - `Err` if audit trail could not be read
+    pub fn replay(&mut self) -> Result<usize, ErrorType> {
+        let path = match &self.dir {
+            Some(dir) => dir.join(ALARM_HISTORY_FILENAME),
+            None => return Ok(0),
+        };
+        let transitions: Vec<AlarmTransition> = read_records(&path)?;
+
+        self.active.clear();
+        for transition in transitions.iter() {
+            match transition {
+                AlarmTransition::Raised { alarm } => {
+                    self.active.insert(alarm.name().clone(), alarm.clone());
+                }
+                AlarmTransition::Escalated { name, severity, .. } => {
+                    if let Some(alarm) = self.active.get_mut(name) {
+                        alarm.escalate(*severity);
+                    }
+                }
+                AlarmTransition::Acknowledged { timestamp, name, .. } => {
+                    if let Some(alarm) = self.active.get_mut(name) {
+                        alarm.acknowledge(*timestamp);
+                    }
+                }
+                AlarmTransition::Cleared { name, .. } => {
+                    self.active.remove(name);
+                }
+            }
+        }
+        Ok(transitions.len())
+    }
+
+    /// Append transition to audit trail and persist active alarms, if directory exists
+    fn record(&self, transition: &AlarmTransition) {
+        if let Some(dir) = self.dir.as_ref().filter(|dir| dir.exists()) {
+            if let Err(e) = append_record(&dir.join(ALARM_HISTORY_FILENAME), transition) {
+                console::error(format!("Could not record alarm transition: {}", e));
+            }
+            if let Err(e) = self.save() {
+                console::error(format!("Could not persist active alarms: {}", e));
+            }
+        }
+    }
+
+    /// Re-raise alarms that were active before a restart
+    ///
+    /// Alarms that are already active are left untouched. Unacknowledged alarms are delivered to
+    /// the sinks of their policy, while acknowledged alarms are restored silently. The original
+    /// time of raising is retained, so that escalation continues where it left off.
+    ///
+    /// # Returns
+    ///
+    /// Number of re-raised alarms
+    fn restore_active(&mut self, alarms: HashMap<String, Alarm>) -> usize {
+        let mut count = 0;
+        for (name, alarm) in alarms {
+            if self.active.contains_key(&name) {
+                continue;
+            }
+            if !alarm.is_acknowledged() {
+                let policy = self.policies.get(&name).unwrap_or(&self.default_policy);
+                notify(&mut self.sinks, policy.sinks(), &alarm);
+            }
+            self.active.insert(name, alarm);
+            count += 1;
+        }
+        count
+    }
+}
+
+/// Deliver notification to named sinks
+///
+/// Errors during delivery and missing sinks are printed to stderr but do not halt delivery to
+/// other sinks.
+fn notify(sinks: &mut HashMap<String, BoxedSink>, names: &[String], alarm: &Alarm) {
+    let mut results = Vec::new();
+    for name in names {
+        match sinks.get_mut(name) {
+            Some(sink) => results.push(sink.notify(alarm)),
+            None => console::warning(format!("Unknown notification sink \"{}\"", name)),
+        }
+    }
+    let _ = check_results(&results);
+}
+
+/// Deliver recovery notification to named sinks
+///
+/// Behaves like [`notify()`], but uses [`crate::alarm::NotificationSink::recover()`].
+fn recover(sinks: &mut HashMap<String, BoxedSink>, names: &[String], alarm: &Alarm) {
+    let mut results = Vec::new();
+    for name in names {
+        match sinks.get_mut(name) {
+            Some(sink) => results.push(sink.recover(alarm)),
+            None => console::warning(format!("Unknown notification sink \"{}\"", name)),
+        }
+    }
+    let _ = check_results(&results);
+}
+
+impl Chronicle for AlarmHandler {
+    fn log(&self) -> Option<Def<Log>> {
+        self.log.clone()
+    }
+}
+
+impl Document for AlarmHandler {
+    fn dir(&self) -> Option<&PathBuf> {
+        self.dir.as_ref()
+    }
+
+    fn set_dir_ref<P>(&mut self, path: P) -> &mut Self
+        where Self: Sized,
+              P: AsRef<Path>
+    {
+        self.dir = Some(PathBuf::from(path.as_ref()));
+        self
+    }
+
+    fn filename(&self) -> String {
+        format!("{}{}", ALARM_FN, FILETYPE)
+    }
+}
+
+impl Persistent for AlarmHandler {
+    /// Save active alarms and acknowledgement records to disk in JSON format
+    fn save(&self) -> Result<(), ErrorType> {
+        let file = writable_or_create(self.full_path());
+        // file shrinks when alarms are cleared
+        file.set_len(0)?;
+        let writer = BufWriter::new(file);
+
+        match serde_json::to_writer_pretty(writer, &self) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let msg = e.to_string();
+                Err(Box::new(FilesystemError::SerializationError { msg }))
+            }
+        }
+    }
+
+    /// Load alarm records from disk and re-raise persisted active alarms
+    ///
+    /// Any acknowledgement records already stored in memory are replaced.
+    fn load(&mut self) -> Result<(), ErrorType> {
+        let file = File::open(self.full_path())?;
+        let reader = BufReader::new(file);
+
+        let buff: AlarmHandler = match serde_json::from_reader(reader) {
+            Ok(data) => data,
+            Err(e) => {
+                let msg = e.to_string();
+                return Err(Box::new(FilesystemError::SerializationError { msg }));
+            }
+        };
+        self.acknowledged = buff.acknowledged;
+        self.restore_active(buff.active);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use std::cell::RefCell;
+    use std::fs::{create_dir_all, remove_dir_all, remove_file};
+    use std::rc::Rc;
+
+    use crate::alarm::{Alarm, AlarmHandler, AlarmSeverity, EscalationPolicy, EscalationTier, NotificationSink};
+    use crate::errors::ErrorType;
+    use crate::helpers::Def;
+    use crate::io::EventKind;
+    use crate::storage::{Document, Log, Persistent};
+
+    type Recorded = Rc<RefCell<Vec<String>>>;
+
+    /// Sink that records names of notified alarms
+    struct RecordingSink(Recorded);
+
+    impl NotificationSink for RecordingSink {
+        fn notify(&mut self, alarm: &Alarm) -> Result<(), ErrorType> {
+            self.0.borrow_mut().push(alarm.name().clone());
+            Ok(())
+        }
+    }
+
+    fn handler() -> (AlarmHandler, Recorded, Recorded) {
+        let first = Rc::new(RefCell::new(Vec::new()));
+        let second = Rc::new(RefCell::new(Vec::new()));
+
+        let mut alarms = AlarmHandler::default();
+        alarms
+            .add_sink("first", Box::new(RecordingSink(first.clone())))
+            .add_sink("second", Box::new(RecordingSink(second.clone())))
+            .set_default_policy(
+                EscalationPolicy::new(&["first"])
+                    .add_tier(EscalationTier::new(Duration::minutes(5), AlarmSeverity::Warning, &["second"]))
+                    .add_tier(EscalationTier::new(Duration::minutes(10), AlarmSeverity::Critical, &["first", "second"])));
+
+        (alarms, first, second)
+    }
+
+    #[test]
+    fn raise() {
+        let (mut alarms, first, second) = handler();
+
+        alarms.raise("a", AlarmSeverity::Info, "");
+        // raising an active alarm does not notify again
+        alarms.raise("a", AlarmSeverity::Info, "");
+
+        assert_eq!(1, first.borrow().len());
+        assert_eq!(0, second.borrow().len());
+        assert_eq!(1, alarms.active().count());
+
+        assert!(alarms.clear("a").is_some());
+        assert_eq!(0, alarms.active().count());
+    }
+
+    #[test]
+    fn suppression() {
+        let (mut alarms, first, second) = handler();
+        let raised = Utc::now();
+        alarms.suppress("pump");
+
+        alarms.raise_alarm(Alarm::new("a", AlarmSeverity::Info, "", raised).set_device("pump"));
+        alarms.raise_alarm(Alarm::new("b", AlarmSeverity::Info, "", raised).set_device("fan"));
+        assert_eq!(vec!["b".to_string()], *first.borrow());
+        assert_eq!(2, alarms.active().count());
+
+        // escalation is deferred
+        alarms.escalate_at(raised + Duration::minutes(6));
+        assert_eq!(AlarmSeverity::Info, alarms.get("a").unwrap().severity());
+        assert_eq!(vec!["b".to_string()], *second.borrow());
+
+        let suppressed = alarms.unsuppress("pump");
+        assert_eq!(1, suppressed.len());
+        assert_eq!("a", suppressed[0].name());
+        alarms.escalate_at(raised + Duration::minutes(6));
+        assert_eq!(AlarmSeverity::Warning, alarms.get("a").unwrap().severity());
+    }
+
+    #[test]
+    fn escalation() {
+        let (mut alarms, first, second) = handler();
+        let raised = Utc::now();
+
+        alarms.raise("a", AlarmSeverity::Info, "");
+
+        alarms.escalate_at(raised + Duration::minutes(1));
+        assert_eq!(AlarmSeverity::Info, alarms.get("a").unwrap().severity());
+
+        alarms.escalate_at(raised + Duration::minutes(6));
+        assert_eq!(AlarmSeverity::Warning, alarms.get("a").unwrap().severity());
+        assert_eq!(1, second.borrow().len());
+
+        // tier is only applied once
+        alarms.escalate_at(raised + Duration::minutes(7));
+        assert_eq!(1, second.borrow().len());
+
+        alarms.escalate_at(raised + Duration::minutes(11));
+        assert_eq!(AlarmSeverity::Critical, alarms.get("a").unwrap().severity());
+        assert_eq!(2, first.borrow().len());
+        assert_eq!(2, second.borrow().len());
+    }
+
+    #[test]
+    fn per_alarm_policy() {
+        let (mut alarms, first, second) = handler();
+        alarms.set_policy("b", EscalationPolicy::new(&["second"]));
+
+        alarms.raise("b", AlarmSeverity::Info, "");
+        alarms.escalate_at(Utc::now() + Duration::minutes(11));
+
+        assert_eq!(0, first.borrow().len());
+        assert_eq!(1, second.borrow().len());
+        assert_eq!(AlarmSeverity::Info, alarms.get("b").unwrap().severity());
+    }
+
+    #[test]
+    fn acknowledge() {
+        let (mut alarms, _, second) = handler();
+
+        assert!(alarms.acknowledge("a", "").is_err());
+
+        alarms.raise("a", AlarmSeverity::Info, "");
+        alarms.acknowledge("a", "operator").unwrap();
+        assert!(alarms.acknowledge("a", "operator").is_err());
+
+        // acknowledged alarms are not escalated
+        alarms.escalate_at(Utc::now() + Duration::minutes(11));
+        assert_eq!(0, second.borrow().len());
+        assert_eq!(AlarmSeverity::Info, alarms.get("a").unwrap().severity());
+    }
+
+    #[test]
+    fn log() {
+        let (mut alarms, _, _) = handler();
+        let log = Def::new(Log::default());
+        alarms.set_log(log.clone());
+
+        alarms.raise("a", AlarmSeverity::Info, "");
+        alarms.clear("a");
+        // clearing an inactive alarm is not logged
+        alarms.clear("a");
+
+        let log = log.try_lock().unwrap();
+        assert_eq!(1, log.filter_kind(EventKind::AlarmRaised).count());
+        assert_eq!(1, log.filter_kind(EventKind::AlarmCleared).count());
+    }
+
+    #[test]
+    fn load_save() {
+        const TMP_DIR: &str = "/tmp/sensd/alarm_tests";
+
+        let (mut alarms, _, _) = handler();
+        alarms.set_dir_ref(TMP_DIR);
+
+        alarms.raise("a", AlarmSeverity::Info, "");
+        alarms.acknowledge("a", "operator").unwrap();
+        alarms.save().unwrap();
+
+        let mut loaded = AlarmHandler::default().set_dir(TMP_DIR);
+        loaded.load().unwrap();
+
+        assert_eq!(alarms.acknowledgements(), loaded.acknowledgements());
+
+        remove_file(alarms.full_path()).unwrap();
+    }
+
+    #[test]
+    /// Assert that active alarms survive a restart and still require acknowledgement
+    fn restart() {
+        const TMP_DIR: &str = "/tmp/sensd/alarm_restart_tests";
+        create_dir_all(TMP_DIR).unwrap();
+
+        let (mut alarms, _, _) = handler();
+        alarms.set_dir_ref(TMP_DIR);
+        alarms.raise("overtemp", AlarmSeverity::Critical, "");
+        alarms.raise("b", AlarmSeverity::Info, "");
+        alarms.raise("c", AlarmSeverity::Info, "");
+        alarms.acknowledge("b", "operator").unwrap();
+        alarms.clear("c");
+
+        let (mut restarted, first, _) = handler();
+        restarted.set_dir_ref(TMP_DIR);
+        restarted.load().unwrap();
+
+        assert_eq!(2, restarted.active().count());
+        assert!(restarted.get("b").unwrap().is_acknowledged());
+        // only unacknowledged alarms are delivered again
+        assert_eq!(vec![String::from("overtemp")], *first.borrow());
+        restarted.acknowledge("overtemp", "operator").unwrap();
+
+        let _ = remove_dir_all(TMP_DIR);
+    }
+
+    #[test]
+    fn replay() {
+        const TMP_DIR: &str = "/tmp/sensd/alarm_replay_tests";
+        create_dir_all(TMP_DIR).unwrap();
+
+        let (mut alarms, _, _) = handler();
+        alarms.set_dir_ref(TMP_DIR);
+
+        let raised = Utc::now();
+        alarms.raise("a", AlarmSeverity::Info, "");
+        alarms.raise("b", AlarmSeverity::Info, "");
+        alarms.escalate_at(raised + Duration::minutes(6));
+        alarms.acknowledge("a", "operator").unwrap();
+        alarms.clear("b");
+
+        let (mut replayed, first, _) = handler();
+        replayed.set_dir_ref(TMP_DIR);
+        assert_eq!(6, replayed.replay().unwrap());
+
+        assert_eq!(1, replayed.active().count());
+        assert_eq!(alarms.get("a"), replayed.get("a"));
+        // sinks are not notified of replayed alarms
+        assert!(first.borrow().is_empty());
+
+        let _ = remove_dir_all(TMP_DIR);
+    }
+}
@@ -0,0 +1,18 @@
+//! Raise, escalate, and acknowledge alarms
+mod record;
+mod annunciator;
+mod coalesce;
+mod digest;
+mod escalation;
+mod handler;
+mod sink;
+mod sms;
+
+pub use record::{Alarm, AlarmSeverity, AlarmTransition, AckRecord};
+pub use annunciator::{AlarmAnnunciator, AnnunciatorPattern, Signal};
+pub use coalesce::CoalescingSink;
+pub use digest::{AlarmCounts, Digest, DigestPeriod, DigestSummary, ValueSummary, DIGEST_NAME};
+pub use escalation::{EscalationPolicy, EscalationTier};
+pub use handler::{AlarmHandler, ALARM_HISTORY_FILENAME};
+pub use sink::{BoxedSink, ConsoleSink, NotificationSink};
+pub use sms::*;
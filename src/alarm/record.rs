@@ -0,0 +1,250 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+use crate::io::RawValue;
+
+/// Discrete levels of alarm severity
+///
+/// Variants are ordered so that escalation can only ever increase severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+pub enum AlarmSeverity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Display for AlarmSeverity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AlarmSeverity::Info => "Info",
+            AlarmSeverity::Warning => "Warning",
+            AlarmSeverity::Critical => "Critical",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// A single active alarm condition
+///
+/// Alarms are keyed by `name`, therefore only one alarm of the same name may be active at once.
+pub struct Alarm {
+    name: String,
+    message: String,
+    severity: AlarmSeverity,
+    raised: DateTime<Utc>,
+
+    /// Number of escalation tiers that have been applied
+    tier: usize,
+
+    /// Time of acknowledgement
+    #[serde(default)]
+    acknowledged: Option<DateTime<Utc>>,
+
+    /// Name of device that triggered alarm
+    #[serde(default)]
+    device: Option<String>,
+    /// Value that triggered alarm
+    #[serde(default)]
+    value: Option<RawValue>,
+    /// Threshold that was exceeded
+    #[serde(default)]
+    threshold: Option<RawValue>,
+
+    /// Time that alarm condition cleared. Only set on recovery notifications.
+    #[serde(default)]
+    cleared: Option<DateTime<Utc>>,
+    /// Number of notifications merged into this one. See [`crate::alarm::CoalescingSink`].
+    #[serde(default = "single")]
+    occurrences: usize,
+}
+
+fn single() -> usize {
+    1
+}
+
+impl Alarm {
+    /// Constructor for [`Alarm`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Unique name of alarm condition
+    /// - `severity`: Initial severity
+    /// - `message`: Human readable description of alarm condition
+    /// - `raised`: Time that alarm condition was detected
+    pub fn new<N, M>(name: N, severity: AlarmSeverity, message: M, raised: DateTime<Utc>) -> Self
+    where
+        N: Into<String>,
+        M: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            message: message.into(),
+            severity,
+            raised,
+            tier: 0,
+            acknowledged: None,
+            device: None,
+            value: None,
+            threshold: None,
+            cleared: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Builder method to associate originating device
+    pub fn set_device<N>(mut self, device: N) -> Self
+    where
+        N: Into<String>
+    {
+        self.device = Some(device.into());
+        self
+    }
+
+    /// Builder method to store the value that triggered alarm
+    pub fn set_value(mut self, value: RawValue) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Builder method to store the threshold that was exceeded
+    pub fn set_threshold(mut self, threshold: RawValue) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Builder method to set number of notifications merged into this one
+    pub fn set_occurrences(mut self, occurrences: usize) -> Self {
+        self.occurrences = occurrences.max(1);
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+
+    pub fn severity(&self) -> AlarmSeverity {
+        self.severity
+    }
+
+    pub fn raised(&self) -> DateTime<Utc> {
+        self.raised
+    }
+
+    pub fn device(&self) -> Option<&String> {
+        self.device.as_ref()
+    }
+
+    pub fn value(&self) -> Option<RawValue> {
+        self.value
+    }
+
+    pub fn threshold(&self) -> Option<RawValue> {
+        self.threshold
+    }
+
+    /// Number of escalation tiers that have been applied to this alarm
+    pub fn tier(&self) -> usize {
+        self.tier
+    }
+
+    /// Check if alarm has been acknowledged
+    ///
+    /// Acknowledged alarms remain active until cleared, but are no longer escalated.
+    pub fn is_acknowledged(&self) -> bool {
+        self.acknowledged.is_some()
+    }
+
+    /// Time that alarm condition cleared, if this is a recovery notification
+    pub fn cleared(&self) -> Option<DateTime<Utc>> {
+        self.cleared
+    }
+
+    /// Check if this is a recovery notification
+    pub fn is_cleared(&self) -> bool {
+        self.cleared.is_some()
+    }
+
+    /// Number of notifications merged into this one
+    pub fn occurrences(&self) -> usize {
+        self.occurrences
+    }
+
+    pub(crate) fn clear(&mut self, timestamp: DateTime<Utc>) {
+        self.cleared = Some(timestamp);
+    }
+
+    pub(crate) fn acknowledge(&mut self, timestamp: DateTime<Utc>) {
+        self.acknowledged = Some(timestamp);
+    }
+
+    /// Apply the next escalation tier
+    ///
+    /// Severity is only changed if `severity` is higher than current severity.
+    pub(crate) fn escalate(&mut self, severity: AlarmSeverity) {
+        self.severity = self.severity.max(severity);
+        self.tier += 1;
+    }
+}
+
+impl Display for Alarm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.cleared {
+            Some(_) => write!(f, "[Cleared] {}: {}", self.name, self.message)?,
+            None => write!(f, "[{}] {}: {}", self.severity, self.name, self.message)?,
+        }
+        if self.occurrences > 1 {
+            write!(f, " ({} occurrences)", self.occurrences)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Persisted record of an acknowledged alarm
+pub struct AckRecord {
+    pub alarm: Alarm,
+    pub acknowledged: DateTime<Utc>,
+
+    /// Name of user or process that acknowledged the alarm
+    pub by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "event")]
+/// Audit record of a change in alarm state
+///
+/// Transitions are persisted by [`crate::alarm::AlarmHandler`] so that active alarms may be
+/// reconstructed by [`crate::alarm::AlarmHandler::replay()`].
+pub enum AlarmTransition {
+    Raised { alarm: Alarm },
+    Escalated { timestamp: DateTime<Utc>, name: String, severity: AlarmSeverity },
+    Acknowledged { timestamp: DateTime<Utc>, name: String, by: String },
+    Cleared { timestamp: DateTime<Utc>, name: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use crate::alarm::{Alarm, AlarmSeverity};
+
+    #[test]
+    /// Assert that escalation never lowers severity
+    fn escalate() {
+        let mut alarm = Alarm::new("", AlarmSeverity::Warning, "", Utc::now());
+
+        alarm.escalate(AlarmSeverity::Info);
+        assert_eq!(AlarmSeverity::Warning, alarm.severity());
+        assert_eq!(1, alarm.tier());
+
+        alarm.escalate(AlarmSeverity::Critical);
+        assert_eq!(AlarmSeverity::Critical, alarm.severity());
+        assert_eq!(2, alarm.tier());
+    }
+}
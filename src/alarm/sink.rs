@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+
+use crate::alarm::Alarm;
+use crate::console::{self, Level};
+use crate::errors::ErrorType;
+
+pub type BoxedSink = Box<dyn NotificationSink>;
+
+/// Interface for delivering alarm notifications to the outside world
+pub trait NotificationSink {
+    /// Deliver a notification for `alarm`
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` if notification was delivered
+    /// - `Err` if delivery failed
+    fn notify(&mut self, alarm: &Alarm) -> Result<(), ErrorType>;
+
+    /// Deliver a recovery notification once the condition of `alarm` has cleared
+    ///
+    /// [`Alarm::is_cleared()`] is `true` for `alarm`. By default, recoveries are delivered by
+    /// [`NotificationSink::notify()`].
+    fn recover(&mut self, alarm: &Alarm) -> Result<(), ErrorType> {
+        self.notify(alarm)
+    }
+
+    /// Deliver notifications held back until `now`
+    ///
+    /// Called regularly by [`crate::alarm::AlarmHandler::attempt_escalations()`]. Does nothing by
+    /// default.
+    fn flush(&mut self, _now: DateTime<Utc>) -> Result<(), ErrorType> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+/// Sink that prints notifications to stdout
+pub struct ConsoleSink;
+
+impl NotificationSink for ConsoleSink {
+    fn notify(&mut self, alarm: &Alarm) -> Result<(), ErrorType> {
+        console::emit(Level::Alarm, alarm);
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+use chrono::NaiveDate;
+
+use crate::alarm::{Alarm, AlarmSeverity, NotificationSink};
+use crate::errors::{AlarmError, ErrorType};
+use crate::helpers::now;
+
+/// Default base URL of a Twilio-compatible API
+pub const DEFAULT_SMS_URL: &str = "https://api.twilio.com";
+
+/// Default template used for SMS message bodies
+pub const DEFAULT_SMS_TEMPLATE: &str = "[{severity}] {name}: {device} read {value} (threshold {threshold})";
+
+/// Request to be sent to a Twilio-compatible "Messages" endpoint
+///
+/// The request should be sent as a `POST` with `form` url-encoded as the body, using HTTP basic
+/// authentication with `username` and `password`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmsRequest {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub form: Vec<(String, String)>,
+}
+
+/// Function that delivers an [`SmsRequest`] over HTTP
+///
+/// An HTTP client is intentionally not bundled so that any client may be used.
+pub type SmsTransport = Box<dyn FnMut(&SmsRequest) -> Result<(), ErrorType>>;
+
+/// Sink that sends SMS messages via a Twilio-compatible HTTP API
+///
+/// Message bodies are rendered from a template where the following placeholders are replaced:
+///
+/// - `{name}`: name of alarm
+/// - `{severity}`: current severity
+/// - `{message}`: alarm message
+/// - `{device}`: name of originating device
+/// - `{value}`: value that triggered alarm
+/// - `{threshold}`: threshold that was exceeded
+///
+/// Placeholders without an associated value are replaced with "n/a".
+///
+/// By default, only [`AlarmSeverity::Critical`] alarms are sent. The number of messages sent per
+/// day (UTC) is limited by [`SmsSink::set_daily_cap()`]; once reached, notifications fail with
+/// [`AlarmError::DailyCapReached`].
+///
+/// # Example
+///
+/// ```
+/// use sensd::alarm::{AlarmHandler, SmsSink};
+///
+/// let sink = SmsSink::new("ACXXXX", "token", "+15550000000", Box::new(|_request| {
+///     // send `_request` using HTTP client of choice
+///     Ok(())
+/// }))
+///     .add_recipient("+15551234567")
+///     .set_daily_cap(20);
+///
+/// let mut alarms = AlarmHandler::default();
+/// alarms.add_sink("sms", Box::new(sink));
+/// ```
+pub struct SmsSink {
+    base_url: String,
+    account_sid: String,
+    auth_token: String,
+    from: String,
+    recipients: Vec<String>,
+
+    template: String,
+    min_severity: AlarmSeverity,
+
+    daily_cap: usize,
+    sent: usize,
+    day: NaiveDate,
+
+    transport: SmsTransport,
+}
+
+impl SmsSink {
+    /// Constructor for [`SmsSink`]
+    ///
+    /// # Parameters
+    ///
+    /// - `account_sid`: Account identifier used for endpoint and authentication
+    /// - `auth_token`: Secret token used for authentication
+    /// - `from`: Sending phone number
+    /// - `transport`: Function that performs the HTTP request
+    pub fn new<S, T, F>(account_sid: S, auth_token: T, from: F, transport: SmsTransport) -> Self
+    where
+        S: Into<String>,
+        T: Into<String>,
+        F: Into<String>,
+    {
+        Self {
+            base_url: DEFAULT_SMS_URL.to_string(),
+            account_sid: account_sid.into(),
+            auth_token: auth_token.into(),
+            from: from.into(),
+            recipients: Vec::new(),
+            template: DEFAULT_SMS_TEMPLATE.to_string(),
+            min_severity: AlarmSeverity::Critical,
+            daily_cap: 10,
+            sent: 0,
+            day: now().date_naive(),
+            transport,
+        }
+    }
+
+    /// Builder method to add a receiving phone number
+    pub fn add_recipient<N>(mut self, number: N) -> Self
+    where
+        N: Into<String>
+    {
+        self.recipients.push(number.into());
+        self
+    }
+
+    /// Builder method to override API base URL
+    pub fn set_base_url<U>(mut self, url: U) -> Self
+    where
+        U: Into<String>
+    {
+        self.base_url = url.into();
+        self
+    }
+
+    /// Builder method to set message template
+    pub fn set_template<T>(mut self, template: T) -> Self
+    where
+        T: Into<String>
+    {
+        self.template = template.into();
+        self
+    }
+
+    /// Builder method to set lowest severity that is sent
+    pub fn set_min_severity(mut self, severity: AlarmSeverity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    /// Builder method to set maximum number of messages sent per day
+    pub fn set_daily_cap(mut self, cap: usize) -> Self {
+        self.daily_cap = cap;
+        self
+    }
+
+    /// Number of messages sent today
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+
+    /// Render message body for `alarm` using template
+    ///
+    /// The template is rendered in a single pass, so that placeholders within substituted values
+    /// (ie: an alarm message containing `{device}`) are left as is. Unknown placeholders are kept.
+    pub fn render(&self, alarm: &Alarm) -> String {
+        let na = || "n/a".to_string();
+        let mut body = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find('{') {
+            let placeholder = &rest[start..];
+            let end = match placeholder.find('}') {
+                Some(end) => end,
+                None => break,
+            };
+            body.push_str(&rest[..start]);
+            let value = match &placeholder[1..end] {
+                "name" => alarm.name().to_string(),
+                "severity" => alarm.severity().to_string(),
+                "message" => alarm.message().to_string(),
+                "device" => alarm.device().cloned().unwrap_or_else(na),
+                "value" => alarm.value().map(|v| v.to_string()).unwrap_or_else(na),
+                "threshold" => alarm.threshold().map(|v| v.to_string()).unwrap_or_else(na),
+                _ => placeholder[..=end].to_string(),
+            };
+            body.push_str(&value);
+            rest = &placeholder[end + 1..];
+        }
+        body.push_str(rest);
+        body
+    }
+
+    /// Build request for a single recipient
+    pub fn request(&self, to: &str, body: &str) -> SmsRequest {
+        SmsRequest {
+            url: format!("{}/2010-04-01/Accounts/{}/Messages.json", self.base_url, self.account_sid),
+            username: self.account_sid.clone(),
+            password: self.auth_token.clone(),
+            form: vec![
+                ("To".to_string(), to.to_string()),
+                ("From".to_string(), self.from.clone()),
+                ("Body".to_string(), body.to_string()),
+            ],
+        }
+    }
+
+    /// Send notification relative to a given day
+    ///
+    /// The message counter is reset whenever `today` differs from the day of the last message.
+    pub(crate) fn notify_on(&mut self, alarm: &Alarm, today: NaiveDate) -> Result<(), ErrorType> {
+        if alarm.severity() < self.min_severity {
+            return Ok(());
+        }
+
+        if today != self.day {
+            self.day = today;
+            self.sent = 0;
+        }
+
+        let body = self.render(alarm);
+        for to in self.recipients.clone() {
+            if self.sent >= self.daily_cap {
+                return Err(Box::new(AlarmError::DailyCapReached { limit: self.daily_cap }));
+            }
+            let request = self.request(&to, &body);
+            (self.transport)(&request)?;
+            self.sent += 1;
+        }
+        Ok(())
+    }
+}
+
+impl NotificationSink for SmsSink {
+    fn notify(&mut self, alarm: &Alarm) -> Result<(), ErrorType> {
+        self.notify_on(alarm, now().date_naive())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::alarm::{Alarm, AlarmSeverity, SmsRequest, SmsSink};
+    use crate::io::RawValue;
+
+    fn sink(requests: Rc<RefCell<Vec<SmsRequest>>>) -> SmsSink {
+        SmsSink::new("AC123", "token", "+1000", Box::new(move |request| {
+            requests.borrow_mut().push(request.clone());
+            Ok(())
+        }))
+            .add_recipient("+1555")
+            .set_base_url("http://localhost")
+    }
+
+    #[test]
+    fn request() {
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let mut sink = sink(requests.clone()).set_template("{device}={value}/{threshold} {message}");
+
+        let alarm = Alarm::new("overtemp", AlarmSeverity::Critical, "too hot", Utc::now())
+            .set_device("thermometer")
+            .set_value(RawValue::Float(31.0))
+            .set_threshold(RawValue::Float(30.0));
+        sink.notify_on(&alarm, Utc::now().date_naive()).unwrap();
+
+        // non-critical alarms are not sent
+        let alarm = Alarm::new("minor", AlarmSeverity::Warning, "", Utc::now());
+        sink.notify_on(&alarm, Utc::now().date_naive()).unwrap();
+
+        let requests = requests.borrow();
+        assert_eq!(1, requests.len());
+        assert_eq!("http://localhost/2010-04-01/Accounts/AC123/Messages.json", requests[0].url);
+        assert_eq!(("Body".to_string(), "thermometer=31/30 too hot".to_string()), requests[0].form[2]);
+    }
+
+    #[test]
+    fn render_single_pass() {
+        let sink = sink(Rc::new(RefCell::new(Vec::new())))
+            .set_template("{message} on {device} {unknown} {value");
+        let alarm = Alarm::new("", AlarmSeverity::Critical, "check {device} {value}", Utc::now())
+            .set_device("{value}");
+        assert_eq!("check {device} {value} on {value} {unknown} {value", sink.render(&alarm));
+    }
+
+    #[test]
+    fn daily_cap() {
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let mut sink = sink(requests.clone()).set_daily_cap(2);
+
+        let alarm = Alarm::new("", AlarmSeverity::Critical, "", Utc::now());
+        let today = Utc::now().date_naive();
+
+        assert!(sink.notify_on(&alarm, today).is_ok());
+        assert!(sink.notify_on(&alarm, today).is_ok());
+        assert!(sink.notify_on(&alarm, today).is_err());
+        assert_eq!(2, requests.borrow().len());
+
+        // counter resets on the next day
+        assert!(sink.notify_on(&alarm, today + Duration::days(1)).is_ok());
+        assert_eq!(1, sink.sent());
+    }
+}
@@ -0,0 +1,97 @@
+//! Command-line entry point
+//!
+//! # Usage
+//!
+//! ```text
+//! sensd demo [ROOT]            Run a simulated greenhouse. Data is stored under ROOT (default: /tmp/sensd_demo).
+//! sensd verify KEYFILE PATH...  Verify signatures of log files, or of all logs under directories.
+//! sensd logs ROOT [GROUP]       Summarize saved device logs of every group (or of GROUP) under ROOT.
+//! ```
+//!
+//! `verify` exits with status 1 if any log is unsigned or has been altered.
+//!
+//! Notifications are printed as JSON lines when the environment (or ".env") sets
+//! `CONSOLE_FORMAT=json`.
+extern crate sensd;
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use sensd::console;
+use sensd::demo::Demo;
+use sensd::inspect::stats;
+use sensd::settings::Settings;
+use sensd::storage::{verify, verify_dir, Archive, SigningKey, Verification};
+
+const USAGE: &str = "usage: sensd demo [ROOT]\n       sensd verify KEYFILE PATH...\n       sensd logs ROOT [GROUP]";
+
+fn main() {
+    // applies console format
+    Settings::initialize();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("demo") => {
+            let root = args.get(1).map(String::as_str).unwrap_or("/tmp/sensd_demo");
+            Demo::greenhouse(root).run();
+        }
+        Some("verify") if args.len() > 2 => {
+            let key = SigningKey::from_file(&args[1]).unwrap_or_else(|e| {
+                console::error(format!("Could not read key from {}: {}", args[1], e));
+                exit(2);
+            });
+            let mut failed = false;
+            for path in &args[2..] {
+                let results: Vec<Verification> = match Path::new(path).is_dir() {
+                    true => verify_dir(path, &key).unwrap_or_else(|e| vec![(PathBuf::from(path), Err(e))]),
+                    false => vec![(PathBuf::from(path), verify(path, &key))],
+                };
+                for (path, result) in results {
+                    match result {
+                        Ok(_) => println!("OK      {}", path.display()),
+                        Err(e) => {
+                            println!("FAILED  {}: {}", path.display(), e);
+                            failed = true;
+                        }
+                    }
+                }
+            }
+            if failed {
+                exit(1);
+            }
+        }
+        Some("logs") if args.len() > 1 => {
+            let archive = Archive::open(&args[1]).unwrap_or_else(|e| {
+                console::error(format!("Could not open {}: {}", args[1], e));
+                exit(2);
+            });
+            let groups: Vec<&str> = match args.get(2) {
+                Some(group) => vec![group.as_str()],
+                None => archive.groups().collect(),
+            };
+            for group in groups {
+                let logs = archive.logs(group).unwrap_or_else(|e| {
+                    console::error(format!("Could not read logs of {}: {}", group, e));
+                    exit(1);
+                });
+                println!("{}", group);
+                for log in logs {
+                    let stats = stats(&log);
+                    let range = match (stats.first, stats.last) {
+                        (Some(first), Some(last)) => format!("{} .. {}", first, last),
+                        _ => String::from("empty"),
+                    };
+                    match log.metadata() {
+                        Some(metadata) => println!("  {:<24} {:>8} events  {}", metadata.to_string(), stats.events, range),
+                        None => println!("  {:<24} {:>8} events  {}", "unknown", stats.events, range),
+                    }
+                }
+            }
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Notifications printed to the console
+//!
+//! Every notification of the library (ie: errors encountered while polling, save confirmations,
+//! and alarms) is printed through this module, so that daemon output is either human readable
+//! text or JSON lines which log shippers can parse reliably. The format is process-wide, and is
+//! shared by every group and [`crate::settings::Settings`] instance within a process. It is
+//! selected by [`set_console_format()`], or from the environment by
+//! [`crate::settings::Settings::initialize()`].
+//!
+//! Informational messages and alarms are printed to stdout, while warnings and errors are printed
+//! to stderr, in either format.
+//!
+//! # Example
+//!
+//! ```
+//! use sensd::console::{render, set_console_format, ConsoleFormat, Level};
+//!
+//! assert_eq!("█▓▒░ WARNING: Sensor is stale", render(Level::Warning, "Sensor is stale"));
+//!
+//! set_console_format(ConsoleFormat::Json);
+//! let line = render(Level::Warning, "Sensor is stale");
+//! assert!(line.starts_with('{'));
+//! assert!(line.contains("\"level\":\"warning\""));
+//! # set_console_format(ConsoleFormat::default());
+//! ```
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::helpers::now;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Format of console notifications
+pub enum ConsoleFormat {
+    /// Human readable text
+    #[default]
+    Text,
+    /// One JSON object per line, with `timestamp`, `level`, and `message` keys
+    Json,
+}
+
+impl ConsoleFormat {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ConsoleFormat::Json,
+            _ => ConsoleFormat::Text,
+        }
+    }
+}
+
+impl FromStr for ConsoleFormat {
+    type Err = String;
+
+    /// Parse format by name, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(ConsoleFormat::Text),
+            "json" => Ok(ConsoleFormat::Json),
+            _ => Err(format!("Unknown console format \"{}\"", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Severity of a console notification
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+    /// An alarm was raised. See [`crate::alarm::ConsoleSink`].
+    Alarm,
+}
+
+impl Level {
+    fn label(&self) -> &'static str {
+        match self {
+            Level::Info => "INFO",
+            Level::Warning => "WARNING",
+            Level::Error => "ERROR",
+            Level::Alarm => "ALARM",
+        }
+    }
+}
+
+static FORMAT: AtomicU8 = AtomicU8::new(0);
+
+/// Set process-wide format of console notifications
+pub fn set_console_format(format: ConsoleFormat) {
+    FORMAT.store(format as u8, Ordering::Relaxed);
+}
+
+/// Current process-wide format of console notifications
+pub fn console_format() -> ConsoleFormat {
+    ConsoleFormat::from_u8(FORMAT.load(Ordering::Relaxed))
+}
+
+/// Render a notification as a single line in the current format
+pub fn render<M: Display>(level: Level, message: M) -> String {
+    match console_format() {
+        ConsoleFormat::Text => match level {
+            Level::Info => message.to_string(),
+            _ => format!("█▓▒░ {}: {}", level.label(), message),
+        },
+        ConsoleFormat::Json => serde_json::json!({
+            "timestamp": now(),
+            "level": level,
+            "message": message.to_string(),
+        }).to_string(),
+    }
+}
+
+/// Print a notification in the current format
+pub fn emit<M: Display>(level: Level, message: M) {
+    let line = render(level, message);
+    match level {
+        Level::Info | Level::Alarm => println!("{}", line),
+        Level::Warning | Level::Error => eprintln!("{}", line),
+    }
+}
+
+/// Print an informational message to stdout
+pub fn info<M: Display>(message: M) {
+    emit(Level::Info, message)
+}
+
+/// Print a warning to stderr
+pub fn warning<M: Display>(message: M) {
+    emit(Level::Warning, message)
+}
+
+/// Print an error to stderr
+pub fn error<M: Display>(message: M) {
+    emit(Level::Error, message)
+}
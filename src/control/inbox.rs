@@ -0,0 +1,259 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{create_dir_all, read_dir, read_to_string, rename};
+use std::path::{Path, PathBuf};
+
+use crate::alarm::AlarmHandler;
+use crate::console;
+use crate::control::rpc::{dispatch, Request, RpcError, INVALID_REQUEST, PARSE_ERROR};
+use crate::errors::ErrorType;
+use crate::helpers::{now, Def};
+use crate::storage::{append_record, Directory, Group, FILETYPE};
+
+/// Subdirectory of inbox where applied command files are archived
+pub const PROCESSED_DIR: &str = "processed";
+
+/// Subdirectory of inbox where command files that failed are archived
+pub const REJECTED_DIR: &str = "rejected";
+
+/// Filename of command audit trail within group directory
+pub const INBOX_HISTORY_FILENAME: &str = "inbox_history.jsonl";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+/// Contents of a single command file
+struct CommandFile {
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Audit record of a single command file
+pub struct InboxRecord {
+    pub timestamp: DateTime<Utc>,
+
+    /// Filename of command file
+    pub file: String,
+
+    /// Method of command, or `None` if file could not be parsed
+    pub method: Option<String>,
+
+    #[serde(default)]
+    pub params: Value,
+
+    /// Result of command, if applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+
+    /// Reason command was rejected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl InboxRecord {
+    pub fn is_applied(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Drop-folder accepting JSON command files from scripts and legacy systems
+///
+/// Each file contains a single command, using any method supported by [`crate::control`]:
+///
+/// ```text
+/// {"method": "devices.write", "params": {"id": 1, "value": true}}
+/// {"method": "alarms.ack", "params": {"name": "overtemp"}}
+/// {"method": "variables.set", "params": {"name": "setpoint", "value": 22.5}}
+/// ```
+///
+/// [`CommandInbox::process()`] is called from the main event loop, alongside
+/// [`Group::poll()`], and applies every pending file in order of filename. Files are validated,
+/// applied, then moved to [`PROCESSED_DIR`] or [`REJECTED_DIR`] so that they are never applied
+/// twice. Every file is recorded in [`INBOX_HISTORY_FILENAME`] within the group directory, if it
+/// exists. Unless given, `by` is set to the filename so that audit trails of modes and alarms name
+/// the command file.
+///
+/// Only files ending in ".json" are read. Writers should therefore write under a different name and
+/// rename the file into place, so that a partially written file is never read.
+///
+/// The inbox is scanned on every call rather than watched, so that no platform-specific
+/// dependency is needed. Scanning an empty directory is cheap.
+///
+/// # Example
+///
+/// ```
+/// use sensd::control::CommandInbox;
+/// use sensd::storage::Group;
+///
+/// let dir = std::env::temp_dir().join("sensd_inbox_example");
+/// let mut inbox = CommandInbox::open(&dir).unwrap();
+/// let mut group = Group::new("main");
+///
+/// std::fs::write(dir.join("001.json"), r#"{"method": "variables.set", "params": {"name": "setpoint", "value": 22.5}}"#).unwrap();
+///
+/// // called from within main event loop
+/// let records = inbox.process(&mut group).unwrap();
+/// assert!(records[0].is_applied());
+/// # std::fs::remove_dir_all(dir).unwrap();
+/// ```
+pub struct CommandInbox {
+    dir: PathBuf,
+    alarms: Option<Def<AlarmHandler>>,
+}
+
+impl CommandInbox {
+    /// Watch `dir` for command files
+    ///
+    /// `dir` and archive subdirectories are created if they do not exist.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, ErrorType> {
+        let dir = dir.as_ref().to_path_buf();
+        create_dir_all(dir.join(PROCESSED_DIR))?;
+        create_dir_all(dir.join(REJECTED_DIR))?;
+        Ok(Self { dir, alarms: None })
+    }
+
+    /// Builder method to set handler used by `alarms.list` and `alarms.ack`
+    pub fn set_alarm_handler(mut self, alarms: Def<AlarmHandler>) -> Self {
+        self.alarms = Some(alarms);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Command files waiting to be processed, in order of filename
+    pub fn pending(&self) -> Result<Vec<PathBuf>, ErrorType> {
+        let mut pending = Vec::new();
+        for entry in read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.to_string_lossy().ends_with(FILETYPE) {
+                pending.push(path);
+            }
+        }
+        pending.sort();
+        Ok(pending)
+    }
+
+    /// Apply and archive every pending command file
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with a record of every file, in order of processing. Rejected commands are returned
+    ///   as records rather than errors.
+    /// - `Err` if inbox could not be read, or a file could not be archived. Files processed before
+    ///   the error have been archived.
+    pub fn process(&mut self, group: &mut Group) -> Result<Vec<InboxRecord>, ErrorType> {
+        let mut records = Vec::new();
+        for path in self.pending()? {
+            let file = path.file_name().unwrap().to_string_lossy().to_string();
+            let record = self.apply(group, &path, file.clone());
+
+            let archive = match record.is_applied() {
+                true => PROCESSED_DIR,
+                false => REJECTED_DIR,
+            };
+            rename(&path, self.dir.join(archive).join(&file))?;
+
+            let dir = group.full_path();
+            if dir.exists() {
+                if let Err(e) = append_record(&dir.join(INBOX_HISTORY_FILENAME), &record) {
+                    console::error(format!("Could not record command {}: {}", file, e));
+                }
+            }
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// Validate and execute a single command file
+    fn apply(&self, group: &mut Group, path: &Path, file: String) -> InboxRecord {
+        let mut record = InboxRecord {
+            timestamp: now(),
+            file,
+            method: None,
+            params: Value::Null,
+            result: None,
+            error: None,
+        };
+        let command = read_to_string(path)
+            .map_err(|e| RpcError::new(PARSE_ERROR, e.to_string()))
+            .and_then(|contents| serde_json::from_str::<CommandFile>(&contents)
+                .map_err(|e| RpcError::new(INVALID_REQUEST, e.to_string())));
+        let mut command = match command {
+            Ok(command) => command,
+            Err(e) => {
+                record.error = Some(e);
+                return record;
+            }
+        };
+
+        if let Value::Object(params) = &mut command.params {
+            params.entry("by").or_insert_with(|| Value::String(format!("inbox:{}", record.file)));
+        }
+        let request = Request {
+            jsonrpc: String::from("2.0"),
+            method: command.method.clone(),
+            params: command.params.clone(),
+            id: None,
+            token: None,
+            group: None,
+        };
+        match dispatch(group, self.alarms.as_ref(), &request) {
+            Ok(result) => record.result = Some(result),
+            Err(e) => record.error = Some(e),
+        }
+        record.method = Some(command.method);
+        record.params = command.params;
+        record
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{remove_dir_all, write};
+
+    use crate::action::IOCommand;
+    use crate::alarm::{AlarmHandler, AlarmSeverity};
+    use crate::control::{CommandInbox, InboxRecord, INBOX_HISTORY_FILENAME, PROCESSED_DIR, REJECTED_DIR};
+    use crate::helpers::Def;
+    use crate::io::{Device, DeviceGetters, Output, RawValue};
+    use crate::storage::{read_records, Directory, Group, RootDirectory};
+
+    #[test]
+    fn process() {
+        let root = std::env::temp_dir().join("sensd_inbox_test");
+        let _ = remove_dir_all(&root);
+        let mut group = Group::with_root("inbox", &root).init_dir();
+        group.push_output(Output::new("fan", 1, None)
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .init_log());
+        let alarms = Def::new(AlarmHandler::default());
+        alarms.lock().unwrap().raise("overtemp", AlarmSeverity::Warning, "Too warm");
+        let mut inbox = CommandInbox::open(root.join("inbox_dir")).unwrap()
+            .set_alarm_handler(alarms.clone());
+
+        write(inbox.dir().join("1.json"), r#"{"method": "devices.write", "params": {"id": 1, "value": true}}"#).unwrap();
+        write(inbox.dir().join("2.json"), r#"{"method": "alarms.ack", "params": {"name": "overtemp"}}"#).unwrap();
+        write(inbox.dir().join("3.json"), r#"{"method": "devices.write", "params": {"id": 9, "value": true}}"#).unwrap();
+        write(inbox.dir().join("4.json"), "{").unwrap();
+        write(inbox.dir().join("5.json.tmp"), "{").unwrap();
+
+        let records = inbox.process(&mut group).unwrap();
+        assert_eq!(vec![true, true, false, false], records.iter().map(InboxRecord::is_applied).collect::<Vec<_>>());
+        assert_eq!(Some(RawValue::Binary(true)), *group.outputs.get(&1).unwrap().lock().unwrap().state());
+        assert_eq!("inbox:2.json", alarms.lock().unwrap().acknowledgements().last().unwrap().by);
+
+        // files are archived, and never applied twice
+        assert!(inbox.dir().join(PROCESSED_DIR).join("2.json").exists());
+        assert!(inbox.dir().join(REJECTED_DIR).join("4.json").exists());
+        assert!(inbox.process(&mut group).unwrap().is_empty());
+
+        let history: Vec<InboxRecord> = read_records(&group.full_path().join(INBOX_HISTORY_FILENAME)).unwrap();
+        assert_eq!(records, history);
+
+        let _ = remove_dir_all(root);
+    }
+}
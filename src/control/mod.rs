@@ -0,0 +1,42 @@
+//! Local control channel for CLIs and UIs
+//!
+//! [`ControlSocket`] accepts newline-delimited JSON-RPC 2.0 requests on a Unix domain socket.
+//! Supported methods are:
+//!
+//! | Method             | Params                   | Result                                     |
+//! |--------------------|--------------------------|--------------------------------------------|
+//! | `devices.list`     |                          | Metadata and value of every device         |
+//! | `devices.read`     | `id`, `fresh` (optional) | Metadata and value of device               |
+//! | `devices.write`    | `id`, `value`            | Resulting [`crate::io::IOEvent`]           |
+//! | `actions.describe` | `id`                     | Parameters of actions subscribed to input  |
+//! | `mode.get`         |                          | Name of current mode, or `null`            |
+//! | `mode.set`         | `name`, `by` (optional)  | Name of current mode                       |
+//! | `daily.list`       | `id` (optional)          | [`crate::storage::DailySummary`] records   |
+//! | `series.query`     | `id`, `start`, `end`     | Values stitched from every storage tier    |
+//! | `alarms.list`      |                          | Active alarms                              |
+//! | `alarms.ack`       | `name`, `by` (optional)  | [`crate::alarm::AckRecord`]                |
+//! | `variables.set`    | `name`, `value`          | Previous value, or `null`                  |
+//!
+//! `devices.read` returns the last value unless `fresh` is `true`, in which case an input is read
+//! from hardware. Values may be given as plain JSON booleans and numbers.
+//!
+//! ```text
+//! $ echo '{"jsonrpc": "2.0", "method": "devices.write", "params": {"id": 1, "value": true}, "id": 1}' \
+//!     | socat - UNIX-CONNECT:/run/sensd/control.sock
+//! ```
+//!
+//! A process serving several [`crate::tenant::Tenant`]s uses [`ControlSocket::serve_tenants()`]
+//! instead, and each request must carry the `token` of a tenant, plus the name of a `group` when
+//! the tenant has more than one. See [`handle_tenants()`].
+//!
+//! The same methods are accepted as command files dropped into a [`CommandInbox`], for scripts and
+//! legacy systems which cannot open a socket.
+mod inbox;
+mod rpc;
+#[cfg(unix)]
+mod socket;
+
+pub use inbox::{CommandInbox, InboxRecord, INBOX_HISTORY_FILENAME, PROCESSED_DIR, REJECTED_DIR};
+pub use rpc::*;
+#[cfg(unix)]
+pub use socket::ControlSocket;
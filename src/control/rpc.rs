@@ -0,0 +1,422 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::alarm::AlarmHandler;
+use crate::helpers::Def;
+use crate::io::{DeviceGetters, DeviceState, IdType, IODirection, RawValue};
+use crate::storage::Group;
+use crate::tenant::Tenants;
+
+/// Request could not be parsed as JSON
+pub const PARSE_ERROR: i64 = -32700;
+/// Request is not a valid JSON-RPC request object
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// Method was called correctly but failed
+pub const SERVER_ERROR: i64 = -32000;
+/// Token is missing or does not belong to any tenant
+pub const UNAUTHORIZED: i64 = -32001;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// JSON-RPC 2.0 request object
+pub struct Request {
+    pub jsonrpc: String,
+    pub method: String,
+
+    #[serde(default)]
+    pub params: Value,
+
+    /// Requests without an id are notifications, which are executed but not answered
+    #[serde(default)]
+    pub id: Option<Value>,
+
+    /// Token of tenant. Only used by [`handle_tenants()`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Name of group of tenant. Only used by [`handle_tenants()`], and may be omitted when the
+    /// tenant has a single group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// JSON-RPC 2.0 error object
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new<M: Into<String>>(code: i64, message: M) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// JSON-RPC 2.0 response object
+pub struct Response {
+    pub jsonrpc: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+
+    pub id: Value,
+}
+
+impl Response {
+    fn new(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self { jsonrpc: String::from("2.0"), result, error, id }
+    }
+}
+
+#[derive(Deserialize)]
+struct DeviceParams {
+    id: IdType,
+
+    /// Read input from hardware instead of returning last value
+    #[serde(default)]
+    fresh: bool,
+}
+
+#[derive(Deserialize)]
+struct WriteParams {
+    id: IdType,
+    value: Value,
+}
+
+#[derive(Deserialize, Default)]
+struct DailyParams {
+    /// Only return summaries of device
+    #[serde(default)]
+    id: Option<IdType>,
+}
+
+#[derive(Deserialize)]
+struct SeriesParams {
+    id: IdType,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct VariableParams {
+    name: String,
+    value: Value,
+}
+
+#[derive(Deserialize)]
+struct NameParams {
+    name: String,
+
+    /// Name of user or process recorded in audit trails
+    #[serde(default = "default_by")]
+    by: String,
+}
+
+fn default_by() -> String {
+    String::from("control socket")
+}
+
+/// Handle a single line of JSON-RPC input
+///
+/// # Returns
+///
+/// Serialized response, or `None` if request was a notification
+pub fn handle(group: &mut Group, alarms: Option<&Def<AlarmHandler>>, line: &str) -> Option<String> {
+    respond(line, |request| dispatch(group, alarms, request))
+}
+
+/// Handle a single line of JSON-RPC input on behalf of several tenants
+///
+/// Each request must carry the `token` of a tenant, and is executed against the group of that
+/// tenant named by `group`. Groups of other tenants are never accessible.
+///
+/// ```text
+/// {"jsonrpc": "2.0", "method": "devices.list", "token": "lab-token", "group": "incubator", "id": 1}
+/// ```
+///
+/// # Returns
+///
+/// Serialized response, or `None` if request was a notification
+pub fn handle_tenants(tenants: &mut Tenants, line: &str) -> Option<String> {
+    respond(line, |request| {
+        let tenant = request.token.as_deref()
+            .and_then(|token| tenants.authorize(token))
+            .ok_or_else(|| RpcError::new(UNAUTHORIZED, "Unauthorized"))?;
+        let alarms = tenant.alarm_handler().cloned();
+        let group = tenant.group_mut(request.group.as_deref())
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Unknown group '{}'", request.group.as_deref().unwrap_or(""))))?;
+        dispatch(group, alarms.as_ref(), request)
+    })
+}
+
+/// Parse request, and serialize outcome of `execute`
+fn respond<F>(line: &str, execute: F) -> Option<String>
+    where F: FnOnce(&Request) -> Result<Value, RpcError>
+{
+    let response = match serde_json::from_str::<Value>(line) {
+        Ok(value) => match serde_json::from_value::<Request>(value) {
+            Ok(request) if request.jsonrpc == "2.0" => {
+                let outcome = execute(&request);
+                Response::new(request.id?, outcome)
+            }
+            _ => Response::new(Value::Null, Err(RpcError::new(INVALID_REQUEST, "Invalid request"))),
+        },
+        Err(e) => Response::new(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string()))),
+    };
+    Some(serde_json::to_string(&response).unwrap())
+}
+
+/// Execute a request against `group`
+///
+/// See [`crate::control`] for supported methods.
+pub fn dispatch(group: &mut Group, alarms: Option<&Def<AlarmHandler>>, request: &Request) -> Result<Value, RpcError> {
+    match request.method.as_str() {
+        "devices.list" => {
+            let view = group.view();
+            let inputs = view.inputs().values().filter_map(|device| device.state());
+            let outputs = view.outputs().values().filter_map(|device| device.state());
+            to_result(&inputs.chain(outputs).collect::<Vec<DeviceState>>())
+        }
+        "devices.read" => {
+            let params: DeviceParams = params(request)?;
+            if group.inputs.get(&params.id).is_some() {
+                if params.fresh {
+                    group.read_now(params.id).map_err(server_error)?;
+                }
+                let input = group.inputs.get(&params.id).unwrap().lock().unwrap();
+                return to_result(&DeviceState { metadata: input.metadata().clone(), value: *input.state() });
+            }
+            let output = group.outputs.get(&params.id).ok_or_else(|| unknown_device(params.id))?;
+            let output = output.lock().unwrap();
+            to_result(&DeviceState { metadata: output.metadata().clone(), value: *output.state() })
+        }
+        "devices.write" => {
+            let params: WriteParams = params(request)?;
+            let value = to_raw(&params.value)
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Invalid value: {}", params.value)))?;
+            group.outputs.get(&params.id).ok_or_else(|| unknown_device(params.id))?;
+            let event = group.write_now(params.id, value).map_err(server_error)?;
+            to_result(&event)
+        }
+        "actions.describe" => {
+            let params: DeviceParams = params(request)?;
+            let input = group.inputs.get(&params.id).ok_or_else(|| unknown_device(params.id))?;
+            let input = input.lock().unwrap();
+            let described = input.publisher().as_ref()
+                .map(|publisher| publisher.describe())
+                .unwrap_or_default();
+            to_result(&described)
+        }
+        "mode.get" => to_result(&group.mode()),
+        "mode.set" => {
+            let params: NameParams = params(request)?;
+            group.set_mode(&params.name, params.by).map_err(server_error)?;
+            to_result(&group.mode())
+        }
+        "daily.list" => {
+            let params: DailyParams = match request.params {
+                Value::Null => DailyParams::default(),
+                _ => params(request)?,
+            };
+            let summaries: Vec<_> = group.daily_summaries().iter()
+                .filter(|summary| params.id.is_none_or(|id| summary.id == id))
+                .collect();
+            to_result(&summaries)
+        }
+        "series.query" => {
+            let params: SeriesParams = params(request)?;
+            let direction = match (group.inputs.get(&params.id).is_some(), group.outputs.get(&params.id).is_some()) {
+                (true, _) => IODirection::In,
+                (false, true) => IODirection::Out,
+                _ => return Err(unknown_device(params.id)),
+            };
+            let series = group.query(direction, params.id, params.start, params.end)
+                .map_err(server_error)?;
+            to_result(&series.points)
+        }
+        "alarms.list" => {
+            let alarms = alarms.ok_or_else(no_alarms)?.lock().unwrap();
+            let mut active: Vec<_> = alarms.active().cloned().collect();
+            active.sort_by(|a, b| a.name().cmp(b.name()));
+            to_result(&active)
+        }
+        "alarms.ack" => {
+            let params: NameParams = params(request)?;
+            let mut alarms = alarms.ok_or_else(no_alarms)?.lock().unwrap();
+            let record = alarms.acknowledge(&params.name, params.by).map_err(server_error)?;
+            to_result(record)
+        }
+        "variables.set" => {
+            let params: VariableParams = params(request)?;
+            let value = to_raw(&params.value)
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Invalid value: {}", params.value)))?;
+            to_result(&group.variables().set(params.name, value))
+        }
+        method => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+    }
+}
+
+fn params<'a, T: Deserialize<'a>>(request: &'a Request) -> Result<T, RpcError> {
+    T::deserialize(&request.params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_result<T: Serialize + ?Sized>(value: &T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(server_error)
+}
+
+fn server_error<E: ToString>(e: E) -> RpcError {
+    RpcError::new(SERVER_ERROR, e.to_string())
+}
+
+fn unknown_device(id: IdType) -> RpcError {
+    RpcError::new(INVALID_PARAMS, format!("No device with id {}", id))
+}
+
+fn no_alarms() -> RpcError {
+    RpcError::new(SERVER_ERROR, "No alarm handler is set")
+}
+
+/// Convert a JSON value to [`RawValue`]
+///
+/// Plain booleans and numbers are accepted, as well as the serialized form of [`RawValue`]
+/// (ie: `{"Float": 1.5}`).
+fn to_raw(value: &Value) -> Option<RawValue> {
+    match value {
+        Value::Bool(state) => Some(RawValue::Binary(*state)),
+        Value::Number(number) => match number.as_i64() {
+            Some(number) => Some(match i32::try_from(number) {
+                Ok(number) => RawValue::Int(number),
+                Err(_) => RawValue::Int64(number),
+            }),
+            None => number.as_f64().map(|number| RawValue::Float(number as f32)),
+        },
+        _ => serde_json::from_value(value.clone()).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::action::{Action, IOCommand};
+    use crate::action::actions::PID;
+    use crate::alarm::{AlarmHandler, AlarmSeverity};
+    use crate::control::rpc::{handle, handle_tenants, Response, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR, UNAUTHORIZED};
+    use crate::helpers::Def;
+    use crate::io::{Device, DeviceGetters, Input, IOKind, Output, RawValue};
+    use crate::storage::{Group, Mode};
+    use crate::tenant::{Tenant, Tenants};
+
+    fn call(group: &mut Group, alarms: &Def<AlarmHandler>, request: Value) -> Response {
+        let response = handle(group, Some(alarms), &request.to_string()).unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut group = Group::new("");
+        let mut input = Input::new("temp", 0, IOKind::Temperature)
+            .set_command(IOCommand::Input(|| RawValue::Float(21.5)))
+            .init_log()
+            .init_publisher();
+        input.publisher_mut().as_mut().unwrap().subscribe(PID::new("heater", 22.0, 10.0).into_boxed());
+        group.push_input(input);
+        group.push_output(Output::new("fan", 1, None)
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .init_log());
+        group.add_mode(Mode::new("eco"));
+        let alarms = Def::new(AlarmHandler::default());
+        alarms.lock().unwrap().raise("overtemp", AlarmSeverity::Warning, "Too warm");
+
+        let response = call(&mut group, &alarms, json!({"jsonrpc": "2.0", "method": "devices.list", "id": 1}));
+        assert_eq!(2, response.result.unwrap().as_array().unwrap().len());
+        assert_eq!(json!(1), response.id);
+
+        let response = call(&mut group, &alarms,
+                            json!({"jsonrpc": "2.0", "method": "devices.read", "params": {"id": 0, "fresh": true}, "id": 2}));
+        assert_eq!(json!({"Float": 21.5}), response.result.unwrap()["value"]);
+
+        let response = call(&mut group, &alarms,
+                            json!({"jsonrpc": "2.0", "method": "devices.write", "params": {"id": 1, "value": true}, "id": 3}));
+        assert!(response.error.is_none());
+        assert_eq!(Some(RawValue::Binary(true)), *group.outputs.get(&1).unwrap().lock().unwrap().state());
+
+        let response = call(&mut group, &alarms,
+                            json!({"jsonrpc": "2.0", "method": "actions.describe", "params": {"id": 0}, "id": 3}));
+        let output_limit = &response.result.unwrap()["heater"][1];
+        assert_eq!((json!("output_limit"), json!(0.0)), (output_limit["name"].clone(), output_limit["min"].clone()));
+
+        call(&mut group, &alarms, json!({"jsonrpc": "2.0", "method": "mode.set", "params": {"name": "eco"}, "id": 4}));
+        assert_eq!(Some(&String::from("eco")), group.mode());
+
+        let response = call(&mut group, &alarms,
+                            json!({"jsonrpc": "2.0", "method": "alarms.ack", "params": {"name": "overtemp", "by": "cli"}, "id": 5}));
+        assert_eq!(json!("cli"), response.result.unwrap()["by"]);
+        assert!(alarms.lock().unwrap().get("overtemp").unwrap().is_acknowledged());
+
+        call(&mut group, &alarms,
+             json!({"jsonrpc": "2.0", "method": "variables.set", "params": {"name": "setpoint", "value": 22.5}, "id": 5}));
+        assert_eq!(Some(RawValue::Float(22.5)), group.variables().get("setpoint"));
+
+        let response = call(&mut group, &alarms,
+                            json!({"jsonrpc": "2.0", "method": "daily.list", "params": {"id": 0}, "id": 6}));
+        assert_eq!(json!([]), response.result.unwrap());
+
+        let response = call(&mut group, &alarms, json!({"jsonrpc": "2.0", "method": "series.query",
+                            "params": {"id": 0, "start": "2000-01-01T00:00:00Z", "end": "2100-01-01T00:00:00Z"}, "id": 6}));
+        assert_eq!(json!("Memory"), response.result.unwrap()[0]["tier"]);
+
+        // errors
+        let response = call(&mut group, &alarms,
+                            json!({"jsonrpc": "2.0", "method": "devices.read", "params": {"id": 7}, "id": 7}));
+        assert_eq!(INVALID_PARAMS, response.error.unwrap().code);
+        let response = call(&mut group, &alarms, json!({"jsonrpc": "2.0", "method": "reboot", "id": 8}));
+        assert_eq!(METHOD_NOT_FOUND, response.error.unwrap().code);
+        let response: Response = serde_json::from_str(&handle(&mut group, None, "{").unwrap()).unwrap();
+        assert_eq!(PARSE_ERROR, response.error.unwrap().code);
+
+        // notifications are not answered
+        assert!(handle(&mut group, None, r#"{"jsonrpc": "2.0", "method": "mode.get"}"#).is_none());
+    }
+
+    #[test]
+    fn tenants() {
+        let mut tenants = Tenants::default();
+        for (name, token) in [("a", "alpha"), ("b", "beta")] {
+            let mut tenant = Tenant::new(name, format!("/tmp/sensd/rpc_tests/{}", name), token);
+            tenant.add_group("main").unwrap().add_mode(Mode::new(name));
+            tenants.push(tenant).unwrap();
+        }
+        let mut call = |request: Value| -> Response {
+            serde_json::from_str(&handle_tenants(&mut tenants, &request.to_string()).unwrap()).unwrap()
+        };
+
+        let response = call(json!({"jsonrpc": "2.0", "method": "mode.set", "params": {"name": "b"}, "token": "beta", "id": 1}));
+        assert_eq!(json!("b"), response.result.unwrap());
+
+        // modes of other tenants are not visible
+        let response = call(json!({"jsonrpc": "2.0", "method": "mode.set", "params": {"name": "b"}, "token": "alpha", "id": 2}));
+        assert!(response.error.is_some());
+        let response = call(json!({"jsonrpc": "2.0", "method": "mode.get", "token": "alpha", "group": "main", "id": 3}));
+        // `null` result is deserialized as `None`
+        assert_eq!((None, None), (response.result, response.error));
+
+        for request in [json!({"jsonrpc": "2.0", "method": "mode.get", "id": 4}),
+                        json!({"jsonrpc": "2.0", "method": "mode.get", "token": "gamma", "id": 5})] {
+            assert_eq!(UNAUTHORIZED, call(request).error.unwrap().code);
+        }
+        let response = call(json!({"jsonrpc": "2.0", "method": "mode.get", "token": "alpha", "group": "spare", "id": 6}));
+        assert_eq!(INVALID_PARAMS, response.error.unwrap().code);
+    }
+}
@@ -0,0 +1,214 @@
+use std::fs::{remove_file, symlink_metadata};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+use crate::alarm::AlarmHandler;
+use crate::control::rpc::{handle, handle_tenants};
+use crate::errors::ErrorType;
+use crate::helpers::Def;
+use crate::storage::Group;
+use crate::tenant::Tenants;
+
+/// Connected client and any partially received request
+struct Client {
+    stream: UnixStream,
+    buffer: Vec<u8>,
+}
+
+/// Local control channel accepting newline-delimited JSON-RPC 2.0 requests on a Unix socket
+///
+/// The socket never blocks: [`ControlSocket::serve()`] handles whatever requests are pending and
+/// returns immediately, so it is called from the main event loop alongside
+/// [`crate::storage::Group::poll()`]. Requests are therefore executed between polls, and never
+/// concurrently with them.
+///
+/// Access is governed by filesystem permissions of the socket file. No network port is opened.
+///
+/// # Example
+///
+/// ```no_run
+/// use sensd::control::ControlSocket;
+/// use sensd::storage::Group;
+///
+/// let mut group = Group::new("main");
+/// let mut control = ControlSocket::bind("/run/sensd/control.sock").unwrap();
+/// loop {
+///     let _ = group.poll();
+///     control.serve(&mut group).unwrap();
+/// }
+/// ```
+pub struct ControlSocket {
+    listener: UnixListener,
+    path: PathBuf,
+    clients: Vec<Client>,
+    alarms: Option<Def<AlarmHandler>>,
+}
+
+impl ControlSocket {
+    /// Listen on socket at `path`
+    ///
+    /// A stale socket file left behind by a previous process is replaced. Any other file at `path`
+    /// is left untouched and an error is returned.
+    pub fn bind<P: AsRef<Path>>(path: P) -> Result<Self, ErrorType> {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(metadata) = symlink_metadata(&path) {
+            if metadata.file_type().is_socket() {
+                remove_file(&path)?;
+            }
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener, path, clients: Vec::new(), alarms: None })
+    }
+
+    /// Builder method to set handler used by `alarms.list` and `alarms.ack`
+    pub fn set_alarm_handler(mut self, alarms: Def<AlarmHandler>) -> Self {
+        self.alarms = Some(alarms);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Accept pending connections and execute all complete requests
+    ///
+    /// Clients that disconnect or cannot be written to are dropped.
+    ///
+    /// # Returns
+    ///
+    /// Number of requests handled
+    pub fn serve(&mut self, group: &mut Group) -> Result<usize, ErrorType> {
+        let alarms = self.alarms.clone();
+        self.serve_with(|line| handle(group, alarms.as_ref(), line))
+    }
+
+    /// Accept pending connections and execute all complete requests on behalf of `tenants`
+    ///
+    /// Requests are authorized by their token as described by [`handle_tenants()`]. The handler set
+    /// by [`ControlSocket::set_alarm_handler()`] is not used, since each tenant has its own.
+    ///
+    /// # Returns
+    ///
+    /// Number of requests handled
+    pub fn serve_tenants(&mut self, tenants: &mut Tenants) -> Result<usize, ErrorType> {
+        self.serve_with(|line| handle_tenants(tenants, line))
+    }
+
+    fn serve_with<F>(&mut self, mut handler: F) -> Result<usize, ErrorType>
+        where F: FnMut(&str) -> Option<String>
+    {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(true)?;
+                    self.clients.push(Client { stream, buffer: Vec::new() });
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let mut handled = 0;
+        self.clients.retain_mut(|client| {
+            let connected = receive(client);
+
+            while let Some(end) = client.buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = client.buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                handled += 1;
+                if let Some(response) = handler(&line) {
+                    if send(&mut client.stream, &response).is_err() {
+                        return false;
+                    }
+                }
+            }
+            connected
+        });
+        Ok(handled)
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = remove_file(&self.path);
+    }
+}
+
+/// Read all available bytes into buffer
+///
+/// # Returns
+///
+/// `false` if client has disconnected
+fn receive(client: &mut Client) -> bool {
+    let mut chunk = [0; 1024];
+    loop {
+        match client.stream.read(&mut chunk) {
+            Ok(0) => return false,
+            Ok(count) => client.buffer.extend_from_slice(&chunk[..count]),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => return false,
+        }
+    }
+}
+
+/// Write a response followed by a newline
+///
+/// Responses are small, so the stream is briefly made blocking rather than queueing output.
+fn send(stream: &mut UnixStream, response: &str) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let result = stream.write_all(format!("{}\n", response).as_bytes());
+    stream.set_nonblocking(true)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::create_dir_all;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    use crate::control::ControlSocket;
+    use crate::storage::{Group, Mode};
+
+    #[test]
+    fn serve() {
+        const DIR: &str = "/tmp/sensd/control_tests";
+        create_dir_all(DIR).unwrap();
+        let path = format!("{}/control.sock", DIR);
+
+        let mut group = Group::new("");
+        group.add_mode(Mode::new("eco"));
+        let mut control = ControlSocket::bind(&path).unwrap();
+        assert_eq!(0, control.serve(&mut group).unwrap());
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        client.write_all(b"{\"jsonrpc\": \"2.0\", \"method\": \"mode.set\", \"params\": {\"name\": \"eco\"}, \"id\": 1}\n").unwrap();
+        // partial requests are held until complete
+        client.write_all(b"{\"jsonrpc\": \"2.0\", \"method\": ").unwrap();
+        client.flush().unwrap();
+
+        assert_eq!(1, control.serve(&mut group).unwrap());
+        assert_eq!(Some(&String::from("eco")), group.mode());
+
+        let mut reader = BufReader::new(client.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!("{\"jsonrpc\":\"2.0\",\"result\":\"eco\",\"id\":1}\n", line);
+
+        client.write_all(b"\"mode.get\", \"id\": 2}\n").unwrap();
+        assert_eq!(1, control.serve(&mut group).unwrap());
+
+        // stale socket is replaced
+        drop(control);
+        let _stale = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        assert!(ControlSocket::bind(&path).is_ok());
+    }
+}
@@ -0,0 +1,328 @@
+//! Simulated greenhouse for exploring sensd without hardware
+//!
+//! [`Demo::greenhouse()`] builds a [`Group`] whose devices are backed by a simple physical model
+//! instead of hardware, with actions already wired:
+//!
+//! | Id | Device      | Kind                 | Action                                   |
+//! |----|-------------|----------------------|------------------------------------------|
+//! | 0  | temperature | Temperature          |                                          |
+//! | 1  | humidity    | Relative Humidity    | Mister pump runs while below 60%         |
+//! | 2  | ph          | pH                   | Dosing pump runs while below 5.8         |
+//! | 3  | light       | Light                | Grow lights run while below 10,000 lx    |
+//! | 10 | mister pump | Output               |                                          |
+//! | 11 | dosing pump | Output               |                                          |
+//! | 12 | grow lights | Output               |                                          |
+//!
+//! Run the demo with `cargo run --bin sensd -- demo`. The dashboard is redrawn after every poll,
+//! and a [`crate::control::ControlSocket`] is opened within the directory of the group so that the
+//! [`crate::control`] API may be explored alongside.
+//!
+//! # Example
+//!
+//! ```
+//! use sensd::demo::Demo;
+//!
+//! let mut demo = Demo::greenhouse("/tmp/sensd/demo_doc");
+//! demo.step();
+//! assert!(demo.render().contains("temperature"));
+//! ```
+
+use chrono::Duration;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::action::{Action, actions::Threshold, IOCommand, Trigger};
+use crate::console;
+use crate::startup::Startup;
+use crate::errors::DeviceError;
+use crate::helpers::{now, Def};
+use crate::io::{is_on, Device, IdType, Input, IOKind, Output, RawValue, ValueFormat};
+use crate::name::Name;
+use crate::storage::{Directory, Group};
+
+pub const TEMPERATURE: IdType = 0;
+pub const HUMIDITY: IdType = 1;
+pub const PH: IdType = 2;
+pub const LIGHT: IdType = 3;
+pub const MISTER: IdType = 10;
+pub const DOSING_PUMP: IdType = 11;
+pub const GROW_LIGHTS: IdType = 12;
+
+/// Number of readings shown in dashboard sparklines
+const HISTORY: usize = 30;
+
+/// State of the simulated greenhouse
+#[derive(Debug, Clone)]
+pub struct Environment {
+    pub temperature: f32,
+    pub humidity: f32,
+    pub ph: f32,
+    pub light: f32,
+
+    pub mister: bool,
+    pub dosing: bool,
+    pub lights: bool,
+
+    /// Simulated time of day in hours
+    pub hour: f32,
+
+    /// State of pseudo-random sensor noise
+    seed: u32,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            temperature: 22.0,
+            humidity: 65.0,
+            ph: 6.2,
+            light: 0.0,
+            mister: false,
+            dosing: false,
+            lights: false,
+            hour: 6.0,
+            seed: 1,
+        }
+    }
+}
+
+impl Environment {
+    /// Advance simulation by `minutes` of simulated time
+    ///
+    /// Actuators push their quantity towards a healthy range, while the environment drifts away
+    /// from it. Temperature follows the daylight, which is supplemented by the grow lights.
+    pub fn advance(&mut self, minutes: f32) {
+        self.hour = (self.hour + minutes / 60.0) % 24.0;
+
+        let daylight = (std::f32::consts::PI * (self.hour - 6.0) / 12.0).sin().max(0.0);
+        // sensor is mounted outside, so that grow lights do not switch themselves off
+        self.light = daylight * 30_000.0;
+
+        let target = 18.0 + daylight * 10.0 + if self.lights { 2.0 } else { 0.0 };
+        self.temperature += (target - self.temperature) * 0.05 * minutes;
+
+        self.humidity += minutes * if self.mister { 1.5 } else { -0.4 - daylight * 0.4 };
+        self.humidity = self.humidity.clamp(20.0, 100.0);
+
+        self.ph += minutes * if self.dosing { 0.04 } else { -0.01 };
+    }
+
+    /// Apply sensor noise of up to `±amplitude` to `value`
+    fn noisy(&mut self, value: f32, amplitude: f32) -> f32 {
+        // linear congruential generator, sufficient for simulated noise
+        self.seed = self.seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        let unit = (self.seed >> 16) as f32 / u16::MAX as f32;
+        value + (unit * 2.0 - 1.0) * amplitude
+    }
+}
+
+/// Simulated greenhouse and its dashboard
+pub struct Demo {
+    group: Group,
+    environment: Def<Environment>,
+
+    /// Simulated minutes that pass per poll
+    speed: f32,
+}
+
+impl Demo {
+    /// Build simulated greenhouse with data stored under `root`
+    pub fn greenhouse<P: AsRef<Path>>(root: P) -> Self {
+        let environment = Def::new(Environment::default());
+        let mut group = Group::with_root("greenhouse", root);
+        group.set_interval(Duration::seconds(1));
+
+        for (id, name, field) in [
+            (MISTER, "mister pump", Actuator::Mister),
+            (DOSING_PUMP, "dosing pump", Actuator::Dosing),
+            (GROW_LIGHTS, "grow lights", Actuator::Lights),
+        ] {
+            let environment = environment.clone();
+            let command = IOCommand::OutputFn(Arc::new(move |value| {
+                let mut environment = environment.lock().unwrap();
+                match field {
+                    Actuator::Mister => environment.mister = is_on(value),
+                    Actuator::Dosing => environment.dosing = is_on(value),
+                    Actuator::Lights => environment.lights = is_on(value),
+                }
+                Ok(())
+            }));
+            group.push_output(Output::new(name, id, None).set_command(command).init_log());
+        }
+
+        let sensors = [
+            (TEMPERATURE, "temperature", IOKind::Temperature, None),
+            (HUMIDITY, "humidity", IOKind::RelativeHumidity, Some((MISTER, 60.0))),
+            (PH, "ph", IOKind::PH, Some((DOSING_PUMP, 5.8))),
+            (LIGHT, "light", IOKind::Light, Some((GROW_LIGHTS, 10_000.0))),
+        ];
+        for (id, name, kind, action) in sensors {
+            let reading = environment.clone();
+            let command = IOCommand::InputFn(Arc::new(move || {
+                let mut environment = reading.lock().unwrap();
+                let value = match kind {
+                    IOKind::Temperature => environment.temperature,
+                    IOKind::RelativeHumidity => environment.humidity,
+                    IOKind::PH => environment.ph,
+                    _ => environment.light,
+                };
+                let amplitude = value.abs() * 0.005;
+                RawValue::Float(environment.noisy(value, amplitude))
+            }));
+            let mut input = Input::new(name, id, kind).set_command(command).init_log();
+
+            if let Some((output, threshold)) = action {
+                let output = group.outputs.get(&output).unwrap().clone();
+                let action = Threshold::with_output(
+                    format!("{} control", name), RawValue::Float(threshold), Trigger::LT, output);
+                input = input.init_publisher();
+                input.publisher_mut().as_mut().unwrap().subscribe(action.into_boxed());
+            }
+            group.push_input(input);
+        }
+
+        Self { group, environment, speed: 5.0 }
+    }
+
+    /// Builder method to set how many simulated minutes pass per poll
+    pub fn set_speed(mut self, minutes: f32) -> Self {
+        self.speed = minutes;
+        self
+    }
+
+    pub fn group(&self) -> &Group {
+        &self.group
+    }
+
+    pub fn group_mut(&mut self) -> &mut Group {
+        &mut self.group
+    }
+
+    /// Copy of the current state of the simulation
+    pub fn environment(&self) -> Environment {
+        self.environment.lock().unwrap().clone()
+    }
+
+    /// Advance simulation and poll all inputs, regardless of polling interval
+    ///
+    /// # Returns
+    ///
+    /// Errors that arose from reading inputs
+    pub fn step(&mut self) -> Vec<DeviceError> {
+        self.environment.lock().unwrap().advance(self.speed);
+
+        let mut errors = Vec::new();
+        for input in self.group.inputs.values() {
+            if let Err(e) = input.lock().unwrap().read() {
+                errors.push(e);
+            }
+        }
+        errors
+    }
+
+    /// Render dashboard of every device, including a sparkline of recent readings
+    pub fn render(&self) -> String {
+        let view = self.group.view();
+        let hour = self.environment.lock().unwrap().hour;
+        let mut text = format!(
+            "█▓▒░ sensd demo: {} ({:02}:{:02} simulated)\n\n",
+            self.group.name(), hour as u32, ((hour % 1.0) * 60.0) as u32);
+
+        for device in view.inputs().values() {
+            let state = match device.state() {
+                Some(state) => state,
+                None => continue,
+            };
+            let value = state.value
+                .map(|value| ValueFormat::for_kind(state.metadata.kind).format(value))
+                .unwrap_or_else(|| String::from("-"));
+            let sparkline = device.sparkline(HISTORY).unwrap_or_default();
+            text.push_str(&format!("  {:<12} {:>14}  {}\n", state.metadata.name, value, sparkline));
+        }
+        text.push('\n');
+        for device in view.outputs().values() {
+            if let Some(state) = device.state() {
+                let value = state.value.map(is_on).unwrap_or(false);
+                text.push_str(&format!("  {:<12} {:>14}\n", state.metadata.name, if value { "on" } else { "off" }));
+            }
+        }
+        text
+    }
+
+    /// Run simulation and redraw dashboard until interrupted
+    ///
+    /// Inputs are polled at the polling interval of the group. A control socket is opened at
+    /// `control.sock` within the directory of the group.
+    pub fn run(&mut self) {
+        if let Err(e) = Startup::new().check_group(&self.group).run() {
+            console::error(e);
+            return;
+        }
+
+        #[cfg(unix)]
+        let mut control = {
+            let dir = self.group.full_path();
+            let _ = std::fs::create_dir_all(&dir);
+            crate::control::ControlSocket::bind(dir.join("control.sock")).ok()
+        };
+
+        loop {
+            let started = now();
+            let errors = self.step();
+
+            // clear screen and move cursor to top-left
+            print!("\x1B[2J\x1B[H{}", self.render());
+            for error in errors {
+                console::error(error);
+            }
+
+            #[cfg(unix)]
+            if let Some(control) = control.as_mut() {
+                if let Err(e) = control.serve(&mut self.group) {
+                    console::warning(format!("control socket failed: {}", e));
+                }
+                console::info(format!("control socket: {}", control.path().display()));
+            }
+
+            let remaining = self.group.tick() - (now() - started);
+            if let Ok(remaining) = remaining.to_std() {
+                std::thread::sleep(remaining);
+            }
+        }
+    }
+}
+
+/// Actuator of the simulation driven by an output
+#[derive(Clone, Copy)]
+enum Actuator {
+    Mister,
+    Dosing,
+    Lights,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::demo::{Demo, DOSING_PUMP, GROW_LIGHTS, MISTER};
+    use crate::io::{DeviceGetters, RawValue};
+
+    #[test]
+    fn greenhouse() {
+        let mut demo = Demo::greenhouse("/tmp/sensd/demo_tests").set_speed(10.0);
+        for _ in 0..200 {
+            assert!(demo.step().is_empty());
+        }
+
+        // actions keep environment within a healthy range
+        let environment = demo.environment();
+        assert!(environment.ph > 5.5, "{}", environment.ph);
+        assert!(environment.humidity > 55.0, "{}", environment.humidity);
+
+        for id in [MISTER, DOSING_PUMP, GROW_LIGHTS] {
+            let output = demo.group().outputs.get(&id).unwrap().lock().unwrap();
+            assert!(matches!(output.state(), Some(RawValue::Binary(_))));
+        }
+
+        let dashboard = demo.render();
+        assert!(dashboard.contains("dosing pump"));
+    }
+}
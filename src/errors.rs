@@ -2,7 +2,7 @@ use std::error::Error as _Error;
 
 use custom_error::custom_error;
 
-use crate::io::DeviceMetadata;
+use crate::io::{DeviceMetadata, RawValue};
 
 pub type ErrorType = Box<dyn _Error>;
 
@@ -11,15 +11,149 @@ custom_error! { pub ContainerError
     ContainerEmpty = "Container is empty",
     ContainerNotEmpty = "Container is not empty",
     KeyExists{key: String} = "Device entry {key} exists",
+    KeyMissing{key: String} = "Device entry {key} does not exist",
+    AmbiguousKey{key: String} = "Device entry {key} exists as both an input and an output",
+    MetadataMismatch{expected: String, found: String} = "Cannot merge log of {found} into log of {expected}",
 }
 
 custom_error! { pub DeviceError
     HWFault{metadata: DeviceMetadata} = "HW fault from {metadata}",
     NoCommand{metadata: DeviceMetadata} = "No associated command for {metadata}",
     ValueExpected{metadata: DeviceMetadata} = "Value expected from {metadata}",
+    LoadLimited{metadata: DeviceMetadata} = "Write to {metadata} is queued by load limiter",
+    Inhibited{metadata: DeviceMetadata} = "Write to {metadata} is queued until output is enabled",
+    UnknownProfile{name: String} = "No device profile named \"{name}\"",
+    NonFinite{metadata: DeviceMetadata} = "Non-finite value read from {metadata}",
+    Timeout{metadata: DeviceMetadata, attempts: usize} = "Timed out reading {metadata} after {attempts} attempt(s)",
+    BusError{metadata: DeviceMetadata, attempts: usize, source: std::io::Error} = "Bus error from {metadata} after {attempts} attempt(s): {source}",
+    ChecksumFailure{metadata: DeviceMetadata, attempts: usize} = "Checksum failure from {metadata} after {attempts} attempt(s)",
+    OutOfRange{metadata: DeviceMetadata, attempts: usize, value: RawValue} = "Raw reading {value} from {metadata} is out of range after {attempts} attempt(s)",
+    WriteFailed{metadata: DeviceMetadata, value: RawValue} = "Write of {value} to {metadata} failed",
+    Claimed{metadata: DeviceMetadata, owner: String} = "{metadata} is claimed by \"{owner}\"",
+    NotClaimed{metadata: DeviceMetadata, owner: String} = "\"{owner}\" does not hold claim on {metadata}",
+    Asynchronous{metadata: DeviceMetadata} = "{metadata} has an asynchronous command, which is only read by Group::poll_async()",
+}
+
+// Failure reported by a fallible input command. See [`crate::action::IOCommand::TryInputFn`].
+custom_error! { pub ReadError
+    Timeout = "Device did not respond",
+    Bus{source: std::io::Error} = "Bus error: {source}",
+    Checksum = "Checksum mismatch",
+    OutOfRange{value: RawValue} = "Raw reading {value} is out of range",
+    Unavailable = "Source of derived value has no reading",
+    Asynchronous = "Asynchronous command cannot be read synchronously",
+}
+
+impl ReadError {
+    /// Convert into a [`DeviceError`] describing a single failed attempt of a device
+    pub fn with_metadata(self, metadata: DeviceMetadata) -> DeviceError {
+        let attempts = 1;
+        match self {
+            ReadError::Timeout => DeviceError::Timeout { metadata, attempts },
+            ReadError::Bus { source } => DeviceError::BusError { metadata, attempts, source },
+            ReadError::Checksum => DeviceError::ChecksumFailure { metadata, attempts },
+            ReadError::OutOfRange { value } => DeviceError::OutOfRange { metadata, attempts, value },
+            ReadError::Unavailable => DeviceError::ValueExpected { metadata },
+            ReadError::Asynchronous => DeviceError::Asynchronous { metadata },
+        }
+    }
+}
+
+custom_error! { pub InspectError
+    InvalidPredicate{predicate: String} = "Invalid value predicate \"{predicate}\"",
+}
+
+impl DeviceError {
+    /// Check if error is caused by a write being queued instead of performed
+    pub fn is_queued(&self) -> bool {
+        matches!(self, DeviceError::LoadLimited { .. } | DeviceError::Inhibited { .. })
+    }
+
+    /// Metadata of device which caused error. `None` if error is not caused by a device.
+    pub fn metadata(&self) -> Option<&DeviceMetadata> {
+        match self {
+            DeviceError::HWFault { metadata }
+            | DeviceError::NoCommand { metadata }
+            | DeviceError::ValueExpected { metadata }
+            | DeviceError::LoadLimited { metadata }
+            | DeviceError::Inhibited { metadata }
+            | DeviceError::NonFinite { metadata }
+            | DeviceError::Asynchronous { metadata }
+            | DeviceError::Timeout { metadata, .. }
+            | DeviceError::BusError { metadata, .. }
+            | DeviceError::ChecksumFailure { metadata, .. }
+            | DeviceError::OutOfRange { metadata, .. }
+            | DeviceError::WriteFailed { metadata, .. }
+            | DeviceError::Claimed { metadata, .. }
+            | DeviceError::NotClaimed { metadata, .. } => Some(metadata),
+            DeviceError::UnknownProfile { .. } => None,
+        }
+    }
+
+    /// Number of attempts made before error was returned. `None` if error does not track attempts.
+    pub fn attempts(&self) -> Option<usize> {
+        match self {
+            DeviceError::Timeout { attempts, .. }
+            | DeviceError::BusError { attempts, .. }
+            | DeviceError::ChecksumFailure { attempts, .. }
+            | DeviceError::OutOfRange { attempts, .. } => Some(*attempts),
+            _ => None,
+        }
+    }
+
+    /// Overwrite number of attempts of errors which track attempts
+    pub(crate) fn set_attempts(mut self, count: usize) -> Self {
+        match &mut self {
+            DeviceError::Timeout { attempts, .. }
+            | DeviceError::BusError { attempts, .. }
+            | DeviceError::ChecksumFailure { attempts, .. }
+            | DeviceError::OutOfRange { attempts, .. } => *attempts = count,
+            _ => (),
+        }
+        self
+    }
+}
+
+custom_error! { pub ValueError
+    InvalidFixed{value: String} = "Invalid fixed-point decimal \"{value}\"",
 }
 
 custom_error! { pub FilesystemError
     SerializationError{msg: String} = "Error during serialization: {msg}",
     PermissionError{path: String} = "Incorrect permissions for {path}",
+    PathExists{path: String} = "{path} already exists",
+    MigrationMismatch{path: String, expected: usize, found: usize} = "Migration of {path} kept {found} of {expected} events",
+    UnsupportedFormat{path: String, found: u32, supported: u32} = "{path} was written in data format {found}, but this build only reads up to format {supported}",
+}
+
+custom_error! { pub StartupError
+    Incompatible{count: usize, report: String} = "Found {count} incompatibilities with this build:\n{report}",
+}
+
+custom_error! { pub IntegrityError
+    Unsigned{path: String} = "{path} has not been signed",
+    Malformed{path: String} = "Signature of {path} is malformed",
+    Mismatch{path: String} = "{path} does not match its signature",
+}
+
+custom_error! { pub TenantError
+    NameExists{tenant: String} = "Tenant \"{tenant}\" already exists",
+    TokenExists{tenant: String} = "Token of tenant \"{tenant}\" is already used by another tenant",
+    RootOverlaps{tenant: String, other: String} = "Root of tenant \"{tenant}\" overlaps root of \"{other}\"",
+    GroupExists{tenant: String, name: String} = "Tenant \"{tenant}\" already has a group named \"{name}\"",
+    QuotaExceeded{tenant: String, usage: u64, quota: u64} = "Tenant \"{tenant}\" uses {usage} bytes, exceeding quota of {quota} bytes",
+}
+
+custom_error! { pub AlarmError
+    UnknownAlarm{name: String} = "No active alarm named \"{name}\"",
+    AlreadyAcknowledged{name: String} = "Alarm \"{name}\" has already been acknowledged",
+    DailyCapReached{limit: usize} = "Daily limit of {limit} notifications has been reached",
+}
+
+custom_error! { pub ActionError
+    UnknownAction{name: String} = "No action named \"{name}\"",
+    UnknownParameter{action: String, parameter: String} = "Action \"{action}\" has no parameter \"{parameter}\"",
+    InvalidParameter{action: String, parameter: String} = "Invalid value for parameter \"{parameter}\" of \"{action}\"",
+    TargetUnavailable{name: String} = "Write target of \"{name}\" is unavailable",
+    UnknownMode{name: String} = "No mode named \"{name}\"",
 }
@@ -1,7 +1,12 @@
+use std::cell::Cell;
 use std::fs::{create_dir_all, File};
 use std::path::Path;
 use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockResult};
+use std::time::Instant;
 
+use chrono::{DateTime, Utc};
+
+use crate::console;
 use crate::errors::ErrorType;
 
 /// Return a writable `File` from a given path.
@@ -27,13 +32,58 @@ where P: AsRef<Path>
         })
 }
 
+/// Wall-clock and monotonic time which override system time
+#[derive(Clone, Copy)]
+pub(crate) struct FakeTime {
+    pub wall: DateTime<Utc>,
+    pub monotonic: Instant,
+}
+
+thread_local! {
+    static CLOCK: Cell<Option<FakeTime>> = const { Cell::new(None) };
+}
+
+/// Current wall-clock time as seen by sensd
+///
+/// Wall-clock time may jump (ie: by NTP corrections) and should only be used for timestamps. Use
+/// [`monotonic()`] for measuring intervals.
+///
+/// Equivalent to `Utc::now()` unless overridden on the current thread by
+/// [`crate::testkit::FakeClock`].
+pub fn now() -> DateTime<Utc> {
+    CLOCK.with(|clock| clock.get())
+        .map(|fake| fake.wall)
+        .unwrap_or_else(Utc::now)
+}
+
+/// Current monotonic time as seen by sensd
+///
+/// Equivalent to `Instant::now()` unless overridden on the current thread by
+/// [`crate::testkit::FakeClock`].
+pub fn monotonic() -> Instant {
+    CLOCK.with(|clock| clock.get())
+        .map(|fake| fake.monotonic)
+        .unwrap_or_else(Instant::now)
+}
+
+/// Override values returned by [`now()`] and [`monotonic()`] on the current thread. `None` restores
+/// system time.
+pub(crate) fn set_clock(time: Option<FakeTime>) {
+    CLOCK.with(|clock| clock.set(time));
+}
+
+/// Get current override set by [`set_clock()`]
+pub(crate) fn fake_time() -> Option<FakeTime> {
+    CLOCK.with(|clock| clock.get())
+}
+
 /// Check a sequence of `Result`
 /// This used to check the returned outputs of recursive or parallel operations.
 /// This does not crash the program but instead prints any errors via `dbg!`.
 pub fn check_results<T>(results: &[Result<T, ErrorType>]) -> Result<(), ErrorType> {
     for result in results {
         match result {
-            Err(e) => eprintln!("█▓▒░ ERROR: {}", e),
+            Err(e) => console::error(e),
             _ => continue,
         };
     }
@@ -0,0 +1,333 @@
+//! Log inspection utilities powering `sensd logs tail/grep/stats`
+//!
+//! These functions operate on persisted device logs so that command-line tools and third-party
+//! tools do not need to parse log files themselves. A [`Follower`] yields events as they are saved,
+//! similar to `tail -f`.
+//!
+//! # Example
+//!
+//! ```
+//! use chrono::{Duration, Utc};
+//! use sensd::inspect::{grep, stats, tail, Predicate};
+//! use sensd::io::{DeviceMetadata, IOEvent, RawValue};
+//! use sensd::storage::Log;
+//!
+//! let start = Utc::now();
+//! let mut log = Log::with_metadata(&DeviceMetadata::default());
+//! for (i, value) in [21.0, 24.5, 26.0].iter().enumerate() {
+//!     log.push(IOEvent::with_timestamp(start + Duration::seconds(i as i64), RawValue::Float(*value))).unwrap();
+//! }
+//!
+//! assert_eq!(RawValue::Float(26.0), tail(&log, 1)[0].value);
+//!
+//! let predicate: Predicate = "> 24".parse().unwrap();
+//! assert_eq!(2, grep(&log, |event| predicate.matches(event)).len());
+//!
+//! let stats = stats(&log);
+//! assert_eq!(3, stats.events);
+//! assert_eq!(26.0, stats.values.unwrap().max);
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::{metadata, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use crate::alarm::ValueSummary;
+use crate::errors::{ErrorType, FilesystemError, InspectError};
+use crate::io::{EventKind, IOEvent, RawValue};
+use crate::report::sorted;
+use crate::storage::Log;
+
+/// Read a log file saved by [`crate::storage::Persistent::save()`]
+pub fn read_log<P: AsRef<Path>>(path: P) -> Result<Log, ErrorType> {
+    let reader = BufReader::new(File::open(path)?);
+    serde_json::from_reader(reader)
+        .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() }.into())
+}
+
+/// Most recent events of a log
+///
+/// # Parameters
+///
+/// - `log`: Log to inspect
+/// - `count`: Maximum number of events to return
+///
+/// # Returns
+///
+/// Up to `count` events in chronological order
+pub fn tail(log: &Log, count: usize) -> Vec<IOEvent> {
+    let events = sorted(log);
+    let skip = events.len().saturating_sub(count);
+    events.into_iter().skip(skip).cloned().collect()
+}
+
+/// Events of a log which satisfy `predicate`, in chronological order
+///
+/// # See Also
+///
+/// - [`Predicate`] for filtering by value
+pub fn grep<F>(log: &Log, predicate: F) -> Vec<IOEvent>
+where
+    F: Fn(&IOEvent) -> bool
+{
+    sorted(log).into_iter()
+        .filter(|event| predicate(event))
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Comparison operator of a [`Predicate`]
+pub enum Comparison {
+    GT,
+    GTE,
+    LT,
+    LTE,
+    EQ,
+    NE,
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Comparison::GT => ">",
+            Comparison::GTE => ">=",
+            Comparison::LT => "<",
+            Comparison::LTE => "<=",
+            Comparison::EQ => "==",
+            Comparison::NE => "!=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Numeric comparison of event values against a constant
+///
+/// Predicates are parsed from strings such as `"> 25"`, `"<=7.5"`, or `"== true"`. Binary values
+/// compare as `1` and `0`.
+pub struct Predicate {
+    pub comparison: Comparison,
+    pub value: f32,
+}
+
+impl Predicate {
+    /// Check if the value of `event` satisfies predicate
+    pub fn matches(&self, event: &IOEvent) -> bool {
+        let value = match to_float(event.value) {
+            Some(value) => value,
+            None => return false,
+        };
+        match self.comparison {
+            Comparison::GT => value > self.value,
+            Comparison::GTE => value >= self.value,
+            Comparison::LT => value < self.value,
+            Comparison::LTE => value <= self.value,
+            Comparison::EQ => value == self.value,
+            Comparison::NE => value != self.value,
+        }
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = InspectError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InspectError::InvalidPredicate { predicate: s.to_string() };
+        let s = s.trim();
+
+        // two-character operators must be checked first
+        let operators = [
+            (">=", Comparison::GTE),
+            ("<=", Comparison::LTE),
+            ("==", Comparison::EQ),
+            ("!=", Comparison::NE),
+            (">", Comparison::GT),
+            ("<", Comparison::LT),
+        ];
+        let (comparison, rest) = operators.iter()
+            .find_map(|(symbol, comparison)| s.strip_prefix(symbol).map(|rest| (*comparison, rest)))
+            .ok_or_else(invalid)?;
+
+        let value = match rest.trim() {
+            "true" => 1.0,
+            "false" => 0.0,
+            number => number.parse().map_err(|_| invalid())?,
+        };
+        Ok(Self { comparison, value })
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.comparison, self.value)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Summary of an entire log
+pub struct LogStats {
+    pub events: usize,
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+
+    /// Number of events of each kind
+    pub kinds: HashMap<EventKind, usize>,
+
+    /// Statistics of numeric readings. `None` if log contains no numeric readings.
+    pub values: Option<ValueSummary>,
+}
+
+/// Summarize a log
+pub fn stats(log: &Log) -> LogStats {
+    let events = sorted(log);
+
+    let mut kinds = HashMap::new();
+    for event in events.iter() {
+        *kinds.entry(event.kind).or_insert(0) += 1;
+    }
+    let values: Vec<f32> = events.iter()
+        .filter(|event| event.kind == EventKind::SensorRead)
+        .filter_map(|event| event.value.finite_float())
+        .collect();
+
+    LogStats {
+        events: events.len(),
+        first: events.first().map(|event| event.timestamp),
+        last: events.last().map(|event| event.timestamp),
+        kinds,
+        values: ValueSummary::from_values(&values),
+    }
+}
+
+/// Follows a log file, yielding events as they are saved
+///
+/// Logs are saved as a whole, therefore the file is re-read whenever it is modified.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::thread::sleep;
+/// use std::time::Duration;
+/// use sensd::inspect::Follower;
+///
+/// let mut follower = Follower::new("/var/sensd/logs/main/log_temperature_0.json");
+/// loop {
+///     for event in follower.poll().unwrap() {
+///         println!("{}: {}", event.timestamp, event.value);
+///     }
+///     sleep(Duration::from_secs(1));
+/// }
+/// ```
+pub struct Follower {
+    path: PathBuf,
+
+    /// Timestamp of most recent yielded event
+    seen: Option<DateTime<Utc>>,
+    /// Modification time of file when last read
+    modified: Option<SystemTime>,
+}
+
+impl Follower {
+    /// Begin following log at `path`
+    ///
+    /// Events that already exist are skipped. Use [`tail()`] to show recent events beforehand.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let mut follower = Self {
+            path: path.as_ref().to_path_buf(),
+            seen: None,
+            modified: None,
+        };
+        let _ = follower.poll();
+        follower
+    }
+
+    /// Events saved since the previous poll
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with new events in chronological order. Empty if file is unchanged or does not exist.
+    /// - `Err` if file could not be read or parsed
+    pub fn poll(&mut self) -> Result<Vec<IOEvent>, ErrorType> {
+        let modified = match metadata(&self.path) {
+            Ok(metadata) => metadata.modified()?,
+            Err(_) => return Ok(Vec::new()),
+        };
+        if self.modified == Some(modified) {
+            return Ok(Vec::new());
+        }
+
+        let log = read_log(&self.path)?;
+        self.modified = Some(modified);
+
+        let events = grep(&log, |event| self.seen.map(|seen| event.timestamp > seen).unwrap_or(true));
+        if let Some(last) = events.last() {
+            self.seen = Some(last.timestamp);
+        }
+        Ok(events)
+    }
+}
+
+/// Numeric value of event. Binary values are `1` or `0`.
+fn to_float(value: RawValue) -> Option<f32> {
+    match value {
+        RawValue::Binary(state) => Some(if state { 1.0 } else { 0.0 }),
+        _ => value.as_float(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use std::fs::{create_dir_all, remove_dir_all};
+
+    use crate::inspect::{Comparison, Follower, Predicate};
+    use crate::io::{DeviceMetadata, IOEvent, RawValue};
+    use crate::storage::{Document, Log, Persistent};
+
+    #[test]
+    fn predicate() {
+        let predicate: Predicate = ">= 7.5".parse().unwrap();
+        assert_eq!(Comparison::GTE, predicate.comparison);
+        assert!(predicate.matches(&IOEvent::new(RawValue::Float(7.5))));
+        assert!(!predicate.matches(&IOEvent::new(RawValue::Int(7))));
+
+        let predicate: Predicate = "==true".parse().unwrap();
+        assert!(predicate.matches(&IOEvent::new(RawValue::Binary(true))));
+
+        assert!("7.5".parse::<Predicate>().is_err());
+        assert!("> warm".parse::<Predicate>().is_err());
+    }
+
+    #[test]
+    fn follow() {
+        const TMP_DIR: &str = "/tmp/sensd/inspect_tests";
+        create_dir_all(TMP_DIR).unwrap();
+
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let mut log = Log::with_metadata(&DeviceMetadata::default());
+        log.set_dir_ref(TMP_DIR);
+        log.push(IOEvent::with_timestamp(start, RawValue::Float(1.0))).unwrap();
+        log.save().unwrap();
+
+        let mut follower = Follower::new(log.full_path());
+        assert!(follower.poll().unwrap().is_empty());
+
+        log.push(IOEvent::with_timestamp(start + Duration::seconds(1), RawValue::Float(2.0))).unwrap();
+        // ensure modification time differs
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        log.save().unwrap();
+
+        let events = follower.poll().unwrap();
+        assert_eq!(1, events.len());
+        assert_eq!(RawValue::Float(2.0), events[0].value);
+
+        let _ = remove_dir_all(TMP_DIR);
+    }
+}
@@ -0,0 +1,225 @@
+//! Slowly varying offset correction of drifting sensors
+use chrono::{DateTime, Utc};
+
+use crate::io::{DeviceView, EventKind, EventQuality, IOEvent, Input, RawValue};
+use crate::storage::Log;
+
+/// Name of field storing the offset added to a corrected [`IOEvent`]
+///
+/// The uncorrected reading is the value of the event less this field.
+pub const BIAS_FIELD: &str = "bias";
+
+/// Name of field storing the uncorrected reading that a manual reference entry was compared to
+///
+/// See [`Input::add_reference()`].
+pub const MEASURED_FIELD: &str = "measured";
+
+#[derive(Clone)]
+/// Offset correction learned from a reference
+///
+/// Cheap sensors drift slowly (ie: dissolved oxygen probes). The offset between the drifting
+/// sensor and a trusted reference is learned as an exponentially weighted average, so that noise
+/// in any single comparison is smoothed out, and is added to every reading by [`Input::read()`].
+///
+/// The reference is either a second input, set by [`BiasCorrection::set_reference()`], or manual
+/// measurements entered by [`Input::add_reference()`], which are logged as
+/// [`EventKind::Annotation`] events. Both may be used together.
+///
+/// Every corrected reading stores the offset applied in [`IOEvent::fields`] as [`BIAS_FIELD`], so
+/// corrections are traceable. The learned offset is restored from the log when a
+/// [`crate::storage::Group`] is loaded. See [`BiasCorrection::restore()`].
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::IOCommand;
+/// use sensd::io::{BiasCorrection, Device, Input, IOKind, RawValue, BIAS_FIELD};
+///
+/// let mut probe = Input::new("do", 0, IOKind::Unassigned)
+///     .set_command(IOCommand::Input(|| RawValue::Float(7.5)))
+///     .set_bias_correction(BiasCorrection::new(0.5))
+///     .init_log();
+///
+/// probe.read().unwrap();
+/// // a handheld meter reads 8.0
+/// probe.add_reference(8.0).unwrap();
+///
+/// let event = probe.read().unwrap();
+/// assert_eq!(RawValue::Float(8.0), event.value);
+/// assert_eq!(Some(RawValue::Float(0.5)), event.field(BIAS_FIELD));
+/// ```
+pub struct BiasCorrection {
+    /// Weight of each new comparison, between 0 (exclusive) and 1
+    gain: f32,
+    /// Maximum magnitude of offset
+    limit: Option<f32>,
+    reference: Option<DeviceView<Input>>,
+
+    /// `None` until first comparison
+    offset: Option<f32>,
+    /// Time of last reference reading that was compared
+    compared: Option<DateTime<Utc>>,
+}
+
+impl BiasCorrection {
+    /// Constructor for an offset which has not been learned yet
+    ///
+    /// The first comparison sets the offset directly. Subsequent comparisons move the offset by
+    /// `gain` of their difference from it.
+    ///
+    /// # Panics
+    ///
+    /// If `gain` is not within `(0, 1]`
+    pub fn new(gain: f32) -> Self {
+        assert!(gain > 0.0 && gain <= 1.0, "Gain must be within (0, 1]");
+        Self {
+            gain,
+            limit: None,
+            reference: None,
+            offset: None,
+            compared: None,
+        }
+    }
+
+    /// Builder method to compare every reading against the latest logged reading of `reference`
+    ///
+    /// Each reference reading is only compared once. The reference input must have a log, and
+    /// should have a lower id than the corrected input so that it is polled first.
+    pub fn set_reference(mut self, reference: DeviceView<Input>) -> Self {
+        self.reference = Some(reference);
+        self
+    }
+
+    /// Builder method to limit magnitude of offset, so that a faulty reference cannot dominate
+    pub fn set_limit(mut self, limit: f32) -> Self {
+        self.limit = Some(limit.abs());
+        self
+    }
+
+    /// Learned offset. `None` until first comparison.
+    pub fn offset(&self) -> Option<f32> {
+        self.offset
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Compare an uncorrected reading with a reference value
+    pub fn observe(&mut self, reference: f32, measured: f32) {
+        let error = reference - measured;
+        let offset = match self.offset {
+            None => error,
+            Some(offset) => offset + self.gain * (error - offset),
+        };
+        self.offset = Some(match self.limit {
+            Some(limit) => offset.clamp(-limit, limit),
+            None => offset,
+        });
+    }
+
+    /// Compare with reference input if it has a reading that has not been compared
+    fn compare_reference(&mut self, measured: f32) {
+        let latest = self.reference.as_ref()
+            .and_then(|reference| reference.history(1))
+            .and_then(|mut history| history.pop());
+        let latest = match latest {
+            Some(event) => event,
+            None => return,
+        };
+        let unseen = self.compared.is_none_or(|compared| latest.timestamp > compared);
+        if let (true, true, Some(value)) = (unseen, latest.quality != EventQuality::Bad, latest.value.finite_float()) {
+            self.observe(value, measured);
+            self.compared = Some(latest.timestamp);
+        }
+    }
+
+    /// Correct value of `event` in place
+    ///
+    /// Events without a finite value, or read before an offset has been learned, are left as-is.
+    pub fn apply(&mut self, event: &mut IOEvent) {
+        let value = match event.value.finite_float() {
+            Some(value) => value,
+            None => return,
+        };
+        self.compare_reference(value);
+        if let Some(offset) = self.offset {
+            if let Some(corrected) = event.value.with_float(value + offset) {
+                event.fields.insert(BIAS_FIELD.to_string(), RawValue::Float(offset));
+                event.value = corrected;
+            }
+        }
+    }
+
+    /// Restore learned offset from the log of the corrected input
+    ///
+    /// The offset applied to the latest corrected reading is restored, then any manual reference
+    /// entries logged after it are replayed.
+    pub fn restore(&mut self, log: &Log) {
+        let latest = log.iter()
+            .rev()
+            .find_map(|(_, event)| event.field(BIAS_FIELD)
+                .and_then(|offset| offset.as_float())
+                .map(|offset| (event.timestamp, offset)));
+        if let Some((timestamp, offset)) = latest {
+            self.offset = Some(offset);
+            self.compared = Some(timestamp);
+        }
+
+        let since = latest.map(|(timestamp, _)| timestamp);
+        let entries: Vec<(f32, f32)> = log.filter_kind(EventKind::Annotation)
+            .filter(|(_, event)| since.is_none_or(|since| event.timestamp > since))
+            .filter_map(|(_, event)| Some((event.value.finite_float()?, event.field(MEASURED_FIELD)?.finite_float()?)))
+            .collect();
+        for (reference, measured) in entries {
+            self.observe(reference, measured);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::IOCommand;
+    use crate::io::{BiasCorrection, Device, DeviceView, EventKind, Input, IOKind, RawValue, BIAS_FIELD, MEASURED_FIELD};
+    use crate::storage::Chronicle;
+
+    #[test]
+    fn reference_input() {
+        let reference = Input::new("reference", 0, IOKind::Unassigned)
+            .set_command(IOCommand::Input(|| RawValue::Float(9.0)))
+            .init_log()
+            .into_deferred();
+        let mut probe = Input::new("probe", 1, IOKind::Unassigned)
+            .set_command(IOCommand::Input(|| RawValue::Float(8.0)))
+            .set_bias_correction(BiasCorrection::new(0.5)
+                .set_reference(DeviceView::new(&reference))
+                .set_limit(0.8))
+            .init_log();
+
+        // nothing learned without reference reading
+        let event = probe.read().unwrap();
+        assert_eq!(RawValue::Float(8.0), event.value);
+        assert!(event.fields.is_empty());
+
+        // first comparison is clamped to limit
+        reference.lock().unwrap().read().unwrap();
+        let event = probe.read().unwrap();
+        assert_eq!(RawValue::Float(8.8), event.value);
+        assert_eq!(Some(RawValue::Float(0.8)), event.field(BIAS_FIELD));
+
+        // reference readings are only compared once
+        probe.read().unwrap();
+        assert_eq!(Some(0.8), probe.bias_correction().unwrap().offset());
+
+        // manual entries are logged and learned
+        let entry = probe.add_reference(8.6).unwrap();
+        assert_eq!(EventKind::Annotation, entry.kind);
+        assert_eq!(Some(RawValue::Float(8.0)), entry.field(MEASURED_FIELD));
+        assert!((probe.bias_correction().unwrap().offset().unwrap() - 0.7).abs() < 1e-6);
+
+        // offset and later manual entries are restored from log
+        let mut restored = BiasCorrection::new(0.5);
+        restored.restore(&probe.log().unwrap().try_lock().unwrap());
+        assert_eq!(probe.bias_correction().unwrap().offset(), restored.offset());
+    }
+}
@@ -0,0 +1,211 @@
+//! Temperature compensation of conductivity and pH readings
+use serde::{Deserialize, Serialize};
+
+use crate::io::{DeviceView, EventQuality, IOEvent, Input, RawValue, TemperatureUnit};
+
+/// Temperature coefficient of most nutrient solutions, as a fraction per °C
+pub const NUTRIENT_COEFFICIENT: f32 = 0.019;
+
+/// Name of field storing the uncompensated reading of a compensated [`IOEvent`]
+pub const RAW_FIELD: &str = "raw";
+
+/// Name of field storing the temperature in °C used to compensate an [`IOEvent`]
+pub const TEMPERATURE_FIELD: &str = "temperature";
+
+/// pH at which electrode potential does not depend on temperature
+const ISOPOTENTIAL_PH: f32 = 7.0;
+
+/// Zero °C in Kelvin
+const ZERO_CELSIUS: f32 = 273.15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Standard curve used to convert a reading to its value at the reference temperature
+pub enum CompensationCurve {
+    /// Linear compensation of electrical conductivity
+    ///
+    /// `EC_ref = EC / (1 + coefficient * (T - T_ref))`
+    Conductivity { coefficient: f32 },
+
+    /// Compensation of pH electrode slope according to the Nernst equation
+    ///
+    /// `pH_ref = 7 + (pH - 7) * (T_ref + 273.15) / (T + 273.15)`
+    Nernst,
+}
+
+impl CompensationCurve {
+    /// Compensate `value` measured at `celsius` to `reference` °C
+    pub fn compensate(&self, value: f32, celsius: f32, reference: f32) -> f32 {
+        match self {
+            CompensationCurve::Conductivity { coefficient } => value / (1.0 + coefficient * (celsius - reference)),
+            CompensationCurve::Nernst => {
+                ISOPOTENTIAL_PH + (value - ISOPOTENTIAL_PH) * (reference + ZERO_CELSIUS) / (celsius + ZERO_CELSIUS)
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+/// Temperature compensation of an EC or pH [`Input`] using the latest reading of a temperature input
+///
+/// Set with [`Input::set_compensation()`]. Every reading is compensated by [`Input::read()`]
+/// before it is cached, logged, or published, so that actions act on the compensated value. The
+/// uncompensated reading and the temperature used are stored in [`IOEvent::fields`] as
+/// [`RAW_FIELD`] and [`TEMPERATURE_FIELD`].
+///
+/// When the temperature input has no finite reading, or is in use, the reading is left
+/// uncompensated and marked [`EventQuality::Suspect`].
+///
+/// The temperature input should have a lower id than the compensated input so that it is polled
+/// first by [`crate::storage::Group::poll()`].
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::IOCommand;
+/// use sensd::io::{Compensation, Device, DeviceView, Input, IOKind, RawValue, RAW_FIELD};
+///
+/// let temperature = Input::new("temperature", 0, IOKind::Temperature)
+///     .set_command(IOCommand::Input(|| RawValue::Float(30.0)))
+///     .into_deferred();
+/// let mut ec = Input::new("ec", 1, IOKind::EC)
+///     .set_command(IOCommand::Input(|| RawValue::Float(2.19)))
+///     .set_compensation(Compensation::conductivity(DeviceView::new(&temperature)));
+///
+/// temperature.lock().unwrap().read().unwrap();
+/// let event = ec.read().unwrap();
+///
+/// assert_eq!(Some(RawValue::Float(2.19)), event.field(RAW_FIELD));
+/// assert!((event.value.as_float().unwrap() - 2.0).abs() < 0.01);
+/// ```
+pub struct Compensation {
+    curve: CompensationCurve,
+    temperature: DeviceView<Input>,
+    unit: TemperatureUnit,
+
+    /// Temperature in °C that readings are compensated to
+    reference: f32,
+}
+
+impl Compensation {
+    /// Constructor for compensation using `curve`, to a reference of 25 °C
+    ///
+    /// # Parameters
+    ///
+    /// - `curve`: Compensation curve
+    /// - `temperature`: View of input reading temperature in °C
+    pub fn new(curve: CompensationCurve, temperature: DeviceView<Input>) -> Self {
+        Self {
+            curve,
+            temperature,
+            unit: TemperatureUnit::default(),
+            reference: 25.0,
+        }
+    }
+
+    /// Compensation of conductivity using [`NUTRIENT_COEFFICIENT`]
+    pub fn conductivity(temperature: DeviceView<Input>) -> Self {
+        Self::new(CompensationCurve::Conductivity { coefficient: NUTRIENT_COEFFICIENT }, temperature)
+    }
+
+    /// Compensation of pH using [`CompensationCurve::Nernst`]
+    pub fn ph(temperature: DeviceView<Input>) -> Self {
+        Self::new(CompensationCurve::Nernst, temperature)
+    }
+
+    /// Builder method to set unit of temperature readings
+    pub fn set_unit(mut self, unit: TemperatureUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Builder method to set temperature in °C that readings are compensated to
+    pub fn set_reference(mut self, reference: f32) -> Self {
+        self.reference = reference;
+        self
+    }
+
+    pub fn curve(&self) -> CompensationCurve {
+        self.curve
+    }
+
+    pub fn reference(&self) -> f32 {
+        self.reference
+    }
+
+    /// Latest temperature reading in °C
+    ///
+    /// # Returns
+    ///
+    /// `None` if temperature input is in use or has no finite reading
+    pub fn temperature(&self) -> Option<f32> {
+        let value = self.temperature.state()?.value?.finite_float()?;
+        Some(self.unit.to_celsius(value))
+    }
+
+    /// Compensate value of `event` in place
+    ///
+    /// Events without a finite value are left as-is.
+    pub fn apply(&self, event: &mut IOEvent) {
+        let value = match event.value.finite_float() {
+            Some(value) => value,
+            None => return,
+        };
+        let celsius = match self.temperature() {
+            Some(celsius) => celsius,
+            None => {
+                if event.quality == EventQuality::Good {
+                    event.quality = EventQuality::Suspect;
+                }
+                return;
+            }
+        };
+        event.fields.insert(RAW_FIELD.to_string(), event.value);
+        event.fields.insert(TEMPERATURE_FIELD.to_string(), RawValue::Float(celsius));
+        event.value = RawValue::Float(self.curve.compensate(value, celsius, self.reference));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::IOCommand;
+    use crate::io::{Compensation, CompensationCurve, Device, DeviceGetters, DeviceView, EventQuality, Input, IOKind, RawValue, TemperatureUnit, RAW_FIELD, TEMPERATURE_FIELD};
+
+    #[test]
+    fn curves() {
+        let conductivity = CompensationCurve::Conductivity { coefficient: 0.02 };
+        assert_eq!(2.0, conductivity.compensate(2.0, 25.0, 25.0));
+        assert!((conductivity.compensate(1.8, 20.0, 25.0) - 2.0).abs() < 1e-4);
+
+        // isopotential point is unaffected by temperature
+        assert_eq!(7.0, CompensationCurve::Nernst.compensate(7.0, 40.0, 25.0));
+        // slope is steeper at higher temperatures, so readings are drawn towards isopotential point
+        assert!(CompensationCurve::Nernst.compensate(5.0, 40.0, 25.0) > 5.0);
+        assert!(CompensationCurve::Nernst.compensate(5.0, 10.0, 25.0) < 5.0);
+    }
+
+    #[test]
+    fn ph() {
+        let temperature = Input::new("temperature", 0, IOKind::Temperature)
+            .set_command(IOCommand::Input(|| RawValue::Float(104.0)))
+            .into_deferred();
+        let mut ph = Input::new("ph", 1, IOKind::PH)
+            .set_command(IOCommand::Input(|| RawValue::Float(5.0)))
+            .set_compensation(Compensation::ph(DeviceView::new(&temperature)).set_unit(TemperatureUnit::Fahrenheit))
+            .init_log();
+
+        // no temperature reading
+        let event = ph.read().unwrap();
+        assert_eq!(RawValue::Float(5.0), event.value);
+        assert_eq!(EventQuality::Suspect, event.quality);
+        assert!(event.fields.is_empty());
+
+        temperature.lock().unwrap().read().unwrap();
+        let event = ph.read().unwrap();
+        let expected = 7.0 - 2.0 * 298.15 / 313.15;
+        assert!((event.value.as_float().unwrap() - expected).abs() < 1e-4);
+        assert_eq!(Some(RawValue::Float(5.0)), event.field(RAW_FIELD));
+        assert_eq!(Some(RawValue::Float(40.0)), event.field(TEMPERATURE_FIELD));
+        assert_eq!(EventQuality::Good, event.quality);
+        assert_eq!(Some(event.value), *ph.state());
+    }
+}
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+
+use crate::action::{Action, BoxedAction, WriteTarget};
+use crate::helpers::Def;
+use crate::io::{IOEvent, RawValue};
+
+/// Direction of change of a smoothed value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Trend {
+    Rising,
+    Falling,
+    #[default]
+    Steady,
+}
+
+/// Channels exposed by [`Ewma`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EwmaChannel {
+    /// Smoothed value
+    Value,
+    /// Smoothed variance
+    Variance,
+    /// Trend direction as `RawValue::Int` of `1`, `-1`, or `0`
+    Trend,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+/// Exponentially weighted statistics maintained by [`Ewma`]
+pub struct EwmaStats {
+    /// Smoothed value, `None` until first reading
+    pub value: Option<f32>,
+    pub variance: f32,
+    /// Smoothed change between readings
+    pub slope: f32,
+    pub trend: Trend,
+    /// Number of readings incorporated
+    pub count: usize,
+}
+
+impl EwmaStats {
+    /// Get single channel as [`RawValue`]
+    ///
+    /// # Returns
+    ///
+    /// `None` if no readings have been incorporated
+    pub fn channel(&self, channel: EwmaChannel) -> Option<RawValue> {
+        let value = self.value?;
+        Some(match channel {
+            EwmaChannel::Value => RawValue::Float(value),
+            EwmaChannel::Variance => RawValue::Float(self.variance),
+            EwmaChannel::Trend => RawValue::Int(match self.trend {
+                Trend::Rising => 1,
+                Trend::Falling => -1,
+                Trend::Steady => 0,
+            }),
+        })
+    }
+}
+
+/// Soft sensor exposing exponentially weighted moving statistics of a source input
+///
+/// [`Ewma`] subscribes to the [`crate::action::Publisher`] of a source [`crate::io::Input`], and is
+/// updated on every reading. Statistics are stored behind a shared [`Def`] handle obtained by
+/// [`Ewma::stats()`] so that other actions can be trend-aware without recomputing from logs.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::{Action, IOCommand};
+/// use sensd::io::{Device, Ewma, EwmaChannel, Input, RawValue, Trend};
+///
+/// let ewma = Ewma::new("ph smoothing", 0.3);
+/// let stats = ewma.stats();
+///
+/// let mut input = Input::default()
+///     .set_command(IOCommand::Input(|| RawValue::Float(7.0)))
+///     .init_publisher();
+/// input.publisher_mut().as_mut().unwrap().subscribe(ewma.into_boxed());
+///
+/// input.read().unwrap();
+///
+/// let stats = stats.try_lock().unwrap();
+/// assert_eq!(Some(RawValue::Float(7.0)), stats.channel(EwmaChannel::Value));
+/// assert_eq!(Trend::Steady, stats.trend);
+/// ```
+pub struct Ewma {
+    name: String,
+    /// Smoothing factor between `0.0` and `1.0`. Higher values weigh recent readings more.
+    alpha: f32,
+    /// Minimum absolute slope to be considered a trend
+    deadband: f32,
+    stats: Def<EwmaStats>,
+}
+
+impl Ewma {
+    /// Constructor for [`Ewma`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of soft sensor
+    /// - `alpha`: Smoothing factor between `0.0` and `1.0`
+    ///
+    /// # Panics
+    ///
+    /// If `alpha` is not within `(0.0, 1.0]`
+    pub fn new<N>(name: N, alpha: f32) -> Self
+    where
+        N: Into<String>
+    {
+        assert!(alpha > 0.0 && alpha <= 1.0, "Smoothing factor must be within (0.0, 1.0]");
+        Self {
+            name: name.into(),
+            alpha,
+            deadband: 0.0,
+            stats: Def::default(),
+        }
+    }
+
+    /// Builder method to set minimum slope considered to be a trend
+    pub fn set_deadband(mut self, deadband: f32) -> Self {
+        self.deadband = deadband.abs();
+        self
+    }
+
+    /// Shared handle to statistics
+    pub fn stats(&self) -> Def<EwmaStats> {
+        self.stats.clone()
+    }
+
+    /// Incorporate a new reading
+    ///
+    /// Non-numeric and non-finite readings are ignored.
+    pub fn update(&mut self, value: RawValue) {
+        let value = match value.finite_float() {
+            Some(value) => value,
+            None => return,
+        };
+
+        let mut stats = self.stats.try_lock().unwrap();
+        match stats.value {
+            None => stats.value = Some(value),
+            Some(mean) => {
+                let diff = value - mean;
+                let increment = self.alpha * diff;
+                stats.value = Some(mean + increment);
+                stats.variance = (1.0 - self.alpha) * (stats.variance + diff * increment);
+                stats.slope += self.alpha * (increment - stats.slope);
+
+                stats.trend = if stats.slope > self.deadband {
+                    Trend::Rising
+                } else if stats.slope < -self.deadband {
+                    Trend::Falling
+                } else {
+                    Trend::Steady
+                };
+            }
+        }
+        stats.count += 1;
+    }
+}
+
+impl Action for Ewma {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn evaluate(&mut self, data: &IOEvent) {
+        self.update(data.value)
+    }
+
+    /// Soft sensors have no output. This is a no-op.
+    fn set_output<T>(self, _target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized
+    {
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        None
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{Ewma, EwmaChannel, RawValue, Trend};
+
+    #[test]
+    fn update() {
+        let mut ewma = Ewma::new("", 0.5);
+        let stats = ewma.stats();
+
+        assert!(stats.try_lock().unwrap().channel(EwmaChannel::Value).is_none());
+
+        ewma.update(RawValue::Float(0.0));
+        ewma.update(RawValue::Float(4.0));
+        {
+            let stats = stats.try_lock().unwrap();
+            assert_eq!(Some(2.0), stats.value);
+            assert_eq!(4.0, stats.variance);
+            assert_eq!(Trend::Rising, stats.trend);
+        }
+
+        for _ in 0..10 {
+            ewma.update(RawValue::Float(-10.0));
+        }
+        let stats = stats.try_lock().unwrap();
+        assert_eq!(Trend::Falling, stats.trend);
+        assert_eq!(Some(RawValue::Int(-1)), stats.channel(EwmaChannel::Trend));
+        assert_eq!(12, stats.count);
+    }
+
+    #[test]
+    fn ignores_binary() {
+        let mut ewma = Ewma::new("", 0.5);
+        ewma.update(RawValue::Binary(true));
+
+        assert_eq!(0, ewma.stats().try_lock().unwrap().count);
+    }
+}
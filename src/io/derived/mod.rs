@@ -0,0 +1,12 @@
+//! Soft sensors whose values are computed from other inputs
+mod bias;
+mod compensation;
+mod ewma;
+mod psychrometric;
+mod totalizer;
+
+pub use bias::*;
+pub use compensation::*;
+pub use ewma::*;
+pub use psychrometric::*;
+pub use totalizer::*;
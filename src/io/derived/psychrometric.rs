@@ -0,0 +1,554 @@
+//! Soft sensors computing psychrometric properties of air from temperature and relative humidity
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::action::{Action, BoxedAction, IOCommand, WriteTarget};
+use crate::errors::ReadError;
+use crate::helpers::Def;
+use crate::io::{Device, IdType, IOEvent, Input, IOKind, RawValue};
+
+/// Coefficients of the Magnus form used by [`saturation_vapor_pressure()`] and [`dew_point()`]
+const MAGNUS_A: f32 = 17.27;
+const MAGNUS_B: f32 = 237.3;
+
+/// Specific gas constant of water vapor in J/(kg·K)
+const WATER_VAPOR_CONSTANT: f32 = 461.5;
+
+/// Unit of temperature readings used by psychrometric soft sensors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    /// Convert a temperature in this unit to degrees Celsius
+    pub fn to_celsius(&self, value: f32) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => value,
+            TemperatureUnit::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            TemperatureUnit::Kelvin => value - 273.15,
+        }
+    }
+}
+
+/// Saturation vapor pressure over water in kPa, using the Tetens equation
+///
+/// # Parameters
+///
+/// - `celsius`: Temperature in °C
+pub fn saturation_vapor_pressure(celsius: f32) -> f32 {
+    0.6108 * (MAGNUS_A * celsius / (celsius + MAGNUS_B)).exp()
+}
+
+/// Vapor pressure deficit in kPa
+///
+/// # Parameters
+///
+/// - `air`: Air temperature in °C
+/// - `relative_humidity`: Relative humidity of air in percent
+/// - `leaf`: Leaf temperature in °C. Passing `air` gives the deficit of air.
+pub fn vapor_pressure_deficit(air: f32, relative_humidity: f32, leaf: f32) -> f32 {
+    saturation_vapor_pressure(leaf) - saturation_vapor_pressure(air) * relative_humidity / 100.0
+}
+
+/// Temperature in °C at which air becomes saturated, using the Magnus formula
+///
+/// # Parameters
+///
+/// - `celsius`: Air temperature in °C
+/// - `relative_humidity`: Relative humidity in percent. Must be greater than 0.
+pub fn dew_point(celsius: f32, relative_humidity: f32) -> f32 {
+    let gamma = (relative_humidity / 100.0).ln() + MAGNUS_A * celsius / (MAGNUS_B + celsius);
+    MAGNUS_B * gamma / (MAGNUS_A - gamma)
+}
+
+/// Apparent temperature in °C, using the regression of the US National Weather Service
+///
+/// Below roughly 27 °C, the simpler Steadman approximation is used, as the regression is not valid.
+///
+/// # Parameters
+///
+/// - `celsius`: Air temperature in °C
+/// - `relative_humidity`: Relative humidity in percent
+pub fn heat_index(celsius: f32, relative_humidity: f32) -> f32 {
+    let t = celsius * 9.0 / 5.0 + 32.0;
+    let rh = relative_humidity;
+
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    let fahrenheit = if (simple + t) / 2.0 < 80.0 {
+        simple
+    } else {
+        let mut index = -42.379 + 2.049_015_3 * t + 10.143_332 * rh
+            - 0.224_755_4 * t * rh - 0.006_837_83 * t * t
+            - 0.054_817_17 * rh * rh + 0.001_228_74 * t * t * rh
+            + 0.000_852_82 * t * rh * rh - 0.000_001_99 * t * t * rh * rh;
+
+        // adjustments for dry and very humid air
+        if rh < 13.0 && (80.0..=112.0).contains(&t) {
+            index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+        } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+            index += (rh - 85.0) / 10.0 * (87.0 - t) / 5.0;
+        }
+        index
+    };
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+/// Mass of water vapor per volume of air in g/m³
+///
+/// # Parameters
+///
+/// - `celsius`: Air temperature in °C
+/// - `relative_humidity`: Relative humidity in percent
+pub fn absolute_humidity(celsius: f32, relative_humidity: f32) -> f32 {
+    let pascals = saturation_vapor_pressure(celsius) * relative_humidity * 10.0;
+    pascals / (WATER_VAPOR_CONSTANT * (celsius + 273.15)) * 1000.0
+}
+
+/// Latest readings of source inputs
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct AirReadings {
+    temperature: Option<f32>,
+    humidity: Option<f32>,
+}
+
+/// Source inputs shared by all psychrometric soft sensors
+#[derive(Clone, Default)]
+struct AirSources {
+    readings: Def<AirReadings>,
+    unit: TemperatureUnit,
+}
+
+impl AirSources {
+    fn subscriber(&self, name: &str, temperature: bool) -> BoxedAction {
+        AirSource { name: name.to_string(), temperature, readings: self.readings.clone() }.into_boxed()
+    }
+
+    /// Latest temperature in unit of source, and relative humidity
+    ///
+    /// # Returns
+    ///
+    /// - `Err` with [`ReadError::Unavailable`] if either source has no finite reading
+    /// - `Err` with [`ReadError::OutOfRange`] if relative humidity is not within 0-100%
+    fn latest(&self) -> Result<(f32, f32), ReadError> {
+        let readings = *self.readings.lock().unwrap();
+        let temperature = readings.temperature.ok_or(ReadError::Unavailable)?;
+        let humidity = readings.humidity.ok_or(ReadError::Unavailable)?;
+        if !(0.0..=100.0).contains(&humidity) {
+            return Err(ReadError::OutOfRange { value: RawValue::Float(humidity) });
+        }
+        Ok((temperature, humidity))
+    }
+
+    /// Latest temperature in °C, and relative humidity
+    fn latest_celsius(&self) -> Result<(f32, f32), ReadError> {
+        let (temperature, humidity) = self.latest()?;
+        Ok((self.unit.to_celsius(temperature), humidity))
+    }
+}
+
+/// Create input of `kind` whose readings are computed by `compute`
+fn derived_input<N, F>(name: N, id: IdType, kind: IOKind, compute: F) -> Input
+where
+    N: Into<String>,
+    F: Fn() -> Result<f32, ReadError> + Send + Sync + 'static,
+{
+    Input::new(name, id, kind)
+        .set_command(IOCommand::TryInputFn(Arc::new(move || compute().map(RawValue::Float))))
+}
+
+/// Soft sensor computing vapor pressure deficit (VPD) from temperature and relative humidity
+///
+/// VPD describes the drying power of air, and therefore how quickly plants transpire. Unlike
+/// relative humidity, the same VPD has the same effect on plants regardless of temperature, which
+/// makes it the quantity that should be controlled.
+///
+/// The actions returned by [`Vpd::temperature()`] and [`Vpd::humidity()`] subscribe to the
+/// [`crate::action::Publisher`] of the source inputs and record their latest readings.
+/// [`Vpd::into_input()`] then creates an [`Input`] whose readings are computed from them, so that
+/// VPD is logged, and actions may subscribe to it, like any other input. Source inputs should have
+/// lower ids than the VPD input so that they are polled first by [`crate::storage::Group::poll()`].
+/// Otherwise, VPD lags by one polling cycle.
+///
+/// [`DewPoint`], [`HeatIndex`], and [`AbsoluteHumidity`] are used in the same way.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::IOCommand;
+/// use sensd::io::{Device, Input, IOKind, RawValue, TemperatureUnit, Vpd};
+///
+/// let vpd = Vpd::new().set_unit(TemperatureUnit::Fahrenheit);
+///
+/// let mut temperature = Input::new("temperature", 0, IOKind::Temperature)
+///     .set_command(IOCommand::Input(|| RawValue::Float(77.0)))
+///     .init_publisher();
+/// temperature.publisher_mut().as_mut().unwrap().subscribe(vpd.temperature());
+/// let mut humidity = Input::new("humidity", 1, IOKind::RelativeHumidity)
+///     .set_command(IOCommand::Input(|| RawValue::Float(60.0)))
+///     .init_publisher();
+/// humidity.publisher_mut().as_mut().unwrap().subscribe(vpd.humidity());
+///
+/// let mut input = vpd.into_input("vpd", 2);
+///
+/// temperature.read().unwrap();
+/// humidity.read().unwrap();
+/// let kpa = input.read().unwrap().value.as_float().unwrap();
+/// assert!((kpa - 1.27).abs() < 0.01);
+/// ```
+#[derive(Clone, Default)]
+pub struct Vpd {
+    sources: AirSources,
+
+    /// Difference between leaf and air temperature, in degrees of temperature unit
+    leaf_offset: f32,
+}
+
+impl Vpd {
+    /// Constructor for [`Vpd`] computing deficit of air with temperatures in °C
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to set unit of temperature readings
+    pub fn set_unit(mut self, unit: TemperatureUnit) -> Self {
+        self.sources.unit = unit;
+        self
+    }
+
+    /// Builder method to compute leaf VPD instead of air VPD
+    ///
+    /// # Parameters
+    ///
+    /// - `offset`: Difference between leaf and air temperature in degrees of temperature unit.
+    ///   Leaves are usually cooler than air, so this is typically negative (ie: `-2.0`).
+    pub fn set_leaf_offset(mut self, offset: f32) -> Self {
+        self.leaf_offset = offset;
+        self
+    }
+
+    /// Action to subscribe to the input reading air temperature
+    pub fn temperature(&self) -> BoxedAction {
+        self.sources.subscriber("vpd temperature", true)
+    }
+
+    /// Action to subscribe to the input reading relative humidity in percent
+    pub fn humidity(&self) -> BoxedAction {
+        self.sources.subscriber("vpd humidity", false)
+    }
+
+    /// Compute VPD in kPa from latest readings of source inputs
+    ///
+    /// # Returns
+    ///
+    /// - `Err` with [`ReadError::Unavailable`] if either source has no finite reading
+    /// - `Err` with [`ReadError::OutOfRange`] if relative humidity is not within 0-100%
+    pub fn compute(&self) -> Result<f32, ReadError> {
+        let (temperature, humidity) = self.sources.latest()?;
+        let unit = self.sources.unit;
+        Ok(vapor_pressure_deficit(unit.to_celsius(temperature), humidity,
+                                  unit.to_celsius(temperature + self.leaf_offset)))
+    }
+
+    /// Create input of kind [`IOKind::VaporPressureDeficit`] which reads VPD
+    ///
+    /// Input has no log or publisher.
+    pub fn into_input<N>(self, name: N, id: IdType) -> Input
+    where
+        N: Into<String>
+    {
+        derived_input(name, id, IOKind::VaporPressureDeficit, move || self.compute())
+    }
+}
+
+/// Soft sensor computing dew point in °C from temperature and relative humidity
+///
+/// Surfaces cooler than the dew point collect condensation, which promotes mold and disease.
+///
+/// See [`Vpd`] for usage.
+#[derive(Clone, Default)]
+pub struct DewPoint {
+    sources: AirSources,
+}
+
+impl DewPoint {
+    /// Constructor for [`DewPoint`] with temperatures in °C
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to set unit of temperature readings. Dew point is always in °C.
+    pub fn set_unit(mut self, unit: TemperatureUnit) -> Self {
+        self.sources.unit = unit;
+        self
+    }
+
+    /// Action to subscribe to the input reading air temperature
+    pub fn temperature(&self) -> BoxedAction {
+        self.sources.subscriber("dew point temperature", true)
+    }
+
+    /// Action to subscribe to the input reading relative humidity in percent
+    pub fn humidity(&self) -> BoxedAction {
+        self.sources.subscriber("dew point humidity", false)
+    }
+
+    /// Compute dew point in °C from latest readings of source inputs
+    ///
+    /// # Returns
+    ///
+    /// See [`Vpd::compute()`]. Relative humidity of 0% is also out of range.
+    pub fn compute(&self) -> Result<f32, ReadError> {
+        let (temperature, humidity) = self.sources.latest_celsius()?;
+        if humidity <= 0.0 {
+            return Err(ReadError::OutOfRange { value: RawValue::Float(humidity) });
+        }
+        Ok(dew_point(temperature, humidity))
+    }
+
+    /// Create input of kind [`IOKind::DewPoint`] which reads dew point
+    ///
+    /// Input has no log or publisher.
+    pub fn into_input<N>(self, name: N, id: IdType) -> Input
+    where
+        N: Into<String>
+    {
+        derived_input(name, id, IOKind::DewPoint, move || self.compute())
+    }
+}
+
+/// Soft sensor computing heat index (apparent temperature) in °C from temperature and relative
+/// humidity
+///
+/// See [`Vpd`] for usage.
+#[derive(Clone, Default)]
+pub struct HeatIndex {
+    sources: AirSources,
+}
+
+impl HeatIndex {
+    /// Constructor for [`HeatIndex`] with temperatures in °C
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to set unit of temperature readings. Heat index is always in °C.
+    pub fn set_unit(mut self, unit: TemperatureUnit) -> Self {
+        self.sources.unit = unit;
+        self
+    }
+
+    /// Action to subscribe to the input reading air temperature
+    pub fn temperature(&self) -> BoxedAction {
+        self.sources.subscriber("heat index temperature", true)
+    }
+
+    /// Action to subscribe to the input reading relative humidity in percent
+    pub fn humidity(&self) -> BoxedAction {
+        self.sources.subscriber("heat index humidity", false)
+    }
+
+    /// Compute heat index in °C from latest readings of source inputs
+    ///
+    /// # Returns
+    ///
+    /// See [`Vpd::compute()`]
+    pub fn compute(&self) -> Result<f32, ReadError> {
+        let (temperature, humidity) = self.sources.latest_celsius()?;
+        Ok(heat_index(temperature, humidity))
+    }
+
+    /// Create input of kind [`IOKind::HeatIndex`] which reads heat index
+    ///
+    /// Input has no log or publisher.
+    pub fn into_input<N>(self, name: N, id: IdType) -> Input
+    where
+        N: Into<String>
+    {
+        derived_input(name, id, IOKind::HeatIndex, move || self.compute())
+    }
+}
+
+/// Soft sensor computing absolute humidity in g/m³ from temperature and relative humidity
+///
+/// See [`Vpd`] for usage.
+#[derive(Clone, Default)]
+pub struct AbsoluteHumidity {
+    sources: AirSources,
+}
+
+impl AbsoluteHumidity {
+    /// Constructor for [`AbsoluteHumidity`] with temperatures in °C
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to set unit of temperature readings
+    pub fn set_unit(mut self, unit: TemperatureUnit) -> Self {
+        self.sources.unit = unit;
+        self
+    }
+
+    /// Action to subscribe to the input reading air temperature
+    pub fn temperature(&self) -> BoxedAction {
+        self.sources.subscriber("absolute humidity temperature", true)
+    }
+
+    /// Action to subscribe to the input reading relative humidity in percent
+    pub fn humidity(&self) -> BoxedAction {
+        self.sources.subscriber("absolute humidity humidity", false)
+    }
+
+    /// Compute absolute humidity in g/m³ from latest readings of source inputs
+    ///
+    /// # Returns
+    ///
+    /// See [`Vpd::compute()`]
+    pub fn compute(&self) -> Result<f32, ReadError> {
+        let (temperature, humidity) = self.sources.latest_celsius()?;
+        Ok(absolute_humidity(temperature, humidity))
+    }
+
+    /// Create input of kind [`IOKind::AbsoluteHumidity`] which reads absolute humidity
+    ///
+    /// Input has no log or publisher.
+    pub fn into_input<N>(self, name: N, id: IdType) -> Input
+    where
+        N: Into<String>
+    {
+        derived_input(name, id, IOKind::AbsoluteHumidity, move || self.compute())
+    }
+}
+
+/// Records readings of a source input of a psychrometric soft sensor
+struct AirSource {
+    name: String,
+    /// Source is temperature if `true`, otherwise relative humidity
+    temperature: bool,
+    readings: Def<AirReadings>,
+}
+
+impl Action for AirSource {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Non-numeric and non-finite readings are ignored
+    fn evaluate(&mut self, data: &IOEvent) {
+        if let Some(value) = data.value.finite_float() {
+            let mut readings = self.readings.lock().unwrap();
+            match self.temperature {
+                true => readings.temperature = Some(value),
+                false => readings.humidity = Some(value),
+            }
+        }
+    }
+
+    /// Soft sensors have no output. This is a no-op.
+    fn set_output<T>(self, _target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized
+    {
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        None
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+    use std::sync::Arc;
+
+    use crate::action::IOCommand;
+    use crate::errors::{DeviceError, ReadError};
+    use crate::helpers::Def;
+    use crate::io::{absolute_humidity, dew_point, heat_index, vapor_pressure_deficit, Device, DeviceGetters, DewPoint, Input, IOKind, RawValue, TemperatureUnit, Vpd};
+
+    #[test]
+    fn deficit() {
+        // saturated air has no deficit
+        assert!(approx_eq!(f32, 0.0, vapor_pressure_deficit(25.0, 100.0, 25.0), epsilon = 1e-6));
+        assert!(approx_eq!(f32, 1.268, vapor_pressure_deficit(25.0, 60.0, 25.0), epsilon = 1e-3));
+
+        // cooler leaves have a lower deficit
+        assert!(vapor_pressure_deficit(25.0, 60.0, 23.0) < vapor_pressure_deficit(25.0, 60.0, 25.0));
+
+        assert_eq!(25.0, TemperatureUnit::Fahrenheit.to_celsius(77.0));
+        assert_eq!(25.0, TemperatureUnit::Kelvin.to_celsius(298.15));
+    }
+
+    #[test]
+    /// Compare against published reference values
+    fn psychrometrics() {
+        assert!(approx_eq!(f32, 25.0, dew_point(25.0, 100.0), epsilon = 1e-3));
+        assert!(approx_eq!(f32, 16.7, dew_point(25.0, 60.0), epsilon = 0.1));
+
+        // NWS table: 90 °F at 70% is 106 °F
+        assert!(approx_eq!(f32, 41.1, heat_index(32.22, 70.0), epsilon = 0.3));
+        // mild conditions are close to air temperature
+        assert!((heat_index(20.0, 50.0) - 20.0).abs() < 1.0);
+
+        assert!(approx_eq!(f32, 13.8, absolute_humidity(25.0, 60.0), epsilon = 0.1));
+    }
+
+    #[test]
+    fn into_input() {
+        let vpd = Vpd::new();
+        let mut temperature = Input::default()
+            .set_command(IOCommand::Input(|| RawValue::Float(25.0)))
+            .init_publisher();
+        temperature.publisher_mut().as_mut().unwrap().subscribe(vpd.temperature());
+        let reading = Def::new(160.0);
+        let source = reading.clone();
+        let mut humidity = Input::default()
+            .set_command(IOCommand::InputFn(Arc::new(move || RawValue::Float(*source.lock().unwrap()))))
+            .init_publisher();
+        humidity.publisher_mut().as_mut().unwrap().subscribe(vpd.humidity());
+        let mut input = vpd.clone().into_input("vpd", 2);
+
+        // sources have not been read
+        let error = input.read().unwrap_err();
+        assert!(matches!(error, DeviceError::ValueExpected { .. }));
+
+        temperature.read().unwrap();
+        humidity.read().unwrap();
+        assert!(matches!(vpd.compute(), Err(ReadError::OutOfRange { .. })));
+
+        *reading.lock().unwrap() = 60.0;
+        humidity.read().unwrap();
+        let leaf = vpd.set_leaf_offset(-2.0);
+        assert!(leaf.compute().unwrap() < input.read().unwrap().value.as_float().unwrap());
+    }
+
+    #[test]
+    fn dew_point_input() {
+        let sensor = DewPoint::new().set_unit(TemperatureUnit::Fahrenheit);
+        let mut temperature = Input::default()
+            .set_command(IOCommand::Input(|| RawValue::Float(77.0)))
+            .init_publisher();
+        temperature.publisher_mut().as_mut().unwrap().subscribe(sensor.temperature());
+        let mut humidity = Input::default()
+            .set_command(IOCommand::Input(|| RawValue::Float(60.0)))
+            .init_publisher();
+        humidity.publisher_mut().as_mut().unwrap().subscribe(sensor.humidity());
+
+        let mut input = sensor.into_input("dew point", 2);
+        assert_eq!(IOKind::DewPoint, input.metadata().kind);
+
+        temperature.read().unwrap();
+        humidity.read().unwrap();
+        let value = input.read().unwrap().value.as_float().unwrap();
+        assert!(approx_eq!(f32, dew_point(25.0, 60.0), value, epsilon = 1e-3));
+    }
+}
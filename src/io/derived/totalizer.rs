@@ -0,0 +1,383 @@
+use chrono::{DateTime, Duration, FixedOffset, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::action::{Action, BoxedAction, ParameterDescriptor, ParameterType, WriteTarget};
+use crate::errors::{ActionError, ErrorType, FilesystemError};
+use crate::helpers::{now, Def};
+use crate::io::{EventQuality, Fixed, IOEvent, RawValue};
+
+/// Time unit of the rate measured by the source input of a [`Totalizer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum RateUnit {
+    #[default]
+    PerSecond,
+    PerMinute,
+    PerHour,
+}
+
+impl RateUnit {
+    /// Number of seconds in unit
+    pub fn seconds(&self) -> f64 {
+        match self {
+            RateUnit::PerSecond => 1.0,
+            RateUnit::PerMinute => 60.0,
+            RateUnit::PerHour => 3600.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Running total maintained by [`Totalizer`]
+pub struct Totals {
+    /// Integral of rate since `since`, in units of the rate multiplied by its time unit (ie: liters
+    /// for a flow in L/min)
+    pub total: Fixed,
+
+    /// Number of times `total` wrapped around the rollover limit
+    pub rollovers: u64,
+
+    /// Time at which counting started, or of the most recent reset
+    pub since: DateTime<Utc>,
+
+    /// Total of the previous day. Only set when totals are reset daily.
+    pub previous: Option<Fixed>,
+
+    /// Timestamp and rate of the most recent reading, which is held until the next reading
+    last: Option<(DateTime<Utc>, f64)>,
+}
+
+impl Totals {
+    fn new(since: DateTime<Utc>) -> Self {
+        Self { total: Fixed::ZERO, rollovers: 0, since, previous: None, last: None }
+    }
+
+    pub fn value(&self) -> RawValue {
+        RawValue::Fixed(self.total)
+    }
+
+    /// Restart counting from zero
+    ///
+    /// The most recent reading is retained, so integration resumes from `at`.
+    pub fn reset(&mut self, at: DateTime<Utc>) {
+        self.preset(Fixed::ZERO, at);
+    }
+
+    /// Restart counting from `total` (ie: to match a mechanical meter)
+    pub fn preset(&mut self, total: Fixed, at: DateTime<Utc>) {
+        self.total = total;
+        self.rollovers = 0;
+        self.since = at;
+    }
+
+    /// Add to total, wrapping around `rollover`
+    ///
+    /// Sum is computed in 128 bits, so that even the largest increments cannot overflow.
+    fn add(&mut self, increment: Fixed, rollover: Fixed) {
+        let limit = rollover.units() as i128;
+        let sum = self.total.units() as i128 + increment.units() as i128;
+        self.rollovers += (sum / limit) as u64;
+        self.total = Fixed::from_units((sum % limit) as i64);
+    }
+}
+
+/// Soft sensor integrating a rate-type input (ie: flow) into a running total (ie: volume)
+///
+/// [`Totalizer`] subscribes to the [`crate::action::Publisher`] of a source
+/// [`crate::io::Input`]. Each reading is held until the next, and rate multiplied by elapsed time is
+/// added to a [`Fixed`] total so that accumulation is exact over any period. Non-numeric readings,
+/// readings of [`EventQuality::Bad`], and negative rates contribute nothing.
+///
+/// Totals wrap around a rollover limit (by default, the largest [`Fixed`]) like a mechanical
+/// meter, and the number of rollovers is counted. Totals are shared by the handle returned by
+/// [`Totalizer::totals()`], are persisted with [`crate::storage::Snapshot`], and are reset or
+/// preset through the `total` parameter (see [`crate::storage::Group::set_parameter()`]).
+///
+/// # Example
+///
+/// ```
+/// use chrono::{Duration, Utc};
+/// use sensd::action::{Action, Publisher};
+/// use sensd::io::{Fixed, IOEvent, RateUnit, RawValue, Totalizer};
+///
+/// // flow sensor reporting liters per minute
+/// let totalizer = Totalizer::new("water used", RateUnit::PerMinute);
+/// let totals = totalizer.totals();
+///
+/// let mut publisher = Publisher::default();
+/// publisher.subscribe(totalizer.into_boxed());
+///
+/// let start = Utc::now();
+/// publisher.propagate(&IOEvent::with_timestamp(start, RawValue::Float(12.0)));
+/// publisher.propagate(&IOEvent::with_timestamp(start + Duration::seconds(30), RawValue::Float(0.0)));
+///
+/// assert_eq!(Fixed::from_int(6), totals.try_lock().unwrap().total);
+/// ```
+pub struct Totalizer {
+    name: String,
+    unit: RateUnit,
+
+    /// Total at which counting wraps around to zero
+    rollover: Fixed,
+
+    /// Readings further apart are not integrated (ie: after the sensor was offline)
+    max_gap: Option<Duration>,
+
+    /// Offset of local time when totals are reset at local midnight
+    daily: Option<FixedOffset>,
+
+    totals: Def<Totals>,
+}
+
+impl Totalizer {
+    /// Constructor for [`Totalizer`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of soft sensor
+    /// - `unit`: Time unit of rate measured by source input
+    pub fn new<N>(name: N, unit: RateUnit) -> Self
+    where
+        N: Into<String>
+    {
+        Self {
+            name: name.into(),
+            unit,
+            rollover: Fixed::MAX,
+            max_gap: None,
+            daily: None,
+            totals: Def::new(Totals::new(now())),
+        }
+    }
+
+    /// Builder method to wrap total around `limit`
+    ///
+    /// # Panics
+    ///
+    /// If `limit` is not positive
+    pub fn set_rollover(mut self, limit: Fixed) -> Self {
+        assert!(limit > Fixed::ZERO, "Rollover limit must be positive");
+        self.rollover = limit;
+        self
+    }
+
+    /// Builder method to ignore intervals between readings which are longer than `gap`
+    pub fn set_max_gap(mut self, gap: Duration) -> Self {
+        self.max_gap = Some(gap);
+        self
+    }
+
+    /// Builder method to reset totals at local midnight, so that `total` is the amount of the
+    /// current day, and `previous` is the amount of the previous day
+    ///
+    /// Intervals spanning midnight are split between both days.
+    pub fn set_daily_reset(mut self, offset: FixedOffset) -> Self {
+        self.daily = Some(offset);
+        self
+    }
+
+    /// Shared handle to totals
+    pub fn totals(&self) -> Def<Totals> {
+        self.totals.clone()
+    }
+
+    /// Incorporate a new reading
+    pub fn update(&mut self, event: &IOEvent) {
+        if event.quality == EventQuality::Bad {
+            return;
+        }
+        let rate = match event.value.finite_float() {
+            Some(rate) => rate.max(0.0) as f64,
+            None => return,
+        };
+
+        let mut totals = self.totals.try_lock().unwrap();
+        if totals.last.is_none() {
+            // count from the first reading, even if it was taken before construction
+            totals.since = totals.since.min(event.timestamp);
+        }
+        if let Some((at, held)) = totals.last {
+            let integrated = self.max_gap.is_none_or(|gap| event.timestamp - at <= gap);
+            if let Some(offset) = self.daily {
+                let midnight = local_midnight(event.timestamp, offset);
+                if totals.since < midnight {
+                    if integrated {
+                        self.integrate(&mut totals, held, at, midnight);
+                    }
+                    totals.previous = Some(totals.total);
+                    totals.reset(midnight);
+                }
+            }
+            if integrated {
+                self.integrate(&mut totals, held, at, event.timestamp);
+            }
+        }
+        totals.last = Some((event.timestamp, rate));
+    }
+
+    /// Add `rate` held between `from` and `to` to total, excluding any time before last reset
+    fn integrate(&self, totals: &mut Totals, rate: f64, from: DateTime<Utc>, to: DateTime<Utc>) {
+        let elapsed = to - from.max(totals.since);
+        if elapsed > Duration::zero() {
+            let seconds = elapsed.num_milliseconds() as f64 / 1000.0;
+            totals.add(Fixed::from_f64(rate * seconds / self.unit.seconds()), self.rollover);
+        }
+    }
+}
+
+/// Most recent local midnight at or before `at`
+fn local_midnight(at: DateTime<Utc>, offset: FixedOffset) -> DateTime<Utc> {
+    let date = at.with_timezone(&offset).date_naive();
+    offset.from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+impl Action for Totalizer {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn evaluate(&mut self, data: &IOEvent) {
+        self.update(data)
+    }
+
+    /// Soft sensors have no output. This is a no-op.
+    fn set_output<T>(self, _target: T) -> Self
+    where
+        T: Into<WriteTarget>,
+        Self: Sized
+    {
+        self
+    }
+
+    fn target(&self) -> Option<WriteTarget> {
+        None
+    }
+
+    fn parameter(&self, name: &str) -> Option<RawValue> {
+        match name {
+            "total" => Some(self.totals.try_lock().unwrap().value()),
+            _ => None,
+        }
+    }
+
+    fn parameter_names(&self) -> &[&'static str] {
+        &["total"]
+    }
+
+    fn describe(&self) -> Vec<ParameterDescriptor> {
+        vec![
+            ParameterDescriptor::new("total", ParameterType::Float)
+                .set_range(0.0, self.rollover.to_f64())
+                .set_value(self.parameter("total")),
+        ]
+    }
+
+    /// Setting `total` presets totals, and `0` resets them
+    fn set_parameter(&mut self, name: &str, value: RawValue) -> Result<(), ErrorType> {
+        if name != "total" {
+            return Err(Box::new(ActionError::UnknownParameter { action: self.name.clone(), parameter: name.to_string() }));
+        }
+        let total = match value {
+            RawValue::Fixed(total) => total,
+            value => Fixed::from_f64(value.as_float().map(f64::from).unwrap_or(-1.0)),
+        };
+        if total < Fixed::ZERO || total >= self.rollover {
+            return Err(Box::new(ActionError::InvalidParameter { action: self.name.clone(), parameter: name.to_string() }));
+        }
+        self.totals.try_lock().unwrap().preset(total, now());
+        Ok(())
+    }
+
+    fn state(&self) -> Option<Value> {
+        serde_json::to_value(&*self.totals.try_lock().unwrap()).ok()
+    }
+
+    fn restore_state(&mut self, state: &Value) -> Result<(), ErrorType> {
+        let totals: Totals = serde_json::from_value(state.clone())
+            .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+        *self.totals.try_lock().unwrap() = totals;
+        Ok(())
+    }
+
+    fn into_boxed(self) -> BoxedAction {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, FixedOffset, TimeZone, Utc};
+
+    use crate::action::Action;
+    use crate::io::{EventQuality, Fixed, IOEvent, RateUnit, RawValue, Totalizer};
+
+    #[test]
+    fn update() {
+        let start = Utc.with_ymd_and_hms(2023, 6, 1, 12, 0, 0).unwrap();
+        let at = |seconds| start + Duration::seconds(seconds);
+        let mut totalizer = Totalizer::new("", RateUnit::PerHour)
+            .set_max_gap(Duration::minutes(5));
+        let totals = totalizer.totals();
+
+        totalizer.update(&IOEvent::with_timestamp(at(0), RawValue::Float(3600.0)));
+        totalizer.update(&IOEvent::with_timestamp(at(10), RawValue::Float(-5.0)));
+        assert_eq!(Fixed::from_int(10), totals.try_lock().unwrap().total);
+
+        // negative rates, bad readings, and gaps contribute nothing
+        totalizer.update(&IOEvent::with_timestamp(at(20), RawValue::Float(7200.0)));
+        let mut bad = IOEvent::with_timestamp(at(25), RawValue::Float(0.0));
+        bad.quality = EventQuality::Bad;
+        totalizer.update(&bad);
+        totalizer.update(&IOEvent::with_timestamp(at(30), RawValue::Float(0.0)));
+        assert_eq!(Fixed::from_int(30), totals.try_lock().unwrap().total);
+        totalizer.update(&IOEvent::with_timestamp(at(30), RawValue::Float(3600.0)));
+        totalizer.update(&IOEvent::with_timestamp(at(3600), RawValue::Float(0.0)));
+        assert_eq!(Fixed::from_int(30), totals.try_lock().unwrap().total);
+
+        // preset and reset
+        totalizer.set_parameter("total", RawValue::Float(100.0)).unwrap();
+        assert_eq!(Some(RawValue::Fixed(Fixed::from_int(100))), totalizer.parameter("total"));
+        assert!(totalizer.set_parameter("total", RawValue::Float(-1.0)).is_err());
+
+        // state is persisted
+        let state = totalizer.state().unwrap();
+        let mut restored = Totalizer::new("", RateUnit::PerHour);
+        restored.restore_state(&state).unwrap();
+        assert_eq!(*totals.try_lock().unwrap(), *restored.totals().try_lock().unwrap());
+    }
+
+    #[test]
+    fn rollover() {
+        let start = Utc::now();
+        let mut totalizer = Totalizer::new("", RateUnit::PerSecond)
+            .set_rollover(Fixed::from_int(1000));
+        let totals = totalizer.totals();
+
+        totalizer.update(&IOEvent::with_timestamp(start, RawValue::Float(100.0)));
+        totalizer.update(&IOEvent::with_timestamp(start + Duration::seconds(25), RawValue::Float(0.0)));
+
+        let totals = totals.try_lock().unwrap();
+        assert_eq!((Fixed::from_int(500), 2), (totals.total, totals.rollovers));
+    }
+
+    #[test]
+    fn daily_reset() {
+        let offset = FixedOffset::west_opt(5 * 3600).unwrap();
+        // 23:00 local
+        let start = Utc.with_ymd_and_hms(2023, 6, 2, 4, 0, 0).unwrap();
+        let mut totalizer = Totalizer::new("", RateUnit::PerMinute)
+            .set_daily_reset(offset);
+        let totals = totalizer.totals();
+        totals.try_lock().unwrap().reset(start);
+
+        totalizer.update(&IOEvent::with_timestamp(start, RawValue::Float(1.0)));
+        totalizer.update(&IOEvent::with_timestamp(start + Duration::minutes(90), RawValue::Float(1.0)));
+
+        let totals = totals.try_lock().unwrap();
+        assert_eq!(Some(Fixed::from_int(60)), totals.previous);
+        assert_eq!(Fixed::from_int(30), totals.total);
+        assert_eq!(start + Duration::hours(1), totals.since);
+    }
+}
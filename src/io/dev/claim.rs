@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Filename of claim audit trail within output directory
+pub const CLAIM_HISTORY_FILENAME: &str = "claim_history.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Precedence of a claim on an output
+///
+/// A claim is only preempted by a claimant of strictly higher priority.
+pub enum ClaimPriority {
+    /// Actions evaluated by the event loop
+    Automatic,
+    /// Operator driving the output by hand
+    Manual,
+    /// Safety interlock, which takes precedence over all other claimants
+    Interlock,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Right of a single owner to write to an output
+pub struct Claim {
+    pub owner: String,
+    pub priority: ClaimPriority,
+    /// Time that claim was granted
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Kind of change in ownership of an output
+pub enum ClaimChange {
+    /// Unclaimed output was claimed, or owner changed priority of its claim
+    Granted,
+    /// Claim was taken from previous owner by a claimant of higher priority
+    Preempted,
+    /// Owner gave up claim, leaving output unclaimed
+    Released,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Audit record of a change in ownership of an output
+///
+/// Returned by [`crate::io::Output::claim()`] and [`crate::io::Output::release()`], and appended
+/// to [`CLAIM_HISTORY_FILENAME`] within the output directory, if it exists.
+pub struct ClaimTransition {
+    pub timestamp: DateTime<Utc>,
+    pub change: ClaimChange,
+
+    /// Claim held before transition
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<Claim>,
+
+    /// Claim held after transition. `None` if output was released.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Claim>,
+}
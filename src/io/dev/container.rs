@@ -1,15 +1,21 @@
 use crate::errors::{ContainerError};
 use crate::helpers::Def;
 use crate::io::{Device, IdTraits};
-use std::collections::hash_map::{Entry, Iter, Values, ValuesMut};
-use std::collections::HashMap;
+#[cfg(not(feature = "indexmap"))]
+use std::collections::hash_map::{Entry, HashMap as Map, Iter, Values, ValuesMut};
+#[cfg(feature = "indexmap")]
+use indexmap::map::{Entry, IndexMap as Map, Iter, Values, ValuesMut};
 use std::fmt::Display;
 use std::ops::DerefMut;
 use crate::storage::{RootPath, Directory};
 
 /// Generic mapped container for storing [`Device`] objects
+///
+/// Devices are stored in a `HashMap`, so iteration order is arbitrary and changes between runs.
+/// When the `indexmap` feature is enabled, devices are stored in an `IndexMap` and iterated in the
+/// order they were inserted. This order is followed by group polls, saves, and reports.
 #[derive(Default)]
-pub struct DeviceContainer<K: IdTraits, D: Device>(HashMap<K, Def<D>>);
+pub struct DeviceContainer<K: IdTraits, D: Device>(Map<K, Def<D>>);
 
 impl<K, D> DeviceContainer<K, D>
 where
@@ -39,6 +45,23 @@ where
         self.0.get(k)
     }
 
+    /// Remove device from container
+    ///
+    /// Any [`crate::action::Routine`] scheduled for this device keeps a dangling reference to its
+    /// log once the returned device is dropped. See [`crate::storage::Group::sweep()`].
+    ///
+    /// With the `indexmap` feature, the order of remaining devices is preserved.
+    pub fn remove(&mut self, k: &K) -> Option<Def<D>> {
+        #[cfg(feature = "indexmap")]
+        {
+            self.0.shift_remove(k)
+        }
+        #[cfg(not(feature = "indexmap"))]
+        {
+            self.0.remove(k)
+        }
+    }
+
     pub fn iter(&self) -> Iter<K, Def<D>> {
         self.0.iter()
     }
@@ -158,4 +181,17 @@ mod tests {
                 .dir().is_some());
     }
 
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn insertion_order() {
+        let mut container = DeviceContainer::default();
+        for id in [7, 3, 9, 1] {
+            container.insert(id, Input::new("", id, None).into_deferred()).unwrap();
+        }
+        container.remove(&3);
+
+        let ids: Vec<u32> = container.iter().map(|(id, _)| *id).collect();
+        assert_eq!(vec![7, 9, 1], ids);
+    }
+
 }
\ No newline at end of file
@@ -8,12 +8,14 @@
 //!
 //! - [`DeviceMetadata`] for user defined metadata and field descriptions
 
+use chrono::{DateTime, Duration, Utc};
 use std::path::{Path};
+use uuid::Uuid;
 use crate::action::IOCommand;
 use crate::helpers::Def;
 use crate::io::{DeviceMetadata, IODirection, IOKind, IdType, RawValue};
 use crate::storage::Document;
-use crate::storage::{Chronicle, Log, Persistent};
+use crate::storage::{Chronicle, EventHooks, Log, Persistent};
 use crate::errors::ErrorType;
 use crate::name::Name;
 
@@ -56,6 +58,58 @@ pub trait Device: Name + Chronicle + DeviceGetters + DeviceSetters + Persistent
         self
     }
 
+    /// Builder method to assign a known UUID instead of the one generated at creation
+    ///
+    /// This is only needed when a device is renamed or renumbered in code before its saved log has
+    /// ever been loaded, since loading a log otherwise restores the UUID it was saved with.
+    ///
+    /// # Parameters
+    ///
+    /// - `uuid`: UUID of device, as found in [`DeviceMetadata`] of its saved log or a snapshot
+    fn set_uuid(mut self, uuid: Uuid) -> Self
+    where
+        Self: Sized,
+    {
+        self.metadata_mut().uuid = uuid;
+        if let Some(log) = self.log() {
+            log.try_lock().unwrap().set_metadata_ref(self.metadata().clone());
+        }
+        self
+    }
+
+    /// Estimate value at an arbitrary time from logged samples
+    ///
+    /// See [`Log::estimate_at()`] for how samples are interpolated and staleness is limited.
+    ///
+    /// # Returns
+    ///
+    /// `None` if device has no log, or no usable sample within `max_gap` before `timestamp`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use sensd::io::{Device, Input, IOEvent, IOKind, RawValue};
+    /// use sensd::storage::Chronicle;
+    ///
+    /// let input = Input::new("temp", 0, IOKind::Temperature).init_log();
+    /// let start = Utc::now() - Duration::minutes(1);
+    /// {
+    ///     let log = input.log().unwrap();
+    ///     let mut log = log.try_lock().unwrap();
+    ///     log.push(IOEvent::with_timestamp(start, RawValue::Float(20.0))).unwrap();
+    ///     log.push(IOEvent::with_timestamp(start + Duration::seconds(10), RawValue::Float(21.0))).unwrap();
+    /// }
+    ///
+    /// let estimate = input.estimate_at(start + Duration::seconds(5), Duration::seconds(30));
+    /// assert_eq!(Some(RawValue::Float(20.5)), estimate);
+    /// ```
+    fn estimate_at(&self, timestamp: DateTime<Utc>, max_gap: Duration) -> Option<RawValue> {
+        let log = self.log()?;
+        let log = log.try_lock().ok()?;
+        log.estimate_at(timestamp, max_gap)
+    }
+
     fn into_deferred(self) -> Def<Self>
     where
         Self: Sized
@@ -108,8 +162,20 @@ pub trait DeviceGetters {
 pub trait DeviceSetters {
     fn set_id(&mut self, id: IdType);
 
+    /// Mutable reference to device metadata
+    ///
+    /// Changing `name` directly orphans stored logs. Use
+    /// [`crate::storage::Group::rename_device()`] instead.
+    fn metadata_mut(&mut self) -> &mut DeviceMetadata;
+
     /// Setter for `log` field
     fn set_log(&mut self, log: Def<Log>);
+
+    /// Setter for shared `hooks` field
+    ///
+    /// This is called by [`crate::storage::Group`] when a device is added so that group-level
+    /// callbacks are invoked for device events.
+    fn set_hooks(&mut self, hooks: Def<EventHooks>);
 }
 
 impl<T: Device> Persistent for T {
@@ -120,11 +186,16 @@ impl<T: Device> Persistent for T {
         }
     }
 
+    /// Load saved log, and restore UUID that log was saved with
     fn load(&mut self) -> Result<(), ErrorType> {
-        match self.log() {
-            Some(log) => log.try_lock().unwrap().load(),
-            None => Ok(())
+        if let Some(log) = self.log() {
+            let mut log = log.try_lock().unwrap();
+            log.load()?;
+            if let Some(metadata) = log.metadata() {
+                self.metadata_mut().uuid = metadata.uuid;
+            }
         }
+        Ok(())
     }
 }
 
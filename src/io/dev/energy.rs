@@ -0,0 +1,210 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::now;
+use crate::io::RawValue;
+use crate::report::actuator_stats;
+use crate::storage::Log;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Accumulates energy consumed by an [`crate::io::Output`]
+///
+/// Energy is the on-time of an output multiplied by its power rating. Any non-zero or `true` value
+/// written to the output is considered on. Cost is calculated when a tariff is given.
+///
+/// The running total is updated by every write and is included in [`crate::storage::Snapshot`].
+/// Usage within an arbitrary time range (ie: the past week) is calculated from the output log by
+/// [`EnergyMeter::usage()`].
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::IOCommand;
+/// use sensd::io::{Device, EnergyMeter, Output, RawValue};
+///
+/// let mut heater = Output::default()
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .set_energy_meter(EnergyMeter::new(1500.0).set_tariff(0.15));
+///
+/// heater.write(RawValue::Binary(true)).unwrap();
+/// heater.write(RawValue::Binary(false)).unwrap();
+///
+/// let meter = heater.energy_meter().unwrap();
+/// assert!(meter.kwh() >= 0.0);
+/// ```
+pub struct EnergyMeter {
+    /// Power rating in watts
+    power: f32,
+
+    /// Cost per kWh
+    tariff: Option<f32>,
+
+    /// Energy accumulated by completed on-periods
+    kwh: f64,
+
+    /// Beginning of current on-period
+    on_since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+/// Energy consumed within a time range
+pub struct EnergyUsage {
+    pub on_seconds: f64,
+    pub kwh: f64,
+
+    /// Only available when a tariff is set
+    pub cost: Option<f64>,
+}
+
+impl EnergyMeter {
+    /// # Parameters
+    ///
+    /// - `power`: Power rating of output in watts
+    pub fn new(power: f32) -> Self {
+        Self {
+            power,
+            tariff: None,
+            kwh: 0.0,
+            on_since: None,
+        }
+    }
+
+    /// Builder method to set cost per kWh
+    pub fn set_tariff(mut self, tariff: f32) -> Self {
+        self.tariff = Some(tariff);
+        self
+    }
+
+    pub fn power(&self) -> f32 {
+        self.power
+    }
+
+    pub fn tariff(&self) -> Option<f32> {
+        self.tariff
+    }
+
+    /// Update accumulator with a value written to output
+    ///
+    /// # Parameters
+    ///
+    /// - `value`: Value written to output
+    /// - `timestamp`: Time of write
+    pub fn record(&mut self, value: RawValue, timestamp: DateTime<Utc>) {
+        match (is_on(value), self.on_since) {
+            (true, None) => self.on_since = Some(timestamp),
+            (false, Some(since)) => {
+                self.kwh += self.energy(since, timestamp);
+                self.on_since = None;
+            }
+            _ => (),
+        }
+    }
+
+    /// Total energy consumed, including the current on-period
+    pub fn kwh(&self) -> f64 {
+        self.kwh_at(now())
+    }
+
+    /// Total energy consumed up until `now`
+    pub fn kwh_at(&self, now: DateTime<Utc>) -> f64 {
+        self.kwh + self.on_since.map(|since| self.energy(since, now)).unwrap_or(0.0)
+    }
+
+    /// Total cost of energy consumed
+    ///
+    /// # Returns
+    ///
+    /// `None` if no tariff is set
+    pub fn cost(&self) -> Option<f64> {
+        self.cost_at(now())
+    }
+
+    /// Total cost of energy consumed up until `now`
+    pub fn cost_at(&self, now: DateTime<Utc>) -> Option<f64> {
+        self.tariff.map(|tariff| tariff as f64 * self.kwh_at(now))
+    }
+
+    /// Replace accumulated energy (ie: when restoring a snapshot)
+    pub fn set_kwh(&mut self, kwh: f64) {
+        self.kwh = kwh;
+    }
+
+    /// Clear accumulated energy
+    pub fn reset(&mut self) {
+        self.kwh = 0.0;
+        self.on_since = self.on_since.map(|_| now());
+    }
+
+    /// Energy consumed within a time range, calculated from output log
+    ///
+    /// # Parameters
+    ///
+    /// - `log`: Log of output
+    /// - `start`: Beginning of time range
+    /// - `end`: End of time range
+    pub fn usage(&self, log: &Log, start: DateTime<Utc>, end: DateTime<Utc>) -> EnergyUsage {
+        let on_seconds = actuator_stats(log, start, end).on_seconds;
+        let kwh = to_kwh(self.power, on_seconds);
+        EnergyUsage {
+            on_seconds,
+            kwh,
+            cost: self.tariff.map(|tariff| tariff as f64 * kwh),
+        }
+    }
+
+    /// Energy in kWh consumed between `start` and `end`
+    fn energy(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+        let seconds = (end - start).num_milliseconds().max(0) as f64 / 1000.0;
+        to_kwh(self.power, seconds)
+    }
+}
+
+/// Any non-zero or `true` value is considered on
+pub(crate) fn is_on(value: RawValue) -> bool {
+    match value {
+        RawValue::Binary(state) => state,
+        _ => value.as_float().map(|value| value != 0.0).unwrap_or(false),
+    }
+}
+
+/// Convert on-time at a given power rating in watts to kWh
+fn to_kwh(power: f32, seconds: f64) -> f64 {
+    power as f64 * seconds / 3600.0 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::io::{DeviceMetadata, EnergyMeter, IOEvent, RawValue};
+    use crate::storage::Log;
+
+    #[test]
+    fn record() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let mut meter = EnergyMeter::new(1000.0).set_tariff(0.25);
+
+        meter.record(RawValue::Binary(true), start);
+        meter.record(RawValue::Binary(true), start + Duration::minutes(30));
+        assert_eq!(0.5, meter.kwh_at(start + Duration::minutes(30)));
+
+        meter.record(RawValue::Binary(false), start + Duration::hours(1));
+        assert_eq!(1.0, meter.kwh_at(start + Duration::hours(5)));
+        assert_eq!(Some(0.25), meter.cost_at(start + Duration::hours(5)));
+    }
+
+    #[test]
+    fn usage() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let meter = EnergyMeter::new(2000.0);
+
+        let mut log = Log::with_metadata(&DeviceMetadata::default());
+        log.push(IOEvent::with_timestamp(start, RawValue::Binary(true))).unwrap();
+        log.push(IOEvent::with_timestamp(start + Duration::minutes(90), RawValue::Binary(false))).unwrap();
+
+        let usage = meter.usage(&log, start + Duration::minutes(30), start + Duration::days(7));
+        assert_eq!(3600.0, usage.on_seconds);
+        assert_eq!(2.0, usage.kwh);
+        assert_eq!(None, usage.cost);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::io::{IOEvent, RawValue};
+
+/// Function computing a single field of an [`IOEvent`] at log time
+///
+/// Given the event before any field is added. Returning `None` leaves the field out.
+pub type Enrichment = Box<dyn Fn(&IOEvent) -> Option<RawValue>>;
+
+#[derive(Default)]
+/// Named [`Enrichment`] functions of a single device
+///
+/// Computed fields are stored in [`IOEvent::fields`], alongside the unaltered value, so that logs
+/// keep both raw readings and derived values (ie: temperature compensated conductivity).
+pub struct Enrichments {
+    fields: Vec<(String, Enrichment)>,
+}
+
+impl Enrichments {
+    /// Register a function computing field `name`
+    ///
+    /// A function registered under an existing name replaces it.
+    pub fn add<N, F>(&mut self, name: N, enrichment: F)
+    where
+        N: Into<String>,
+        F: Fn(&IOEvent) -> Option<RawValue> + 'static
+    {
+        let name = name.into();
+        self.fields.retain(|(existing, _)| *existing != name);
+        self.fields.push((name, Box::new(enrichment)));
+    }
+
+    /// Names of computed fields, in order of registration
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.fields.iter().map(|(name, _)| name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Add every computed field to `event`
+    ///
+    /// Every function is given the event as it was before enrichment.
+    pub fn apply(&self, event: &mut IOEvent) {
+        if self.fields.is_empty() {
+            return;
+        }
+        let computed: Vec<(String, RawValue)> = self.fields.iter()
+            .filter_map(|(name, enrichment)| enrichment(event).map(|value| (name.clone(), value)))
+            .collect();
+        event.fields.extend(computed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{Enrichments, IOEvent, RawValue};
+
+    #[test]
+    fn apply() {
+        let mut enrichments = Enrichments::default();
+        enrichments.add("doubled", |event: &IOEvent| Some(event.value * RawValue::Float(2.0)));
+        enrichments.add("skipped", |_: &IOEvent| None);
+        enrichments.add("doubled", |event: &IOEvent| Some(event.value + event.value));
+
+        let mut event = IOEvent::new(RawValue::Float(1.5));
+        enrichments.apply(&mut event);
+
+        assert_eq!(vec!["skipped", "doubled"], enrichments.names().collect::<Vec<_>>());
+        assert_eq!(RawValue::Float(1.5), event.value);
+        assert_eq!(Some(RawValue::Float(3.0)), event.field("doubled"));
+        assert_eq!(None, event.field("skipped"));
+    }
+}
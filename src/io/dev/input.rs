@@ -1,12 +1,17 @@
 use std::fmt::Formatter;
 use std::path::{Path, PathBuf};
-use crate::action::{Command, IOCommand, Publisher};
-use crate::errors::DeviceError;
-use crate::helpers::Def;
-use crate::io::{Device, DeviceMetadata, IODirection, IOEvent, IOKind, IdType, RawValue, DeviceGetters, DeviceSetters};
+use std::thread::sleep;
+use chrono::{DateTime, Duration, Utc};
+use crate::action::{HoldPolicy, IOCommand, Publisher};
+use crate::console;
+use crate::errors::{DeviceError, ReadError};
+use crate::io::dev::profile::get_profile;
+use crate::helpers::{now, Def};
+use crate::io::{non_finite_policy, BiasCorrection, Calibration, Compensation, CorrelationId, Device, DeviceMetadata, Enrichments, EventKind, EventQuality, IODirection, IOEvent, IOKind, IdType, NonFinitePolicy, RawValue, RunningStats, Span, DeviceGetters, DeviceSetters, ValueFormat, MEASURED_FIELD};
 use crate::io::dev::device::set_log_dir;
 use crate::name::Name;
-use crate::storage::{Chronicle, Directory, Log};
+use crate::storage::{with_hooks, Chronicle, Directory, EventHooks, Log};
+use crate::testkit::{Fault, FaultInjector};
 
 #[derive(Default)]
 /// This is the generic implementation for any external input device.
@@ -56,6 +61,37 @@ pub struct Input {
     state: Option<RawValue>,
 
     dir: Option<PathBuf>,
+    hooks: Option<Def<EventHooks>>,
+
+    /// Time that device was initialized
+    powered: DateTime<Utc>,
+    /// Duration after `powered` where readings are considered invalid
+    warmup: Option<Duration>,
+
+    /// Readings are logged but not propagated
+    calibrating: bool,
+
+    /// Correction applied to every raw reading
+    calibration: Option<Calibration>,
+    /// Number of times a failed read is retried
+    retries: usize,
+    /// Overrides default unit of device kind
+    unit: Option<String>,
+
+    /// Injects faults into reads for robustness testing
+    faults: Option<FaultInjector>,
+
+    /// Statistics of readings since last reset
+    stats: RunningStats,
+
+    /// Fields computed for every logged reading
+    enrichments: Enrichments,
+
+    /// Temperature compensation applied to every reading
+    compensation: Option<Compensation>,
+
+    /// Offset correction learned from a reference
+    bias: Option<BiasCorrection>,
 }
 
 /// Implement unique constructors and builder methods
@@ -87,6 +123,10 @@ impl Device for Input {
         let state = None;
 
         let dir = None;
+        let hooks = None;
+
+        let powered = now();
+        let warmup = None;
 
         Self {
             metadata,
@@ -95,6 +135,18 @@ impl Device for Input {
             command,
             state,
             dir,
+            hooks,
+            powered,
+            warmup,
+            calibrating: false,
+            calibration: None,
+            retries: 0,
+            unit: None,
+            faults: None,
+            stats: RunningStats::new(powered),
+            enrichments: Enrichments::default(),
+            compensation: None,
+            bias: None,
         }
     }
 
@@ -163,44 +215,27 @@ impl DeviceSetters for Input {
         self.metadata.id = id;
     }
 
+    fn metadata_mut(&mut self) -> &mut DeviceMetadata {
+        &mut self.metadata
+    }
+
     fn set_log(&mut self, log: Def<Log>) {
         self.log = Some(log.clone());
+        if let Some(publisher) = &mut self.publisher {
+            publisher.set_log(log.clone());
+        }
 
         if let Some(dir) = &self.dir {
             set_log_dir(Some(log), dir)
         }
     }
-}
-
-impl Input {
-    /// Execute low-level GPIO command to read data
-    ///
-    /// # Returns
-    ///
-    /// A `Result` containing:
-    ///
-    /// - `Ok` with [`IOEvent`] if low-level operation occurred successfully.
-    /// - `Err` with [`ErrorType`] if no `command` is set or there device command failed
-    ///
-    /// # Issues
-    ///
-    /// [Low level error type](https://github.com/PoorRican/sensd/issues/192)
-    fn rx(&self) -> Result<IOEvent, DeviceError> {
-        let read_value = if let Some(command) = &self.command {
-            // execute command
-            let result = command.execute(None)?;
-            // return error if no value is read from device
-            match result {
-                None => Err(DeviceError::ValueExpected {metadata: self.metadata.clone()})?,
-                Some(inner) => inner,
-            }
-        } else {
-            Err(DeviceError::NoCommand {metadata: self.metadata.clone()})?
-        };
 
-        Ok(IOEvent::new(read_value))
+    fn set_hooks(&mut self, hooks: Def<EventHooks>) {
+        self.hooks = Some(hooks);
     }
+}
 
+impl Input {
     /// Propagate `IOEvent` to all subscribers.
     ///
     /// Silently fails when there is no associated publisher.
@@ -255,28 +290,441 @@ impl Input {
     ///
     /// - [`Publisher::propagate()`] for how [`IOEvent`] is given to subscribing [`Action`]'s
     /// - [`Input::push_to_log()`] for adding [`IOEvent`] to [`Log`]
+    /// - [`EventHooks`] for how group-level callbacks are notified
     pub fn read(&mut self) -> Result<IOEvent, DeviceError> {
-        let event = self.rx()?;
+        let result = self.sampler().read();
+        self.record(result)
+    }
+
+    /// Complete a read which was sampled elsewhere
+    ///
+    /// Used by [`crate::storage::Group::poll_async()`], which samples inputs concurrently using
+    /// [`Input::sampler()`].
+    #[cfg(feature = "async")]
+    pub(crate) fn complete_read(&mut self, result: Result<IOEvent, DeviceError>) -> Result<IOEvent, DeviceError> {
+        self.record(result)
+    }
+
+    /// Everything needed to sample input independently of device, including retries and faults
+    pub(crate) fn sampler(&self) -> Sampler {
+        Sampler {
+            metadata: self.metadata.clone(),
+            command: self.command.clone(),
+            faults: self.faults.clone(),
+            calibration: self.calibration,
+            state: self.state,
+            retries: self.retries,
+        }
+    }
+
+    /// Process result of a read: apply corrections, update state, log, and propagate event
+    fn record(&mut self, result: Result<IOEvent, DeviceError>) -> Result<IOEvent, DeviceError> {
+        let mut event = match result {
+            Ok(event) => event,
+            Err(e) => {
+                with_hooks(&self.hooks, |hooks| hooks.dispatch_error(&self.metadata, &e));
+                return Err(e);
+            }
+        };
+        if let Some(compensation) = &self.compensation {
+            compensation.apply(&mut event);
+        }
+        if let Some(bias) = &mut self.bias {
+            bias.apply(&mut event);
+        }
 
         // Update cached state
         self.state = Some(event.value);
+        if event.quality != EventQuality::Bad {
+            if let Some(value) = event.value.finite_float() {
+                self.stats.push(value);
+            }
+        }
 
-        self.propagate(&event);
+        self.enrichments.apply(&mut event);
+
+        // event is logged first so that subscribers may annotate it
         self.push_to_log(&event);
+        // publisher withholds data from subscribers while calibrating
+        self.propagate(&event);
+
+        with_hooks(&self.hooks, |hooks| hooks.dispatch_read(&self.metadata, &event));
 
         Ok(event)
     }
 
+    /// Builder method to set warm-up delay
+    ///
+    /// Many sensors return invalid data for the first few seconds after power-on. Inputs are
+    /// skipped by [`crate::storage::Group::poll()`] until `warmup` has elapsed since
+    /// initialization.
+    ///
+    /// # Parameters
+    ///
+    /// - `warmup`: Duration to wait after initialization
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to allow method chaining
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use sensd::io::{Device, Input};
+    ///
+    /// let input = Input::new("", 0, None)
+    ///     .set_warmup(Duration::seconds(5));
+    ///
+    /// assert!(!input.is_warm());
+    /// ```
+    pub fn set_warmup(mut self, warmup: Duration) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    /// Getter for warm-up delay
+    pub fn warmup(&self) -> Duration {
+        self.warmup.unwrap_or_else(Duration::zero)
+    }
+
+    /// Check if warm-up delay has elapsed
+    pub fn is_warm(&self) -> bool {
+        self.is_warm_at(now())
+    }
+
+    /// Check if warm-up delay has elapsed relative to a given time
+    pub fn is_warm_at(&self, now: DateTime<Utc>) -> bool {
+        now >= self.powered + self.warmup()
+    }
+
+    /// Isolate device from control while calibrating
+    ///
+    /// Readings continue to be logged, but are not propagated to subscribers. Subscribers are
+    /// notified via [`crate::action::Action::hold()`] so that outputs are not actuated by
+    /// calibration readings (ie: when a pH probe is placed in buffer solution).
+    ///
+    /// # Parameters
+    ///
+    /// - `policy`: How dependent actions should treat their outputs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensd::action::{HoldPolicy, IOCommand};
+    /// use sensd::io::{Device, Input, RawValue};
+    ///
+    /// let mut input = Input::default()
+    ///     .set_command(IOCommand::Input(|| RawValue::Float(4.0)))
+    ///     .init_publisher();
+    ///
+    /// input.start_calibration(HoldPolicy::Safe);
+    /// input.read().unwrap();
+    /// input.end_calibration();
+    /// ```
+    pub fn start_calibration(&mut self, policy: HoldPolicy) {
+        if self.calibrating {
+            return;
+        }
+        self.calibrating = true;
+        if let Some(publisher) = &mut self.publisher {
+            publisher.hold(policy);
+        }
+    }
+
+    /// Return device to normal operation after [`Input::start_calibration()`]
+    pub fn end_calibration(&mut self) {
+        if !self.calibrating {
+            return;
+        }
+        self.calibrating = false;
+        if let Some(publisher) = &mut self.publisher {
+            publisher.release();
+        }
+    }
+
+    pub fn is_calibrating(&self) -> bool {
+        self.calibrating
+    }
+
+    /// Alternate constructor using a profile registered by [`crate::io::DeviceProfile::register()`]
+    ///
+    /// # Parameters
+    ///
+    /// - `profile`: Name of registered profile
+    /// - `id`: Id of new device
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with [`Input`] named after profile. Use [`crate::name::Name::set_name()`] to rename.
+    /// - `Err` if no profile named `profile` is registered
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::io::DeviceProfile::input()`]
+    pub fn from_profile(profile: &str, id: IdType) -> Result<Self, DeviceError> {
+        Ok(get_profile(profile)?.input(id))
+    }
+
+    /// Builder method to set correction applied to every raw reading
+    pub fn set_calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = Some(calibration);
+        self
+    }
+
+    pub fn calibration(&self) -> Option<Calibration> {
+        self.calibration
+    }
+
+    /// Builder method to set valid measurement range
+    ///
+    /// Readings outside of `min..=max` are still logged and propagated, but are marked as
+    /// [`EventQuality::Bad`]. Bounds are stored in [`DeviceMetadata`].
+    pub fn set_bounds(mut self, min: f32, max: f32) -> Self {
+        self.metadata.bounds = Some((min, max));
+        self
+    }
+
+    pub fn bounds(&self) -> Option<(f32, f32)> {
+        self.metadata.bounds
+    }
+
+    /// Builder method to set engineering range, so that readings may be expressed as a percent of
+    /// range. Span is stored in [`DeviceMetadata`].
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::action::actions::Normalized`] for configuring actions in percent terms
+    pub fn set_span(mut self, span: Span) -> Self {
+        self.metadata.span = Some(span);
+        self
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.metadata.span
+    }
+
+    /// Most recent reading as a percent of span
+    ///
+    /// # Returns
+    ///
+    /// `None` if no span is set, or if there is no numeric reading
+    pub fn percent(&self) -> Option<RawValue> {
+        self.metadata.span?.normalize(self.state?)
+    }
+
+    /// Builder method to register a field computed for every logged event
+    ///
+    /// Computed fields are stored in [`IOEvent::fields`] next to the unaltered value. See
+    /// [`Enrichments`].
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of field
+    /// - `enrichment`: Function computing field from event. Returning `None` leaves field out.
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to allow method chaining
+    pub fn add_enrichment<N, F>(mut self, name: N, enrichment: F) -> Self
+    where
+        N: Into<String>,
+        F: Fn(&IOEvent) -> Option<RawValue> + 'static
+    {
+        self.enrichments.add(name, enrichment);
+        self
+    }
+
+    pub fn enrichments(&self) -> &Enrichments {
+        &self.enrichments
+    }
+
+    /// Builder method to compensate readings for temperature
+    ///
+    /// Readings are compensated before they are cached, logged, or published. The uncompensated
+    /// reading is kept in [`IOEvent::fields`]. See [`Compensation`].
+    pub fn set_compensation(mut self, compensation: Compensation) -> Self {
+        self.compensation = Some(compensation);
+        self
+    }
+
+    pub fn compensation(&self) -> Option<&Compensation> {
+        self.compensation.as_ref()
+    }
+
+    /// Builder method to correct readings by an offset learned from a reference
+    ///
+    /// Readings are corrected after temperature compensation. See [`BiasCorrection`].
+    pub fn set_bias_correction(mut self, bias: BiasCorrection) -> Self {
+        self.bias = Some(bias);
+        self
+    }
+
+    pub fn bias_correction(&self) -> Option<&BiasCorrection> {
+        self.bias.as_ref()
+    }
+
+    /// Record a manual reference measurement (ie: from a handheld meter)
+    ///
+    /// The reference is compared with the uncorrected value of the latest reading, and is logged as
+    /// an [`EventKind::Annotation`] whose value is the reference and which stores the uncorrected
+    /// reading as [`crate::io::MEASURED_FIELD`]. Any [`BiasCorrection`] learns from the comparison.
+    ///
+    /// # Errors
+    ///
+    /// [`DeviceError::ValueExpected`] if input has not been read
+    pub fn add_reference(&mut self, reference: f32) -> Result<IOEvent, DeviceError> {
+        let corrected = self.state
+            .and_then(|value| value.finite_float())
+            .ok_or_else(|| DeviceError::ValueExpected { metadata: self.metadata.clone() })?;
+        let offset = self.bias.as_ref().and_then(|bias| bias.offset()).unwrap_or(0.0);
+        let measured = corrected - offset;
+
+        let mut event = IOEvent::new(RawValue::Float(reference)).set_kind(EventKind::Annotation);
+        event.fields.insert(MEASURED_FIELD.to_string(), RawValue::Float(measured));
+        if let Some(bias) = &mut self.bias {
+            bias.observe(reference, measured);
+        }
+        self.push_to_log(&event);
+        Ok(event)
+    }
+
+    /// Restore learned offset of [`BiasCorrection`] from log
+    ///
+    /// Called by [`crate::storage::Group`] once logs are loaded.
+    pub fn restore_bias_correction(&mut self) {
+        if let (Some(bias), Some(log)) = (self.bias.as_mut(), self.log.as_ref()) {
+            bias.restore(&log.try_lock().unwrap());
+        }
+    }
+
+    /// Builder method to set number of times a failed read is retried by [`Input::read()`]
+    pub fn set_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn retries(&self) -> usize {
+        self.retries
+    }
+
+    /// Builder method to read input at its own interval
+    ///
+    /// Inputs are read by [`crate::storage::Group::poll()`] once `interval` has elapsed since
+    /// their last read. Group polls at the shortest interval of the group and its inputs, so that
+    /// fast sensors may be sampled more often than slow ones. The interval is stored in
+    /// [`DeviceMetadata`] so that it is persisted with other device metadata.
+    ///
+    /// # Parameters
+    ///
+    /// - `interval`: Time between reads
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to allow method chaining
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use sensd::io::{Device, Input};
+    ///
+    /// let input = Input::new("flow", 0, None)
+    ///     .set_interval(Duration::milliseconds(100));
+    ///
+    /// assert_eq!(Some(Duration::milliseconds(100)), input.interval());
+    /// ```
+    pub fn set_interval(mut self, interval: Duration) -> Self {
+        self.metadata.interval = Some(interval.num_milliseconds());
+        self
+    }
+
+    /// Getter for interval between reads. `None` if interval of group is used.
+    pub fn interval(&self) -> Option<Duration> {
+        self.metadata.interval.map(Duration::milliseconds)
+    }
+
+    /// Statistics of readings since initialization or last call to [`Input::reset_stats()`]
+    ///
+    /// Updated by every [`Input::read()`]. Readings of bad quality or which are non-numeric are
+    /// excluded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensd::action::IOCommand;
+    /// use sensd::io::{Device, Input, RawValue};
+    ///
+    /// let mut input = Input::new("", 0, None)
+    ///     .set_command(IOCommand::Input(|| RawValue::Float(7.5)));
+    /// input.read().unwrap();
+    /// input.read().unwrap();
+    ///
+    /// assert_eq!(2, input.stats().count());
+    /// assert_eq!(Some(7.5), input.stats().mean());
+    ///
+    /// input.reset_stats();
+    /// assert_eq!(0, input.stats().count());
+    /// ```
+    pub fn stats(&self) -> &RunningStats {
+        &self.stats
+    }
+
+    /// Discard statistics of previous readings
+    pub fn reset_stats(&mut self) {
+        self.stats = RunningStats::new(now());
+    }
+
+    /// Builder method to randomly inject faults into reads
+    ///
+    /// Intended for verifying that alarms and fail-safe configurations behave under failure. See
+    /// [`FaultInjector`].
+    pub fn set_fault_injector(mut self, faults: FaultInjector) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Builder method to override default unit of device kind
+    pub fn set_unit<U>(mut self, unit: U) -> Self
+    where
+        U: Into<String>
+    {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Unit of readings
+    ///
+    /// # Returns
+    ///
+    /// Unit set by [`Input::set_unit()`], otherwise the default unit of device kind
+    pub fn unit(&self) -> Option<String> {
+        self.unit.clone()
+            .or_else(|| self.metadata.kind.unit().map(String::from))
+    }
+
+    /// Format for rendering readings with unit
+    pub fn value_format(&self) -> ValueFormat {
+        match self.unit() {
+            Some(unit) => ValueFormat::default().set_unit(unit),
+            None => ValueFormat::default(),
+        }
+    }
+
     /// Create and set publisher or silently fail
     pub fn init_publisher(mut self) -> Self
     where
         Self: Sized {
         match self.publisher {
             None => {
-                self.publisher = Some(Publisher::default());
+                let mut publisher = Publisher::default();
+                if let Some(log) = &self.log {
+                    publisher.set_log(log.clone());
+                }
+                self.publisher = Some(publisher);
             }
             _ => {
-                eprintln!("Publisher already exists!");
+                console::warning("Publisher already exists!");
             }
         }
         self
@@ -323,11 +771,143 @@ impl PartialEq for Input {
 }
 
 // Testing
+#[derive(Clone)]
+/// Detached copy of an [`Input`] which executes its command
+///
+/// Sampling is performed without access to the input, so that commands may be executed
+/// concurrently by [`crate::storage::Group::poll_async()`]. Synchronous and asynchronous reads share
+/// the same retries, injected faults, and conversion of raw values.
+pub(crate) struct Sampler {
+    metadata: DeviceMetadata,
+    command: Option<IOCommand>,
+    faults: Option<FaultInjector>,
+    calibration: Option<Calibration>,
+    /// Cached state of input, returned by stale reads
+    state: Option<RawValue>,
+    retries: usize,
+}
+
+impl Sampler {
+    /// Read, retrying failed attempts
+    pub(crate) fn read(&self) -> Result<IOEvent, DeviceError> {
+        let mut result = self.attempt();
+        let mut attempts = 1;
+        while result.is_err() && attempts <= self.retries {
+            result = self.attempt();
+            attempts += 1;
+        }
+        result.map_err(|e| e.set_attempts(attempts))
+    }
+
+    /// Asynchronous alternative to [`Sampler::read()`]
+    ///
+    /// [`IOCommand::AsyncInputFn`] commands are awaited, while other commands are executed on the
+    /// blocking thread pool of the runtime.
+    #[cfg(feature = "async")]
+    pub(crate) async fn read_async(self) -> Result<IOEvent, DeviceError> {
+        let mut result = self.attempt_async().await;
+        let mut attempts = 1;
+        while result.is_err() && attempts <= self.retries {
+            result = self.attempt_async().await;
+            attempts += 1;
+        }
+        result.map_err(|e| e.set_attempts(attempts))
+    }
+
+    /// Execute command once
+    fn attempt(&self) -> Result<IOEvent, DeviceError> {
+        let fault = self.begin()?;
+        if let Some(Fault::Delay(delay)) = fault {
+            sleep(delay);
+        }
+
+        let command = self.command.as_ref()
+            .ok_or_else(|| DeviceError::NoCommand { metadata: self.metadata.clone() })?;
+        self.finish(command.try_read(), fault)
+    }
+
+    /// Execute command once without blocking the runtime
+    #[cfg(feature = "async")]
+    async fn attempt_async(&self) -> Result<IOEvent, DeviceError> {
+        let inner = match &self.command {
+            Some(IOCommand::AsyncInputFn(inner)) => inner.clone(),
+            _ => {
+                let sampler = self.clone();
+                return tokio::task::spawn_blocking(move || sampler.attempt()).await
+                    .unwrap_or_else(|_| Err(DeviceError::HWFault { metadata: self.metadata.clone() }));
+            }
+        };
+
+        let fault = self.begin()?;
+        if let Some(Fault::Delay(delay)) = fault {
+            let _ = tokio::task::spawn_blocking(move || sleep(delay)).await;
+        }
+        self.finish(Some(inner().await), fault)
+    }
+
+    /// Choose fault to inject into attempt
+    ///
+    /// # Returns
+    ///
+    /// `Err` if an error is injected, in which case command is not executed
+    fn begin(&self) -> Result<Option<Fault>, DeviceError> {
+        match self.faults.as_ref().and_then(|faults| faults.sample()) {
+            Some(Fault::Error) => Err(DeviceError::HWFault { metadata: self.metadata.clone() }),
+            fault => Ok(fault),
+        }
+    }
+
+    /// Convert value read by command into an [`IOEvent`]
+    ///
+    /// Calibration, injected faults, non-finite policy, and bounds are applied.
+    fn finish(&self, result: Option<Result<RawValue, ReadError>>, fault: Option<Fault>) -> Result<IOEvent, DeviceError> {
+        let read_value = match result {
+            None => Err(DeviceError::ValueExpected { metadata: self.metadata.clone() })?,
+            Some(result) => result.map_err(|e| e.with_metadata(self.metadata.clone()))?,
+        };
+
+        let read_value = match &self.calibration {
+            Some(calibration) => calibration.apply(read_value),
+            None => read_value,
+        };
+        let read_value = match fault {
+            Some(Fault::NaN) => RawValue::Float(f32::NAN),
+            Some(Fault::Stale) => self.state.unwrap_or(read_value),
+            _ => read_value,
+        };
+
+        let finite = read_value.is_finite();
+        let read_value = match non_finite_policy() {
+            _ if finite => read_value,
+            NonFinitePolicy::Reject => return Err(DeviceError::NonFinite { metadata: self.metadata.clone() }),
+            NonFinitePolicy::Clamp => read_value.to_finite().unwrap_or(read_value),
+            NonFinitePolicy::Ignore => read_value,
+        };
+
+        let mut event = IOEvent::new(read_value)
+            .set_correlation(CorrelationId::generate());
+        if !finite {
+            event.quality = EventQuality::Bad;
+        }
+        if let (Some((min, max)), Some(value)) = (self.metadata.bounds, read_value.as_float()) {
+            if value < min || value > max {
+                event.quality = EventQuality::Bad;
+            }
+        }
+        Ok(event)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::action::{IOCommand};
-    use crate::io::{Device, Input, IOKind, RawValue};
+    use chrono::{Duration, Utc};
+    use std::sync::Arc;
+    use crate::action::{Action, HoldPolicy, IOCommand, Trigger};
+    use crate::errors::{DeviceError, ReadError};
+    use crate::action::actions::Threshold;
+    use crate::io::{Device, DeviceGetters, EventQuality, Input, IOKind, Output, RawValue, Span};
     use crate::storage::{Chronicle, Directory, Document};
+    use crate::testkit::{Fault, FaultInjector};
 
     const DUMMY_OUTPUT: RawValue = RawValue::Float(1.2);
     const COMMAND: IOCommand = IOCommand::Input(move || DUMMY_OUTPUT);
@@ -352,7 +932,7 @@ mod tests {
 
         input.command = Some(COMMAND);
 
-        let event = input.rx().unwrap();
+        let event = input.sampler().read().unwrap();
         assert_eq!(event.value, DUMMY_OUTPUT);
     }
 
@@ -373,6 +953,40 @@ mod tests {
     }
 
     /// Test `::add_publisher()` and `::has_publisher()`
+    #[test]
+    fn warmup() {
+        let input = Input::new("", 0, None);
+        assert!(input.is_warm());
+
+        let input = input.set_warmup(Duration::seconds(5));
+        assert!(!input.is_warm());
+        assert!(input.is_warm_at(Utc::now() + Duration::seconds(5)));
+    }
+
+    #[test]
+    /// Assert that readings are not propagated and outputs are held during calibration
+    fn calibration() {
+        let output = Output::default()
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .set_safe_state(RawValue::Binary(false))
+            .into_deferred();
+        let action = Threshold::with_output("", RawValue::Float(1.0), Trigger::GT, output.clone());
+
+        let mut input = Input::default()
+            .set_command(IOCommand::Input(|| RawValue::Float(2.0)))
+            .init_publisher();
+        input.publisher_mut().as_mut().unwrap().subscribe(action.into_boxed());
+
+        input.start_calibration(HoldPolicy::Safe);
+        assert!(input.is_calibrating());
+        input.read().unwrap();
+        assert_eq!(Some(RawValue::Binary(false)), *output.try_lock().unwrap().state());
+
+        input.end_calibration();
+        input.read().unwrap();
+        assert_eq!(Some(RawValue::Binary(true)), *output.try_lock().unwrap().state());
+    }
+
     #[test]
     fn test_init_publisher() {
         let mut input = Input::default();
@@ -412,4 +1026,92 @@ mod tests {
             .dir()
             .is_some());
     }
-}
\ No newline at end of file
+
+    #[test]
+    /// Assert that injected faults replace readings
+    fn fault_injector() {
+        let faults = FaultInjector::new(1).set_stale(1.0);
+        let mut input = Input::new("", 0, None)
+            .set_command(COMMAND)
+            .set_fault_injector(faults.clone());
+
+        // there is no previous value to repeat
+        assert_eq!(DUMMY_OUTPUT, input.read().unwrap().value);
+
+        let mut input = input.set_fault_injector(FaultInjector::new(1).set_nan(1.0));
+        let event = input.read().unwrap();
+        assert_eq!(EventQuality::Bad, event.quality);
+        match event.value {
+            RawValue::Float(value) => assert!(value.is_nan()),
+            value => panic!("Unexpected value {}", value),
+        }
+        assert_eq!(vec![Fault::Stale], faults.injected());
+    }
+
+    #[test]
+    /// Assert that readings of bad quality are excluded from statistics
+    fn stats() {
+        let mut input = Input::new("", 0, None)
+            .set_command(COMMAND)
+            .set_bounds(0.0, 1.0);
+        input.read().unwrap();
+        assert_eq!(0, input.stats().count());
+
+        let mut input = input.set_bounds(0.0, 2.0);
+        input.read().unwrap();
+        assert_eq!(1, input.stats().count());
+        assert_eq!(Some(0.0), input.stats().variance());
+    }
+
+    #[test]
+    fn percent() {
+        let mut input = Input::new("", 0, None)
+            .set_command(COMMAND);
+        input.read().unwrap();
+        assert_eq!(None, input.percent());
+
+        let input = input.set_span(Span::new(0.0, 2.4));
+        assert_eq!(Some(RawValue::Float(50.0)), input.percent());
+    }
+
+    #[test]
+    /// Assert that computed fields are logged alongside the unaltered reading
+    fn enrichment() {
+        let mut input = Input::new("ec", 0, IOKind::EC)
+            .set_command(IOCommand::Input(|| RawValue::Float(1.2)))
+            .add_enrichment("compensated", |event| Some(event.value * RawValue::Float(0.5)))
+            .init_log();
+
+        let event = input.read().unwrap();
+        assert_eq!(RawValue::Float(1.2), event.value);
+        assert_eq!(Some(RawValue::Float(0.6)), event.field("compensated"));
+
+        let log = input.log().unwrap();
+        let log = log.try_lock().unwrap();
+        assert_eq!(&event, log.iter().next().unwrap().1);
+    }
+
+    #[test]
+    /// Assert that failures of fallible commands carry metadata and attempt count
+    fn read_error() {
+        let command = IOCommand::TryInputFn(Arc::new(|| Err(ReadError::Checksum)));
+        let mut input = Input::new("probe", 3, None)
+            .set_command(command)
+            .set_retries(2);
+
+        let error = input.read().unwrap_err();
+        assert!(matches!(error, DeviceError::ChecksumFailure { .. }));
+        assert_eq!(Some(3), error.attempts());
+        assert_eq!(3, error.metadata().unwrap().id);
+    }
+
+    #[test]
+    /// Assert that asynchronous commands are not read synchronously
+    fn read_async_command() {
+        let command = IOCommand::AsyncInputFn(Arc::new(|| Box::pin(async { Ok(RawValue::Float(4.0)) })));
+        let mut input = Input::new("probe", 3, None)
+            .set_command(command);
+
+        assert!(matches!(input.read(), Err(DeviceError::Asynchronous { .. })));
+    }
+}
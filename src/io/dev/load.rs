@@ -0,0 +1,202 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::helpers::Def;
+use crate::io::IdType;
+
+#[derive(Default)]
+struct LoadState {
+    max_concurrent: Option<usize>,
+    max_amps: Option<f32>,
+
+    /// Minimum time between successive outputs turning on
+    stagger: Option<Duration>,
+
+    /// Current draw of outputs which are on, keyed by output id
+    active: BTreeMap<IdType, f32>,
+    /// Outputs waiting for capacity, in order of request
+    queue: VecDeque<IdType>,
+    last_start: Option<DateTime<Utc>>,
+}
+
+impl LoadState {
+    fn fits(&self, amps: f32, now: DateTime<Utc>) -> bool {
+        let concurrent = self.max_concurrent
+            .map(|max| self.active.len() < max)
+            .unwrap_or(true);
+        let current = self.max_amps
+            .map(|max| self.active.values().sum::<f32>() + amps <= max)
+            .unwrap_or(true);
+        let staggered = match (self.stagger, self.last_start) {
+            (Some(stagger), Some(last)) => now - last >= stagger,
+            _ => true,
+        };
+        concurrent && current && staggered
+    }
+}
+
+#[derive(Clone, Default)]
+/// Limits how many high-power outputs may be on simultaneously
+///
+/// Outputs sharing one circuit (ie: heaters, pumps, and chillers) are attached to the same limiter
+/// using [`crate::io::Output::set_load_limiter()`]. Capacity may be limited by the number of
+/// outputs which are on, by their total current draw, or both. Optionally, outputs are staggered so
+/// that inrush currents do not coincide.
+///
+/// Turning an output on when there is no capacity queues the write instead. Queued writes are
+/// granted in order of request once capacity is freed, and are retried by
+/// [`crate::io::Output::attempt_pending()`], which is called by
+/// [`crate::storage::Group::attempt_routines()`]. Turning an output off always succeeds and cancels
+/// any queued write.
+///
+/// Outputs are identified by id, so all outputs attached to a limiter must have unique ids.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::IOCommand;
+/// use sensd::io::{Device, LoadLimiter, Output, RawValue};
+///
+/// let limiter = LoadLimiter::new().set_max_amps(15.0);
+///
+/// let mut heater = Output::new("heater", 0, None)
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .set_load_limiter(limiter.clone(), 12.0);
+/// let mut chiller = Output::new("chiller", 1, None)
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .set_load_limiter(limiter.clone(), 8.0);
+///
+/// heater.write(RawValue::Binary(true)).unwrap();
+///
+/// // chiller waits until heater is off
+/// assert!(chiller.write(RawValue::Binary(true)).is_err());
+/// assert!(chiller.is_pending());
+///
+/// heater.write(RawValue::Binary(false)).unwrap();
+/// assert!(chiller.attempt_pending().unwrap().is_ok());
+/// assert_eq!(vec![1], limiter.active());
+/// ```
+pub struct LoadLimiter(Def<LoadState>);
+
+impl LoadLimiter {
+    /// Constructor for [`LoadLimiter`] without any limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to limit number of outputs that may be on simultaneously
+    pub fn set_max_concurrent(self, max: usize) -> Self {
+        self.0.lock().unwrap().max_concurrent = Some(max);
+        self
+    }
+
+    /// Builder method to limit total current draw of outputs that are on
+    pub fn set_max_amps(self, max: f32) -> Self {
+        self.0.lock().unwrap().max_amps = Some(max);
+        self
+    }
+
+    /// Builder method to set minimum time between successive outputs turning on
+    pub fn set_stagger(self, stagger: Duration) -> Self {
+        self.0.lock().unwrap().stagger = Some(stagger);
+        self
+    }
+
+    /// Request capacity for an output to turn on
+    ///
+    /// Requests are granted in order. If an earlier request is still queued, this request is queued
+    /// behind it even if there is enough capacity.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: Id of output
+    /// - `amps`: Current drawn by output
+    /// - `now`: Time of request
+    ///
+    /// # Returns
+    ///
+    /// `true` if output may turn on, or is already on. Otherwise, output is queued and `false` is
+    /// returned.
+    pub fn request(&self, id: IdType, amps: f32, now: DateTime<Utc>) -> bool {
+        let mut state = self.0.lock().unwrap();
+        if state.active.contains_key(&id) {
+            return true;
+        }
+
+        let next = state.queue.front().map(|next| *next == id).unwrap_or(true);
+        if next && state.fits(amps, now) {
+            state.queue.retain(|queued| *queued != id);
+            state.active.insert(id, amps);
+            state.last_start = Some(now);
+            return true;
+        }
+
+        if !state.queue.contains(&id) {
+            state.queue.push_back(id);
+        }
+        false
+    }
+
+    /// Release capacity held by an output and remove it from queue
+    pub fn release(&self, id: IdType) {
+        let mut state = self.0.lock().unwrap();
+        state.active.remove(&id);
+        state.queue.retain(|queued| *queued != id);
+    }
+
+    /// Ids of outputs which are on
+    pub fn active(&self) -> Vec<IdType> {
+        self.0.lock().unwrap().active.keys().copied().collect()
+    }
+
+    /// Ids of outputs waiting for capacity, in order of request
+    pub fn queued(&self) -> Vec<IdType> {
+        self.0.lock().unwrap().queue.iter().copied().collect()
+    }
+
+    /// Total current draw of outputs which are on
+    pub fn amps(&self) -> f32 {
+        self.0.lock().unwrap().active.values().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::io::LoadLimiter;
+
+    #[test]
+    fn concurrent() {
+        let now = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let limiter = LoadLimiter::new().set_max_concurrent(2);
+
+        assert!(limiter.request(0, 1.0, now));
+        assert!(limiter.request(1, 1.0, now));
+        assert!(!limiter.request(2, 1.0, now));
+        assert!(!limiter.request(3, 1.0, now));
+        assert_eq!(vec![2, 3], limiter.queued());
+
+        // queued requests are granted in order
+        limiter.release(0);
+        assert!(!limiter.request(3, 1.0, now));
+        assert!(limiter.request(2, 1.0, now));
+        assert_eq!(vec![1, 2], limiter.active());
+        assert_eq!(vec![3], limiter.queued());
+    }
+
+    #[test]
+    fn stagger() {
+        let now = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let limiter = LoadLimiter::new()
+            .set_max_amps(20.0)
+            .set_stagger(Duration::seconds(5));
+
+        assert!(limiter.request(0, 10.0, now));
+        assert!(!limiter.request(1, 10.0, now + Duration::seconds(1)));
+        assert!(limiter.request(1, 10.0, now + Duration::seconds(5)));
+        assert_eq!(20.0, limiter.amps());
+
+        assert!(!limiter.request(2, 1.0, now + Duration::seconds(20)));
+    }
+}
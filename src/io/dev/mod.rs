@@ -1,9 +1,27 @@
 mod device;
 mod input;
 mod output;
+mod claim;
 mod container;
+mod energy;
+mod enrich;
+mod load;
+mod profile;
+mod quantize;
+mod stats;
+mod view;
 
 pub use device::{Device, DeviceGetters, DeviceSetters};
 pub use input::Input;
 pub use output::Output;
+pub use claim::{Claim, ClaimChange, ClaimPriority, ClaimTransition, CLAIM_HISTORY_FILENAME};
 pub use container::DeviceContainer;
+pub use energy::{EnergyMeter, EnergyUsage};
+pub use enrich::{Enrichment, Enrichments};
+pub use load::LoadLimiter;
+pub use profile::{Calibration, DeviceProfile};
+pub use quantize::{Quantization, Rounding};
+pub use stats::RunningStats;
+pub use view::{DeviceState, DeviceView};
+pub(crate) use energy::is_on;
+pub(crate) use profile::get_profile;
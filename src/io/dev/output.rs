@@ -1,13 +1,16 @@
 use std::fmt::Formatter;
 use std::path::{Path, PathBuf};
-use chrono::{Duration, Utc};
-use crate::action::{Command, IOCommand, Routine};
+use chrono::Duration;
+use crate::action::{Command, IOCommand, Publisher, Routine};
+use crate::console;
 use crate::errors::{DeviceError, ErrorType};
-use crate::helpers::Def;
-use crate::io::{Device, DeviceMetadata, IODirection, IOEvent, IOKind, IdType, RawValue, DeviceGetters, DeviceSetters};
+use crate::helpers::{now, Def};
+use crate::io::{Claim, ClaimChange, ClaimPriority, ClaimTransition, CLAIM_HISTORY_FILENAME, CorrelationId, Device, DeviceMetadata, EnergyMeter, Enrichments, LoadLimiter, EventKind, Quantization, EventQuality, IODirection, IOEvent, IOKind, IdType, RawValue, DeviceGetters, DeviceSetters};
 use crate::io::dev::device::set_log_dir;
+use crate::io::dev::is_on;
+use crate::io::dev::profile::get_profile;
 use crate::name::Name;
-use crate::storage::{Chronicle, Directory, Log};
+use crate::storage::{append_record, with_hooks, Chronicle, Directory, EventHooks, Log};
 
 #[derive(Default)]
 /// This is the generic implementation for any external output device.
@@ -49,6 +52,13 @@ use crate::storage::{Chronicle, Directory, Log};
 ///
 /// With a `command` set, [`Output::write()`] can be used to actuate or send data
 /// to devices.
+///
+/// Like an [`crate::io::Input`], an output may be given a [`Publisher`] by
+/// [`Output::init_publisher()`] so that actions are evaluated whenever the output is written
+/// (ie: to alarm when a pump has been running for too long).
+///
+/// When built with [`Output::require_claims()`], actions may only write while holding a [`Claim`]
+/// on the output. See [`Output::claim()`].
 pub struct Output {
     metadata: DeviceMetadata,
     // cached state
@@ -57,6 +67,34 @@ pub struct Output {
     command: Option<IOCommand>,
 
     dir: Option<PathBuf>,
+    hooks: Option<Def<EventHooks>>,
+
+    /// Passes successful writes to subscribed actions
+    publisher: Option<Publisher>,
+
+    /// Value written when device is driven to a safe state
+    safe_state: Option<RawValue>,
+
+    energy: Option<EnergyMeter>,
+
+    /// Shared load limiter and current drawn by this output
+    load: Option<(LoadLimiter, f32)>,
+    /// Write queued by load limiter, or while inhibited
+    pending: Option<(RawValue, Option<CorrelationId>)>,
+
+    /// Prevent output from turning on
+    inhibited: bool,
+
+    /// Fields computed for every logged write
+    enrichments: Enrichments,
+
+    /// Resolution of values accepted by hardware
+    quantization: Option<Quantization>,
+
+    /// Reject writes by owners which do not hold claim
+    claims_required: bool,
+    /// Current owner of output
+    claim: Option<Claim>,
 }
 
 impl Name for Output {
@@ -109,13 +147,24 @@ impl DeviceSetters for Output {
         self.metadata.id = id;
     }
 
+    fn metadata_mut(&mut self) -> &mut DeviceMetadata {
+        &mut self.metadata
+    }
+
     fn set_log(&mut self, log: Def<Log>) {
         self.log = Some(log.clone());
+        if let Some(publisher) = &mut self.publisher {
+            publisher.set_log(log.clone());
+        }
 
         if let Some(dir) = &self.dir {
             set_log_dir(Some(log), dir)
         }
     }
+
+    fn set_hooks(&mut self, hooks: Def<EventHooks>) {
+        self.hooks = Some(hooks);
+    }
 }
 
 /// Implement unique constructors and builder methods
@@ -141,6 +190,13 @@ impl Device for Output {
         let command = None;
         let log = None;
         let dir = None;
+        let hooks = None;
+        let publisher = None;
+        let safe_state = None;
+        let energy = None;
+        let load = None;
+        let pending = None;
+        let inhibited = false;
 
         Self {
             metadata,
@@ -148,6 +204,17 @@ impl Device for Output {
             log,
             command,
             dir,
+            hooks,
+            publisher,
+            safe_state,
+            energy,
+            load,
+            pending,
+            inhibited,
+            enrichments: Enrichments::default(),
+            quantization: None,
+            claims_required: false,
+            claim: None,
         }
     }
 
@@ -181,12 +248,15 @@ impl Output {
     /// [Low level error type](https://github.com/PoorRican/sensd/issues/192)
     fn tx(&self, value: RawValue) -> Result<IOEvent, DeviceError> {
         if let Some(command) = &self.command {
-            command.execute(Some(value))?;
+            command.execute(Some(value)).map_err(|e| match e {
+                DeviceError::WriteFailed { value, .. } => DeviceError::WriteFailed { metadata: self.metadata.clone(), value },
+                e => e,
+            })?;
         } else {
             Err(DeviceError::NoCommand {metadata: self.metadata.clone()})?;
         };
 
-        Ok(IOEvent::new(value))
+        Ok(IOEvent::new(value).set_kind(EventKind::OutputWrite))
     }
 
     /// Get [`IOEvent`], add to log and update cache.
@@ -196,7 +266,8 @@ impl Output {
     ///
     /// # Parameters
     ///
-    /// - `value`: [`RawValue`] to write to device. There is no check on value.
+    /// - `value`: [`RawValue`] to write to device. There is no check on value, other than
+    ///   quantization set by [`Output::set_quantization()`].
     ///
     /// # Notes
     ///
@@ -204,7 +275,7 @@ impl Output {
     ///
     /// # Panics
     ///
-    /// - If there is an error when writing to device on a low-level
+    /// - If output has no command
     ///
     /// # Examples
     ///
@@ -232,16 +303,478 @@ impl Output {
     ///
     /// - [`Input::push_to_log()`] for adding [`IOEvent`] to [`Log`]
     pub fn write(&mut self, value: RawValue) -> Result<IOEvent, ErrorType> {
-        let event = self.tx(value).expect("Low level device error while writing");
+        self.write_correlated(value, None)
+    }
+
+    /// Write data and stamp resulting [`IOEvent`] with a correlation id
+    ///
+    /// Behaves identically to [`Output::write()`].
+    ///
+    /// # Parameters
+    ///
+    /// - `value`: [`RawValue`] to send to device
+    /// - `correlation`: Id of input event that caused this write
+    ///
+    /// # Returns
+    ///
+    /// `Err` with [`DeviceError::Inhibited`] if output is inhibited, or [`DeviceError::LoadLimited`]
+    /// if write was queued by a [`LoadLimiter`]. The queued write is performed by
+    /// [`Output::attempt_pending()`]. Values which turn output off are never queued.
+    ///
+    /// `Err` with [`DeviceError::WriteFailed`] if the command failed. The attempt is logged with
+    /// [`EventQuality::Bad`], and cached state, energy usage, and load are left as they were.
+    ///
+    /// Only successful writes are propagated to subscribers of [`Output::publisher()`].
+    ///
+    /// # Panics
+    ///
+    /// - If output has no command
+    pub fn write_correlated<C>(&mut self, value: RawValue, correlation: C) -> Result<IOEvent, ErrorType>
+    where
+        C: Into<Option<CorrelationId>>
+    {
+        let correlation = correlation.into();
+        let value = match &self.quantization {
+            Some(quantization) => quantization.apply(value),
+            None => value,
+        };
+        if self.inhibited && is_on(value) {
+            self.pending = Some((value, correlation));
+            return Err(Box::new(DeviceError::Inhibited { metadata: self.metadata.clone() }));
+        }
+        if let Some((limiter, amps)) = &self.load {
+            if !is_on(value) {
+                limiter.release(self.metadata.id);
+            } else if !limiter.request(self.metadata.id, *amps, now()) {
+                self.pending = Some((value, correlation));
+                return Err(Box::new(DeviceError::LoadLimited { metadata: self.metadata.clone() }));
+            }
+        }
+        self.pending = None;
+
+        let mut event = match self.tx(value) {
+            Ok(event) => event.set_correlation(correlation),
+            Err(e @ DeviceError::WriteFailed { .. }) => {
+                // failed attempt is logged, but state is left as it was
+                let mut event = IOEvent::new(value)
+                    .set_kind(EventKind::OutputWrite)
+                    .set_correlation(correlation);
+                event.quality = EventQuality::Bad;
+                self.push_to_log(&event);
+                if let (Some((limiter, _)), true) = (&self.load, is_on(value)) {
+                    limiter.release(self.metadata.id);
+                }
+
+                with_hooks(&self.hooks, |hooks| hooks.dispatch_error(&self.metadata, &e));
+                return Err(Box::new(e));
+            }
+            Err(e) => {
+                with_hooks(&self.hooks, |hooks| hooks.dispatch_error(&self.metadata, &e));
+                panic!("Low level device error while writing: {}", e);
+            }
+        };
 
         // update cached state
         self.state = Some(event.value);
+        if let Some(energy) = self.energy.as_mut() {
+            energy.record(event.value, event.timestamp);
+        }
 
+        self.enrichments.apply(&mut event);
         self.push_to_log(&event);
 
+        with_hooks(&self.hooks, |hooks| hooks.dispatch_write(&self.metadata, &event));
+
+        if let Some(publisher) = &mut self.publisher {
+            publisher.propagate(&event);
+        }
+
         Ok(event)
     }
 
+    /// Update cached state, energy usage, and load after a write performed by a [`Routine`]
+    ///
+    /// Values which turn output off release its share of [`LoadLimiter`], so that outputs queued
+    /// on the same circuit may turn on.
+    pub(crate) fn sync_state(&mut self, event: &IOEvent) {
+        if let (Some((limiter, _)), false) = (&self.load, is_on(event.value)) {
+            limiter.release(self.metadata.id);
+        }
+        self.state = Some(event.value);
+        if let Some(energy) = self.energy.as_mut() {
+            energy.record(event.value, event.timestamp);
+        }
+    }
+
+    /// Alternate constructor using a profile registered by [`crate::io::DeviceProfile::register()`]
+    ///
+    /// Only kind and log settings of a profile apply to outputs.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with [`Output`] named after profile
+    /// - `Err` if no profile named `profile` is registered
+    pub fn from_profile(profile: &str, id: IdType) -> Result<Self, DeviceError> {
+        Ok(get_profile(profile)?.output(id))
+    }
+
+    /// Builder method to set value written when device is driven to a safe state
+    ///
+    /// # Parameters
+    ///
+    /// - `value`: Safe value (ie: `RawValue::Binary(false)` for a pump)
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to allow method chaining
+    pub fn set_safe_state(mut self, value: RawValue) -> Self {
+        self.safe_state = Some(value);
+        self
+    }
+
+    /// Builder method to label device with `tag`
+    ///
+    /// Tags allow actions to target outputs by label. See [`crate::storage::Roster`].
+    pub fn add_tag<T: Into<String>>(mut self, tag: T) -> Self {
+        self.metadata.tags.insert(tag.into());
+        self
+    }
+
+    /// Builder method to round written values to hardware resolution
+    ///
+    /// Every value given to [`Output::write()`] or [`Output::create_routine()`] is quantized before
+    /// it is sent, queued, cached, or logged, so that logs reflect what was actually sent.
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to allow method chaining
+    pub fn set_quantization(mut self, quantization: Quantization) -> Self {
+        self.quantization = Some(quantization);
+        self
+    }
+
+    pub fn quantization(&self) -> Option<&Quantization> {
+        self.quantization.as_ref()
+    }
+
+    /// Builder method to only accept writes by owner of claim
+    ///
+    /// Only writes made by [`Output::write_as()`], which are those of actions, and routines
+    /// scheduled on behalf of an owner (see [`Routine::set_owner()`]) are checked.
+    /// [`Output::write()`] is reserved for the system itself (ie: driving output to safe state) and
+    /// is never rejected.
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to allow method chaining
+    pub fn require_claims(mut self) -> Self {
+        self.claims_required = true;
+        self
+    }
+
+    pub fn claims_required(&self) -> bool {
+        self.claims_required
+    }
+
+    /// Current claim on output. `None` if unclaimed.
+    pub fn claimant(&self) -> Option<&Claim> {
+        self.claim.as_ref()
+    }
+
+    /// Claim exclusive right to write to output
+    ///
+    /// A claim is granted if output is unclaimed, or already held by `owner`, in which case its
+    /// priority is updated. A claim held by another owner is only preempted by a strictly higher
+    /// `priority`, so that a safety interlock always takes precedence over manual override, which
+    /// takes precedence over actions.
+    ///
+    /// Every transition is printed and appended to [`CLAIM_HISTORY_FILENAME`] within output
+    /// directory, if it exists.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with [`ClaimTransition`] describing change of ownership
+    /// - `Err` with [`DeviceError::Claimed`] if output is held by another owner of equal or higher
+    ///   priority
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensd::action::IOCommand;
+    /// use sensd::io::{ClaimChange, ClaimPriority, Device, Output, RawValue};
+    ///
+    /// let mut heater = Output::new("heater", 0, None)
+    ///     .set_command(IOCommand::Output(|_| Ok(())))
+    ///     .require_claims();
+    ///
+    /// heater.claim("thermostat", ClaimPriority::Automatic).unwrap();
+    /// assert!(heater.write_as("thermostat", RawValue::Binary(true), None).is_ok());
+    ///
+    /// // over-temperature interlock takes control
+    /// let transition = heater.claim("overtemp", ClaimPriority::Interlock).unwrap();
+    /// assert_eq!(ClaimChange::Preempted, transition.change);
+    /// assert!(heater.write_as("thermostat", RawValue::Binary(true), None).is_err());
+    /// assert!(heater.claim("operator", ClaimPriority::Manual).is_err());
+    /// ```
+    pub fn claim<O>(&mut self, owner: O, priority: ClaimPriority) -> Result<ClaimTransition, DeviceError>
+    where
+        O: Into<String>
+    {
+        let owner = owner.into();
+        let change = match &self.claim {
+            None => ClaimChange::Granted,
+            Some(claim) if claim.owner == owner => ClaimChange::Granted,
+            Some(claim) if priority > claim.priority => ClaimChange::Preempted,
+            Some(claim) => return Err(DeviceError::Claimed {
+                metadata: self.metadata.clone(),
+                owner: claim.owner.clone(),
+            }),
+        };
+        let timestamp = now();
+        let to = Claim { owner, priority, since: timestamp };
+        let transition = ClaimTransition {
+            timestamp,
+            change,
+            from: self.claim.replace(to.clone()),
+            to: Some(to),
+        };
+        self.record_claim(&transition);
+        Ok(transition)
+    }
+
+    /// Give up claim held by `owner`, leaving output unclaimed
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with [`ClaimTransition`] describing change of ownership
+    /// - `Err` with [`DeviceError::NotClaimed`] if `owner` does not hold claim
+    pub fn release(&mut self, owner: &str) -> Result<ClaimTransition, DeviceError> {
+        if self.claim.as_ref().is_none_or(|claim| claim.owner != owner) {
+            return Err(DeviceError::NotClaimed { metadata: self.metadata.clone(), owner: owner.to_string() });
+        }
+        let transition = ClaimTransition {
+            timestamp: now(),
+            change: ClaimChange::Released,
+            from: self.claim.take(),
+            to: None,
+        };
+        self.record_claim(&transition);
+        Ok(transition)
+    }
+
+    /// Print and persist a change of ownership
+    fn record_claim(&self, transition: &ClaimTransition) {
+        let describe = |claim: &Option<Claim>| claim.as_ref()
+            .map(|claim| format!("\"{}\" ({:?})", claim.owner, claim.priority))
+            .unwrap_or_else(|| String::from("nobody"));
+        console::info(format!("Claim on {} {:?}: {} -> {}",
+                              self.metadata, transition.change, describe(&transition.from), describe(&transition.to)));
+
+        if self.dir.is_some() && self.full_path().exists() {
+            if let Err(e) = append_record(&self.full_path().join(CLAIM_HISTORY_FILENAME), transition) {
+                console::error(format!("Could not record claim on {}: {}", self.metadata, e));
+            }
+        }
+    }
+
+    /// Write data on behalf of `owner`
+    ///
+    /// Behaves identically to [`Output::write_correlated()`], unless claims are required by
+    /// [`Output::require_claims()`].
+    ///
+    /// # Returns
+    ///
+    /// `Err` with [`DeviceError::NotClaimed`] if claims are required and `owner` does not hold
+    /// claim. Nothing is written or queued.
+    pub fn write_as<C>(&mut self, owner: &str, value: RawValue, correlation: C) -> Result<IOEvent, ErrorType>
+    where
+        C: Into<Option<CorrelationId>>
+    {
+        if self.claims_required && self.claim.as_ref().is_none_or(|claim| claim.owner != owner) {
+            return Err(Box::new(DeviceError::NotClaimed { metadata: self.metadata.clone(), owner: owner.to_string() }));
+        }
+        self.write_correlated(value, correlation)
+    }
+
+    /// Getter for safe state
+    pub fn safe_state(&self) -> Option<RawValue> {
+        self.safe_state
+    }
+
+    /// Write safe state to device
+    ///
+    /// # Returns
+    ///
+    /// - `None` if no safe state is set
+    /// - `Some` with result of [`Output::write()`]
+    pub fn write_safe_state(&mut self) -> Option<Result<IOEvent, ErrorType>> {
+        let value = self.safe_state?;
+        Some(self.write(value))
+    }
+
+    /// Builder method to register a field computed for every logged write
+    ///
+    /// See [`crate::io::Input::add_enrichment()`]
+    pub fn add_enrichment<N, F>(mut self, name: N, enrichment: F) -> Self
+    where
+        N: Into<String>,
+        F: Fn(&IOEvent) -> Option<RawValue> + 'static
+    {
+        self.enrichments.add(name, enrichment);
+        self
+    }
+
+    pub fn enrichments(&self) -> &Enrichments {
+        &self.enrichments
+    }
+
+    /// Builder method to track energy consumption
+    ///
+    /// # Parameters
+    ///
+    /// - `meter`: [`EnergyMeter`] configured with power rating and optional tariff
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to allow method chaining
+    pub fn set_energy_meter(mut self, meter: EnergyMeter) -> Self {
+        self.energy = Some(meter);
+        self
+    }
+
+    /// Getter for energy meter
+    pub fn energy_meter(&self) -> Option<&EnergyMeter> {
+        self.energy.as_ref()
+    }
+
+    /// Mutable getter for energy meter
+    pub fn energy_meter_mut(&mut self) -> Option<&mut EnergyMeter> {
+        self.energy.as_mut()
+    }
+
+    /// Set cached state without writing to device (ie: when replaying log after a restart)
+    pub(crate) fn restore_state(&mut self, value: RawValue) {
+        self.state = Some(value);
+    }
+
+    /// Builder method to share a circuit with other outputs
+    ///
+    /// # Parameters
+    ///
+    /// - `limiter`: [`LoadLimiter`] shared by all outputs on circuit
+    /// - `amps`: Current drawn by this output when on
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `Self` to allow method chaining
+    pub fn set_load_limiter(mut self, limiter: LoadLimiter, amps: f32) -> Self {
+        self.load = Some((limiter, amps));
+        self
+    }
+
+    /// Getter for load limiter
+    pub fn load_limiter(&self) -> Option<&LoadLimiter> {
+        self.load.as_ref().map(|(limiter, _)| limiter)
+    }
+
+    /// Check if a write is queued
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Prevent output from turning on until [`Output::enable()`] is called
+    ///
+    /// Writes which would turn output on are queued instead. Only the most recent write is kept.
+    /// Routines scheduled with [`Routine::set_output()`] are queued in the same way.
+    pub fn inhibit(&mut self) {
+        self.inhibited = true;
+    }
+
+    /// Allow output to turn on after [`Output::inhibit()`]
+    ///
+    /// Queued writes are not performed until [`Output::attempt_pending()`] is called.
+    pub fn enable(&mut self) {
+        self.inhibited = false;
+    }
+
+    pub fn is_inhibited(&self) -> bool {
+        self.inhibited
+    }
+
+    /// Queue write to be performed by [`Output::attempt_pending()`], replacing any queued write
+    pub(crate) fn queue(&mut self, value: RawValue, correlation: Option<CorrelationId>) {
+        self.pending = Some((value, correlation));
+    }
+
+    /// Retry queued write
+    ///
+    /// # Returns
+    ///
+    /// - `None` if no write is queued
+    /// - `Some` with result of [`Output::write_correlated()`]
+    pub fn attempt_pending(&mut self) -> Option<Result<IOEvent, ErrorType>> {
+        let (value, correlation) = self.pending?;
+        Some(self.write_correlated(value, correlation))
+    }
+
+    /// Create and set publisher or silently fail
+    ///
+    /// Every successful write is propagated, including writes which do not change state, so that
+    /// subscribers may track how long a state has been held. Writes performed by a [`Routine`] are
+    /// not propagated.
+    ///
+    /// Subscribers must not write to this output, since it remains locked during propagation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensd::action::{IOCommand, Trigger};
+    /// use sensd::action::actions::Threshold;
+    /// use sensd::action::Action;
+    /// use sensd::io::{Device, DeviceGetters, Output, RawValue};
+    ///
+    /// // indicator follows pump
+    /// let indicator = Output::default()
+    ///     .set_command(IOCommand::Output(|_| Ok(())))
+    ///     .into_deferred();
+    /// let mut pump = Output::default()
+    ///     .set_command(IOCommand::Output(|_| Ok(())))
+    ///     .init_publisher();
+    /// pump.publisher_mut().as_mut().unwrap().subscribe(
+    ///     Threshold::with_output("running", RawValue::Binary(false), Trigger::GT, indicator.clone())
+    ///         .into_boxed());
+    ///
+    /// pump.write(RawValue::Binary(true)).unwrap();
+    /// assert_eq!(Some(RawValue::Binary(true)), *indicator.try_lock().unwrap().state());
+    /// ```
+    pub fn init_publisher(mut self) -> Self {
+        match self.publisher {
+            None => {
+                let mut publisher = Publisher::default();
+                if let Some(log) = &self.log {
+                    publisher.set_log(log.clone());
+                }
+                self.publisher = Some(publisher);
+            }
+            _ => {
+                console::warning("Publisher already exists!");
+            }
+        }
+        self
+    }
+
+    pub fn publisher_mut(&mut self) -> &mut Option<Publisher> {
+        &mut self.publisher
+    }
+
+    pub fn publisher(&self) -> &Option<Publisher> {
+        &self.publisher
+    }
+
+    pub fn has_publisher(&self) -> bool {
+        self.publisher.is_some()
+    }
+
     /// Create a [`Routine`] given a value to write and a duration
     ///
     /// # Parameters
@@ -251,9 +784,14 @@ impl Output {
     ///
     /// # Returns
     ///
-    /// [`Routine`] ready to be added to [`crate::action::SchedRoutineHandler`]
+    /// [`Routine`] ready to be added to [`crate::action::SchedRoutineHandler`]. `value` is
+    /// quantized (see [`Output::set_quantization()`]).
     pub fn create_routine(&self, value: RawValue, duration: Duration) -> Routine {
-        let timestamp = Utc::now() + duration;
+        let value = match &self.quantization {
+            Some(quantization) => quantization.apply(value),
+            None => value,
+        };
+        let timestamp = now() + duration;
         let log = self.log.as_ref()
             .expect("Output device does not have log")
             .to_owned()
@@ -297,9 +835,11 @@ impl PartialEq for Output {
 
 #[cfg(test)]
 mod tests {
-    use crate::action::IOCommand;
-    use crate::io::{Device, DeviceGetters, IOKind, Output, RawValue};
-    use crate::storage::{Chronicle, Directory, Document};
+    use crate::action::actions::Threshold;
+    use crate::action::{Action, IOCommand, Trigger};
+    use crate::errors::DeviceError;
+    use crate::io::{ClaimChange, ClaimPriority, ClaimTransition, Device, DeviceGetters, EventQuality, IOKind, Output, Quantization, RawValue, CLAIM_HISTORY_FILENAME};
+    use crate::storage::{read_records, Chronicle, Directory, Document};
 
     /// Dummy output command for testing.
     /// Accepts value and returns `Ok(())`
@@ -358,6 +898,100 @@ mod tests {
         assert_eq!(log.try_lock().unwrap().iter().count(), 1);
     }
 
+    #[test]
+    /// Assert that written values are quantized before being cached and logged
+    fn quantization() {
+        let mut output = Output::default()
+            .set_command(COMMAND)
+            .set_quantization(Quantization::new(0.5))
+            .init_log();
+
+        let event = output.write(RawValue::Float(21.3)).unwrap();
+        assert_eq!(RawValue::Float(21.5), event.value);
+        assert_eq!(Some(RawValue::Float(21.5)), *output.state());
+        assert_eq!(RawValue::Float(21.5), output.log().unwrap().try_lock().unwrap().last().unwrap().value);
+    }
+
+    #[test]
+    /// Assert that only owner of claim may write, and that transitions are recorded
+    fn claims() {
+        let root = std::env::temp_dir().join("sensd_claims_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let output = Output::new("heater", 0, None)
+            .set_command(COMMAND)
+            .require_claims()
+            .set_parent_dir(&root);
+        std::fs::create_dir_all(output.full_path()).unwrap();
+        let output = output.into_deferred();
+        let action = Threshold::new("thermostat", RawValue::Float(20.0), Trigger::LT)
+            .set_output(output.clone());
+
+        // unclaimed writes by actions are dropped
+        action.write(RawValue::Binary(true));
+        assert_eq!(None, *output.try_lock().unwrap().state());
+
+        output.try_lock().unwrap().claim("thermostat", ClaimPriority::Automatic).unwrap();
+        action.write(RawValue::Binary(true));
+        assert_eq!(Some(RawValue::Binary(true)), *output.try_lock().unwrap().state());
+
+        let mut binding = output.try_lock().unwrap();
+        let transition = binding.claim("operator", ClaimPriority::Manual).unwrap();
+        assert_eq!(ClaimChange::Preempted, transition.change);
+        assert_eq!("thermostat", transition.from.unwrap().owner);
+
+        // equal priority cannot preempt
+        match binding.claim("supervisor", ClaimPriority::Manual) {
+            Err(DeviceError::Claimed { owner, .. }) => assert_eq!("operator", owner),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        assert!(binding.release("thermostat").is_err());
+        binding.write_as("operator", RawValue::Binary(false), None).unwrap();
+        drop(binding);
+
+        // preempted action no longer controls output
+        action.write(RawValue::Binary(true));
+        assert_eq!(Some(RawValue::Binary(false)), *output.try_lock().unwrap().state());
+
+        // system writes are never rejected
+        output.try_lock().unwrap().write(RawValue::Binary(true)).unwrap();
+
+        output.try_lock().unwrap().release("operator").unwrap();
+        assert!(output.try_lock().unwrap().claimant().is_none());
+
+        let history: Vec<ClaimTransition> = read_records(&output.try_lock().unwrap().full_path().join(CLAIM_HISTORY_FILENAME)).unwrap();
+        let changes: Vec<ClaimChange> = history.iter().map(|transition| transition.change).collect();
+        assert_eq!(vec![ClaimChange::Granted, ClaimChange::Preempted, ClaimChange::Released], changes);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that failed commands are returned as errors without updating state
+    fn failed_write() {
+        let mut output = Output::new("relay", 3, None)
+            .set_command(IOCommand::Output(|value| match value {
+                RawValue::Binary(true) => Err(()),
+                _ => Ok(()),
+            }))
+            .init_log();
+        output.write(RawValue::Binary(false)).unwrap();
+
+        let error = output.write(RawValue::Binary(true)).unwrap_err();
+        match error.downcast_ref::<DeviceError>() {
+            Some(DeviceError::WriteFailed { metadata, value }) => {
+                assert_eq!(3, metadata.id);
+                assert_eq!(RawValue::Binary(true), *value);
+            }
+            _ => panic!("Unexpected error: {}", error),
+        }
+        assert_eq!(Some(RawValue::Binary(false)), *output.state());
+
+        let log = output.log().unwrap();
+        let log = log.try_lock().unwrap();
+        assert_eq!(2, log.len());
+        assert_eq!(EventQuality::Bad, log.last().unwrap().quality);
+    }
+
     #[test]
     fn test_init_log() {
         let mut output = Output::default();
@@ -369,6 +1003,40 @@ mod tests {
         assert_eq!(true, output.has_log());
     }
 
+    #[test]
+    /// Assert that successful writes are propagated to subscribers
+    fn publisher() {
+        let indicator = Output::default()
+            .set_command(COMMAND)
+            .into_deferred();
+        let mut pump = Output::default()
+            .set_command(COMMAND)
+            .init_log()
+            .init_publisher();
+        assert!(pump.has_publisher());
+        pump.publisher_mut().as_mut().unwrap().subscribe(
+            Threshold::with_output("running", RawValue::Binary(false), Trigger::GT, indicator.clone())
+                .into_boxed());
+
+        let state = || *indicator.try_lock().unwrap().state();
+        pump.write(RawValue::Binary(true)).unwrap();
+        assert_eq!(Some(RawValue::Binary(true)), state());
+        pump.write(RawValue::Binary(true)).unwrap();
+        pump.write(RawValue::Binary(false)).unwrap();
+        assert_eq!(Some(RawValue::Binary(false)), state());
+        assert_eq!(3, pump.publisher().as_ref().unwrap().evaluations());
+
+        // failed writes are not propagated
+        let mut pump = Output::default()
+            .set_command(IOCommand::Output(|_| Err(())))
+            .init_publisher();
+        pump.publisher_mut().as_mut().unwrap().subscribe(
+            Threshold::with_output("running", RawValue::Binary(false), Trigger::GT, indicator.clone())
+                .into_boxed());
+        assert!(pump.write(RawValue::Binary(true)).is_err());
+        assert_eq!(0, pump.publisher().as_ref().unwrap().evaluations());
+    }
+
     #[test]
     fn set_dir() {
         let mut output = Output::default().init_log();
@@ -0,0 +1,245 @@
+use chrono::Duration;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::errors::DeviceError;
+use crate::io::{Device, IOKind, IdType, Input, Output, RawValue};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Linear correction applied to raw readings: `value * scale + offset`
+pub struct Calibration {
+    pub scale: f32,
+    pub offset: f32,
+}
+
+impl Calibration {
+    pub fn new(scale: f32, offset: f32) -> Self {
+        Self { scale, offset }
+    }
+
+    /// Apply correction to a reading
+    ///
+    /// Binary values are returned unchanged. Integer values are rounded.
+    pub fn apply(&self, value: RawValue) -> RawValue {
+        match value.as_float() {
+            Some(raw) => value.with_float(raw * self.scale + self.offset).unwrap_or(value),
+            None => value,
+        }
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self { scale: 1.0, offset: 0.0 }
+    }
+}
+
+/// Registered profiles keyed by name
+fn registry() -> &'static Mutex<HashMap<String, DeviceProfile>> {
+    static PROFILES: OnceLock<Mutex<HashMap<String, DeviceProfile>>> = OnceLock::new();
+    PROFILES.get_or_init(Default::default)
+}
+
+/// Get registered profile by name
+pub(crate) fn get_profile(name: &str) -> Result<DeviceProfile, DeviceError> {
+    registry().lock().unwrap()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| DeviceError::UnknownProfile { name: name.to_string() })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Reusable template for instantiating many similar devices
+///
+/// A profile captures the settings shared by every device of a model (ie: every Atlas pH probe),
+/// so that only the id and command differ between instances. Profiles may be used directly via
+/// [`DeviceProfile::input()`], or registered once by name and instantiated anywhere with
+/// [`Input::from_profile()`] or [`Output::from_profile()`].
+///
+/// # Example
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::action::IOCommand;
+/// use sensd::io::{Calibration, Device, DeviceProfile, Input, IOKind, RawValue};
+/// use sensd::name::Name;
+///
+/// DeviceProfile::new("atlas_ph", IOKind::PH)
+///     .set_bounds(0.0, 14.0)
+///     .set_calibration(Calibration::new(1.0, -0.1))
+///     .set_warmup(Duration::seconds(2))
+///     .set_retries(3)
+///     .register();
+///
+/// let input = Input::from_profile("atlas_ph", 4).unwrap()
+///     .set_command(IOCommand::Input(|| RawValue::Float(7.0)));
+///
+/// assert_eq!("atlas_ph", input.name());
+/// assert_eq!(Some((0.0, 14.0)), input.bounds());
+/// assert!(Input::from_profile("missing", 0).is_err());
+/// ```
+pub struct DeviceProfile {
+    name: String,
+    kind: IOKind,
+    unit: Option<String>,
+    bounds: Option<(f32, f32)>,
+    calibration: Option<Calibration>,
+    warmup: Option<Duration>,
+    retries: usize,
+    interval: Option<Duration>,
+
+    /// Initialize a log for each device
+    log: bool,
+}
+
+impl DeviceProfile {
+    /// Constructor for [`DeviceProfile`]
+    ///
+    /// Devices instantiated from a profile are logged by default.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of profile, which is also used as default name of devices
+    /// - `kind`: Kind of devices
+    pub fn new<N>(name: N, kind: IOKind) -> Self
+    where
+        N: Into<String>
+    {
+        Self {
+            name: name.into(),
+            kind,
+            unit: None,
+            bounds: None,
+            calibration: None,
+            warmup: None,
+            retries: 0,
+            interval: None,
+            log: true,
+        }
+    }
+
+    /// Builder method to override default unit of `kind`. See [`Input::set_unit()`].
+    pub fn set_unit<U>(mut self, unit: U) -> Self
+    where
+        U: Into<String>
+    {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Builder method to set valid measurement range. See [`Input::set_bounds()`].
+    pub fn set_bounds(mut self, min: f32, max: f32) -> Self {
+        self.bounds = Some((min, max));
+        self
+    }
+
+    /// Builder method to set default calibration. See [`Input::set_calibration()`].
+    pub fn set_calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = Some(calibration);
+        self
+    }
+
+    /// Builder method to set warm-up delay. See [`Input::set_warmup()`].
+    pub fn set_warmup(mut self, warmup: Duration) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    /// Builder method to set retry policy. See [`Input::set_retries()`].
+    pub fn set_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Builder method to set interval between reads. See [`Input::set_interval()`].
+    pub fn set_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Builder method to set whether a log is initialized for each device
+    pub fn set_log(mut self, log: bool) -> Self {
+        self.log = log;
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn kind(&self) -> IOKind {
+        self.kind
+    }
+
+    /// Register profile by name, replacing any profile with the same name
+    pub fn register(self) {
+        registry().lock().unwrap().insert(self.name.clone(), self);
+    }
+
+    /// Instantiate an [`Input`] from profile
+    ///
+    /// A command must still be set using [`Device::set_command()`].
+    pub fn input(&self, id: IdType) -> Input {
+        let mut input = Input::new(self.name.clone(), id, self.kind)
+            .set_retries(self.retries);
+        if let Some(unit) = &self.unit {
+            input = input.set_unit(unit.clone());
+        }
+        if let Some((min, max)) = self.bounds {
+            input = input.set_bounds(min, max);
+        }
+        if let Some(calibration) = self.calibration {
+            input = input.set_calibration(calibration);
+        }
+        if let Some(warmup) = self.warmup {
+            input = input.set_warmup(warmup);
+        }
+        if let Some(interval) = self.interval {
+            input = input.set_interval(interval);
+        }
+        if self.log {
+            input = input.init_log();
+        }
+        input
+    }
+
+    /// Instantiate an [`Output`] from profile
+    ///
+    /// Only kind and log settings apply to outputs.
+    pub fn output(&self, id: IdType) -> Output {
+        let output = Output::new(self.name.clone(), id, self.kind);
+        match self.log {
+            true => output.init_log(),
+            false => output,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use crate::action::IOCommand;
+    use crate::io::{Calibration, Device, DeviceGetters, DeviceProfile, EventQuality, IOKind, RawValue};
+    use crate::storage::Chronicle;
+
+    #[test]
+    fn input() {
+        let profile = DeviceProfile::new("probe", IOKind::EC)
+            .set_bounds(0.0, 5.0)
+            .set_calibration(Calibration::new(2.0, 1.0))
+            .set_interval(Duration::seconds(5))
+            .set_log(false);
+
+        let mut input = profile.input(3)
+            .set_command(IOCommand::Input(|| RawValue::Float(2.5)));
+        assert_eq!(3, input.id());
+        assert_eq!(Some(Duration::seconds(5)), input.interval());
+        assert_eq!(Some(String::from("mS/cm")), input.unit());
+        assert!(input.log().is_none());
+
+        // calibrated value exceeds range
+        let event = input.read().unwrap();
+        assert_eq!(RawValue::Float(6.0), event.value);
+        assert_eq!(EventQuality::Bad, event.quality);
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use crate::io::{Fixed, RawValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+/// Direction in which values between two steps are rounded
+pub enum Rounding {
+    /// Round to the nearest step. Halfway values are rounded away from zero.
+    #[default]
+    Nearest,
+    /// Round towards negative infinity
+    Down,
+    /// Round towards positive infinity
+    Up,
+    /// Round towards zero, so that magnitude never increases
+    TowardZero,
+}
+
+impl Rounding {
+    fn apply(&self, steps: f64) -> f64 {
+        match self {
+            Rounding::Nearest => steps.round(),
+            Rounding::Down => steps.floor(),
+            Rounding::Up => steps.ceil(),
+            Rounding::TowardZero => steps.trunc(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Resolution of values accepted by an output
+///
+/// Set with [`crate::io::Output::set_quantization()`]. Values are quantized before they are
+/// written, so that logs record the value that was actually sent to hardware (ie: a heater
+/// controller accepting 0.5 °C steps).
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::{Quantization, RawValue, Rounding};
+///
+/// let quantization = Quantization::new(0.5);
+/// assert_eq!(RawValue::Float(21.5), quantization.apply(RawValue::Float(21.3)));
+///
+/// let quantization = quantization.set_rounding(Rounding::Down);
+/// assert_eq!(RawValue::Float(21.0), quantization.apply(RawValue::Float(21.3)));
+/// ```
+pub struct Quantization {
+    step: f64,
+    rounding: Rounding,
+}
+
+impl Quantization {
+    /// Constructor for quantization to multiples of `step`, rounded to nearest
+    ///
+    /// # Panics
+    ///
+    /// If `step` is not finite and positive
+    pub fn new(step: f64) -> Self {
+        assert!(step.is_finite() && step > 0.0, "Step size must be finite and positive");
+        Self { step, rounding: Rounding::default() }
+    }
+
+    /// Builder method to set rounding direction
+    pub fn set_rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    pub fn rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    /// Round `value` to a multiple of step size
+    ///
+    /// The variant of `value` is kept. Binary, timestamp, and non-finite values are returned
+    /// unchanged.
+    pub fn apply(&self, value: RawValue) -> RawValue {
+        if !value.is_finite() {
+            return value;
+        }
+        match value {
+            RawValue::Binary(_) | RawValue::Timestamp(_) => value,
+            RawValue::Float(x) => RawValue::Float(self.quantize(x as f64) as f32),
+            RawValue::Float64(x) => RawValue::Float64(self.quantize(x)),
+            RawValue::Fixed(x) => RawValue::Fixed(Fixed::from_f64(self.quantize(x.to_f64()))),
+            RawValue::Int64(x) => RawValue::Int64(self.quantize(x as f64) as i64),
+            _ => value.as_float()
+                .and_then(|x| value.with_float(self.quantize(x as f64) as f32))
+                .unwrap_or(value),
+        }
+    }
+
+    fn quantize(&self, value: f64) -> f64 {
+        self.rounding.apply(value / self.step) * self.step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{Fixed, Quantization, RawValue, Rounding};
+
+    #[test]
+    fn apply() {
+        let quantization = Quantization::new(0.5);
+        assert_eq!(RawValue::Float(20.0), quantization.apply(RawValue::Float(20.2)));
+        assert_eq!(RawValue::Float(-20.5), quantization.apply(RawValue::Float(-20.25)));
+        assert_eq!(RawValue::Float64(1.5), quantization.apply(RawValue::Float64(1.7)));
+        assert_eq!(RawValue::Fixed(Fixed::from_f64(1.0)), quantization.apply(RawValue::Fixed(Fixed::from_f64(0.8))));
+        assert_eq!(RawValue::Binary(true), quantization.apply(RawValue::Binary(true)));
+        assert!(quantization.apply(RawValue::Float(f32::NAN)).as_float().unwrap().is_nan());
+
+        let quantization = Quantization::new(10.0);
+        assert_eq!(RawValue::Int(-20), quantization.apply(RawValue::Int(-15)));
+        assert_eq!(RawValue::Int(-10), quantization.set_rounding(Rounding::TowardZero).apply(RawValue::Int(-15)));
+        assert_eq!(RawValue::Int(-10), quantization.set_rounding(Rounding::Up).apply(RawValue::Int(-15)));
+        assert_eq!(RawValue::PosInt8(250), quantization.set_rounding(Rounding::Down).apply(RawValue::PosInt8(255)));
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::now;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Online statistics of readings, updated in constant time and memory
+///
+/// Mean and variance are accumulated using Welford's algorithm, which remains numerically stable
+/// over millions of readings. Maintained by every [`crate::io::Input`], and accessed with
+/// [`crate::io::Input::stats()`].
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::RunningStats;
+///
+/// let mut stats = RunningStats::default();
+/// for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+///     stats.push(value);
+/// }
+///
+/// assert_eq!(8, stats.count());
+/// assert_eq!(Some(5.0), stats.mean());
+/// assert_eq!(Some(2.0), stats.std_dev());
+/// assert_eq!(Some(9.0), stats.max());
+/// ```
+pub struct RunningStats {
+    count: usize,
+    mean: f64,
+    /// Sum of squared differences from mean
+    m2: f64,
+    min: Option<f32>,
+    max: Option<f32>,
+
+    /// Time when statistics were last reset
+    since: DateTime<Utc>,
+}
+
+impl RunningStats {
+    /// Empty statistics starting at `since`
+    pub fn new(since: DateTime<Utc>) -> Self {
+        Self { count: 0, mean: 0.0, m2: 0.0, min: None, max: None, since }
+    }
+
+    /// Incorporate a reading
+    pub fn push(&mut self, value: f32) {
+        self.count += 1;
+        let delta = value as f64 - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value as f64 - self.mean);
+
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+    }
+
+    /// Number of readings incorporated
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Mean of readings. `None` if there are no readings.
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// Population variance of readings. `None` if there are no readings.
+    pub fn variance(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.m2 / self.count as f64)
+    }
+
+    /// Population standard deviation of readings. `None` if there are no readings.
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    pub fn min(&self) -> Option<f32> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<f32> {
+        self.max
+    }
+
+    /// Time when statistics were last reset
+    pub fn since(&self) -> DateTime<Utc> {
+        self.since
+    }
+}
+
+impl Default for RunningStats {
+    fn default() -> Self {
+        Self::new(now())
+    }
+}
@@ -0,0 +1,110 @@
+use serde::Serialize;
+
+use crate::helpers::Def;
+use crate::inspect::tail;
+use crate::io::{Device, DeviceMetadata, IOEvent, RawValue};
+use crate::sparkline::{sparkline, values};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+/// Copy of the current state of a device, as returned by [`DeviceView::state()`]
+pub struct DeviceState {
+    pub metadata: DeviceMetadata,
+
+    /// Last value read or written, if any
+    pub value: Option<RawValue>,
+}
+
+/// Read-only handle to a device for UI consumers
+///
+/// Views are cheap to clone and hold a shared reference to the device, but expose no way to mutate
+/// or lock it. Every accessor only attempts to lock the device, and returns `None` instead of
+/// waiting while the device is in use. A dashboard therefore cannot stall the control loop, nor
+/// can a stalled dashboard hold up a device. All data is returned as an owned copy.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::IOCommand;
+/// use sensd::io::{Device, DeviceView, Input, IOKind, RawValue};
+///
+/// let input = Input::new("temp", 0, IOKind::Temperature)
+///     .set_command(IOCommand::Input(|| RawValue::Float(21.5)))
+///     .init_log()
+///     .into_deferred();
+/// let view = DeviceView::new(&input);
+///
+/// input.lock().unwrap().read().unwrap();
+///
+/// assert_eq!(Some(RawValue::Float(21.5)), view.state().unwrap().value);
+/// assert_eq!(1, view.history(10).unwrap().len());
+/// assert_eq!("▅", view.sparkline(10).unwrap());
+///
+/// // views never wait for a device that is in use
+/// let _guard = input.lock().unwrap();
+/// assert!(view.state().is_none());
+/// ```
+pub struct DeviceView<D: Device>(Def<D>);
+
+impl<D: Device> DeviceView<D> {
+    /// Create view of `device`
+    pub fn new(device: &Def<D>) -> Self {
+        Self(device.clone())
+    }
+
+    /// Current metadata and value of device
+    ///
+    /// # Returns
+    ///
+    /// `None` if device is in use
+    pub fn state(&self) -> Option<DeviceState> {
+        let device = self.0.try_lock().ok()?;
+        Some(DeviceState {
+            metadata: device.metadata().clone(),
+            value: *device.state(),
+        })
+    }
+
+    /// Copy of device metadata
+    ///
+    /// # Returns
+    ///
+    /// `None` if device is in use
+    pub fn metadata(&self) -> Option<DeviceMetadata> {
+        self.0.try_lock().ok().map(|device| device.metadata().clone())
+    }
+
+    /// Most recent logged events of device, in chronological order
+    ///
+    /// # Parameters
+    ///
+    /// - `count`: Maximum number of events to return
+    ///
+    /// # Returns
+    ///
+    /// `None` if device has no log, or if device or log is in use
+    pub fn history(&self, count: usize) -> Option<Vec<IOEvent>> {
+        let log = self.0.try_lock().ok()?.log()?;
+        let log = log.try_lock().ok()?;
+        Some(tail(&log, count))
+    }
+
+    /// Sparkline of the most recent `width` logged readings
+    ///
+    /// # Returns
+    ///
+    /// `None` if device has no log, or if device or log is in use
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::sparkline`] for other renderings
+    pub fn sparkline(&self, width: usize) -> Option<String> {
+        let history = self.history(width)?;
+        Some(sparkline(&values(&history), width))
+    }
+}
+
+impl<D: Device> Clone for DeviceView<D> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
@@ -1,7 +1,72 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
 
+use crate::helpers::now;
 use crate::io::{IdTraits, RawValue};
+use crate::storage::EventKey;
+
+/// Discriminant describing what an [`IOEvent`] represents
+///
+/// Allows a single, time-ordered log to capture device reads, writes, and alarm transitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum EventKind {
+    /// Value read from an input device
+    #[default]
+    SensorRead,
+    /// Value written to an output device
+    OutputWrite,
+    /// An alarm condition was raised
+    AlarmRaised,
+    /// An alarm condition was cleared
+    AlarmCleared,
+    /// Arbitrary user annotation
+    Annotation,
+}
+
+/// Confidence in the value of an [`IOEvent`]
+///
+/// Readings are assumed to be good. Actions (ie: [`crate::action::actions::Anomaly`]) may downgrade
+/// quality after the fact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum EventQuality {
+    #[default]
+    Good,
+    /// Value is plausible but deviates from expected behavior
+    Suspect,
+    /// Value is known to be invalid
+    Bad,
+}
+
+/// Shared identifier used to trace an input reading to any resulting actuation
+///
+/// A new id is generated for every [`crate::io::Input::read()`] and is carried by any [`IOEvent`]
+/// that results from that reading, including those from scheduled [`crate::action::Routine`]s.
+///
+/// Ids combine a millisecond timestamp with a process-wide counter so that they remain unique
+/// across restarts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CorrelationId(u64);
+
+impl CorrelationId {
+    /// Generate a new, unique id
+    pub fn generate() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let millis = now().timestamp_millis() as u64;
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        Self((millis << 16) | (count & 0xFFFF))
+    }
+}
+
+impl Display for CorrelationId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
 
 /// Dedicated object for storing a single record at a specific point in time.
 ///
@@ -22,13 +87,51 @@ use crate::io::{IdTraits, RawValue};
 /// However, if a specific `timestamp` is desired, the [`IOEvent::with_timestamp()`]
 /// allows `timestamp` to be passed as a parameter.
 ///
+/// Events default to [`EventKind::SensorRead`]. Other kinds are set using [`IOEvent::set_kind()`]:
+///
+/// ```
+/// use sensd::io::{EventKind, IOEvent, RawValue};
+///
+/// let event = IOEvent::new(RawValue::Binary(true))
+///     .set_kind(EventKind::OutputWrite);
+///
+/// assert_eq!(EventKind::OutputWrite, event.kind);
+/// ```
+///
 /// # See Also
 ///
 /// A collection of multiple [`IOEvent`] objects is handled by [`crate::storage::EventCollection`].
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct IOEvent {
     pub timestamp: DateTime<Utc>,
     pub value: RawValue,
+
+    /// Defaults to [`EventKind::SensorRead`] for logs that predate this field
+    #[serde(default)]
+    pub kind: EventKind,
+
+    /// Links events caused by the same input reading
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation: Option<CorrelationId>,
+
+    #[serde(default)]
+    pub quality: EventQuality,
+
+    /// Position among events of a log which share `timestamp`
+    ///
+    /// Only assigned by [`crate::storage::CollisionPolicy::Sequence`]. Zero otherwise.
+    #[serde(default, skip_serializing_if = "is_first")]
+    pub sequence: u32,
+
+    /// Values computed from this event when it was logged
+    ///
+    /// See [`crate::io::Enrichments`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, RawValue>,
+}
+
+fn is_first(sequence: &u32) -> bool {
+    *sequence == 0
 }
 
 impl IOEvent {
@@ -61,6 +164,11 @@ impl IOEvent {
         IOEvent {
             timestamp,
             value,
+            kind: EventKind::default(),
+            correlation: None,
+            quality: EventQuality::default(),
+            sequence: 0,
+            fields: BTreeMap::new(),
         }
     }
 
@@ -86,9 +194,56 @@ impl IOEvent {
     /// assert_eq!(value, event.value);
     /// ```
     pub fn new(value: RawValue) -> Self {
-        let timestamp = Utc::now();
+        let timestamp = now();
         IOEvent::with_timestamp(timestamp, value)
     }
+
+    /// Builder method to set event kind
+    ///
+    /// # Parameters
+    ///
+    /// - `kind`: What event represents
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `self` with updated `kind`
+    pub fn set_kind(mut self, kind: EventKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Builder method to set correlation id
+    ///
+    /// # Parameters
+    ///
+    /// - `correlation`: Id shared with originating event, if any
+    ///
+    /// # Returns
+    ///
+    /// Ownership of `self` with updated `correlation`
+    pub fn set_correlation<C>(mut self, correlation: C) -> Self
+    where
+        C: Into<Option<CorrelationId>>
+    {
+        self.correlation = correlation.into();
+        self
+    }
+
+    /// Value of a computed field
+    ///
+    /// # Returns
+    ///
+    /// `None` if field was not computed when event was logged
+    pub fn field(&self, name: &str) -> Option<RawValue> {
+        self.fields.get(name).copied()
+    }
+
+    /// Key of event within a [`crate::storage::Log`]
+    ///
+    /// Events sharing a timestamp are ordered by `sequence`.
+    pub fn key(&self) -> EventKey {
+        EventKey::new(self.timestamp, self.sequence)
+    }
 }
 
 impl IdTraits for DateTime<Utc> {}
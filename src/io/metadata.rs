@@ -1,13 +1,20 @@
 use crate::io;
-use crate::io::{IdType, IOKind, IODirection};
+use crate::io::{IdType, IOKind, IODirection, Span};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::fmt::Formatter;
+use uuid::Uuid;
 
 /// Encapsulate device metadata
 ///
 /// This struct stores information about a physical or abstract device, including a user provided name, ID,
 /// the kind of device, and the dataflow direction (defaults to input). In future releases, the included data
 /// must be minimal and remain universal and agnostic to device type.
+///
+/// `id` is a human-friendly index which may be renumbered, whereas `uuid` is generated once when a
+/// device is created and is persisted with its log and in snapshots. Once a saved log has been
+/// loaded, a device keeps the UUID it was first assigned, so that its log and snapshot entries are
+/// still found after it is renamed or renumbered.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct DeviceMetadata {
     /// User given name of device
@@ -16,11 +23,35 @@ pub struct DeviceMetadata {
     /// User given device id
     pub id: IdType,
 
+    /// Stable identity of device. Nil for metadata saved before UUIDs were introduced.
+    #[serde(default)]
+    pub uuid: Uuid,
+
     /// Sensor/device type
     pub kind: IOKind,
 
     /// I/O direction
     pub direction: IODirection,
+
+    /// Arbitrary labels used to select groups of devices
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+
+    /// Physical location of device (ie: "greenhouse 2, bench 4")
+    #[serde(default)]
+    pub location: Option<String>,
+
+    /// Valid measurement range as `(min, max)`
+    #[serde(default)]
+    pub bounds: Option<(f32, f32)>,
+
+    /// Engineering range used to express values as a percent of range
+    #[serde(default)]
+    pub span: Option<Span>,
+
+    /// Time between reads in milliseconds. `None` uses interval of group.
+    #[serde(default)]
+    pub interval: Option<i64>,
 }
 
 impl DeviceMetadata {
@@ -35,7 +66,7 @@ impl DeviceMetadata {
     ///
     /// # Returns
     ///
-    /// A new [`DeviceMetadata`] instance with given parameters
+    /// A new [`DeviceMetadata`] instance with given parameters and a newly generated UUID
     ///
     /// # Example
     ///
@@ -53,6 +84,7 @@ impl DeviceMetadata {
     /// assert_eq!(metadata.id, id);
     /// assert_eq!(metadata.kind, kind);
     /// assert_eq!(metadata.direction, direction);
+    /// assert!(!metadata.uuid.is_nil());
     /// ```
     pub fn new<N>(name: N, id: IdType, kind: io::IOKind, direction: io::IODirection) -> Self
     where
@@ -61,9 +93,112 @@ impl DeviceMetadata {
         DeviceMetadata {
             name: name.into(),
             id,
+            uuid: Uuid::new_v4(),
             kind,
             direction,
+            ..Default::default()
+        }
+    }
+
+    /// Check if device is labelled with `tag`
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Change to descriptive metadata applied to many devices at once
+///
+/// Only tags, location, and bounds may be patched. Fields which are not set are left untouched.
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::{DeviceMetadata, MetadataPatch};
+///
+/// let patch = MetadataPatch::default()
+///     .add_tag("bench-4")
+///     .set_location("greenhouse 2")
+///     .set_bounds(0.0, 14.0);
+///
+/// let mut metadata = DeviceMetadata::default();
+/// assert!(patch.apply(&mut metadata));
+/// assert!(metadata.has_tag("bench-4"));
+///
+/// // patch has already been applied
+/// assert!(!patch.apply(&mut metadata));
+/// ```
+pub struct MetadataPatch {
+    pub add_tags: BTreeSet<String>,
+    pub remove_tags: BTreeSet<String>,
+
+    /// `Some(None)` clears location
+    pub location: Option<Option<String>>,
+
+    /// `Some(None)` clears bounds
+    pub bounds: Option<Option<(f32, f32)>>,
+}
+
+impl MetadataPatch {
+    pub fn add_tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<String>
+    {
+        self.add_tags.insert(tag.into());
+        self
+    }
+
+    pub fn remove_tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<String>
+    {
+        self.remove_tags.insert(tag.into());
+        self
+    }
+
+    pub fn set_location<L>(mut self, location: L) -> Self
+    where
+        L: Into<String>
+    {
+        self.location = Some(Some(location.into()));
+        self
+    }
+
+    pub fn clear_location(mut self) -> Self {
+        self.location = Some(None);
+        self
+    }
+
+    pub fn set_bounds(mut self, min: f32, max: f32) -> Self {
+        self.bounds = Some(Some((min, max)));
+        self
+    }
+
+    pub fn clear_bounds(mut self) -> Self {
+        self.bounds = Some(None);
+        self
+    }
+
+    /// Apply patch to `metadata`
+    ///
+    /// # Returns
+    ///
+    /// `true` if `metadata` was changed
+    pub fn apply(&self, metadata: &mut DeviceMetadata) -> bool {
+        let before = metadata.clone();
+
+        for tag in self.remove_tags.iter() {
+            metadata.tags.remove(tag);
+        }
+        metadata.tags.extend(self.add_tags.iter().cloned());
+        if let Some(location) = &self.location {
+            metadata.location = location.clone();
         }
+        if let Some(bounds) = self.bounds {
+            metadata.bounds = bounds;
+        }
+
+        *metadata != before
     }
 }
 
@@ -87,4 +222,17 @@ mod tests {
         DeviceMetadata::new("as &str", 0, IOKind::default(), IODirection::default());
         DeviceMetadata::new(String::from("as String"), 0, IOKind::default(), IODirection::default());
     }
+
+    #[test]
+    /// Test that every device is assigned a distinct UUID, and that older metadata without a UUID
+    /// may still be read
+    fn uuid() {
+        let a = DeviceMetadata::new("a", 0, IOKind::default(), IODirection::default());
+        let b = DeviceMetadata::new("a", 0, IOKind::default(), IODirection::default());
+        assert_ne!(a.uuid, b.uuid);
+
+        let legacy: DeviceMetadata = serde_json::from_str(
+            r#"{"name": "a", "id": 0, "kind": "Unassigned", "direction": "In"}"#).unwrap();
+        assert!(legacy.uuid.is_nil());
+    }
 }
\ No newline at end of file
@@ -3,8 +3,10 @@ mod event;
 mod metadata;
 mod types;
 mod dev;
+mod derived;
 
 pub use dev::*;
-pub use event::IOEvent;
-pub use metadata::DeviceMetadata;
+pub use derived::*;
+pub use event::{CorrelationId, EventKind, EventQuality, IOEvent};
+pub use metadata::{DeviceMetadata, MetadataPatch};
 pub use types::*;
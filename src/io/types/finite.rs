@@ -0,0 +1,171 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+/// How `NaN` and infinite floats are handled
+///
+/// Faulty sensors and drivers may return `NaN` or infinity. Left unchecked, these silently
+/// propagate into control decisions: every comparison with `NaN` is false, and a single `NaN`
+/// poisons an average. The policy is applied process-wide, and is set using
+/// [`set_non_finite_policy()`].
+///
+/// Non-finite readings are always marked as [`crate::io::EventQuality::Bad`]. Triggers, controllers,
+/// and aggregation treat values according to [`crate::io::RawValue::to_finite()`]:
+///
+/// - [`NonFinitePolicy::Ignore`] logs readings unchanged, then treats them as absent
+/// - [`NonFinitePolicy::Clamp`] replaces infinity with the largest finite value of the same sign,
+///   both when logged and when used. `NaN` is ignored.
+/// - [`NonFinitePolicy::Reject`] fails [`crate::io::Input::read()`], so readings are never logged
+///
+/// Logs remain readable in every case since non-finite floats are serialized as strings.
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::{set_non_finite_policy, NonFinitePolicy, RawValue};
+///
+/// assert_eq!(None, RawValue::Float(f32::NAN).to_finite());
+///
+/// set_non_finite_policy(NonFinitePolicy::Clamp);
+/// assert_eq!(Some(RawValue::Float(f32::MAX)), RawValue::Float(f32::INFINITY).to_finite());
+/// # set_non_finite_policy(NonFinitePolicy::default());
+/// ```
+pub enum NonFinitePolicy {
+    /// Treat non-finite values as if there were no value
+    #[default]
+    Ignore,
+    /// Saturate infinity to the largest finite value
+    Clamp,
+    /// Reject non-finite readings at input
+    Reject,
+}
+
+impl NonFinitePolicy {
+    /// Apply policy to a float
+    ///
+    /// # Returns
+    ///
+    /// `None` if `value` should be treated as absent
+    pub fn apply(&self, value: f32) -> Option<f32> {
+        if value.is_finite() {
+            return Some(value);
+        }
+        match self {
+            NonFinitePolicy::Clamp if !value.is_nan() => Some(value.clamp(f32::MIN, f32::MAX)),
+            _ => None,
+        }
+    }
+
+    /// Apply policy to a 64-bit float. See [`NonFinitePolicy::apply()`].
+    pub fn apply_f64(&self, value: f64) -> Option<f64> {
+        if value.is_finite() {
+            return Some(value);
+        }
+        match self {
+            NonFinitePolicy::Clamp if !value.is_nan() => Some(value.clamp(f64::MIN, f64::MAX)),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => NonFinitePolicy::Clamp,
+            2 => NonFinitePolicy::Reject,
+            _ => NonFinitePolicy::Ignore,
+        }
+    }
+}
+
+static POLICY: AtomicU8 = AtomicU8::new(0);
+
+/// Set process-wide handling of non-finite floats
+pub fn set_non_finite_policy(policy: NonFinitePolicy) {
+    POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+/// Current process-wide handling of non-finite floats
+pub fn non_finite_policy() -> NonFinitePolicy {
+    NonFinitePolicy::from_u8(POLICY.load(Ordering::Relaxed))
+}
+
+/// Serialize non-finite floats as strings, since JSON has no representation for them
+pub(crate) mod float {
+    use std::str::FromStr;
+    use super::*;
+
+    /// Floats which may be non-finite
+    pub trait NonFinite: Copy + Serialize + FromStr {
+        /// Textual representation of value, or `None` if value is finite
+        fn non_finite(&self) -> Option<&'static str>;
+    }
+
+    macro_rules! impl_non_finite {
+        ($t:ty) => {
+            impl NonFinite for $t {
+                fn non_finite(&self) -> Option<&'static str> {
+                    match *self {
+                        value if value.is_finite() => None,
+                        value if value.is_nan() => Some("NaN"),
+                        value if value > 0.0 => Some("inf"),
+                        _ => Some("-inf"),
+                    }
+                }
+            }
+        };
+    }
+    impl_non_finite!(f32);
+    impl_non_finite!(f64);
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr<T> {
+        Number(T),
+        Text(String),
+    }
+
+    pub fn serialize<T: NonFinite, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        match value.non_finite() {
+            Some(text) => serializer.serialize_str(text),
+            None => value.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: NonFinite + Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        match Repr::<T>::deserialize(deserializer)? {
+            Repr::Number(value) => Ok(value),
+            Repr::Text(text) => text.parse().map_err(|_| serde::de::Error::custom(format!("Invalid float \"{}\"", text))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{NonFinitePolicy, RawValue};
+
+    #[test]
+    fn apply() {
+        assert_eq!(Some(1.5), NonFinitePolicy::Ignore.apply(1.5));
+        assert_eq!(None, NonFinitePolicy::Ignore.apply(f32::INFINITY));
+        assert_eq!(Some(f32::MIN), NonFinitePolicy::Clamp.apply(f32::NEG_INFINITY));
+        assert_eq!(None, NonFinitePolicy::Clamp.apply(f32::NAN));
+        assert_eq!(None, NonFinitePolicy::Reject.apply(f32::NAN));
+    }
+
+    #[test]
+    fn serialize() {
+        for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 2.5] {
+            let json = serde_json::to_string(&RawValue::Float(value)).unwrap();
+            let parsed: RawValue = serde_json::from_str(&json).unwrap();
+            match parsed {
+                RawValue::Float(parsed) if value.is_nan() => assert!(parsed.is_nan()),
+                RawValue::Float(parsed) => assert_eq!(value, parsed),
+                _ => panic!("Unexpected variant"),
+            }
+        }
+        assert_eq!("{\"Float\":\"-inf\"}", serde_json::to_string(&RawValue::Float(f32::NEG_INFINITY)).unwrap());
+    }
+}
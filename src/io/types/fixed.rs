@@ -0,0 +1,227 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use crate::errors::ValueError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Fixed-point decimal with six decimal places
+///
+/// Repeatedly adding small increments to an `f32` loses precision once the total is large (ie: a
+/// totalizer of dosed volume after months of operation). [`Fixed`] stores an exact count of
+/// millionths, so accumulation is exact and totals never drift.
+///
+/// Serialized as a decimal string so that no precision is lost in logs or snapshots.
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::{Fixed, RawValue};
+///
+/// let mut total = RawValue::Fixed(Fixed::ZERO);
+/// for _ in 0..1_000_000 {
+///     total = total + RawValue::Fixed("0.1".parse().unwrap());
+/// }
+/// assert_eq!(RawValue::Fixed(Fixed::from_int(100_000)), total);
+/// ```
+pub struct Fixed(i64);
+
+impl Fixed {
+    /// Number of decimal places
+    pub const DECIMALS: u32 = 6;
+    const SCALE: i64 = 10_i64.pow(Self::DECIMALS);
+
+    pub const ZERO: Fixed = Fixed(0);
+    pub const MAX: Fixed = Fixed(i64::MAX);
+
+    /// Create from a count of millionths
+    pub fn from_units(units: i64) -> Self {
+        Self(units)
+    }
+
+    /// Count of millionths
+    pub fn units(&self) -> i64 {
+        self.0
+    }
+
+    pub fn from_int(value: i64) -> Self {
+        Self(value * Self::SCALE)
+    }
+
+    /// Create from a float, rounded to the nearest millionth
+    pub fn from_f64(value: f64) -> Self {
+        Self((value * Self::SCALE as f64).round() as i64)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / Self::SCALE as f64
+    }
+}
+
+impl Display for Fixed {
+    /// Trailing zeros are omitted
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let units = self.0.unsigned_abs();
+        let scale = Self::SCALE as u64;
+        let fraction = format!("{:0width$}", units % scale, width = Self::DECIMALS as usize);
+        match fraction.trim_end_matches('0') {
+            "" => write!(f, "{}{}", sign, units / scale),
+            fraction => write!(f, "{}{}.{}", sign, units / scale, fraction),
+        }
+    }
+}
+
+impl FromStr for Fixed {
+    type Err = ValueError;
+
+    /// Parse decimal string exactly
+    ///
+    /// Digits beyond six decimal places are rejected rather than rounded.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ValueError::InvalidFixed { value: s.to_string() };
+        let (negative, digits) = match s.trim().strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.trim()),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let valid = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if integer.is_empty() || !valid(integer) || !valid(fraction) || fraction.len() > Self::DECIMALS as usize {
+            return Err(invalid());
+        }
+
+        let integer: i64 = integer.parse().map_err(|_| invalid())?;
+        let fraction: i64 = format!("{:0<width$}", fraction, width = Self::DECIMALS as usize)
+            .parse()
+            .map_err(|_| invalid())?;
+        let units = integer.checked_mul(Self::SCALE)
+            .and_then(|units| units.checked_add(fraction))
+            .ok_or_else(invalid)?;
+        Ok(Self(if negative { -units } else { units }))
+    }
+}
+
+impl Serialize for Fixed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Fixed {
+    /// Accepts decimal strings, and numbers which are rounded to the nearest millionth
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(f64),
+            Text(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(value) => Ok(Fixed::from_f64(value)),
+            Repr::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+// █▓▒░ Basic mathematical operations
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0 + other.0)
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0 - other.0)
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    /// Product is rounded to the nearest millionth
+    fn mul(self, other: Fixed) -> Fixed {
+        let product = self.0 as i128 * other.0 as i128;
+        Fixed(div_round(product, Self::SCALE as i128) as i64)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+
+    /// Quotient is rounded to the nearest millionth
+    ///
+    /// # Panics
+    ///
+    /// If `other` is zero
+    fn div(self, other: Fixed) -> Fixed {
+        Fixed(div_round(widen(self.0), other.0 as i128) as i64)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Fixed(-self.0)
+    }
+}
+
+/// Units scaled by another factor of [`Fixed::SCALE`], so that a quotient retains its decimals
+fn widen(units: i64) -> i128 {
+    units as i128 * Fixed::SCALE as i128
+}
+
+/// Integer division rounded half away from zero
+fn div_round(dividend: i128, divisor: i128) -> i128 {
+    let quotient = dividend / divisor;
+    let remainder = dividend % divisor;
+    if remainder.abs() * 2 >= divisor.abs() {
+        quotient + dividend.signum() * divisor.signum()
+    } else {
+        quotient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::Fixed;
+
+    #[test]
+    fn parse() {
+        let fixed: Fixed = "-12.05".parse().unwrap();
+        assert_eq!(-12_050_000, fixed.units());
+        assert_eq!("-12.05", fixed.to_string());
+        assert_eq!("3", Fixed::from_int(3).to_string());
+        assert_eq!("0.000001", Fixed::from_units(1).to_string());
+
+        assert!("1.0000001".parse::<Fixed>().is_err());
+        assert!(".5".parse::<Fixed>().is_err());
+        assert!("1e3".parse::<Fixed>().is_err());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a: Fixed = "2.5".parse().unwrap();
+        let b: Fixed = "0.4".parse().unwrap();
+        assert_eq!(Fixed::from_int(1), a * b);
+        assert_eq!("6.25", (a / b).to_string());
+        assert_eq!("0.333333", (Fixed::from_int(1) / Fixed::from_int(3)).to_string());
+        assert_eq!("-0.666667", (Fixed::from_int(-2) / Fixed::from_int(3)).to_string());
+        assert_eq!("2.1", (a - b).to_string());
+    }
+
+    #[test]
+    fn serialize() {
+        let fixed: Fixed = "1234567.890123".parse().unwrap();
+        let json = serde_json::to_string(&fixed).unwrap();
+        assert_eq!("\"1234567.890123\"", json);
+        assert_eq!(fixed, serde_json::from_str(&json).unwrap());
+        assert_eq!(Fixed::from_units(1_500_000), serde_json::from_str("1.5").unwrap());
+    }
+}
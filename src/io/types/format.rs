@@ -0,0 +1,197 @@
+use crate::io::{IOKind, RawValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Separators used when rendering numbers
+pub struct Locale {
+    pub decimal: char,
+
+    /// Separator between groups of thousands. `None` disables grouping.
+    pub thousands: Option<char>,
+}
+
+impl Locale {
+    /// `1,234.5`
+    pub const EN: Locale = Locale { decimal: '.', thousands: Some(',') };
+    /// `1.234,5`
+    pub const DE: Locale = Locale { decimal: ',', thousands: Some('.') };
+    /// `1 234,5`
+    pub const FR: Locale = Locale { decimal: ',', thousands: Some('\u{202F}') };
+    /// `1234.5`
+    pub const PLAIN: Locale = Locale { decimal: '.', thousands: None };
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::EN
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Renders values with a unit into display strings
+///
+/// Display of [`RawValue`] is intended for logs and debugging. Anything shown to a person (ie:
+/// notifications and reports) should be rendered with [`ValueFormat`], so that the same value is
+/// displayed consistently everywhere.
+///
+/// Floats and fixed-point decimals are rounded to a fixed number of decimal places. Integers are
+/// never given decimal places, and binary values are rendered as "on" or "off". Timestamps are
+/// rendered without a unit.
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::{IOKind, Locale, RawValue, ValueFormat};
+///
+/// let format = ValueFormat::for_kind(IOKind::Temperature);
+/// assert_eq!("23.50 °C", format.format(RawValue::Float(23.5)));
+///
+/// let format = ValueFormat::default()
+///     .set_locale(Locale::DE)
+///     .set_precision(1)
+///     .set_unit("L");
+/// assert_eq!("1.234,6 L", format.format(RawValue::Float(1234.56)));
+/// ```
+pub struct ValueFormat {
+    locale: Locale,
+    precision: usize,
+    unit: Option<String>,
+}
+
+impl Default for ValueFormat {
+    fn default() -> Self {
+        Self {
+            locale: Locale::default(),
+            precision: 2,
+            unit: None,
+        }
+    }
+}
+
+impl ValueFormat {
+    /// Format with default unit of `kind`. See [`IOKind::unit()`].
+    pub fn for_kind(kind: IOKind) -> Self {
+        let format = Self::default();
+        match kind.unit() {
+            Some(unit) => format.set_unit(unit),
+            None => format,
+        }
+    }
+
+    pub fn set_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Builder method to set number of decimal places of floats
+    pub fn set_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn set_unit<U>(mut self, unit: U) -> Self
+    where
+        U: Into<String>
+    {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    pub fn precision(&self) -> usize {
+        self.precision
+    }
+
+    pub fn unit(&self) -> Option<&String> {
+        self.unit.as_ref()
+    }
+
+    /// Render value with unit
+    pub fn format(&self, value: RawValue) -> String {
+        let number = match value {
+            RawValue::Binary(state) => return String::from(if state { "on" } else { "off" }),
+            RawValue::Float(value) => self.number(value as f64, self.precision),
+            RawValue::Fixed(value) => self.number(value.to_f64(), self.precision),
+            RawValue::Float64(value) => self.number(value, self.precision),
+            // rendered directly since large values are not representable as `f64`
+            RawValue::Int64(value) => self.separate(&value.unsigned_abs().to_string(), value < 0),
+            RawValue::Timestamp(time) => return time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            _ => self.number(value.as_float().unwrap() as f64, 0),
+        };
+        self.with_unit(number)
+    }
+
+    /// Render a float with unit
+    pub fn format_float(&self, value: f32) -> String {
+        self.with_unit(self.number(value as f64, self.precision))
+    }
+
+    fn with_unit(&self, number: String) -> String {
+        match &self.unit {
+            // percent signs are not separated from value
+            Some(unit) if unit == "%" => format!("{}{}", number, unit),
+            Some(unit) => format!("{} {}", number, unit),
+            None => number,
+        }
+    }
+
+    /// Render number with separators of locale
+    fn number(&self, value: f64, precision: usize) -> String {
+        self.separate(&format!("{:.*}", precision, value.abs()), value < 0.0)
+    }
+
+    /// Insert separators of locale into a rendered magnitude
+    fn separate(&self, rendered: &str, negative: bool) -> String {
+        let (integer, fraction) = match rendered.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (rendered, None),
+        };
+
+        let mut text = String::new();
+        // avoid rendering "-0.00"
+        if negative && rendered.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            text.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            let remaining = integer.len() - i;
+            if i > 0 && remaining % 3 == 0 {
+                if let Some(separator) = self.locale.thousands {
+                    text.push(separator);
+                }
+            }
+            text.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            text.push(self.locale.decimal);
+            text.push_str(fraction);
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{IOKind, Locale, RawValue, ValueFormat};
+
+    #[test]
+    fn format() {
+        let format = ValueFormat::default();
+        assert_eq!("1,234,567.00", format.format(RawValue::Float(1234567.0)));
+        assert_eq!("-12.35", format.format(RawValue::Float(-12.345)));
+        assert_eq!("0.00", format.format(RawValue::Float(-0.001)));
+        assert_eq!("1,000", format.format(RawValue::Int(1000)));
+        assert_eq!("off", format.format(RawValue::Binary(false)));
+        assert_eq!("12.35", format.format(RawValue::Fixed("12.345678".parse().unwrap())));
+        assert_eq!("9,007,199,254,740,993", format.format(RawValue::Int64(9_007_199_254_740_993)));
+
+        let format = ValueFormat::for_kind(IOKind::RelativeHumidity)
+            .set_locale(Locale::FR)
+            .set_precision(1);
+        assert_eq!("55,5%", format.format_float(55.5));
+        assert_eq!("1\u{202F}000,0%", format.format_float(1000.0));
+
+        assert_eq!("7.00", ValueFormat::for_kind(IOKind::PH).format_float(7.0));
+    }
+}
@@ -25,6 +25,40 @@ pub enum IOKind {
     Flow,
     EC,
     PH,
+    /// Vapor pressure deficit. See [`crate::io::Vpd`].
+    VaporPressureDeficit,
+    /// See [`crate::io::DewPoint`]
+    DewPoint,
+    /// Apparent temperature. See [`crate::io::HeatIndex`].
+    HeatIndex,
+    /// Mass of water vapor per volume of air. See [`crate::io::AbsoluteHumidity`].
+    AbsoluteHumidity,
+}
+
+impl IOKind {
+    /// Default unit of measurement used when rendering values
+    ///
+    /// # Returns
+    ///
+    /// `None` for dimensionless or unassigned kinds
+    pub fn unit(&self) -> Option<&'static str> {
+        match self {
+            IOKind::Light => Some("lx"),
+            IOKind::Pressure => Some("hPa"),
+            IOKind::Proximity => Some("mm"),
+            IOKind::RelativeHumidity => Some("%"),
+            IOKind::Temperature => Some("°C"),
+            IOKind::Voltage => Some("V"),
+            IOKind::Current => Some("A"),
+            IOKind::TVOC => Some("ppb"),
+            IOKind::Flow => Some("L/min"),
+            IOKind::EC => Some("mS/cm"),
+            IOKind::VaporPressureDeficit => Some("kPa"),
+            IOKind::DewPoint | IOKind::HeatIndex => Some("°C"),
+            IOKind::AbsoluteHumidity => Some("g/m³"),
+            _ => None,
+        }
+    }
 }
 
 impl Display for IOKind {
@@ -46,6 +80,10 @@ impl Display for IOKind {
             IOKind::Flow => "Flow (liquid)",
             IOKind::EC => "Electrical Conductivity (EC)",
             IOKind::PH => "pH",
+            IOKind::VaporPressureDeficit => "Vapor Pressure Deficit (VPD)",
+            IOKind::DewPoint => "Dew Point",
+            IOKind::HeatIndex => "Heat Index",
+            IOKind::AbsoluteHumidity => "Absolute Humidity",
         };
         write!(f, "{}", name)
     }
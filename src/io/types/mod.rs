@@ -1,11 +1,19 @@
 //! Low-level type and interface definitions for I/O with the filesystem, memory, and other resources.
 
 mod direction;
+mod finite;
+mod fixed;
+mod format;
 mod id;
 mod kind;
 mod raw;
+mod span;
 
 pub use direction::*;
+pub use fixed::Fixed;
+pub use finite::{non_finite_policy, set_non_finite_policy, NonFinitePolicy};
+pub use format::{Locale, ValueFormat};
 pub use id::*;
 pub use kind::*;
 pub use raw::*;
+pub use span::Span;
@@ -1,4 +1,7 @@
+use chrono::{DateTime, Utc};
 use crate::errors::ErrorType;
+use crate::io::types::finite::float;
+use crate::io::{non_finite_policy, Fixed};
 use float_cmp::approx_eq;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
@@ -20,16 +23,97 @@ pub enum RawValue {
     Int8(i8),
     PosInt(u32),
     Int(i32),
-    Float(f32),
+    Float(#[serde(with = "float")] f32),
+    /// Exact decimal for totals accumulated over long periods. See [`Fixed`].
+    Fixed(Fixed),
+    /// Counters which may exceed 32 bits
+    Int64(i64),
+    Float64(#[serde(with = "float")] f64),
+    /// Time-valued channels (ie: time of last backwash)
+    Timestamp(DateTime<Utc>),
 }
 
 impl RawValue {
     pub fn is_numeric(&self) -> bool {
+        !matches!(self, Self::Binary(_) | Self::Timestamp(_))
+    }
+
+    /// Convert numeric value to `f32`
+    ///
+    /// # Returns
+    ///
+    /// `None` if value is [`RawValue::Binary`] or [`RawValue::Timestamp`]
+    pub fn as_float(&self) -> Option<f32> {
+        match *self {
+            Self::Binary(_) | Self::Timestamp(_) => None,
+            Self::PosInt8(x) => Some(x as f32),
+            Self::Int8(x) => Some(x as f32),
+            Self::PosInt(x) => Some(x as f32),
+            Self::Int(x) => Some(x as f32),
+            Self::Float(x) => Some(x),
+            Self::Fixed(x) => Some(x.to_f64() as f32),
+            Self::Int64(x) => Some(x as f32),
+            Self::Float64(x) => Some(x as f32),
+        }
+    }
+
+    /// Check if value is not `NaN` or infinite. Non-float values are always finite.
+    pub fn is_finite(&self) -> bool {
         match self {
-            Self::Binary(_) => false,
+            Self::Float(x) => x.is_finite(),
+            Self::Float64(x) => x.is_finite(),
             _ => true,
         }
     }
+
+    /// Apply [`crate::io::NonFinitePolicy`] to value
+    ///
+    /// # Returns
+    ///
+    /// `None` if value is non-finite and should be treated as absent. Otherwise, value is returned
+    /// unchanged or clamped.
+    pub fn to_finite(&self) -> Option<RawValue> {
+        match *self {
+            Self::Float(x) => non_finite_policy().apply(x).map(Self::Float),
+            Self::Float64(x) => non_finite_policy().apply_f64(x).map(Self::Float64),
+            value => Some(value),
+        }
+    }
+
+    /// Convert numeric value to `f32`, applying [`crate::io::NonFinitePolicy`]
+    ///
+    /// This should be used instead of [`RawValue::as_float()`] wherever values are compared or
+    /// aggregated.
+    ///
+    /// # Returns
+    ///
+    /// `None` if value is [`RawValue::Binary`], or is non-finite and should be treated as absent
+    pub fn finite_float(&self) -> Option<f32> {
+        self.as_float().and_then(|x| non_finite_policy().apply(x))
+    }
+
+    /// Create a value of the same variant as `self` from an `f32`
+    ///
+    /// Integer variants are rounded and saturated to the bounds of the underlying type.
+    ///
+    /// # Returns
+    ///
+    /// `None` if `self` is [`RawValue::Binary`]
+    pub fn with_float(&self, value: f32) -> Option<RawValue> {
+        let rounded = value.round();
+        match self {
+            Self::Binary(_) => None,
+            Self::PosInt8(_) => Some(Self::PosInt8(rounded as u8)),
+            Self::Int8(_) => Some(Self::Int8(rounded as i8)),
+            Self::PosInt(_) => Some(Self::PosInt(rounded as u32)),
+            Self::Int(_) => Some(Self::Int(rounded as i32)),
+            Self::Float(_) => Some(Self::Float(value)),
+            Self::Fixed(_) => Some(Self::Fixed(Fixed::from_f64(value as f64))),
+            Self::Int64(_) => Some(Self::Int64(rounded as i64)),
+            Self::Float64(_) => Some(Self::Float64(value as f64)),
+            Self::Timestamp(_) => None,
+        }
+    }
 }
 
 impl Default for RawValue {
@@ -52,6 +136,10 @@ impl Display for RawValue {
                 Self::PosInt(val) => val.to_string(),
                 Self::Int(val) => val.to_string(),
                 Self::Float(val) => val.to_string(),
+                Self::Fixed(val) => val.to_string(),
+                Self::Int64(val) => val.to_string(),
+                Self::Float64(val) => val.to_string(),
+                Self::Timestamp(val) => val.to_rfc3339(),
             }
         )
     }
@@ -88,6 +176,30 @@ impl TryFrom<f32> for RawValue {
         Ok(RawValue::Float(value))
     }
 }
+impl TryFrom<Fixed> for RawValue {
+    type Error = ErrorType;
+    fn try_from(value: Fixed) -> Result<Self, Self::Error> {
+        Ok(RawValue::Fixed(value))
+    }
+}
+impl TryFrom<i64> for RawValue {
+    type Error = ErrorType;
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        Ok(RawValue::Int64(value))
+    }
+}
+impl TryFrom<f64> for RawValue {
+    type Error = ErrorType;
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        Ok(RawValue::Float64(value))
+    }
+}
+impl TryFrom<DateTime<Utc>> for RawValue {
+    type Error = ErrorType;
+    fn try_from(value: DateTime<Utc>) -> Result<Self, Self::Error> {
+        Ok(RawValue::Timestamp(value))
+    }
+}
 impl TryFrom<bool> for RawValue {
     type Error = ErrorType;
     fn try_from(value: bool) -> Result<Self, Self::Error> {
@@ -103,6 +215,9 @@ impl Add for RawValue {
         match (self, other) {
             (RawValue::Binary(x), RawValue::Binary(y)) => RawValue::Binary(x || y),
             (RawValue::Float(x), RawValue::Float(y)) => RawValue::Float(x + y),
+            (RawValue::Fixed(x), RawValue::Fixed(y)) => RawValue::Fixed(x + y),
+            (RawValue::Int64(x), RawValue::Int64(y)) => RawValue::Int64(x + y),
+            (RawValue::Float64(x), RawValue::Float64(y)) => RawValue::Float64(x + y),
             (RawValue::Int8(x), RawValue::Int8(y)) => RawValue::Int8(x + y),
             (RawValue::PosInt8(x), RawValue::PosInt8(y)) => RawValue::PosInt8(x + y),
             (RawValue::Int(x), RawValue::Int(y)) => RawValue::Int(x + y),
@@ -119,6 +234,11 @@ impl Sub for RawValue {
         // TODO: Catch binary as type
         match (self, other) {
             (RawValue::Float(x), RawValue::Float(y)) => RawValue::Float(x - y),
+            (RawValue::Fixed(x), RawValue::Fixed(y)) => RawValue::Fixed(x - y),
+            (RawValue::Int64(x), RawValue::Int64(y)) => RawValue::Int64(x - y),
+            (RawValue::Float64(x), RawValue::Float64(y)) => RawValue::Float64(x - y),
+            // elapsed time in milliseconds
+            (RawValue::Timestamp(x), RawValue::Timestamp(y)) => RawValue::Int64((x - y).num_milliseconds()),
             (RawValue::Int8(x), RawValue::Int8(y)) => RawValue::Int8(x - y),
             (RawValue::PosInt8(x), RawValue::PosInt8(y)) => RawValue::PosInt8(x - y),
             (RawValue::Int(x), RawValue::Int(y)) => RawValue::Int(x - y),
@@ -135,6 +255,9 @@ impl Mul for RawValue {
         // TODO: Catch binary as type
         match (self, other) {
             (RawValue::Float(x), RawValue::Float(y)) => RawValue::Float(x * y),
+            (RawValue::Fixed(x), RawValue::Fixed(y)) => RawValue::Fixed(x * y),
+            (RawValue::Int64(x), RawValue::Int64(y)) => RawValue::Int64(x * y),
+            (RawValue::Float64(x), RawValue::Float64(y)) => RawValue::Float64(x * y),
             (RawValue::Int8(x), RawValue::Int8(y)) => RawValue::Int8(x * y),
             (RawValue::PosInt8(x), RawValue::PosInt8(y)) => RawValue::PosInt8(x * y),
             (RawValue::Int(x), RawValue::Int(y)) => RawValue::Int(x * y),
@@ -151,6 +274,9 @@ impl Div for RawValue {
         // TODO: Catch binary as type
         match (self, other) {
             (RawValue::Float(x), RawValue::Float(y)) => RawValue::Float(x / y),
+            (RawValue::Fixed(x), RawValue::Fixed(y)) => RawValue::Fixed(x / y),
+            (RawValue::Int64(x), RawValue::Int64(y)) => RawValue::Int64(x / y),
+            (RawValue::Float64(x), RawValue::Float64(y)) => RawValue::Float64(x / y),
             (RawValue::Int8(x), RawValue::Int8(y)) => RawValue::Int8(x / y),
             (RawValue::PosInt8(x), RawValue::PosInt8(y)) => RawValue::PosInt8(x / y),
             (RawValue::Int(x), RawValue::Int(y)) => RawValue::Int(x / y),
@@ -167,6 +293,9 @@ impl Neg for RawValue {
         match self {
             RawValue::Int(x) => RawValue::Int(-x),
             RawValue::Float(x) => RawValue::Float(-x),
+            RawValue::Fixed(x) => RawValue::Fixed(-x),
+            RawValue::Int64(x) => RawValue::Int64(-x),
+            RawValue::Float64(x) => RawValue::Float64(-x),
             RawValue::Int8(x) => RawValue::Int8(-x),
             RawValue::Binary(x) => RawValue::Binary(
                 match x {
@@ -188,6 +317,7 @@ impl Rem for RawValue {
             (RawValue::Int(x), RawValue::Int(y)) => RawValue::Int(x % y),
             (RawValue::Int8(x), RawValue::Int8(y)) => RawValue::Int8(x % y),
             (RawValue::PosInt(x), RawValue::PosInt(y)) => RawValue::PosInt(x % y),
+            (RawValue::Int64(x), RawValue::Int64(y)) => RawValue::Int64(x % y),
             _ => panic!("Cannot calculate remainder for non-integer types"),
         }
     }
@@ -198,6 +328,10 @@ impl PartialEq for RawValue {
         match (self, other) {
             (RawValue::Binary(x), RawValue::Binary(y)) => x == y,
             (RawValue::Float(x), RawValue::Float(y)) => approx_eq!(f32, *x, *y, ulps = 2),
+            (RawValue::Fixed(x), RawValue::Fixed(y)) => x == y,
+            (RawValue::Int64(x), RawValue::Int64(y)) => x == y,
+            (RawValue::Float64(x), RawValue::Float64(y)) => approx_eq!(f64, *x, *y, ulps = 2),
+            (RawValue::Timestamp(x), RawValue::Timestamp(y)) => x == y,
             (RawValue::Int8(x), RawValue::Int8(y)) => x == y,
             (RawValue::PosInt8(x), RawValue::PosInt8(y)) => x == y,
             (RawValue::Int(x), RawValue::Int(y)) => x == y,
@@ -209,6 +343,8 @@ impl PartialEq for RawValue {
 
 #[cfg(test)]
 mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
     use crate::io::RawValue;
 
     #[test]
@@ -229,6 +365,16 @@ mod tests {
         assert_eq!(c, RawValue::Binary(true));
     }
 
+    #[test]
+    fn float_conversion() {
+        assert_eq!(Some(5.0), RawValue::Int(5).as_float());
+        assert_eq!(None, RawValue::Binary(true).as_float());
+
+        assert_eq!(Some(RawValue::PosInt8(255)), RawValue::PosInt8(0).with_float(300.0));
+        assert_eq!(Some(RawValue::Int(3)), RawValue::Int(0).with_float(2.6));
+        assert_eq!(None, RawValue::Binary(true).with_float(1.0));
+    }
+
     #[should_panic]
     #[test]
     fn test_rawvalue_add_mismatched() {
@@ -306,4 +452,23 @@ mod tests {
         let b = RawValue::Float(7.0);
         let _ = a / b;
     }
+
+    #[test]
+    fn wide_variants() {
+        let counter = RawValue::Int64(i32::MAX as i64) + RawValue::Int64(1);
+        assert_eq!(RawValue::Int64(2_147_483_648), counter);
+        assert_eq!(RawValue::Float64(0.3), RawValue::Float64(0.1) + RawValue::Float64(0.2));
+
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let backwash = RawValue::Timestamp(start + Duration::minutes(1));
+        assert!(backwash > RawValue::Timestamp(start));
+        assert_eq!(RawValue::Int64(60_000), backwash - RawValue::Timestamp(start));
+        assert!(!backwash.is_numeric());
+        assert_eq!(None, backwash.as_float());
+
+        for value in [counter, RawValue::Float64(f64::INFINITY), backwash] {
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(value.to_string(), serde_json::from_str::<RawValue>(&json).unwrap().to_string());
+        }
+    }
 }
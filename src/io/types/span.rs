@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::io::RawValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Engineering range of a process value, used to express values as a percent of range
+///
+/// `min` maps to 0% and `max` maps to 100%. `max` may be less than `min` for reverse-acting
+/// instruments (ie: a level sensor measuring distance from the top of a tank). Values outside of
+/// the range map to percentages below 0% or above 100%, and are not clamped.
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::{RawValue, Span};
+///
+/// let level = Span::new(0.0, 2000.0);
+/// assert_eq!(25.0, level.percent(500.0));
+/// assert_eq!(1500.0, level.value(75.0));
+/// assert_eq!(Some(RawValue::Float(50.0)), level.normalize(RawValue::Int(1000)));
+/// ```
+pub struct Span {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Span {
+    /// Constructor for [`Span`]
+    ///
+    /// # Panics
+    ///
+    /// If `min` and `max` are equal, or either is not finite
+    pub fn new(min: f32, max: f32) -> Self {
+        assert!(min.is_finite() && max.is_finite() && min != max, "Span must have a finite, non-zero width");
+        Self { min, max }
+    }
+
+    /// Convert value in engineering units to percent of range
+    pub fn percent(&self, value: f32) -> f32 {
+        (value - self.min) / (self.max - self.min) * 100.0
+    }
+
+    /// Convert percent of range to value in engineering units
+    pub fn value(&self, percent: f32) -> f32 {
+        self.min + percent / 100.0 * (self.max - self.min)
+    }
+
+    /// Convert numeric value to percent of range
+    ///
+    /// # Returns
+    ///
+    /// `RawValue::Float` percentage, or `None` if `value` is not numeric or not finite
+    pub fn normalize(&self, value: RawValue) -> Option<RawValue> {
+        value.finite_float().map(|value| RawValue::Float(self.percent(value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::Span;
+
+    #[test]
+    fn reverse_acting() {
+        let span = Span::new(100.0, 0.0);
+        assert_eq!(0.0, span.percent(100.0));
+        assert_eq!(90.0, span.percent(10.0));
+        assert_eq!(-10.0, span.percent(110.0));
+        assert_eq!(10.0, span.value(90.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_width() {
+        Span::new(1.0, 1.0);
+    }
+}
@@ -4,9 +4,23 @@ extern crate float_cmp;
 extern crate pid as ext_pid;
 
 pub mod action;
+pub mod alarm;
+pub mod console;
+pub mod control;
+pub mod demo;
 pub mod errors;
 pub mod helpers;
+pub mod inspect;
 pub mod io;
 pub mod name;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod recipes;
+pub mod report;
 pub mod settings;
+pub mod sparkline;
+pub mod startup;
 pub mod storage;
+pub mod telemetry;
+pub mod tenant;
+pub mod testkit;
@@ -0,0 +1,285 @@
+//! Python bindings for scripting experiments and analysing logged data
+//!
+//! Enabled by the `python` feature. The extension module is named `sensd` and is built as a shared
+//! library:
+//!
+//! ```text
+//! cargo rustc --release --lib --features python --crate-type cdylib
+//! ```
+//!
+//! Rename the resulting `libsensd.so` to `sensd.so` (or use `maturin`) so that Python can import it.
+//!
+//! Devices are backed by Python callables. Values are passed to and from Python as `bool`, `int`,
+//! or `float`, and timestamps as RFC 3339 strings.
+//!
+//! # Example
+//!
+//! ```text
+//! import random
+//! import sensd
+//!
+//! group = sensd.Group("greenhouse", root="/tmp/sensd", interval=1.0)
+//! group.add_input("temperature", 0, "Temperature", lambda: 20 + random.random())
+//! group.add_output("fan", 1, "Unassigned", lambda value: print("fan:", value))
+//!
+//! errors = group.poll()
+//! print(group.log(0, 10))
+//! group.save()
+//!
+//! # offline analysis of a data root
+//! events = sensd.grep("/tmp/sensd/greenhouse/temperature/log__temperature_0.json", "> 20.5")
+//! ```
+
+// triggered by code generated by `#[pymethods]` and `#[pyfunction]`
+#![allow(clippy::useless_conversion)]
+
+use chrono::Duration;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBool;
+use std::io;
+use std::sync::Arc;
+
+use crate::action::IOCommand;
+use crate::errors::{ErrorType, ReadError};
+use crate::inspect;
+use crate::inspect::Predicate;
+use crate::io::{Device, DeviceGetters, IOEvent, IOKind, IdType, Input, Output, RawValue};
+use crate::name::Name;
+use crate::storage::{Chronicle, Group, Persistent};
+
+/// Python wrapper of [`Group`]
+#[pyclass(name = "Group", unsendable)]
+pub struct PyGroup {
+    group: Group,
+}
+
+#[pymethods]
+impl PyGroup {
+    /// Constructor for [`PyGroup`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of group
+    /// - `root`: Optional data root. Defaults to [`crate::settings::DATA_ROOT`].
+    /// - `interval`: Optional polling interval in seconds
+    #[new]
+    #[pyo3(signature = (name, root=None, interval=None))]
+    fn new(name: String, root: Option<String>, interval: Option<f64>) -> PyResult<Self> {
+        let mut group = match root {
+            Some(root) => Group::with_root(name, root),
+            None => Group::new(name),
+        };
+        if let Some(interval) = interval {
+            group.set_interval(seconds(interval)?);
+        }
+        Ok(Self { group })
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.group.name().clone()
+    }
+
+    /// Add an input whose value is read by calling `read`
+    ///
+    /// `read` takes no arguments and returns a `bool`, `int`, or `float`. An exception raised by
+    /// `read` is treated as a failed read.
+    fn add_input(&mut self, name: String, id: IdType, kind: &str, read: PyObject) -> PyResult<()> {
+        let command = IOCommand::TryInputFn(Arc::new(move || {
+            Python::with_gil(|py| {
+                read.call0(py)
+                    .and_then(|value| to_raw(value.bind(py)))
+                    .map_err(|e| ReadError::Bus { source: io::Error::other(e.to_string()) })
+            })
+        }));
+        let input = Input::new(name, id, parse_kind(kind)?)
+            .set_command(command)
+            .init_log();
+        self.group.push_input(input);
+        Ok(())
+    }
+
+    /// Add an output whose value is written by calling `write` with the new value
+    ///
+    /// An exception raised by `write` is treated as a failed write.
+    fn add_output(&mut self, name: String, id: IdType, kind: &str, write: PyObject) -> PyResult<()> {
+        let command = IOCommand::OutputFn(Arc::new(move |value| {
+            Python::with_gil(|py| {
+                write.call1(py, (to_py(py, value),))
+                    .map(|_| ())
+                    .map_err(|_| ())
+            })
+        }));
+        let output = Output::new(name, id, parse_kind(kind)?)
+            .set_command(command)
+            .init_log();
+        self.group.push_output(output);
+        Ok(())
+    }
+
+    /// Poll all inputs. See [`Group::poll()`].
+    ///
+    /// # Returns
+    ///
+    /// - Messages of any read errors if poll was executed
+    /// - `None` if poll was not due, or group is paused
+    fn poll(&mut self) -> Option<Vec<String>> {
+        let report = self.group.poll();
+        report.executed()
+            .then(|| report.errors().map(|e| e.to_string()).collect())
+    }
+
+    /// Suspend polling. See [`Group::pause()`].
+    #[pyo3(signature = (safe=false))]
+    fn pause(&mut self, safe: bool) {
+        self.group.pause(safe);
+    }
+
+    fn resume(&mut self) {
+        self.group.resume();
+    }
+
+    #[getter]
+    fn paused(&self) -> bool {
+        self.group.is_paused()
+    }
+
+    /// Polling interval in seconds
+    #[getter]
+    fn interval(&self) -> f64 {
+        self.group.interval().num_milliseconds() as f64 / 1000.0
+    }
+
+    #[setter]
+    fn set_interval(&mut self, interval: f64) -> PyResult<()> {
+        self.group.set_interval(seconds(interval)?);
+        Ok(())
+    }
+
+    /// Most recent value of input with `id`
+    fn state(&self, py: Python<'_>, id: IdType) -> PyResult<Option<PyObject>> {
+        let input = self.group.inputs.get(&id)
+            .ok_or_else(|| PyValueError::new_err(format!("No input with id {}", id)))?;
+        let state = *input.lock().unwrap().state();
+        Ok(state.map(|value| to_py(py, value)))
+    }
+
+    /// Logged events of input with `id` as `(timestamp, value)` tuples in chronological order
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: Id of input
+    /// - `count`: Optional maximum number of most recent events to return
+    #[pyo3(signature = (id, count=None))]
+    fn log(&self, py: Python<'_>, id: IdType, count: Option<usize>) -> PyResult<Vec<(String, PyObject)>> {
+        let input = self.group.inputs.get(&id)
+            .ok_or_else(|| PyValueError::new_err(format!("No input with id {}", id)))?;
+        let log = match input.lock().unwrap().log() {
+            Some(log) => log,
+            None => return Ok(Vec::new()),
+        };
+        let log = log.lock().unwrap();
+        let events = inspect::tail(&log, count.unwrap_or(log.len()));
+        Ok(to_tuples(py, &events))
+    }
+
+    /// Save group and device logs to data root
+    fn save(&self) -> PyResult<()> {
+        self.group.save().map_err(to_py_err)
+    }
+
+    /// Operational status as a JSON string. See [`Group::health()`].
+    fn health(&self) -> PyResult<String> {
+        serde_json::to_string(&self.group.health())
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+}
+
+/// Most recent events of a saved log as `(timestamp, value)` tuples. See [`inspect::tail()`].
+#[pyfunction]
+#[pyo3(signature = (path, count=None))]
+fn tail(py: Python<'_>, path: &str, count: Option<usize>) -> PyResult<Vec<(String, PyObject)>> {
+    let log = inspect::read_log(path).map_err(to_py_err)?;
+    let events = inspect::tail(&log, count.unwrap_or(log.len()));
+    Ok(to_tuples(py, &events))
+}
+
+/// Events of a saved log which satisfy a predicate such as `"> 25"`. See [`Predicate`].
+#[pyfunction]
+fn grep(py: Python<'_>, path: &str, predicate: &str) -> PyResult<Vec<(String, PyObject)>> {
+    let predicate: Predicate = predicate.parse()
+        .map_err(|e: crate::errors::InspectError| PyValueError::new_err(e.to_string()))?;
+    let log = inspect::read_log(path).map_err(to_py_err)?;
+    let events = inspect::grep(&log, |event| predicate.matches(event));
+    Ok(to_tuples(py, &events))
+}
+
+/// Summary of a saved log as a JSON string. See [`inspect::stats()`].
+#[pyfunction]
+fn stats(path: &str) -> PyResult<String> {
+    let log = inspect::read_log(path).map_err(to_py_err)?;
+    serde_json::to_string(&inspect::stats(&log))
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
+#[pymodule]
+fn sensd(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGroup>()?;
+    m.add_function(wrap_pyfunction!(tail, m)?)?;
+    m.add_function(wrap_pyfunction!(grep, m)?)?;
+    m.add_function(wrap_pyfunction!(stats, m)?)?;
+    Ok(())
+}
+
+fn to_py_err(e: ErrorType) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+/// Parse name of an [`IOKind`] variant, such as `"Temperature"`
+fn parse_kind(kind: &str) -> PyResult<IOKind> {
+    serde_json::from_value(serde_json::Value::String(kind.to_string()))
+        .map_err(|_| PyValueError::new_err(format!("Unknown kind '{}'", kind)))
+}
+
+fn seconds(seconds: f64) -> PyResult<Duration> {
+    match seconds.is_finite() && seconds > 0.0 {
+        true => Ok(Duration::milliseconds((seconds * 1000.0) as i64)),
+        false => Err(PyValueError::new_err("Interval must be positive")),
+    }
+}
+
+/// Convert a Python `bool`, `int`, or `float` to [`RawValue`]
+fn to_raw(value: &Bound<'_, PyAny>) -> PyResult<RawValue> {
+    if value.is_instance_of::<PyBool>() {
+        return Ok(RawValue::Binary(value.extract()?));
+    }
+    if let Ok(value) = value.extract::<i64>() {
+        return Ok(match i32::try_from(value) {
+            Ok(value) => RawValue::Int(value),
+            Err(_) => RawValue::Int64(value),
+        });
+    }
+    Ok(RawValue::Float(value.extract::<f64>()? as f32))
+}
+
+fn to_py(py: Python<'_>, value: RawValue) -> PyObject {
+    match value {
+        RawValue::Binary(state) => state.into_py(py),
+        RawValue::PosInt8(x) => x.into_py(py),
+        RawValue::Int8(x) => x.into_py(py),
+        RawValue::PosInt(x) => x.into_py(py),
+        RawValue::Int(x) => x.into_py(py),
+        RawValue::Int64(x) => x.into_py(py),
+        RawValue::Float(x) => x.into_py(py),
+        RawValue::Float64(x) => x.into_py(py),
+        RawValue::Fixed(x) => x.to_f64().into_py(py),
+        RawValue::Timestamp(time) => time.to_rfc3339().into_py(py),
+    }
+}
+
+fn to_tuples(py: Python<'_>, events: &[IOEvent]) -> Vec<(String, PyObject)> {
+    events.iter()
+        .map(|event| (event.timestamp.to_rfc3339(), to_py(py, event.value)))
+        .collect()
+}
@@ -0,0 +1,257 @@
+//! Ready-made assemblies of devices and actions for common setups
+//!
+//! Each recipe adds its inputs and outputs to a [`Group`], given an id and command for every
+//! device, and subscribes the actions which control them. Recipes double as documentation of how
+//! devices and actions are wired together, and their source is a starting point for setups which
+//! need more than their parameters allow.
+//!
+//! Devices are added with a log, and are named after the recipe (ie: "reservoir level" and
+//! "reservoir pump" for a recipe named "reservoir").
+//!
+//! # Example
+//!
+//! ```
+//! use sensd::action::IOCommand;
+//! use sensd::io::{DeviceGetters, RawValue};
+//! use sensd::recipes;
+//! use sensd::storage::Group;
+//!
+//! let mut group = Group::new("tent");
+//! let thermostat = recipes::thermostat(
+//!     &mut group,
+//!     "tent",
+//!     (0, IOCommand::Input(|| RawValue::Float(18.0))),
+//!     (1, IOCommand::Output(|_| Ok(()))),
+//!     21.0,
+//!     0.5,
+//! ).unwrap();
+//!
+//! group.poll();
+//! let heater = thermostat.outputs[0].try_lock().unwrap();
+//! assert_eq!(Some(RawValue::Binary(true)), *heater.state());
+//! ```
+use chrono::NaiveTime;
+
+use crate::action::actions::{Photoperiod, Threshold};
+use crate::action::{Action, BoxedAction, IOCommand, Trigger};
+use crate::errors::{ContainerError, ErrorType};
+use crate::helpers::Def;
+use crate::io::{Device, IdType, IOKind, Input, Output, RawValue};
+use crate::storage::Group;
+
+/// Id and low-level command of a device used by a recipe
+pub type Wiring = (IdType, IOCommand);
+
+/// Devices added to a [`Group`] by a recipe
+pub struct Assembly {
+    /// Inputs in the order given to the recipe
+    pub inputs: Vec<Def<Input>>,
+    /// Outputs in the order given to the recipe
+    pub outputs: Vec<Def<Output>>,
+}
+
+/// Keep a reservoir topped up between two levels
+///
+/// The pump is switched on when the level falls below `low`, and off once it reaches `high`.
+///
+/// # Parameters
+///
+/// - `name`: Name of reservoir
+/// - `level`: Level sensor
+/// - `pump`: Fill pump or valve
+/// - `low`: Level at which filling starts
+/// - `high`: Level at which filling stops
+pub fn reservoir_level_keeper(
+    group: &mut Group,
+    name: &str,
+    level: Wiring,
+    pump: Wiring,
+    low: f32,
+    high: f32,
+) -> Result<Assembly, ErrorType> {
+    check_ids(group, &[level.0], &[pump.0])?;
+    let pump = push_output(group, format!("{} pump", name), pump);
+
+    let action = Threshold::new(format!("{} fill", name), RawValue::Float(low), Trigger::LT)
+        .set_hysteresis(high - low)
+        .set_output(pump.clone());
+    let level = push_input(group, format!("{} level", name), IOKind::Unassigned, level, vec![action.into_boxed()]);
+
+    Ok(Assembly { inputs: vec![level], outputs: vec![pump] })
+}
+
+/// Hold pH within a band around a setpoint using an acid and a base dosing pump
+///
+/// The acid pump runs while pH is above `setpoint + deadband`, and the base pump runs while pH is
+/// below `setpoint - deadband`. Neither runs within the band, so that the pumps never fight.
+///
+/// # Parameters
+///
+/// - `name`: Name of solution
+/// - `ph`: pH probe
+/// - `acid`: Pump dosing pH down solution
+/// - `base`: Pump dosing pH up solution
+/// - `setpoint`: Target pH
+/// - `deadband`: Distance from `setpoint` which is tolerated
+pub fn ph_doser_pair(
+    group: &mut Group,
+    name: &str,
+    ph: Wiring,
+    acid: Wiring,
+    base: Wiring,
+    setpoint: f32,
+    deadband: f32,
+) -> Result<Assembly, ErrorType> {
+    check_ids(group, &[ph.0], &[acid.0, base.0])?;
+    let deadband = deadband.abs();
+    let acid = push_output(group, format!("{} acid pump", name), acid);
+    let base = push_output(group, format!("{} base pump", name), base);
+
+    let actions = vec![
+        Threshold::new(format!("{} ph down", name), RawValue::Float(setpoint + deadband), Trigger::GT)
+            .set_output(acid.clone())
+            .into_boxed(),
+        Threshold::new(format!("{} ph up", name), RawValue::Float(setpoint - deadband), Trigger::LT)
+            .set_output(base.clone())
+            .into_boxed(),
+    ];
+    let ph = push_input(group, format!("{} ph", name), IOKind::PH, ph, actions);
+
+    Ok(Assembly { inputs: vec![ph], outputs: vec![acid, base] })
+}
+
+/// Heat to a setpoint without rapidly cycling the heater
+///
+/// The heater is switched on below `setpoint`, and off once temperature reaches
+/// `setpoint + hysteresis`.
+///
+/// # Parameters
+///
+/// - `name`: Name of space being heated
+/// - `sensor`: Temperature sensor
+/// - `heater`: Heater
+/// - `setpoint`: Temperature at which heating starts
+/// - `hysteresis`: Overshoot before heating stops
+pub fn thermostat(
+    group: &mut Group,
+    name: &str,
+    sensor: Wiring,
+    heater: Wiring,
+    setpoint: f32,
+    hysteresis: f32,
+) -> Result<Assembly, ErrorType> {
+    check_ids(group, &[sensor.0], &[heater.0])?;
+    let heater = push_output(group, format!("{} heater", name), heater);
+
+    let action = Threshold::new(format!("{} thermostat", name), RawValue::Float(setpoint), Trigger::LT)
+        .set_hysteresis(hysteresis)
+        .set_output(heater.clone());
+    let sensor = push_input(group, format!("{} temperature", name), IOKind::Temperature, sensor, vec![action.into_boxed()]);
+
+    Ok(Assembly { inputs: vec![sensor], outputs: vec![heater] })
+}
+
+/// Switch grow lights on for a fixed daily period, supplementing daylight
+///
+/// Lights are on from `on` until `off` in UTC, while the light sensor reads below `supplement`.
+/// See [`Photoperiod`].
+///
+/// # Parameters
+///
+/// - `name`: Name of growing area
+/// - `sensor`: Light sensor
+/// - `lights`: Grow lights
+/// - `on`: Time of day in UTC that lights switch on
+/// - `off`: Time of day in UTC that lights switch off
+/// - `supplement`: Light level above which lights are not needed
+pub fn photoperiod_lights(
+    group: &mut Group,
+    name: &str,
+    sensor: Wiring,
+    lights: Wiring,
+    on: NaiveTime,
+    off: NaiveTime,
+    supplement: f32,
+) -> Result<Assembly, ErrorType> {
+    check_ids(group, &[sensor.0], &[lights.0])?;
+    let lights = push_output(group, format!("{} lights", name), lights);
+
+    let action = Photoperiod::new(format!("{} photoperiod", name), on, off)
+        .set_supplement(RawValue::Float(supplement))
+        .set_output(lights.clone());
+    let sensor = push_input(group, format!("{} light", name), IOKind::Light, sensor, vec![action.into_boxed()]);
+
+    Ok(Assembly { inputs: vec![sensor], outputs: vec![lights] })
+}
+
+/// Ensure that no device is added unless every id is free
+fn check_ids(group: &Group, inputs: &[IdType], outputs: &[IdType]) -> Result<(), ContainerError> {
+    let taken = inputs.iter().find(|id| group.inputs.get(id).is_some())
+        .or_else(|| outputs.iter().find(|id| group.outputs.get(id).is_some()));
+    match taken {
+        Some(id) => Err(ContainerError::KeyExists { key: id.to_string() }),
+        None => Ok(()),
+    }
+}
+
+fn push_output(group: &mut Group, name: String, (id, command): Wiring) -> Def<Output> {
+    group.push_output(Output::new(name, id, None).set_command(command).init_log());
+    group.outputs.get(&id).unwrap().clone()
+}
+
+fn push_input(group: &mut Group, name: String, kind: IOKind, (id, command): Wiring, actions: Vec<BoxedAction>) -> Def<Input> {
+    let mut input = Input::new(name, id, kind)
+        .set_command(command)
+        .init_log()
+        .init_publisher();
+    let publisher = input.publisher_mut().as_mut().unwrap();
+    for action in actions {
+        publisher.subscribe(action);
+    }
+    group.push_input(input);
+    group.inputs.get(&id).unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use crate::action::IOCommand;
+    use crate::helpers::Def;
+    use crate::io::{DeviceGetters, Output, RawValue};
+    use crate::recipes::{photoperiod_lights, ph_doser_pair, reservoir_level_keeper};
+    use crate::storage::Group;
+
+    const PUMP: IOCommand = IOCommand::Output(|_| Ok(()));
+
+    fn state(output: &Def<Output>) -> Option<RawValue> {
+        *output.try_lock().unwrap().state()
+    }
+
+    #[test]
+    fn ph_doser_pair_band() {
+        let mut group = Group::new("recipes");
+        let assembly = ph_doser_pair(&mut group, "nutrients", (0, IOCommand::Input(|| RawValue::Float(6.5))),
+                                     (1, PUMP), (2, PUMP), 5.8, 0.2).unwrap();
+        group.poll();
+        assert_eq!(Some(RawValue::Binary(true)), state(&assembly.outputs[0]));
+        assert_eq!(Some(RawValue::Binary(false)), state(&assembly.outputs[1]));
+
+        // ids are checked before any device is added
+        assert!(reservoir_level_keeper(&mut group, "reservoir", (3, IOCommand::Input(|| RawValue::Float(0.0))),
+                                       (2, PUMP), 20.0, 80.0).is_err());
+        assert!(group.inputs.get(&3).is_none());
+    }
+
+    #[test]
+    fn photoperiod_lights_supplement() {
+        let mut group = Group::new("recipes");
+        let midnight = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
+        // period covers the entire day, but daylight is sufficient
+        let assembly = photoperiod_lights(&mut group, "bench", (0, IOCommand::Input(|| RawValue::Float(20_000.0))),
+                                          (1, PUMP), midnight, midnight - chrono::Duration::seconds(1), 10_000.0).unwrap();
+        group.poll();
+        assert_eq!("bench light", assembly.inputs[0].try_lock().unwrap().metadata().name);
+        assert_eq!(Some(RawValue::Binary(false)), state(&assembly.outputs[0]));
+    }
+}
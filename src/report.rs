@@ -0,0 +1,609 @@
+//! Controller performance reports generated from device logs
+//!
+//! A [`Report`] evaluates a single control loop, made up of an input measuring the process
+//! variable and an output driving the actuator, over a time range. The resulting [`LoopReport`]
+//! may be emitted as Markdown or JSON.
+//!
+//! Installations with several groups (ie: one per zone) may merge the health, active alarms, and
+//! daily statistics of every group into a single [`CombinedReport`].
+//!
+//! # Example
+//!
+//! ```
+//! use chrono::{Duration, Utc};
+//! use sensd::io::{DeviceMetadata, IOEvent, RawValue};
+//! use sensd::report::Report;
+//! use sensd::storage::Log;
+//!
+//! let start = Utc::now();
+//! let end = start + Duration::minutes(10);
+//!
+//! let mut ph = Log::with_metadata(&DeviceMetadata::default());
+//! ph.push(IOEvent::with_timestamp(start, RawValue::Float(7.0))).unwrap();
+//! ph.push(IOEvent::with_timestamp(start + Duration::minutes(5), RawValue::Float(7.6))).unwrap();
+//!
+//! let mut pump = Log::with_metadata(&DeviceMetadata::default());
+//! pump.push(IOEvent::with_timestamp(start, RawValue::Binary(true))).unwrap();
+//! pump.push(IOEvent::with_timestamp(start + Duration::minutes(1), RawValue::Binary(false))).unwrap();
+//!
+//! let report = Report::new("ph", 7.5, start, end)
+//!     .set_dose_rate(2.0)     // mL per second
+//!     .generate(&ph, &pump);
+//!
+//! assert_eq!(1, report.actuator.activations);
+//! assert_eq!(Some(120.0), report.actuator.dose);
+//! println!("{}", report.to_markdown());
+//! ```
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::alarm::{Alarm, AlarmSeverity};
+use crate::errors::{ErrorType, FilesystemError};
+use crate::helpers::now;
+use crate::io::{EventQuality, IOEvent, RawValue};
+use crate::storage::{append_record, DailySummary, Group, Health, Log};
+
+/// Parameters of a controller performance report
+pub struct Report {
+    name: String,
+    setpoint: f32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+
+    /// Quantity delivered per second of actuator on-time
+    dose_rate: Option<f32>,
+
+    /// Errors within this band are not counted as setpoint crossings
+    deadband: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Setpoint tracking error statistics. Error is defined as `setpoint - measurement`.
+pub struct TrackingStats {
+    pub samples: usize,
+    pub mean_error: f32,
+    pub mean_abs_error: f32,
+    pub rms_error: f32,
+    pub max_abs_error: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Actuator usage statistics
+pub struct ActuatorStats {
+    /// Number of off-to-on transitions
+    pub activations: usize,
+
+    /// Total time actuator was on, in seconds
+    pub on_seconds: f64,
+
+    /// Fraction of time range actuator was on
+    pub duty_cycle: f64,
+
+    /// Total quantity delivered. Only available when a dose rate is given.
+    pub dose: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Performance of a single control loop over a time range
+pub struct LoopReport {
+    pub name: String,
+    pub setpoint: f32,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+
+    pub tracking: TrackingStats,
+    pub actuator: ActuatorStats,
+
+    /// Number of times process variable crossed the setpoint
+    pub crossings: usize,
+
+    /// Number of full oscillation cycles (two crossings each)
+    pub oscillations: usize,
+}
+
+impl Report {
+    /// # Parameters
+    ///
+    /// - `name`: Name of control loop
+    /// - `setpoint`: Setpoint of process variable
+    /// - `start`: Beginning of time range
+    /// - `end`: End of time range
+    pub fn new<N>(name: N, setpoint: f32, start: DateTime<Utc>, end: DateTime<Utc>) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            setpoint,
+            start,
+            end,
+            dose_rate: None,
+            deadband: 0.0,
+        }
+    }
+
+    /// Builder method to set quantity delivered per second of actuator on-time
+    pub fn set_dose_rate(mut self, rate: f32) -> Self {
+        self.dose_rate = Some(rate);
+        self
+    }
+
+    /// Builder method to ignore setpoint crossings caused by noise
+    ///
+    /// # Parameters
+    ///
+    /// - `deadband`: Absolute error below which process variable is considered at setpoint
+    pub fn set_deadband(mut self, deadband: f32) -> Self {
+        self.deadband = deadband.abs();
+        self
+    }
+
+    /// Evaluate control loop
+    ///
+    /// # Parameters
+    ///
+    /// - `input`: Log of process variable. Non-numeric and non-finite values are ignored.
+    /// - `output`: Log of actuator. Any non-zero or `true` value is considered on.
+    pub fn generate(&self, input: &Log, output: &Log) -> LoopReport {
+        let errors: Vec<f32> = sorted(input)
+            .into_iter()
+            .filter(|event| self.contains(event.timestamp))
+            .filter_map(|event| event.value.finite_float())
+            .map(|measurement| self.setpoint - measurement)
+            .collect();
+
+        let crossings = self.crossings(&errors);
+
+        LoopReport {
+            name: self.name.clone(),
+            setpoint: self.setpoint,
+            start: self.start,
+            end: self.end,
+            tracking: tracking(&errors),
+            actuator: self.actuator(output),
+            crossings,
+            oscillations: crossings / 2,
+        }
+    }
+
+    fn contains(&self, timestamp: DateTime<Utc>) -> bool {
+        self.start <= timestamp && timestamp <= self.end
+    }
+
+    fn crossings(&self, errors: &[f32]) -> usize {
+        let mut crossings = 0;
+        let mut last_sign = None;
+        for error in errors.iter().filter(|error| error.abs() > self.deadband) {
+            let sign = error.is_sign_positive();
+            if last_sign.map(|last| last != sign).unwrap_or(false) {
+                crossings += 1;
+            }
+            last_sign = Some(sign);
+        }
+        crossings
+    }
+
+    fn actuator(&self, output: &Log) -> ActuatorStats {
+        let mut stats = actuator_stats(output, self.start, self.end);
+        stats.dose = self.dose_rate.map(|rate| rate as f64 * stats.on_seconds);
+        stats
+    }
+}
+
+impl LoopReport {
+    /// Render report as a Markdown document
+    pub fn to_markdown(&self) -> String {
+        let dose = match self.actuator.dose {
+            Some(dose) => format!("{:.3}", dose),
+            None => String::from("n/a"),
+        };
+        format!(
+            "# Control Loop Report: {}\n\n\
+            {} to {}\n\n\
+            Setpoint: {}\n\n\
+            ## Setpoint Tracking\n\n\
+            | Metric | Value |\n\
+            |---|---|\n\
+            | Samples | {} |\n\
+            | Mean error | {:.4} |\n\
+            | Mean absolute error | {:.4} |\n\
+            | RMS error | {:.4} |\n\
+            | Max absolute error | {:.4} |\n\
+            | Setpoint crossings | {} |\n\
+            | Oscillations | {} |\n\n\
+            ## Actuator\n\n\
+            | Metric | Value |\n\
+            |---|---|\n\
+            | Activations | {} |\n\
+            | On-time (s) | {:.3} |\n\
+            | Duty cycle | {:.2}% |\n\
+            | Dose | {} |\n",
+            self.name,
+            self.start, self.end,
+            self.setpoint,
+            self.tracking.samples,
+            self.tracking.mean_error,
+            self.tracking.mean_abs_error,
+            self.tracking.rms_error,
+            self.tracking.max_abs_error,
+            self.crossings,
+            self.oscillations,
+            self.actuator.activations,
+            self.actuator.on_seconds,
+            self.actuator.duty_cycle * 100.0,
+            dose,
+        )
+    }
+
+    /// Render report as JSON
+    pub fn to_json(&self) -> Result<String, ErrorType> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() }.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Status of a single group within a [`CombinedReport`]
+pub struct GroupOverview {
+    pub health: Health,
+
+    /// Alarms active when report was generated, ordered by name
+    pub alarms: Vec<Alarm>,
+
+    /// Daily summaries of the reported date
+    pub daily: Vec<DailySummary>,
+}
+
+impl GroupOverview {
+    /// Overview of `group` on `date`
+    ///
+    /// Alarms are taken from the handler set by [`Group::set_alarm_handler()`]. A handler shared by
+    /// several groups is therefore listed under each of them.
+    pub fn new(group: &Group, date: NaiveDate) -> Self {
+        let mut alarms: Vec<Alarm> = group.alarm_handler()
+            .map(|alarms| alarms.lock().unwrap().active().cloned().collect())
+            .unwrap_or_default();
+        alarms.sort_by(|a, b| a.name().cmp(b.name()));
+        let daily = group.daily_summaries().iter()
+            .filter(|summary| summary.date == date)
+            .cloned()
+            .collect();
+        Self { health: group.health(), alarms, daily }
+    }
+
+    /// Short description of operational status
+    pub fn status(&self) -> &'static str {
+        if self.health.faulted {
+            "faulted"
+        } else if self.health.paused {
+            "paused"
+        } else {
+            "ok"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Daily overview of several groups
+///
+/// Multi-zone installations run one [`Group`] per zone. Rather than a report per group, a single
+/// combined report lists the health and active alarms of every group, followed by their daily
+/// statistics (see [`Group::set_daily_stats()`]). Reports may be rendered as Markdown or JSON, or
+/// appended to a JSON lines feed by [`CombinedReport::append()`].
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use sensd::report::CombinedReport;
+/// use sensd::storage::Group;
+///
+/// let north = Group::new("north");
+/// let south = Group::new("south");
+///
+/// let date = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+/// let report = CombinedReport::generate([&north, &south], date);
+///
+/// assert_eq!(2, report.groups.len());
+/// assert_eq!(0, report.active_alarms());
+/// println!("{}", report.to_markdown());
+/// ```
+pub struct CombinedReport {
+    pub date: NaiveDate,
+    pub generated: DateTime<Utc>,
+    pub groups: Vec<GroupOverview>,
+}
+
+impl CombinedReport {
+    /// Merge status of `groups` into a single report
+    ///
+    /// # Parameters
+    ///
+    /// - `groups`: Groups to report, in order of appearance
+    /// - `date`: Local date of daily statistics to include
+    pub fn generate<'a, I>(groups: I, date: NaiveDate) -> Self
+    where
+        I: IntoIterator<Item = &'a Group>,
+    {
+        Self {
+            date,
+            generated: now(),
+            groups: groups.into_iter().map(|group| GroupOverview::new(group, date)).collect(),
+        }
+    }
+
+    /// Number of groups which are faulted or paused
+    pub fn attention(&self) -> usize {
+        self.groups.iter().filter(|group| group.status() != "ok").count()
+    }
+
+    /// Number of active alarms across all groups
+    pub fn active_alarms(&self) -> usize {
+        self.groups.iter().map(|group| group.alarms.len()).sum()
+    }
+
+    /// Highest severity of any active alarm. `None` if no alarms are active.
+    pub fn severity(&self) -> Option<AlarmSeverity> {
+        self.groups.iter()
+            .flat_map(|group| group.alarms.iter().map(Alarm::severity))
+            .max()
+    }
+
+    /// Render report as a Markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut text = format!(
+            "# Combined Report: {}\n\n\
+            Generated {}\n\n\
+            {} group(s), {} needing attention, {} active alarm(s)\n\n\
+            ## Groups\n\n\
+            | Group | Status | Inputs | Outputs | Last poll | Quarantined | Alarms |\n\
+            |---|---|---|---|---|---|---|\n",
+            self.date,
+            self.generated,
+            self.groups.len(), self.attention(), self.active_alarms(),
+        );
+        for group in &self.groups {
+            text.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} |\n",
+                group.health.name,
+                group.status(),
+                group.health.inputs,
+                group.health.outputs,
+                group.health.last_poll,
+                group.health.quarantined.len(),
+                group.alarms.len(),
+            ));
+        }
+
+        if self.active_alarms() > 0 {
+            text.push_str("\n## Active Alarms\n\n\
+                | Group | Alarm | Severity | Raised | Acknowledged | Message |\n\
+                |---|---|---|---|---|---|\n");
+            for group in &self.groups {
+                for alarm in &group.alarms {
+                    text.push_str(&format!(
+                        "| {} | {} | {} | {} | {} | {} |\n",
+                        group.health.name,
+                        alarm.name(),
+                        alarm.severity(),
+                        alarm.raised(),
+                        if alarm.is_acknowledged() { "yes" } else { "no" },
+                        alarm.message(),
+                    ));
+                }
+            }
+        }
+
+        for group in self.groups.iter().filter(|group| !group.daily.is_empty()) {
+            text.push_str(&format!("\n## Daily Statistics: {}\n\n", group.health.name));
+            text.push_str(&daily_markdown(&group.daily));
+        }
+        text
+    }
+
+    /// Render report as JSON
+    pub fn to_json(&self) -> Result<String, ErrorType> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() }.into())
+    }
+
+    /// Append report as a single line to a JSON lines feed at `path`
+    pub fn append<P: AsRef<Path>>(&self, path: P) -> Result<(), ErrorType> {
+        append_record(path.as_ref(), self)
+    }
+}
+
+/// Render daily summaries as a Markdown table with one row per device and day
+///
+/// # See Also
+///
+/// - [`crate::storage::Group::daily_summaries()`]
+pub fn daily_markdown(summaries: &[DailySummary]) -> String {
+    let optional = |value: Option<f64>| match value {
+        Some(value) => format!("{:.3}", value),
+        None => String::from("n/a"),
+    };
+    let mut text = String::from(
+        "| Date | Device | Samples | Min | Max | Mean | Total | On-time (s) | Activations |\n\
+        |---|---|---|---|---|---|---|---|---|\n");
+    for summary in summaries {
+        text.push_str(&format!(
+            "| {} | {} {} | {} | {} | {} | {} | {:.3} | {} | {} |\n",
+            summary.date,
+            summary.direction, summary.id,
+            summary.samples,
+            optional(summary.min.map(f64::from)),
+            optional(summary.max.map(f64::from)),
+            optional(summary.mean),
+            summary.total,
+            optional(summary.on_seconds),
+            summary.activations.map(|count| count.to_string()).unwrap_or_else(|| String::from("n/a")),
+        ));
+    }
+    text
+}
+
+/// Events of log in chronological order
+pub(crate) fn sorted(log: &Log) -> Vec<&IOEvent> {
+    log.iter().map(|(_, event)| event).collect()
+}
+
+/// Actuator usage of `output` between `start` and `end`, excluding dose
+///
+/// Any non-zero or `true` value is considered on. The state before `start` is carried from the last
+/// preceding event.
+pub(crate) fn actuator_stats(output: &Log, start: DateTime<Utc>, end: DateTime<Utc>) -> ActuatorStats {
+    // failed writes did not change state
+    let mut events = sorted(output);
+    events.retain(|event| event.quality != EventQuality::Bad);
+    let contains = |timestamp| start <= timestamp && timestamp <= end;
+
+    // state carried into range from last event before `start`
+    let mut on = events.iter()
+        .take_while(|event| event.timestamp < start)
+        .last()
+        .map(|event| is_on(event.value))
+        .unwrap_or(false);
+    let mut since = start;
+
+    let mut stats = ActuatorStats::default();
+    let mut on_time = chrono::Duration::zero();
+    for event in events.iter().filter(|event| contains(event.timestamp)) {
+        let state = is_on(event.value);
+        if on {
+            on_time = on_time + (event.timestamp - since);
+        } else if state {
+            stats.activations += 1;
+        }
+        on = state;
+        since = event.timestamp;
+    }
+    if on {
+        on_time = on_time + (end - since);
+    }
+
+    let range = (end - start).num_milliseconds() as f64 / 1000.0;
+    stats.on_seconds = on_time.num_milliseconds() as f64 / 1000.0;
+    stats.duty_cycle = if range > 0.0 { stats.on_seconds / range } else { 0.0 };
+    stats
+}
+
+fn is_on(value: RawValue) -> bool {
+    match value {
+        RawValue::Binary(state) => state,
+        _ => value.as_float().map(|value| value != 0.0).unwrap_or(false),
+    }
+}
+
+fn tracking(errors: &[f32]) -> TrackingStats {
+    if errors.is_empty() {
+        return TrackingStats::default();
+    }
+    let count = errors.len() as f32;
+    TrackingStats {
+        samples: errors.len(),
+        mean_error: errors.iter().sum::<f32>() / count,
+        mean_abs_error: errors.iter().map(|error| error.abs()).sum::<f32>() / count,
+        rms_error: (errors.iter().map(|error| error * error).sum::<f32>() / count).sqrt(),
+        max_abs_error: errors.iter().map(|error| error.abs()).fold(0.0, f32::max),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+    use std::fs::remove_dir_all;
+
+    use crate::alarm::{AlarmHandler, AlarmSeverity};
+    use crate::helpers::Def;
+    use crate::io::{Device, DeviceMetadata, IODirection, IOEvent, IOKind, Input, RawValue};
+    use crate::report::{CombinedReport, Report};
+    use crate::storage::{read_records, Chronicle, Group, Log, RootDirectory};
+
+    #[test]
+    fn generate() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let at = |seconds| start + Duration::seconds(seconds);
+
+        let mut input = Log::with_metadata(&DeviceMetadata::default());
+        for (seconds, value) in [(0, 6.0), (10, 8.0), (20, 6.0), (30, 8.0), (40, 7.05)] {
+            input.push(IOEvent::with_timestamp(at(seconds), RawValue::Float(value))).unwrap();
+        }
+
+        let mut output = Log::with_metadata(&DeviceMetadata::default());
+        // on before range begins
+        output.push(IOEvent::with_timestamp(at(-10), RawValue::Binary(true))).unwrap();
+        for (seconds, value) in [(10, false), (20, true), (25, false)] {
+            output.push(IOEvent::with_timestamp(at(seconds), RawValue::Binary(value))).unwrap();
+        }
+
+        let report = Report::new("loop", 7.0, start, at(50))
+            .set_deadband(0.1)
+            .set_dose_rate(0.5)
+            .generate(&input, &output);
+
+        assert_eq!(5, report.tracking.samples);
+        assert_eq!(1.0, report.tracking.max_abs_error);
+        assert_eq!(3, report.crossings);
+        assert_eq!(1, report.oscillations);
+
+        assert_eq!(1, report.actuator.activations);
+        assert_eq!(15.0, report.actuator.on_seconds);
+        assert_eq!(0.3, report.actuator.duty_cycle);
+        assert_eq!(Some(7.5), report.actuator.dose);
+
+        assert!(report.to_markdown().contains("| Oscillations | 1 |"));
+        assert!(report.to_json().unwrap().contains("\"oscillations\": 1"));
+    }
+
+    #[test]
+    fn combined() {
+        let root = std::env::temp_dir().join("sensd_combined_report_test");
+        let _ = remove_dir_all(&root);
+        let date = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+        let midnight = Utc.with_ymd_and_hms(2023, 6, 2, 0, 0, 0).unwrap();
+
+        let mut north = Group::with_root("north", &root).init_dir();
+        north.push_input(Input::new("flow", 0, IOKind::Flow).init_log());
+        north.set_daily_stats(IODirection::In, 0, FixedOffset::east_opt(0).unwrap());
+        {
+            let input = north.inputs.get(&0).unwrap().try_lock().unwrap();
+            let log = input.log().unwrap();
+            let mut log = log.try_lock().unwrap();
+            for hours in [-12, -6] {
+                log.push(IOEvent::with_timestamp(midnight + Duration::hours(hours), RawValue::Float(2.0))).unwrap();
+            }
+        }
+        north.attempt_daily_rollover_at(midnight - Duration::hours(1)).unwrap();
+        north.attempt_daily_rollover_at(midnight + Duration::minutes(1)).unwrap();
+
+        let mut south = Group::with_root("south", &root).init_dir();
+        let alarms = Def::new(AlarmHandler::default());
+        alarms.lock().unwrap().raise("overtemp", AlarmSeverity::Critical, "Too warm");
+        alarms.lock().unwrap().raise("door", AlarmSeverity::Warning, "Door open");
+        south.set_alarm_handler(alarms);
+        south.pause(false);
+
+        let report = CombinedReport::generate([&north, &south], date);
+        assert_eq!(1, report.groups[0].daily.len());
+        assert_eq!("paused", report.groups[1].status());
+        assert_eq!("door", report.groups[1].alarms[0].name());
+        assert_eq!(1, report.attention());
+        assert_eq!(2, report.active_alarms());
+        assert_eq!(Some(AlarmSeverity::Critical), report.severity());
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| south | paused | 0 | 0 |"));
+        assert!(markdown.contains("| south | overtemp | Critical |"));
+        assert!(markdown.contains("## Daily Statistics: north"));
+
+        let feed = root.join("combined.jsonl");
+        report.append(&feed).unwrap();
+        let reports: Vec<CombinedReport> = read_records(&feed).unwrap();
+        assert_eq!(vec![report], reports);
+
+        let _ = remove_dir_all(root);
+    }
+}
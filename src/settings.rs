@@ -1,5 +1,6 @@
 use dotenv::dotenv;
 use std::env::var;
+use crate::console::{self, set_console_format, ConsoleFormat};
 use crate::storage::RootPath;
 
 /// Default values
@@ -40,6 +41,10 @@ impl Settings {
     /// If values do not exist in ".env" file, then default values are used. However, ".env" is not
     /// updated.
     ///
+    /// `CONSOLE_FORMAT` may be "text" or "json". Since notifications are printed throughout the
+    /// library, the format is not stored in [`Settings`], but applied process-wide using
+    /// [`set_console_format()`]. It is therefore shared by all instances within a process.
+    ///
     /// # Returns
     ///
     /// Fully initialized [`Settings`]
@@ -47,6 +52,16 @@ impl Settings {
         dotenv().ok();
         let version = var("VERSION").unwrap_or_else(|_| String::from(VERSION));
         let data_root = var("DATA_ROOT").unwrap_or_else(|_| String::from(DATA_ROOT));
+        let console_format = match var("CONSOLE_FORMAT").map(|format| format.parse()) {
+            Ok(Ok(format)) => format,
+            Ok(Err(e)) => {
+                console::warning(e);
+                ConsoleFormat::default()
+            }
+            Err(_) => ConsoleFormat::default(),
+        };
+
+        set_console_format(console_format);
 
         Settings {
             version,
@@ -0,0 +1,140 @@
+//! Compact text plots of recent device history
+//!
+//! Sparklines convey a trend at a glance where a full chart is unavailable, such as in terminal
+//! output or in notifications. [`sparkline()`] renders a single line of block characters, and
+//! [`braille()`] renders a multi-line plot with higher resolution using braille patterns.
+//!
+//! When there are more values than columns, consecutive values are averaged. Non-finite values are
+//! skipped.
+//!
+//! # Example
+//!
+//! ```
+//! use sensd::sparkline::{braille, sparkline};
+//!
+//! let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+//! assert_eq!("▁▂▃▄▅▆▇█", sparkline(&values, 8));
+//! assert_eq!("▁▃▆█", sparkline(&values, 4));
+//!
+//! assert_eq!("⣀⠤⠒⠉", braille(&values, 4, 1));
+//! ```
+
+use crate::io::IOEvent;
+
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Bits of the dots in each row of a braille character, for the left and right column
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Numeric values of events which may be plotted
+pub fn values(events: &[IOEvent]) -> Vec<f32> {
+    events.iter()
+        .filter_map(|event| event.value.finite_float())
+        .collect()
+}
+
+/// Render values as a single line of block characters
+///
+/// # Parameters
+///
+/// - `values`: Values in chronological order
+/// - `width`: Maximum number of characters
+///
+/// # Returns
+///
+/// One character per value, or per group of averaged values. Empty if there are no finite values.
+pub fn sparkline(values: &[f32], width: usize) -> String {
+    let columns = resample(values, width);
+    let levels = scale(&columns, BLOCKS.len());
+    levels.into_iter().map(|level| BLOCKS[level]).collect()
+}
+
+/// Render values as a plot of braille characters
+///
+/// Each character contains two columns and four rows of dots, so resolution is twice the width
+/// and four times the height.
+///
+/// # Parameters
+///
+/// - `values`: Values in chronological order
+/// - `width`: Maximum number of characters per line
+/// - `height`: Number of lines
+///
+/// # Returns
+///
+/// Lines separated by `'\n'`, with the highest values on the first line. Empty if there are no
+/// finite values.
+pub fn braille(values: &[f32], width: usize, height: usize) -> String {
+    let columns = resample(values, width * 2);
+    if columns.is_empty() || height == 0 {
+        return String::new();
+    }
+    let rows = height * 4;
+    let levels = scale(&columns, rows);
+
+    let chars = columns.len().div_ceil(2);
+    let mut cells = vec![vec![0u32; chars]; height];
+    for (column, level) in levels.into_iter().enumerate() {
+        // rows are counted from the top
+        let row = rows - 1 - level;
+        cells[row / 4][column / 2] |= BRAILLE_DOTS[row % 4][column % 2];
+    }
+
+    cells.into_iter()
+        .map(|line| line.into_iter()
+            .map(|bits| char::from_u32(0x2800 + bits).unwrap())
+            .collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Average consecutive values so that there are at most `width` columns
+fn resample(values: &[f32], width: usize) -> Vec<f32> {
+    let values: Vec<f32> = values.iter().copied().filter(|value| value.is_finite()).collect();
+    if values.len() <= width {
+        return values;
+    }
+    (0..width)
+        .map(|column| {
+            let start = column * values.len() / width;
+            let end = (column + 1) * values.len() / width;
+            let bucket = &values[start..end];
+            bucket.iter().sum::<f32>() / bucket.len() as f32
+        })
+        .collect()
+}
+
+/// Map values onto `0..levels`. Constant values are placed in the middle.
+fn scale(values: &[f32], levels: usize) -> Vec<usize> {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let range = max - min;
+    values.iter()
+        .map(|value| match range > 0.0 {
+            true => (((value - min) / range) * (levels - 1) as f32).round() as usize,
+            false => levels / 2,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sparkline::{braille, sparkline};
+
+    #[test]
+    fn sparkline_edge_cases() {
+        assert_eq!("", sparkline(&[], 10));
+        assert_eq!("▅▅▅", sparkline(&[3.0, 3.0, 3.0], 10));
+        assert_eq!("▁█", sparkline(&[0.0, f32::NAN, 1.0], 10));
+    }
+
+    #[test]
+    fn braille_height() {
+        let plot = braille(&[0.0, 1.0], 1, 2);
+        let lines: Vec<&str> = plot.lines().collect();
+        assert_eq!(2, lines.len());
+        // high value is in the right column of the top line
+        assert_eq!("⠈", lines[0]);
+        assert_eq!("⡀", lines[1]);
+    }
+}
@@ -0,0 +1,277 @@
+//! Startup banner and compatibility checks
+//!
+//! A daemon should refuse to start when its data or configuration cannot be served by the running
+//! build, rather than failing obscurely once polling has begun. [`Startup`] verifies that:
+//!
+//! - group directories were written in a data format this build can read (see
+//!   [`FORMAT_VERSION`]), and that their state and snapshot can be parsed
+//! - required crate features (ie: "python") are compiled in
+//! - required device profiles (see [`crate::io::DeviceProfile::register()`]) are registered
+//!
+//! Every problem is collected so that a single report lists all missing capabilities.
+//!
+//! # Example
+//!
+//! ```
+//! use sensd::startup::Startup;
+//! use sensd::storage::Group;
+//!
+//! let group = Group::new("greenhouse");
+//!
+//! let startup = Startup::new()
+//!     .set_banner("greenhouse controller")
+//!     .check_group(&group)
+//!     .require_feature("no-such-feature");
+//!
+//! let problems = startup.check();
+//! assert_eq!(1, problems.len());
+//! assert!(startup.run().is_err());
+//! ```
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use crate::console;
+use crate::errors::{ErrorType, FilesystemError, StartupError};
+use crate::io::get_profile;
+use crate::storage::{Directory, Group, GroupState, Snapshot, FORMAT_VERSION, GROUP_STATE_FILENAME, SNAPSHOT_FILENAME};
+
+/// Optional crate features, and whether each is compiled into this build
+pub const FEATURES: [(&str, bool); 3] = [
+    ("python", cfg!(feature = "python")),
+    ("indexmap", cfg!(feature = "indexmap")),
+    ("async", cfg!(feature = "async")),
+];
+
+/// Names of optional features compiled into this build
+pub fn compiled_features() -> Vec<&'static str> {
+    FEATURES.iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Default banner naming crate version, data format, and compiled features
+pub fn banner() -> String {
+    let features = compiled_features();
+    let features = match features.is_empty() {
+        true => String::from("none"),
+        false => features.join(", "),
+    };
+    format!("sensd {} (data format {}, features: {})", env!("CARGO_PKG_VERSION"), FORMAT_VERSION, features)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Single capability that this build lacks
+pub enum Incompatibility {
+    /// Required crate feature is not compiled in
+    MissingFeature { feature: String },
+
+    /// Required device profile has not been registered
+    MissingProfile { name: String },
+
+    /// File was written in a newer data format
+    UnsupportedFormat { path: PathBuf, found: u32 },
+
+    /// File could not be read or parsed
+    Unreadable { path: PathBuf, reason: String },
+}
+
+impl Display for Incompatibility {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Incompatibility::MissingFeature { feature } =>
+                write!(f, "feature \"{}\" is not compiled in", feature),
+            Incompatibility::MissingProfile { name } =>
+                write!(f, "device profile \"{}\" is not registered", name),
+            Incompatibility::UnsupportedFormat { path, found } =>
+                write!(f, "{} was written in data format {}, but this build reads up to format {}",
+                       path.display(), found, FORMAT_VERSION),
+            Incompatibility::Unreadable { path, reason } =>
+                write!(f, "{} could not be read: {}", path.display(), reason),
+        }
+    }
+}
+
+/// Checks performed before a daemon begins polling
+///
+/// Built with builder methods, then evaluated by [`Startup::run()`], which prints the banner
+/// through [`crate::console`] and fails if any check fails.
+pub struct Startup {
+    /// `None` if banner is hidden
+    banner: Option<String>,
+    features: Vec<String>,
+    profiles: Vec<String>,
+    dirs: Vec<PathBuf>,
+}
+
+impl Default for Startup {
+    fn default() -> Self {
+        Self {
+            banner: Some(banner()),
+            features: Vec::new(),
+            profiles: Vec::new(),
+            dirs: Vec::new(),
+        }
+    }
+}
+
+impl Startup {
+    /// Constructor for checks with the default banner, and nothing to verify
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to replace banner
+    ///
+    /// The default banner (see [`banner()`]) is useful in bug reports, and may be included in
+    /// `banner`.
+    pub fn set_banner<B>(mut self, banner: B) -> Self
+    where
+        B: Into<String>
+    {
+        self.banner = Some(banner.into());
+        self
+    }
+
+    /// Builder method to start without printing a banner
+    pub fn hide_banner(mut self) -> Self {
+        self.banner = None;
+        self
+    }
+
+    /// Builder method to require a crate feature. See [`FEATURES`].
+    pub fn require_feature<F>(mut self, feature: F) -> Self
+    where
+        F: Into<String>
+    {
+        self.features.push(feature.into());
+        self
+    }
+
+    /// Builder method to require a registered device profile
+    pub fn require_profile<N>(mut self, name: N) -> Self
+    where
+        N: Into<String>
+    {
+        self.profiles.push(name.into());
+        self
+    }
+
+    /// Builder method to verify data within a group directory
+    ///
+    /// Directories which do not exist yet are always compatible.
+    pub fn check_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Builder method to verify data within directory of `group`
+    pub fn check_group(self, group: &Group) -> Self {
+        self.check_dir(group.full_path())
+    }
+
+    pub fn banner(&self) -> Option<&String> {
+        self.banner.as_ref()
+    }
+
+    /// Evaluate every check
+    ///
+    /// # Returns
+    ///
+    /// Every incompatibility found. Empty if this build can serve all data and configuration.
+    pub fn check(&self) -> Vec<Incompatibility> {
+        let mut problems = Vec::new();
+        for feature in self.features.iter() {
+            if !compiled_features().contains(&feature.as_str()) {
+                problems.push(Incompatibility::MissingFeature { feature: feature.clone() });
+            }
+        }
+        for name in self.profiles.iter() {
+            if get_profile(name).is_err() {
+                problems.push(Incompatibility::MissingProfile { name: name.clone() });
+            }
+        }
+        for dir in self.dirs.iter() {
+            let results = [
+                (dir.join(GROUP_STATE_FILENAME), GroupState::load(dir).map(|_| ())),
+                (dir.join(SNAPSHOT_FILENAME), Snapshot::load(dir).map(|_| ())),
+            ];
+            for (path, result) in results {
+                if let Err(e) = result {
+                    problems.push(match e.downcast_ref::<FilesystemError>() {
+                        Some(FilesystemError::UnsupportedFormat { found, .. }) =>
+                            Incompatibility::UnsupportedFormat { path, found: *found },
+                        _ => Incompatibility::Unreadable { path, reason: e.to_string() },
+                    });
+                }
+            }
+        }
+        problems
+    }
+
+    /// Print banner, then evaluate every check
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` if no incompatibilities were found
+    /// - `Err` with [`StartupError::Incompatible`] listing every incompatibility
+    pub fn run(&self) -> Result<(), ErrorType> {
+        if let Some(banner) = &self.banner {
+            console::info(banner);
+        }
+        let problems = self.check();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        let report = problems.iter()
+            .map(|problem| format!("- {}", problem))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Err(Box::new(StartupError::Incompatible { count: problems.len(), report }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    use crate::io::{DeviceProfile, IOKind};
+    use crate::startup::{Incompatibility, Startup};
+    use crate::storage::{Group, Persistent, RootDirectory, FORMAT_VERSION, GROUP_STATE_FILENAME, SNAPSHOT_FILENAME};
+
+    #[test]
+    fn check() {
+        let root = std::env::temp_dir().join("sensd_startup_test");
+        let _ = remove_dir_all(&root);
+        let group = Group::with_root("current", &root).init_dir();
+        group.save().unwrap();
+        group.save_snapshot().unwrap();
+        DeviceProfile::new("startup_probe", IOKind::Temperature).register();
+
+        let startup = Startup::new()
+            .hide_banner()
+            .check_group(&group)
+            .require_profile("startup_probe");
+        assert!(startup.run().is_ok());
+
+        // written by a newer build
+        let newer = root.join("newer");
+        create_dir_all(&newer).unwrap();
+        write(newer.join(GROUP_STATE_FILENAME), format!(r#"{{"format": {}, "renamed": true}}"#, FORMAT_VERSION + 1)).unwrap();
+        write(newer.join(SNAPSHOT_FILENAME), "{").unwrap();
+
+        let problems = startup
+            .check_dir(&newer)
+            .require_feature("missing")
+            .require_profile("missing")
+            .check();
+        assert_eq!(vec![
+            Incompatibility::MissingFeature { feature: String::from("missing") },
+            Incompatibility::MissingProfile { name: String::from("missing") },
+            Incompatibility::UnsupportedFormat { path: newer.join(GROUP_STATE_FILENAME), found: FORMAT_VERSION + 1 },
+        ], problems[..3]);
+        assert!(matches!(problems[3], Incompatibility::Unreadable { .. }));
+
+        let _ = remove_dir_all(root);
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ContainerError, ErrorType};
+use crate::inspect::read_log;
+use crate::io::{DeviceMetadata, EventKind, IdType, IODirection, IOEvent};
+use crate::storage::daily::read_summaries;
+use crate::storage::integrity::find_logs;
+use crate::storage::mode::read_history;
+use crate::storage::{read_records, DailySummary, GroupState, Log, MetadataChange, ModeChange, METADATA_HISTORY_FILENAME};
+
+/// Read-only access to a data root written by [`crate::storage::Group`]
+///
+/// An archive is opened without constructing devices, actions, or commands, so that offline
+/// analysis tools and `sensd logs` may query logs, daily summaries, audit trails, and annotations
+/// of any group that has been saved under a root. Every subdirectory of the root is treated as a
+/// group directory. Nothing is ever written.
+///
+/// Files are read on every call, so an archive opened while groups are running sees data as it is
+/// saved. Groups created after opening require a new archive.
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::{Device, Input, IOKind};
+/// use sensd::storage::{Archive, Group, Persistent, RootDirectory};
+///
+/// let root = std::env::temp_dir().join("sensd_archive_example");
+/// let mut group = Group::with_root("greenhouse", &root).init_dir();
+/// group.push_input(Input::new("temp", 0, IOKind::Temperature).init_log());
+/// group.save().unwrap();
+///
+/// let archive = Archive::open(&root).unwrap();
+/// assert_eq!(vec!["greenhouse"], archive.groups().collect::<Vec<_>>());
+/// assert_eq!("temp", archive.logs("greenhouse").unwrap()[0].name());
+/// # std::fs::remove_dir_all(root).unwrap();
+/// ```
+pub struct Archive {
+    root: PathBuf,
+
+    /// Directory of each group, by name
+    groups: BTreeMap<String, PathBuf>,
+}
+
+impl Archive {
+    /// Open an existing data root
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `root` cannot be read
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self, ErrorType> {
+        let root = root.as_ref().to_path_buf();
+        let mut groups = BTreeMap::new();
+        for entry in read_dir(&root)? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+                groups.insert(name.to_string(), path.clone());
+            }
+        }
+        Ok(Self { root, groups })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Names of all groups, in alphabetical order
+    pub fn groups(&self) -> impl Iterator<Item = &str> {
+        self.groups.keys().map(String::as_str)
+    }
+
+    /// Directory of a group
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ContainerError::KeyMissing`] if no group named `group` exists under root
+    pub fn group_dir(&self, group: &str) -> Result<&Path, ErrorType> {
+        self.groups.get(group)
+            .map(PathBuf::as_path)
+            .ok_or_else(|| ContainerError::KeyMissing { key: group.to_string() }.into())
+    }
+
+    /// All device logs of a group, sorted by path
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if group does not exist, or if any log cannot be read or parsed
+    pub fn logs(&self, group: &str) -> Result<Vec<Log>, ErrorType> {
+        let mut paths = Vec::new();
+        find_logs(self.group_dir(group)?, &mut paths)?;
+        paths.sort();
+        paths.iter().map(read_log).collect()
+    }
+
+    /// Log of a single device
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(None)` if no log was saved for device
+    /// - `Ok(Some)` with saved log
+    /// - `Err` if group does not exist, or if any log cannot be read or parsed
+    pub fn log(&self, group: &str, direction: IODirection, id: IdType) -> Result<Option<Log>, ErrorType> {
+        Ok(self.logs(group)?
+            .into_iter()
+            .find(|log| log.metadata()
+                .is_some_and(|metadata| metadata.direction == direction && metadata.id == id)))
+    }
+
+    /// Daily summaries of a group, in order of generation
+    ///
+    /// See [`crate::storage::Group::set_daily_stats()`]
+    pub fn summaries(&self, group: &str) -> Result<Vec<DailySummary>, ErrorType> {
+        read_summaries(self.group_dir(group)?)
+    }
+
+    /// Audit trail of device metadata changes of a group
+    ///
+    /// See [`crate::storage::Group::update_devices()`]
+    pub fn metadata_history(&self, group: &str) -> Result<Vec<MetadataChange>, ErrorType> {
+        read_records(&self.group_dir(group)?.join(METADATA_HISTORY_FILENAME))
+    }
+
+    /// Mode changes of a group, in chronological order
+    ///
+    /// See [`crate::storage::Group::set_mode()`]
+    pub fn mode_history(&self, group: &str) -> Result<Vec<ModeChange>, ErrorType> {
+        read_history(self.group_dir(group)?)
+    }
+
+    /// Annotations of every device of a group, in chronological order
+    ///
+    /// # Returns
+    ///
+    /// Metadata of annotated device alongside each [`EventKind::Annotation`] event
+    pub fn annotations(&self, group: &str) -> Result<Vec<(DeviceMetadata, IOEvent)>, ErrorType> {
+        let mut annotations = Vec::new();
+        for log in self.logs(group)? {
+            let metadata = log.metadata().cloned().unwrap_or_default();
+            annotations.extend(log.iter()
+                .filter(|(_, event)| event.kind == EventKind::Annotation)
+                .map(|(_, event)| (metadata.clone(), event.clone())));
+        }
+        annotations.sort_by_key(|(_, event)| event.timestamp);
+        Ok(annotations)
+    }
+
+    /// Runtime state of a group when it was last saved
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if group was saved without state
+    pub fn state(&self, group: &str) -> Result<Option<GroupState>, ErrorType> {
+        GroupState::load(self.group_dir(group)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use std::fs::remove_dir_all;
+
+    use crate::errors::ContainerError;
+    use crate::io::{Device, EventKind, Input, IODirection, IOEvent, IOKind, MetadataPatch, RawValue};
+    use crate::storage::{Archive, Chronicle, Group, Mode, Persistent, RootDirectory};
+
+    #[test]
+    fn open() {
+        let root = std::env::temp_dir().join("sensd_archive_test");
+        let _ = remove_dir_all(&root);
+        let ph = Input::new("ph", 0, IOKind::PH).init_log();
+        {
+            let log = ph.log().unwrap();
+            let mut log = log.try_lock().unwrap();
+            let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+            log.push(IOEvent::with_timestamp(start, RawValue::Float(6.5))).unwrap();
+            log.push(IOEvent::with_timestamp(start + Duration::minutes(1), RawValue::Float(0.0)).set_kind(EventKind::Annotation)).unwrap();
+        }
+        let mut group = Group::with_root("tank", &root).init_dir();
+        group
+            .push_input(ph)
+            .push_input(Input::new("temp", 1, IOKind::Temperature).init_log())
+            .add_mode(Mode::new("night"));
+        group.set_mode("night", "operator").unwrap();
+        group.update_devices(|metadata| metadata.id == 1, &MetadataPatch::default().add_tag("water")).unwrap();
+        group.save().unwrap();
+
+        let archive = Archive::open(&root).unwrap();
+        assert_eq!(vec!["tank"], archive.groups().collect::<Vec<_>>());
+        assert_eq!(2, archive.logs("tank").unwrap().len());
+        assert_eq!("temp", archive.log("tank", IODirection::In, 1).unwrap().unwrap().name());
+        assert!(archive.log("tank", IODirection::Out, 1).unwrap().is_none());
+        assert_eq!(1, archive.mode_history("tank").unwrap().len());
+        assert_eq!(1, archive.metadata_history("tank").unwrap().len());
+        assert!(archive.summaries("tank").unwrap().is_empty());
+        assert!(archive.state("tank").unwrap().is_some());
+
+        let annotations = archive.annotations("tank").unwrap();
+        assert_eq!(1, annotations.len());
+        assert_eq!("ph", annotations[0].0.name);
+
+        let error = archive.summaries("missing").unwrap_err();
+        assert!(matches!(error.downcast_ref::<ContainerError>(), Some(ContainerError::KeyMissing { .. })));
+        assert!(Archive::open(root.join("missing")).is_err());
+
+        let _ = remove_dir_all(root);
+    }
+}
@@ -0,0 +1,303 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::errors::ErrorType;
+use crate::io::{EventKind, EventQuality, IdType, IODirection, RunningStats};
+use crate::report::actuator_stats;
+use crate::storage::{append_record, read_records, Log};
+
+/// Filename of daily summaries within group directory
+pub const DAILY_FILENAME: &str = "daily.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Statistics of a single device over one local calendar day
+///
+/// Summaries are generated by [`crate::storage::Group::poll()`] once local midnight has passed for
+/// devices registered with [`crate::storage::Group::set_daily_stats()`], and may be generated for
+/// any day directly from a log by [`DailySummary::from_log()`].
+///
+/// # Example
+///
+/// ```
+/// use chrono::{FixedOffset, NaiveDate, TimeZone};
+/// use sensd::io::{DeviceMetadata, IODirection, IOEvent, RawValue};
+/// use sensd::storage::{DailySummary, Log};
+///
+/// let offset = FixedOffset::west_opt(5 * 3600).unwrap();
+/// let date = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+/// let at = |hour| offset.from_local_datetime(&date.and_hms_opt(hour, 0, 0).unwrap()).unwrap().into();
+///
+/// let mut log = Log::with_metadata(&DeviceMetadata::default());
+/// log.push(IOEvent::with_timestamp(at(0), RawValue::Float(2.0))).unwrap();
+/// log.push(IOEvent::with_timestamp(at(12), RawValue::Float(4.0))).unwrap();
+///
+/// let summary = DailySummary::from_log(&log, IODirection::In, 0, date, offset);
+/// assert_eq!(Some(3.0), summary.mean);
+/// // 2 for 12 hours, then 4 for 12 hours
+/// assert_eq!(259_200.0, summary.total);
+/// ```
+pub struct DailySummary {
+    pub id: IdType,
+    pub direction: IODirection,
+
+    /// Local date
+    pub date: NaiveDate,
+
+    /// Offset of local time from UTC in seconds
+    pub utc_offset: i32,
+
+    /// Number of numeric values read or written
+    pub samples: usize,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub mean: Option<f64>,
+
+    /// Integral of values over the day, in value-seconds (ie: a flow in L/s totals to liters)
+    ///
+    /// Each value is held until the next. The last value of the previous day is carried into the
+    /// beginning of the day.
+    pub total: f64,
+
+    /// Seconds an output was on. `None` for inputs.
+    pub on_seconds: Option<f64>,
+
+    /// Number of times an output was switched on. `None` for inputs.
+    pub activations: Option<usize>,
+}
+
+impl DailySummary {
+    /// Summarize a day of a device log
+    ///
+    /// Readings of inputs with [`EventQuality::Bad`] are ignored, as are events other than reads
+    /// or writes. Days that have been trimmed from the log (see [`Log::set_capacity()`]) are
+    /// incomplete.
+    ///
+    /// # Parameters
+    ///
+    /// - `log`: Log of device
+    /// - `direction`: Direction of device
+    /// - `id`: Id of device
+    /// - `date`: Local date to summarize
+    /// - `offset`: Offset of local time from UTC
+    pub fn from_log(log: &Log, direction: IODirection, id: IdType, date: NaiveDate, offset: FixedOffset) -> Self {
+        let (start, end) = day_bounds(date, offset);
+        let kind = match direction {
+            IODirection::In => EventKind::SensorRead,
+            IODirection::Out => EventKind::OutputWrite,
+        };
+
+        let values: Vec<(DateTime<Utc>, f32)> = log.iter()
+            .map(|(_, event)| event)
+            .filter(|event| event.kind == kind && event.quality != EventQuality::Bad)
+            .filter_map(|event| Some((event.timestamp, event.value.finite_float()?)))
+            .collect();
+
+        let mut stats = RunningStats::new(start);
+        let mut total = 0.0;
+        let mut held = values.iter()
+            .take_while(|(timestamp, _)| *timestamp < start)
+            .last()
+            .map(|(_, value)| *value);
+        let mut since = start;
+        for (timestamp, value) in values.iter().filter(|(timestamp, _)| start <= *timestamp && *timestamp < end) {
+            stats.push(*value);
+            if let Some(held) = held {
+                total += held as f64 * seconds(*timestamp - since);
+            }
+            held = Some(*value);
+            since = *timestamp;
+        }
+        if let Some(held) = held {
+            total += held as f64 * seconds(end - since);
+        }
+
+        let actuator = (direction == IODirection::Out).then(|| actuator_stats(log, start, end));
+
+        Self {
+            id,
+            direction,
+            date,
+            utc_offset: offset.local_minus_utc(),
+            samples: stats.count(),
+            min: stats.min(),
+            max: stats.max(),
+            mean: stats.mean(),
+            total,
+            on_seconds: actuator.as_ref().map(|stats| stats.on_seconds),
+            activations: actuator.map(|stats| stats.activations),
+        }
+    }
+}
+
+/// Beginning and end of local `date` in UTC
+fn day_bounds(date: NaiveDate, offset: FixedOffset) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = offset.from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .unwrap()
+        .with_timezone(&Utc);
+    (start, start + Duration::days(1))
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.num_milliseconds() as f64 / 1000.0
+}
+
+/// Device whose statistics roll over daily
+struct Tracked {
+    direction: IODirection,
+    id: IdType,
+    offset: FixedOffset,
+
+    /// Local date currently being accumulated. `None` until first check.
+    day: Option<NaiveDate>,
+}
+
+#[derive(Default)]
+/// Tracks local date of each registered device, and retains generated summaries
+pub(crate) struct DailyRollover {
+    tracked: Vec<Tracked>,
+    summaries: Vec<DailySummary>,
+}
+
+impl DailyRollover {
+    /// Register device, replacing any previous registration
+    pub fn track(&mut self, direction: IODirection, id: IdType, offset: FixedOffset) {
+        self.tracked.retain(|tracked| !(tracked.direction == direction && tracked.id == id));
+        let day = self.resume_day(direction, id);
+        self.tracked.push(Tracked { direction, id, offset, day });
+    }
+
+    /// Days which have ended since the previous check
+    ///
+    /// The first check of a device only stores the current local date, unless summaries of the
+    /// device have been restored, in which case days missed while not running are also due.
+    ///
+    /// # Returns
+    ///
+    /// Direction, id, local date, and offset of each day to summarize. Days of each device are in
+    /// chronological order.
+    pub fn due(&mut self, now: DateTime<Utc>) -> Vec<(IODirection, IdType, NaiveDate, FixedOffset)> {
+        let mut due = Vec::new();
+        for tracked in self.tracked.iter_mut() {
+            let today = now.with_timezone(&tracked.offset).date_naive();
+            let mut day = *tracked.day.get_or_insert(today);
+            while day < today {
+                due.push((tracked.direction, tracked.id, day, tracked.offset));
+                day = day.succ_opt().unwrap();
+            }
+            tracked.day = Some(today);
+        }
+        due
+    }
+
+    pub fn push(&mut self, summary: DailySummary) {
+        self.summaries.push(summary);
+    }
+
+    pub fn summaries(&self) -> &[DailySummary] {
+        &self.summaries
+    }
+
+    /// Replace summaries, and resume registered devices from the day after their last summary
+    pub fn restore(&mut self, summaries: Vec<DailySummary>) {
+        self.summaries = summaries;
+        let resumed: Vec<_> = self.tracked.iter()
+            .map(|tracked| self.resume_day(tracked.direction, tracked.id))
+            .collect();
+        for (tracked, day) in self.tracked.iter_mut().zip(resumed) {
+            if day.is_some() {
+                tracked.day = day;
+            }
+        }
+    }
+
+    /// Day following the last summary of a device
+    fn resume_day(&self, direction: IODirection, id: IdType) -> Option<NaiveDate> {
+        self.summaries.iter()
+            .filter(|summary| summary.direction == direction && summary.id == id)
+            .map(|summary| summary.date)
+            .max()
+            .and_then(|date| date.succ_opt())
+    }
+}
+
+/// Append summary to daily summaries in `dir`
+///
+/// Summaries are stored as newline delimited JSON.
+pub(crate) fn append_summary(dir: &Path, summary: &DailySummary) -> Result<(), ErrorType> {
+    append_record(&dir.join(DAILY_FILENAME), summary)
+}
+
+/// Read summaries written by [`append_summary()`] from `dir`
+pub(crate) fn read_summaries(dir: &Path) -> Result<Vec<DailySummary>, ErrorType> {
+    read_records(&dir.join(DAILY_FILENAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+
+    use crate::io::{DeviceMetadata, EventKind, IODirection, IOEvent, RawValue};
+    use crate::storage::daily::{DailyRollover, DailySummary};
+    use crate::storage::Log;
+
+    #[test]
+    fn from_log() {
+        let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+        let date = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+        // local midnight
+        let start = Utc.with_ymd_and_hms(2023, 5, 31, 22, 0, 0).unwrap();
+        let at = |hours| start + Duration::hours(hours);
+
+        let mut log = Log::with_metadata(&DeviceMetadata::default());
+        for (hours, state) in [(-1, true), (6, false), (12, true), (18, false), (25, true)] {
+            let event = IOEvent::with_timestamp(at(hours), RawValue::Binary(state))
+                .set_kind(EventKind::OutputWrite);
+            log.push(event).unwrap();
+        }
+
+        let summary = DailySummary::from_log(&log, IODirection::Out, 1, date, offset);
+        assert_eq!(7200, summary.utc_offset);
+        assert_eq!(0, summary.samples);
+        assert_eq!(Some(12.0 * 3600.0), summary.on_seconds);
+        assert_eq!(Some(1), summary.activations);
+
+        // events of other days and kinds are excluded
+        let mut log = Log::with_metadata(&DeviceMetadata::default());
+        for (hours, value) in [(-1, 100.0), (6, 1.0), (18, 3.0), (25, 100.0)] {
+            log.push(IOEvent::with_timestamp(at(hours), RawValue::Float(value))).unwrap();
+        }
+        log.push(IOEvent::with_timestamp(at(12), RawValue::Float(50.0)).set_kind(EventKind::Annotation)).unwrap();
+
+        let summary = DailySummary::from_log(&log, IODirection::In, 0, date, offset);
+        assert_eq!(2, summary.samples);
+        assert_eq!(Some(1.0), summary.min);
+        assert_eq!(Some(3.0), summary.max);
+        assert_eq!(None, summary.on_seconds);
+        assert_eq!(3600.0 * (6.0 * 100.0 + 12.0 * 1.0 + 6.0 * 3.0), summary.total);
+    }
+
+    #[test]
+    fn due() {
+        let offset = FixedOffset::west_opt(5 * 3600).unwrap();
+        let mut rollover = DailyRollover::default();
+        rollover.track(IODirection::In, 0, offset);
+
+        // 23:00 local on first check
+        let now = Utc.with_ymd_and_hms(2023, 6, 2, 4, 0, 0).unwrap();
+        assert!(rollover.due(now).is_empty());
+        assert!(rollover.due(now + Duration::minutes(59)).is_empty());
+
+        let due = rollover.due(now + Duration::hours(1));
+        assert_eq!(1, due.len());
+        assert_eq!(NaiveDate::from_ymd_opt(2023, 6, 1).unwrap(), due[0].2);
+        assert!(rollover.due(now + Duration::hours(2)).is_empty());
+
+        // days missed while not running are summarized after restoring
+        let summary = DailySummary::from_log(&Log::default(), IODirection::In, 0, due[0].2, offset);
+        let mut restarted = DailyRollover::default();
+        restarted.track(IODirection::In, 0, offset);
+        restarted.restore(vec![summary]);
+        assert_eq!(3, restarted.due(now + Duration::days(3) + Duration::hours(1)).len());
+    }
+}
@@ -1,10 +1,26 @@
-use crate::errors::{DeviceError, ErrorType};
-use crate::helpers::check_results;
-use crate::io::{Device, DeviceContainer, DeviceGetters, IdType, Input, Output};
-use crate::settings::DATA_ROOT;
-use crate::storage::{Directory, Persistent, RootDirectory, RootPath};
-
-use chrono::{DateTime, Duration, Utc};
+use crate::action::{ActionMetrics, Heartbeat, Publisher};
+use crate::alarm::{Alarm, AlarmAnnunciator, AlarmHandler, AlarmSeverity};
+use crate::console;
+use crate::errors::{ActionError, ContainerError, DeviceError, ErrorType};
+use crate::helpers::{check_results, monotonic, now, Def};
+use crate::inspect::read_log;
+use crate::io::{is_on, Device, DeviceContainer, DeviceGetters, DeviceMetadata, DeviceSetters, DeviceView, EnergyUsage, EventKind, EventQuality, IdType, IODirection, IOEvent, Input, MetadataPatch, Output, RawValue};
+use crate::settings::{DATA_ROOT, LOG_FN_PREFIX};
+use crate::storage::daily::{append_summary, read_summaries, DailyRollover};
+use crate::storage::maintenance::Maintenance;
+use crate::storage::mode::{append_history, read_history, ModeSelector};
+use crate::storage::quarantine::{alarm_name as quarantine_alarm, Quarantine, Transition};
+use crate::storage::metadata::patch_device;
+use crate::storage::migrate::migrate_dir;
+use crate::storage::rename::migrate_device;
+use crate::storage::{Chronicle, CollisionPolicy, Directory, Document, Log, EventHooks, Health, Persistent, RootDirectory, RootPath, Sweep, FILETYPE};
+use crate::storage::{append_record, sign, FORMAT_VERSION, stitch, DailySummary, Difference, GroupState, GroupView, JitterStats, LogStream, MaintenanceSummary, MaintenanceWindow, MetadataChange, Migration, Mode, ModeChange, ParameterOverride, PollOutcome, PollReport, PollSchedule, QuarantinePolicy, Roster, Series, SigningKey, Snapshot, Topology, Variables, METADATA_HISTORY_FILENAME, VARIABLES_FILENAME, MAINTENANCE_NAME};
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{read_dir, remove_file};
+use std::ops::{Deref, DerefMut};
+use std::time::{Duration as StdDuration, Instant};
 use std::path::{Path, PathBuf};
 use crate::name::Name;
 
@@ -67,6 +83,96 @@ use crate::name::Name;
 /// In order to set `interval`, either the alternate constructor [`Group::with_interval()`] can be utilized,
 /// or the builder method [`Group::set_interval()`] both result in user configured `interval`:
 ///
+/// ## Event Hooks
+///
+/// Lightweight callbacks can be registered for every successful read, every output write, and every
+/// device error. Hooks are shared by all devices in the group, including devices added after a hook
+/// is registered:
+///
+/// ```
+/// use sensd::storage::Group;
+///
+/// let mut group = Group::new("");
+/// group
+///     .on_read(|metadata, event| println!("{}: {}", metadata.name, event.value))
+///     .on_error(|metadata, error| eprintln!("{}: {}", metadata.name, error));
+/// ```
+///
+/// ## Heartbeat
+///
+/// A [`Heartbeat`] set via [`Group::set_heartbeat()`] is updated by [`Group::attempt_routines()`].
+/// Any device errors during [`Group::poll()`] put the heartbeat into a fault state, which is cleared by
+/// the next poll without errors.
+///
+/// ## Quarantine
+///
+/// Inputs that repeatedly fail to be read may be quarantined by setting a [`QuarantinePolicy`] via
+/// [`Group::set_quarantine()`]. Quarantined inputs are skipped by [`Group::poll()`] and
+/// periodically re-admitted on a backoff schedule, so that a dead sensor does not flood the returned
+/// errors every poll.
+///
+/// ## Modes
+///
+/// Named [`Mode`]s override action parameters (ie: a lower heater setpoint at night). Modes are
+/// selected by [`Group::set_mode()`] or on a daily schedule set by [`Group::schedule_mode()`], and
+/// every change is recorded in [`Group::mode_history()`]. To avoid slamming actuators, parameters
+/// of an action may be ramped to their new values at a rate set by [`Group::set_ramp_rate()`].
+///
+/// ## Daily Statistics
+///
+/// Devices registered with [`Group::set_daily_stats()`] are summarized once local midnight passes
+/// in their timezone. Summaries of min/max/mean, totalized values, and output on-time are retained in
+/// [`Group::daily_summaries()`] and appended to [`crate::storage::DAILY_FILENAME`]:
+///
+/// ```
+/// use chrono::FixedOffset;
+/// use sensd::io::IODirection;
+/// use sensd::storage::Group;
+///
+/// let mut group = Group::new("");
+/// group.set_daily_stats(IODirection::In, 0, FixedOffset::west_opt(5 * 3600).unwrap());
+/// ```
+///
+/// ## Maintenance
+///
+/// [`Group::pause()`] suspends polling and routine execution without tearing down devices, such as
+/// during maintenance windows or calibration. Outputs may optionally be driven to their safe state
+/// (see [`Output::set_safe_state()`]). [`Group::resume()`] continues normal operation:
+///
+/// ```
+/// use sensd::storage::Group;
+///
+/// let mut group = Group::new("");
+///
+/// group.pause(true);
+/// assert!(group.health().paused);
+/// assert!(group.poll().paused);
+///
+/// group.resume();
+/// assert!(!group.is_paused());
+/// ```
+///
+/// To avoid inrush currents tripping breakers when many outputs restore at once, a delay set by
+/// [`Group::set_soft_start()`] staggers re-enabling outputs on startup and after resuming. See
+/// [`Group::begin_soft_start()`].
+///
+/// Removing devices leaves behind log files and scheduled routines that reference dropped logs.
+/// [`Group::sweep()`] reports these (counts are also included in [`Group::health()`]), and
+/// [`Group::clean()`] removes them.
+///
+/// ## Data Integrity
+///
+/// Deployments which must prove that logged data has not been altered after the fact may set a
+/// secret key with [`Group::set_signing_key()`]. Every log is then signed with HMAC-SHA256 each
+/// time it is saved, and may later be checked with [`crate::storage::verify()`] or
+/// `sensd verify`:
+///
+/// ```
+/// use sensd::storage::{Group, SigningKey};
+///
+/// let mut group = Group::new("");
+/// group.set_signing_key(SigningKey::new("secret"));
+/// ```
 pub struct Group {
     /// Name used to identify this specific device grouping.
     ///
@@ -74,6 +180,32 @@ pub struct Group {
     name: String,
     /// Buffer to store time of the last successful poll.
     last_execution: DateTime<Utc>,
+    /// `last_execution` was restored by [`Persistent::load()`], so the first poll resumes its phase
+    resumed: bool,
+
+    /// Monotonic time of the next scheduled poll. `None` until first poll.
+    next_poll: Option<Instant>,
+    /// Scheduled time of poll which last read each input
+    last_reads: HashMap<IdType, Instant>,
+    /// Time between polls. Refreshed whenever inputs are read. See [`Group::tick()`].
+    tick: Duration,
+
+    schedule: PollSchedule,
+    jitter: JitterStats,
+
+    /// Secondary log streams keyed by input id
+    streams: HashMap<IdType, Vec<LogStream>>,
+
+    /// Named values shared between actions
+    variables: Variables,
+
+    /// Outputs shared with actions which target outputs by tag
+    roster: Roster,
+
+    modes: ModeSelector,
+
+    /// Daily statistics of registered devices
+    daily: DailyRollover,
 
     /// Immutable storage of runtime settings
     root: RootPath,
@@ -82,44 +214,284 @@ pub struct Group {
 
     pub inputs: DeviceContainer<IdType, Input>,
     pub outputs: DeviceContainer<IdType, Output>,
+
+    /// User callbacks shared with all devices
+    hooks: Def<EventHooks>,
+
+    /// Optional status LED reflecting health of polling
+    heartbeat: Option<Def<Heartbeat>>,
+
+    /// Optional buzzers and strobes reflecting active alarms
+    annunciator: Option<Def<AlarmAnnunciator>>,
+
+    /// Number of valid readings required before actions are enabled
+    bring_up: usize,
+
+    /// Suspend polling and routines
+    paused: bool,
+
+    /// Delay between outputs being re-enabled by soft start
+    soft_start: Option<Duration>,
+    /// Time at which each output is re-enabled, in order
+    staging: Vec<(DateTime<Utc>, IdType)>,
+
+    /// Scheduled and open maintenance windows
+    maintenance: Vec<Maintenance>,
+
+    /// Configuration differences from last-running snapshot, detected on startup
+    drift: Vec<Difference>,
+
+    /// Consecutive read failures of inputs. `None` disables quarantine.
+    quarantine: Option<Quarantine>,
+    /// Optional handler used to raise alarms for quarantined inputs
+    alarms: Option<Def<AlarmHandler>>,
+
+    /// Key used to sign logs when saved. `None` if logs are not signed.
+    signing_key: Option<SigningKey>,
 }
 
 impl Group {
     /// Primary callable to iterate through input device container once.
     ///
     /// [`Input::read()`] is called once on each input device at a frequency of
-    /// [`Group::interval()`]. Generated [`crate::io::IOEvent`] instances are
-    /// handled by [`Input::read()`].
+    /// [`Group::interval()`], or of [`Input::interval()`] when set. Generated
+    /// [`crate::io::IOEvent`] instances are handled by [`Input::read()`].
+    ///
+    /// Failure of any individual read does not halt execution. Instead, the outcome of every
+    /// [`Input::read()`] is returned.
+    ///
+    /// Interval is measured using a monotonic clock so that system clock corrections neither freeze
+    /// nor accelerate polling. A warning is printed when wall-clock time moves backwards.
+    ///
+    /// # Returns
+    ///
+    /// [`PollReport`] with the outcome of each input which was read. [`PollReport::executed()`] is
+    /// `false` when poll was not due, or when group is paused.
+    pub fn poll(&mut self) -> PollReport {
+        let mut report = PollReport::default();
+        let Some((scheduled, started)) = self.begin_poll(&mut report) else {
+            return report;
+        };
+        let timer = Instant::now();
+        let wall = now();
+
+        for id in self.due_inputs(scheduled, wall) {
+            let mut binding = self.inputs.get(&id).unwrap().try_lock().unwrap();
+            let evaluations = binding.publisher().as_ref().map(Publisher::evaluations);
+            let result = read_input(&mut binding, &mut self.quarantine, &self.alarms, &mut self.streams, wall, Input::read);
+            if let (Some(before), Some(publisher)) = (evaluations, binding.publisher()) {
+                report.propagated += publisher.evaluations() - before;
+            }
+            report.outcomes.push(PollOutcome { id, result });
+        }
+        self.finish_poll(&mut report, scheduled, started, wall, timer);
+        report
+    }
+
+    /// Asynchronous alternative to [`Group::poll()`] which reads all inputs concurrently
+    ///
+    /// Commands of all due inputs are executed at once, so that slow sensors (ie: on I2C or serial
+    /// buses) do not delay the entire poll. [`IOCommand::AsyncInputFn`] commands are awaited as
+    /// tokio tasks, while blocking commands are executed on the blocking thread pool of the
+    /// runtime. Retries and injected faults apply to each input exactly as by [`Input::read()`].
+    /// Once every input has been sampled, readings are processed in the same order and manner as
+    /// by [`Group::poll()`].
     ///
-    /// Failure of any individual read does not halt execution. Instead, errors
-    /// from [`Input::read()`] are returned as a [`Vec`].
+    /// Must be called within a tokio runtime. Requires the "async" feature.
+    ///
+    /// [`IOCommand::AsyncInputFn`]: crate::action::IOCommand::AsyncInputFn
     ///
     /// # Returns
     ///
-    /// A `Result` containing:
+    /// [`PollReport`] with the outcome of each input which was read. [`PollReport::executed()`] is
+    /// `false` when poll was not due, or when group is paused.
+    #[cfg(feature = "async")]
+    pub async fn poll_async(&mut self) -> PollReport {
+        let mut report = PollReport::default();
+        let Some((scheduled, started)) = self.begin_poll(&mut report) else {
+            return report;
+        };
+        let timer = Instant::now();
+        let wall = now();
+
+        let due = self.due_inputs(scheduled, wall);
+        let tasks = due.iter()
+            .map(|id| tokio::spawn(self.inputs.get(id).unwrap().try_lock().unwrap().sampler().read_async()))
+            .collect::<Vec<_>>();
+        let mut results = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            results.push(task.await);
+        }
+
+        for (id, result) in due.into_iter().zip(results) {
+            let mut binding = self.inputs.get(&id).unwrap().try_lock().unwrap();
+            let evaluations = binding.publisher().as_ref().map(Publisher::evaluations);
+            // task only fails if command panicked
+            let result = result.unwrap_or_else(|_| Err(DeviceError::HWFault { metadata: binding.metadata().clone() }));
+            let result = read_input(&mut binding, &mut self.quarantine, &self.alarms, &mut self.streams, wall,
+                                    |input| input.complete_read(result));
+            if let (Some(before), Some(publisher)) = (evaluations, binding.publisher()) {
+                report.propagated += publisher.evaluations() - before;
+            }
+            report.outcomes.push(PollOutcome { id, result });
+        }
+        self.finish_poll(&mut report, scheduled, started, wall, timer);
+        report
+    }
+
+    /// Check whether poll is due, reporting why not
+    ///
+    /// # Returns
     ///
-    /// - `Ok` when poll has been executed. `Ok` value will contain any errors
-    ///   that arose.
-    /// - `Err` when poll was not executed
-    pub fn poll(&mut self) -> Result<Vec<DeviceError>, ()> {
-        let mut errors = Vec::new();
-        let next_execution = self.last_execution + *self.interval();
+    /// Scheduled and actual start of poll, or `None` if poll is not due or group is paused.
+    fn begin_poll(&mut self, report: &mut PollReport) -> Option<(Instant, Instant)> {
+        self.attempt_maintenance_at(now());
+
+        let started = monotonic();
+        let scheduled = match self.next_poll {
+            Some(next) => next,
+            None => {
+                // a new schedule reads every input
+                self.last_reads.clear();
+                self.first_poll(started)
+            }
+        };
+        self.next_poll = Some(scheduled);
+
+        if self.paused || scheduled > started {
+            report.paused = self.paused;
+            report.early = !self.paused;
+            return None;
+        }
+        Some((scheduled, started))
+    }
+
+    /// Ids of inputs to be read by poll scheduled for `scheduled`
+    fn due_inputs(&mut self, scheduled: Instant, wall: DateTime<Utc>) -> Vec<IdType> {
+        // outputs may have been added to or removed from container directly
+        self.roster.sync(&self.outputs);
+
+        // inputs may have been removed from container directly
+        let inputs = &self.inputs;
+        self.last_reads.retain(|id, _| inputs.get(id).is_some());
+
+        let mut due = Vec::new();
+        let mut tick = self.interval;
+        for input in self.inputs.values() {
+            let binding = input.try_lock().unwrap();
+            if let Some(interval) = binding.interval() {
+                tick = tick.min(interval);
+            }
+
+            // skip devices that have not finished warming up
+            if !binding.is_warm() {
+                continue;
+            }
 
-        if next_execution <= Utc::now() {
-            for input in self.inputs.values_mut() {
-                let mut binding = input.try_lock().unwrap();
-                let result = binding.read();
+            // skip quarantined devices until re-admission is attempted
+            let id = binding.id();
+            if let Some(quarantine) = &self.quarantine {
+                if !quarantine.allows(id, wall) {
+                    continue;
+                }
+            }
 
-                // Add errors to array
-                if result.is_err() {
-                    errors.push(result.err().unwrap());
+            // skip devices whose interval has not elapsed
+            let interval = binding.interval().unwrap_or(self.interval).to_std().unwrap_or_default();
+            if let Some(last) = self.last_reads.get(&id) {
+                if scheduled < *last + interval {
+                    continue;
                 }
             }
-            self.last_execution = next_execution;
-            Ok(errors)
-        } else {
-            Err(())
+            self.last_reads.insert(id, scheduled);
+            due.push(id);
+        }
+        self.tick = tick;
+        due
+    }
+
+    /// Schedule next poll, and update bookkeeping once all inputs have been read
+    fn finish_poll(&mut self, report: &mut PollReport, scheduled: Instant, started: Instant, wall: DateTime<Utc>, timer: Instant) {
+        self.schedule_next(scheduled, started);
+
+        if wall < self.last_execution {
+            console::warning(format!("System clock moved backwards by {}", self.last_execution - wall));
+        }
+        self.last_execution = wall;
+
+        if let Err(e) = self.attempt_daily_rollover_at(wall) {
+            console::error(format!("Could not record daily statistics: {}", e));
+        }
+
+        if let Some(heartbeat) = &self.heartbeat {
+            let mut heartbeat = heartbeat.try_lock().unwrap();
+            match report.has_errors() {
+                true => heartbeat.fault(),
+                false => heartbeat.clear_fault(),
+            }
         }
+        report.duration = timer.elapsed();
+    }
+
+    /// Read a single input immediately, regardless of the polling schedule
+    ///
+    /// Intended for remote requests and calibration flows which cannot wait for the next poll. The
+    /// reading is logged, propagated to actions, passed to streams, and counted by the quarantine
+    /// policy exactly as a reading taken by [`Group::poll()`]. Warm-up, quarantine, and pausing are
+    /// not checked, and the schedule of the next poll is unaffected.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: Id of input
+    ///
+    /// # Returns
+    ///
+    /// Generated [`IOEvent`], [`ContainerError::KeyMissing`] if no input has `id`, or the
+    /// [`DeviceError`] returned by [`Input::read()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensd::action::IOCommand;
+    /// use sensd::io::{Device, Input, IOKind, RawValue};
+    /// use sensd::storage::Group;
+    ///
+    /// let mut group = Group::new("");
+    /// group.push_input(Input::new("ph", 0, IOKind::PH)
+    ///     .set_command(IOCommand::Input(|| RawValue::Float(6.5))));
+    ///
+    /// assert!(group.poll().executed());
+    /// // does not wait for interval to elapse
+    /// assert_eq!(RawValue::Float(6.5), group.read_now(0).unwrap().value);
+    /// assert!(group.read_now(1).is_err());
+    /// ```
+    pub fn read_now(&mut self, id: IdType) -> Result<IOEvent, ErrorType> {
+        let input = self.inputs.get(&id)
+            .ok_or_else(|| ContainerError::KeyMissing { key: id.to_string() })?;
+        let mut input = input.try_lock().unwrap();
+        let event = read_input(&mut input, &mut self.quarantine, &self.alarms, &mut self.streams, now(), Input::read)?;
+        Ok(event)
+    }
+
+    /// Write to a single output immediately, regardless of the polling schedule
+    ///
+    /// The write is logged and passed to hooks exactly as a write made by an action. Pausing is not
+    /// checked.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: Id of output
+    /// - `value`: Value to write
+    ///
+    /// # Returns
+    ///
+    /// Generated [`IOEvent`], [`ContainerError::KeyMissing`] if no output has `id`, or any error
+    /// returned by [`Output::write()`].
+    pub fn write_now(&mut self, id: IdType, value: RawValue) -> Result<IOEvent, ErrorType> {
+        let output = self.outputs.get(&id)
+            .ok_or_else(|| ContainerError::KeyMissing { key: id.to_string() })?;
+        let event = output.try_lock().unwrap().write(value)?;
+        Ok(event)
     }
 
     /// Primary constructor.
@@ -150,20 +522,45 @@ impl Group {
         N: Into<String>
     {
         let interval = Duration::seconds(5);
-        let last_execution = Utc::now() - interval;
+        let last_execution = now() - interval;
 
         let inputs = <DeviceContainer<IdType, Input>>::default();
         let outputs = <DeviceContainer<IdType, Output>>::default();
 
         let root = RootPath::from(DATA_ROOT);
 
+        let hooks = Def::default();
+
         Self {
             name: name.into(),
             interval,
             root,
             last_execution,
+            resumed: false,
+            next_poll: None,
+            last_reads: HashMap::new(),
+            tick: interval,
+            schedule: PollSchedule::default(),
+            jitter: JitterStats::default(),
+            streams: HashMap::new(),
+            variables: Variables::default(),
+            roster: Roster::default(),
+            modes: ModeSelector::default(),
+            daily: DailyRollover::default(),
             inputs,
             outputs,
+            hooks,
+            heartbeat: None,
+            annunciator: None,
+            bring_up: 0,
+            paused: false,
+            soft_start: None,
+            staging: Vec::new(),
+            maintenance: Vec::new(),
+            drift: Vec::new(),
+            quarantine: None,
+            alarms: None,
+            signing_key: None,
         }
     }
 
@@ -240,6 +637,16 @@ impl Group {
         let id = device.id();
 
         device.set_parent_dir_ref(self.full_path());
+        device.set_hooks(self.hooks.clone());
+        if let Some(publisher) = device.publisher_mut() {
+            publisher.set_bring_up(self.bring_up);
+        }
+
+        // an input re-added after removal does not inherit the schedule of its predecessor
+        self.last_reads.remove(&id);
+        if let Some(interval) = device.interval() {
+            self.tick = self.tick.min(interval);
+        }
 
         self.inputs.insert(id, device.into_deferred())
             .unwrap();
@@ -272,140 +679,1701 @@ impl Group {
         let id = device.id();
 
         device.set_parent_dir_ref(self.full_path());
+        device.set_hooks(self.hooks.clone());
 
         self.outputs.insert(id, device.into_deferred())
             .unwrap();
+        self.roster.sync(&self.outputs);
 
         self
     }
 
-    pub fn attempt_routines(&self) {
-        for device in self.inputs.values() {
+    pub fn attempt_routines(&mut self) {
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.try_lock().unwrap().attempt();
+        }
+        if let (Some(annunciator), Some(alarms)) = (&self.annunciator, &self.alarms) {
+            annunciator.try_lock().unwrap().attempt(&alarms.try_lock().unwrap());
+        }
+
+        if self.paused {
+            return;
+        }
+
+        self.notify_actions(|publisher| publisher.attempt_routines());
+
+        self.attempt_soft_start();
+
+        // retry queued writes
+        for device in self.outputs.values() {
             let mut binding = device.try_lock().unwrap();
-            if let Some(publisher) = binding.publisher_mut() {
-                publisher.attempt_routines()
+            match binding.attempt_pending() {
+                Some(Err(e)) if !e.downcast_ref().map(DeviceError::is_queued).unwrap_or(false) => {
+                    console::error(format!("Could not write queued value to {}: {}", binding.name(), e));
+                }
+                _ => (),
             }
         }
     }
 
-    //
-    // Getters
-
-
-    #[inline]
-    /// Getter for `interval`
+    /// Suspend polling and routine execution
     ///
-    /// # Notes
+    /// Devices, logs, and scheduled routines are retained. Routines that become due while paused
+    /// are executed once [`Group::resume()`] is called. Actions are notified by
+    /// [`crate::action::Action::on_pause()`].
     ///
-    /// Since this is frequently used in iterators and polling, this
-    /// method is marked inline to avoiding jumping in memory.
+    /// # Parameters
+    ///
+    /// - `safe`: Write safe state to all outputs that have one
     ///
     /// # Returns
     ///
-    /// Immutable reference to `interval`
-    pub fn interval(&self) -> &Duration {
-        &self.interval
-    }
+    /// Mutable reference to `self` to allow method chaining
+    pub fn pause(&mut self, safe: bool) -> &mut Self {
+        self.paused = true;
+        self.notify_actions(|publisher| publisher.on_pause());
 
-    /// Setter for `interval`
-    ///
-    /// # Parameters
-    ///
-    /// - `interval`: any value that can be coerced into [`Duration`]
-    pub fn set_interval(&mut self, interval: Duration) {
-        self.interval = interval
+        if safe {
+            for output in self.outputs.values() {
+                let mut output = output.try_lock().unwrap();
+                if let Some(Err(e)) = output.write_safe_state() {
+                    console::error(format!("Could not write safe state to {}: {}", output.name(), e));
+                }
+            }
+        }
+        self
     }
-}
 
-/// Only save and load log data since [`Group`] is statically initialized
-/// If `&None` is given to either methods, then current directory is used.
-impl Persistent for Group {
-    /// Save all device logs
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if any single save fails. However, failure is silent and
-    /// does not prevent saving other device logs.
+    /// Stop group so that the process may exit
     ///
-    /// # Panics
-    ///
-    /// Panics when any single input or output device cannot be locked.
+    /// Polling is suspended and actions are notified by [`crate::action::Action::on_shutdown()`] so
+    /// that outputs are not left energized. Safe state is then written to all outputs that have
+    /// one, and runtime state is persisted by [`Group::save_snapshot()`].
     ///
     /// # Returns
     ///
-    /// A [`Result`] containing:
-    ///
-    /// - `Ok` that is empty when saving occurred without error.
-    /// - `Err` containing the first error stored. There may be more errors that were
-    ///   not returned. An error occurring does not halt saving other logs.
-    fn save(&self) -> Result<(), ErrorType> {
-        let mut results = Vec::new();
+    /// `Err` if snapshot could not be saved
+    pub fn shutdown(&mut self) -> Result<(), ErrorType> {
+        self.paused = true;
+        self.notify_actions(|publisher| publisher.on_shutdown());
 
-        for device in self.inputs.values() {
-            let binding = device.try_lock().expect("Could not lock input");
-            results.push(
-                binding.save());
+        for output in self.outputs.values() {
+            let mut output = output.try_lock().unwrap();
+            if let Some(Err(e)) = output.write_safe_state() {
+                console::error(format!("Could not write safe state to {}: {}", output.name(), e));
+            }
         }
+        self.save_snapshot()
+    }
 
-        for device in self.outputs.values() {
-            let binding = device.try_lock().expect("Could not lock output");
-            results.push(
-                binding.save());
+    /// Call `notify` with the publisher of every input and output that has one
+    fn notify_actions<F>(&self, notify: F)
+    where
+        F: Fn(&mut Publisher)
+    {
+        for input in self.inputs.values() {
+            if let Some(publisher) = input.try_lock().unwrap().publisher_mut() {
+                notify(publisher);
+            }
+        }
+        for output in self.outputs.values() {
+            if let Some(publisher) = output.try_lock().unwrap().publisher_mut() {
+                notify(publisher);
+            }
         }
-
-        check_results(&results)
     }
 
-    /// Load all device logs
+    /// Continue polling and routine execution after [`Group::pause()`]
     ///
-    /// # Errors
+    /// # Returns
     ///
-    /// Returns an error if any single load fails. However, failure is silent and does not prevent
-    /// loading other device logs.
+    /// Mutable reference to `self` to allow method chaining
+    pub fn resume(&mut self) -> &mut Self {
+        self.paused = false;
+        // time spent paused should not count as jitter
+        self.next_poll = None;
+        self.begin_soft_start()
+    }
+
+    /// Set delay between outputs being re-enabled by [`Group::begin_soft_start()`]
     ///
-    /// # Panics
+    /// # Parameters
     ///
-    /// Panics when any single input or output device cannot be locked.
+    /// - `delay`: Time between successive outputs being re-enabled
     ///
     /// # Returns
     ///
-    /// A [`Result`] containing:
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_soft_start(&mut self, delay: Duration) -> &mut Self {
+        self.soft_start = Some(delay);
+        self
+    }
+
+    /// Begin staged re-enable sequence of outputs
     ///
-    /// - `Ok` that is empty when loading occurred without error.
-    /// - `Err` containing the first error stored. There may be more errors that were
-    ///   not returned. An error occurring does not halt loading other logs.
-    fn load(&mut self) -> Result<(), ErrorType> {
-        let mut results = Vec::new();
+    /// Should be called on startup, and is called by [`Group::resume()`]. Outputs which are not on
+    /// are inhibited (see [`Output::inhibit()`]), then re-enabled one at a time in order of id by
+    /// [`Group::attempt_routines()`], so that inrush currents of devices restoring at once do not
+    /// trip breakers. Writes to inhibited outputs are queued, and performed once re-enabled.
+    ///
+    /// Has no effect unless a delay was set by [`Group::set_soft_start()`].
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn begin_soft_start(&mut self) -> &mut Self {
+        self.begin_soft_start_at(now())
+    }
 
-        for device in self.outputs.values() {
-            let mut binding = device.try_lock().unwrap();
-            results.push(
-                binding.load());
+    /// Begin staged re-enable sequence of outputs at `now`
+    pub fn begin_soft_start_at(&mut self, now: DateTime<Utc>) -> &mut Self {
+        let delay = match self.soft_start {
+            Some(delay) => delay,
+            None => return self,
+        };
+
+        let mut ids: Vec<IdType> = self.outputs.iter().map(|(id, _)| *id).collect();
+        ids.sort();
+
+        self.staging.clear();
+        let mut at = now;
+        for id in ids {
+            let mut output = self.outputs.get(&id).unwrap().try_lock().unwrap();
+            let on = output.state().map(is_on).unwrap_or(false);
+            if !on {
+                output.inhibit();
+                self.staging.push((at, id));
+                at += delay;
+            }
         }
+        self
+    }
 
-        for device in self.inputs.values() {
-            let mut binding = device.try_lock().unwrap();
-            results.push(
-                binding.load());
-        }
+    /// Check if any outputs are still waiting to be re-enabled
+    pub fn is_soft_starting(&self) -> bool {
+        self.staging.iter().any(|(_, id)| {
+            self.outputs.get(id)
+                .map(|output| output.try_lock().unwrap().is_inhibited())
+                .unwrap_or(false)
+        })
+    }
 
-        check_results(&results)
+    /// Re-enable outputs whose time in soft start sequence has passed
+    ///
+    /// Called by [`Group::attempt_routines()`].
+    pub fn attempt_soft_start(&mut self) {
+        self.attempt_soft_start_at(now())
     }
-}
 
-impl Name for Group {
-    /// Getter for `name`
+    /// Re-enable outputs whose time in soft start sequence has passed at `now`
     ///
-    /// # Returns
+    /// Each output is re-enabled once, so that outputs inhibited afterwards (ie: by a maintenance
+    /// window) remain inhibited.
+    pub fn attempt_soft_start_at(&mut self, now: DateTime<Utc>) {
+        let due = self.staging.iter().take_while(|(at, _)| *at <= now).count();
+        for (_, id) in self.staging.drain(..due) {
+            // outputs inhibited by maintenance are enabled once window ends
+            if self.maintenance.iter().any(|open| open.inhibited.contains(&id)) {
+                continue;
+            }
+            if let Some(output) = self.outputs.get(&id) {
+                output.try_lock().unwrap().enable();
+            }
+        }
+    }
+
+    /// Schedule a maintenance window
     ///
-    /// Immutable reference to `name`
-    fn name(&self) -> &String {
-        &self.name
+    /// Windows begin and end during [`Group::poll()`], including while paused.
+    ///
+    /// # See Also
+    ///
+    /// - [`MaintenanceWindow`] for usage
+    pub fn schedule_maintenance(&mut self, window: MaintenanceWindow) -> &mut Self {
+        self.maintenance.push(Maintenance::new(window));
+        self
     }
 
-    /// Setter for `name`
+    /// Maintenance windows which are scheduled or open
+    pub fn maintenance(&self) -> impl Iterator<Item = &MaintenanceWindow> {
+        self.maintenance.iter().map(|maintenance| &maintenance.window)
+    }
+
+    /// Begin and end maintenance windows at `now`
     ///
-    /// # Parameters
+    /// Devices covered by a window are resolved when it begins. Devices covered by more than one
+    /// open window remain suppressed and inhibited until the last of those windows ends.
+    ///
+    /// # Returns
+    ///
+    /// Summary of each window which ended. Summaries are also delivered through the alarm handler
+    /// set by [`Group::set_alarm_handler()`].
+    pub fn attempt_maintenance_at(&mut self, now: DateTime<Utc>) -> Vec<MaintenanceSummary> {
+        let mut summaries = Vec::new();
+        for index in 0..self.maintenance.len() {
+            if self.maintenance[index].opened.is_none() && self.maintenance[index].window.start() <= now {
+                self.open_maintenance(index, now);
+            }
+        }
+
+        let (ended, open): (Vec<Maintenance>, Vec<Maintenance>) = std::mem::take(&mut self.maintenance)
+            .into_iter()
+            .partition(|maintenance| maintenance.opened.is_some() && maintenance.window.end() <= now);
+        self.maintenance = open;
+
+        for maintenance in ended {
+            summaries.push(self.close_maintenance(maintenance, now));
+        }
+        summaries
+    }
+
+    /// Suppress alarms of covered devices, and inhibit covered outputs
+    fn open_maintenance(&mut self, index: usize, now: DateTime<Utc>) {
+        let maintenance = &mut self.maintenance[index];
+        maintenance.opened = Some(now);
+
+        let target = maintenance.window.target();
+        for input in self.inputs.values() {
+            let input = input.try_lock().unwrap();
+            if target.matches(input.metadata()) {
+                maintenance.devices.push(input.name().clone());
+            }
+        }
+        for output in self.outputs.values() {
+            let mut output = output.try_lock().unwrap();
+            if target.matches(output.metadata()) {
+                maintenance.devices.push(output.name().clone());
+                if maintenance.window.inhibits() {
+                    output.inhibit();
+                    maintenance.inhibited.push(output.id());
+                }
+            }
+        }
+
+        if let Some(alarms) = &self.alarms {
+            let mut alarms = alarms.lock().unwrap();
+            for device in maintenance.devices.iter() {
+                alarms.suppress(device.as_str());
+            }
+        }
+    }
+
+    /// Restore devices no longer covered by any open window, and deliver summary
+    fn close_maintenance(&mut self, maintenance: Maintenance, now: DateTime<Utc>) -> MaintenanceSummary {
+        let covered = |device: &String| self.maintenance.iter().any(|open| open.devices.contains(device));
+        let inhibited = |id: &IdType| self.maintenance.iter().any(|open| open.inhibited.contains(id));
+
+        for id in maintenance.inhibited.iter().filter(|id| !inhibited(id)) {
+            if let Some(output) = self.outputs.get(id) {
+                output.try_lock().unwrap().enable();
+            }
+        }
+
+        let mut summary = MaintenanceSummary {
+            name: maintenance.window.name().clone(),
+            start: maintenance.opened.unwrap(),
+            end: now,
+            devices: maintenance.devices.clone(),
+            suppressed: Vec::new(),
+            inhibited: maintenance.inhibited.len(),
+        };
+        if let Some(alarms) = &self.alarms {
+            let mut alarms = alarms.lock().unwrap();
+            for device in maintenance.devices.iter().filter(|device| !covered(device)) {
+                summary.suppressed.extend(alarms.unsuppress(device));
+            }
+            let notification = Alarm::new(MAINTENANCE_NAME, AlarmSeverity::Info, summary.to_text(), now);
+            let sinks = alarms.policy(MAINTENANCE_NAME).sinks().to_vec();
+            alarms.deliver(&sinks, &notification);
+        }
+        summary
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Current operational status
+    pub fn health(&self) -> Health {
+        let faulted = self.heartbeat.as_ref()
+            .map(|heartbeat| heartbeat.try_lock().unwrap().is_faulted())
+            .unwrap_or(false);
+
+        Health {
+            name: self.name.clone(),
+            paused: self.paused,
+            faulted,
+            inputs: self.inputs.len(),
+            outputs: self.outputs.len(),
+            last_poll: self.last_execution,
+            orphaned_logs: self.orphaned_logs().len(),
+            dangling_routines: self.dangling_routines(),
+            jitter: self.jitter,
+            quarantined: self.quarantined(),
+            action_metrics: self.action_metrics(),
+        }
+    }
+
+    /// Counters of all actions, keyed by id of source input and action name
+    ///
+    /// Inputs without subscribers are omitted.
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::action::Publisher::metrics()`]
+    pub fn action_metrics(&self) -> BTreeMap<IdType, BTreeMap<String, ActionMetrics>> {
+        self.inputs.iter()
+            .filter_map(|(id, input)| {
+                let input = input.try_lock().unwrap();
+                input.publisher().as_ref()
+                    .map(|publisher| publisher.metrics())
+                    .filter(|metrics| !metrics.is_empty())
+                    .map(|metrics| (*id, metrics))
+            })
+            .collect()
+    }
+
+    /// Capture runtime state of all stateful actions, shared variables, mode, and energy meters
+    ///
+    /// Metadata of all devices and parameters of all actions are also captured.
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::action::Action::state()`]
+    pub fn snapshot(&self) -> Snapshot {
+        let mut actions = BTreeMap::new();
+        let mut parameters = BTreeMap::new();
+        let mut inputs = BTreeMap::new();
+        for (id, input) in self.inputs.iter() {
+            let input = input.try_lock().unwrap();
+            inputs.insert(*id, input.metadata().clone());
+            if let Some(publisher) = input.publisher() {
+                let state = publisher.state();
+                if !state.is_empty() {
+                    actions.insert(*id, state);
+                }
+                let values = publisher.parameters();
+                if !values.is_empty() {
+                    parameters.insert(*id, values);
+                }
+            }
+        }
+        let mut energy = BTreeMap::new();
+        let mut outputs = BTreeMap::new();
+        for (id, output) in self.outputs.iter() {
+            let output = output.try_lock().unwrap();
+            outputs.insert(*id, output.metadata().clone());
+            if let Some(meter) = output.energy_meter() {
+                energy.insert(*id, meter.kwh());
+            }
+        }
+
+        Snapshot {
+            taken: Some(now()),
+            actions,
+            variables: self.variables.to_map(),
+            mode: self.modes.current().cloned(),
+            energy,
+            inputs,
+            outputs,
+            parameters,
+            format: FORMAT_VERSION,
+        }
+    }
+
+    /// Restore runtime state from a [`Snapshot`]
+    ///
+    /// Devices are matched by UUID, so that state is restored to devices which have since been
+    /// renumbered. Otherwise, devices are matched by id and adopt the UUID of the captured device of
+    /// the same name. Entries for inputs or actions that no longer exist are ignored.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), ErrorType> {
+        self.variables.extend(&snapshot.variables);
+
+        let inputs: BTreeMap<IdType, IdType> = snapshot.inputs.iter()
+            .map(|(id, captured)| (*id, match_device(&self.inputs, *id, captured)))
+            .collect();
+        let outputs: BTreeMap<IdType, IdType> = snapshot.outputs.iter()
+            .map(|(id, captured)| (*id, match_device(&self.outputs, *id, captured)))
+            .collect();
+
+        for (id, kwh) in snapshot.energy.iter() {
+            let id = outputs.get(id).unwrap_or(id);
+            if let Some(output) = self.outputs.get(id) {
+                if let Some(meter) = output.try_lock().unwrap().energy_meter_mut() {
+                    meter.set_kwh(*kwh);
+                }
+            }
+        }
+
+        let mut results = Vec::new();
+        for (id, states) in snapshot.actions.iter() {
+            let id = inputs.get(id).unwrap_or(id);
+            if let Some(input) = self.inputs.get(id) {
+                if let Some(publisher) = input.try_lock().unwrap().publisher_mut() {
+                    results.push(publisher.restore_state(states));
+                }
+            }
+        }
+
+        if let Some(mode) = &snapshot.mode {
+            if self.modes.current() != Some(mode) {
+                results.push(self.set_mode(mode, "snapshot"));
+            }
+        }
+        check_results(&results)
+    }
+
+    /// Save [`Group::snapshot()`] to group directory
+    ///
+    /// Should be called periodically, and before shutdown, so that controllers resume without a
+    /// bump after a restart.
+    pub fn save_snapshot(&self) -> Result<(), ErrorType> {
+        self.snapshot().save(self.full_path())
+    }
+
+    /// Restore snapshot stored in group directory, if any
+    ///
+    /// Configuration of devices and actions is compared against the snapshot once restored, and any
+    /// drift is printed to stderr and retained in [`Group::drift()`].
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(true)` if a snapshot was restored
+    /// - `Ok(false)` if no snapshot exists
+    /// - `Err` if snapshot could not be read
+    pub fn load_snapshot(&mut self) -> Result<bool, ErrorType> {
+        match Snapshot::load(self.full_path())? {
+            Some(snapshot) => {
+                self.restore(&snapshot)?;
+
+                // compare after restoring so that parameters overridden by mode are not reported
+                self.drift = snapshot.diff(&self.snapshot()).into_iter()
+                    .filter(|difference| difference.is_config())
+                    .collect();
+                for difference in self.drift.iter() {
+                    console::warning(format!("Config drift in {}: {}", self.name, difference));
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Configuration differences between code and the last-running snapshot
+    ///
+    /// Populated by [`Group::load_snapshot()`]. Empty if no snapshot was loaded, or if
+    /// configuration is unchanged.
+    pub fn drift(&self) -> &Vec<Difference> {
+        &self.drift
+    }
+
+    /// Energy consumed by output `id` within a time range
+    ///
+    /// # Returns
+    ///
+    /// `None` if output does not exist, or has no energy meter or log
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::io::EnergyMeter::usage()`]
+    pub fn energy_usage(&self, id: IdType, start: DateTime<Utc>, end: DateTime<Utc>) -> Option<EnergyUsage> {
+        let output = self.outputs.get(&id)?.try_lock().unwrap();
+        let log = output.log()?;
+        let usage = output.energy_meter()?.usage(&log.try_lock().unwrap(), start, end);
+        Some(usage)
+    }
+
+    /// Handle to store of named variables shared between actions
+    ///
+    /// # See Also
+    ///
+    /// - [`Variables`] for usage
+    pub fn variables(&self) -> Variables {
+        self.variables.clone()
+    }
+
+    /// Handle to outputs of group, used to target outputs by tag
+    ///
+    /// # See Also
+    ///
+    /// - [`Roster`] for usage
+    /// - [`WriteTarget::Tagged`](crate::action::WriteTarget::Tagged)
+    pub fn roster(&self) -> Roster {
+        self.roster.clone()
+    }
+
+    /// Read-only handle to devices and variables for UI consumers
+    ///
+    /// # See Also
+    ///
+    /// - [`GroupView`] for usage
+    pub fn view(&self) -> GroupView {
+        let inputs = self.inputs.iter()
+            .map(|(id, input)| (*id, DeviceView::new(input)))
+            .collect();
+        let outputs = self.outputs.iter()
+            .map(|(id, output)| (*id, DeviceView::new(output)))
+            .collect();
+        GroupView::new(self.name.clone(), inputs, outputs, self.variables.clone())
+    }
+
+    /// Wiring of inputs, outputs, actions, and pending routines
+    ///
+    /// # See Also
+    ///
+    /// - [`Topology`] for usage, and [`Topology::to_dot()`] for visualizing
+    pub fn topology(&self) -> Topology {
+        let mut topology = Topology::new(self.name.clone());
+        for input in self.inputs.values() {
+            topology.add_device(input.try_lock().unwrap().deref());
+        }
+        for output in self.outputs.values() {
+            topology.add_device(output.try_lock().unwrap().deref());
+        }
+
+        for input in self.inputs.values() {
+            let input = input.try_lock().unwrap();
+            if let Some(publisher) = input.publisher() {
+                let owner = topology.add_device(input.deref());
+                topology.add_publisher(&owner, publisher);
+            }
+        }
+        for output in self.outputs.values() {
+            let output = output.try_lock().unwrap();
+            if let Some(publisher) = output.publisher() {
+                let owner = topology.add_device(output.deref());
+                topology.add_publisher(&owner, publisher);
+            }
+        }
+        topology
+    }
+
+    /// Record every change to shared variables in group directory
+    ///
+    /// The journal allows variables to be reconstructed by [`Group::replay()`]. Group directory
+    /// should be initialized beforehand.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn journal_variables(&mut self) -> &mut Self {
+        self.variables.set_journal(self.full_path().join(VARIABLES_FILENAME));
+        self
+    }
+
+    /// Reconstruct last known state by replaying persisted event streams
+    ///
+    /// This is an alternative to [`Group::load_snapshot()`] which does not depend on a snapshot
+    /// having been saved shortly before a crash:
+    ///
+    /// - Cached output states are set to the last value written in each output log. Logs must be
+    ///   loaded beforehand by [`Persistent::load()`].
+    /// - Variables are replayed from the journal written after [`Group::journal_variables()`]
+    /// - Mode history is read from the audit trail, and overrides of the most recent mode are
+    ///   applied without ramping
+    /// - Daily summaries are read from [`crate::storage::DAILY_FILENAME`]
+    ///
+    /// Active alarms are reconstructed by [`crate::alarm::AlarmHandler::replay()`].
+    ///
+    /// Missing streams are ignored. Streams that cannot be read and overrides that cannot be applied
+    /// are printed to stderr, and do not prevent replaying other streams.
+    pub fn replay(&mut self) -> Result<(), ErrorType> {
+        let dir = self.full_path();
+        let mut results = Vec::new();
+
+        for output in self.outputs.values() {
+            let mut output = output.try_lock().unwrap();
+            let last = output.log().and_then(|log| {
+                log.try_lock().unwrap()
+                    .filter_kind(EventKind::OutputWrite)
+                    .rfind(|(_, event)| event.quality != EventQuality::Bad)
+                    .map(|(_, event)| event.value)
+            });
+            if let Some(value) = last {
+                output.restore_state(value);
+            }
+        }
+
+        results.push(self.variables.replay(dir.join(VARIABLES_FILENAME)).map(|_| ()));
+
+        match read_history(&dir) {
+            Ok(history) if !history.is_empty() => {
+                self.modes.restore_history(history);
+                let overrides = self.modes.current()
+                    .and_then(|name| self.modes.get(name))
+                    .map(|mode| mode.overrides().to_vec())
+                    .unwrap_or_default();
+                for o in overrides.iter() {
+                    results.push(self.set_parameter(o.input, &o.action, &o.parameter, o.value));
+                }
+            }
+            Ok(_) => (),
+            Err(e) => results.push(Err(e)),
+        }
+
+        match read_summaries(&dir) {
+            Ok(summaries) => self.daily.restore(summaries),
+            Err(e) => results.push(Err(e)),
+        }
+        check_results(&results)
+    }
+
+    /// Summarize a device every day at local midnight
+    ///
+    /// Summaries are generated from the device log by [`Group::poll()`] once a day has ended in the
+    /// timezone of the device. The device should therefore have a log which retains at least a day
+    /// of events. After [`Group::replay()`], days which ended while the program was not running are
+    /// also summarized.
+    ///
+    /// # Parameters
+    ///
+    /// - `direction`: Direction of device
+    /// - `id`: Id of device
+    /// - `offset`: Offset of local time from UTC. Daylight saving time is not observed.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    ///
+    /// # See Also
+    ///
+    /// - [`DailySummary`] for available statistics
+    pub fn set_daily_stats(&mut self, direction: IODirection, id: IdType, offset: FixedOffset) -> &mut Self {
+        self.daily.track(direction, id, offset);
+        self
+    }
+
+    /// Sign logs with `key` every time they are saved
+    ///
+    /// Signatures are written alongside each log file by [`crate::storage::sign()`]. Only logs
+    /// are signed.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_signing_key(&mut self, key: SigningKey) -> &mut Self {
+        self.signing_key = Some(key);
+        self
+    }
+
+    /// Daily summaries generated since startup, or restored by [`Group::replay()`]
+    pub fn daily_summaries(&self) -> &[DailySummary] {
+        self.daily.summaries()
+    }
+
+    /// Series of values of a device within a time range, regardless of where they are stored
+    ///
+    /// Values are taken from the in-memory log where possible. Only if the in-memory log does not
+    /// reach back to `start` are the saved log and any pages (see [`Log::set_paging()`]) read from
+    /// disk, and only if those do not reach back either are [`Group::daily_summaries()`] used.
+    ///
+    /// # Parameters
+    ///
+    /// - `direction`: Direction of device
+    /// - `id`: Id of device
+    /// - `start`: Earliest timestamp, inclusive
+    /// - `end`: Latest timestamp, exclusive
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with stitched series. Empty if device has no log or summaries.
+    /// - `Err` with [`ContainerError::KeyMissing`] if device does not exist, or if saved log could
+    ///   not be read
+    ///
+    /// # See Also
+    ///
+    /// - [`stitch()`] for how tiers are combined
+    pub fn query(&self, direction: IODirection, id: IdType, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Series, ErrorType> {
+        let log = match direction {
+            IODirection::In => self.inputs.get(&id).map(|input| input.try_lock().unwrap().log()),
+            IODirection::Out => self.outputs.get(&id).map(|output| output.try_lock().unwrap().log()),
+        }.ok_or_else(|| ContainerError::KeyMissing { key: id.to_string() })?;
+        let daily: Vec<DailySummary> = self.daily.summaries().iter()
+            .filter(|summary| summary.direction == direction && summary.id == id)
+            .cloned()
+            .collect();
+
+        let log = match log {
+            Some(log) => log,
+            None => return Ok(stitch(start, end, None, None, &daily)),
+        };
+        let memory = log.try_lock().unwrap();
+        let covered = memory.first().is_some_and(|event| event.timestamp <= start);
+        let archive = match covered || memory.dir().is_none() {
+            true => None,
+            false => {
+                let mut archive = match memory.exists() {
+                    true => Some(read_log(memory.full_path())?),
+                    false => None,
+                };
+                // events paged out of memory are not contained in the saved log
+                let oldest = memory.first().map(IOEvent::key);
+                let paged: Vec<IOEvent> = memory.query(start, end)?.into_iter()
+                    .filter(|event| oldest.is_none_or(|oldest| event.key() < oldest))
+                    .collect();
+                if !paged.is_empty() {
+                    let archive = archive.get_or_insert_with(Log::default);
+                    archive.set_collision_policy(CollisionPolicy::Overwrite);
+                    for event in paged {
+                        archive.push(event)?;
+                    }
+                }
+                archive
+            }
+        };
+        Ok(stitch(start, end, Some(&memory), archive.as_ref(), &daily))
+    }
+
+    /// Summarize days which have ended since the previous check
+    ///
+    /// This is called by [`Group::poll()`]. The first check of a device does not generate a summary.
+    /// Summaries are appended to [`crate::storage::DAILY_FILENAME`] if the group directory exists.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` once summaries are generated. Devices which no longer exist, or have no log, are
+    ///   skipped.
+    /// - `Err` if any summary could not be stored
+    pub fn attempt_daily_rollover_at(&mut self, now: DateTime<Utc>) -> Result<(), ErrorType> {
+        let dir = self.full_path();
+        let mut results = Vec::new();
+        for (direction, id, date, offset) in self.daily.due(now) {
+            let log = match direction {
+                IODirection::In => self.inputs.get(&id).and_then(|input| input.try_lock().unwrap().log()),
+                IODirection::Out => self.outputs.get(&id).and_then(|output| output.try_lock().unwrap().log()),
+            };
+            let log = match log {
+                Some(log) => log,
+                None => continue,
+            };
+
+            let summary = DailySummary::from_log(&log.try_lock().unwrap(), direction, id, date, offset);
+            if dir.exists() {
+                results.push(append_summary(&dir, &summary));
+            }
+            self.daily.push(summary);
+        }
+        check_results(&results)
+    }
+
+    /// Register a system mode
+    ///
+    /// A previously registered mode of the same name is replaced.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn add_mode(&mut self, mode: Mode) -> &mut Self {
+        self.modes.add(mode);
+        self
+    }
+
+    /// Switch to mode `name` every day at `at`
+    ///
+    /// Schedules are checked by [`Group::attempt_mode_schedule()`].
+    ///
+    /// # Parameters
+    ///
+    /// - `at`: Time of day in UTC
+    /// - `name`: Name of registered mode
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn schedule_mode<N>(&mut self, at: NaiveTime, name: N) -> &mut Self
+    where
+        N: Into<String>
+    {
+        self.modes.schedule(at, name.into());
+        self
+    }
+
+    /// Select mode and apply its parameter overrides
+    ///
+    /// The change is recorded in [`Group::mode_history()`] and, if the group directory exists,
+    /// appended to [`crate::storage::MODE_HISTORY_FILENAME`].
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of registered mode
+    /// - `by`: Name of user or process changing mode
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` once mode is selected. Overrides that cannot be applied are printed to stderr.
+    /// - `Err` if no mode named `name` is registered
+    pub fn set_mode<B>(&mut self, name: &str, by: B) -> Result<(), ErrorType>
+    where
+        B: Into<String>
+    {
+        let mode = self.modes.get(name)
+            .ok_or_else(|| ActionError::UnknownMode { name: name.to_string() })?
+            .clone();
+
+        let now = now();
+        let mut results = Vec::new();
+        for o in mode.overrides() {
+            results.push(self.apply_override(o, now));
+        }
+
+        let dir = self.full_path();
+        let change = self.modes.record(name, by.into(), now);
+        if dir.exists() {
+            results.push(append_history(&dir, change));
+        }
+        check_results(&results)
+    }
+
+    /// Name of current mode, if any has been selected
+    pub fn mode(&self) -> Option<&String> {
+        self.modes.current()
+    }
+
+    /// Audit trail of mode changes since startup
+    pub fn mode_history(&self) -> &[ModeChange] {
+        self.modes.history()
+    }
+
+    /// Ramp parameters of an action when modes change, instead of applying them as a step
+    ///
+    /// Only numeric parameters are ramped. Ramps are advanced by [`Group::attempt_mode_schedule()`].
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: Id of input whose publisher owns the action
+    /// - `action`: Name of action
+    /// - `rate`: Maximum change of each parameter per second
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_ramp_rate<A>(&mut self, id: IdType, action: A, rate: f32) -> &mut Self
+    where
+        A: Into<String>
+    {
+        self.modes.set_rate(id, action.into(), rate);
+        self
+    }
+
+    /// Check if any parameter is still ramping towards the value of the current mode
+    pub fn is_ramping(&self) -> bool {
+        !self.modes.ramps().is_empty()
+    }
+
+    /// Apply scheduled mode changes and advance parameter ramps
+    ///
+    /// This should be called regularly from the main event loop.
+    pub fn attempt_mode_schedule(&mut self) -> Result<(), ErrorType> {
+        self.attempt_mode_schedule_at(now())
+    }
+
+    /// Apply mode changes scheduled between the previous check and `now`, and advance parameter
+    /// ramps to `now`
+    ///
+    /// The first call does not change mode. If several switching times have passed, only the most
+    /// recent is applied.
+    pub fn attempt_mode_schedule_at(&mut self, now: DateTime<Utc>) -> Result<(), ErrorType> {
+        let mut results = Vec::new();
+        let steps: Vec<_> = self.modes.ramps().iter()
+            .map(|ramp| (ramp.target.clone(), ramp.value_at(now).0))
+            .collect();
+        for (target, value) in steps {
+            results.push(self.set_parameter(target.input, &target.action, &target.parameter, value));
+        }
+        self.modes.finish_ramps(now);
+        check_results(&results)?;
+
+        match self.modes.due(now) {
+            Some(name) if self.modes.current() != Some(&name) => self.set_mode(&name, "schedule"),
+            _ => Ok(()),
+        }
+    }
+
+    /// Apply parameter override of a mode, either directly or by starting a ramp
+    fn apply_override(&mut self, o: &ParameterOverride, now: DateTime<Utc>) -> Result<(), ErrorType> {
+        let rate = self.modes.rate(o.input, &o.action);
+        let from = self.parameter(o.input, &o.action, &o.parameter).and_then(|value| value.as_float());
+
+        match (rate, from, o.value.as_float()) {
+            (Some(rate), Some(from), Some(_)) if rate > 0.0 => {
+                self.modes.start_ramp(o.clone(), from, rate, now);
+                Ok(())
+            }
+            _ => {
+                self.modes.cancel_ramp(o);
+                self.set_parameter(o.input, &o.action, &o.parameter, o.value)
+            }
+        }
+    }
+
+    /// Get parameter of an action subscribed to input `id`
+    ///
+    /// # Returns
+    ///
+    /// `None` if input, action, or parameter does not exist
+    pub fn parameter(&self, id: IdType, action: &str, parameter: &str) -> Option<RawValue> {
+        let input = self.inputs.get(&id)?.try_lock().unwrap();
+        input.publisher().as_ref()?
+            .subscribers().iter()
+            .find(|subscriber| subscriber.name() == action)?
+            .parameter(parameter)
+    }
+
+    /// Set parameter of an action subscribed to input `id`
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::action::Action::set_parameter()`]
+    pub fn set_parameter(&mut self, id: IdType, action: &str, parameter: &str, value: RawValue) -> Result<(), ErrorType> {
+        let input = self.inputs.get(&id)
+            .ok_or_else(|| ContainerError::KeyMissing { key: id.to_string() })?;
+        let mut input = input.try_lock().unwrap();
+        let publisher = input.publisher_mut().as_mut()
+            .ok_or_else(|| ActionError::UnknownAction { name: action.to_string() })?;
+        publisher.set_parameter(action, parameter, value)
+    }
+
+    /// Attach a secondary log stream to an input
+    ///
+    /// Events read during [`Group::poll()`] are passed to every stream attached to the originating
+    /// input, in addition to the device log. This allows raw samples and decimated data to be
+    /// written to different backends.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: Id of input. Input does not need to exist yet.
+    /// - `stream`: [`LogStream`] to attach
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn add_stream(&mut self, id: IdType, stream: LogStream) -> &mut Self {
+        self.streams.entry(id).or_default().push(stream);
+        self
+    }
+
+    /// Store pending data of all decimated streams
+    ///
+    /// Should be called before shutdown so that partial windows are not lost.
+    pub fn flush_streams(&mut self) -> Result<(), ErrorType> {
+        let mut results = Vec::new();
+        for (id, streams) in self.streams.iter_mut() {
+            if let Some(input) = self.inputs.get(id) {
+                let input = input.try_lock().unwrap();
+                for stream in streams.iter_mut() {
+                    results.push(stream.flush(input.metadata()));
+                }
+            }
+        }
+        check_results(&results)
+    }
+
+    /// Set strategy used to schedule polling cycles
+    ///
+    /// # Parameters
+    ///
+    /// - `schedule`: [`PollSchedule`] to use. Defaults to [`PollSchedule::Drift`].
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_schedule(&mut self, schedule: PollSchedule) -> &mut Self {
+        self.schedule = schedule;
+        self.next_poll = None;
+        self
+    }
+
+    pub fn schedule(&self) -> PollSchedule {
+        self.schedule
+    }
+
+    /// Statistics of delay between scheduled and actual polling
+    pub fn jitter(&self) -> &JitterStats {
+        &self.jitter
+    }
+
+    /// Clear jitter statistics
+    pub fn reset_jitter(&mut self) {
+        self.jitter = JitterStats::default();
+    }
+
+    /// Monotonic time of first poll
+    ///
+    /// Polling begins immediately, unless using [`PollSchedule::FixedPhase`], where polling begins
+    /// on the next wall-clock multiple of interval. When state was restored by [`Persistent::load()`],
+    /// polling begins one interval after the last poll before the restart.
+    fn first_poll(&self, started: Instant) -> Instant {
+        match self.schedule {
+            PollSchedule::Drift if self.resumed => {
+                let due = self.last_execution + self.tick();
+                started + (due - now()).to_std().unwrap_or_default()
+            }
+            PollSchedule::Drift => started,
+            PollSchedule::FixedPhase => {
+                let interval = self.tick().num_milliseconds();
+                if interval <= 0 {
+                    return started;
+                }
+                let phase = now().timestamp_millis().rem_euclid(interval);
+                match phase {
+                    0 => started,
+                    _ => started + StdDuration::from_millis((interval - phase) as u64),
+                }
+            }
+        }
+    }
+
+    /// Record jitter and set time of next poll
+    ///
+    /// # Parameters
+    ///
+    /// - `scheduled`: Time current poll was scheduled for
+    /// - `started`: Time current poll began
+    fn schedule_next(&mut self, scheduled: Instant, started: Instant) {
+        self.jitter.record(started - scheduled);
+
+        let interval = self.tick().to_std().unwrap_or_default();
+        let next = match self.schedule {
+            PollSchedule::Drift => started + interval,
+            PollSchedule::FixedPhase => {
+                let mut next = scheduled + interval;
+                // skip cycles that were missed entirely
+                while next <= started && !interval.is_zero() {
+                    next += interval;
+                    self.jitter.missed += 1;
+                }
+                next
+            }
+        };
+        self.next_poll = Some(next);
+    }
+
+    /// Rename a device and migrate its stored logs
+    ///
+    /// Since log filenames and device directories embed the device name, renaming a device directly
+    /// orphans existing logs. Instead, the device directory and log file are moved, and metadata
+    /// embedded in the log file is updated.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: Id of input or output device
+    /// - `name`: New name for device
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] that is:
+    ///
+    /// - `Ok` when device has been renamed
+    /// - `Err` if no device has `id`, if both an input and output have `id`, or if migration failed.
+    ///   The device retains its original name upon error.
+    pub fn rename_device<S>(&mut self, id: IdType, name: S) -> Result<(), ErrorType>
+    where
+        S: Into<String>,
+    {
+        let name = name.into();
+        match (self.inputs.get(&id), self.outputs.get(&id)) {
+            (Some(input), None) => migrate_device(input.try_lock().unwrap().deref_mut(), name),
+            (None, Some(output)) => migrate_device(output.try_lock().unwrap().deref_mut(), name),
+            (Some(_), Some(_)) => Err(Box::new(ContainerError::AmbiguousKey { key: id.to_string() })),
+            (None, None) => Err(Box::new(ContainerError::KeyMissing { key: id.to_string() })),
+        }
+    }
+
+    /// Convert logs saved in the legacy layout into the current layout
+    ///
+    /// Older versions saved every log directly in the group directory, identified only by device id
+    /// and an optional name, without embedded [`DeviceMetadata`]. Each legacy log is matched to a
+    /// device of this group and rewritten at [`Log::full_path()`] with metadata embedded. The number
+    /// of events is verified before the legacy file is renamed with
+    /// [`crate::storage::MIGRATED_SUFFIX`], so that it is kept as a backup and not migrated again.
+    ///
+    /// Called by [`Persistent::load()`] before device logs are loaded. Legacy logs matching no
+    /// device, or devices without a log directory, are left in place.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with a [`Migration`] for every converted log. Empty if group directory does not exist.
+    /// - `Err` if a legacy log matches both an input and output, if a device already has a saved
+    ///   log, or if events were lost. Logs converted before the error are kept.
+    pub fn migrate_legacy(&self) -> Result<Vec<Migration>, ErrorType> {
+        let inputs = self.inputs.values().map(|input| {
+            let input = input.try_lock().unwrap();
+            (input.metadata().clone(), input.log())
+        });
+        let outputs = self.outputs.values().map(|output| {
+            let output = output.try_lock().unwrap();
+            (output.metadata().clone(), output.log())
+        });
+
+        let mut targets = Vec::new();
+        for (metadata, log) in inputs.chain(outputs) {
+            if let Some(log) = log {
+                let log = log.try_lock().unwrap();
+                if log.dir().is_some() {
+                    targets.push((metadata, log.full_path()));
+                }
+            }
+        }
+        migrate_dir(&self.full_path(), &targets)
+    }
+
+    /// Apply a metadata patch to every device matching `filter`
+    ///
+    /// Metadata embedded in device logs is updated, and saved log files are rewritten. Every change
+    /// is appended to an audit trail in the group directory, if it exists.
+    ///
+    /// # Parameters
+    ///
+    /// - `filter`: Selects devices to update by their current metadata
+    /// - `patch`: Changes to apply
+    ///
+    /// # Returns
+    ///
+    /// Record of each device whose metadata changed. Devices which already match `patch` are
+    /// omitted. Log files and audit records that cannot be written are printed to stderr.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensd::io::{Device, Input, IOKind, MetadataPatch};
+    /// use sensd::storage::Group;
+    ///
+    /// let mut group = Group::new("");
+    /// group
+    ///     .push_input(Input::new("ph_1", 0, IOKind::PH))
+    ///     .push_input(Input::new("ph_2", 1, IOKind::PH))
+    ///     .push_input(Input::new("temp", 2, IOKind::Temperature));
+    ///
+    /// let changes = group.update_devices(
+    ///     |metadata| metadata.kind == IOKind::PH,
+    ///     &MetadataPatch::default().add_tag("reservoir").set_bounds(0.0, 14.0)).unwrap();
+    ///
+    /// assert_eq!(2, changes.len());
+    /// ```
+    pub fn update_devices<F>(&mut self, filter: F, patch: &MetadataPatch) -> Result<Vec<MetadataChange>, ErrorType>
+    where
+        F: Fn(&DeviceMetadata) -> bool
+    {
+        let mut results = Vec::new();
+        for input in self.inputs.values() {
+            let mut input = input.try_lock().unwrap();
+            if filter(input.metadata()) {
+                results.push(patch_device(input.deref_mut(), patch));
+            }
+        }
+        for output in self.outputs.values() {
+            let mut output = output.try_lock().unwrap();
+            if filter(output.metadata()) {
+                results.push(patch_device(output.deref_mut(), patch));
+            }
+        }
+
+        let dir = self.full_path();
+        let mut changes = Vec::new();
+        for result in results {
+            match result {
+                Ok(Some(change)) => {
+                    if dir.exists() {
+                        if let Err(e) = append_record(&dir.join(METADATA_HISTORY_FILENAME), &change) {
+                            console::error(format!("Could not record metadata change: {}", e));
+                        }
+                    }
+                    changes.push(change);
+                }
+                Ok(None) => (),
+                Err(e) => console::error(e),
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Detect stale storage artifacts left behind by removed devices
+    ///
+    /// Nothing is modified. Use [`Group::clean()`] to remove detected artifacts.
+    pub fn sweep(&self) -> Sweep {
+        Sweep {
+            orphaned_logs: self.orphaned_logs(),
+            dangling_routines: self.dangling_routines(),
+        }
+    }
+
+    /// Delete orphaned log files and discard dangling routines
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing:
+    ///
+    /// - `Ok` with [`Sweep`] of removed artifacts
+    /// - `Err` if any orphaned log could not be deleted. Dangling routines are discarded regardless.
+    pub fn clean(&mut self) -> Result<Sweep, ErrorType> {
+        let mut dangling_routines = 0;
+        for device in self.inputs.values() {
+            if let Some(publisher) = device.try_lock().unwrap().publisher_mut() {
+                dangling_routines += publisher.handler_ref().try_lock().unwrap().purge_dangling();
+            }
+        }
+
+        let orphaned_logs = self.orphaned_logs();
+        for path in orphaned_logs.iter() {
+            remove_file(path)?;
+        }
+
+        Ok(Sweep { orphaned_logs, dangling_routines })
+    }
+
+    /// Log files within group directory which are not associated with a configured device
+    ///
+    /// Only device subdirectories of [`Group::full_path()`] are searched.
+    fn orphaned_logs(&self) -> Vec<PathBuf> {
+        let mut expected = HashSet::new();
+        for device in self.inputs.values() {
+            if let Some(log) = device.try_lock().unwrap().log() {
+                expected.insert(log.try_lock().unwrap().full_path());
+            }
+        }
+        for device in self.outputs.values() {
+            if let Some(log) = device.try_lock().unwrap().log() {
+                expected.insert(log.try_lock().unwrap().full_path());
+            }
+        }
+
+        let is_log = |path: &Path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with(LOG_FN_PREFIX) && name.ends_with(FILETYPE))
+                .unwrap_or(false)
+        };
+
+        let mut orphaned = Vec::new();
+        let dirs = match read_dir(self.full_path()) {
+            Ok(dirs) => dirs,
+            Err(_) => return orphaned,
+        };
+        for dir in dirs.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()) {
+            if let Ok(files) = read_dir(dir) {
+                orphaned.extend(
+                    files.flatten()
+                        .map(|entry| entry.path())
+                        .filter(|path| is_log(path) && !expected.contains(path)));
+            }
+        }
+        orphaned.sort();
+        orphaned
+    }
+
+    fn dangling_routines(&self) -> usize {
+        self.inputs.values()
+            .filter_map(|device| device.try_lock().unwrap().publisher().as_ref().map(|publisher| {
+                publisher.handler_ref().try_lock().unwrap().dangling()
+            }))
+            .sum()
+    }
+
+    /// Set number of valid readings each input must produce before its actions are enabled
+    ///
+    /// This allows a staged bring-up where controllers are only engaged once sensor readings have
+    /// stabilized. Applies to inputs already in the group and any that are added later.
+    ///
+    /// # Parameters
+    ///
+    /// - `count`: Number of readings to withhold from actions
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    ///
+    /// # See Also
+    ///
+    /// - [`Input::set_warmup()`] for ignoring readings shortly after power-on
+    pub fn set_bring_up(&mut self, count: usize) -> &mut Self {
+        self.bring_up = count;
+        for input in self.inputs.values() {
+            if let Some(publisher) = input.try_lock().unwrap().publisher_mut() {
+                publisher.set_bring_up(count);
+            }
+        }
+        self
+    }
+
+    /// Set status LED to indicate health of polling
+    ///
+    /// # Parameters
+    ///
+    /// - `heartbeat`: [`Heartbeat`] that is updated by [`Group::attempt_routines()`]
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_heartbeat(&mut self, heartbeat: Heartbeat) -> &mut Self {
+        self.heartbeat = Some(Def::new(heartbeat));
+        self
+    }
+
+    /// Getter for heartbeat
+    pub fn heartbeat(&self) -> Option<Def<Heartbeat>> {
+        self.heartbeat.clone()
+    }
+
+    /// Signal active alarms on buzzers and strobes
+    ///
+    /// Requires an alarm handler set by [`Group::set_alarm_handler()`].
+    ///
+    /// # Parameters
+    ///
+    /// - `annunciator`: [`AlarmAnnunciator`] that is updated by [`Group::attempt_routines()`]
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_annunciator(&mut self, annunciator: AlarmAnnunciator) -> &mut Self {
+        self.annunciator = Some(Def::new(annunciator));
+        self
+    }
+
+    /// Getter for annunciator
+    pub fn annunciator(&self) -> Option<Def<AlarmAnnunciator>> {
+        self.annunciator.clone()
+    }
+
+    /// Quarantine inputs which repeatedly fail to be read
+    ///
+    /// # Parameters
+    ///
+    /// - `policy`: Failure budget, backoff schedule, and treatment of dependent actions
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    ///
+    /// # See Also
+    ///
+    /// - [`QuarantinePolicy`] for how inputs are quarantined and re-admitted
+    pub fn set_quarantine(&mut self, policy: QuarantinePolicy) -> &mut Self {
+        self.quarantine = Some(Quarantine::new(policy));
+        self
+    }
+
+    /// Set handler used to raise an alarm while an input is quarantined
+    ///
+    /// The handler is also used to suppress alarms during maintenance windows (see
+    /// [`Group::schedule_maintenance()`]).
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_alarm_handler(&mut self, alarms: Def<AlarmHandler>) -> &mut Self {
+        self.alarms = Some(alarms);
+        self
+    }
+
+    /// Getter for handler set by [`Group::set_alarm_handler()`]
+    pub fn alarm_handler(&self) -> Option<&Def<AlarmHandler>> {
+        self.alarms.as_ref()
+    }
+
+    /// Ids of inputs which are quarantined
+    pub fn quarantined(&self) -> Vec<IdType> {
+        self.quarantine.as_ref()
+            .map(|quarantine| quarantine.quarantined())
+            .unwrap_or_default()
+    }
+
+    /// Time at which re-admission of a quarantined input is next attempted
+    pub fn quarantined_until(&self, id: IdType) -> Option<DateTime<Utc>> {
+        self.quarantine.as_ref().and_then(|quarantine| quarantine.until(id))
+    }
+
+    /// Manually re-admit a quarantined input without waiting for a trial read
+    ///
+    /// Dependent actions are released and the alarm is cleared.
+    ///
+    /// # Returns
+    ///
+    /// `true` if input was quarantined
+    pub fn readmit(&mut self, id: IdType) -> bool {
+        let (quarantine, input) = match (&mut self.quarantine, self.inputs.get(&id)) {
+            (Some(quarantine), Some(input)) => (quarantine, input),
+            _ => return false,
+        };
+        if !quarantine.readmit(id) {
+            return false;
+        }
+        let mut binding = input.try_lock().unwrap();
+        apply_transition(quarantine, &self.alarms, &mut binding, Transition::Readmitted);
+        true
+    }
+
+    /// Register a callback for every successful read by an input device
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: Function accepting metadata of originating device and generated [`IOEvent`]
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    ///
+    /// # Panics
+    ///
+    /// If hooks are currently being dispatched
+    pub fn on_read<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&DeviceMetadata, &IOEvent) + 'static
+    {
+        self.hooks.try_lock().unwrap().on_read(hook);
+        self
+    }
+
+    /// Register a callback for every write to an output device
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: Function accepting metadata of originating device and generated [`IOEvent`]
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    ///
+    /// # Panics
+    ///
+    /// If hooks are currently being dispatched
+    pub fn on_write<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&DeviceMetadata, &IOEvent) + 'static
+    {
+        self.hooks.try_lock().unwrap().on_write(hook);
+        self
+    }
+
+    /// Register a callback for every device error
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: Function accepting metadata of originating device and returned [`DeviceError`]
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    ///
+    /// # Panics
+    ///
+    /// If hooks are currently being dispatched
+    pub fn on_error<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&DeviceMetadata, &DeviceError) + 'static
+    {
+        self.hooks.try_lock().unwrap().on_error(hook);
+        self
+    }
+
+    //
+    // Getters
+
+
+    #[inline]
+    /// Getter for `interval`
+    ///
+    /// # Notes
+    ///
+    /// Since this is frequently used in iterators and polling, this
+    /// method is marked inline to avoiding jumping in memory.
+    ///
+    /// # Returns
+    ///
+    /// Immutable reference to `interval`
+    pub fn interval(&self) -> &Duration {
+        &self.interval
+    }
+
+    /// Time between polls
+    ///
+    /// Shortest of [`Group::interval()`] and the interval of any input (see
+    /// [`Input::set_interval()`]).
+    ///
+    /// # Notes
+    ///
+    /// Value is cached, and only reflects inputs added or removed directly through
+    /// [`Group::inputs`] once they are next polled.
+    pub fn tick(&self) -> Duration {
+        self.tick
+    }
+
+    /// Setter for `interval`
+    ///
+    /// Inputs without an interval of their own are read at `interval`.
+    ///
+    /// # Parameters
+    ///
+    /// - `interval`: any value that can be coerced into [`Duration`]
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+        self.tick = self.inputs.values()
+            .filter_map(|input| input.try_lock().ok().and_then(|input| input.interval()))
+            .fold(interval, Duration::min);
+
+        // realign phase to new interval
+        if self.schedule == PollSchedule::FixedPhase {
+            self.next_poll = None;
+        }
+    }
+}
+
+impl Group {
+    /// Sign saved log of a device. Does nothing without a signing key or a saved log.
+    fn sign_log(&self, log: Option<Def<Log>>) -> Result<(), ErrorType> {
+        let (key, log) = match (&self.signing_key, log) {
+            (Some(key), Some(log)) => (key, log),
+            _ => return Ok(()),
+        };
+        let path = log.lock().unwrap().full_path();
+        match path.exists() {
+            true => sign(path, key),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Only save and load log data since [`Group`] is statically initialized
+/// If `&None` is given to either methods, then current directory is used.
+impl Persistent for Group {
+    /// Save all device logs and group runtime state
+    ///
+    /// Logs are signed after being saved when a key has been set by [`Group::set_signing_key()`].
+    ///
+    /// Interval, schedule, and time of last poll are saved as [`GroupState`] once the group directory
+    /// exists, so that [`Persistent::load()`] resumes the polling phase.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any single save fails. However, failure is silent and
+    /// does not prevent saving other device logs.
+    ///
+    /// # Panics
+    ///
+    /// Panics when any single input or output device cannot be locked.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing:
+    ///
+    /// - `Ok` that is empty when saving occurred without error.
+    /// - `Err` containing the first error stored. There may be more errors that were
+    ///   not returned. An error occurring does not halt saving other logs.
+    fn save(&self) -> Result<(), ErrorType> {
+        let mut results = Vec::new();
+
+        for device in self.inputs.values() {
+            let binding = device.try_lock().expect("Could not lock input");
+            results.push(
+                binding.save());
+            results.push(self.sign_log(binding.log()));
+        }
+
+        for device in self.outputs.values() {
+            let binding = device.try_lock().expect("Could not lock output");
+            results.push(
+                binding.save());
+            results.push(self.sign_log(binding.log()));
+        }
+
+        if self.full_path().exists() {
+            let state = GroupState {
+                name: self.name.clone(),
+                root: self.root.deref(),
+                interval: self.interval.num_milliseconds(),
+                schedule: self.schedule,
+                last_execution: self.last_execution,
+                saved: now(),
+                format: FORMAT_VERSION,
+                written_by: Some(env!("CARGO_PKG_VERSION").to_string()),
+            };
+            results.push(state.save(self.full_path()));
+        }
+
+        check_results(&results)
+    }
+
+    /// Load all device logs and group runtime state
+    ///
+    /// Logs saved in the legacy layout are first converted by [`Group::migrate_legacy()`]. Actions
+    /// are then re-seeded with archived events by [`crate::action::Action::on_attach()`].
+    ///
+    /// When [`GroupState`] was saved, interval, schedule, and time of last poll are restored. With
+    /// [`PollSchedule::Drift`], the next poll is then due one interval after the last poll before
+    /// the restart, rather than immediately. [`PollSchedule::FixedPhase`] is anchored to wall-clock
+    /// time and needs no state to resume its phase.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any single load fails. However, failure is silent and does not prevent
+    /// loading other device logs.
+    ///
+    /// # Panics
+    ///
+    /// Panics when any single input or output device cannot be locked.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing:
+    ///
+    /// - `Ok` that is empty when loading occurred without error.
+    /// - `Err` containing the first error stored. There may be more errors that were
+    ///   not returned. An error occurring does not halt loading other logs.
+    fn load(&mut self) -> Result<(), ErrorType> {
+        let mut results = Vec::new();
+
+        if let Err(e) = self.migrate_legacy() {
+            results.push(Err(e));
+        }
+
+        for device in self.outputs.values() {
+            let mut binding = device.try_lock().unwrap();
+            results.push(
+                binding.load());
+        }
+
+        for device in self.inputs.values() {
+            let mut binding = device.try_lock().unwrap();
+            results.push(
+                binding.load());
+
+            binding.restore_bias_correction();
+
+            // re-seed actions with archived events
+            if let Some(publisher) = binding.publisher_mut() {
+                publisher.attach();
+            }
+        }
+
+        match GroupState::load(self.full_path()) {
+            Ok(Some(state)) => {
+                self.schedule = state.schedule;
+                self.set_interval(state.interval());
+                self.last_execution = state.last_execution;
+                self.resumed = true;
+                self.next_poll = None;
+            }
+            Ok(None) => (),
+            Err(e) => results.push(Err(e)),
+        }
+
+        check_results(&results)
+    }
+}
+
+impl Name for Group {
+    /// Getter for `name`
+    ///
+    /// # Returns
+    ///
+    /// Immutable reference to `name`
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Setter for `name`
+    ///
+    /// # Parameters
     ///
     /// - `name`: new name for group. Uses `Into<_>` to coerce into `String`.
     fn set_name<S>(&mut self, name: S)
@@ -467,17 +2435,128 @@ impl RootDirectory for Group {
     }
 }
 
+/// Id of device which was captured by a snapshot as `captured` under `id`
+///
+/// Devices are matched by UUID. Otherwise, the device with the same id is used, and adopts the UUID
+/// of `captured` if it has the same name.
+fn match_device<D: Device + Directory>(devices: &DeviceContainer<IdType, D>, id: IdType, captured: &DeviceMetadata) -> IdType {
+    if captured.uuid.is_nil() {
+        return id;
+    }
+    let found = devices.iter()
+        .find(|(_, device)| device.try_lock().unwrap().metadata().uuid == captured.uuid);
+    if let Some((id, _)) = found {
+        return *id;
+    }
+
+    if let Some(device) = devices.get(&id) {
+        let mut device = device.try_lock().unwrap();
+        if device.metadata().name == captured.name {
+            device.metadata_mut().uuid = captured.uuid;
+            if let Some(log) = device.log() {
+                log.try_lock().unwrap().set_metadata_ref(device.metadata().clone());
+            }
+        }
+    }
+    id
+}
+
+/// Read input using `read`, updating quarantine and passing the reading to streams of input
+fn read_input<F>(
+    input: &mut Input,
+    quarantine: &mut Option<Quarantine>,
+    alarms: &Option<Def<AlarmHandler>>,
+    streams: &mut HashMap<IdType, Vec<LogStream>>,
+    wall: DateTime<Utc>,
+    read: F,
+) -> Result<IOEvent, DeviceError>
+where
+    F: FnOnce(&mut Input) -> Result<IOEvent, DeviceError>
+{
+    let id = input.id();
+    let result = read(input);
+    if let Some(quarantine) = quarantine {
+        let transition = match &result {
+            Ok(_) => quarantine.success(id),
+            Err(_) => quarantine.failure(id, wall),
+        };
+        if let Some(transition) = transition {
+            apply_transition(quarantine, alarms, input, transition);
+        }
+    }
+
+    if let (Ok(event), Some(streams)) = (&result, streams.get_mut(&id)) {
+        for stream in streams.iter_mut() {
+            if let Err(e) = stream.push(input.metadata(), event) {
+                console::error(format!("Could not write to stream {}: {}", stream.name(), e));
+            }
+        }
+    }
+    result
+}
+
+/// Hold or release dependent actions of an input, and raise or clear its alarm
+fn apply_transition(quarantine: &Quarantine, alarms: &Option<Def<AlarmHandler>>, input: &mut Input, transition: Transition) {
+    let name = quarantine_alarm(input.id());
+    match transition {
+        Transition::Quarantined(until) => {
+            let message = format!("{} quarantined after {} consecutive failed reads. Retrying at {}",
+                                  input.metadata(), quarantine.failures(input.id()), until);
+            console::warning(&message);
+            if let Some(publisher) = input.publisher_mut() {
+                publisher.hold(quarantine.policy().hold());
+            }
+            if let Some(alarms) = alarms {
+                let alarm = Alarm::new(name, quarantine.policy().severity(), message, now())
+                    .set_device(input.name().as_str());
+                alarms.lock().unwrap().raise_alarm(alarm);
+            }
+        }
+        Transition::Extended(_) => (),
+        Transition::Readmitted => {
+            // calibration continues to withhold data
+            if !input.is_calibrating() {
+                if let Some(publisher) = input.publisher_mut() {
+                    publisher.release();
+                }
+            }
+            if let Some(alarms) = alarms {
+                alarms.lock().unwrap().clear(&name);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use chrono::Duration;
-    use std::fs::remove_dir_all;
+    use chrono::{Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+    use std::cell::Cell;
+    use std::fs::{read_to_string, remove_dir_all, write};
     use std::path::{Path, PathBuf};
-
-    use crate::io::{Device, Input, IOKind, Output};
-    use crate::storage::{Directory, Group, RootDirectory, RootPath};
+    use std::rc::Rc;
+
+    use crate::action::{Action, Heartbeat, IOCommand, Ramp, Routine, SchedRoutineHandler, Trigger, WriteTarget};
+    use crate::action::actions::{PID, Threshold};
+    use crate::alarm::{Alarm, AlarmHandler, AlarmSeverity};
+    use crate::helpers::Def;
+    use crate::inspect::read_log;
+    use crate::io::{Device, DeviceGetters, DeviceMetadata, EnergyMeter, IdType, Input, IODirection, IOEvent, IOKind, LoadLimiter, MetadataPatch, Output, RawValue};
+    use crate::name::Name;
+    use crate::report;
+    use crate::errors::{DeviceError, ErrorType};
+    use crate::storage::{verify, verify_dir, Chronicle, Decimation, Difference, Directory, Document, Group, Log, LogStream, MaintenanceTarget, MaintenanceWindow, Mode, Persistent, PollSchedule, QuarantinePolicy, RemoteStorage, RootDirectory, RootPath, SigningKey, Tier, Variables, METADATA_HISTORY_FILENAME, MIGRATED_SUFFIX, MODE_HISTORY_FILENAME};
+    use crate::telemetry::Backoff;
+    use crate::testkit::{FakeClock, FaultInjector};
 
     const DIR_PATH: &str = "/tmp/sensd_tests";
 
+    /// Empty directory unique to a single test, since tests run in parallel
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("sensd_group_{}_test", name));
+        let _ = remove_dir_all(&root);
+        root
+    }
+
     #[test]
     /// Test that constructor accepts `name` as `&str` or `String`
     fn new_name_parameter() {
@@ -591,6 +2670,640 @@ mod tests {
         }
     }
 
+    #[test]
+    /// Test that hooks are called for reads, writes, and errors of grouped devices
+    fn hooks() {
+        let reads = Rc::new(Cell::new(0));
+        let writes = Rc::new(Cell::new(0));
+        let errors = Rc::new(Cell::new(0));
+
+        let mut group = Group::new("name");
+        group
+            .push_input(Input::new("", 0, None)
+                .set_command(IOCommand::Input(RawValue::default)))
+            .push_input(Input::new("", 1, None))
+            .push_output(Output::new("", 0, None)
+                .set_command(IOCommand::Output(|_| Ok(()))));
+
+        {
+            let (reads, writes, errors) = (reads.clone(), writes.clone(), errors.clone());
+            group
+                .on_read(move |_, _| reads.set(reads.get() + 1))
+                .on_write(move |_, _| writes.set(writes.get() + 1))
+                .on_error(move |_, _| errors.set(errors.get() + 1));
+        }
+
+        assert!(group.poll().executed());
+        group.outputs.get(&0).unwrap()
+            .try_lock().unwrap()
+            .write(RawValue::Binary(true)).unwrap();
+
+        assert_eq!(1, reads.get());
+        assert_eq!(1, writes.get());
+        assert_eq!(1, errors.get());
+    }
+
+    #[test]
+    /// Assert that device errors during poll fault heartbeat
+    fn heartbeat() {
+        let mut group = Group::with_interval("name", Duration::nanoseconds(1));
+        group
+            .set_heartbeat(Heartbeat::new(IOCommand::Output(|_| Ok(()))))
+            .push_input(Input::new("", 0, None));
+
+        assert!(group.poll().executed());
+        assert!(group.heartbeat().unwrap().try_lock().unwrap().is_faulted());
+
+        let mut group = Group::with_interval("name", Duration::nanoseconds(1));
+        group
+            .set_heartbeat(Heartbeat::new(IOCommand::Output(|_| Ok(()))))
+            .push_input(Input::new("", 0, None)
+                .set_command(IOCommand::Input(RawValue::default)));
+
+        group.heartbeat().unwrap().try_lock().unwrap().fault();
+        assert!(group.poll().executed());
+        assert!(!group.heartbeat().unwrap().try_lock().unwrap().is_faulted());
+    }
+
+    #[test]
+    fn poll_report() {
+        let mut input = Input::new("", 0, None)
+            .set_command(IOCommand::Input(|| RawValue::Float(2.0)))
+            .init_publisher();
+        let action = Threshold::new("fan", RawValue::Float(1.0), Trigger::GT)
+            .set_output(WriteTarget::variable(Variables::default(), "fan"));
+        input.publisher_mut().as_mut().unwrap().subscribe(action.into_boxed());
+
+        let mut group = Group::with_interval("", Duration::seconds(10));
+        group
+            .push_input(input)
+            .push_input(Input::new("", 1, None));
+
+        let report = group.poll();
+        assert!(report.executed());
+        assert_eq!(2, report.outcomes.len());
+        assert_eq!(1, report.events().count());
+        assert_eq!(1, report.errors().count());
+        let failed = report.outcomes.iter().find(|outcome| outcome.id == 1).unwrap();
+        assert!(failed.result.is_err());
+        assert_eq!(1, report.propagated);
+
+        let report = group.poll();
+        assert!(report.early && !report.paused);
+        assert!(report.outcomes.is_empty());
+        assert_eq!(std::time::Duration::ZERO, report.duration);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn poll_async() {
+        use std::sync::{Arc, Condvar, Mutex};
+        use crate::errors::ReadError;
+
+        let mut group = Group::with_interval("", Duration::seconds(10));
+        let started = Arc::new((Mutex::new(0), Condvar::new()));
+        for id in 0..3 {
+            let started = started.clone();
+            group.push_input(Input::new("", id, None)
+                .set_command(IOCommand::TryInputFn(Arc::new(move || {
+                    // each read only completes once all reads have begun
+                    let (count, all) = &*started;
+                    let mut count = count.lock().unwrap();
+                    *count += 1;
+                    all.notify_all();
+                    let (count, _) = all.wait_timeout_while(count, std::time::Duration::from_secs(5), |count| *count < 3).unwrap();
+                    match *count {
+                        3 => Ok(RawValue::Float(1.0)),
+                        _ => Err(ReadError::Timeout),
+                    }
+                }))));
+        }
+        group
+            .push_input(Input::new("", 3, None)
+                .set_command(IOCommand::AsyncInputFn(Arc::new(|| Box::pin(async { Ok(RawValue::Float(2.0)) })))))
+            .push_input(Input::new("", 4, None));
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let report = runtime.block_on(group.poll_async());
+        assert!(report.executed());
+        assert_eq!(5, report.outcomes.len());
+        assert_eq!(4, report.events().count());
+        assert_eq!(1, report.errors().count());
+
+        assert!(runtime.block_on(group.poll_async()).early);
+    }
+
+    #[test]
+    /// Assert that asynchronous commands fail when polled synchronously
+    fn poll_async_command() {
+        let mut group = Group::new("");
+        group.push_input(Input::new("", 0, None)
+            .set_command(IOCommand::AsyncInputFn(std::sync::Arc::new(|| Box::pin(async { Ok(RawValue::Float(2.0)) })))));
+
+        let report = group.poll();
+        assert!(matches!(report.outcomes[0].result, Err(DeviceError::Asynchronous { .. })));
+    }
+
+    #[test]
+    fn input_interval() {
+        let mut group = Group::with_interval("", Duration::seconds(10));
+        group
+            .push_input(Input::new("ph", 0, None)
+                .set_command(IOCommand::Input(|| RawValue::Float(6.5))))
+            .push_input(Input::new("flow", 1, None)
+                .set_command(IOCommand::Input(|| RawValue::Float(1.0)))
+                .set_interval(Duration::milliseconds(20)));
+        assert_eq!(Duration::milliseconds(20), group.tick());
+
+        assert_eq!(2, group.poll().outcomes.len());
+        assert!(group.poll().early);
+
+        // group interval has not elapsed
+        std::thread::sleep(std::time::Duration::from_millis(25));
+        let report = group.poll();
+        assert!(report.executed());
+        assert_eq!(vec![1], report.outcomes.iter().map(|outcome| outcome.id).collect::<Vec<_>>());
+
+        // new schedule reads every input
+        group.resume();
+        assert_eq!(2, group.poll().outcomes.len());
+
+        // tick does not lock inputs
+        {
+            let _guard = group.inputs.get(&1).unwrap().lock().unwrap();
+            assert_eq!(Duration::milliseconds(20), group.tick());
+        }
+
+        // re-added input does not inherit schedule of removed input
+        group.inputs.remove(&0);
+        group.push_input(Input::new("ph", 0, None)
+            .set_command(IOCommand::Input(|| RawValue::Float(6.5))));
+        std::thread::sleep(std::time::Duration::from_millis(25));
+        assert_eq!(2, group.poll().outcomes.len());
+
+        // interval is persisted with metadata
+        assert_eq!(Some(20), group.snapshot().inputs[&1].interval);
+    }
+
+    #[test]
+    fn immediate() {
+        let writes = Rc::new(Cell::new(0));
+        let mut group = Group::with_interval("", Duration::minutes(1));
+        group
+            .push_input(Input::new("", 0, None)
+                .set_command(IOCommand::Input(|| RawValue::Float(1.0)))
+                .init_log())
+            .push_input(Input::new("", 1, None))
+            .push_output(Output::new("", 0, None)
+                .set_command(IOCommand::Output(|_| Ok(()))));
+        {
+            let writes = writes.clone();
+            group.on_write(move |_, _| writes.set(writes.get() + 1));
+        }
+
+        assert!(group.poll().executed());
+        group.read_now(0).unwrap();
+        let input = group.inputs.get(&0).unwrap().clone();
+        assert_eq!(2, input.try_lock().unwrap().log().unwrap().try_lock().unwrap().iter().count());
+
+        // schedule is unaffected
+        assert!(group.poll().early);
+
+        assert!(group.read_now(1).is_err());
+        assert!(group.read_now(2).is_err());
+
+        let event = group.write_now(0, RawValue::Binary(true)).unwrap();
+        assert_eq!(RawValue::Binary(true), event.value);
+        assert_eq!(1, writes.get());
+        assert!(group.write_now(1, RawValue::Binary(true)).is_err());
+    }
+
+    #[test]
+    fn bring_up() {
+        let mut group = Group::with_interval("name", Duration::nanoseconds(1));
+        group
+            .push_input(Input::new("", 0, None)
+                .set_command(IOCommand::Input(RawValue::default))
+                .init_publisher())
+            .push_input(Input::new("", 1, None)
+                .set_command(IOCommand::Input(RawValue::default))
+                .set_warmup(Duration::hours(1)))
+            .set_bring_up(2);
+
+        let enabled = |group: &Group| group.inputs.get(&0).unwrap()
+            .try_lock().unwrap()
+            .publisher().as_ref().unwrap()
+            .is_enabled();
+
+        for _ in 0..2 {
+            assert!(!enabled(&group));
+            assert!(group.poll().executed());
+        }
+        assert!(enabled(&group));
+
+        // input that is still warming up is never read
+        assert!(group.inputs.get(&1).unwrap().try_lock().unwrap().state().is_none());
+    }
+
+    #[test]
+    fn pause() {
+        let mut group = Group::with_interval("name", Duration::nanoseconds(1));
+        group
+            .push_input(Input::new("", 0, None)
+                .set_command(IOCommand::Input(RawValue::default)))
+            .push_output(Output::new("", 0, None)
+                .set_command(IOCommand::Output(|_| Ok(())))
+                .set_safe_state(RawValue::Binary(false)))
+            .push_output(Output::new("", 1, None)
+                .set_command(IOCommand::Output(|_| Ok(()))));
+
+        group.pause(true);
+        assert!(!group.poll().executed());
+        assert!(group.health().paused);
+
+        let state = |id| *group.outputs.get(&id).unwrap().try_lock().unwrap().state();
+        assert_eq!(Some(RawValue::Binary(false)), state(0));
+        assert_eq!(None, state(1));
+
+        group.resume();
+        assert!(group.poll().executed());
+    }
+
+    #[test]
+    /// Assert that outputs energized by actions are released, and state persisted, on shutdown
+    fn shutdown() {
+        let root = temp_root("shutdown");
+        let mut group = Group::with_root("shutdown", &root);
+        group.push_output(Output::new("", 0, None)
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .init_log());
+        let output = group.outputs.get(&0).unwrap().clone();
+
+        let mut input = Input::new("", 0, None)
+            .set_command(IOCommand::Input(|| RawValue::Float(5.0)))
+            .init_publisher();
+        let publisher = input.publisher_mut().as_mut().unwrap();
+        let pid = PID::new("pid", 7.5, 10.0)
+            .set_p(1.0, 10.0)
+            .set_output(output.clone())
+            .set_handler(publisher.handler_ref());
+        publisher.subscribe(pid.into_boxed());
+        group.push_input(input);
+
+        let state = || *output.try_lock().unwrap().state();
+        assert!(group.poll().executed());
+        assert_eq!(Some(RawValue::Binary(true)), state());
+
+        group.pause(false);
+        assert_eq!(Some(RawValue::Binary(false)), state());
+
+        group.resume();
+        assert!(group.poll().executed());
+        assert_eq!(Some(RawValue::Binary(true)), state());
+
+        group.shutdown().unwrap();
+        assert_eq!(Some(RawValue::Binary(false)), state());
+        assert!(group.is_paused());
+        assert!(Group::with_root("shutdown", &root).load_snapshot().unwrap());
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    fn sweep() {
+        let root = temp_root("sweep");
+        let mut group = Group::with_root("sweep", &root);
+        group
+            .push_input(Input::new("kept", 0, None).init_log())
+            .push_output(Output::new("removed", 1, None).init_log());
+
+        let mut input = Input::new("", 2, None)
+            .set_command(IOCommand::Input(|| RawValue::Binary(true)))
+            .init_publisher();
+        {
+            let output = group.outputs.get(&1).unwrap().try_lock().unwrap();
+            input.publisher_mut().as_mut().unwrap().handler_ref().try_lock().unwrap()
+                .push(Routine::new(Utc::now(), RawValue::Binary(true), output.log(), IOCommand::Output(|_| Ok(()))));
+        }
+        group.push_input(input);
+
+        group.save().unwrap();
+        assert!(group.sweep().is_clean());
+
+        group.outputs.remove(&1);
+        let sweep = group.sweep();
+        assert_eq!(1, sweep.orphaned_logs.len());
+        assert_eq!(1, sweep.dangling_routines);
+        assert_eq!(1, group.health().orphaned_logs);
+
+        assert_eq!(sweep, group.clean().unwrap());
+        assert!(group.sweep().is_clean());
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    fn rename_device() {
+        let root = temp_root("rename");
+        let mut group = Group::with_root("rename", &root);
+        group.push_input(Input::new("old", 0, None)
+            .set_command(IOCommand::Input(|| RawValue::Binary(true)))
+            .init_log());
+        group.inputs.get(&0).unwrap().try_lock().unwrap().read().unwrap();
+        group.save().unwrap();
+
+        group.rename_device(0, "new").unwrap();
+        assert!(group.sweep().is_clean());
+
+        let input = group.inputs.get(&0).unwrap().try_lock().unwrap();
+        assert_eq!("new", input.name());
+        assert!(!group.full_path().join("old").exists());
+
+        // embedded metadata is updated
+        let mut log = Log::default().set_dir(input.full_path());
+        log.set_metadata_ref(input.metadata().clone());
+        let path = log.full_path();
+        assert!(path.exists());
+        let contents: serde_json::Value = serde_json::from_str(&read_to_string(path).unwrap()).unwrap();
+        assert_eq!("new", contents["metadata"]["name"]);
+        drop(input);
+
+        assert!(group.rename_device(1, "").is_err());
+        group.push_output(Output::new("", 0, None));
+        assert!(group.rename_device(0, "").is_err());
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that polling continues after system clock moves backwards
+    fn clock_jump() {
+        let start = Utc::now();
+        let clock = FakeClock::install(start);
+
+        let mut group = Group::with_interval("", Duration::seconds(5));
+        assert!(group.poll().executed());
+
+        clock.set(start - Duration::hours(1));
+        assert!(!group.poll().executed());
+
+        clock.advance(Duration::seconds(5));
+        assert!(group.poll().executed());
+    }
+
+    #[test]
+    fn fixed_phase() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap() + Duration::milliseconds(400);
+        let clock = FakeClock::install(start);
+
+        let mut group = Group::with_interval("", Duration::seconds(1));
+        group.set_schedule(PollSchedule::FixedPhase);
+
+        // first poll is aligned to next second
+        assert!(!group.poll().executed());
+        clock.advance(Duration::milliseconds(600));
+        assert!(group.poll().executed());
+
+        // late poll does not shift phase
+        clock.advance(Duration::milliseconds(1250));
+        assert!(group.poll().executed());
+        assert_eq!(std::time::Duration::from_millis(250), group.jitter().last);
+        clock.advance(Duration::milliseconds(750));
+        assert!(group.poll().executed());
+
+        // missed cycles are skipped
+        clock.advance(Duration::milliseconds(2500));
+        assert!(group.poll().executed());
+        assert_eq!(1, group.jitter().missed);
+        clock.advance(Duration::milliseconds(499));
+        assert!(!group.poll().executed());
+        clock.advance(Duration::milliseconds(1));
+        assert!(group.poll().executed());
+
+        assert_eq!(5, group.health().jitter.count);
+    }
+
+    #[test]
+    fn streams() {
+        struct Counter(Rc<Cell<usize>>);
+        impl RemoteStorage for Counter {
+            fn name(&self) -> &str {
+                "counter"
+            }
+
+            fn store(&mut self, _: &DeviceMetadata, events: &[IOEvent]) -> Result<(), ErrorType> {
+                self.0.set(self.0.get() + events.len());
+                Ok(())
+            }
+        }
+
+        let raw = Rc::new(Cell::new(0));
+        let decimated = Rc::new(Cell::new(0));
+
+        let clock = FakeClock::install(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap());
+        let mut group = Group::with_interval("", Duration::seconds(1));
+        group
+            .push_input(Input::new("", 0, None)
+                .set_command(IOCommand::Input(|| RawValue::Float(1.0))))
+            .add_stream(0, LogStream::raw(Box::new(Counter(raw.clone()))))
+            .add_stream(0, LogStream::decimated(
+                Box::new(Counter(decimated.clone())), Duration::seconds(10), Decimation::Mean));
+
+        for _ in 0..15 {
+            assert!(group.poll().executed());
+            clock.advance(Duration::seconds(1));
+        }
+        assert_eq!(15, raw.get());
+        assert_eq!(1, decimated.get());
+
+        group.flush_streams().unwrap();
+        assert_eq!(2, decimated.get());
+    }
+
+    #[test]
+    /// Assert that PID state and variables survive a restart
+    fn snapshot() {
+        let root = temp_root("snapshot");
+        let build = || {
+            let output = Output::new("", 0, None)
+                .set_command(IOCommand::Output(|_| Ok(())))
+                .init_log()
+                .into_deferred();
+            let mut input = Input::new("", 0, None)
+                .set_command(IOCommand::Input(|| RawValue::Float(5.0)))
+                .init_publisher();
+            let publisher = input.publisher_mut().as_mut().unwrap();
+            let pid = PID::new("pid", 7.5, 10.0)
+                .set_i(0.5, 10.0)
+                .set_output(output)
+                .set_handler(publisher.handler_ref());
+            publisher.subscribe(pid.into_boxed());
+
+            let mut group = Group::with_root("snapshot", &root);
+            group.push_input(input);
+            group
+        };
+
+        let mut group = build();
+        assert!(!group.load_snapshot().unwrap());
+        assert!(group.poll().executed());
+        group.variables().set("mode", RawValue::Int(2));
+        group.save_snapshot().unwrap();
+        let snapshot = group.snapshot();
+        assert!(snapshot.actions[&0].contains_key("pid"));
+
+        let mut restarted = build();
+        assert!(restarted.load_snapshot().unwrap());
+        assert_eq!(snapshot.actions, restarted.snapshot().actions);
+        assert_eq!(Some(RawValue::Int(2)), restarted.variables().get("mode"));
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that configuration changed between runs is detected when loading snapshot
+    fn drift() {
+        let root = temp_root("drift");
+        let build = |setpoint, outputs| {
+            let mut group = Group::with_root("drift", &root);
+            let mut input = Input::new("", 0, None).init_publisher();
+            input.publisher_mut().as_mut().unwrap().subscribe(PID::new("heater", setpoint, 10.0).into_boxed());
+            group.push_input(input);
+            for id in 0..outputs {
+                group.push_output(Output::new("", id, None));
+            }
+            group
+        };
+
+        let group = build(22.0, 1);
+        group.variables().set("dose", RawValue::Float(1.5));
+        group.save_snapshot().unwrap();
+
+        let mut restarted = build(22.0, 1);
+        assert!(restarted.load_snapshot().unwrap());
+        assert!(restarted.drift().is_empty());
+
+        let mut restarted = build(20.0, 2);
+        restarted.load_snapshot().unwrap();
+        let drift = restarted.drift();
+        assert_eq!(2, drift.len());
+        assert!(matches!(drift[0], Difference::DeviceAdded { id: 1, .. }));
+        assert_eq!(Difference::ParameterChanged {
+            id: 0,
+            action: String::from("heater"),
+            parameter: String::from("setpoint"),
+            before: Some(RawValue::Float(22.0)),
+            after: Some(RawValue::Float(20.0)),
+        }, drift[1]);
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    fn quarantine() {
+        let clock = FakeClock::install(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap());
+        let faults = FaultInjector::new(1).set_error(1.0);
+        let alarms = Def::new(AlarmHandler::default());
+
+        let mut group = Group::with_interval("", Duration::seconds(1));
+        group
+            .push_input(Input::new("", 0, None)
+                .set_command(IOCommand::Input(|| RawValue::Float(1.0)))
+                .set_fault_injector(faults.clone()))
+            .set_quarantine(QuarantinePolicy::new(2)
+                .set_backoff(Backoff::new(Duration::seconds(10), Duration::minutes(1))))
+            .set_alarm_handler(alarms.clone());
+
+        for _ in 0..2 {
+            assert_eq!(1, group.poll().errors().count());
+            clock.advance(Duration::seconds(1));
+        }
+        assert_eq!(vec![0], group.health().quarantined);
+        assert_eq!(1, alarms.lock().unwrap().active().count());
+
+        // quarantined input is not polled
+        for _ in 0..5 {
+            assert!(!group.poll().has_errors());
+            clock.advance(Duration::seconds(1));
+        }
+
+        // successful trial read re-admits input
+        let _ = faults.set_error(0.0);
+        clock.advance(Duration::seconds(5));
+        assert!(!group.poll().has_errors());
+        assert!(group.quarantined().is_empty());
+        assert_eq!(0, alarms.lock().unwrap().active().count());
+    }
+
+    #[test]
+    fn maintenance() {
+        let alarms = Def::new(AlarmHandler::default());
+        let start = Utc::now();
+
+        let mut group = Group::new("");
+        group
+            .push_input(Input::new("probe", 0, None))
+            .push_output(Output::new("pump", 1, None)
+                .set_command(IOCommand::Output(|_| Ok(())))
+                .add_tag("dosing"))
+            .set_alarm_handler(alarms.clone())
+            .schedule_maintenance(MaintenanceWindow::new("calibrate", start, start + Duration::hours(1),
+                                                         MaintenanceTarget::Input(0)))
+            .schedule_maintenance(MaintenanceWindow::new("dosing", start, start + Duration::hours(2),
+                                                         MaintenanceTarget::Tag("dosing".into()))
+                .set_inhibit(true));
+        let pump = group.outputs.get(&1).unwrap().clone();
+
+        assert!(group.attempt_maintenance_at(start).is_empty());
+        assert!(pump.try_lock().unwrap().is_inhibited());
+        let alarm = Alarm::new("ph", AlarmSeverity::Warning, "", start).set_device("probe");
+        alarms.lock().unwrap().raise_alarm(alarm.clone());
+        assert!(alarms.lock().unwrap().is_suppressed(&alarm));
+
+        let summaries = group.attempt_maintenance_at(start + Duration::hours(1));
+        assert_eq!(1, summaries.len());
+        assert_eq!(vec!["probe".to_string()], summaries[0].devices);
+        assert_eq!(1, summaries[0].suppressed.len());
+        assert!(!alarms.lock().unwrap().is_suppressed(&alarm));
+        assert!(pump.try_lock().unwrap().is_inhibited());
+
+        let summaries = group.attempt_maintenance_at(start + Duration::hours(2));
+        assert_eq!((1, 0), (summaries[0].inhibited, summaries[0].suppressed.len()));
+        assert!(!pump.try_lock().unwrap().is_inhibited());
+        assert_eq!(0, group.maintenance().count());
+    }
+
+    #[test]
+    /// Assert that steps of a ramp do not turn on an output inhibited by maintenance
+    fn maintenance_ramp() {
+        let start = Utc::now();
+        let mut group = Group::new("");
+        group
+            .push_output(Output::new("lamp", 0, None)
+                .set_command(IOCommand::Output(|_| Ok(())))
+                .init_log())
+            .schedule_maintenance(MaintenanceWindow::new("relamp", start + Duration::seconds(1),
+                                                         start + Duration::hours(1), MaintenanceTarget::Output(0))
+                .set_inhibit(true));
+        let lamp = group.outputs.get(&0).unwrap().clone();
+        let handler = Def::new(SchedRoutineHandler::default());
+        let ramp = Ramp::new(lamp.clone(), Duration::seconds(3), handler.clone())
+            .set_steps(4);
+        ramp.write(RawValue::Float(0.0)).unwrap();
+        ramp.write(RawValue::Float(100.0)).unwrap();
+
+        group.attempt_maintenance_at(start + Duration::seconds(1));
+        handler.try_lock().unwrap().attempt_routines_at(start + Duration::seconds(4));
+        assert!(handler.try_lock().unwrap().scheduled().is_empty());
+        assert_eq!(Some(RawValue::Float(25.0)), *lamp.try_lock().unwrap().state());
+        assert!(lamp.try_lock().unwrap().is_pending());
+
+        // final step is written once window ends
+        group.attempt_maintenance_at(start + Duration::hours(1));
+        group.attempt_routines();
+        assert_eq!(Some(RawValue::Float(100.0)), *lamp.try_lock().unwrap().state());
+    }
+
     #[test]
     #[should_panic]
     fn push_output_panics() {
@@ -626,4 +3339,523 @@ mod tests {
 
         remove_dir_all(group.full_path().parent().unwrap()).unwrap();
     }
+
+    #[test]
+    /// Assert that modes apply parameter overrides manually and on schedule
+    fn modes() {
+        let root = temp_root("modes");
+        let mut group = Group::with_root("modes", &root).init_dir();
+
+        let mut input = Input::new("", 0, None).init_publisher();
+        input.publisher_mut().as_mut().unwrap().subscribe(PID::new("heater", 22.0, 10.0).into_boxed());
+        group.push_input(input);
+
+        let setpoint = |group: &Group| {
+            let input = group.inputs.get(&0).unwrap().try_lock().unwrap();
+            input.publisher().as_ref().unwrap().subscribers()[0].parameter("setpoint")
+        };
+
+        group
+            .add_mode(Mode::new("day").set_parameter(0, "heater", "setpoint", RawValue::Float(24.0)))
+            .add_mode(Mode::new("night").set_parameter(0, "heater", "setpoint", RawValue::Float(18.0)))
+            .schedule_mode(NaiveTime::from_hms_opt(6, 0, 0).unwrap(), "day")
+            .schedule_mode(NaiveTime::from_hms_opt(20, 0, 0).unwrap(), "night");
+
+        assert!(group.set_mode("missing", "operator").is_err());
+
+        group.set_mode("night", "operator").unwrap();
+        assert_eq!(Some(RawValue::Float(18.0)), setpoint(&group));
+
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 5, 0, 0).unwrap();
+        group.attempt_mode_schedule_at(start).unwrap();
+        assert_eq!(Some(&String::from("night")), group.mode());
+
+        group.attempt_mode_schedule_at(start + Duration::hours(2)).unwrap();
+        assert_eq!(Some(&String::from("day")), group.mode());
+        assert_eq!(Some(RawValue::Float(24.0)), setpoint(&group));
+
+        let history = group.mode_history();
+        assert_eq!(2, history.len());
+        assert_eq!(Some(String::from("night")), history[1].from);
+        assert_eq!("schedule", history[1].by);
+
+        let trail = read_to_string(group.full_path().join(MODE_HISTORY_FILENAME)).unwrap();
+        assert_eq!(2, trail.lines().count());
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that mode changes are ramped at configured rate
+    fn mode_ramp() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let clock = FakeClock::install(start);
+
+        let mut group = Group::new("");
+        let mut input = Input::new("", 0, None).init_publisher();
+        input.publisher_mut().as_mut().unwrap().subscribe(PID::new("heater", 22.0, 10.0).into_boxed());
+        group.push_input(input);
+
+        group
+            .add_mode(Mode::new("night").set_parameter(0, "heater", "setpoint", RawValue::Float(18.0)))
+            .set_ramp_rate(0, "heater", 0.1);
+
+        group.set_mode("night", "operator").unwrap();
+        assert!(group.is_ramping());
+        assert_eq!(Some(RawValue::Float(22.0)), group.parameter(0, "heater", "setpoint"));
+
+        clock.advance(Duration::seconds(20));
+        group.attempt_mode_schedule().unwrap();
+        assert_eq!(Some(RawValue::Float(20.0)), group.parameter(0, "heater", "setpoint"));
+
+        clock.advance(Duration::seconds(30));
+        group.attempt_mode_schedule().unwrap();
+        assert_eq!(Some(RawValue::Float(18.0)), group.parameter(0, "heater", "setpoint"));
+        assert!(!group.is_ramping());
+    }
+
+    #[test]
+    /// Assert that energy is queryable from logs and restored from snapshot
+    fn energy() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let clock = FakeClock::install(start);
+
+        let build = || {
+            let mut group = Group::new("");
+            group.push_output(Output::new("heater", 0, None)
+                .set_command(IOCommand::Output(|_| Ok(())))
+                .set_energy_meter(EnergyMeter::new(1500.0).set_tariff(0.5))
+                .init_log());
+            group
+        };
+
+        let group = build();
+        {
+            let mut heater = group.outputs.get(&0).unwrap().try_lock().unwrap();
+            heater.write(RawValue::Binary(true)).unwrap();
+            clock.advance(Duration::hours(2));
+            heater.write(RawValue::Binary(false)).unwrap();
+        }
+
+        let usage = group.energy_usage(0, start, start + Duration::weeks(1)).unwrap();
+        assert_eq!(3.0, usage.kwh);
+        assert_eq!(Some(1.5), usage.cost);
+
+        let snapshot = group.snapshot();
+        assert_eq!(3.0, snapshot.energy[&0]);
+
+        let mut restarted = build();
+        restarted.restore(&snapshot).unwrap();
+        let heater = restarted.outputs.get(&0).unwrap().try_lock().unwrap();
+        assert_eq!(Some(1.5), heater.energy_meter().unwrap().cost());
+    }
+
+    #[test]
+    /// Assert that writes queued by load limiter are performed once capacity is freed
+    fn load_limiter() {
+        let limiter = LoadLimiter::new().set_max_concurrent(1);
+        let mut group = Group::new("");
+        for id in 0..2 {
+            group.push_output(Output::new("", id, None)
+                .set_command(IOCommand::Output(|_| Ok(())))
+                .set_load_limiter(limiter.clone(), 10.0));
+        }
+        let write = |group: &Group, id, value| {
+            group.outputs.get(&id).unwrap().try_lock().unwrap().write(RawValue::Binary(value))
+        };
+
+        write(&group, 0, true).unwrap();
+        assert!(write(&group, 1, true).is_err());
+
+        group.attempt_routines();
+        assert_eq!(vec![1], limiter.queued());
+
+        write(&group, 0, false).unwrap();
+        group.attempt_routines();
+        assert_eq!(vec![1], limiter.active());
+
+        let output = group.outputs.get(&1).unwrap().try_lock().unwrap();
+        assert_eq!(Some(RawValue::Binary(true)), *output.state());
+    }
+
+    #[test]
+    /// Assert that load is released when a routine turns an output off
+    fn load_limiter_routine() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let clock = FakeClock::install(start);
+
+        let limiter = LoadLimiter::new().set_max_concurrent(1);
+        let mut group = Group::new("");
+        for id in 0..2 {
+            group.push_output(Output::new("", id, None)
+                .set_command(IOCommand::Output(|_| Ok(())))
+                .set_load_limiter(limiter.clone(), 10.0)
+                .init_log());
+        }
+        let heater = group.outputs.get(&0).unwrap().clone();
+
+        let mut input = Input::new("", 0, None)
+            .set_command(IOCommand::Input(|| RawValue::Float(5.0)))
+            .init_publisher();
+        let publisher = input.publisher_mut().as_mut().unwrap();
+        let pid = PID::new("pid", 7.5, 10.0)
+            .set_p(1.0, 10.0)
+            .set_output(heater)
+            .set_handler(publisher.handler_ref());
+        publisher.subscribe(pid.into_boxed());
+        group.push_input(input);
+
+        // pulse of heater occupies circuit
+        assert!(group.poll().executed());
+        assert_eq!(vec![0], limiter.active());
+        let pump = group.outputs.get(&1).unwrap().clone();
+        assert!(pump.try_lock().unwrap().write(RawValue::Binary(true)).is_err());
+
+        // routine ending pulse frees circuit for queued write
+        clock.advance(Duration::seconds(10));
+        group.attempt_routines();
+        assert_eq!(vec![1], limiter.active());
+        assert_eq!(Some(RawValue::Binary(true)), *pump.try_lock().unwrap().state());
+    }
+
+    #[test]
+    /// Assert that outputs are re-enabled one at a time after resuming
+    fn soft_start() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let clock = FakeClock::install(start);
+
+        let mut group = Group::new("");
+        for id in 0..2 {
+            group.push_output(Output::new("", id, None)
+                .set_command(IOCommand::Output(|_| Ok(())))
+                .set_safe_state(RawValue::Binary(false)));
+        }
+        group.set_soft_start(Duration::seconds(10));
+
+        group.pause(true);
+        group.resume();
+        assert!(group.is_soft_starting());
+
+        let state = |group: &Group, id| *group.outputs.get(&id).unwrap().try_lock().unwrap().state();
+        for id in 0..2 {
+            let mut output = group.outputs.get(&id).unwrap().try_lock().unwrap();
+            assert!(output.write(RawValue::Binary(true)).is_err());
+        }
+
+        group.attempt_routines();
+        assert_eq!(Some(RawValue::Binary(true)), state(&group, 0));
+        assert_eq!(Some(RawValue::Binary(false)), state(&group, 1));
+
+        clock.advance(Duration::seconds(10));
+        group.attempt_routines();
+        assert_eq!(Some(RawValue::Binary(true)), state(&group, 1));
+        assert!(!group.is_soft_starting());
+
+        // outputs inhibited after soft start are not re-enabled
+        group.outputs.get(&0).unwrap().try_lock().unwrap().inhibit();
+        group.attempt_routines();
+        assert!(group.outputs.get(&0).unwrap().try_lock().unwrap().is_inhibited());
+    }
+
+    #[test]
+    /// Assert that output states, variables, and mode are reconstructed from persisted streams
+    fn replay() {
+        let root = temp_root("replay");
+        let build = || {
+            let mut group = Group::with_root("replay", &root).init_dir();
+            let mut input = Input::new("", 0, None).init_publisher();
+            input.publisher_mut().as_mut().unwrap().subscribe(PID::new("heater", 22.0, 10.0).into_boxed());
+            group.push_input(input);
+            group.push_output(Output::new("pump", 0, None)
+                .set_command(IOCommand::Output(|_| Ok(())))
+                .init_log());
+            group
+                .add_mode(Mode::new("night").set_parameter(0, "heater", "setpoint", RawValue::Float(18.0)))
+                .journal_variables();
+            group
+        };
+
+        let mut group = build();
+        group.outputs.get(&0).unwrap().try_lock().unwrap().write(RawValue::Binary(true)).unwrap();
+        group.variables().set("dose", RawValue::Float(1.5));
+        group.variables().set("temporary", RawValue::Int(1));
+        group.variables().remove("temporary");
+        group.set_mode("night", "operator").unwrap();
+        group.save().unwrap();
+
+        let mut restarted = build();
+        restarted.load().unwrap();
+        restarted.replay().unwrap();
+
+        let output = restarted.outputs.get(&0).unwrap().try_lock().unwrap();
+        assert_eq!(Some(RawValue::Binary(true)), *output.state());
+        assert_eq!(Some(RawValue::Float(1.5)), restarted.variables().get("dose"));
+        assert!(!restarted.variables().contains("temporary"));
+        assert_eq!(Some(&String::from("night")), restarted.mode());
+        assert_eq!(1, restarted.mode_history().len());
+        assert_eq!(Some(RawValue::Float(18.0)), restarted.parameter(0, "heater", "setpoint"));
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that interval and time of last poll are restored so that polling phase resumes
+    fn resume_phase() {
+        let root = temp_root("resume_phase");
+        let build = || {
+            let mut group = Group::with_root("resume_phase", &root).init_dir();
+            group.push_input(Input::new("", 0, None)
+                .set_command(IOCommand::Input(|| RawValue::Float(1.0)))
+                .init_log());
+            group
+        };
+
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let clock = FakeClock::install(start);
+
+        let mut group = build();
+        group.set_interval(Duration::hours(1));
+        assert!(group.poll().executed());
+        group.save().unwrap();
+
+        // restarted after more than the default interval
+        clock.advance(Duration::minutes(1));
+        let mut restarted = build();
+        restarted.load().unwrap();
+        assert_eq!(Duration::hours(1), *restarted.interval());
+        assert_eq!(Duration::hours(1), restarted.tick());
+        assert_eq!(group.last_execution, restarted.last_execution);
+        assert!(restarted.poll().early);
+
+        group.set_schedule(PollSchedule::FixedPhase);
+        group.save().unwrap();
+        let mut restarted = build();
+        restarted.load().unwrap();
+        assert_eq!(PollSchedule::FixedPhase, restarted.schedule());
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that logs in the legacy layout are converted and loaded
+    fn migrate_legacy() {
+        let root = temp_root("migrate_legacy");
+        let build = || {
+            let mut group = Group::with_root("migrate_legacy", &root).init_dir();
+            group
+                .push_input(Input::new("ph", 0, IOKind::PH).init_log())
+                .push_input(Input::new("temp", 1, IOKind::Temperature).init_log());
+            group
+        };
+        let mut group = build();
+
+        let legacy = group.full_path().join("ph.json");
+        let mut events = std::collections::BTreeMap::new();
+        for minute in 0..3 {
+            let event = IOEvent::with_timestamp(Utc.with_ymd_and_hms(2022, 1, 1, 0, minute, 0).unwrap(), RawValue::Float(6.0));
+            events.insert(event.key(), event);
+        }
+        let contents = serde_json::json!({"id": 0, "name": "ph", "log": events});
+        write(&legacy, contents.to_string()).unwrap();
+
+        let _ = group.load();
+        let input = group.inputs.get(&0).unwrap().try_lock().unwrap();
+        assert_eq!(3, input.log().unwrap().try_lock().unwrap().len());
+        assert_eq!(Some(0), input.log().unwrap().try_lock().unwrap().metadata().map(|metadata| metadata.id));
+        drop(input);
+        assert!(!legacy.exists());
+        assert!(group.full_path().join(format!("ph.json{}", MIGRATED_SUFFIX)).exists());
+
+        // backups are not migrated again
+        assert!(build().migrate_legacy().unwrap().is_empty());
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that statistics roll over at local midnight, are persisted, and resume after restart
+    fn daily_stats() {
+        let root = temp_root("daily_stats");
+        let offset = FixedOffset::east_opt(10 * 3600).unwrap();
+        let build = || {
+            let mut group = Group::with_root("daily_stats", &root).init_dir();
+            group.push_input(Input::new("flow", 0, IOKind::Flow).init_log());
+            group.set_daily_stats(IODirection::In, 0, offset);
+            group
+        };
+        // local midnight of 2023-06-02
+        let midnight = Utc.with_ymd_and_hms(2023, 6, 1, 14, 0, 0).unwrap();
+
+        let mut group = build();
+        {
+            let input = group.inputs.get(&0).unwrap().try_lock().unwrap();
+            let log = input.log().unwrap();
+            let mut log = log.try_lock().unwrap();
+            for (hours, value) in [(-12, 1.0), (-6, 3.0), (6, 5.0), (30, 7.0)] {
+                log.push(IOEvent::with_timestamp(midnight + Duration::hours(hours), RawValue::Float(value))).unwrap();
+            }
+        }
+
+        group.attempt_daily_rollover_at(midnight - Duration::hours(1)).unwrap();
+        assert!(group.daily_summaries().is_empty());
+        group.attempt_daily_rollover_at(midnight + Duration::minutes(1)).unwrap();
+
+        let summary = &group.daily_summaries()[0];
+        assert_eq!(NaiveDate::from_ymd_opt(2023, 6, 1).unwrap(), summary.date);
+        assert_eq!(2, summary.samples);
+        assert_eq!(Some(2.0), summary.mean);
+
+        // day missed while not running is summarized after restart
+        group.save().unwrap();
+        let mut restarted = build();
+        restarted.load().unwrap();
+        restarted.replay().unwrap();
+        assert_eq!(1, restarted.daily_summaries().len());
+        restarted.attempt_daily_rollover_at(midnight + Duration::hours(50)).unwrap();
+        assert_eq!(3, restarted.daily_summaries().len());
+        assert_eq!(Some(5.0), restarted.daily_summaries()[1].max);
+        assert!(report::daily_markdown(restarted.daily_summaries()).contains("| 2023-06-03 | Input 0 | 1 |"));
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that events discarded from memory are read from the saved log
+    fn query() {
+        let root = temp_root("query");
+        let mut group = Group::with_root("query", &root).init_dir();
+        group.push_input(Input::new("level", 0, IOKind::Flow).init_log());
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+
+        let log = group.inputs.get(&0).unwrap().try_lock().unwrap().log().unwrap();
+        for minutes in 0..6 {
+            let event = IOEvent::with_timestamp(start + Duration::minutes(minutes), RawValue::Float(minutes as f32));
+            log.try_lock().unwrap().push(event).unwrap();
+        }
+        group.save().unwrap();
+        log.try_lock().unwrap().set_capacity(Some(2));
+
+        let series = group.query(IODirection::In, 0, start, start + Duration::hours(1)).unwrap();
+        assert_eq!(6, series.points.len());
+        assert_eq!(Tier::Archive, series.points[3].tier);
+        assert_eq!(Tier::Memory, series.points[4].tier);
+
+        // saved log is not read when memory covers range
+        let series = group.query(IODirection::In, 0, start + Duration::minutes(4), start + Duration::hours(1)).unwrap();
+        assert_eq!(1, series.segments.len());
+
+        assert!(group.query(IODirection::Out, 0, start, start).is_err());
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that logs are signed when saved, and that alteration is detected
+    fn signing() {
+        let root = temp_root("signing");
+        let mut group = Group::with_root("signing", &root).init_dir();
+        group.push_input(Input::new("flow", 0, IOKind::Flow).init_log());
+        group.push_output(Output::new("pump", 1, None).init_log());
+        let key = SigningKey::new("secret");
+        group.set_signing_key(key.clone());
+
+        group.inputs.get(&0).unwrap().try_lock().unwrap()
+            .log().unwrap().try_lock().unwrap()
+            .push(IOEvent::new(RawValue::Float(1.0))).unwrap();
+        group.save().unwrap();
+
+        let results = verify_dir(group.full_path(), &key).unwrap();
+        assert_eq!(2, results.len());
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+
+        // altered value
+        let path = &results[0].0;
+        let altered = read_to_string(path).unwrap().replace("1.0", "2.0");
+        write(path, altered).unwrap();
+        assert!(verify(path, &key).is_err());
+
+        // re-signed on next save
+        group.save().unwrap();
+        assert!(verify(path, &key).is_ok());
+    }
+
+    #[test]
+    /// Assert that UUIDs persist across restarts and match logs and snapshots of renumbered devices
+    fn uuid() {
+        let root = temp_root("uuid");
+        let build = |input: IdType, output: IdType| {
+            let mut group = Group::with_root("uuid", &root).init_dir();
+            group.push_input(Input::new("ph", input, IOKind::PH).init_log());
+            group.push_output(Output::new("pump", output, None)
+                .set_energy_meter(EnergyMeter::new(10.0))
+                .init_log());
+            group
+        };
+
+        let group = build(0, 1);
+        let input = group.inputs.get(&0).unwrap().clone();
+        input.try_lock().unwrap().log().unwrap().try_lock().unwrap()
+            .push(IOEvent::new(RawValue::Float(6.5))).unwrap();
+        group.outputs.get(&1).unwrap().try_lock().unwrap().energy_meter_mut().unwrap().set_kwh(2.0);
+        group.save().unwrap();
+        group.save_snapshot().unwrap();
+        let uuid = input.try_lock().unwrap().metadata().uuid;
+        let pump = group.outputs.get(&1).unwrap().try_lock().unwrap().metadata().uuid;
+
+        // loading a log restores UUID
+        let mut restarted = build(0, 1);
+        assert_ne!(uuid, restarted.inputs.get(&0).unwrap().try_lock().unwrap().metadata().uuid);
+        restarted.load().unwrap();
+        assert_eq!(uuid, restarted.inputs.get(&0).unwrap().try_lock().unwrap().metadata().uuid);
+
+        // renumbered devices find their log and snapshot entries by UUID
+        let mut renumbered = Group::with_root("uuid", &root).init_dir();
+        renumbered.push_input(Input::new("ph", 5, IOKind::PH).init_log().set_uuid(uuid));
+        renumbered.push_output(Output::new("pump", 6, None)
+            .set_energy_meter(EnergyMeter::new(10.0))
+            .init_log()
+            .set_uuid(pump));
+        renumbered.load().unwrap();
+        assert_eq!(1, renumbered.inputs.get(&5).unwrap().try_lock().unwrap().log().unwrap().try_lock().unwrap().len());
+        assert!(renumbered.load_snapshot().unwrap());
+        let output = renumbered.outputs.get(&6).unwrap().try_lock().unwrap();
+        assert_eq!(2.0, output.energy_meter().unwrap().kwh());
+
+        let _ = remove_dir_all(root);
+    }
+
+    #[test]
+    /// Assert that metadata patches are persisted to saved logs and recorded in audit trail
+    fn update_devices() {
+        let root = temp_root("update_devices");
+        let mut group = Group::with_root("update_devices", &root).init_dir();
+        group
+            .push_input(Input::new("ph_1", 0, IOKind::PH).init_log())
+            .push_input(Input::new("ph_2", 1, IOKind::PH).init_log())
+            .push_input(Input::new("temp", 2, IOKind::Temperature).init_log());
+        group.save().unwrap();
+
+        let patch = MetadataPatch::default()
+            .add_tag("reservoir")
+            .set_location("tank 1")
+            .set_bounds(0.0, 14.0);
+        let changes = group.update_devices(|metadata| metadata.kind == IOKind::PH, &patch).unwrap();
+        assert_eq!(2, changes.len());
+        assert!(changes.iter().all(|change| change.before.tags.is_empty()));
+
+        // re-applying patch has no effect
+        assert!(group.update_devices(|metadata| metadata.has_tag("reservoir"), &patch).unwrap().is_empty());
+
+        let input = group.inputs.get(&1).unwrap().try_lock().unwrap();
+        assert_eq!(Some((0.0, 14.0)), input.bounds());
+        assert_eq!(Some(String::from("tank 1")), input.metadata().location);
+        let saved = read_log(input.log().unwrap().try_lock().unwrap().full_path()).unwrap();
+        assert!(saved.metadata().unwrap().has_tag("reservoir"));
+        assert!(!group.inputs.get(&2).unwrap().try_lock().unwrap().metadata().has_tag("reservoir"));
+
+        let audit = read_to_string(group.full_path().join(METADATA_HISTORY_FILENAME)).unwrap();
+        assert_eq!(2, audit.lines().count());
+
+        let _ = remove_dir_all(root);
+    }
 }
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::action::ActionMetrics;
+use crate::io::IdType;
+use crate::storage::JitterStats;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Snapshot of the operational status of a [`crate::storage::Group`]
+///
+/// Returned by [`crate::storage::Group::health()`]. This is serializable so that it may be exposed
+/// by an external status endpoint.
+pub struct Health {
+    pub name: String,
+
+    /// Polling and routines are suspended
+    pub paused: bool,
+
+    /// Heartbeat is in fault state. Always `false` when no heartbeat is set.
+    pub faulted: bool,
+
+    pub inputs: usize,
+    pub outputs: usize,
+
+    /// Time of the last poll
+    pub last_poll: DateTime<Utc>,
+
+    /// Number of log files on disk without a configured device. See [`Sweep`].
+    #[serde(default)]
+    pub orphaned_logs: usize,
+
+    /// Number of scheduled routines whose device log has been dropped. See [`Sweep`].
+    #[serde(default)]
+    pub dangling_routines: usize,
+
+    /// Polling jitter
+    #[serde(default)]
+    pub jitter: JitterStats,
+
+    /// Ids of inputs which are quarantined. See [`crate::storage::QuarantinePolicy`].
+    #[serde(default)]
+    pub quarantined: Vec<IdType>,
+
+    /// Counters of all actions. See [`crate::storage::Group::action_metrics()`].
+    #[serde(default)]
+    pub action_metrics: BTreeMap<IdType, BTreeMap<String, ActionMetrics>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Stale storage artifacts left behind after devices are removed from a [`crate::storage::Group`]
+///
+/// Returned by [`crate::storage::Group::sweep()`] and [`crate::storage::Group::clean()`].
+pub struct Sweep {
+    /// Log files within group directory that do not belong to any configured device
+    pub orphaned_logs: Vec<PathBuf>,
+
+    /// Number of scheduled [`crate::action::Routine`]s whose device log has been dropped
+    pub dangling_routines: usize,
+}
+
+impl Sweep {
+    /// `true` when no stale artifacts were found
+    pub fn is_clean(&self) -> bool {
+        self.orphaned_logs.is_empty() && self.dangling_routines == 0
+    }
+}
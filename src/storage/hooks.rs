@@ -0,0 +1,140 @@
+use crate::errors::DeviceError;
+use crate::helpers::Def;
+use crate::io::{DeviceMetadata, IOEvent};
+
+/// Callback that is given device metadata and the generated [`IOEvent`]
+pub type EventHook = Box<dyn Fn(&DeviceMetadata, &IOEvent)>;
+
+/// Callback that is given device metadata and the [`DeviceError`] that occurred
+pub type ErrorHook = Box<dyn Fn(&DeviceMetadata, &DeviceError)>;
+
+#[derive(Default)]
+/// Collection of user callbacks for group-level device events
+///
+/// Hooks are a lightweight extension point for custom telemetry. Unlike [`crate::action::Action`]
+/// subscribers, hooks cannot actuate outputs and are not bound to a single device. Instead, every
+/// device within a [`crate::storage::Group`] shares the same [`EventHooks`] instance.
+///
+/// # Notes
+///
+/// Hooks are not re-entrant: a hook that causes another device event to be dispatched (ie: by
+/// writing to an output) will not have nested hooks called.
+pub struct EventHooks {
+    on_read: Vec<EventHook>,
+    on_write: Vec<EventHook>,
+    on_error: Vec<ErrorHook>,
+}
+
+impl EventHooks {
+    /// Register a callback for every successful read
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: Function accepting metadata of originating device and generated [`IOEvent`]
+    pub fn on_read<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&DeviceMetadata, &IOEvent) + 'static
+    {
+        self.on_read.push(Box::new(hook));
+        self
+    }
+
+    /// Register a callback for every output write
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: Function accepting metadata of originating device and generated [`IOEvent`]
+    pub fn on_write<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&DeviceMetadata, &IOEvent) + 'static
+    {
+        self.on_write.push(Box::new(hook));
+        self
+    }
+
+    /// Register a callback for every device error
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: Function accepting metadata of originating device and returned [`DeviceError`]
+    pub fn on_error<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&DeviceMetadata, &DeviceError) + 'static
+    {
+        self.on_error.push(Box::new(hook));
+        self
+    }
+
+    /// Call all registered read hooks
+    pub fn dispatch_read(&self, metadata: &DeviceMetadata, event: &IOEvent) {
+        for hook in self.on_read.iter() {
+            hook(metadata, event);
+        }
+    }
+
+    /// Call all registered write hooks
+    pub fn dispatch_write(&self, metadata: &DeviceMetadata, event: &IOEvent) {
+        for hook in self.on_write.iter() {
+            hook(metadata, event);
+        }
+    }
+
+    /// Call all registered error hooks
+    pub fn dispatch_error(&self, metadata: &DeviceMetadata, error: &DeviceError) {
+        for hook in self.on_error.iter() {
+            hook(metadata, error);
+        }
+    }
+}
+
+/// Helper for calling shared hooks from within a device
+///
+/// Silently fails if no hooks are associated or if hooks are already being dispatched.
+pub(crate) fn with_hooks<F>(hooks: &Option<Def<EventHooks>>, f: F)
+where
+    F: FnOnce(&EventHooks)
+{
+    if let Some(hooks) = hooks {
+        if let Ok(binding) = hooks.try_lock() {
+            f(&binding);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::errors::DeviceError;
+    use crate::io::{DeviceMetadata, IOEvent, RawValue};
+    use crate::storage::EventHooks;
+
+    #[test]
+    fn dispatch() {
+        let reads = Rc::new(Cell::new(0));
+        let writes = Rc::new(Cell::new(0));
+        let errors = Rc::new(Cell::new(0));
+
+        let mut hooks = EventHooks::default();
+        {
+            let (reads, writes, errors) = (reads.clone(), writes.clone(), errors.clone());
+            hooks
+                .on_read(move |_, _| reads.set(reads.get() + 1))
+                .on_write(move |_, _| writes.set(writes.get() + 1))
+                .on_error(move |_, _| errors.set(errors.get() + 1));
+        }
+
+        let metadata = DeviceMetadata::default();
+        let event = IOEvent::new(RawValue::default());
+
+        hooks.dispatch_read(&metadata, &event);
+        hooks.dispatch_read(&metadata, &event);
+        hooks.dispatch_write(&metadata, &event);
+        hooks.dispatch_error(&metadata, &DeviceError::NoCommand { metadata: metadata.clone() });
+
+        assert_eq!(2, reads.get());
+        assert_eq!(1, writes.get());
+        assert_eq!(1, errors.get());
+    }
+}
@@ -0,0 +1,252 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{read, read_dir, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ErrorType, FilesystemError, IntegrityError};
+use crate::settings::LOG_FN_PREFIX;
+use crate::storage::FILETYPE;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Suffix appended to the filename of a signed file to give the filename of its signature
+pub const SIGNATURE_SUFFIX: &str = ".sig";
+
+/// Name of algorithm recorded in signatures
+const ALGORITHM: &str = "HMAC-SHA256";
+
+#[derive(Clone)]
+/// Secret key used to sign and verify persisted logs
+///
+/// The same key must be available to whoever verifies the data, and should be kept apart from the
+/// data itself (ie: not under the root directory), since anyone holding the key can re-sign altered
+/// data.
+///
+/// # Example
+///
+/// ```
+/// use sensd::storage::SigningKey;
+///
+/// let key = SigningKey::new("correct horse battery staple");
+/// // key material is never printed
+/// assert_eq!("SigningKey(..)", format!("{:?}", key));
+/// ```
+pub struct SigningKey(Vec<u8>);
+
+impl SigningKey {
+    pub fn new<K: Into<Vec<u8>>>(key: K) -> Self {
+        Self(key.into())
+    }
+
+    /// Read key from file
+    ///
+    /// A single trailing newline is ignored, so that keys written by text editors or `echo` are
+    /// read as intended.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ErrorType> {
+        let mut key = read(path)?;
+        if key.last() == Some(&b'\n') {
+            key.pop();
+            if key.last() == Some(&b'\r') {
+                key.pop();
+            }
+        }
+        Ok(Self(key))
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.0).expect("HMAC accepts keys of any length")
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SigningKey(..)")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Contents of a signature file
+struct Signature {
+    algorithm: String,
+
+    /// Hex encoded MAC of the complete contents of the signed file
+    mac: String,
+}
+
+/// Path of signature of `path`
+pub fn signature_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut filename = OsString::from(path.as_ref().as_os_str());
+    filename.push(SIGNATURE_SUFFIX);
+    PathBuf::from(filename)
+}
+
+/// Sign contents of file as they currently are
+///
+/// The signature is written alongside the file (see [`signature_path()`]), replacing any previous
+/// signature. Since a log is rewritten completely every time it is saved, it must be re-signed
+/// after every save. [`crate::storage::Group::save()`] does so when a key has been set by
+/// [`crate::storage::Group::set_signing_key()`].
+///
+/// # Parameters
+///
+/// - `path`: File to sign
+/// - `key`: Secret key
+///
+/// # Example
+///
+/// ```
+/// use sensd::storage::{sign, verify, SigningKey};
+///
+/// let path = std::env::temp_dir().join("sensd_sign_example.json");
+/// std::fs::write(&path, "{}").unwrap();
+///
+/// let key = SigningKey::new("secret");
+/// sign(&path, &key).unwrap();
+/// assert!(verify(&path, &key).is_ok());
+///
+/// std::fs::write(&path, "{\"altered\": true}").unwrap();
+/// assert!(verify(&path, &key).is_err());
+/// ```
+pub fn sign<P: AsRef<Path>>(path: P, key: &SigningKey) -> Result<(), ErrorType> {
+    let path = path.as_ref();
+    let mut mac = key.mac();
+    mac.update(&read(path)?);
+
+    let signature = Signature {
+        algorithm: ALGORITHM.to_string(),
+        mac: hex::encode(mac.finalize().into_bytes()),
+    };
+    let contents = serde_json::to_string_pretty(&signature)
+        .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+    write(signature_path(path), contents)?;
+    Ok(())
+}
+
+/// Verify that a file has not been altered since it was signed by [`sign()`]
+///
+/// # Parameters
+///
+/// - `path`: Signed file
+/// - `key`: Secret key used to sign file
+///
+/// # Returns
+///
+/// - `Ok` when the signature matches the contents of the file
+/// - `Err` with [`IntegrityError`] when the file has no signature, the signature is malformed, or
+///   the file (or signature) has been altered or was signed with a different key. Errors reading
+///   either file are returned as-is.
+pub fn verify<P: AsRef<Path>>(path: P, key: &SigningKey) -> Result<(), ErrorType> {
+    let path = path.as_ref();
+    let display = path.display().to_string();
+    let signature_path = signature_path(path);
+    if !signature_path.exists() {
+        return Err(Box::new(IntegrityError::Unsigned { path: display }));
+    }
+
+    let signature: Signature = serde_json::from_str(&read_to_string(signature_path)?)
+        .map_err(|_| IntegrityError::Malformed { path: display.clone() })?;
+    if signature.algorithm != ALGORITHM {
+        return Err(Box::new(IntegrityError::Malformed { path: display }));
+    }
+    let expected = hex::decode(&signature.mac)
+        .map_err(|_| IntegrityError::Malformed { path: display.clone() })?;
+
+    let mut mac = key.mac();
+    mac.update(&read(path)?);
+    mac.verify_slice(&expected)
+        .map_err(|_| Box::new(IntegrityError::Mismatch { path: display }) as ErrorType)
+}
+
+/// Path of a verified file, and result of [`verify()`]
+pub type Verification = (PathBuf, Result<(), ErrorType>);
+
+/// Verify every log under a directory
+///
+/// Directories are searched recursively for log files, which are verified regardless of whether
+/// they have been signed, so that deleting a signature does not go unnoticed.
+///
+/// # Returns
+///
+/// [`Verification`] of each log found, sorted by path. An error is returned only
+/// if `root` cannot be read.
+pub fn verify_dir<P: AsRef<Path>>(root: P, key: &SigningKey) -> Result<Vec<Verification>, ErrorType> {
+    let mut logs = Vec::new();
+    find_logs(root.as_ref(), &mut logs)?;
+    logs.sort();
+    Ok(logs.into_iter()
+        .map(|path| {
+            let result = verify(&path, key);
+            (path, result)
+        })
+        .collect())
+}
+
+pub(crate) fn find_logs(dir: &Path, logs: &mut Vec<PathBuf>) -> Result<(), ErrorType> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_logs(&path, logs)?;
+        } else if is_log(&path) {
+            logs.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_log(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(LOG_FN_PREFIX) && name.ends_with(FILETYPE))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, remove_dir_all, remove_file, write};
+
+    use crate::errors::{ErrorType, IntegrityError};
+    use crate::storage::integrity::{sign, signature_path, verify, verify_dir, SigningKey};
+
+    #[test]
+    fn sign_verify() {
+        let dir = std::env::temp_dir().join("sensd_integrity_test");
+        let _ = remove_dir_all(&dir);
+        create_dir_all(dir.join("device")).unwrap();
+        let path = dir.join("device").join("log__device_0.json");
+        write(&path, "{\"log\": {}}").unwrap();
+
+        let key = SigningKey::new("secret");
+        let error = verify(&path, &key).unwrap_err();
+        assert!(matches!(error.downcast_ref::<IntegrityError>(), Some(IntegrityError::Unsigned { .. })));
+
+        sign(&path, &key).unwrap();
+        verify(&path, &key).unwrap();
+
+        // wrong key
+        let mismatch = |result: Result<(), ErrorType>| {
+            matches!(result.unwrap_err().downcast_ref::<IntegrityError>(), Some(IntegrityError::Mismatch { .. }))
+        };
+        assert!(mismatch(verify(&path, &SigningKey::new("guess"))));
+
+        // altered contents
+        write(&path, "{\"log\": {\"2023-01-01T00:00:00Z\": {}}}").unwrap();
+        assert!(mismatch(verify(&path, &key)));
+
+        // unsigned logs are reported, other files are ignored
+        sign(&path, &key).unwrap();
+        write(dir.join("notes.txt"), "").unwrap();
+        write(dir.join("log__other_1.json"), "{}").unwrap();
+        let results = verify_dir(&dir, &key).unwrap();
+        assert_eq!(2, results.len());
+        assert!(results[0].1.is_ok());
+        assert!(results[1].1.is_err());
+
+        // deleted signature
+        remove_file(signature_path(&path)).unwrap();
+        assert!(verify(&path, &key).is_err());
+
+        let _ = remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,34 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{read_to_string, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::errors::{ErrorType, FilesystemError};
+
+/// Append record to an append-only journal of newline delimited JSON
+pub(crate) fn append_record<T: Serialize>(path: &Path, record: &T) -> Result<(), ErrorType> {
+    let line = serde_json::to_string(record)
+        .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Read all records from a journal written by [`append_record()`]
+///
+/// # Returns
+///
+/// - `Ok` with records in order of writing. Empty if journal does not exist.
+/// - `Err` if journal could not be read, or any record could not be parsed
+pub(crate) fn read_records<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, ErrorType> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line)
+            .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() }.into()))
+        .collect()
+}
@@ -1,16 +1,20 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::collections::hash_map::{Entry, Iter};
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::btree_map::Iter;
+use std::collections::BTreeMap;
+use std::ops::Bound::{Excluded, Unbounded};
+use std::fs::{read_dir, rename, File};
+use std::io::{BufReader, BufWriter, Write};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 
+use crate::console;
 use crate::errors::{ContainerError, ErrorType, FilesystemError};
-use crate::helpers::writable_or_create;
-use crate::io::{DeviceMetadata, IdType, IOEvent};
+use crate::helpers::{writable_or_create, Def};
+use crate::io::{DeviceMetadata, EventKind, EventQuality, IdType, IOEvent, RawValue};
 use crate::settings;
-use crate::storage::{EventCollection, Persistent, FILETYPE, Document};
+use crate::storage::logging::paging::{find_pages, prune_pages, write_page};
+use crate::storage::{EventCollection, EventKey, Persistent, Page, FILETYPE, Document};
 
 
 /// A record of [`IOEvent`]s from a single device keyed by datetime
@@ -32,7 +36,25 @@ pub struct Log {
     dir: Option<PathBuf>,
 
     /// Collection of `IOEvent` objects
+    #[serde(deserialize_with = "deserialize_events")]
     log: EventCollection,
+
+    #[serde(skip)]
+    /// Maximum number of events retained in memory. `None` if unbounded.
+    capacity: Option<usize>,
+
+    #[serde(skip)]
+    /// Handling of events whose timestamp already exists
+    collision: CollisionPolicy,
+
+    #[serde(skip)]
+    /// Number of events written to each page once capacity is exceeded. `None` if evicted events
+    /// are discarded.
+    page_size: Option<usize>,
+
+    #[serde(skip)]
+    /// Maximum number of pages kept on disk. `None` if pages are never removed.
+    page_retention: Option<usize>,
 }
 
 impl Log {
@@ -103,21 +125,328 @@ impl Log {
     ///
     /// Ownership of `self` with updated metadata. This is meant to be used by method
     pub fn set_metadata(mut self, metadata: DeviceMetadata) -> Self {
+        self.set_metadata_ref(metadata);
+        self
+    }
+
+    /// Setter for `metadata`
+    ///
+    /// This does not take ownership of `self`, unlike [`Log::set_metadata()`]. Used when
+    /// originating device is renamed.
+    ///
+    /// # Parameters
+    ///
+    /// - `metadata`: Device metadata to store internally
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_metadata_ref(&mut self, metadata: DeviceMetadata) -> &mut Self {
         self.metadata = Some(metadata);
         self
     }
 
+    /// Limit number of events retained in memory
+    ///
+    /// Logs are unbounded by default, which is unsuitable for a long-running process unless logs
+    /// are otherwise archived. Once `capacity` is reached, the oldest event is discarded for every
+    /// new event. Discarded events are not saved, so logs should be saved or streamed (see
+    /// [`crate::storage::LogStream`]) more often than they fill, or paged to disk (see
+    /// [`Log::set_paging()`]).
+    ///
+    /// # Parameters
+    ///
+    /// - `capacity`: Maximum number of events, or `None` to remove limit. A capacity of `0` is
+    ///   treated as `1`.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_capacity(&mut self, capacity: Option<usize>) -> &mut Self {
+        self.capacity = capacity.map(|capacity| capacity.max(1));
+        self.trim(0);
+        self
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Page events evicted by capacity to disk instead of discarding them
+    ///
+    /// Once capacity is exceeded, the oldest `page_size` events are moved out of memory together
+    /// and written as a [`Page`] beside the saved log. Memory use therefore stays between
+    /// `capacity - page_size` and `capacity` events, while no history is lost. Paged events are
+    /// returned by [`Log::query()`] alongside those in memory.
+    ///
+    /// Events are discarded as usual while no directory is set, or if a page cannot be written.
+    /// Pages accumulate indefinitely unless a limit is set by [`Log::set_page_retention()`].
+    ///
+    /// # Parameters
+    ///
+    /// - `page_size`: Number of events per page, or `None` to discard evicted events. Limited to
+    ///   capacity. A size of `0` is treated as `1`.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_paging(&mut self, page_size: Option<usize>) -> &mut Self {
+        self.page_size = page_size.map(|size| size.max(1));
+        self
+    }
+
+    pub fn paging(&self) -> Option<usize> {
+        self.page_size
+    }
+
+    /// Limit number of pages kept on disk
+    ///
+    /// Oldest pages are deleted once a new page is written, so that history older than the
+    /// retained pages is lost.
+    ///
+    /// # Parameters
+    ///
+    /// - `pages`: Maximum number of pages, or `None` to keep every page
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_page_retention(&mut self, pages: Option<usize>) -> &mut Self {
+        self.page_retention = pages;
+        self
+    }
+
+    pub fn page_retention(&self) -> Option<usize> {
+        self.page_retention
+    }
+
+    /// Pages written by this log or a previous run of it, sorted chronologically
+    ///
+    /// # Returns
+    ///
+    /// Empty if no directory or metadata is set
+    pub fn pages(&self) -> Result<Vec<Page>, ErrorType> {
+        match (&self.dir, &self.metadata) {
+            (Some(dir), Some(_)) => find_pages(dir, &self.stem()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Events within a time range, including events paged to disk
+    ///
+    /// Only pages which overlap the range are read. Events in memory take precedence over paged
+    /// events with the same key.
+    ///
+    /// # Parameters
+    ///
+    /// - `start`: Earliest timestamp, inclusive
+    /// - `end`: Latest timestamp, exclusive
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with events in chronological order
+    /// - `Err` if any overlapping page cannot be read or parsed
+    pub fn query(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<IOEvent>, ErrorType> {
+        let mut events = BTreeMap::new();
+        for page in self.pages()?.iter().filter(|page| page.overlaps(start, end)) {
+            for event in page.read()? {
+                if start <= event.timestamp && event.timestamp < end {
+                    events.insert(event.key(), event);
+                }
+            }
+        }
+        events.extend(self.range(start, end).map(|(key, event)| (*key, event.clone())));
+        Ok(events.into_values().collect())
+    }
+
+    /// Filename of saved log without filetype
+    fn stem(&self) -> String {
+        let filename = self.filename();
+        filename.strip_suffix(FILETYPE).unwrap_or(&filename).to_string()
+    }
+
+    /// Set handling of events whose timestamp already exists
+    ///
+    /// Collisions occur when polling faster than the clock resolution, or when importing data
+    /// which overlaps the log. The policy is applied by [`Log::push()`], [`Log::extend()`], and
+    /// [`Log::merge()`].
+    ///
+    /// # Parameters
+    ///
+    /// - `policy`: Policy to apply. Defaults to [`CollisionPolicy::Reject`].
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to `self` to allow method chaining
+    pub fn set_collision_policy(&mut self, policy: CollisionPolicy) -> &mut Self {
+        self.collision = policy;
+        self
+    }
+
+    pub fn collision_policy(&self) -> CollisionPolicy {
+        self.collision
+    }
+
+    /// Discard oldest events until `reserve` events may be added without exceeding capacity
+    ///
+    /// Events are paged to disk instead, when paging is enabled and a directory is set.
+    fn trim(&mut self, reserve: usize) {
+        let capacity = match self.capacity {
+            Some(capacity) => capacity,
+            None => return,
+        };
+        let excess = (self.log.len() + reserve).saturating_sub(capacity).min(self.log.len());
+        if excess == 0 {
+            return;
+        }
+        let page_size = match (self.page_size, &self.dir, &self.metadata) {
+            (Some(page_size), Some(_), Some(_)) => page_size.min(capacity),
+            _ => {
+                for _ in 0..excess {
+                    self.log.pop_first();
+                }
+                return;
+            }
+        };
+
+        let count = excess.max(page_size).min(self.log.len());
+        let evicted: Vec<IOEvent> = (0..count)
+            .filter_map(|_| self.log.pop_first().map(|(_, event)| event))
+            .collect();
+        let dir = self.dir.as_ref().unwrap();
+        if let Err(e) = write_page(dir, &self.stem(), &evicted) {
+            console::error(format!("Could not page {} events of {}: {}", evicted.len(), self.name(), e));
+        }
+        if let Some(retention) = self.page_retention {
+            if let Err(e) = prune_pages(dir, &self.stem(), retention) {
+                console::error(format!("Could not remove old pages of {}: {}", self.name(), e));
+            }
+        }
+    }
+
     /// Iterator over keys and values
     ///
     /// # Returns
     ///
-    /// Iterator that returns ([`DateTime<Utc>`], [`IOEvent`]).
-    pub fn iter(&self) -> Iter<DateTime<Utc>, IOEvent> {
+    /// Iterator that returns ([`EventKey`], [`IOEvent`]) in chronological order.
+    pub fn iter(&self) -> Iter<EventKey, IOEvent> {
+        self.log.iter()
+    }
+
+    /// Iterator over events within a time range
+    ///
+    /// # Parameters
+    ///
+    /// - `start`: Earliest timestamp, inclusive
+    /// - `end`: Latest timestamp, exclusive
+    ///
+    /// # Returns
+    ///
+    /// Iterator that returns ([`EventKey`], [`IOEvent`]) in chronological order. Every event
+    /// sharing a timestamp is either inside or outside of the range.
+    pub fn range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> impl DoubleEndedIterator<Item = (&EventKey, &IOEvent)> {
+        // an inverted range would panic
+        let end = end.max(start);
+        self.log.range(EventKey::from(start)..EventKey::from(end))
+    }
+
+    /// Earliest event, if any
+    pub fn first(&self) -> Option<&IOEvent> {
+        self.log.values().next()
+    }
+
+    /// Most recent event, if any
+    pub fn last(&self) -> Option<&IOEvent> {
+        self.log.values().next_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.log.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.log.is_empty()
+    }
+
+    /// Get mutable reference to a single event
+    ///
+    /// # Parameters
+    ///
+    /// - `timestamp`: Timestamp of event
+    ///
+    /// # Returns
+    ///
+    /// `None` if no event exists at `timestamp`. Sequenced events are not returned.
+    pub fn get_mut(&mut self, timestamp: &DateTime<Utc>) -> Option<&mut IOEvent> {
+        self.log.get_mut(&EventKey::from(*timestamp))
+    }
+
+    /// Iterator over events of a single kind
+    ///
+    /// # Parameters
+    ///
+    /// - `kind`: Kind of event to yield
+    ///
+    /// # Returns
+    ///
+    /// Iterator that returns ([`EventKey`], [`IOEvent`]) where event matches `kind`, in
+    /// chronological order.
+    pub fn filter_kind(&self, kind: EventKind) -> impl DoubleEndedIterator<Item = (&EventKey, &IOEvent)> {
         self.log.iter()
+            .filter(move |(_, event)| event.kind == kind)
+    }
+
+    /// Estimate value at an arbitrary time from the samples surrounding it
+    ///
+    /// Numeric values are linearly interpolated between the latest sample at or before `timestamp`
+    /// and the earliest sample after it, so that consumers sampling at a different rate than the
+    /// device is polled see consistent values. Binary and timestamp values, and values following
+    /// the most recent sample, are held from the previous sample.
+    ///
+    /// Only readings and writes are used as samples. Events with [`EventQuality::Bad`] or a
+    /// non-finite value are skipped. Events paged to disk are not searched.
+    ///
+    /// # Parameters
+    ///
+    /// - `timestamp`: Time to estimate value at
+    /// - `max_gap`: Staleness limit. Samples older than `max_gap` before `timestamp` are not used,
+    ///   and samples further apart than `max_gap` are not interpolated between.
+    ///
+    /// # Returns
+    ///
+    /// `None` if there is no usable sample within `max_gap` before `timestamp`. Interpolated values
+    /// keep the variant of the previous sample, so integers are rounded. 64-bit values are
+    /// interpolated in double precision.
+    pub fn estimate_at(&self, timestamp: DateTime<Utc>, max_gap: Duration) -> Option<RawValue> {
+        let usable = |event: &&IOEvent| {
+            matches!(event.kind, EventKind::SensorRead | EventKind::OutputWrite)
+                && event.quality != EventQuality::Bad
+                && event.value.is_finite()
+        };
+        let before = self.log.range(..=EventKey::last(timestamp)).map(|(_, event)| event).rev().find(usable)?;
+        if before.timestamp == timestamp {
+            return Some(before.value);
+        }
+        if timestamp - before.timestamp > max_gap {
+            return None;
+        }
+
+        let after = self.log.range((Excluded(EventKey::last(timestamp)), Unbounded)).map(|(_, event)| event).find(usable);
+        let interpolated = after
+            .filter(|after| after.timestamp - before.timestamp <= max_gap)
+            .and_then(|after| {
+                let span = (after.timestamp - before.timestamp).num_nanoseconds()? as f64;
+                let elapsed = (timestamp - before.timestamp).num_nanoseconds()? as f64;
+                interpolate(&before.value, &after.value, elapsed / span)
+            });
+        Some(interpolated.unwrap_or(before.value))
     }
 
     /// Push a new event to log
     ///
+    /// Events are keyed by [`IOEvent::key()`]. If the key already exists, the event is handled
+    /// according to [`Log::collision_policy()`].
+    ///
     /// # Parameters
     ///
     /// - `event`: new event to append
@@ -126,38 +455,285 @@ impl Log {
     ///
     /// A `Result` that contains:
     ///
-    /// - `Ok`: with a reference to inserted log is inserted when [`IOEvent.timestamp`] does not exist in log
-    /// - `Err`: with an [`ErrorKind::ContainerError`] error if timestamp already exists in log
+    /// - `Ok`: with a reference to inserted event, which may have been relocated by
+    ///   [`CollisionPolicy::Bump`] or [`CollisionPolicy::Sequence`]
+    /// - `Err`: with an [`ErrorKind::ContainerError`] error if key already exists in log and policy
+    ///   is [`CollisionPolicy::Reject`]
+    ///
+    /// # See Also
+    ///
+    /// - [`Log::set_capacity()`] for limiting number of retained events
+    /// - [`Log::set_collision_policy()`] for handling of duplicate timestamps
     pub fn push(
         &mut self,
         event: IOEvent,
     ) -> Result<&mut IOEvent, ContainerError> {
-        match self.log.entry(event.timestamp) {
-            Entry::Occupied(_) => Err(ContainerError::KeyExists { key: event.timestamp.to_string()}),
-            Entry::Vacant(entry) => Ok(entry.insert(event)),
+        let key = event.key();
+        let event = match (self.log.contains_key(&key), self.collision) {
+            (false, _) => event,
+            (true, CollisionPolicy::Reject) => {
+                return Err(ContainerError::KeyExists { key: key.to_string()});
+            }
+            (true, CollisionPolicy::Overwrite) => {
+                let existing = self.log.get_mut(&key).unwrap();
+                *existing = event;
+                return Ok(existing);
+            }
+            (true, _) => self.relocate(event, false).unwrap(),
+        };
+        self.trim(1);
+        Ok(self.log.entry(event.key()).or_insert(event))
+    }
+
+    /// Move event to the first vacant key following its own, according to collision policy
+    ///
+    /// # Parameters
+    ///
+    /// - `event`: Event whose key exists
+    /// - `dedupe`: Whether to stop at an identical event which was previously relocated
+    ///
+    /// # Returns
+    ///
+    /// Relocated event, or `None` if `dedupe` is set and an identical event was found
+    fn relocate(&self, mut event: IOEvent, dedupe: bool) -> Option<IOEvent> {
+        while let Some(existing) = self.log.get(&event.key()) {
+            if dedupe && *existing == event {
+                return None;
+            }
+            match self.collision {
+                CollisionPolicy::Sequence => event.sequence += 1,
+                _ => event.timestamp += Duration::nanoseconds(1),
+            }
         }
+        Some(event)
     }
 
     /// Extend current [`Log`] with [`EventCollection`] from another [`Log`]
     ///
-    /// This is used for loading archived logs into memory.
+    /// This is used for loading archived logs into memory. Equivalent to [`Log::merge()`] with
+    /// [`MergeMode::Strict`].
     ///
     /// # Parameters
     ///
     /// - `other`: [`Log`] to pull [`EventCollection`] from
     ///
-    /// # Panics
+    /// # Returns
+    ///
+    /// - `Ok` with [`MergeResult`] describing which events were merged
+    /// - `Err` with [`ContainerError::MetadataMismatch`] if both `metadata` fields do not match
+    pub fn extend(&mut self, other: &Log) -> Result<MergeResult, ContainerError> {
+        self.merge(other, MergeMode::Strict)
+    }
+
+    /// Merge events from another [`Log`]
+    ///
+    /// Events which already exist with an identical value are skipped. Events whose timestamp
+    /// already exists with a different event are reported as conflicts and resolved by `mode`.
+    ///
+    /// # Parameters
+    ///
+    /// - `other`: [`Log`] to pull events from
+    /// - `mode`: How metadata mismatches and conflicting events are handled
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with [`MergeResult`] describing which events were merged
+    /// - `Err` with [`ContainerError::MetadataMismatch`] if `mode` is [`MergeMode::Strict`] and
+    ///   both `metadata` fields do not match. Log is unchanged.
+    pub fn merge(&mut self, other: &Log, mode: MergeMode) -> Result<MergeResult, ContainerError> {
+        self.merge_events(other.metadata.as_ref(), &other.log, mode)
+    }
+
+    fn merge_events(
+        &mut self,
+        metadata: Option<&DeviceMetadata>,
+        events: &EventCollection,
+        mode: MergeMode,
+    ) -> Result<MergeResult, ContainerError> {
+        if mode == MergeMode::Strict && self.metadata.as_ref() != metadata {
+            let describe = |metadata: Option<&DeviceMetadata>| metadata
+                .map(|metadata| metadata.to_string())
+                .unwrap_or_else(|| String::from("unknown device"));
+            return Err(ContainerError::MetadataMismatch {
+                expected: describe(self.metadata.as_ref()),
+                found: describe(metadata),
+            });
+        }
+
+        let mut result = MergeResult::default();
+        for (key, incoming) in events.iter() {
+            let existing = match self.log.get(key) {
+                None => {
+                    self.log.insert(*key, incoming.clone());
+                    result.merged += 1;
+                    continue;
+                }
+                Some(existing) if existing == incoming => {
+                    result.skipped += 1;
+                    continue;
+                }
+                Some(existing) => existing.clone(),
+            };
+
+            let resolved = match (mode, self.collision) {
+                (MergeMode::Overwrite, _) | (_, CollisionPolicy::Overwrite) => Some((*key, incoming.clone())),
+                (_, CollisionPolicy::Reject) => None,
+                _ => match self.relocate(incoming.clone(), true) {
+                    Some(relocated) => Some((relocated.key(), relocated)),
+                    // merged by a previous import
+                    None => {
+                        result.skipped += 1;
+                        continue;
+                    }
+                },
+            };
+            result.conflicts.push(Conflict {
+                timestamp: key.timestamp,
+                existing,
+                incoming: incoming.clone(),
+            });
+            match resolved {
+                Some((key, event)) => {
+                    self.log.insert(key, event);
+                    result.merged += 1;
+                }
+                None => result.skipped += 1,
+            }
+        }
+        self.trim(0);
+        Ok(result)
+    }
+}
+
+impl Log {
+    /// Search logs saved by devices of the same group for one with the same UUID
+    ///
+    /// # Returns
     ///
-    /// If both `metadata` fields do not match, then program panics.
-    pub fn extend(&mut self, other: &mut Log) {
-        if self.metadata != other.metadata {
-            panic!("Metadata does not match. Cannot extend");
+    /// `None` if UUID is nil, or no log was saved with it
+    fn find_by_uuid(&self) -> Option<PathBuf> {
+        /// Deserializes metadata only, so that events are skipped
+        #[derive(Deserialize)]
+        struct Header {
+            metadata: Option<DeviceMetadata>,
         }
 
-        self.log.extend(other.log.clone());
+        let uuid = self.metadata.as_ref()?.uuid;
+        if uuid.is_nil() {
+            return None;
+        }
+        let group = self.dir.as_ref()?.parent()?;
+        read_dir(group).ok()?
+            .flatten()
+            .filter_map(|device| read_dir(device.path()).ok())
+            .flatten()
+            .flatten()
+            .map(|file| file.path())
+            .filter(|path| path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(settings::LOG_FN_PREFIX) && name.ends_with(FILETYPE)))
+            .find(|path| {
+                let header = File::open(path).ok()
+                    .and_then(|file| serde_json::from_reader::<_, Header>(BufReader::new(file)).ok());
+                header.and_then(|header| header.metadata).is_some_and(|metadata| metadata.uuid == uuid)
+            })
+    }
+}
+
+impl Def<Log> {
+    /// Merge events from another shared [`Log`]
+    ///
+    /// Events of `other` are copied before `self` is locked, so both logs are never locked at the
+    /// same time. This avoids deadlocks when two logs are merged into each other concurrently, or
+    /// when a log is merged into itself.
+    ///
+    /// # See Also
+    ///
+    /// - [`Log::merge()`] for how events are merged
+    pub fn merge(&self, other: &Def<Log>, mode: MergeMode) -> Result<MergeResult, ContainerError> {
+        let (metadata, events) = {
+            let other = other.lock().unwrap();
+            (other.metadata.clone(), other.log.clone())
+        };
+        self.lock().unwrap().merge_events(metadata.as_ref(), &events, mode)
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+/// Handling of an event whose timestamp already exists in a [`Log`]
+///
+/// # Example
+///
+/// ```
+/// use chrono::Utc;
+/// use sensd::io::{IOEvent, RawValue};
+/// use sensd::storage::{CollisionPolicy, Log};
+///
+/// let now = Utc::now();
+/// let mut log = Log::default();
+/// log.set_collision_policy(CollisionPolicy::Sequence);
+/// log.push(IOEvent::with_timestamp(now, RawValue::Int(1))).unwrap();
+///
+/// let event = log.push(IOEvent::with_timestamp(now, RawValue::Int(2))).unwrap();
+/// assert_eq!((now, 1), (event.timestamp, event.sequence));
+/// assert_eq!(2, log.len());
+/// ```
+pub enum CollisionPolicy {
+    /// Event is not added. [`Log::push()`] returns an error.
+    #[default]
+    Reject,
+
+    /// Timestamp of event is moved forward by a nanosecond until it is unique
+    Bump,
+
+    /// Event keeps its timestamp and is numbered by [`IOEvent::sequence`], ordering it after the
+    /// events it collides with. Since it is keyed by [`IOEvent::key()`], it cannot be found by its
+    /// timestamp alone (ie: with [`Log::get_mut()`]).
+    Sequence,
+
+    /// Existing event is replaced
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Handling of metadata mismatches and conflicting events when merging logs
+///
+/// Conflicting events are handled by the [`CollisionPolicy`] of the log being merged into, unless
+/// the mode is [`MergeMode::Overwrite`]. What follows describes the default
+/// [`CollisionPolicy::Reject`].
+pub enum MergeMode {
+    /// Metadata must match. Conflicting events keep the existing event.
+    #[default]
+    Strict,
+
+    /// Metadata mismatches are tolerated. Conflicting events keep the existing event.
+    KeepExisting,
+
+    /// Metadata mismatches are tolerated. Conflicting events are replaced by the incoming event.
+    Overwrite,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Two different events which share a timestamp
+pub struct Conflict {
+    pub timestamp: DateTime<Utc>,
+    pub existing: IOEvent,
+    pub incoming: IOEvent,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// Outcome of [`Log::merge()`]
+pub struct MergeResult {
+    /// Number of events inserted or replaced
+    pub merged: usize,
+
+    /// Number of events left out, either because they already exist or were conflicting
+    pub skipped: usize,
+
+    /// Every conflicting event, regardless of how it was resolved
+    pub conflicts: Vec<Conflict>,
+}
+
 // Implement save/load operations for `Log`
 impl Persistent for Log {
     /// Save log to disk in JSON format
@@ -178,17 +754,21 @@ impl Persistent for Log {
     ///
     /// - [`Log::full_path()`] explains usage of `path` parameter.
     fn save(&self) -> Result<(), ErrorType> {
-        let file = writable_or_create(self.full_path());
-        let writer = BufWriter::new(file);
-
-        match serde_json::to_writer_pretty(writer, &self) {
-            Ok(_) => println!("Saved"),
-            Err(e) => {
-                let msg = e.to_string();
-                return Err(
-                    Box::new(FilesystemError::SerializationError {msg}));
-            }
+        // written to a temporary file then moved into place, so that a log which shrank since the
+        // last save does not leave the tail of the previous file behind
+        let path = self.full_path();
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(writable_or_create(&tmp));
+        writer.get_ref().set_len(0)?;
+
+        if let Err(e) = serde_json::to_writer_pretty(&mut writer, &self) {
+            let msg = e.to_string();
+            return Err(
+                Box::new(FilesystemError::SerializationError {msg}));
         }
+        writer.flush()?;
+        rename(&tmp, &path)?;
+        console::info(format!("Saved {}", path.display()));
         Ok(())
     }
 
@@ -204,6 +784,10 @@ impl Persistent for Log {
     ///
     /// # Returns
     ///
+    /// If no log exists at [`Log::full_path()`], logs of other devices of the same group are searched
+    /// for one saved with the same UUID, so that logs are found after a device is renamed or
+    /// renumbered. The UUID of the saved log is adopted once loaded.
+    ///
     /// A `Result` containing:
     ///
     /// - `Ok()`: with `()` when loading from disk and deserialization is successful.
@@ -215,7 +799,11 @@ impl Persistent for Log {
     /// - [`Log::full_path()`] explains usage of `path` parameter.
     fn load(&mut self) -> Result<(), ErrorType> {
         if self.log.is_empty() {
-            let file = File::open(self.full_path().deref())?;
+            let path = match self.exists() {
+                true => self.full_path(),
+                false => self.find_by_uuid().unwrap_or_else(|| self.full_path()),
+            };
+            let file = File::open(path.deref())?;
             let reader = BufReader::new(file);
 
             let buff: Log = match serde_json::from_reader(reader) {
@@ -228,6 +816,12 @@ impl Persistent for Log {
                 }
             };
             self.log = buff.log;
+
+            // adopt identity log was saved with
+            let saved = buff.metadata.map(|metadata| metadata.uuid).filter(|uuid| !uuid.is_nil());
+            if let (Some(metadata), Some(uuid)) = (self.metadata.as_mut(), saved) {
+                metadata.uuid = uuid;
+            }
             Ok(())
         } else {
             Err(Box::new(ContainerError::ContainerNotEmpty))
@@ -271,10 +865,40 @@ impl Document for Log {
 }
 
 // Testing
+/// Value a `fraction` of the way from `start` to `end`, in the variant of `start`
+///
+/// 64-bit variants are interpolated in `f64`, since `f32` loses precision above 2^24.
+fn interpolate(start: &RawValue, end: &RawValue, fraction: f64) -> Option<RawValue> {
+    match (*start, *end) {
+        (RawValue::Int64(start), RawValue::Int64(end)) => {
+            // widened so that the difference cannot overflow
+            let delta = ((end as i128 - start as i128) as f64 * fraction).round() as i128;
+            Some(RawValue::Int64((start as i128 + delta) as i64))
+        }
+        (RawValue::Float64(start), RawValue::Float64(end)) =>
+            Some(RawValue::Float64(start + (end - start) * fraction)),
+        _ => {
+            let (first, last) = (start.as_float()?, end.as_float()?);
+            start.with_float(first + (last - first) * fraction as f32)
+        }
+    }
+}
+
+/// Deserialize events, keying each by [`IOEvent::key()`]
+///
+/// Logs saved by earlier builds keyed sequenced events by their timestamp offset by `sequence`
+/// nanoseconds. Re-keying them prevents those keys from colliding with later events.
+fn deserialize_events<'de, D: Deserializer<'de>>(deserializer: D) -> Result<EventCollection, D::Error> {
+    let events = EventCollection::deserialize(deserializer)?;
+    Ok(events.into_values().map(|event| (event.key(), event)).collect())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::io::{IOKind, RawValue, IOEvent, DeviceMetadata, IODirection};
-    use crate::storage::{Document, Log, Persistent};
+    use chrono::{Duration as ChronoDuration, SecondsFormat, TimeZone, Utc};
+    use crate::io::{IOKind, RawValue, IOEvent, DeviceMetadata, IODirection, EventKind, EventQuality};
+    use crate::helpers::Def;
+    use crate::storage::{CollisionPolicy, Document, EventKey, Log, MergeMode, Persistent};
     use std::path::Path;
     use std::time::Duration;
     use std::{fs, thread};
@@ -343,6 +967,23 @@ mod tests {
         fs::remove_file(filename).unwrap();
     }
 
+    #[test]
+    /// Assert that events are yielded chronologically regardless of insertion order
+    fn chronological() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let mut log = Log::default();
+        for offset in [3, 1, 4, 0, 2] {
+            log.push(IOEvent::with_timestamp(start + ChronoDuration::seconds(offset), RawValue::Int(offset as i32))).unwrap();
+        }
+
+        let values: Vec<RawValue> = log.iter().map(|(_, event)| event.value).collect();
+        assert_eq!((0..5).map(RawValue::Int).collect::<Vec<_>>(), values);
+        assert_eq!(RawValue::Int(0), log.first().unwrap().value);
+        assert_eq!(RawValue::Int(4), log.last().unwrap().value);
+        assert_eq!(2, log.range(start + ChronoDuration::seconds(1), start + ChronoDuration::seconds(3)).count());
+        assert_eq!(0, log.range(start + ChronoDuration::seconds(3), start).count());
+    }
+
     #[test]
     fn set_dir() {
         let mut log = Log::default();
@@ -354,15 +995,306 @@ mod tests {
         assert!(log.dir().is_some())
     }
 
+    #[test]
+    fn filter_kind() {
+        let mut log = generate_log(5, None);
+        log.push(IOEvent::new(RawValue::default()).set_kind(EventKind::OutputWrite)).unwrap();
+
+        assert_eq!(5, log.filter_kind(EventKind::SensorRead).count());
+        assert_eq!(1, log.filter_kind(EventKind::OutputWrite).count());
+        assert_eq!(0, log.filter_kind(EventKind::AlarmRaised).count());
+    }
+
     #[test]
     fn test_extend() {
         let mut orig = generate_log(50, None);
-        let mut new = generate_log(50, None);
+        let new = generate_log(50, None);
 
         assert_eq!(50, orig.iter().count());
 
-        orig.extend(&mut new);
+        orig.extend(&new).unwrap();
 
         assert_eq!(100, orig.iter().count())
     }
+
+    #[test]
+    fn merge() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let metadata = DeviceMetadata::new("test", 0, IOKind::Unassigned, IODirection::In);
+        let event = |offset: i64, value: i32| IOEvent::with_timestamp(start + ChronoDuration::seconds(offset), RawValue::Int(value));
+
+        let mut orig = Log::with_metadata(&metadata);
+        orig.push(event(0, 0)).unwrap();
+        orig.push(event(1, 1)).unwrap();
+
+        let mut other = Log::with_metadata(&metadata);
+        other.push(event(0, 0)).unwrap();
+        other.push(event(1, 5)).unwrap();
+        other.push(event(2, 2)).unwrap();
+
+        let result = orig.merge(&other, MergeMode::Strict).unwrap();
+        assert_eq!(1, result.merged);
+        assert_eq!(2, result.skipped);
+        assert_eq!(1, result.conflicts.len());
+        assert_eq!(RawValue::Int(1), result.conflicts[0].existing.value);
+        assert_eq!(RawValue::Int(1), orig.range(start + ChronoDuration::seconds(1), start + ChronoDuration::seconds(2)).next().unwrap().1.value);
+
+        let result = orig.merge(&other, MergeMode::Overwrite).unwrap();
+        assert_eq!(1, result.merged);
+        assert_eq!(RawValue::Int(5), orig.range(start + ChronoDuration::seconds(1), start + ChronoDuration::seconds(2)).next().unwrap().1.value);
+
+        // metadata mismatch is only tolerated by tolerant modes
+        let mut foreign = Log::default();
+        foreign.push(event(3, 3)).unwrap();
+        assert!(orig.extend(&foreign).is_err());
+        assert_eq!(3, orig.len());
+        assert_eq!(1, orig.merge(&foreign, MergeMode::KeepExisting).unwrap().merged);
+
+        // shared logs may be merged into themselves without deadlocking
+        let shared = Def::new(orig);
+        let result = shared.merge(&shared.clone(), MergeMode::Strict).unwrap();
+        assert_eq!(0, result.merged);
+        assert_eq!(4, result.skipped);
+    }
+
+    #[test]
+    fn collision_policy() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let event = |value: i32| IOEvent::with_timestamp(start, RawValue::Int(value));
+        let nanos = |nanos: i64| start + ChronoDuration::nanoseconds(nanos);
+
+        let mut log = Log::default();
+        log.push(event(0)).unwrap();
+        assert!(log.push(event(1)).is_err());
+
+        log.set_collision_policy(CollisionPolicy::Overwrite);
+        log.push(event(1)).unwrap();
+        assert_eq!((1, RawValue::Int(1)), (log.len(), log.first().unwrap().value));
+
+        log.set_collision_policy(CollisionPolicy::Bump);
+        assert_eq!(nanos(1), log.push(event(2)).unwrap().timestamp);
+        assert_eq!(nanos(2), log.push(event(3)).unwrap().timestamp);
+
+        log.set_collision_policy(CollisionPolicy::Sequence);
+        let sequenced = log.push(event(4)).unwrap();
+        assert_eq!((start, 1), (sequenced.timestamp, sequenced.sequence));
+        let values: Vec<_> = log.iter().map(|(_, event)| event.value).collect();
+        assert_eq!([1, 4, 2, 3].map(RawValue::Int).to_vec(), values);
+
+        // imported events are relocated once, rather than on every import
+        let mut other = Log::default();
+        other.push(event(5)).unwrap();
+        other.push(IOEvent::with_timestamp(nanos(10), RawValue::Int(6))).unwrap();
+        let result = log.merge(&other, MergeMode::KeepExisting).unwrap();
+        assert_eq!((2, 1), (result.merged, result.conflicts.len()));
+        assert_eq!(RawValue::Int(5), log.iter().nth(2).unwrap().1.value);
+        let result = log.merge(&other, MergeMode::KeepExisting).unwrap();
+        assert_eq!((0, 2, 0), (result.merged, result.skipped, result.conflicts.len()));
+
+        // sequence survives saving
+        let json = serde_json::to_string(&log).unwrap();
+        let loaded: Log = serde_json::from_str(&json).unwrap();
+        assert_eq!(6, loaded.len());
+        assert_eq!(2, loaded.iter().nth(2).unwrap().1.sequence);
+        assert_eq!(log.iter().collect::<Vec<_>>(), loaded.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn backfill_after_sequence() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let backfilled = IOEvent::with_timestamp(start + ChronoDuration::nanoseconds(1), RawValue::Int(2));
+
+        let mut log = Log::default();
+        log.set_collision_policy(CollisionPolicy::Sequence);
+        log.push(IOEvent::with_timestamp(start, RawValue::Int(0))).unwrap();
+        log.push(IOEvent::with_timestamp(start, RawValue::Int(1))).unwrap();
+
+        // lands where sequenced events were once keyed, but does not collide
+        let mut other = Log::default();
+        other.push(backfilled.clone()).unwrap();
+        log.set_collision_policy(CollisionPolicy::Overwrite);
+        let result = log.merge(&other, MergeMode::Overwrite).unwrap();
+        assert_eq!((1, 0), (result.merged, result.conflicts.len()));
+        let pushed = log.push(backfilled.clone()).unwrap();
+        assert_eq!(0, pushed.sequence);
+
+        let events: Vec<_> = log.iter().map(|(_, event)| (event.sequence, event.value)).collect();
+        assert_eq!(vec![(0, RawValue::Int(0)), (1, RawValue::Int(1)), (0, RawValue::Int(2))], events);
+    }
+
+    #[test]
+    fn offset_keys_are_rekeyed() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let mut sequenced = IOEvent::with_timestamp(start, RawValue::Int(1));
+        sequenced.sequence = 1;
+
+        // sequenced events were keyed by their timestamp offset by `sequence` nanoseconds
+        let offset = (start + ChronoDuration::nanoseconds(1)).to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        let json = serde_json::json!({
+            "metadata": null,
+            "log": {
+                (start.to_rfc3339_opts(SecondsFormat::AutoSi, true)): IOEvent::with_timestamp(start, RawValue::Int(0)),
+                (offset): sequenced,
+            },
+        });
+        let mut log: Log = serde_json::from_value(json).unwrap();
+        assert_eq!(vec![EventKey::from(start), EventKey::new(start, 1)], log.iter().map(|(key, _)| *key).collect::<Vec<_>>());
+
+        let backfilled = log.push(IOEvent::with_timestamp(start + ChronoDuration::nanoseconds(1), RawValue::Int(2))).unwrap();
+        assert_eq!(0, backfilled.sequence);
+        assert_eq!(3, log.len());
+    }
+
+    #[test]
+    fn capacity() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let event = |offset: i64| IOEvent::with_timestamp(start + ChronoDuration::seconds(offset), RawValue::Int(offset as i32));
+
+        let mut log = Log::default();
+        for offset in 0..5 {
+            log.push(event(offset)).unwrap();
+        }
+        log.set_capacity(Some(3));
+        assert_eq!(3, log.len());
+        assert_eq!(start + ChronoDuration::seconds(2), log.first().unwrap().timestamp);
+
+        log.push(event(5)).unwrap();
+        assert_eq!(3, log.len());
+        assert_eq!(start + ChronoDuration::seconds(3), log.first().unwrap().timestamp);
+
+        // duplicate timestamps do not discard events
+        assert!(log.push(event(5)).is_err());
+        assert_eq!(3, log.len());
+
+        let mut other = Log::default();
+        other.push(event(10)).unwrap();
+        other.push(event(11)).unwrap();
+        log.merge(&other, MergeMode::KeepExisting).unwrap();
+        assert_eq!(3, log.len());
+        assert_eq!(start + ChronoDuration::seconds(11), log.last().unwrap().timestamp);
+    }
+
+    #[test]
+    fn paging() {
+        let dir = std::env::temp_dir().join("sensd_paging_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let at = |offset: i64| start + ChronoDuration::seconds(offset);
+        let metadata = DeviceMetadata::new("level", 0, IOKind::Flow, IODirection::In);
+        let mut log = Log::with_metadata(&metadata);
+        log.set_dir_ref(&dir);
+        log.set_capacity(Some(4)).set_paging(Some(2));
+
+        for offset in 0..9 {
+            log.push(IOEvent::with_timestamp(at(offset), RawValue::Int(offset as i32))).unwrap();
+        }
+        // memory stays within capacity, and oldest events are written in pages of 2
+        assert_eq!(3, log.len());
+        assert_eq!(at(6), log.first().unwrap().timestamp);
+        let pages = log.pages().unwrap();
+        assert_eq!(3, pages.len());
+        assert_eq!((at(0), at(1)), (pages[0].first, pages[0].last));
+
+        // range spanning pages and memory
+        let events = log.query(at(3), at(7)).unwrap();
+        let values: Vec<RawValue> = events.iter().map(|event| event.value).collect();
+        assert_eq!(vec![RawValue::Int(3), RawValue::Int(4), RawValue::Int(5), RawValue::Int(6)], values);
+        assert_eq!(9, log.query(at(0), at(10)).unwrap().len());
+
+        // saved log does not contain pages
+        log.save().unwrap();
+        assert_eq!(3, crate::inspect::read_log(log.full_path()).unwrap().len());
+
+        // log shrinks when paged after save, and may still be reloaded
+        log.set_capacity(Some(2));
+        assert_eq!(1, log.len());
+        log.save().unwrap();
+        let mut loaded = Log::with_metadata(&metadata);
+        loaded.set_dir_ref(&dir);
+        loaded.load().unwrap();
+        assert_eq!(1, loaded.len());
+
+        // oldest pages are removed beyond retention
+        log.set_page_retention(Some(2));
+        for offset in 9..11 {
+            log.push(IOEvent::with_timestamp(at(offset), RawValue::Int(offset as i32))).unwrap();
+        }
+        let pages = log.pages().unwrap();
+        assert_eq!(2, pages.len());
+        assert_eq!(at(6), pages[0].first);
+
+        // without directory, events are discarded
+        let mut log = Log::with_metadata(&metadata);
+        log.set_capacity(Some(2)).set_paging(Some(2));
+        for offset in 0..4 {
+            log.push(IOEvent::with_timestamp(at(offset), RawValue::Int(offset as i32))).unwrap();
+        }
+        assert_eq!(2, log.query(at(0), at(10)).unwrap().len());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    /// Assert that a log which shrank since its last save can be reloaded
+    fn save_after_trim() {
+        let dir = std::env::temp_dir().join("sensd_save_after_trim_test");
+        let _ = fs::remove_dir_all(&dir);
+
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let metadata = DeviceMetadata::new("level", 0, IOKind::Flow, IODirection::In);
+        let mut log = Log::with_metadata(&metadata);
+        log.set_dir_ref(&dir);
+        for offset in 0..6 {
+            log.push(IOEvent::with_timestamp(start + ChronoDuration::seconds(offset), RawValue::Int(offset as i32))).unwrap();
+        }
+        log.save().unwrap();
+
+        log.set_capacity(Some(2));
+        log.save().unwrap();
+
+        let mut loaded = Log::with_metadata(&metadata);
+        loaded.set_dir_ref(&dir);
+        loaded.load().unwrap();
+        assert_eq!(2, loaded.len());
+        assert_eq!(start + ChronoDuration::seconds(4), loaded.first().unwrap().timestamp);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn estimate_at() {
+        let at = |seconds| Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap() + ChronoDuration::seconds(seconds);
+        let mut log = Log::default();
+        log.push(IOEvent::with_timestamp(at(0), RawValue::Float(10.0))).unwrap();
+        log.push(IOEvent::with_timestamp(at(10), RawValue::Float(20.0))).unwrap();
+        let mut bad = IOEvent::with_timestamp(at(15), RawValue::Float(100.0));
+        bad.quality = EventQuality::Bad;
+        log.push(bad).unwrap();
+        log.push(IOEvent::with_timestamp(at(20), RawValue::Float(0.0))).unwrap();
+        log.push(IOEvent::with_timestamp(at(100), RawValue::Float(50.0))).unwrap();
+        let gap = ChronoDuration::seconds(30);
+
+        assert_eq!(None, log.estimate_at(at(-1), gap));
+        assert_eq!(Some(RawValue::Float(10.0)), log.estimate_at(at(0), gap));
+        assert_eq!(Some(RawValue::Float(15.0)), log.estimate_at(at(5), gap));
+        // bad samples are skipped
+        assert_eq!(Some(RawValue::Float(10.0)), log.estimate_at(at(15), gap));
+        // samples too far apart are held rather than interpolated
+        assert_eq!(Some(RawValue::Float(0.0)), log.estimate_at(at(40), gap));
+        assert_eq!(None, log.estimate_at(at(60), gap));
+        assert_eq!(Some(RawValue::Float(50.0)), log.estimate_at(at(110), gap));
+
+        // 64-bit values keep precision beyond f32
+        let mut log = Log::default();
+        log.push(IOEvent::with_timestamp(at(0), RawValue::Int64(1 << 40))).unwrap();
+        log.push(IOEvent::with_timestamp(at(10), RawValue::Int64((1 << 40) + 10))).unwrap();
+        assert_eq!(Some(RawValue::Int64((1 << 40) + 3)), log.estimate_at(at(3), gap));
+
+        let mut log = Log::default();
+        log.push(IOEvent::with_timestamp(at(0), RawValue::Float64(1e9))).unwrap();
+        log.push(IOEvent::with_timestamp(at(10), RawValue::Float64(1e9 + 1.0))).unwrap();
+        assert_eq!(Some(RawValue::Float64(1e9 + 0.5)), log.estimate_at(at(5), gap));
+    }
 }
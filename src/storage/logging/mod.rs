@@ -1,8 +1,10 @@
 //! Datalogging of `IOEvent` objects
 mod chronicle;
 mod log;
+mod paging;
 mod types;
 
 pub use chronicle::Chronicle;
 pub use log::*;
+pub use paging::{Page, PAGE_SUFFIX};
 pub use types::*;
\ No newline at end of file
@@ -0,0 +1,105 @@
+use chrono::{DateTime, TimeZone, Utc};
+use std::fs::{read_dir, remove_file, write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ErrorType, FilesystemError};
+use crate::io::IOEvent;
+use crate::storage::read_records;
+
+/// Suffix of files storing events paged out of memory by [`crate::storage::Log`]
+pub const PAGE_SUFFIX: &str = ".page";
+
+#[derive(Debug, Clone, PartialEq)]
+/// Segment of events paged to disk once the capacity of a [`crate::storage::Log`] was exceeded
+///
+/// Pages are stored beside the saved log as newline delimited JSON, and are named after the log
+/// and the keys of the first and last events they contain, so that a range query only reads pages
+/// which overlap the range.
+pub struct Page {
+    /// Key of earliest event
+    pub first: DateTime<Utc>,
+    /// Key of latest event
+    pub last: DateTime<Utc>,
+    pub path: PathBuf,
+}
+
+impl Page {
+    /// Check if page contains any key within `start` (inclusive) and `end` (exclusive)
+    pub fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        self.first < end && start <= self.last
+    }
+
+    /// Read all events of page, in chronological order
+    pub fn read(&self) -> Result<Vec<IOEvent>, ErrorType> {
+        read_records(&self.path)
+    }
+}
+
+/// Write events to a new page
+///
+/// # Parameters
+///
+/// - `dir`: Directory of log
+/// - `stem`: Filename of log without filetype
+/// - `events`: Events in chronological order. Nothing is written if empty.
+pub(crate) fn write_page(dir: &Path, stem: &str, events: &[IOEvent]) -> Result<(), ErrorType> {
+    let (first, last) = match (events.first(), events.last()) {
+        (Some(first), Some(last)) => (first.timestamp, last.timestamp),
+        _ => return Ok(()),
+    };
+    let mut contents = String::new();
+    for event in events {
+        let line = serde_json::to_string(event)
+            .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+        contents.push_str(&line);
+        contents.push('\n');
+    }
+    let name = format!("{}.{}_{}{}", stem, nanos(first), nanos(last), PAGE_SUFFIX);
+    write(dir.join(name), contents)?;
+    Ok(())
+}
+
+/// Pages of a log, sorted by key of earliest event
+///
+/// # Returns
+///
+/// Empty if `dir` does not exist
+pub(crate) fn find_pages(dir: &Path, stem: &str) -> Result<Vec<Page>, ErrorType> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let prefix = format!("{}.", stem);
+    let mut pages = Vec::new();
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        let keys = path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|name| name.strip_suffix(PAGE_SUFFIX))
+            .and_then(|keys| keys.split_once('_'))
+            .and_then(|(first, last)| Some((first.parse().ok()?, last.parse().ok()?)));
+        if let Some((first, last)) = keys {
+            pages.push(Page {
+                first: Utc.timestamp_nanos(first),
+                last: Utc.timestamp_nanos(last),
+                path,
+            });
+        }
+    }
+    pages.sort_by_key(|page| page.first);
+    Ok(pages)
+}
+
+/// Delete oldest pages of a log until at most `retain` pages remain
+pub(crate) fn prune_pages(dir: &Path, stem: &str, retain: usize) -> Result<(), ErrorType> {
+    let pages = find_pages(dir, stem)?;
+    let excess = pages.len().saturating_sub(retain);
+    for page in pages.iter().take(excess) {
+        remove_file(&page.path)?;
+    }
+    Ok(())
+}
+
+fn nanos(timestamp: DateTime<Utc>) -> i64 {
+    timestamp.timestamp_nanos()
+}
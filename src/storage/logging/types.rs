@@ -1,13 +1,89 @@
 use crate::helpers::Def;
 use crate::io::IOEvent;
 use crate::storage::Log;
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
-/// Mapped collection for storing [`IOEvent`]s by [`DateTime<Utc>`] keys
+/// Separates timestamp and sequence of a serialized [`EventKey`]
+const SEQUENCE_SEPARATOR: char = '#';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Key of an [`IOEvent`] within an [`EventCollection`]
+///
+/// Events which share a timestamp are told apart by [`IOEvent::sequence`], which orders them after
+/// the first event of that timestamp. Since the timestamp itself is never altered, a sequenced key
+/// cannot collide with an event logged at a later time.
 ///
-/// All events should originate from a single source.
-pub type EventCollection = HashMap<DateTime<Utc>, IOEvent>;
+/// Keys are serialized as the RFC 3339 timestamp, followed by `#` and the sequence when it is not
+/// zero (ie: `2023-01-01T00:00:00Z#1`).
+pub struct EventKey {
+    pub timestamp: DateTime<Utc>,
+    pub sequence: u32,
+}
+
+impl EventKey {
+    pub fn new(timestamp: DateTime<Utc>, sequence: u32) -> Self {
+        Self { timestamp, sequence }
+    }
+
+    /// Latest possible key at `timestamp`, for including every event of `timestamp` in a range
+    pub fn last(timestamp: DateTime<Utc>) -> Self {
+        Self::new(timestamp, u32::MAX)
+    }
+}
+
+impl From<DateTime<Utc>> for EventKey {
+    /// Key of first event at `timestamp`
+    fn from(timestamp: DateTime<Utc>) -> Self {
+        Self::new(timestamp, 0)
+    }
+}
+
+impl Display for EventKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true))?;
+        match self.sequence {
+            0 => Ok(()),
+            sequence => write!(f, "{}{}", SEQUENCE_SEPARATOR, sequence),
+        }
+    }
+}
+
+impl FromStr for EventKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (timestamp, sequence) = match s.split_once(SEQUENCE_SEPARATOR) {
+            Some((timestamp, sequence)) => (timestamp, sequence.parse().map_err(|e| format!("{}", e))?),
+            None => (s, 0),
+        };
+        let timestamp = timestamp.parse().map_err(|e| format!("{}", e))?;
+        Ok(Self::new(timestamp, sequence))
+    }
+}
+
+impl Serialize for EventKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EventKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Mapped collection for storing [`IOEvent`]s by [`EventKey`]
+///
+/// Events are ordered by timestamp, so iteration is always chronological. All events should
+/// originate from a single source.
+pub type EventCollection = BTreeMap<EventKey, IOEvent>;
 
 /// Primary container for storing multiple [`Log`] instances
 ///
@@ -18,7 +94,7 @@ pub type LogContainer = Vec<Def<Log>>;
 mod test_event_collection {
     use chrono::{Duration, Utc};
     use crate::io::{IOEvent, RawValue};
-    use crate::storage::EventCollection;
+    use crate::storage::{EventCollection, EventKey};
 
     fn generate_log(count: usize) -> EventCollection {
         let mut log = EventCollection::default();
@@ -29,7 +105,7 @@ mod test_event_collection {
             let timestamp = now - Duration::seconds(i as i64);
             let event = IOEvent::with_timestamp(timestamp, RawValue::Binary(true));
 
-            log.insert(timestamp, event);
+            log.insert(timestamp.into(), event);
         }
 
         log
@@ -44,4 +120,19 @@ mod test_event_collection {
         orig.extend(ext);
         assert_eq!(10, orig.len());
     }
+
+    #[test]
+    fn key() {
+        let timestamp = Utc::now();
+        let first = EventKey::from(timestamp);
+        let sequenced = EventKey::new(timestamp, 1);
+        let later = EventKey::from(timestamp + Duration::nanoseconds(1));
+        assert!(first < sequenced && sequenced < later);
+
+        for key in [first, sequenced] {
+            let json = serde_json::to_string(&key).unwrap();
+            assert_eq!(key, serde_json::from_str(&json).unwrap());
+        }
+        assert_eq!(first, serde_json::from_value(serde_json::to_value(timestamp).unwrap()).unwrap());
+    }
 }
\ No newline at end of file
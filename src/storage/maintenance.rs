@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::alarm::Alarm;
+use crate::io::{DeviceMetadata, IdType, IODirection};
+
+/// Name of the notification passed to sinks when a maintenance window ends
+pub const MAINTENANCE_NAME: &str = "maintenance";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Devices covered by a [`MaintenanceWindow`]
+pub enum MaintenanceTarget {
+    Input(IdType),
+    Output(IdType),
+
+    /// Every device labelled with tag when the window begins
+    Tag(String),
+}
+
+impl MaintenanceTarget {
+    pub fn matches(&self, metadata: &DeviceMetadata) -> bool {
+        match self {
+            MaintenanceTarget::Input(id) => metadata.direction == IODirection::In && metadata.id == *id,
+            MaintenanceTarget::Output(id) => metadata.direction == IODirection::Out && metadata.id == *id,
+            MaintenanceTarget::Tag(tag) => metadata.has_tag(tag),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Period during which alarms of devices are suppressed, and outputs are optionally inhibited
+///
+/// Windows are scheduled by [`crate::storage::Group::schedule_maintenance()`] and begin and end
+/// during [`crate::storage::Group::poll()`]. While a window is open:
+///
+/// - Alarms associated with covered devices are suppressed. They are still tracked and logged, but
+///   sinks are not notified. See [`crate::alarm::AlarmHandler::suppress()`].
+/// - If set by [`MaintenanceWindow::set_inhibit()`], covered outputs are inhibited so that they
+///   cannot be turned on. See [`crate::io::Output::inhibit()`].
+///
+/// When a window ends, a [`MaintenanceSummary`] is delivered as an
+/// [`crate::alarm::AlarmSeverity::Info`] notification named [`MAINTENANCE_NAME`] to the sinks of its
+/// policy.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{Duration, Utc};
+/// use sensd::storage::{Group, MaintenanceTarget, MaintenanceWindow};
+///
+/// let start = Utc::now() + Duration::hours(1);
+/// let mut group = Group::new("");
+/// group.schedule_maintenance(
+///     MaintenanceWindow::new("replace dosing lines", start, start + Duration::hours(2),
+///                            MaintenanceTarget::Tag("dosing".into()))
+///         .set_inhibit(true));
+///
+/// // window has not begun
+/// assert!(group.attempt_maintenance_at(start - Duration::minutes(1)).is_empty());
+/// assert_eq!(1, group.maintenance().count());
+///
+/// let summaries = group.attempt_maintenance_at(start + Duration::hours(2));
+/// assert_eq!("replace dosing lines", summaries[0].name);
+/// assert_eq!(0, group.maintenance().count());
+/// ```
+pub struct MaintenanceWindow {
+    name: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    target: MaintenanceTarget,
+
+    /// Prevent covered outputs from turning on
+    inhibit: bool,
+}
+
+impl MaintenanceWindow {
+    /// Constructor for [`MaintenanceWindow`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of window, used in summary
+    /// - `start`: Time at which window begins
+    /// - `end`: Time at which window ends
+    /// - `target`: Devices covered by window
+    pub fn new<N>(name: N, start: DateTime<Utc>, end: DateTime<Utc>, target: MaintenanceTarget) -> Self
+    where
+        N: Into<String>
+    {
+        Self { name: name.into(), start, end, target, inhibit: false }
+    }
+
+    /// Builder method to inhibit covered outputs while window is open
+    pub fn set_inhibit(mut self, inhibit: bool) -> Self {
+        self.inhibit = inhibit;
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start
+    }
+
+    pub fn end(&self) -> DateTime<Utc> {
+        self.end
+    }
+
+    pub fn target(&self) -> &MaintenanceTarget {
+        &self.target
+    }
+
+    pub fn inhibits(&self) -> bool {
+        self.inhibit
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Record of a [`MaintenanceWindow`] which has ended
+pub struct MaintenanceSummary {
+    pub name: String,
+
+    /// Time at which window was opened
+    pub start: DateTime<Utc>,
+    /// Time at which window was closed
+    pub end: DateTime<Utc>,
+
+    /// Names of devices covered by window
+    pub devices: Vec<String>,
+
+    /// Alarms raised for covered devices while window was open
+    pub suppressed: Vec<Alarm>,
+
+    /// Number of outputs which were inhibited
+    pub inhibited: usize,
+}
+
+impl MaintenanceSummary {
+    /// Human readable summary, used as message of delivered notification
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Maintenance \"{}\" ended after {} minutes. {} devices covered, {} outputs inhibited, {} alarms suppressed",
+                               self.name, (self.end - self.start).num_minutes(), self.devices.len(),
+                               self.inhibited, self.suppressed.len());
+        for alarm in self.suppressed.iter() {
+            text.push_str(&format!("\n- {} ({}): {}", alarm.name(), alarm.severity(), alarm.message()));
+        }
+        text
+    }
+}
+
+/// Scheduled window, and its state once open
+pub(crate) struct Maintenance {
+    pub window: MaintenanceWindow,
+
+    /// Time at which window was opened. `None` until window begins.
+    pub opened: Option<DateTime<Utc>>,
+
+    /// Names of covered devices whose alarms are suppressed
+    pub devices: Vec<String>,
+
+    /// Ids of covered outputs which were inhibited
+    pub inhibited: Vec<IdType>,
+}
+
+impl Maintenance {
+    pub fn new(window: MaintenanceWindow) -> Self {
+        Self { window, opened: None, devices: Vec::new(), inhibited: Vec::new() }
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ErrorType;
+use crate::helpers::now;
+use crate::io::{Device, DeviceMetadata, MetadataPatch};
+use crate::storage::rename::rewrite_log;
+use crate::storage::Document;
+
+/// Filename of metadata audit trail within group directory
+pub const METADATA_HISTORY_FILENAME: &str = "metadata_history.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Audit record of a change to device metadata
+pub struct MetadataChange {
+    pub timestamp: DateTime<Utc>,
+    pub before: DeviceMetadata,
+    pub after: DeviceMetadata,
+}
+
+/// Apply patch to device metadata, and to metadata embedded in its log
+///
+/// If the log has been saved, the log file is rewritten with updated metadata.
+///
+/// # Returns
+///
+/// - `Ok(None)` if patch did not change metadata
+/// - `Ok(Some)` with record of change
+/// - `Err` if log file could not be rewritten. Metadata has already been changed in memory.
+pub(crate) fn patch_device<D: Device>(device: &mut D, patch: &MetadataPatch) -> Result<Option<MetadataChange>, ErrorType> {
+    let before = device.metadata().clone();
+    if !patch.apply(device.metadata_mut()) {
+        return Ok(None);
+    }
+    let after = device.metadata().clone();
+
+    if let Some(log) = device.log() {
+        let mut log = log.try_lock().unwrap();
+        log.set_metadata_ref(after.clone());
+
+        if log.dir().is_some() && log.full_path().exists() {
+            let path = log.full_path();
+            rewrite_log(&path, &path, &after)?;
+        }
+    }
+    Ok(Some(MetadataChange { timestamp: now(), before, after }))
+}
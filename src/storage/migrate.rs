@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_file, rename, write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ContainerError, ErrorType, FilesystemError};
+use crate::inspect::read_log;
+use crate::io::{DeviceMetadata, IdType};
+use crate::storage::{EventCollection, Log, FILETYPE};
+
+/// Suffix appended to legacy log files once their events have been migrated
+pub const MIGRATED_SUFFIX: &str = ".migrated";
+
+#[derive(Debug, Clone, PartialEq)]
+/// Record of a single legacy log converted by [`crate::storage::Group::migrate_legacy()`]
+pub struct Migration {
+    /// Original location of legacy log. Renamed with [`MIGRATED_SUFFIX`] once migrated.
+    pub source: PathBuf,
+    /// Location of converted log
+    pub destination: PathBuf,
+    /// Number of events migrated
+    pub events: usize,
+}
+
+#[derive(Deserialize)]
+/// Log written before device metadata was embedded in logs
+///
+/// Legacy logs were saved directly in the group directory, rather than in per-device directories,
+/// and only identified their device by id, and sometimes by name.
+struct LegacyLog {
+    id: IdType,
+    #[serde(default)]
+    name: Option<String>,
+    log: EventCollection,
+}
+
+/// Find legacy logs saved directly within a group directory
+///
+/// Logs with embedded metadata, and files which do not parse as a legacy log, are ignored.
+fn find_legacy(dir: &Path) -> Result<Vec<(PathBuf, LegacyLog)>, ErrorType> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut found = Vec::new();
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || !path.to_string_lossy().ends_with(FILETYPE) {
+            continue;
+        }
+        let contents: serde_json::Value = match read_to_string(&path).ok().and_then(|s| serde_json::from_str(&s).ok()) {
+            Some(contents) => contents,
+            None => continue,
+        };
+        if contents.get("metadata").is_some() {
+            continue;
+        }
+        if let Ok(legacy) = serde_json::from_value::<LegacyLog>(contents) {
+            found.push((path, legacy));
+        }
+    }
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(found)
+}
+
+/// Convert every legacy log within a group directory
+///
+/// Each legacy log is matched to a device by id, and by name when the legacy log stored one. Legacy
+/// logs matching no device are left untouched. Converted logs are written with embedded metadata,
+/// then read back to verify that no events were lost, before the legacy log is renamed with
+/// [`MIGRATED_SUFFIX`].
+///
+/// # Parameters
+///
+/// - `dir`: Group directory
+/// - `targets`: Metadata and log path of every device that has a log directory
+///
+/// # Errors
+///
+/// Migration stops at the first error. Legacy logs that have already been converted are not
+/// reverted.
+///
+/// - [`ContainerError::AmbiguousKey`] if a legacy log matches more than one device
+/// - [`FilesystemError::PathExists`] if a device has already saved a log
+/// - [`FilesystemError::MigrationMismatch`] if the converted log does not contain every event. The
+///   converted log is removed.
+pub(crate) fn migrate_dir(dir: &Path, targets: &[(DeviceMetadata, PathBuf)]) -> Result<Vec<Migration>, ErrorType> {
+    let mut migrations = Vec::new();
+    for (source, legacy) in find_legacy(dir)? {
+        let matches: Vec<&(DeviceMetadata, PathBuf)> = targets.iter()
+            .filter(|(metadata, _)| metadata.id == legacy.id)
+            .filter(|(metadata, _)| legacy.name.as_ref().is_none_or(|name| *name == metadata.name))
+            .collect();
+        let (metadata, destination) = match matches.as_slice() {
+            [] => continue,
+            [target] => target,
+            _ => return Err(Box::new(ContainerError::AmbiguousKey { key: legacy.id.to_string() })),
+        };
+        migrations.push(migrate_log(source, legacy, metadata, destination)?);
+    }
+    Ok(migrations)
+}
+
+fn migrate_log(source: PathBuf, legacy: LegacyLog, metadata: &DeviceMetadata, destination: &Path) -> Result<Migration, ErrorType> {
+    if destination.exists() {
+        return Err(Box::new(FilesystemError::PathExists { path: destination.display().to_string() }));
+    }
+
+    let expected = legacy.log.len();
+    let mut log = Log::with_metadata(metadata);
+    for (_, event) in legacy.log {
+        log.push(event)?;
+    }
+    let contents = serde_json::to_string_pretty(&log)
+        .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+
+    if let Some(parent) = destination.parent() {
+        create_dir_all(parent)?;
+    }
+    let tmp = destination.with_extension("tmp");
+    write(&tmp, contents)?;
+    rename(&tmp, destination)?;
+
+    let found = read_log(destination).map(|log| log.len()).unwrap_or(0);
+    if found != expected {
+        remove_file(destination)?;
+        return Err(Box::new(FilesystemError::MigrationMismatch {
+            path: source.display().to_string(),
+            expected,
+            found,
+        }));
+    }
+
+    let mut backup = source.clone().into_os_string();
+    backup.push(MIGRATED_SUFFIX);
+    rename(&source, &backup)?;
+
+    Ok(Migration { source, destination: destination.to_path_buf(), events: expected })
+}
@@ -1,15 +1,62 @@
 //! Data structures and interfaces to store data
 //!
+mod archive;
+mod daily;
 mod group;
+mod health;
+mod hooks;
+mod integrity;
+mod journal;
 mod logging;
+mod maintenance;
+mod metadata;
+mod migrate;
+mod mode;
 mod persistent;
+mod poll;
+mod query;
+mod quarantine;
+mod remote;
+mod rename;
+mod roster;
+mod schedule;
+mod snapshot;
+mod state;
+mod stream;
+mod topology;
+mod variables;
+mod view;
 mod directory;
 mod root;
 mod document;
 
+pub use archive::Archive;
+pub use daily::{DailySummary, DAILY_FILENAME};
 pub use document::*;
 pub use group::Group;
+pub use health::{Health, Sweep};
+pub use hooks::*;
+pub use integrity::{sign, signature_path, verify, verify_dir, SigningKey, Verification, SIGNATURE_SUFFIX};
 pub use logging::*;
+pub use maintenance::{MaintenanceSummary, MaintenanceTarget, MaintenanceWindow, MAINTENANCE_NAME};
+pub use metadata::{MetadataChange, METADATA_HISTORY_FILENAME};
+pub use migrate::{Migration, MIGRATED_SUFFIX};
+pub use mode::{Mode, ModeChange, ParameterOverride, MODE_HISTORY_FILENAME};
 pub use persistent::{Persistent, FILETYPE};
+pub use poll::{PollOutcome, PollReport};
+pub use quarantine::QuarantinePolicy;
+pub use query::{stitch, Segment, Series, SeriesPoint, Tier};
+pub use remote::*;
+pub use roster::Roster;
 pub use directory::*;
 pub use root::*;
+pub use schedule::{JitterStats, PollSchedule};
+pub use snapshot::{Difference, Snapshot, SNAPSHOT_FILENAME};
+pub use state::{GroupState, FORMAT_VERSION, GROUP_STATE_FILENAME};
+pub(crate) use state::read_versioned;
+pub use stream::{Decimation, LogStream};
+pub use topology::{NodeKind, PendingRoutine, Topology, TopologyEdge, TopologyNode};
+pub use variables::{VariableChange, Variables, VARIABLES_FILENAME};
+pub use view::GroupView;
+
+pub(crate) use journal::{append_record, read_records};
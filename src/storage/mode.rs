@@ -0,0 +1,289 @@
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::errors::ErrorType;
+use crate::io::{IdType, RawValue};
+use crate::storage::{append_record, read_records};
+
+/// Filename of mode change audit trail within group directory
+pub const MODE_HISTORY_FILENAME: &str = "mode_history.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Value applied to an action parameter while a [`Mode`] is active
+pub struct ParameterOverride {
+    /// Id of input whose publisher owns the action
+    pub input: IdType,
+    pub action: String,
+    pub parameter: String,
+    pub value: RawValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Named system mode (ie: Day/Night, Veg/Bloom) with per-mode action parameters
+///
+/// When a mode is selected via [`crate::storage::Group::set_mode()`], each override is applied using
+/// [`crate::action::Action::set_parameter()`].
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::RawValue;
+/// use sensd::storage::Mode;
+///
+/// let night = Mode::new("night")
+///     .set_parameter(0, "heater", "setpoint", RawValue::Float(18.0));
+///
+/// assert_eq!(1, night.overrides().len());
+/// ```
+pub struct Mode {
+    name: String,
+    overrides: Vec<ParameterOverride>,
+}
+
+impl Mode {
+    pub fn new<N>(name: N) -> Self
+    where
+        N: Into<String>
+    {
+        Self { name: name.into(), overrides: Vec::new() }
+    }
+
+    /// Builder method to add a parameter override
+    ///
+    /// # Parameters
+    ///
+    /// - `input`: Id of input whose publisher owns the action
+    /// - `action`: Name of action
+    /// - `parameter`: Name of parameter
+    /// - `value`: Value applied when mode is selected
+    pub fn set_parameter<A, P>(mut self, input: IdType, action: A, parameter: P, value: RawValue) -> Self
+    where
+        A: Into<String>,
+        P: Into<String>,
+    {
+        self.overrides.push(ParameterOverride {
+            input,
+            action: action.into(),
+            parameter: parameter.into(),
+            value,
+        });
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn overrides(&self) -> &[ParameterOverride] {
+        &self.overrides
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Audit record of a mode change
+pub struct ModeChange {
+    pub timestamp: DateTime<Utc>,
+    pub from: Option<String>,
+    pub to: String,
+
+    /// User or process that changed mode. Scheduled changes are attributed to `"schedule"`.
+    pub by: String,
+}
+
+/// Gradual transition of a numeric parameter towards the value of a [`ParameterOverride`]
+pub(crate) struct ParameterRamp {
+    pub target: ParameterOverride,
+    from: f32,
+
+    /// Change per second
+    rate: f32,
+    started: DateTime<Utc>,
+}
+
+impl ParameterRamp {
+    /// Intermediate value at `now`
+    ///
+    /// # Returns
+    ///
+    /// Value to apply and whether target has been reached
+    pub fn value_at(&self, now: DateTime<Utc>) -> (RawValue, bool) {
+        let to = self.target.value.as_float().unwrap();
+        let elapsed = (now - self.started).num_milliseconds().max(0) as f32 / 1000.0;
+        let step = self.rate * elapsed;
+
+        if step >= (to - self.from).abs() {
+            return (self.target.value, true);
+        }
+        let value = self.from + step * (to - self.from).signum();
+        (self.target.value.with_float(value).unwrap(), false)
+    }
+
+    fn is_for(&self, target: &ParameterOverride) -> bool {
+        self.target.input == target.input
+            && self.target.action == target.action
+            && self.target.parameter == target.parameter
+    }
+}
+
+#[derive(Default)]
+/// Registered modes, current mode, daily schedule, and history of mode changes
+pub(crate) struct ModeSelector {
+    modes: Vec<Mode>,
+    current: Option<String>,
+
+    /// Ramp rates keyed by input id and action name
+    rates: HashMap<(IdType, String), f32>,
+    ramps: Vec<ParameterRamp>,
+
+    /// Daily switching times in UTC, sorted by time
+    schedule: Vec<(NaiveTime, String)>,
+    /// Time of last schedule check. `None` until first check.
+    checked: Option<DateTime<Utc>>,
+
+    history: Vec<ModeChange>,
+}
+
+impl ModeSelector {
+    pub fn add(&mut self, mode: Mode) {
+        self.modes.retain(|existing| existing.name != mode.name);
+        self.modes.push(mode);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Mode> {
+        self.modes.iter().find(|mode| mode.name == name)
+    }
+
+    pub fn current(&self) -> Option<&String> {
+        self.current.as_ref()
+    }
+
+    pub fn schedule(&mut self, at: NaiveTime, name: String) {
+        self.schedule.push((at, name));
+        self.schedule.sort_by_key(|(at, _)| *at);
+    }
+
+    pub fn set_rate(&mut self, input: IdType, action: String, rate: f32) {
+        self.rates.insert((input, action), rate.abs());
+    }
+
+    pub fn rate(&self, input: IdType, action: &str) -> Option<f32> {
+        self.rates.get(&(input, action.to_string())).copied()
+    }
+
+    /// Begin ramp towards `target`, replacing any ramp of the same parameter
+    pub fn start_ramp(&mut self, target: ParameterOverride, from: f32, rate: f32, now: DateTime<Utc>) {
+        self.cancel_ramp(&target);
+        self.ramps.push(ParameterRamp { target, from, rate, started: now });
+    }
+
+    pub fn cancel_ramp(&mut self, target: &ParameterOverride) {
+        self.ramps.retain(|ramp| !ramp.is_for(target));
+    }
+
+    pub fn ramps(&self) -> &[ParameterRamp] {
+        &self.ramps
+    }
+
+    /// Remove ramps which have reached their target
+    pub fn finish_ramps(&mut self, now: DateTime<Utc>) {
+        self.ramps.retain(|ramp| !ramp.value_at(now).1);
+    }
+
+    /// Record transition to mode `to`
+    pub fn record(&mut self, to: &str, by: String, timestamp: DateTime<Utc>) -> &ModeChange {
+        let from = self.current.replace(to.to_string());
+        self.history.push(ModeChange { timestamp, from, to: to.to_string(), by });
+        self.history.last().unwrap()
+    }
+
+    pub fn history(&self) -> &[ModeChange] {
+        &self.history
+    }
+
+    /// Replace history, and select the most recent mode without applying overrides
+    pub fn restore_history(&mut self, history: Vec<ModeChange>) {
+        self.current = history.last().map(|change| change.to.clone());
+        self.history = history;
+    }
+
+    /// Most recent scheduled mode whose switching time passed since the previous check
+    ///
+    /// The first check only stores `now`, so that starting the program does not override a mode
+    /// that was restored or set manually.
+    pub fn due(&mut self, now: DateTime<Utc>) -> Option<String> {
+        let since = self.checked.replace(now)?;
+        if now <= since {
+            return None;
+        }
+
+        let mut due = None;
+        let mut day = since.date_naive();
+        while day <= now.date_naive() {
+            for (at, name) in self.schedule.iter() {
+                let time = Utc.from_utc_datetime(&day.and_time(*at));
+                if since < time && time <= now {
+                    due = Some(name.clone());
+                }
+            }
+            day += Duration::days(1);
+        }
+        due
+    }
+}
+
+/// Append mode change to audit trail in `dir`
+///
+/// Changes are stored as newline delimited JSON.
+pub(crate) fn append_history(dir: &Path, change: &ModeChange) -> Result<(), ErrorType> {
+    append_record(&dir.join(MODE_HISTORY_FILENAME), change)
+}
+
+/// Read audit trail written by [`append_history()`] from `dir`
+pub(crate) fn read_history(dir: &Path) -> Result<Vec<ModeChange>, ErrorType> {
+    read_records(&dir.join(MODE_HISTORY_FILENAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveTime, TimeZone, Utc};
+
+    use crate::io::RawValue;
+    use crate::storage::mode::{ModeSelector, ParameterRamp};
+    use crate::storage::ParameterOverride;
+
+    #[test]
+    fn due() {
+        let mut selector = ModeSelector::default();
+        selector.schedule(NaiveTime::from_hms_opt(20, 0, 0).unwrap(), String::from("night"));
+        selector.schedule(NaiveTime::from_hms_opt(6, 0, 0).unwrap(), String::from("day"));
+
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(None, selector.due(start));
+        assert_eq!(None, selector.due(start + Duration::hours(7)));
+        assert_eq!(Some(String::from("night")), selector.due(start + Duration::hours(9)));
+
+        // crosses midnight
+        assert_eq!(Some(String::from("day")), selector.due(start + Duration::hours(19)));
+
+        // multiple switching times elapsed
+        assert_eq!(Some(String::from("day")), selector.due(start + Duration::hours(43)));
+    }
+
+    #[test]
+    fn ramp() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let target = ParameterOverride {
+            input: 0,
+            action: String::from("heater"),
+            parameter: String::from("setpoint"),
+            value: RawValue::Float(18.0),
+        };
+        let ramp = ParameterRamp { target, from: 22.0, rate: 0.1, started: start };
+
+        assert_eq!((RawValue::Float(22.0), false), ramp.value_at(start));
+        assert_eq!((RawValue::Float(20.0), false), ramp.value_at(start + Duration::seconds(20)));
+        assert_eq!((RawValue::Float(18.0), true), ramp.value_at(start + Duration::seconds(45)));
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use crate::errors::DeviceError;
+use crate::io::{IdType, IOEvent};
+
+#[derive(Debug)]
+/// Result of reading a single input during [`crate::storage::Group::poll()`]
+pub struct PollOutcome {
+    pub id: IdType,
+    pub result: Result<IOEvent, DeviceError>,
+}
+
+#[derive(Debug, Default)]
+/// Summary of a single call to [`crate::storage::Group::poll()`]
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::IOCommand;
+/// use sensd::io::{Device, Input, IOKind, RawValue};
+/// use sensd::storage::Group;
+///
+/// let mut group = Group::new("");
+/// group.push_input(Input::new("temp", 0, IOKind::Temperature)
+///     .set_command(IOCommand::Input(|| RawValue::Float(21.0))));
+///
+/// let report = group.poll();
+/// assert!(report.executed());
+/// assert_eq!(1, report.events().count());
+///
+/// // interval has not elapsed
+/// assert!(group.poll().early);
+/// ```
+pub struct PollReport {
+    /// Outcome of each input which was read, in order of reading
+    ///
+    /// Inputs which are warming up or quarantined are not read, and are left out.
+    pub outcomes: Vec<PollOutcome>,
+
+    /// Time taken to read inputs and evaluate actions. Zero if poll was not executed.
+    pub duration: Duration,
+
+    /// Poll was not executed because interval has not elapsed since the previous poll
+    pub early: bool,
+
+    /// Poll was not executed because group is paused
+    pub paused: bool,
+
+    /// Number of action evaluations caused by readings
+    pub propagated: usize,
+}
+
+impl PollReport {
+    /// Check if inputs were read
+    pub fn executed(&self) -> bool {
+        !self.early && !self.paused
+    }
+
+    /// Events of inputs which were read successfully
+    pub fn events(&self) -> impl Iterator<Item = &IOEvent> {
+        self.outcomes.iter().filter_map(|outcome| outcome.result.as_ref().ok())
+    }
+
+    /// Errors of inputs which could not be read
+    pub fn errors(&self) -> impl Iterator<Item = &DeviceError> {
+        self.outcomes.iter().filter_map(|outcome| outcome.result.as_ref().err())
+    }
+
+    /// Check if any input could not be read
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Consume report, keeping only errors
+    pub fn into_errors(self) -> Vec<DeviceError> {
+        self.outcomes.into_iter()
+            .filter_map(|outcome| outcome.result.err())
+            .collect()
+    }
+}
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::action::HoldPolicy;
+use crate::alarm::AlarmSeverity;
+use crate::io::IdType;
+use crate::telemetry::Backoff;
+
+#[derive(Debug, Clone)]
+/// Limits how many consecutive read failures are tolerated before an input is quarantined
+///
+/// Set by [`crate::storage::Group::set_quarantine()`]. Once `budget` consecutive reads of an input
+/// have failed, the input is quarantined:
+///
+/// - The input is no longer polled, so that a dead sensor does not return an error every poll
+/// - Dependent actions are held according to the hold policy (see [`crate::action::Action::hold()`])
+/// - An alarm is raised if an alarm handler is set by [`crate::storage::Group::set_alarm_handler()`]
+///
+/// After a delay given by the backoff schedule, a single trial read is attempted. A successful read
+/// re-admits the input: actions are released and the alarm is cleared. Otherwise, the input remains
+/// quarantined for the next, longer, delay.
+///
+/// # Example
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::action::HoldPolicy;
+/// use sensd::storage::{Group, QuarantinePolicy};
+/// use sensd::telemetry::Backoff;
+///
+/// let mut group = Group::new("");
+/// group.set_quarantine(
+///     QuarantinePolicy::new(5)
+///         .set_backoff(Backoff::new(Duration::minutes(1), Duration::hours(1)))
+///         .set_hold(HoldPolicy::Safe));
+/// ```
+pub struct QuarantinePolicy {
+    budget: usize,
+    backoff: Backoff,
+    hold: HoldPolicy,
+    severity: AlarmSeverity,
+}
+
+impl QuarantinePolicy {
+    /// Constructor for [`QuarantinePolicy`]
+    ///
+    /// # Parameters
+    ///
+    /// - `budget`: Number of consecutive failed reads before an input is quarantined
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget: budget.max(1),
+            backoff: Backoff::default(),
+            hold: HoldPolicy::default(),
+            severity: AlarmSeverity::Warning,
+        }
+    }
+
+    /// Builder method to set delays between re-admission attempts
+    pub fn set_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Builder method to set how dependent actions treat their outputs while quarantined
+    pub fn set_hold(mut self, hold: HoldPolicy) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    /// Builder method to set severity of raised alarms
+    pub fn set_severity(mut self, severity: AlarmSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    pub fn hold(&self) -> HoldPolicy {
+        self.hold
+    }
+
+    pub fn severity(&self) -> AlarmSeverity {
+        self.severity
+    }
+}
+
+impl Default for QuarantinePolicy {
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+/// Failure tracking of a single input
+struct Budget {
+    failures: usize,
+    backoff: Backoff,
+
+    /// Time of next re-admission attempt. `None` while not quarantined.
+    until: Option<DateTime<Utc>>,
+}
+
+/// Change in quarantine status caused by a read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Transition {
+    /// Input was quarantined until given time
+    Quarantined(DateTime<Utc>),
+    /// Input remains quarantined after a failed trial read
+    Extended(DateTime<Utc>),
+    /// Input was re-admitted after a successful trial read
+    Readmitted,
+}
+
+/// Tracks consecutive read failures of every input of a group
+pub(crate) struct Quarantine {
+    policy: QuarantinePolicy,
+    budgets: HashMap<IdType, Budget>,
+}
+
+impl Quarantine {
+    pub fn new(policy: QuarantinePolicy) -> Self {
+        Self { policy, budgets: HashMap::new() }
+    }
+
+    pub fn policy(&self) -> &QuarantinePolicy {
+        &self.policy
+    }
+
+    fn budget(&mut self, id: IdType) -> &mut Budget {
+        let backoff = self.policy.backoff.clone();
+        self.budgets.entry(id).or_insert_with(|| Budget { failures: 0, backoff, until: None })
+    }
+
+    /// Check if input should be read at `now`
+    ///
+    /// Inputs which are not quarantined are always read. Quarantined inputs are read once their
+    /// delay has elapsed.
+    pub fn allows(&self, id: IdType, now: DateTime<Utc>) -> bool {
+        match self.budgets.get(&id).and_then(|budget| budget.until) {
+            Some(until) => now >= until,
+            None => true,
+        }
+    }
+
+    /// Record a successful read
+    pub fn success(&mut self, id: IdType) -> Option<Transition> {
+        let budget = self.budgets.get_mut(&id)?;
+        budget.failures = 0;
+        budget.backoff.reset();
+        budget.until.take().map(|_| Transition::Readmitted)
+    }
+
+    /// Record a failed read
+    pub fn failure(&mut self, id: IdType, now: DateTime<Utc>) -> Option<Transition> {
+        let threshold = self.policy.budget;
+        let budget = self.budget(id);
+        budget.failures += 1;
+
+        let quarantined = budget.until.is_some();
+        if quarantined || budget.failures >= threshold {
+            let until = now + budget.backoff.next_delay();
+            budget.until = Some(until);
+            return Some(match quarantined {
+                true => Transition::Extended(until),
+                false => Transition::Quarantined(until),
+            });
+        }
+        None
+    }
+
+    /// Manually remove input from quarantine
+    ///
+    /// # Returns
+    ///
+    /// `true` if input was quarantined
+    pub fn readmit(&mut self, id: IdType) -> bool {
+        self.success(id).is_some()
+    }
+
+    /// Ids of quarantined inputs
+    pub fn quarantined(&self) -> Vec<IdType> {
+        let mut ids: Vec<IdType> = self.budgets.iter()
+            .filter(|(_, budget)| budget.until.is_some())
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Time of next re-admission attempt of a quarantined input
+    pub fn until(&self, id: IdType) -> Option<DateTime<Utc>> {
+        self.budgets.get(&id).and_then(|budget| budget.until)
+    }
+
+    /// Number of consecutive failed reads of an input
+    pub fn failures(&self, id: IdType) -> usize {
+        self.budgets.get(&id).map(|budget| budget.failures).unwrap_or(0)
+    }
+}
+
+/// Name of alarm raised when an input is quarantined
+pub(crate) fn alarm_name(id: IdType) -> String {
+    format!("quarantine_{}", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::storage::quarantine::{Quarantine, Transition};
+    use crate::storage::QuarantinePolicy;
+    use crate::telemetry::Backoff;
+
+    #[test]
+    fn transitions() {
+        let now = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let policy = QuarantinePolicy::new(2)
+            .set_backoff(Backoff::new(Duration::seconds(10), Duration::seconds(60)));
+        let mut quarantine = Quarantine::new(policy);
+
+        assert_eq!(None, quarantine.failure(0, now));
+        assert_eq!(Some(Transition::Quarantined(now + Duration::seconds(10))), quarantine.failure(0, now));
+        assert_eq!(vec![0], quarantine.quarantined());
+        assert!(!quarantine.allows(0, now + Duration::seconds(5)));
+        assert!(quarantine.allows(1, now));
+
+        // failed trial read doubles delay
+        let later = now + Duration::seconds(10);
+        assert!(quarantine.allows(0, later));
+        assert_eq!(Some(Transition::Extended(later + Duration::seconds(20))), quarantine.failure(0, later));
+
+        assert_eq!(Some(Transition::Readmitted), quarantine.success(0));
+        assert!(quarantine.quarantined().is_empty());
+        assert_eq!(0, quarantine.failures(0));
+        assert_eq!(None, quarantine.success(0));
+    }
+}
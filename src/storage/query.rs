@@ -0,0 +1,198 @@
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::io::{EventKind, RawValue};
+use crate::storage::{DailySummary, Log};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Storage tier from which a point of a [`Series`] was taken, from finest to coarsest
+pub enum Tier {
+    /// Events of the log held in memory
+    Memory,
+
+    /// Events of a log saved to disk which are no longer held in memory (ie: discarded once the
+    /// capacity of the in-memory log was reached)
+    Archive,
+
+    /// Mean of each day, taken from [`DailySummary`] records
+    Daily,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Single value of a [`Series`]
+pub struct SeriesPoint {
+    pub timestamp: DateTime<Utc>,
+    pub value: RawValue,
+    pub tier: Tier,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Time range served by a single tier
+pub struct Segment {
+    pub tier: Tier,
+
+    /// Inclusive
+    pub start: DateTime<Utc>,
+    /// Exclusive
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Chronological series of a device stitched together from several storage tiers
+pub struct Series {
+    pub points: Vec<SeriesPoint>,
+
+    /// Time ranges served by each tier, from latest to earliest. Ranges do not overlap.
+    pub segments: Vec<Segment>,
+}
+
+/// Stitch a single series from all available tiers of a device
+///
+/// Each tier is assumed to be complete from its earliest point onward, so the finest tier is used
+/// for as much of the range as it covers, and coarser tiers only fill the remainder before it.
+/// Only reads and writes are included. Alarms and annotations are left out.
+///
+/// Days are represented by their mean at local midnight, and only days beginning within the
+/// remainder of the range are included.
+///
+/// # Parameters
+///
+/// - `start`: Earliest timestamp, inclusive
+/// - `end`: Latest timestamp, exclusive
+/// - `memory`: Log held in memory, if any
+/// - `archive`: Log loaded from disk, if any. Only consulted if `memory` does not cover `start`.
+/// - `daily`: Summaries of device
+///
+/// # Example
+///
+/// ```
+/// use chrono::{Duration, Utc};
+/// use sensd::io::{DeviceMetadata, IOEvent, RawValue};
+/// use sensd::storage::{stitch, Log, Tier};
+///
+/// let end = Utc::now();
+/// let mut archive = Log::with_metadata(&DeviceMetadata::default());
+/// let mut memory = Log::with_metadata(&DeviceMetadata::default());
+/// for minutes in 1..=4 {
+///     let event = IOEvent::with_timestamp(end - Duration::minutes(minutes), RawValue::Float(minutes as f32));
+///     archive.push(event.clone()).unwrap();
+///     if minutes <= 2 {
+///         memory.push(event).unwrap();
+///     }
+/// }
+///
+/// let series = stitch(end - Duration::hours(1), end, Some(&memory), Some(&archive), &[]);
+/// assert_eq!(4, series.points.len());
+/// assert_eq!(Tier::Archive, series.points[0].tier);
+/// assert_eq!(Tier::Memory, series.points[3].tier);
+/// ```
+pub fn stitch(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    memory: Option<&Log>,
+    archive: Option<&Log>,
+    daily: &[DailySummary],
+) -> Series {
+    let mut series = Series::default();
+    let mut boundary = end;
+
+    for (tier, log) in [(Tier::Memory, memory), (Tier::Archive, archive)] {
+        if boundary <= start {
+            break;
+        }
+        let first = match log.and_then(first_value) {
+            Some(first) if first < boundary => first,
+            _ => continue,
+        };
+        let from = first.max(start);
+        let points: Vec<_> = log.unwrap().range(from, boundary)
+            .map(|(_, event)| event)
+            .filter(|event| is_value(event.kind))
+            .map(|event| SeriesPoint { timestamp: event.timestamp, value: event.value, tier })
+            .collect();
+        series.segments.push(Segment { tier, start: from, end: boundary });
+        series.points.splice(0..0, points);
+        boundary = from;
+    }
+
+    if boundary > start {
+        let mut days: Vec<SeriesPoint> = daily.iter()
+            .filter_map(|summary| {
+                let offset = FixedOffset::east_opt(summary.utc_offset)?;
+                let midnight = offset.from_local_datetime(&summary.date.and_hms_opt(0, 0, 0)?).single()?;
+                let value = RawValue::Float(summary.mean? as f32);
+                Some(SeriesPoint { timestamp: midnight.with_timezone(&Utc), value, tier: Tier::Daily })
+            })
+            .filter(|point| start <= point.timestamp && point.timestamp < boundary)
+            .collect();
+        if !days.is_empty() {
+            days.sort_by_key(|point| point.timestamp);
+            series.segments.push(Segment { tier: Tier::Daily, start, end: boundary });
+            series.points.splice(0..0, days);
+        }
+    }
+    series
+}
+
+/// Timestamp of earliest read or write of log
+fn first_value(log: &Log) -> Option<DateTime<Utc>> {
+    log.iter()
+        .find(|(_, event)| is_value(event.kind))
+        .map(|(_, event)| event.timestamp)
+}
+
+fn is_value(kind: EventKind) -> bool {
+    matches!(kind, EventKind::SensorRead | EventKind::OutputWrite)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+
+    use crate::io::{DeviceMetadata, EventKind, IODirection, IOEvent, RawValue};
+    use crate::storage::query::{stitch, Tier};
+    use crate::storage::{DailySummary, Log};
+
+    #[test]
+    fn stitch_tiers() {
+        let offset = FixedOffset::east_opt(0).unwrap();
+        let start = Utc.with_ymd_and_hms(2023, 6, 1, 0, 0, 0).unwrap();
+        let at = |hours| start + Duration::hours(hours);
+
+        // archive spans days 2 and 3, memory only holds day 3
+        let mut archive = Log::with_metadata(&DeviceMetadata::default());
+        let mut memory = Log::with_metadata(&DeviceMetadata::default());
+        for hours in (24..72).step_by(6) {
+            let event = IOEvent::with_timestamp(at(hours), RawValue::Float(hours as f32));
+            archive.push(event.clone()).unwrap();
+            if hours >= 48 {
+                memory.push(event).unwrap();
+            }
+        }
+        memory.push(IOEvent::with_timestamp(at(50), RawValue::Float(0.0)).set_kind(EventKind::Annotation)).unwrap();
+
+        let daily: Vec<_> = (1..=2)
+            .map(|day| {
+                let mut log = Log::default();
+                log.push(IOEvent::with_timestamp(at(day * 24 - 12), RawValue::Float(day as f32))).unwrap();
+                DailySummary::from_log(&log, IODirection::In, 0, NaiveDate::from_ymd_opt(2023, 6, day as u32).unwrap(), offset)
+            })
+            .collect();
+
+        let series = stitch(start, at(72), Some(&memory), Some(&archive), &daily);
+        let tiers: Vec<_> = series.segments.iter().map(|segment| segment.tier).collect();
+        assert_eq!(vec![Tier::Memory, Tier::Archive, Tier::Daily], tiers);
+
+        // day 1 from summary, day 2 from archive, day 3 from memory without annotation
+        assert_eq!(1 + 4 + 4, series.points.len());
+        assert_eq!((Tier::Daily, RawValue::Float(1.0)), (series.points[0].tier, series.points[0].value));
+        assert!(series.points[1..5].iter().all(|point| point.tier == Tier::Archive));
+        assert!(series.points[5..].iter().all(|point| point.tier == Tier::Memory));
+        assert!(series.points.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp));
+
+        // coarser tiers are not used when memory covers range
+        let series = stitch(at(48), at(72), Some(&memory), Some(&archive), &daily);
+        assert_eq!(1, series.segments.len());
+        assert_eq!(4, series.points.len());
+    }
+}
@@ -0,0 +1,266 @@
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+
+use crate::console;
+use crate::errors::ErrorType;
+use crate::io::{DeviceMetadata, IOEvent, IdType};
+use crate::telemetry::{Spool, TelemetryRecord};
+
+pub type BoxedRemoteStorage = Box<dyn RemoteStorage>;
+
+/// Interface for a database or remote backend used as primary storage
+pub trait RemoteStorage {
+    fn name(&self) -> &str;
+
+    /// Store events from a single device
+    ///
+    /// Events are always given in chronological order.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` if all events were stored
+    /// - `Err` if storage failed. All events will be retried.
+    fn store(&mut self, metadata: &DeviceMetadata, events: &[IOEvent]) -> Result<(), ErrorType>;
+}
+
+/// Remote primary storage with a local, per-device spool used while the backend is unreachable
+///
+/// [`SpooledStorage::store()`] writes directly to the backend. If the backend fails, or if a device
+/// already has spooled events, events are appended to a spool file for that device instead. This
+/// guarantees that a device's events reach the backend in chronological order.
+///
+/// [`SpooledStorage::sync()`] should be called regularly to catch up once connectivity returns.
+/// Spooled events are sorted by timestamp and any event whose (device id, timestamp) has already
+/// been stored is suppressed.
+///
+/// # Example
+///
+/// ```
+/// use sensd::errors::ErrorType;
+/// use sensd::helpers::Def;
+/// use sensd::io::{DeviceMetadata, IOEvent};
+/// use sensd::storage::{Group, RemoteStorage, SpooledStorage};
+///
+/// struct Database;
+///
+/// impl RemoteStorage for Database {
+///     fn name(&self) -> &str { "database" }
+///
+///     fn store(&mut self, metadata: &DeviceMetadata, events: &[IOEvent]) -> Result<(), ErrorType> {
+///         Ok(())
+///     }
+/// }
+///
+/// let storage = Def::new(SpooledStorage::new(Box::new(Database), "/tmp/sensd/spool"));
+///
+/// let mut group = Group::new("");
+/// {
+///     let storage = storage.clone();
+///     group.on_read(move |metadata, event| storage.try_lock().unwrap().store(metadata, event));
+/// }
+///
+/// // within main event loop
+/// storage.try_lock().unwrap().sync();
+/// ```
+pub struct SpooledStorage {
+    backend: BoxedRemoteStorage,
+    dir: PathBuf,
+
+    /// Timestamp of latest event stored for each device
+    watermarks: HashMap<IdType, DateTime<Utc>>,
+}
+
+impl SpooledStorage {
+    /// Constructor for [`SpooledStorage`]
+    ///
+    /// # Parameters
+    ///
+    /// - `backend`: Primary storage
+    /// - `dir`: Directory for per-device spool files
+    pub fn new<P>(backend: BoxedRemoteStorage, dir: P) -> Self
+    where
+        P: AsRef<Path>
+    {
+        Self {
+            backend,
+            dir: PathBuf::from(dir.as_ref()),
+            watermarks: HashMap::new(),
+        }
+    }
+
+    /// Spool used for a single device
+    fn spool(&self, id: IdType) -> Spool {
+        Spool::new(self.dir.join(format!("{}.jsonl", id)))
+    }
+
+    /// Check if a device has events waiting to be synchronized
+    pub fn is_spooled(&self, id: IdType) -> bool {
+        !self.spool(id).is_empty()
+    }
+
+    /// Store a single event
+    ///
+    /// Falls back to spooling if backend fails. Duplicate events are ignored.
+    pub fn store(&mut self, metadata: &DeviceMetadata, event: &IOEvent) {
+        if self.is_duplicate(metadata.id, event.timestamp) {
+            return;
+        }
+
+        let spool = self.spool(metadata.id);
+        if spool.is_empty() {
+            match self.backend.store(metadata, std::slice::from_ref(event)) {
+                Ok(_) => {
+                    self.watermarks.insert(metadata.id, event.timestamp);
+                    return;
+                }
+                Err(e) => console::warning(format!("{} unavailable, spooling events for {}: {}",
+                                                   self.backend.name(), metadata.name, e)),
+            }
+        }
+
+        if let Err(e) = spool.append([&TelemetryRecord::new(metadata, event)]) {
+            console::error(format!("Could not spool event for {}: {}", metadata.name, e));
+        }
+    }
+
+    /// Synchronize all spooled events with backend
+    ///
+    /// Devices are synchronized independently, so that a failure for one device does not block
+    /// others.
+    ///
+    /// # Returns
+    ///
+    /// Number of events stored
+    pub fn sync(&mut self) -> usize {
+        let entries = match read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return 0,
+        };
+
+        let ids: Vec<IdType> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                match path.extension()?.to_str()? {
+                    "jsonl" => path.file_stem()?.to_str()?.parse().ok(),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        ids.into_iter()
+            .map(|id| self.sync_device(id).unwrap_or_else(|e| {
+                console::warning(format!("Could not synchronize device {}: {}", id, e));
+                0
+            }))
+            .sum()
+    }
+
+    /// Synchronize spooled events for a single device
+    fn sync_device(&mut self, id: IdType) -> Result<usize, ErrorType> {
+        let spool = self.spool(id);
+        let records = spool.read()?;
+        let count = records.len();
+        if count == 0 {
+            return Ok(0);
+        }
+
+        let metadata = DeviceMetadata { id, name: records[0].name.clone(), ..Default::default() };
+
+        // order by timestamp and suppress duplicates
+        let events: BTreeMap<DateTime<Utc>, IOEvent> = records.into_iter()
+            .filter(|record| !self.is_duplicate(id, record.event.timestamp))
+            .map(|record| (record.event.timestamp, record.event))
+            .collect();
+        let events: Vec<IOEvent> = events.into_values().collect();
+
+        if let Some(last) = events.last() {
+            self.backend.store(&metadata, &events)?;
+            self.watermarks.insert(id, last.timestamp);
+        }
+
+        spool.consume(count)?;
+        Ok(events.len())
+    }
+
+    /// Check if an event from device has already been stored
+    fn is_duplicate(&self, id: IdType, timestamp: DateTime<Utc>) -> bool {
+        self.watermarks.get(&id)
+            .map(|watermark| timestamp <= *watermark)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use std::cell::RefCell;
+    use std::fs::remove_dir_all;
+    use std::rc::Rc;
+
+    use crate::errors::ErrorType;
+    use crate::io::{DeviceMetadata, IOEvent, RawValue};
+    use crate::storage::{RemoteStorage, SpooledStorage};
+
+    struct Backend {
+        down: Rc<RefCell<bool>>,
+        stored: Rc<RefCell<Vec<IOEvent>>>,
+    }
+
+    impl RemoteStorage for Backend {
+        fn name(&self) -> &str {
+            "backend"
+        }
+
+        fn store(&mut self, _: &DeviceMetadata, events: &[IOEvent]) -> Result<(), ErrorType> {
+            if *self.down.borrow() {
+                return Err("unreachable".into());
+            }
+            self.stored.borrow_mut().extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn catch_up() {
+        const DIR: &str = "/tmp/sensd/remote_tests";
+        let _ = remove_dir_all(DIR);
+
+        let down = Rc::new(RefCell::new(false));
+        let stored = Rc::new(RefCell::new(Vec::new()));
+        let mut storage = SpooledStorage::new(
+            Box::new(Backend { down: down.clone(), stored: stored.clone() }), DIR);
+
+        let metadata = DeviceMetadata::default();
+        let now = Utc::now();
+        let event = |seconds: i64| IOEvent::with_timestamp(now + Duration::seconds(seconds), RawValue::Int(seconds as i32));
+
+        storage.store(&metadata, &event(0));
+        assert_eq!(1, stored.borrow().len());
+
+        // duplicate is suppressed
+        storage.store(&metadata, &event(0));
+        assert_eq!(1, stored.borrow().len());
+
+        *down.borrow_mut() = true;
+        storage.store(&metadata, &event(2));
+        storage.store(&metadata, &event(1));
+        assert_eq!(0, storage.sync());
+        assert!(storage.is_spooled(metadata.id));
+
+        // new events are spooled while catching up, even if backend has recovered
+        *down.borrow_mut() = false;
+        storage.store(&metadata, &event(3));
+        assert_eq!(1, stored.borrow().len());
+
+        assert_eq!(3, storage.sync());
+        assert!(!storage.is_spooled(metadata.id));
+
+        let values: Vec<RawValue> = stored.borrow().iter().map(|event| event.value).collect();
+        assert_eq!(vec![RawValue::Int(0), RawValue::Int(1), RawValue::Int(2), RawValue::Int(3)], values);
+
+        let _ = remove_dir_all(DIR);
+    }
+}
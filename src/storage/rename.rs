@@ -0,0 +1,120 @@
+use serde_json::Value;
+use std::fs::{read_to_string, remove_file, rename, write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ErrorType, FilesystemError};
+use crate::io::{Device, DeviceMetadata};
+use crate::storage::{Directory, Document};
+
+/// Rename a device and migrate its directory and log file
+///
+/// The device directory is moved first, followed by the log file which is rewritten with updated
+/// metadata. Each filesystem operation is an atomic rename. If any step fails, previous steps are
+/// reverted and the device retains its original name.
+///
+/// # Parameters
+///
+/// - `device`: Device to rename
+/// - `name`: New name
+pub(crate) fn migrate_device<D>(device: &mut D, name: String) -> Result<(), ErrorType>
+where
+    D: Device + Directory,
+{
+    let old_metadata = device.metadata().clone();
+    let old_dir = device.parent_dir().map(|_| device.full_path());
+    let old_log = log_path(device);
+
+    set_identity(device, name, old_dir.is_some());
+
+    let new_dir = device.parent_dir().map(|_| device.full_path());
+    let new_log = log_path(device);
+
+    let result = move_files(old_dir.as_deref(), new_dir.as_deref(), old_log, new_log, device.metadata());
+    if result.is_err() {
+        set_identity(device, old_metadata.name, old_dir.is_some());
+    }
+    result
+}
+
+/// Update name in device and log metadata, then recompute log directory
+fn set_identity<D>(device: &mut D, name: String, has_dir: bool)
+where
+    D: Device + Directory,
+{
+    device.set_name(name);
+    if let Some(log) = device.log() {
+        log.try_lock().unwrap().set_metadata_ref(device.metadata().clone());
+    }
+    if has_dir {
+        let parent = device.parent_dir().unwrap();
+        device.set_parent_dir_ref(parent);
+    }
+}
+
+fn log_path<D: Device>(device: &D) -> Option<PathBuf> {
+    let log = device.log()?;
+    let log = log.try_lock().unwrap();
+    log.dir().map(|_| log.full_path())
+}
+
+fn move_files(
+    old_dir: Option<&Path>,
+    new_dir: Option<&Path>,
+    old_log: Option<PathBuf>,
+    new_log: Option<PathBuf>,
+    metadata: &DeviceMetadata,
+) -> Result<(), ErrorType> {
+    let mut moved_dir = false;
+    if let (Some(old_dir), Some(new_dir)) = (old_dir, new_dir) {
+        if old_dir.exists() && old_dir != new_dir {
+            if new_dir.exists() {
+                return Err(path_exists(new_dir));
+            }
+            rename(old_dir, new_dir)?;
+            moved_dir = true;
+        }
+    }
+
+    let revert = |e: ErrorType| -> ErrorType {
+        if moved_dir {
+            let _ = rename(new_dir.unwrap(), old_dir.unwrap());
+        }
+        e
+    };
+
+    if let (Some(old_log), Some(new_log)) = (old_log, new_log) {
+        // log file has already been moved along with its directory
+        let current = match moved_dir {
+            true => new_dir.unwrap().join(old_log.file_name().unwrap()),
+            false => old_log,
+        };
+        if current.exists() {
+            if current != new_log && new_log.exists() {
+                return Err(revert(path_exists(&new_log)));
+            }
+            rewrite_log(&current, &new_log, metadata).map_err(revert)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace embedded metadata and atomically write log to new location
+pub(crate) fn rewrite_log(current: &Path, new: &Path, metadata: &DeviceMetadata) -> Result<(), ErrorType> {
+    let mut contents: Value = serde_json::from_str(&read_to_string(current)?)
+        .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+    contents["metadata"] = serde_json::to_value(metadata)
+        .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+
+    let tmp = new.with_extension("tmp");
+    write(&tmp, contents.to_string())?;
+    rename(&tmp, new)?;
+
+    if current != new {
+        remove_file(current)?;
+    }
+    Ok(())
+}
+
+fn path_exists(path: &Path) -> ErrorType {
+    Box::new(FilesystemError::PathExists { path: path.display().to_string() })
+}
@@ -0,0 +1,105 @@
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::helpers::Def;
+use crate::io::{DeviceContainer, DeviceGetters, IdType, Output};
+
+#[derive(Clone, Default)]
+/// Thread-safe handle to the outputs of a [`crate::storage::Group`]
+///
+/// The roster allows actions to select outputs by tag (see
+/// [`crate::io::DeviceMetadata::tags`]) when they write, rather than holding an explicit list of
+/// outputs. Since tags are resolved on every call, an output added to the group with the right tag
+/// is automatically driven by existing actions. Cloning returns a handle to the same roster.
+///
+/// The roster is kept in sync by the group when outputs are pushed, and before every poll. Outputs
+/// are not owned by the roster, so removed outputs are dropped as usual (see
+/// [`crate::storage::Group::sweep()`]).
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::IOCommand;
+/// use sensd::io::{Device, Output, IOKind};
+/// use sensd::storage::Group;
+///
+/// let mut group = Group::new("");
+/// let roster = group.roster();
+/// assert!(roster.tagged("dosing").is_empty());
+///
+/// group.push_output(Output::new("acid pump", 0, IOKind::Unassigned)
+///     .set_command(IOCommand::Output(|_| Ok(())))
+///     .add_tag("dosing"));
+/// assert_eq!(1, roster.tagged("dosing").len());
+/// ```
+pub struct Roster(Def<Vec<Weak<Mutex<Output>>>>);
+
+impl Roster {
+    /// Outputs currently labelled with `tag`, in the order of the group's output container
+    ///
+    /// Outputs which cannot be locked (ie: the output being written by the caller) are skipped.
+    pub fn tagged(&self, tag: &str) -> Vec<Def<Output>> {
+        self.0.lock().unwrap().iter()
+            .filter_map(|output| output.upgrade().map(Def::from))
+            .filter(|output| output.try_lock().is_ok_and(|output| output.metadata().has_tag(tag)))
+            .collect()
+    }
+
+    /// Ids of outputs currently labelled with `tag`
+    pub fn tagged_ids(&self, tag: &str) -> Vec<IdType> {
+        self.tagged(tag).iter()
+            .filter_map(|output| output.try_lock().ok().map(|output| output.id()))
+            .collect()
+    }
+
+    /// Replace outputs with those of `outputs`
+    pub(crate) fn sync(&self, outputs: &DeviceContainer<IdType, Output>) {
+        *self.0.lock().unwrap() = outputs.values()
+            .map(|output| Arc::downgrade(&output.clone().into()))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::{Action, HoldPolicy, IOCommand, Trigger, WriteTarget};
+    use crate::action::actions::Threshold;
+    use crate::io::{Device, DeviceGetters, IOEvent, IOKind, Output, RawValue};
+    use crate::storage::Group;
+
+    fn pump(id: u32) -> Output {
+        Output::new("pump", id, IOKind::Unassigned)
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .set_safe_state(RawValue::Binary(false))
+    }
+
+    fn state(group: &Group, id: u32) -> Option<RawValue> {
+        *group.outputs.get(&id).unwrap().try_lock().unwrap().state()
+    }
+
+    #[test]
+    fn tagged_target() {
+        let mut group = Group::new("");
+        group.push_output(pump(0).add_tag("dosing"))
+            .push_output(pump(1));
+
+        let mut action = Threshold::new("high", RawValue::Float(7.0), Trigger::GT)
+            .set_output(WriteTarget::tagged(group.roster(), "dosing"));
+        action.evaluate(&IOEvent::new(RawValue::Float(8.0)));
+        assert_eq!((Some(RawValue::Binary(true)), None), (state(&group, 0), state(&group, 1)));
+
+        // outputs added later are brought under the existing action
+        group.push_output(pump(2).add_tag("dosing"));
+        let mut ids = group.roster().tagged_ids("dosing");
+        ids.sort();
+        assert_eq!(vec![0, 2], ids);
+
+        action.hold(HoldPolicy::Safe);
+        assert_eq!(Some(RawValue::Binary(false)), state(&group, 0));
+        assert_eq!(Some(RawValue::Binary(false)), state(&group, 2));
+
+        // removed outputs are forgotten before the next poll
+        group.outputs.remove(&2);
+        group.poll();
+        assert_eq!(vec![0], group.roster().tagged_ids("dosing"));
+    }
+}
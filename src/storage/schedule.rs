@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+/// Strategy used by [`crate::storage::Group::poll()`] to schedule the next cycle
+pub enum PollSchedule {
+    /// Next cycle begins one interval after the previous cycle began
+    ///
+    /// Cycles drift by however late each poll is executed.
+    #[default]
+    Drift,
+
+    /// Cycles are anchored to wall-clock multiples of the interval
+    ///
+    /// For example, with an interval of 1s, every cycle begins on the second. Late cycles do not
+    /// shift the phase of later cycles, and cycles that are missed entirely are skipped. This keeps
+    /// samples from multiple devices or groups aligned.
+    FixedPhase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+/// Statistics of the delay between when a poll was scheduled and when it was executed
+pub struct JitterStats {
+    /// Number of executed cycles
+    pub count: u64,
+
+    /// Number of cycles skipped because a poll was too late. Only incremented by
+    /// [`PollSchedule::FixedPhase`].
+    pub missed: u64,
+
+    /// Jitter of the most recent cycle
+    pub last: Duration,
+
+    /// Largest jitter observed
+    pub max: Duration,
+
+    /// Average jitter
+    pub mean: Duration,
+}
+
+impl JitterStats {
+    /// Add the jitter of a single cycle
+    ///
+    /// # Parameters
+    ///
+    /// - `jitter`: Delay between scheduled and actual execution
+    pub fn record(&mut self, jitter: Duration) {
+        self.count += 1;
+        self.last = jitter;
+        self.max = self.max.max(jitter);
+
+        let mean = self.mean.as_secs_f64();
+        self.mean = Duration::from_secs_f64(mean + (jitter.as_secs_f64() - mean) / self.count as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::storage::JitterStats;
+
+    #[test]
+    fn record() {
+        let mut stats = JitterStats::default();
+        stats.record(Duration::from_millis(2));
+        stats.record(Duration::from_millis(4));
+
+        assert_eq!(2, stats.count);
+        assert_eq!(Duration::from_millis(4), stats.last);
+        assert_eq!(Duration::from_millis(4), stats.max);
+        assert!((stats.mean.as_secs_f64() - 0.003).abs() < 1e-9);
+    }
+}
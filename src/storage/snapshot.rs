@@ -0,0 +1,238 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::fs::{create_dir_all, rename, write};
+use std::path::Path;
+
+use crate::errors::{ErrorType, FilesystemError};
+use crate::io::{DeviceMetadata, IdType, IODirection, RawValue};
+use crate::storage::read_versioned;
+
+/// Filename of snapshot within group directory
+pub const SNAPSHOT_FILENAME: &str = "snapshot.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Runtime state of a [`crate::storage::Group`] which should survive a restart
+///
+/// This contains the state of stateful actions (ie: accumulated PID state), keyed by input id and
+/// action name, the contents of [`crate::storage::Variables`], the current [`crate::storage::Mode`],
+/// and energy accumulated by outputs with an [`crate::io::EnergyMeter`].
+///
+/// Device metadata and action parameters are also captured so that configuration which changed
+/// between runs can be detected using [`Snapshot::diff()`].
+///
+/// # See Also
+///
+/// - [`crate::storage::Group::save_snapshot()`]
+/// - [`crate::storage::Group::load_snapshot()`]
+pub struct Snapshot {
+    /// Time when snapshot was taken
+    pub taken: Option<DateTime<Utc>>,
+
+    pub actions: BTreeMap<IdType, BTreeMap<String, Value>>,
+
+    #[serde(default)]
+    pub variables: BTreeMap<String, RawValue>,
+
+    #[serde(default)]
+    pub mode: Option<String>,
+
+    /// Accumulated kWh keyed by output id
+    #[serde(default)]
+    pub energy: BTreeMap<IdType, f64>,
+
+    #[serde(default)]
+    pub inputs: BTreeMap<IdType, DeviceMetadata>,
+
+    #[serde(default)]
+    pub outputs: BTreeMap<IdType, DeviceMetadata>,
+
+    /// Parameter values keyed by input id, then action name
+    #[serde(default)]
+    pub parameters: BTreeMap<IdType, BTreeMap<String, BTreeMap<String, RawValue>>>,
+
+    /// Data format of snapshot. See [`crate::storage::FORMAT_VERSION`].
+    #[serde(default)]
+    pub format: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "change")]
+/// Single difference between two [`Snapshot`]s
+pub enum Difference {
+    DeviceAdded { direction: IODirection, id: IdType, name: String },
+    DeviceRemoved { direction: IODirection, id: IdType, name: String },
+    /// Metadata other than id changed (ie: name, kind, or tags)
+    DeviceChanged { direction: IODirection, id: IdType, before: DeviceMetadata, after: DeviceMetadata },
+    /// Parameter was added, removed, or changed value. Added and removed actions are reported for
+    /// each of their parameters.
+    ParameterChanged {
+        id: IdType,
+        action: String,
+        parameter: String,
+        before: Option<RawValue>,
+        after: Option<RawValue>,
+    },
+    VariableChanged { name: String, before: Option<RawValue>, after: Option<RawValue> },
+    ModeChanged { before: Option<String>, after: Option<String> },
+}
+
+impl Difference {
+    /// Check if difference concerns configuration, as opposed to runtime state
+    ///
+    /// Devices and parameters are configuration. Variables and mode are runtime state.
+    pub fn is_config(&self) -> bool {
+        !matches!(self, Difference::VariableChanged { .. } | Difference::ModeChanged { .. })
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let optional = |value: &Option<RawValue>| value.map(|value| value.to_string())
+            .unwrap_or_else(|| String::from("none"));
+        match self {
+            Difference::DeviceAdded { direction, id, name } =>
+                write!(f, "{} {} (\"{}\") added", direction, id, name),
+            Difference::DeviceRemoved { direction, id, name } =>
+                write!(f, "{} {} (\"{}\") removed", direction, id, name),
+            Difference::DeviceChanged { direction, id, before, after } =>
+                write!(f, "{} {} changed from {} to {}", direction, id, before, after),
+            Difference::ParameterChanged { id, action, parameter, before, after } =>
+                write!(f, "Parameter \"{}\" of \"{}\" on input {} changed from {} to {}",
+                       parameter, action, id, optional(before), optional(after)),
+            Difference::VariableChanged { name, before, after } =>
+                write!(f, "Variable \"{}\" changed from {} to {}", name, optional(before), optional(after)),
+            Difference::ModeChanged { before, after } =>
+                write!(f, "Mode changed from {} to {}",
+                       before.as_deref().unwrap_or("none"), after.as_deref().unwrap_or("none")),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Structured list of differences from `self` to `other`
+    ///
+    /// Action state, energy, and time taken are not compared since they change continuously.
+    ///
+    /// # Parameters
+    ///
+    /// - `other`: Newer snapshot
+    ///
+    /// # Returns
+    ///
+    /// Differences ordered by devices, parameters, variables, then mode
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensd::io::{DeviceMetadata, IODirection, IOKind, RawValue};
+    /// use sensd::storage::{Difference, Snapshot};
+    ///
+    /// let mut before = Snapshot::default();
+    /// before.inputs.insert(0, DeviceMetadata::new("temp", 0, IOKind::Temperature, IODirection::In));
+    ///
+    /// let mut after = Snapshot::default();
+    /// after.variables.insert(String::from("dose"), RawValue::Float(1.5));
+    ///
+    /// let diff = before.diff(&after);
+    /// assert_eq!(2, diff.len());
+    /// assert!(matches!(diff[0], Difference::DeviceRemoved { id: 0, .. }));
+    /// assert!(!diff[1].is_config());
+    /// ```
+    pub fn diff(&self, other: &Snapshot) -> Vec<Difference> {
+        let mut differences = Vec::new();
+        diff_devices(IODirection::In, &self.inputs, &other.inputs, &mut differences);
+        diff_devices(IODirection::Out, &self.outputs, &other.outputs, &mut differences);
+
+        let empty = BTreeMap::new();
+        let ids: BTreeSet<&IdType> = self.parameters.keys().chain(other.parameters.keys()).collect();
+        for id in ids {
+            let before = self.parameters.get(id).unwrap_or(&empty);
+            let after = other.parameters.get(id).unwrap_or(&empty);
+            let actions: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+            for action in actions {
+                let changes = diff_values(
+                    before.get(action).unwrap_or(&BTreeMap::new()),
+                    after.get(action).unwrap_or(&BTreeMap::new()));
+                for (parameter, before, after) in changes {
+                    differences.push(Difference::ParameterChanged { id: *id, action: action.clone(), parameter, before, after });
+                }
+            }
+        }
+
+        for (name, before, after) in diff_values(&self.variables, &other.variables) {
+            differences.push(Difference::VariableChanged { name, before, after });
+        }
+        if self.mode != other.mode {
+            differences.push(Difference::ModeChanged { before: self.mode.clone(), after: other.mode.clone() });
+        }
+        differences
+    }
+
+    /// Atomically write snapshot to `dir`
+    ///
+    /// Snapshot is first written to a temporary file, then renamed so that a crash while writing
+    /// never leaves a partial snapshot.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<(), ErrorType> {
+        let dir = dir.as_ref();
+        create_dir_all(dir)?;
+
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+        let path = dir.join(SNAPSHOT_FILENAME);
+        let tmp = path.with_extension("tmp");
+        write(&tmp, contents)?;
+        rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Read snapshot from `dir`
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(None)` if no snapshot exists
+    /// - `Ok(Some)` with stored snapshot
+    /// - `Err` if snapshot could not be read or parsed, or with
+    ///   [`FilesystemError::UnsupportedFormat`] if snapshot was written in a newer data format
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Option<Self>, ErrorType> {
+        let path = dir.as_ref().join(SNAPSHOT_FILENAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(read_versioned(&path)?))
+    }
+}
+
+fn diff_devices(
+    direction: IODirection,
+    before: &BTreeMap<IdType, DeviceMetadata>,
+    after: &BTreeMap<IdType, DeviceMetadata>,
+    differences: &mut Vec<Difference>,
+) {
+    let ids: BTreeSet<&IdType> = before.keys().chain(after.keys()).collect();
+    for id in ids {
+        match (before.get(id), after.get(id)) {
+            (Some(before), None) =>
+                differences.push(Difference::DeviceRemoved { direction, id: *id, name: before.name.clone() }),
+            (None, Some(after)) =>
+                differences.push(Difference::DeviceAdded { direction, id: *id, name: after.name.clone() }),
+            (Some(before), Some(after)) if before != after =>
+                differences.push(Difference::DeviceChanged { direction, id: *id, before: before.clone(), after: after.clone() }),
+            _ => (),
+        }
+    }
+}
+
+/// Added, removed, and changed values as `(name, before, after)`
+fn diff_values(
+    before: &BTreeMap<String, RawValue>,
+    after: &BTreeMap<String, RawValue>,
+) -> Vec<(String, Option<RawValue>, Option<RawValue>)> {
+    let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    names.into_iter()
+        .map(|name| (name.clone(), before.get(name).copied(), after.get(name).copied()))
+        .filter(|(_, before, after)| before != after)
+        .collect()
+}
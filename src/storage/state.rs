@@ -0,0 +1,107 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{create_dir_all, read_to_string, rename, write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ErrorType, FilesystemError};
+use crate::storage::PollSchedule;
+
+/// Filename of group runtime state within group directory
+pub const GROUP_STATE_FILENAME: &str = "group.json";
+
+/// Version of on-disk data layout written by this build
+///
+/// Stored in [`GroupState`] and [`crate::storage::Snapshot`], and incremented whenever a change
+/// would prevent older builds from reading data correctly. Data written before versioning was
+/// introduced is format 0. Data of a newer format is refused rather than misread. See
+/// [`crate::startup`].
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Runtime state of a [`crate::storage::Group`] which is not stored in device logs
+///
+/// Saved and restored by [`crate::storage::Persistent`] of [`crate::storage::Group`], so that a
+/// restart resumes the polling phase instead of polling immediately or skipping a cycle.
+pub struct GroupState {
+    pub name: String,
+
+    /// Root directory that group directory was saved under
+    pub root: PathBuf,
+
+    /// Polling interval in milliseconds
+    pub interval: i64,
+
+    /// Polling schedule. See [`crate::storage::Group::set_schedule()`].
+    pub schedule: PollSchedule,
+
+    /// Time of the most recent poll
+    pub last_execution: DateTime<Utc>,
+
+    /// Time when state was saved
+    pub saved: DateTime<Utc>,
+
+    /// Data format of group directory. See [`FORMAT_VERSION`].
+    #[serde(default)]
+    pub format: u32,
+
+    /// Version of crate that saved state
+    #[serde(default)]
+    pub written_by: Option<String>,
+}
+
+impl GroupState {
+    /// Polling interval as a [`Duration`]
+    pub fn interval(&self) -> Duration {
+        Duration::milliseconds(self.interval)
+    }
+
+    /// Atomically write state to `dir`
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<(), ErrorType> {
+        let dir = dir.as_ref();
+        create_dir_all(dir)?;
+
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+        let path = dir.join(GROUP_STATE_FILENAME);
+        let tmp = path.with_extension("tmp");
+        write(&tmp, contents)?;
+        rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Read state from `dir`
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(None)` if no state exists
+    /// - `Ok(Some)` with stored state
+    /// - `Err` if state could not be read or parsed, or with [`FilesystemError::UnsupportedFormat`]
+    ///   if state was written in a newer data format
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Option<Self>, ErrorType> {
+        let path = dir.as_ref().join(GROUP_STATE_FILENAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(read_versioned(&path)?))
+    }
+}
+
+/// Parse a JSON document, refusing documents of a newer data format
+///
+/// The format is checked before the document is parsed into `T`, so that the error names the
+/// format rather than a field that could not be parsed.
+pub(crate) fn read_versioned<T: DeserializeOwned>(path: &Path) -> Result<T, ErrorType> {
+    let serialization = |e: serde_json::Error| FilesystemError::SerializationError { msg: e.to_string() };
+    let value: Value = serde_json::from_str(&read_to_string(path)?).map_err(serialization)?;
+    let found = value.get("format").and_then(Value::as_u64).unwrap_or(0);
+    if found > FORMAT_VERSION as u64 {
+        return Err(Box::new(FilesystemError::UnsupportedFormat {
+            path: path.display().to_string(),
+            found: found as u32,
+            supported: FORMAT_VERSION,
+        }));
+    }
+    Ok(serde_json::from_value(value).map_err(serialization)?)
+}
@@ -0,0 +1,220 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use crate::errors::ErrorType;
+use crate::io::{non_finite_policy, DeviceMetadata, IOEvent, RawValue};
+use crate::storage::BoxedRemoteStorage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Method used to reduce all events within a window to a single event
+///
+/// Non-numeric values (ie: [`crate::io::RawValue::Binary`]) are always reduced to the last value.
+pub enum Decimation {
+    Mean,
+    Min,
+    Max,
+    /// Most recent value within window
+    Last,
+}
+
+/// Secondary stream of device events written to a dedicated backend
+///
+/// A raw stream passes every event to its backend, whereas a decimated stream reduces events
+/// to one per window. Windows are aligned to wall-clock multiples of the window duration, and the
+/// reduced event is timestamped with the beginning of its window. Streams are attached to devices
+/// using [`crate::storage::Group::add_stream()`].
+///
+/// # Example
+///
+/// Raw samples and 1-minute averages may be stored by separate backends:
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::errors::ErrorType;
+/// use sensd::io::{DeviceMetadata, IOEvent};
+/// use sensd::storage::{Decimation, Group, LogStream, RemoteStorage};
+///
+/// struct Database;
+/// impl RemoteStorage for Database {
+///     fn name(&self) -> &str { "database" }
+///     fn store(&mut self, _: &DeviceMetadata, _: &[IOEvent]) -> Result<(), ErrorType> { Ok(()) }
+/// }
+///
+/// let mut group = Group::new("");
+/// group
+///     .add_stream(0, LogStream::raw(Box::new(Database)))
+///     .add_stream(0, LogStream::decimated(Box::new(Database), Duration::minutes(1), Decimation::Mean));
+/// ```
+pub struct LogStream {
+    backend: BoxedRemoteStorage,
+
+    /// Window length and method. `None` for raw streams.
+    decimation: Option<(Duration, Decimation)>,
+
+    /// Events within current window
+    pending: Vec<IOEvent>,
+    window_start: Option<DateTime<Utc>>,
+}
+
+impl LogStream {
+    /// Stream which passes every event to `backend`
+    pub fn raw(backend: BoxedRemoteStorage) -> Self {
+        Self {
+            backend,
+            decimation: None,
+            pending: Vec::new(),
+            window_start: None,
+        }
+    }
+
+    /// Stream which reduces events to one per window
+    ///
+    /// # Parameters
+    ///
+    /// - `backend`: Destination of reduced events
+    /// - `window`: Length of each window
+    /// - `method`: How events within a window are reduced
+    ///
+    /// # Panics
+    ///
+    /// If `window` is not positive
+    pub fn decimated(backend: BoxedRemoteStorage, window: Duration, method: Decimation) -> Self {
+        assert!(window > Duration::zero(), "Window must be positive");
+        Self {
+            decimation: Some((window, method)),
+            ..Self::raw(backend)
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.backend.name()
+    }
+
+    /// Pass event to stream
+    ///
+    /// For decimated streams, the reduced event of the previous window is stored once an event
+    /// from a later window arrives.
+    ///
+    /// # Parameters
+    ///
+    /// - `metadata`: Metadata of originating device
+    /// - `event`: Newly generated event
+    ///
+    /// # Returns
+    ///
+    /// Result of storing to backend. Always `Ok` when no event was stored.
+    pub fn push(&mut self, metadata: &DeviceMetadata, event: &IOEvent) -> Result<(), ErrorType> {
+        let window = match self.decimation {
+            Some((window, _)) => window,
+            None => return self.backend.store(metadata, std::slice::from_ref(event)),
+        };
+
+        let start = window_start(event.timestamp, window);
+        let result = match self.window_start {
+            Some(current) if current != start => self.flush(metadata),
+            _ => Ok(()),
+        };
+        self.window_start = Some(start);
+        self.pending.push(event.clone());
+        result
+    }
+
+    /// Store reduced event of the current window, even if window has not elapsed
+    pub fn flush(&mut self, metadata: &DeviceMetadata) -> Result<(), ErrorType> {
+        let (start, method) = match (self.window_start.take(), self.decimation) {
+            (Some(start), Some((_, method))) => (start, method),
+            _ => return Ok(()),
+        };
+        let events: Vec<IOEvent> = self.pending.drain(..).collect();
+        match reduce(&events, method) {
+            Some(value) => self.backend.store(metadata, &[IOEvent::with_timestamp(start, value)]),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Beginning of window containing `timestamp`
+fn window_start(timestamp: DateTime<Utc>, window: Duration) -> DateTime<Utc> {
+    let window = window.num_milliseconds().max(1);
+    let millis = timestamp.timestamp_millis();
+    Utc.timestamp_millis_opt(millis - millis.rem_euclid(window)).unwrap()
+}
+
+fn reduce(events: &[IOEvent], method: Decimation) -> Option<RawValue> {
+    let last = events.last()?.value;
+    let values: Option<Vec<f32>> = events.iter().map(|event| event.value.as_float()).collect();
+    let values: Vec<f32> = match values {
+        Some(values) if method != Decimation::Last => values.into_iter()
+            .filter_map(|value| non_finite_policy().apply(value))
+            .collect(),
+        _ => return Some(last),
+    };
+    if values.is_empty() {
+        return Some(last);
+    }
+
+    let reduced = match method {
+        Decimation::Mean => values.iter().sum::<f32>() / values.len() as f32,
+        Decimation::Min => values.iter().cloned().fold(f32::INFINITY, f32::min),
+        Decimation::Max => values.iter().cloned().fold(f32::NEG_INFINITY, f32::max),
+        Decimation::Last => unreachable!(),
+    };
+    last.with_float(reduced)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::errors::ErrorType;
+    use crate::io::{DeviceMetadata, IOEvent, RawValue};
+    use crate::storage::{Decimation, LogStream, RemoteStorage};
+
+    struct Recorder(Rc<RefCell<Vec<IOEvent>>>);
+
+    impl RemoteStorage for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        fn store(&mut self, _: &DeviceMetadata, events: &[IOEvent]) -> Result<(), ErrorType> {
+            self.0.borrow_mut().extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn decimated() {
+        let stored = Rc::new(RefCell::new(Vec::new()));
+        let mut stream = LogStream::decimated(
+            Box::new(Recorder(stored.clone())), Duration::minutes(1), Decimation::Mean);
+
+        let metadata = DeviceMetadata::default();
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        for (offset, value) in [(10, 1.0), (30, 2.0), (50, 6.0), (70, 10.0)] {
+            let event = IOEvent::with_timestamp(start + Duration::seconds(offset), RawValue::Float(value));
+            stream.push(&metadata, &event).unwrap();
+        }
+
+        assert_eq!(1, stored.borrow().len());
+        assert_eq!(start, stored.borrow()[0].timestamp);
+        assert_eq!(RawValue::Float(3.0), stored.borrow()[0].value);
+
+        stream.flush(&metadata).unwrap();
+        assert_eq!(start + Duration::minutes(1), stored.borrow()[1].timestamp);
+        assert_eq!(RawValue::Float(10.0), stored.borrow()[1].value);
+    }
+
+    #[test]
+    fn raw() {
+        let stored = Rc::new(RefCell::new(Vec::new()));
+        let mut stream = LogStream::raw(Box::new(Recorder(stored.clone())));
+
+        let metadata = DeviceMetadata::default();
+        stream.push(&metadata, &IOEvent::new(RawValue::Binary(true))).unwrap();
+        stream.push(&metadata, &IOEvent::new(RawValue::Binary(false))).unwrap();
+
+        assert_eq!(2, stored.borrow().len());
+    }
+}
@@ -0,0 +1,276 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::action::{Publisher, WriteTarget};
+use crate::io::{DeviceGetters, IdType, IODirection, RawValue};
+use crate::storage::Chronicle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeKind {
+    Input,
+    Output,
+    Action,
+    Variable,
+    Sink,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Device, action, or other write target of a [`Topology`]
+pub struct TopologyNode {
+    /// Unique within topology (ie: `input:0`, or `input:0/pid` for an action subscribed to input 0)
+    pub id: String,
+    pub kind: NodeKind,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Connection between two nodes of a [`Topology`]
+///
+/// Devices are connected to the actions subscribed to their publisher, and actions are connected
+/// to their write target.
+pub struct TopologyEdge {
+    pub from: String,
+    pub to: String,
+
+    /// Name of parameter for actions writing to a parameter of another action, or tag for actions
+    /// writing to tagged outputs
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Routine which has been scheduled but not yet executed
+pub struct PendingRoutine {
+    /// Node of device whose publisher scheduled routine
+    pub source: String,
+
+    /// Node of output written by routine. `None` if routine is dangling or has no log.
+    pub target: Option<String>,
+
+    pub timestamp: DateTime<Utc>,
+    pub value: RawValue,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// Wiring of a running [`crate::storage::Group`]
+///
+/// Returned by [`crate::storage::Group::topology()`]. The topology is a snapshot and is not updated
+/// as actions are subscribed or routines executed. Write targets outside of the group (ie: an
+/// output belonging to another group) are included as nodes.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::{Action, IOCommand, Trigger};
+/// use sensd::action::actions::Threshold;
+/// use sensd::io::{Device, Input, IOKind, Output, RawValue};
+/// use sensd::storage::Group;
+///
+/// let mut group = Group::new("greenhouse");
+/// group.push_output(Output::new("fan", 1, IOKind::Unassigned)
+///     .set_command(IOCommand::Output(|_| Ok(()))));
+/// let fan = group.outputs.get(&1).unwrap().clone();
+///
+/// let mut input = Input::new("temp", 0, IOKind::Temperature).init_publisher();
+/// input.publisher_mut().as_mut().unwrap()
+///     .subscribe(Threshold::with_output("cool", RawValue::Float(30.0), Trigger::GT, fan).into_boxed());
+/// group.push_input(input);
+///
+/// let topology = group.topology();
+/// assert_eq!(3, topology.nodes.len());
+/// assert_eq!(2, topology.edges.len());
+///
+/// let dot = topology.to_dot();
+/// assert!(dot.contains("\"input:0\" -> \"input:0/cool\""));
+/// assert!(dot.contains("\"input:0/cool\" -> \"output:1\""));
+/// ```
+pub struct Topology {
+    /// Name of group
+    pub name: String,
+
+    pub nodes: Vec<TopologyNode>,
+    pub edges: Vec<TopologyEdge>,
+    pub routines: Vec<PendingRoutine>,
+}
+
+impl Topology {
+    pub(crate) fn new(name: String) -> Self {
+        Self { name, ..Default::default() }
+    }
+
+    pub fn node(&self, id: &str) -> Option<&TopologyNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Add node unless a node with the same id exists
+    pub(crate) fn add_node<L: Into<String>>(&mut self, id: String, kind: NodeKind, label: L) -> String {
+        if self.node(&id).is_none() {
+            self.nodes.push(TopologyNode { id: id.clone(), kind, label: label.into() });
+        }
+        id
+    }
+
+    /// Add device node
+    pub(crate) fn add_device<D: DeviceGetters>(&mut self, device: &D) -> String {
+        let metadata = device.metadata();
+        let (id, kind) = device_node(metadata.direction, metadata.id);
+        self.add_node(id, kind, format!("{} ({})", metadata.name, metadata.id))
+    }
+
+    /// Add subscribers and pending routines of the publisher of device `owner`
+    ///
+    /// `owner` is locked by the caller, so write targets which cannot be locked are assumed to be
+    /// `owner` itself.
+    pub(crate) fn add_publisher(&mut self, owner: &str, publisher: &Publisher) {
+        for action in publisher.subscribers() {
+            let id = self.add_node(format!("{}/{}", owner, action.name()), NodeKind::Action, action.name());
+            self.edges.push(TopologyEdge { from: owner.to_string(), to: id.clone(), label: None });
+
+            let (to, label) = match action.target() {
+                None => continue,
+                Some(WriteTarget::Output(output)) => {
+                    let to = match output.try_lock() {
+                        Ok(output) => self.add_device(&*output),
+                        Err(_) => owner.to_string(),
+                    };
+                    (to, None)
+                }
+                Some(WriteTarget::Parameter { input, action, parameter }) => {
+                    let source = match input.try_lock() {
+                        Ok(input) => self.add_device(&*input),
+                        Err(_) => owner.to_string(),
+                    };
+                    let to = self.add_node(format!("{}/{}", source, action), NodeKind::Action, action.as_str());
+                    (to, Some(parameter))
+                }
+                Some(WriteTarget::Variable { name, .. }) => {
+                    (self.add_node(format!("variable:{}", name), NodeKind::Variable, name.as_str()), None)
+                }
+                Some(WriteTarget::Sink(_)) => {
+                    (self.add_node(format!("{}/sink", id), NodeKind::Sink, "sink"), None)
+                }
+                Some(WriteTarget::Tagged { roster, tag }) => {
+                    for output in roster.tagged(&tag) {
+                        let to = self.add_device(&*output.try_lock().unwrap());
+                        self.edges.push(TopologyEdge { from: id.clone(), to, label: Some(tag.clone()) });
+                    }
+                    continue;
+                }
+            };
+            self.edges.push(TopologyEdge { from: id, to, label });
+        }
+
+        for routine in publisher.handler_ref().try_lock().unwrap().scheduled() {
+            let target = routine.log()
+                .and_then(|log| log.try_lock().ok()?.metadata().map(|metadata| device_node(metadata.direction, metadata.id).0));
+            self.routines.push(PendingRoutine {
+                source: owner.to_string(),
+                target,
+                timestamp: routine.timestamp(),
+                value: routine.value(),
+            });
+        }
+    }
+
+    /// Render as a Graphviz DOT digraph
+    ///
+    /// Pending routines are drawn as dashed edges labelled with their value and scheduled time.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph {} {{", quote(&self.name)).unwrap();
+        for node in self.nodes.iter() {
+            let shape = match node.kind {
+                NodeKind::Input | NodeKind::Output => "box",
+                NodeKind::Action => "ellipse",
+                NodeKind::Variable | NodeKind::Sink => "note",
+            };
+            writeln!(dot, "    {} [label={}, shape={}];", quote(&node.id), quote(&node.label), shape).unwrap();
+        }
+        for edge in self.edges.iter() {
+            match &edge.label {
+                Some(label) => writeln!(dot, "    {} -> {} [label={}];", quote(&edge.from), quote(&edge.to), quote(label)),
+                None => writeln!(dot, "    {} -> {};", quote(&edge.from), quote(&edge.to)),
+            }.unwrap();
+        }
+        for routine in self.routines.iter() {
+            if let Some(target) = &routine.target {
+                let label = format!("{} @ {}", routine.value, routine.timestamp.to_rfc3339());
+                writeln!(dot, "    {} -> {} [label={}, style=dashed];", quote(&routine.source), quote(target), quote(&label)).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Id and kind of node of a device
+fn device_node(direction: IODirection, id: IdType) -> (String, NodeKind) {
+    match direction {
+        IODirection::In => (format!("input:{}", id), NodeKind::Input),
+        IODirection::Out => (format!("output:{}", id), NodeKind::Output),
+    }
+}
+
+/// Quote DOT identifier
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::action::{Action, IOCommand, Trigger, WriteTarget};
+    use crate::action::actions::{Threshold, PID};
+    use crate::helpers::now;
+    use crate::io::{Device, Input, IOKind, Output, RawValue};
+    use crate::storage::topology::NodeKind;
+    use crate::storage::Group;
+
+    #[test]
+    fn topology() {
+        let mut group = Group::new("main");
+        group.push_output(Output::new("heater", 1, IOKind::Unassigned)
+            .set_command(IOCommand::Output(|_| Ok(())))
+            .init_log());
+        let heater = group.outputs.get(&1).unwrap().clone();
+
+        // inner loop drives heater, outer loop drives setpoint of inner loop
+        let mut inner = Input::new("water", 0, IOKind::Temperature).init_publisher();
+        let publisher = inner.publisher_mut().as_mut().unwrap();
+        publisher.subscribe(PID::new("inner", 20.0, 10.0).set_output(heater.clone()).into_boxed());
+        publisher.handler_ref().try_lock().unwrap()
+            .push(heater.try_lock().unwrap().create_routine(RawValue::Binary(false), Duration::minutes(1)));
+        group.push_input(inner);
+        let inner = group.inputs.get(&0).unwrap().clone();
+
+        let mut outer = Input::new("air", 2, IOKind::Temperature).init_publisher();
+        let publisher = outer.publisher_mut().as_mut().unwrap();
+        publisher.subscribe(PID::new("outer", 22.0, 10.0)
+            .set_output(WriteTarget::parameter(inner, "inner", "setpoint"))
+            .into_boxed());
+        publisher.subscribe(Threshold::new("frost", RawValue::Float(2.0), Trigger::LT)
+            .set_output(WriteTarget::variable(group.variables(), "frost"))
+            .into_boxed());
+        group.push_input(outer);
+
+        let topology = group.topology();
+        let kinds: Vec<_> = [NodeKind::Input, NodeKind::Output, NodeKind::Action, NodeKind::Variable].iter()
+            .map(|kind| topology.nodes.iter().filter(|node| node.kind == *kind).count())
+            .collect();
+        assert_eq!(vec![2, 1, 3, 1], kinds);
+        assert_eq!("heater (1)", topology.node("output:1").unwrap().label);
+
+        let edge = topology.edges.iter().find(|edge| edge.from == "input:2/outer").unwrap();
+        assert_eq!(("input:0/inner", Some("setpoint")), (edge.to.as_str(), edge.label.as_deref()));
+
+        assert_eq!(1, topology.routines.len());
+        assert_eq!(("input:0", Some("output:1")), (topology.routines[0].source.as_str(), topology.routines[0].target.as_deref()));
+        assert!(topology.routines[0].timestamp > now());
+
+        let dot = topology.to_dot();
+        assert!(dot.starts_with("digraph \"main\" {"));
+        assert!(dot.contains("\"input:0\" -> \"output:1\" [label=\"false @ "));
+        assert!(dot.contains("\"input:2/frost\" -> \"variable:frost\";"));
+    }
+}
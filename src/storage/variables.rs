@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::console;
+use crate::errors::ErrorType;
+use crate::helpers::{now, Def};
+use crate::io::RawValue;
+use crate::storage::{append_record, read_records};
+
+/// Filename of variable journal within group directory
+pub const VARIABLES_FILENAME: &str = "variables.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Journal record of a change to a variable
+pub struct VariableChange {
+    pub timestamp: DateTime<Utc>,
+    pub name: String,
+
+    /// New value, or `None` if variable was removed
+    pub value: Option<RawValue>,
+}
+
+#[derive(Default)]
+struct Store {
+    values: BTreeMap<String, RawValue>,
+
+    /// Path of journal which records every change
+    journal: Option<PathBuf>,
+}
+
+impl Store {
+    fn insert(&mut self, name: String, value: RawValue) -> Option<RawValue> {
+        self.record(&name, Some(value));
+        self.values.insert(name, value)
+    }
+
+    fn remove(&mut self, name: &str) -> Option<RawValue> {
+        let removed = self.values.remove(name);
+        if removed.is_some() {
+            self.record(name, None);
+        }
+        removed
+    }
+
+    fn record(&self, name: &str, value: Option<RawValue>) {
+        if let Some(path) = &self.journal {
+            let change = VariableChange { timestamp: now(), name: name.to_string(), value };
+            if let Err(e) = append_record(path, &change) {
+                console::error(format!("Could not journal variable \"{}\": {}", name, e));
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+/// Thread-safe store of named values shared between actions
+///
+/// Variables provide shared state for coordinating multiple actions (ie: a running dose total, or
+/// the current operating mode). Cloning returns a handle to the same store, so a handle obtained
+/// from [`crate::storage::Group::variables()`] may be moved into any number of actions. Actions may
+/// also write to a variable using [`crate::action::WriteTarget::Variable`].
+///
+/// Variables are included in [`crate::storage::Snapshot`] and therefore survive restarts. Changes
+/// may additionally be recorded to a journal (see [`crate::storage::Group::journal_variables()`])
+/// from which variables are reconstructed by [`crate::storage::Group::replay()`].
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::RawValue;
+/// use sensd::storage::Group;
+///
+/// let group = Group::new("");
+/// let variables = group.variables();
+///
+/// variables.set("daily_dose_total", RawValue::Float(0.0));
+/// variables.update("daily_dose_total", |total| total.unwrap() + RawValue::Float(2.5));
+///
+/// assert_eq!(Some(RawValue::Float(2.5)), group.variables().get("daily_dose_total"));
+/// ```
+pub struct Variables(Def<Store>);
+
+impl Variables {
+    /// Get current value of variable
+    ///
+    /// # Returns
+    ///
+    /// `None` if variable has not been set
+    pub fn get(&self, name: &str) -> Option<RawValue> {
+        self.0.lock().unwrap().values.get(name).copied()
+    }
+
+    /// Set value of variable
+    ///
+    /// # Returns
+    ///
+    /// Previous value, if any
+    pub fn set<N>(&self, name: N, value: RawValue) -> Option<RawValue>
+    where
+        N: Into<String>
+    {
+        self.0.lock().unwrap().insert(name.into(), value)
+    }
+
+    /// Atomically read and modify a variable
+    ///
+    /// The store is locked for the duration of `f`, so concurrent updates are not lost.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Name of variable
+    /// - `f`: Function which is given the current value and returns the new value
+    ///
+    /// # Returns
+    ///
+    /// New value
+    pub fn update<N, F>(&self, name: N, f: F) -> RawValue
+    where
+        N: Into<String>,
+        F: FnOnce(Option<RawValue>) -> RawValue,
+    {
+        let mut variables = self.0.lock().unwrap();
+        let name = name.into();
+        let value = f(variables.values.get(&name).copied());
+        variables.insert(name, value);
+        value
+    }
+
+    /// Remove variable
+    ///
+    /// # Returns
+    ///
+    /// Removed value, if any
+    pub fn remove(&self, name: &str) -> Option<RawValue> {
+        self.0.lock().unwrap().remove(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.lock().unwrap().values.contains_key(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().values.is_empty()
+    }
+
+    /// Copy of all variables
+    pub fn to_map(&self) -> BTreeMap<String, RawValue> {
+        self.0.lock().unwrap().values.clone()
+    }
+
+    /// Copy of all variables, or `None` instead of waiting if store is in use
+    pub(crate) fn try_to_map(&self) -> Option<BTreeMap<String, RawValue>> {
+        self.0.try_lock().ok().map(|store| store.values.clone())
+    }
+
+    /// Set multiple variables at once
+    ///
+    /// Variables not contained in `variables` are left untouched.
+    pub fn extend(&self, variables: &BTreeMap<String, RawValue>) {
+        let mut store = self.0.lock().unwrap();
+        for (name, value) in variables.iter() {
+            store.insert(name.clone(), *value);
+        }
+    }
+
+    /// Record every change to a journal at `path`
+    ///
+    /// Any existing journal is appended to.
+    pub fn set_journal<P: AsRef<Path>>(&self, path: P) {
+        self.0.lock().unwrap().journal = Some(path.as_ref().to_path_buf());
+    }
+
+    /// Reconstruct variables by replaying the journal at `path`
+    ///
+    /// Replayed changes are not journaled again.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` with number of replayed changes
+    /// - `Err` if journal could not be read
+    pub fn replay<P: AsRef<Path>>(&self, path: P) -> Result<usize, ErrorType> {
+        let changes: Vec<VariableChange> = read_records(path.as_ref())?;
+        let mut store = self.0.lock().unwrap();
+        for change in changes.iter() {
+            match change.value {
+                Some(value) => store.values.insert(change.name.clone(), value),
+                None => store.values.remove(&change.name),
+            };
+        }
+        Ok(changes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::io::RawValue;
+    use crate::storage::Variables;
+
+    #[test]
+    fn shared() {
+        let variables = Variables::default();
+        variables.set("total", RawValue::Int(0));
+
+        let handles: Vec<_> = (0..4).map(|_| {
+            let variables = variables.clone();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    variables.update("total", |total| total.unwrap() + RawValue::Int(1));
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(Some(RawValue::Int(400)), variables.get("total"));
+        assert_eq!(Some(RawValue::Int(400)), variables.remove("total"));
+        assert!(variables.is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use crate::io::{DeviceView, IdType, Input, Output, RawValue};
+use crate::storage::Variables;
+
+#[derive(Clone)]
+/// Read-only handle to a [`crate::storage::Group`] for UI consumers
+///
+/// Returned by [`crate::storage::Group::view()`]. A view contains a [`DeviceView`] of every device
+/// and read-only access to group variables, so dashboard code may be handed a view instead of the
+/// group itself. As with [`DeviceView`], nothing is ever locked while waiting.
+///
+/// Devices are captured when the view is created. Devices added to the group afterwards require a
+/// new view.
+///
+/// # Example
+///
+/// ```
+/// use sensd::io::{Device, Input, IOKind, RawValue};
+/// use sensd::storage::Group;
+///
+/// let mut group = Group::new("");
+/// group.push_input(Input::new("temp", 0, IOKind::Temperature));
+/// group.variables().set("setpoint", RawValue::Float(22.0));
+///
+/// let view = group.view();
+/// assert_eq!("temp", view.input(0).unwrap().metadata().unwrap().name);
+/// assert_eq!(Some(RawValue::Float(22.0)), view.variable("setpoint"));
+/// ```
+pub struct GroupView {
+    name: String,
+    inputs: BTreeMap<IdType, DeviceView<Input>>,
+    outputs: BTreeMap<IdType, DeviceView<Output>>,
+    variables: Variables,
+}
+
+impl GroupView {
+    pub(crate) fn new(
+        name: String,
+        inputs: BTreeMap<IdType, DeviceView<Input>>,
+        outputs: BTreeMap<IdType, DeviceView<Output>>,
+        variables: Variables,
+    ) -> Self {
+        Self { name, inputs, outputs, variables }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn input(&self, id: IdType) -> Option<&DeviceView<Input>> {
+        self.inputs.get(&id)
+    }
+
+    pub fn output(&self, id: IdType) -> Option<&DeviceView<Output>> {
+        self.outputs.get(&id)
+    }
+
+    /// Views of all inputs, ordered by id
+    pub fn inputs(&self) -> &BTreeMap<IdType, DeviceView<Input>> {
+        &self.inputs
+    }
+
+    /// Views of all outputs, ordered by id
+    pub fn outputs(&self) -> &BTreeMap<IdType, DeviceView<Output>> {
+        &self.outputs
+    }
+
+    /// Current value of a group variable
+    ///
+    /// # Returns
+    ///
+    /// `None` if variable has not been set, or if variables are in use
+    pub fn variable(&self, name: &str) -> Option<RawValue> {
+        self.variables.try_to_map()?.get(name).copied()
+    }
+
+    /// Copy of all group variables
+    ///
+    /// # Returns
+    ///
+    /// `None` if variables are in use
+    pub fn variables(&self) -> Option<BTreeMap<String, RawValue>> {
+        self.variables.try_to_map()
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::Duration;
+
+#[derive(Debug, Clone)]
+/// Exponential backoff between retries
+///
+/// # Example
+///
+/// ```
+/// use chrono::Duration;
+/// use sensd::telemetry::Backoff;
+///
+/// let mut backoff = Backoff::new(Duration::seconds(1), Duration::seconds(5));
+///
+/// assert_eq!(Duration::seconds(1), backoff.next_delay());
+/// assert_eq!(Duration::seconds(2), backoff.next_delay());
+/// assert_eq!(Duration::seconds(4), backoff.next_delay());
+/// assert_eq!(Duration::seconds(5), backoff.next_delay());
+///
+/// backoff.reset();
+/// assert_eq!(Duration::seconds(1), backoff.next_delay());
+/// ```
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    factor: i32,
+    attempts: u32,
+}
+
+impl Backoff {
+    /// Constructor for [`Backoff`] that doubles delay after every attempt
+    ///
+    /// # Parameters
+    ///
+    /// - `initial`: Delay after first failure
+    /// - `max`: Upper bound of delay
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            factor: 2,
+            attempts: 0,
+        }
+    }
+
+    /// Builder method to set multiplier applied after every attempt
+    pub fn set_factor(mut self, factor: i32) -> Self {
+        self.factor = factor.max(1);
+        self
+    }
+
+    /// Get delay before next attempt and increase delay for subsequent attempts
+    pub fn next_delay(&mut self) -> Duration {
+        let mut delay = self.initial;
+        for _ in 0..self.attempts {
+            delay = delay * self.factor;
+            if delay >= self.max {
+                break;
+            }
+        }
+        self.attempts = self.attempts.saturating_add(1);
+        delay.min(self.max)
+    }
+
+    /// Restore initial delay after a successful attempt
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::seconds(1), Duration::minutes(5))
+    }
+}
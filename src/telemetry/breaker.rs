@@ -0,0 +1,108 @@
+use chrono::{DateTime, Duration, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BreakerState {
+    /// Requests are allowed
+    Closed,
+    /// Requests are paused until given time
+    Open(DateTime<Utc>),
+    /// A single trial request is allowed after cooldown
+    HalfOpen,
+}
+
+#[derive(Debug, Clone)]
+/// Pauses a persistently failing sink
+///
+/// After `threshold` consecutive failures, the breaker opens and no requests are allowed for
+/// `cooldown`. A single trial request is then allowed: success closes the breaker, while failure
+/// re-opens it.
+pub struct CircuitBreaker {
+    threshold: usize,
+    cooldown: Duration,
+    failures: usize,
+    state: BreakerState,
+}
+
+impl CircuitBreaker {
+    /// Constructor for [`CircuitBreaker`]
+    ///
+    /// # Parameters
+    ///
+    /// - `threshold`: Number of consecutive failures before opening
+    /// - `cooldown`: Duration to remain open
+    pub fn new(threshold: usize, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            failures: 0,
+            state: BreakerState::Closed,
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    /// Check if a request is allowed at a given time
+    ///
+    /// Transitions from open to half-open once cooldown has elapsed.
+    pub fn allows(&mut self, now: DateTime<Utc>) -> bool {
+        match self.state {
+            BreakerState::Closed | BreakerState::HalfOpen => true,
+            BreakerState::Open(until) if now >= until => {
+                self.state = BreakerState::HalfOpen;
+                true
+            }
+            BreakerState::Open(_) => false,
+        }
+    }
+
+    /// Record a successful request
+    pub fn success(&mut self) {
+        self.failures = 0;
+        self.state = BreakerState::Closed;
+    }
+
+    /// Record a failed request
+    pub fn failure(&mut self, now: DateTime<Utc>) {
+        self.failures += 1;
+        if self.state == BreakerState::HalfOpen || self.failures >= self.threshold {
+            self.state = BreakerState::Open(now + self.cooldown);
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(5, Duration::minutes(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use crate::telemetry::{BreakerState, CircuitBreaker};
+
+    #[test]
+    fn transitions() {
+        let mut breaker = CircuitBreaker::new(2, Duration::seconds(10));
+        let now = Utc::now();
+
+        breaker.failure(now);
+        assert!(breaker.allows(now));
+
+        breaker.failure(now);
+        assert!(!breaker.allows(now));
+
+        let later = now + Duration::seconds(10);
+        assert!(breaker.allows(later));
+        assert_eq!(BreakerState::HalfOpen, breaker.state());
+
+        // single failure while half-open re-opens breaker
+        breaker.failure(later);
+        assert!(!breaker.allows(later));
+
+        breaker.success();
+        assert_eq!(BreakerState::Closed, breaker.state());
+    }
+}
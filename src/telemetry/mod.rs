@@ -0,0 +1,14 @@
+//! Deliver device events to remote services without stalling polling
+mod backoff;
+mod breaker;
+mod record;
+mod reliable;
+mod sink;
+mod spool;
+
+pub use backoff::Backoff;
+pub use breaker::{BreakerState, CircuitBreaker};
+pub use record::TelemetryRecord;
+pub use reliable::ReliableSink;
+pub use sink::{BoxedTelemetrySink, TelemetrySink};
+pub use spool::Spool;
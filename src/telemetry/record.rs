@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::io::{DeviceMetadata, IOEvent, IdType};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Single device event as delivered to a [`crate::telemetry::TelemetrySink`]
+pub struct TelemetryRecord {
+    pub id: IdType,
+    pub name: String,
+    pub event: IOEvent,
+}
+
+impl TelemetryRecord {
+    /// Build record from device metadata and event
+    ///
+    /// This matches the signature of [`crate::storage::EventHooks`] callbacks.
+    pub fn new(metadata: &DeviceMetadata, event: &IOEvent) -> Self {
+        Self {
+            id: metadata.id,
+            name: metadata.name.clone(),
+            event: event.clone(),
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::path::Path;
+
+use crate::console;
+use crate::helpers::now;
+use crate::telemetry::{Backoff, BoxedTelemetrySink, CircuitBreaker, Spool, TelemetryRecord};
+
+/// Default maximum number of records held in memory
+const DEFAULT_CAPACITY: usize = 1000;
+
+/// Default number of records sent per attempt
+const DEFAULT_BATCH: usize = 100;
+
+/// Wrapper that makes delivery to a [`crate::telemetry::TelemetrySink`] resilient to outages
+///
+/// Records are buffered by [`ReliableSink::push()`], which never blocks. Delivery only happens in
+/// [`ReliableSink::attempt()`], which should be called from the main event loop:
+///
+/// - Failed batches are retried with exponential [`Backoff`].
+/// - After repeated failures, a [`CircuitBreaker`] pauses the sink entirely.
+/// - While paused, or when the in-memory buffer overflows, records are written to a [`Spool`] on
+///   disk. Spooled records are replayed, in order and before newer records, once the sink recovers.
+///
+/// Without a spool, records that overflow the buffer are dropped with a warning.
+///
+/// # Example
+///
+/// ```
+/// use sensd::errors::ErrorType;
+/// use sensd::helpers::Def;
+/// use sensd::storage::Group;
+/// use sensd::telemetry::{ReliableSink, TelemetryRecord, TelemetrySink};
+///
+/// struct Stdout;
+///
+/// impl TelemetrySink for Stdout {
+///     fn name(&self) -> &str { "stdout" }
+///
+///     fn send(&mut self, batch: &[TelemetryRecord]) -> Result<(), ErrorType> {
+///         batch.iter().for_each(|record| println!("{:?}", record));
+///         Ok(())
+///     }
+/// }
+///
+/// let sink = Def::new(ReliableSink::new(Box::new(Stdout))
+///     .set_spool("/tmp/sensd/spool/stdout.jsonl"));
+///
+/// let mut group = Group::new("");
+/// {
+///     let sink = sink.clone();
+///     group.on_read(move |metadata, event| {
+///         sink.try_lock().unwrap().push(TelemetryRecord::new(metadata, event))
+///     });
+/// }
+///
+/// // within main event loop
+/// sink.try_lock().unwrap().attempt();
+/// ```
+pub struct ReliableSink {
+    sink: BoxedTelemetrySink,
+    queue: VecDeque<TelemetryRecord>,
+
+    capacity: usize,
+    batch: usize,
+
+    backoff: Backoff,
+    breaker: CircuitBreaker,
+    spool: Option<Spool>,
+
+    next_attempt: Option<DateTime<Utc>>,
+}
+
+impl ReliableSink {
+    /// Constructor for [`ReliableSink`]
+    ///
+    /// # Parameters
+    ///
+    /// - `sink`: Sink to deliver records to
+    ///
+    /// # Returns
+    ///
+    /// [`ReliableSink`] with default [`Backoff`], default [`CircuitBreaker`], and no spool
+    pub fn new(sink: BoxedTelemetrySink) -> Self {
+        Self {
+            sink,
+            queue: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            batch: DEFAULT_BATCH,
+            backoff: Backoff::default(),
+            breaker: CircuitBreaker::default(),
+            spool: None,
+            next_attempt: None,
+        }
+    }
+
+    /// Builder method to set spool file used during outages
+    pub fn set_spool<P>(mut self, path: P) -> Self
+    where
+        P: AsRef<Path>
+    {
+        self.spool = Some(Spool::new(path));
+        self
+    }
+
+    /// Builder method to set maximum number of records held in memory
+    pub fn set_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Builder method to set maximum number of records sent per attempt
+    pub fn set_batch(mut self, batch: usize) -> Self {
+        self.batch = batch.max(1);
+        self
+    }
+
+    pub fn set_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn set_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Number of records waiting in memory
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Number of records waiting on disk
+    pub fn spooled(&self) -> usize {
+        self.spool.as_ref().map(Spool::len).unwrap_or(0)
+    }
+
+    /// Buffer a record for delivery
+    ///
+    /// This never blocks or performs network I/O.
+    pub fn push(&mut self, record: TelemetryRecord) {
+        self.queue.push_back(record);
+
+        if self.queue.len() > self.capacity {
+            let overflow: Vec<TelemetryRecord> = self.queue
+                .drain(..self.queue.len() - self.capacity)
+                .collect();
+            self.spill(&overflow);
+        }
+    }
+
+    /// Attempt delivery of buffered and spooled records
+    pub fn attempt(&mut self) {
+        self.attempt_at(now())
+    }
+
+    /// Attempt delivery relative to a given time
+    pub fn attempt_at(&mut self, now: DateTime<Utc>) {
+        if !self.breaker.allows(now) {
+            // move buffered records to disk while paused
+            if self.spool.is_some() && !self.queue.is_empty() {
+                let records: Vec<TelemetryRecord> = self.queue.drain(..).collect();
+                self.spill(&records);
+            }
+            return;
+        }
+
+        if let Some(next) = self.next_attempt {
+            if now < next {
+                return;
+            }
+        }
+
+        // spooled records are older, so they are replayed first
+        let spooled = match &self.spool {
+            Some(spool) => match spool.read() {
+                Ok(records) => records,
+                Err(e) => {
+                    console::error(format!("Could not read spool for {}: {}", self.sink.name(), e));
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+
+        let from_spool = spooled.len().min(self.batch);
+        let from_queue = (self.batch - from_spool).min(self.queue.len());
+
+        let batch: Vec<TelemetryRecord> = spooled.into_iter()
+            .take(from_spool)
+            .chain(self.queue.iter().take(from_queue).cloned())
+            .collect();
+        if batch.is_empty() {
+            return;
+        }
+
+        match self.sink.send(&batch) {
+            Ok(_) => {
+                self.breaker.success();
+                self.backoff.reset();
+                self.next_attempt = None;
+
+                if from_spool > 0 {
+                    if let Err(e) = self.spool.as_ref().unwrap().consume(from_spool) {
+                        console::error(format!("Could not update spool for {}: {}", self.sink.name(), e));
+                    }
+                }
+                self.queue.drain(..from_queue);
+            }
+            Err(e) => {
+                console::warning(format!("Telemetry sink {} failed: {}", self.sink.name(), e));
+                self.breaker.failure(now);
+                self.next_attempt = Some(now + self.backoff.next_delay());
+            }
+        }
+    }
+
+    /// Write records to spool, or drop them if no spool is configured
+    fn spill(&self, records: &[TelemetryRecord]) {
+        match &self.spool {
+            Some(spool) => {
+                if let Err(e) = spool.append(records) {
+                    console::error(format!("Could not spool {} records for {}: {}",
+                                           records.len(), self.sink.name(), e));
+                }
+            }
+            None => console::warning(format!("Dropped {} records for {}", records.len(), self.sink.name())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use std::cell::RefCell;
+    use std::fs::remove_file;
+    use std::rc::Rc;
+
+    use crate::errors::ErrorType;
+    use crate::io::{DeviceMetadata, IOEvent, RawValue};
+    use crate::telemetry::{Backoff, CircuitBreaker, ReliableSink, TelemetryRecord, TelemetrySink};
+
+    /// Sink that fails while `down` is set and records delivered values
+    struct FlakySink {
+        down: Rc<RefCell<bool>>,
+        delivered: Rc<RefCell<Vec<RawValue>>>,
+    }
+
+    impl TelemetrySink for FlakySink {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        fn send(&mut self, batch: &[TelemetryRecord]) -> Result<(), ErrorType> {
+            if *self.down.borrow() {
+                return Err("connection refused".into());
+            }
+            self.delivered.borrow_mut().extend(batch.iter().map(|record| record.event.value));
+            Ok(())
+        }
+    }
+
+    fn record(value: i32) -> TelemetryRecord {
+        TelemetryRecord::new(&DeviceMetadata::default(), &IOEvent::new(RawValue::Int(value)))
+    }
+
+    #[test]
+    fn outage_and_replay() {
+        const SPOOL: &str = "/tmp/sensd/telemetry_tests/outage.jsonl";
+        let _ = remove_file(SPOOL);
+
+        let down = Rc::new(RefCell::new(true));
+        let delivered = Rc::new(RefCell::new(Vec::new()));
+        let mut sink = ReliableSink::new(Box::new(FlakySink { down: down.clone(), delivered: delivered.clone() }))
+            .set_spool(SPOOL)
+            .set_backoff(Backoff::new(Duration::seconds(1), Duration::seconds(1)))
+            .set_breaker(CircuitBreaker::new(2, Duration::seconds(10)));
+        let now = Utc::now();
+
+        sink.push(record(1));
+        sink.attempt_at(now);
+        // backoff prevents immediate retry
+        sink.attempt_at(now);
+        sink.attempt_at(now + Duration::seconds(1));
+
+        // breaker is open, so records are spooled
+        sink.push(record(2));
+        sink.attempt_at(now + Duration::seconds(2));
+        assert_eq!(0, sink.pending());
+        assert_eq!(2, sink.spooled());
+
+        *down.borrow_mut() = false;
+        sink.push(record(3));
+        sink.attempt_at(now + Duration::seconds(12));
+
+        assert_eq!(vec![RawValue::Int(1), RawValue::Int(2), RawValue::Int(3)], *delivered.borrow());
+        assert_eq!(0, sink.spooled());
+        assert_eq!(0, sink.pending());
+    }
+
+    #[test]
+    fn overflow_without_spool() {
+        let down = Rc::new(RefCell::new(false));
+        let delivered = Rc::new(RefCell::new(Vec::new()));
+        let mut sink = ReliableSink::new(Box::new(FlakySink { down, delivered: delivered.clone() }))
+            .set_capacity(2);
+
+        for i in 0..3 {
+            sink.push(record(i));
+        }
+        assert_eq!(2, sink.pending());
+
+        sink.attempt();
+        assert_eq!(vec![RawValue::Int(1), RawValue::Int(2)], *delivered.borrow());
+    }
+}
@@ -0,0 +1,20 @@
+use crate::errors::ErrorType;
+use crate::telemetry::TelemetryRecord;
+
+pub type BoxedTelemetrySink = Box<dyn TelemetrySink>;
+
+/// Interface for a remote telemetry service (ie: MQTT broker, HTTP endpoint, InfluxDB)
+///
+/// Implementations only need to attempt delivery. Retries, buffering, and spooling are handled by
+/// [`crate::telemetry::ReliableSink`].
+pub trait TelemetrySink {
+    fn name(&self) -> &str;
+
+    /// Deliver a batch of records
+    ///
+    /// # Returns
+    ///
+    /// - `Ok` if the entire batch was accepted
+    /// - `Err` if delivery failed. The entire batch will be retried.
+    fn send(&mut self, batch: &[TelemetryRecord]) -> Result<(), ErrorType>;
+}
@@ -0,0 +1,101 @@
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ErrorType, FilesystemError};
+use crate::telemetry::TelemetryRecord;
+
+/// Append-only, on-disk queue of [`TelemetryRecord`]s
+///
+/// Records are stored as newline delimited JSON so that appending does not require the existing
+/// file to be parsed.
+pub struct Spool {
+    path: PathBuf,
+}
+
+impl Spool {
+    /// Constructor for [`Spool`]
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: Path of spool file. Parent directories are created when needed.
+    pub fn new<P>(path: P) -> Self
+    where
+        P: AsRef<Path>
+    {
+        Self { path: PathBuf::from(path.as_ref()) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append records to end of spool
+    pub fn append<'a, I>(&self, records: I) -> Result<(), ErrorType>
+    where
+        I: IntoIterator<Item = &'a TelemetryRecord>
+    {
+        if let Some(parent) = self.path.parent() {
+            create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let mut writer = BufWriter::new(file);
+
+        for record in records {
+            let line = serde_json::to_string(record)
+                .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+            writeln!(writer, "{}", line)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read all spooled records in order
+    pub fn read(&self) -> Result<Vec<TelemetryRecord>, ErrorType> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line)
+                .map_err(|e| FilesystemError::SerializationError { msg: e.to_string() })?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// Number of spooled records
+    pub fn len(&self) -> usize {
+        self.read().map(|records| records.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove first `count` records
+    ///
+    /// Remaining records are written to a temporary file which then replaces the spool so that a
+    /// crash never leaves a partially written spool.
+    pub fn consume(&self, count: usize) -> Result<(), ErrorType> {
+        let remaining: Vec<TelemetryRecord> = self.read()?.into_iter().skip(count).collect();
+
+        let tmp = self.path.with_extension("tmp");
+        let _ = std::fs::remove_file(&tmp);
+        Spool::new(&tmp).append(remaining.iter())?;
+
+        if remaining.is_empty() {
+            let _ = std::fs::remove_file(&tmp);
+            let _ = std::fs::remove_file(&self.path);
+        } else {
+            std::fs::rename(&tmp, &self.path)?;
+        }
+        Ok(())
+    }
+}
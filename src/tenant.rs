@@ -0,0 +1,359 @@
+//! Several independently owned installations served by a single process
+//!
+//! Each [`Tenant`] has its own data root, [`Settings`], API token, and storage quota. Groups are
+//! created by the tenant so that they are always stored under its root, and roots of different
+//! tenants may not overlap. The control channel only exposes groups of the tenant whose token
+//! accompanies a request (see [`crate::control::handle_tenants()`]).
+//!
+//! # Example
+//!
+//! ```
+//! use sensd::io::{Device, Input, IOKind};
+//! use sensd::tenant::{Tenant, Tenants};
+//!
+//! let mut tenants = Tenants::default();
+//! tenants.push(Tenant::new("lab", "/tmp/sensd_tenants/lab", "lab-token")
+//!     .set_quota(64 * 1024 * 1024))
+//!     .unwrap();
+//! tenants.push(Tenant::new("farm", "/tmp/sensd_tenants/farm", "farm-token"))
+//!     .unwrap();
+//!
+//! tenants.get_mut("lab").unwrap()
+//!     .add_group("incubator").unwrap()
+//!     .push_input(Input::new("temperature", 0, IOKind::Temperature));
+//!
+//! // roots may not be shared
+//! assert!(tenants.push(Tenant::new("shed", "/tmp/sensd_tenants/lab/shed", "shed-token")).is_err());
+//! ```
+use std::fs::{read_dir, symlink_metadata};
+use std::path::{Path, PathBuf};
+
+use crate::alarm::AlarmHandler;
+use crate::errors::{DeviceError, ErrorType, TenantError};
+use crate::helpers::{check_results, Def};
+use crate::name::Name;
+use crate::settings::Settings;
+use crate::storage::{Group, Persistent};
+
+/// Independently owned installation
+///
+/// A tenant owns its groups, which are created by [`Tenant::add_group()`] under its root.
+pub struct Tenant {
+    name: String,
+
+    /// Settings of tenant. Never shared with other tenants.
+    settings: Settings,
+
+    /// Secret presented by clients of tenant
+    token: String,
+
+    /// Maximum bytes stored under root. `None` if unlimited.
+    quota: Option<u64>,
+
+    groups: Vec<Group>,
+
+    /// Handler used by `alarms.list` and `alarms.ack`
+    alarms: Option<Def<AlarmHandler>>,
+}
+
+impl Tenant {
+    /// Constructor for [`Tenant`]
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Unique name of tenant
+    /// - `root`: Top-level directory of all data of tenant
+    /// - `token`: Secret presented by clients to access tenant
+    pub fn new<N, P, T>(name: N, root: P, token: T) -> Self
+        where
+            N: Into<String>,
+            P: AsRef<Path>,
+            T: Into<String>,
+    {
+        let mut settings = Settings::default();
+        settings.set_root(root.as_ref());
+        Self {
+            name: name.into(),
+            settings,
+            token: token.into(),
+            quota: None,
+            groups: Vec::new(),
+            alarms: None,
+        }
+    }
+
+    /// Builder method to limit bytes stored under root
+    ///
+    /// The quota is checked by [`Tenant::save()`]. Polling continues once the quota is exceeded,
+    /// but nothing further is saved.
+    pub fn set_quota(mut self, bytes: u64) -> Self {
+        self.quota = Some(bytes);
+        self
+    }
+
+    /// Builder method to set handler used by `alarms.list` and `alarms.ack`
+    pub fn set_alarm_handler(mut self, alarms: Def<AlarmHandler>) -> Self {
+        self.alarms = Some(alarms);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn root(&self) -> PathBuf {
+        self.settings.root_path().deref()
+    }
+
+    pub fn quota(&self) -> Option<u64> {
+        self.quota
+    }
+
+    pub fn alarm_handler(&self) -> Option<&Def<AlarmHandler>> {
+        self.alarms.as_ref()
+    }
+
+    /// Check token presented by a client
+    ///
+    /// Tokens are compared in constant time.
+    pub fn authorize(&self, token: &str) -> bool {
+        let (expected, token) = (self.token.as_bytes(), token.as_bytes());
+        expected.len() == token.len()
+            && expected.iter().zip(token).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+
+    /// Create a group stored under root of tenant
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to new group, or [`TenantError::GroupExists`] if tenant already has a
+    /// group named `name`.
+    pub fn add_group<N: Into<String>>(&mut self, name: N) -> Result<&mut Group, ErrorType> {
+        let name = name.into();
+        if self.group(&name).is_some() {
+            return Err(Box::new(TenantError::GroupExists { tenant: self.name.clone(), name }));
+        }
+        self.groups.push(Group::with_root(name, self.root()));
+        Ok(self.groups.last_mut().unwrap())
+    }
+
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    pub fn group(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|group| group.name() == name)
+    }
+
+    /// Group selected by `name`
+    ///
+    /// # Returns
+    ///
+    /// The group named `name`, or the only group of tenant when `name` is `None`. `None` if there
+    /// is no such group, or if no name is given and tenant does not have exactly one group.
+    pub fn group_mut(&mut self, name: Option<&str>) -> Option<&mut Group> {
+        match name {
+            Some(name) => self.groups.iter_mut().find(|group| group.name() == name),
+            None if self.groups.len() == 1 => self.groups.first_mut(),
+            None => None,
+        }
+    }
+
+    /// Poll every group
+    ///
+    /// # Returns
+    ///
+    /// Errors of all groups which were polled
+    pub fn poll(&mut self) -> Vec<DeviceError> {
+        self.groups.iter_mut()
+            .flat_map(|group| group.poll().into_errors())
+            .collect()
+    }
+
+    /// Bytes stored under root. Symbolic links are not followed.
+    pub fn usage(&self) -> Result<u64, ErrorType> {
+        let root = self.root();
+        match root.exists() {
+            true => usage(&root),
+            false => Ok(0),
+        }
+    }
+
+    /// Save all groups
+    ///
+    /// # Returns
+    ///
+    /// [`TenantError::QuotaExceeded`] without saving if usage exceeds quota. Otherwise, the same
+    /// as [`Group::save()`].
+    pub fn save(&self) -> Result<(), ErrorType> {
+        if let Some(quota) = self.quota {
+            let usage = self.usage()?;
+            if usage > quota {
+                return Err(Box::new(TenantError::QuotaExceeded { tenant: self.name.clone(), usage, quota }));
+            }
+        }
+        let results: Vec<_> = self.groups.iter().map(|group| group.save()).collect();
+        check_results(&results)
+    }
+}
+
+/// Total size of files under `dir`
+fn usage(dir: &Path) -> Result<u64, ErrorType> {
+    let mut total = 0;
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        let metadata = symlink_metadata(&path)?;
+        total += match metadata.is_dir() {
+            true => usage(&path)?,
+            false => metadata.len(),
+        };
+    }
+    Ok(total)
+}
+
+#[derive(Default)]
+/// Collection of tenants served by a process
+pub struct Tenants {
+    tenants: Vec<Tenant>,
+}
+
+impl Tenants {
+    /// Add tenant
+    ///
+    /// # Returns
+    ///
+    /// An error if the name or token of `tenant` is already used, or if its root is the same as,
+    /// within, or contains the root of another tenant.
+    pub fn push(&mut self, tenant: Tenant) -> Result<&mut Self, ErrorType> {
+        for other in self.tenants.iter() {
+            let error = if other.name == tenant.name {
+                TenantError::NameExists { tenant: tenant.name }
+            } else if other.authorize(&tenant.token) {
+                TenantError::TokenExists { tenant: tenant.name }
+            } else if other.root().starts_with(tenant.root()) || tenant.root().starts_with(other.root()) {
+                TenantError::RootOverlaps { tenant: tenant.name, other: other.name.clone() }
+            } else {
+                continue;
+            };
+            return Err(Box::new(error));
+        }
+        self.tenants.push(tenant);
+        Ok(self)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Tenant> {
+        self.tenants.iter().find(|tenant| tenant.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Tenant> {
+        self.tenants.iter_mut().find(|tenant| tenant.name == name)
+    }
+
+    /// Tenant to which `token` belongs
+    pub fn authorize(&mut self, token: &str) -> Option<&mut Tenant> {
+        self.tenants.iter_mut().find(|tenant| tenant.authorize(token))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Tenant> {
+        self.tenants.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Tenant> {
+        self.tenants.iter_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tenants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tenants.is_empty()
+    }
+
+    /// Poll groups of every tenant
+    pub fn poll(&mut self) -> Vec<DeviceError> {
+        self.tenants.iter_mut()
+            .flat_map(|tenant| tenant.poll())
+            .collect()
+    }
+
+    /// Save every tenant
+    ///
+    /// A tenant exceeding its quota does not prevent others from being saved.
+    pub fn save(&self) -> Result<(), ErrorType> {
+        let results: Vec<_> = self.tenants.iter().map(|tenant| tenant.save()).collect();
+        check_results(&results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{remove_dir_all, write};
+    use std::path::PathBuf;
+
+    use crate::errors::TenantError;
+    use crate::io::{Device, Input, IOEvent, IOKind, RawValue};
+    use crate::storage::{Chronicle, Directory};
+    use crate::tenant::{Tenant, Tenants};
+
+    const DIR: &str = "/tmp/sensd/tenant_tests";
+
+    #[test]
+    fn isolation() {
+        let root = PathBuf::from(DIR).join("isolation");
+        let mut tenants = Tenants::default();
+        tenants.push(Tenant::new("a", root.join("a"), "alpha")).unwrap();
+        tenants.push(Tenant::new("b", root.join("b"), "beta")).unwrap();
+
+        assert!(tenants.push(Tenant::new("a", root.join("c"), "gamma")).is_err());
+        assert!(tenants.push(Tenant::new("c", root.join("c"), "alpha")).is_err());
+        assert!(tenants.push(Tenant::new("c", root.join("a/c"), "gamma")).is_err());
+        assert!(tenants.push(Tenant::new("c", &root, "gamma")).is_err());
+        assert_eq!(2, tenants.len());
+
+        // groups of the same name are stored under each root
+        let a = tenants.get_mut("a").unwrap();
+        a.add_group("main").unwrap();
+        assert!(a.add_group("main").is_err());
+        let b = tenants.get_mut("b").unwrap();
+        b.add_group("main").unwrap();
+        assert_eq!(root.join("b/main"), b.groups()[0].full_path());
+        assert_ne!(tenants.get("a").unwrap().settings(), tenants.get("b").unwrap().settings());
+
+        assert_eq!(Some("b"), tenants.authorize("beta").map(|tenant| tenant.name().to_string()).as_deref());
+        assert!(tenants.authorize("bet").is_none());
+
+        // only group is selected by default
+        let a = tenants.get_mut("a").unwrap();
+        assert!(a.group_mut(None).is_some());
+        a.add_group("spare").unwrap();
+        assert!(a.group_mut(None).is_none());
+        assert!(a.group_mut(Some("spare")).is_some());
+    }
+
+    #[test]
+    fn quota() {
+        let root = PathBuf::from(DIR).join("quota");
+        let _ = remove_dir_all(&root);
+        let mut tenant = Tenant::new("lab", &root, "token").set_quota(4096);
+        assert_eq!(0, tenant.usage().unwrap());
+
+        let group = tenant.add_group("main").unwrap();
+        group.push_input(Input::new("temp", 0, IOKind::Temperature).init_log());
+        let input = group.inputs.get(&0).unwrap().clone();
+        let log = input.lock().unwrap().log().unwrap();
+        log.lock().unwrap().push(IOEvent::new(RawValue::Float(1.0))).unwrap();
+
+        tenant.save().unwrap();
+        let usage = tenant.usage().unwrap();
+        assert!(0 < usage && usage <= 4096);
+
+        write(root.join("main/bulk"), vec![0; 4096]).unwrap();
+        let error = tenant.save().unwrap_err();
+        assert!(matches!(error.downcast_ref::<TenantError>(), Some(TenantError::QuotaExceeded { quota: 4096, .. })));
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Duration, Utc};
+use std::marker::PhantomData;
+use std::time::Instant;
+
+use crate::helpers::{fake_time, monotonic, now, set_clock, FakeTime};
+
+/// Controllable clock used in place of system time
+///
+/// While an instance is alive, all timestamps generated by sensd on the current thread (events,
+/// routine scheduling, polling intervals, etc) are taken from this clock. System time is restored
+/// when dropped.
+///
+/// Since the override is thread-local, tests running in parallel do not interfere with each other.
+///
+/// Wall-clock and monotonic time are controlled separately: [`FakeClock::advance()`] moves both
+/// forward, whereas [`FakeClock::set()`] only changes wall-clock time. This allows system clock
+/// corrections to be simulated.
+pub struct FakeClock {
+    // clock override only applies to the thread it was installed on
+    _thread: PhantomData<*const ()>,
+}
+
+impl FakeClock {
+    /// Override system time on the current thread
+    ///
+    /// # Parameters
+    ///
+    /// - `start`: Initial time
+    pub fn install(start: DateTime<Utc>) -> Self {
+        set_clock(Some(FakeTime { wall: start, monotonic: Instant::now() }));
+        Self { _thread: PhantomData }
+    }
+
+    /// Current wall-clock time
+    pub fn now(&self) -> DateTime<Utc> {
+        now()
+    }
+
+    /// Current monotonic time
+    pub fn monotonic(&self) -> Instant {
+        monotonic()
+    }
+
+    /// Jump wall-clock to an arbitrary time without affecting monotonic time
+    pub fn set(&self, time: DateTime<Utc>) {
+        let fake = fake_time().expect("Clock is not installed");
+        set_clock(Some(FakeTime { wall: time, ..fake }));
+    }
+
+    /// Move clock forward by `duration`
+    ///
+    /// # Panics
+    ///
+    /// If `duration` is negative
+    pub fn advance(&self, duration: Duration) {
+        let fake = fake_time().expect("Clock is not installed");
+        set_clock(Some(FakeTime {
+            wall: fake.wall + duration,
+            monotonic: fake.monotonic + duration.to_std().expect("Duration is negative"),
+        }));
+    }
+}
+
+impl Drop for FakeClock {
+    fn drop(&mut self) {
+        set_clock(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::helpers::{monotonic, now};
+    use crate::testkit::FakeClock;
+
+    #[test]
+    fn install() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        {
+            let clock = FakeClock::install(start);
+            assert_eq!(start, now());
+
+            let instant = clock.monotonic();
+            clock.advance(Duration::minutes(5));
+            assert_eq!(start + Duration::minutes(5), now());
+            assert_eq!(instant + std::time::Duration::from_secs(300), monotonic());
+
+            // wall-clock jump does not affect monotonic time
+            clock.set(start);
+            assert_eq!(start, now());
+            assert_eq!(instant + std::time::Duration::from_secs(300), monotonic());
+        }
+        assert!(now() > start + Duration::minutes(5));
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Fault injected into a read by [`FaultInjector`]
+pub enum Fault {
+    /// Read fails with [`crate::errors::DeviceError::HWFault`]
+    Error,
+    /// Read returns `NaN`
+    NaN,
+    /// Read returns the previous value instead of a new reading
+    Stale,
+    /// Read blocks while the device is locked
+    Delay(Duration),
+}
+
+struct Injector {
+    /// State of xorshift generator. Must never be zero.
+    rng: u64,
+
+    error: f64,
+    nan: f64,
+    stale: f64,
+    delay: f64,
+    delay_duration: Duration,
+
+    injected: Vec<Fault>,
+}
+
+impl Injector {
+    /// Uniformly distributed number in `[0, 1)`
+    fn next(&mut self) -> f64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Clone)]
+/// Randomly injects faults into reads of an input, for verifying fail-safe configurations
+///
+/// Attached to an input using [`crate::io::Input::set_fault_injector()`]. On each read, at most one
+/// fault is injected, chosen at the configured probabilities. This allows alarms, substitution, and
+/// safe states to be exercised against the failures they are intended to handle.
+///
+/// Faults are drawn from a seeded generator, so a failing run may be reproduced by reusing its
+/// seed. Clones share the same generator and record of injected faults.
+///
+/// # Example
+///
+/// ```
+/// use sensd::action::IOCommand;
+/// use sensd::io::{Device, Input, RawValue};
+/// use sensd::testkit::{Fault, FaultInjector};
+///
+/// let faults = FaultInjector::new(42)
+///     .set_error(0.2)
+///     .set_nan(0.1);
+/// let mut input = Input::new("probe", 0, None)
+///     .set_command(IOCommand::Input(|| RawValue::Float(7.0)))
+///     .set_fault_injector(faults.clone());
+///
+/// let failures = (0..100).filter(|_| input.read().is_err()).count();
+///
+/// assert_eq!(faults.count(Fault::Error), failures);
+/// assert!(failures > 0);
+/// ```
+pub struct FaultInjector(Arc<Mutex<Injector>>);
+
+impl FaultInjector {
+    /// Constructor for [`FaultInjector`] which injects no faults
+    ///
+    /// # Parameters
+    ///
+    /// - `seed`: Seed of random generator
+    pub fn new(seed: u64) -> Self {
+        Self(Arc::new(Mutex::new(Injector {
+            // a zero state would only ever yield zero
+            rng: seed.max(1),
+            error: 0.0,
+            nan: 0.0,
+            stale: 0.0,
+            delay: 0.0,
+            delay_duration: Duration::ZERO,
+            injected: Vec::new(),
+        })))
+    }
+
+    /// Builder method to set probability of a read failing
+    pub fn set_error(self, probability: f64) -> Self {
+        self.0.lock().unwrap().error = probability;
+        self
+    }
+
+    /// Builder method to set probability of a read returning `NaN`
+    pub fn set_nan(self, probability: f64) -> Self {
+        self.0.lock().unwrap().nan = probability;
+        self
+    }
+
+    /// Builder method to set probability of a read returning the previous value
+    pub fn set_stale(self, probability: f64) -> Self {
+        self.0.lock().unwrap().stale = probability;
+        self
+    }
+
+    /// Builder method to set probability of a read blocking for `duration`
+    ///
+    /// Since the device remains locked while blocked, this simulates a slow bus or hung driver.
+    pub fn set_delay(self, probability: f64, duration: Duration) -> Self {
+        let mut injector = self.0.lock().unwrap();
+        injector.delay = probability;
+        injector.delay_duration = duration;
+        drop(injector);
+        self
+    }
+
+    /// Randomly choose fault to inject into a single read
+    ///
+    /// # Returns
+    ///
+    /// `None` if no fault should be injected
+    pub fn sample(&self) -> Option<Fault> {
+        let mut injector = self.0.lock().unwrap();
+        let mut roll = injector.next();
+
+        let faults = [
+            (injector.error, Fault::Error),
+            (injector.nan, Fault::NaN),
+            (injector.stale, Fault::Stale),
+            (injector.delay, Fault::Delay(injector.delay_duration)),
+        ];
+        for (probability, fault) in faults {
+            if roll < probability {
+                injector.injected.push(fault);
+                return Some(fault);
+            }
+            roll -= probability;
+        }
+        None
+    }
+
+    /// All injected faults in order of injection
+    pub fn injected(&self) -> Vec<Fault> {
+        self.0.lock().unwrap().injected.clone()
+    }
+
+    /// Number of times `fault` was injected
+    pub fn count(&self, fault: Fault) -> usize {
+        self.0.lock().unwrap().injected.iter()
+            .filter(|injected| **injected == fault)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testkit::{Fault, FaultInjector};
+
+    #[test]
+    fn probabilities() {
+        let faults = FaultInjector::new(7)
+            .set_error(0.25)
+            .set_stale(0.25);
+        for _ in 0..1000 {
+            faults.sample();
+        }
+        let errors = faults.count(Fault::Error);
+        assert!((200..300).contains(&errors), "{} errors", errors);
+        assert_eq!(0, faults.count(Fault::NaN));
+
+        // same seed yields same faults
+        let replay = FaultInjector::new(7)
+            .set_error(0.25)
+            .set_stale(0.25);
+        let expected: Vec<_> = (0..10).map(|_| replay.sample()).collect();
+        let faults = FaultInjector::new(7)
+            .set_error(0.25)
+            .set_stale(0.25);
+        assert_eq!(expected, (0..10).map(|_| faults.sample()).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::action::IOCommand;
+use crate::io::RawValue;
+
+#[derive(Default)]
+struct Script {
+    queue: VecDeque<RawValue>,
+    last: RawValue,
+    reads: usize,
+}
+
+#[derive(Clone, Default)]
+/// Fake input device which returns a predefined sequence of values
+///
+/// Once the sequence is exhausted, the last value is repeated. If no values were given,
+/// [`RawValue::default()`] is returned.
+///
+/// Clones share the same sequence.
+pub struct ScriptedInput {
+    script: Arc<Mutex<Script>>,
+}
+
+impl ScriptedInput {
+    /// # Parameters
+    ///
+    /// - `values`: Values returned by successive reads
+    pub fn new<I>(values: I) -> Self
+    where
+        I: IntoIterator<Item = RawValue>,
+    {
+        let input = Self::default();
+        for value in values {
+            input.push(value);
+        }
+        input
+    }
+
+    /// Append a value to the end of the sequence
+    pub fn push(&self, value: RawValue) {
+        self.script.lock().unwrap().queue.push_back(value);
+    }
+
+    /// Command to pass to [`crate::io::Device::set_command()`]
+    pub fn command(&self) -> IOCommand {
+        let script = self.script.clone();
+        IOCommand::InputFn(Arc::new(move || {
+            let mut script = script.lock().unwrap();
+            script.reads += 1;
+            if let Some(value) = script.queue.pop_front() {
+                script.last = value;
+            }
+            script.last
+        }))
+    }
+
+    /// Number of values remaining before the last value is repeated
+    pub fn remaining(&self) -> usize {
+        self.script.lock().unwrap().queue.len()
+    }
+
+    /// Number of times the command has been executed
+    pub fn reads(&self) -> usize {
+        self.script.lock().unwrap().reads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::Command;
+    use crate::io::RawValue;
+    use crate::testkit::ScriptedInput;
+
+    #[test]
+    fn sequence() {
+        let input = ScriptedInput::new([RawValue::Int8(1), RawValue::Int8(2)]);
+        let command = input.command();
+
+        assert_eq!(Some(RawValue::Int8(1)), command.execute(None).unwrap());
+        assert_eq!(1, input.remaining());
+        assert_eq!(Some(RawValue::Int8(2)), command.execute(None).unwrap());
+
+        // last value is held
+        assert_eq!(Some(RawValue::Int8(2)), command.execute(None).unwrap());
+        assert_eq!(3, input.reads());
+
+        input.push(RawValue::Int8(3));
+        assert_eq!(Some(RawValue::Int8(3)), command.execute(None).unwrap());
+    }
+}
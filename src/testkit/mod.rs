@@ -0,0 +1,40 @@
+//! Fakes for writing fast, deterministic tests of control configurations
+//!
+//! - [`FakeClock`] controls the time seen by sensd on the current thread.
+//! - [`ScriptedInput`] returns a predefined sequence of values.
+//! - [`CaptureOutput`] records every written value along with when it was written.
+//! - [`FaultInjector`] randomly injects errors, `NaN`s, stale values, and delays into reads.
+//!
+//! # Example
+//!
+//! ```
+//! use chrono::{Duration, TimeZone, Utc};
+//! use sensd::action::{actions::Threshold, Action, Trigger};
+//! use sensd::io::{Device, Input, Output, RawValue};
+//! use sensd::testkit::{CaptureOutput, FakeClock, ScriptedInput};
+//!
+//! let clock = FakeClock::install(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap());
+//! let probe = ScriptedInput::new([RawValue::Float(6.5), RawValue::Float(8.0)]);
+//! let pump = CaptureOutput::default();
+//!
+//! let output = Output::default().set_command(pump.command()).into_deferred();
+//! let mut input = Input::default().set_command(probe.command()).init_publisher();
+//! input.publisher_mut().as_mut().unwrap()
+//!     .subscribe(Threshold::with_output("", RawValue::Float(7.0), Trigger::GT, output).into_boxed());
+//!
+//! input.read().unwrap();
+//! clock.advance(Duration::seconds(1));
+//! input.read().unwrap();
+//!
+//! assert_eq!(vec![RawValue::Binary(false), RawValue::Binary(true)], pump.values());
+//! assert_eq!(clock.now(), pump.writes()[1].0);
+//! ```
+mod clock;
+mod fault;
+mod input;
+mod output;
+
+pub use clock::FakeClock;
+pub use fault::{Fault, FaultInjector};
+pub use input::ScriptedInput;
+pub use output::{CaptureOutput, CapturedWrite};
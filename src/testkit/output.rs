@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+use crate::action::IOCommand;
+use crate::helpers::now;
+use crate::io::RawValue;
+
+/// Value written to a [`CaptureOutput`] and when it was written
+pub type CapturedWrite = (DateTime<Utc>, RawValue);
+
+#[derive(Clone, Default)]
+/// Fake output device which records every written value
+///
+/// Timestamps are taken from [`crate::helpers::now()`], and therefore follow
+/// [`crate::testkit::FakeClock`] when one is installed.
+///
+/// Clones share the same record.
+pub struct CaptureOutput {
+    writes: Arc<Mutex<Vec<CapturedWrite>>>,
+}
+
+impl CaptureOutput {
+    /// Command to pass to [`crate::io::Device::set_command()`]
+    pub fn command(&self) -> IOCommand {
+        let writes = self.writes.clone();
+        IOCommand::OutputFn(Arc::new(move |value| {
+            writes.lock().unwrap().push((now(), value));
+            Ok(())
+        }))
+    }
+
+    /// All writes in the order they occurred
+    pub fn writes(&self) -> Vec<CapturedWrite> {
+        self.writes.lock().unwrap().clone()
+    }
+
+    /// Written values without timestamps
+    pub fn values(&self) -> Vec<RawValue> {
+        self.writes.lock().unwrap().iter().map(|(_, value)| *value).collect()
+    }
+
+    /// Most recently written value
+    pub fn last(&self) -> Option<RawValue> {
+        self.writes.lock().unwrap().last().map(|(_, value)| *value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.writes.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget all recorded writes
+    pub fn clear(&self) {
+        self.writes.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::io::{Device, Output, RawValue};
+    use crate::testkit::{CaptureOutput, FakeClock};
+
+    #[test]
+    fn capture() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let clock = FakeClock::install(start);
+
+        let capture = CaptureOutput::default();
+        let mut output = Output::default().set_command(capture.command());
+        assert!(capture.is_empty());
+
+        output.write(RawValue::Binary(true)).unwrap();
+        clock.advance(Duration::seconds(30));
+        output.write(RawValue::Binary(false)).unwrap();
+
+        assert_eq!(
+            vec![(start, RawValue::Binary(true)), (start + Duration::seconds(30), RawValue::Binary(false))],
+            capture.writes()
+        );
+        assert_eq!(Some(RawValue::Binary(false)), capture.last());
+
+        capture.clear();
+        assert_eq!(0, capture.len());
+    }
+}
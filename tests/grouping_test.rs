@@ -73,7 +73,7 @@ fn test_poll() {
             assert_eq!(log.lock().unwrap().iter().count(), iteration);
         }
 
-        group.poll().unwrap();
+        assert!(group.poll().executed());
 
         std::thread::sleep(std::time::Duration::from_nanos(
             group.interval().num_nanoseconds().unwrap() as u64,
@@ -151,7 +151,7 @@ fn test_directory_hierarchy() {
 
     // Ensure that log exists
     for _ in 0..15 {
-        group.poll().unwrap();
+        assert!(group.poll().executed());
         std::thread::sleep(
             std::time::Duration::from_nanos(INTERVAL as u64));
     }
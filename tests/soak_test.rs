@@ -0,0 +1,167 @@
+//! Long-duration soak test of a simulated group
+//!
+//! A group with a PID-controlled output is run for millions of virtual polling cycles using
+//! [`FakeClock`], checking that a 24/7 process does not degrade over time:
+//!
+//! - Logs never exceed their capacity, and process memory does not grow once logs are full
+//! - Scheduled routines are executed and removed, rather than accumulating
+//! - No two events of a device collide on the same timestamp, which would silently drop a write
+//! - No lock is poisoned
+//!
+//! The test is ignored by default since it is slow in debug builds. Run with:
+//!
+//! ```text
+//! cargo test --release --test soak_test -- --ignored --nocapture
+//! ```
+//!
+//! The number of cycles may be set by `SENSD_SOAK_CYCLES` (default: 1,000,000).
+use chrono::{Duration, TimeZone, Utc};
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use sensd::action::{Action, IOCommand};
+use sensd::action::actions::PID;
+use sensd::io::{Device, Input, IOKind, Output, RawValue};
+use sensd::storage::{Chronicle, Group};
+use sensd::testkit::FakeClock;
+
+const DEFAULT_CYCLES: u64 = 1_000_000;
+
+/// Cycles between checks
+const CHECK: u64 = 1_000;
+
+/// Events retained by each log. Must exceed events written between checks so that collisions can
+/// be detected.
+const CAPACITY: usize = 5_000;
+
+/// Routines are executed this many times per polling cycle
+const SUBSTEPS: i64 = 10;
+
+/// Upper bound of scheduled routines. PID output is limited to 3 seconds, so at most one routine
+/// per cycle may be pending for 3 cycles.
+const MAX_ROUTINES: usize = 4;
+
+/// Allowed growth of resident memory after logs are full
+const MAX_GROWTH_KB: u64 = 16 * 1024;
+
+/// Resident memory of process in kB. `None` on platforms without `/proc`.
+fn resident_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status.lines()
+        .find(|line| line.starts_with("VmRSS:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+#[test]
+#[ignore]
+fn soak() {
+    let cycles = env::var("SENSD_SOAK_CYCLES").ok()
+        .and_then(|cycles| cycles.parse().ok())
+        .unwrap_or(DEFAULT_CYCLES);
+    let interval = Duration::seconds(1);
+    let clock = FakeClock::install(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap());
+
+    let mut group = Group::with_interval("soak", interval);
+
+    // total writes, used to detect writes lost to timestamp collisions
+    let writes = Arc::new(AtomicU64::new(0));
+    let counter = writes.clone();
+    group.push_output(Output::new("pump", 1, None)
+        .set_command(IOCommand::OutputFn(Arc::new(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        })))
+        .init_log());
+    let output = group.outputs.get(&1).unwrap().clone();
+
+    // sawtooth oscillating around setpoint
+    let reads = Arc::new(AtomicU64::new(0));
+    let counter = reads.clone();
+    let mut input = Input::new("level", 0, IOKind::Flow)
+        .set_command(IOCommand::InputFn(Arc::new(move || {
+            RawValue::Float((counter.fetch_add(1, Ordering::Relaxed) % 20) as f32)
+        })))
+        .init_log()
+        .init_publisher();
+    let publisher = input.publisher_mut().as_mut().unwrap();
+    let handler = publisher.handler_ref();
+    publisher.subscribe(PID::new("pid", 10.0, 3.0)
+        .set_p(1.0, 3.0)
+        .set_output(output.clone())
+        .set_handler(handler.clone())
+        .into_boxed());
+    group.push_input(input);
+    let input = group.inputs.get(&0).unwrap().clone();
+
+    let input_log = input.lock().unwrap().log().unwrap();
+    let output_log = output.lock().unwrap().log().unwrap();
+    input_log.lock().unwrap().set_capacity(Some(CAPACITY));
+    output_log.lock().unwrap().set_capacity(Some(CAPACITY));
+
+    let mut polls = 0;
+    let mut last_written = 0;
+    let mut last_logged = None;
+    let mut baseline = None;
+    for cycle in 1..=cycles {
+        let report = group.poll();
+        if report.executed() {
+            assert!(!report.has_errors(), "cycle {}: {:?}", cycle, report.outcomes);
+            polls += 1;
+        }
+        // routines are attempted between polls, as they would be by an event loop
+        for _ in 1..SUBSTEPS {
+            clock.advance(interval / SUBSTEPS as i32);
+            group.attempt_routines();
+        }
+        clock.advance(interval / SUBSTEPS as i32);
+
+        if cycle % CHECK != 0 {
+            continue;
+        }
+
+        // locks
+        assert!(input.lock().is_ok(), "input lock poisoned");
+        assert!(output.lock().is_ok(), "output lock poisoned");
+        assert!(handler.lock().is_ok(), "routine handler lock poisoned");
+
+        // log bounds
+        let input_log = input_log.lock().expect("input log poisoned");
+        let output_log = output_log.lock().expect("output log poisoned");
+        assert!(input_log.len() <= CAPACITY);
+        assert!(output_log.len() <= CAPACITY);
+
+        // every write since last check must have its own event
+        let logged = output_log.iter().map(|(key, _)| key)
+            .filter(|key| last_logged.is_none_or(|last| **key > last))
+            .count() as u64;
+        let written = writes.load(Ordering::Relaxed);
+        assert_eq!(written - last_written, logged, "cycle {}: writes collided", cycle);
+        last_written = written;
+        last_logged = output_log.last().map(|event| event.key());
+
+        // routine leaks
+        let handler = handler.lock().unwrap();
+        assert!(handler.scheduled().len() <= MAX_ROUTINES, "cycle {}: {} routines scheduled", cycle, handler.scheduled().len());
+        assert_eq!(0, handler.dangling());
+
+        // memory growth, relative to when logs have filled up
+        let full = input_log.len() == CAPACITY && output_log.len() == CAPACITY;
+        if let Some(resident) = resident_kb() {
+            match baseline {
+                None if full => baseline = Some(resident),
+                None => (),
+                Some(baseline) => assert!(
+                    resident <= baseline + MAX_GROWTH_KB,
+                    "cycle {}: resident memory grew from {} kB to {} kB", cycle, baseline, resident),
+            }
+        }
+    }
+
+    // polling must keep pace with virtual time
+    assert!(polls >= cycles - 1, "only {} of {} cycles polled", polls, cycles);
+    assert_eq!(polls, reads.load(Ordering::Relaxed));
+}